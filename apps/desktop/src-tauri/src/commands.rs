@@ -1,21 +1,75 @@
 #![allow(non_snake_case)]
 
 use chrono::Utc;
-use infimount_core::{operations, schema::StorageKindSchema, CoreError, Entry};
+use infimount_core::access::{BucketAccess, ObjectAccess};
+use infimount_core::audit::{self, AuditSnapshotSummary, DriftReport};
+use infimount_core::branding::{self, SourceBranding};
+use infimount_core::cancel::{self, CancelToken};
+use infimount_core::config::{self, Preferences};
+use infimount_core::demo;
+use infimount_core::grep::{self, GrepMatch, GrepOptions};
+use infimount_core::hash_cache;
+use infimount_core::hex::{self, HexChunk};
+use infimount_core::i18n::{self, Message};
+use infimount_core::lifecycle::{self, LifecycleRule};
+use infimount_core::mail::{self, EmailPreview};
+use infimount_core::media::{self, FontPreview, ModelPreview};
+use infimount_core::models::ListQuery;
+use infimount_core::multipart::{self, AbortReport, IncompleteUpload};
+use infimount_core::oauth::{self, AuthorizationFlow, OAuthClient, OAuthTokens};
+use infimount_core::ocr;
+use infimount_core::operations::{
+    ChecksumAlgorithm, ConnectionTest, DeleteReport, EntryPage, ListingDiagnostics,
+    SourceCapabilities, WriteOptions,
+};
+use infimount_core::organize::{self, OrganizeRule, PlannedMove};
+use infimount_core::prefetch;
+use infimount_core::progress::{ProgressSink, TransferProgress};
+use infimount_core::registry::operator_for_source;
+use infimount_core::resume::{self, ResumableUpload};
+use infimount_core::share;
+use infimount_core::similar_images::{self, SimilarGroup};
+use infimount_core::structured::{self, JsonPreview, NotebookPreview};
+use infimount_core::synthetic;
+use infimount_core::tabular::{self, SqliteTable, TablePreview};
+use infimount_core::tail::{self, TailLines};
+use infimount_core::thresholds;
+use infimount_core::transfer_report::TransferReport;
+use infimount_core::trash::{self, TrashItem};
+use infimount_core::usage::{self, SourceStatsStatus};
+use infimount_core::volumes::{self, LocalRoot, SuggestedSource};
+use infimount_core::workers::{self, RuntimeStats};
+use infimount_core::{
+    operations, pause, schema::StorageKindSchema, CoreError, Entry, Source, SourceKind,
+};
+use infimount_mcp::credentials::CredentialSet;
+use infimount_mcp::doctor::{self, DoctorReport};
 use infimount_mcp::errors::{err_with_details, McpError, McpErrorCode, McpResult};
-use infimount_mcp::opendal_adapter::{get_capabilities, StorageBackendCapabilities};
-use infimount_mcp::registry::{ensure_unique_name, validate_storage_name, StorageRecord};
+use infimount_mcp::opendal_adapter::{
+    build_operator, get_capabilities, StorageBackendCapabilities,
+};
+use infimount_mcp::registry::{
+    ensure_unique_name, validate_storage_name, RemovedStorage, StorageRecord,
+};
+use infimount_mcp::restic;
 use infimount_mcp::server::ToolDefinition;
 use infimount_mcp::settings::McpSettings;
 use infimount_mcp::tools_storage::{
     export_config, import_config, validate_storage_record, ExportConfigInput, ExportConfigOutput,
     ImportConfigInput, ImportConfigOutput, ValidateStorageOutput,
 };
+use infimount_mcp::workspace::{self, Workspace, WorkspaceImportInput, WorkspaceImportOutput};
 use serde::Deserialize;
-use serde_json::Value;
-use tauri::State;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::ipc::{Channel, InvokeResponseBody, Response};
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::state::{AppState, McpClientSnippets, McpRuntimeStatus};
+use crate::state::{
+    mcp_error_to_core_error, AppState, McpClientSnippets, McpRuntimeStatus, RcloneImportResult,
+};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -34,16 +88,204 @@ pub struct ImportStoragesRequest {
     pub json: String,
     pub mode: String,
     pub on_conflict: String,
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
+/// Entries of a folder, optionally filtered and sorted by `query`.
 #[tauri::command]
 pub async fn list_entries(
     state: State<'_, AppState>,
     sourceId: String,
     path: String,
+    query: Option<ListQuery>,
+) -> Result<Vec<Entry>, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let query = query.unwrap_or_default();
+    let preferences = config::load_preferences().unwrap_or_default();
+    let max_entries = preferences.listing_limit();
+    let mut entries = match prefetch::take_listing(&op, &path) {
+        Some(entries) => query.apply(entries),
+        None => {
+            let (op, path) = (op.clone(), path.clone());
+            workers::run("list_entries", workers::LIST_TIMEOUT, async move {
+                operations::list_entries_query(&op, &path, &query, max_entries).await
+            })
+            .await?
+        }
+    };
+    if preferences.git_annotations {
+        operations::annotate_git_status(&op, &path, &mut entries).await;
+    }
+    Ok(entries)
+}
+
+/// Everything below a folder, flattened, down to `maxDepth` levels (the
+/// whole tree when omitted).
+#[tauri::command]
+pub async fn list_recursive(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    maxDepth: Option<usize>,
 ) -> Result<Vec<Entry>, CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
-    operations::list_entries(&op, &path).await
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let max_entries = config::load_preferences()
+        .unwrap_or_default()
+        .listing_limit();
+    workers::run("list_recursive", workers::WALK_TIMEOUT, async move {
+        operations::list_recursive(&op, &path, maxDepth, max_entries).await
+    })
+    .await
+}
+
+/// Registration and token for a job the UI may or may not have given an id
+/// to cancel it by.
+fn cancellable(operation_id: Option<String>) -> (Option<cancel::Registration>, CancelToken) {
+    let registration = operation_id.map(cancel::register);
+    let token = registration
+        .as_ref()
+        .map(cancel::Registration::token)
+        .unwrap_or_default();
+    (registration, token)
+}
+
+/// Search below `root` for paths matching the glob `pattern` (`**/*.pdf`),
+/// sending each match over `onMatch` as it is found. `cancel_operation`
+/// with `searchId` stops it. Returns the number of matches.
+#[tauri::command]
+pub async fn search_entries(
+    state: State<'_, AppState>,
+    sourceId: String,
+    root: String,
+    pattern: String,
+    searchId: Option<String>,
+    onMatch: Channel<Entry>,
+) -> Result<usize, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let (_registration, token) = cancellable(searchId);
+    workers::run("search_entries", workers::WALK_TIMEOUT, async move {
+        // A closed channel means the UI went away; stop searching.
+        operations::search(&op, &root, &pattern, &token, |entry| {
+            onMatch.send(entry).is_ok()
+        })
+        .await
+    })
+    .await
+}
+
+/// Search the text files below `root` for `query` (plain text, or a regex
+/// with `options.regex`), sending each matching line over `onMatch`.
+/// `cancel_operation` with the same `searchId` stops it. Returns the
+/// number of matching lines.
+#[tauri::command]
+pub async fn search_content(
+    state: State<'_, AppState>,
+    sourceId: String,
+    root: String,
+    query: String,
+    options: Option<GrepOptions>,
+    searchId: String,
+    onMatch: Channel<GrepMatch>,
+) -> Result<usize, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let registration = cancel::register(searchId);
+    let token = registration.token();
+    workers::run("search_content", workers::WALK_TIMEOUT, async move {
+        let mut options = options.unwrap_or_default();
+        options.include_system_files = config::load_preferences()
+            .unwrap_or_default()
+            .include_system_files;
+        grep::grep(&op, &root, &query, &options, &token, |hit| {
+            onMatch.send(hit).is_ok()
+        })
+        .await
+    })
+    .await
+}
+
+/// Stop the content search started with `searchId`, if it is still running.
+/// Same as [`cancel_operation`].
+#[tauri::command]
+pub fn cancel_content_search(searchId: String) {
+    cancel::cancel(&searchId);
+}
+
+/// Whether content search can recognize text in images and scans (the
+/// `ocr` option); builds without the `ocr` feature cannot.
+#[tauri::command]
+pub fn ocr_available() -> bool {
+    ocr::is_available()
+}
+
+/// Groups of images below `root` that look alike (resized or recompressed
+/// copies), at most `maxDistance` hash bits apart. Stop it with
+/// `cancel_operation(searchId)`.
+#[tauri::command]
+pub async fn find_similar_images(
+    state: State<'_, AppState>,
+    sourceId: String,
+    root: String,
+    maxDistance: Option<u32>,
+    searchId: String,
+) -> Result<Vec<SimilarGroup>, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let registration = cancel::register(searchId);
+    let token = registration.token();
+    workers::run("find_similar_images", workers::WALK_TIMEOUT, async move {
+        let max_distance = maxDistance.unwrap_or(similar_images::DEFAULT_MAX_DISTANCE);
+        similar_images::find_similar_images(&op, &sourceId, &root, max_distance, &token).await
+    })
+    .await
+}
+
+/// One page of a folder; pass `next_cursor` back for the following page.
+#[tauri::command]
+pub async fn list_entries_paged(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<EntryPage, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let preferences = config::load_preferences().unwrap_or_default();
+    workers::run("list_entries_paged", workers::LIST_TIMEOUT, async move {
+        let mut page = operations::list_entries_paged(
+            &op,
+            &path,
+            cursor.as_deref(),
+            limit.unwrap_or(operations::DEFAULT_PAGE_SIZE),
+            preferences.listing_limit(),
+        )
+        .await?;
+        if preferences.git_annotations {
+            operations::annotate_git_status(&op, &path, &mut page.entries).await;
+        }
+        Ok(page)
+    })
+    .await
+}
+
+/// Prefetch the folders around `path` in the background when the
+/// `prefetch_adjacent` preference is on. Returns right away.
+#[tauri::command]
+pub async fn prefetch_adjacent(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+) -> Result<(), CoreError> {
+    if !config::load_preferences()
+        .unwrap_or_default()
+        .prefetch_adjacent
+    {
+        return Ok(());
+    }
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    tauri::async_runtime::spawn(async move {
+        let _ = prefetch::prefetch_adjacent(&op, &path).await;
+    });
+    Ok(())
 }
 
 #[tauri::command]
@@ -52,64 +294,743 @@ pub async fn stat_entry(
     sourceId: String,
     path: String,
 ) -> Result<Entry, CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
-    operations::stat_entry(&op, &path).await
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("stat_entry", workers::LIST_TIMEOUT, async move {
+        operations::stat_entry(&op, &path).await
+    })
+    .await
+}
+
+/// Whole contents of a file, e.g. for a preview. Large files need
+/// `confirmed` and the largest are refused; see [`thresholds`].
+#[tauri::command]
+pub async fn read_file(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    confirmed: Option<bool>,
+) -> Result<Vec<u8>, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    if let Some(data) = prefetch::take_preview(&op, &path) {
+        return Ok(data);
+    }
+    let limits = config::load_preferences()
+        .unwrap_or_default()
+        .size_thresholds;
+    workers::run("read_file", workers::READ_TIMEOUT, async move {
+        thresholds::read_for_webview(&op, &path, &limits, confirmed.unwrap_or(false)).await
+    })
+    .await
+}
+
+/// Hex digest of a file, to check it against a published hash. Cached by
+/// etag and size, so asking again for an unchanged file does not re-read it.
+#[tauri::command]
+pub async fn checksum(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    algorithm: ChecksumAlgorithm,
+) -> Result<String, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("checksum", workers::READ_TIMEOUT, async move {
+        hash_cache::cached_checksum(&op, &sourceId, &path, algorithm).await
+    })
+    .await
+}
+
+/// Record the files below `prefix` with their checksums (legal hold), so
+/// later changes can be detected with [`verify_audit_snapshot`].
+#[tauri::command]
+pub async fn freeze_prefix(
+    state: State<'_, AppState>,
+    sourceId: String,
+    prefix: String,
+) -> Result<AuditSnapshotSummary, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("freeze_prefix", workers::WALK_TIMEOUT, async move {
+        audit::freeze(&op, &sourceId, &prefix)
+            .await
+            .map(|snapshot| snapshot.summary())
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn list_audit_snapshots() -> Result<Vec<AuditSnapshotSummary>, CoreError> {
+    audit::list_snapshots()
+}
+
+/// Files modified, removed or added below a snapshot's prefix since it was
+/// taken.
+#[tauri::command]
+pub async fn verify_audit_snapshot(
+    state: State<'_, AppState>,
+    snapshotId: String,
+) -> Result<DriftReport, CoreError> {
+    let snapshot = audit::load_snapshot(&snapshotId)?;
+    let op = state.operator_for_storage_id(&snapshot.source_id).await?;
+    workers::run("verify_audit_snapshot", workers::WALK_TIMEOUT, async move {
+        audit::verify(&op, &snapshot).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn delete_audit_snapshot(snapshotId: String) -> Result<(), CoreError> {
+    audit::delete_snapshot(&snapshotId)
+}
+
+/// Scan `sourceId` for the storage overview in the background and return
+/// right away; [`get_source_stats`] reports its progress and, once done, the
+/// result. Returns `false` when a scan of the source is already running.
+/// `cancel_operation` with `scanId` stops it.
+#[tauri::command]
+pub async fn scan_source_stats(
+    state: State<'_, AppState>,
+    sourceId: String,
+    scanId: Option<String>,
+) -> Result<bool, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let Some(scan) = usage::start(&sourceId) else {
+        return Ok(false);
+    };
+    let (registration, token) = cancellable(scanId);
+    // Whole buckets can take a long time, so the scan is not bounded by a
+    // worker timeout.
+    tauri::async_runtime::spawn(async move {
+        let _registration = registration;
+        if let Err(error) = scan.run(&op, &token).await {
+            eprintln!("usage scan of {sourceId} failed: {error}");
+        }
+    });
+    Ok(true)
+}
+
+/// Object count and bytes of `sourceId`, in total and per top-level folder,
+/// from its last scan, with the progress of a scan still running.
+#[tauri::command]
+pub fn get_source_stats(sourceId: String) -> SourceStatsStatus {
+    usage::status(&sourceId)
+}
+
+/// Multipart uploads on an S3 source that were never completed or aborted.
+/// Their parts are stored and billed but invisible in listings.
+#[tauri::command]
+pub async fn list_incomplete_uploads(
+    state: State<'_, AppState>,
+    sourceId: String,
+) -> Result<Vec<IncompleteUpload>, CoreError> {
+    let client = state.s3_client_for_storage_id(&sourceId, false)?;
+    workers::run(
+        "list_incomplete_uploads",
+        workers::LIST_TIMEOUT,
+        async move { multipart::list_incomplete(&client).await },
+    )
+    .await
+}
+
+/// Abort incomplete multipart uploads, discarding their parts. Every upload
+/// ends up in the report as aborted or failed.
+#[tauri::command]
+pub async fn abort_incomplete_uploads(
+    state: State<'_, AppState>,
+    sourceId: String,
+    uploads: Vec<IncompleteUpload>,
+) -> Result<AbortReport, CoreError> {
+    let client = state.s3_client_for_storage_id(&sourceId, true)?;
+    workers::run(
+        "abort_incomplete_uploads",
+        workers::WALK_TIMEOUT,
+        async move { Ok(multipart::abort_many(&client, uploads).await) },
+    )
+    .await
+}
+
+/// Lifecycle (expiration and transition) rules of an S3 source's bucket.
+#[tauri::command]
+pub async fn get_lifecycle_rules(
+    state: State<'_, AppState>,
+    sourceId: String,
+) -> Result<Vec<LifecycleRule>, CoreError> {
+    let client = state.s3_client_for_storage_id(&sourceId, false)?;
+    workers::run("get_lifecycle_rules", workers::LIST_TIMEOUT, async move {
+        lifecycle::get_rules(&client).await
+    })
+    .await
+}
+
+/// Replace the lifecycle rules of an S3 source's bucket; no rules removes
+/// the configuration.
+#[tauri::command]
+pub async fn put_lifecycle_rules(
+    state: State<'_, AppState>,
+    sourceId: String,
+    rules: Vec<LifecycleRule>,
+) -> Result<(), CoreError> {
+    let client = state.s3_client_for_storage_id(&sourceId, true)?;
+    workers::run("put_lifecycle_rules", workers::LIST_TIMEOUT, async move {
+        lifecycle::put_rules(&client, &rules).await
+    })
+    .await
+}
+
+/// ACL grants, public access settings, policy status and CORS rules of an
+/// S3 or GCS source's bucket.
+#[tauri::command]
+pub async fn get_bucket_access(
+    state: State<'_, AppState>,
+    sourceId: String,
+) -> Result<BucketAccess, CoreError> {
+    let client = state.access_client_for_storage_id(&sourceId)?;
+    workers::run("get_bucket_access", workers::LIST_TIMEOUT, async move {
+        client.bucket_access().await
+    })
+    .await
+}
+
+/// ACL grants of one object and whether anyone can read it.
+#[tauri::command]
+pub async fn get_object_access(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+) -> Result<ObjectAccess, CoreError> {
+    let client = state.access_client_for_storage_id(&sourceId)?;
+    workers::run("get_object_access", workers::LIST_TIMEOUT, async move {
+        client.object_access(&path).await
+    })
+    .await
+}
+
+/// The files among `paths` that anyone can read, for flagging listings.
+#[tauri::command]
+pub async fn find_public_objects(
+    state: State<'_, AppState>,
+    sourceId: String,
+    paths: Vec<String>,
+) -> Result<Vec<String>, CoreError> {
+    let client = state.access_client_for_storage_id(&sourceId)?;
+    workers::run("find_public_objects", workers::WALK_TIMEOUT, async move {
+        client.public_objects(paths).await
+    })
+    .await
+}
+
+/// Up to `length` bytes of a file from `offset`, as a raw `ArrayBuffer`.
+/// Lets previews read only the head, log views the tail and media players
+/// seek without fetching the whole file.
+#[tauri::command]
+pub async fn read_file_range(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    offset: u64,
+    length: u64,
+) -> Result<Response, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let data = workers::run("read_file_range", workers::READ_TIMEOUT, async move {
+        operations::read_range(&op, &path, offset, length).await
+    })
+    .await?;
+    Ok(Response::new(data))
+}
+
+/// Stream a file to the UI over `onChunk` (raw `ArrayBuffer`s) instead of
+/// one IPC message, so large files can be previewed or saved without being
+/// loaded whole. `maxBytes` stops after that many bytes. Returns the number
+/// of bytes sent.
+#[tauri::command]
+pub async fn read_file_chunks(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    chunkSize: Option<usize>,
+    maxBytes: Option<u64>,
+    onChunk: Channel<InvokeResponseBody>,
+) -> Result<u64, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let mut remaining = maxBytes.unwrap_or(u64::MAX);
+    operations::read_chunks(
+        &op,
+        &path,
+        chunkSize.unwrap_or(operations::DEFAULT_READ_CHUNK_SIZE),
+        |chunk| {
+            let take = chunk
+                .len()
+                .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+            remaining -= take as u64;
+            // A closed channel means the UI went away; stop reading.
+            onChunk
+                .send(InvokeResponseBody::Raw(chunk[..take].to_vec()))
+                .is_ok()
+                && remaining > 0
+        },
+    )
+    .await
+    .map(|read| read.min(maxBytes.unwrap_or(u64::MAX)))
+}
+
+/// A hex viewer window of up to `length` bytes (4 KiB by default, at most
+/// 64 KiB) starting at the row holding `offset`, read with a ranged read.
+#[tauri::command]
+pub async fn read_hex_chunk(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    offset: u64,
+    length: Option<u64>,
+) -> Result<HexChunk, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("read_hex_chunk", workers::READ_TIMEOUT, async move {
+        hex::read_hex_chunk(
+            &op,
+            &path,
+            offset,
+            length.unwrap_or(hex::DEFAULT_HEX_WINDOW),
+        )
+        .await
+    })
+    .await
+}
+
+/// Last `lines` lines of a text file, sent over `onLines`. With `follow`
+/// the file keeps being polled and appended lines are sent as they arrive
+/// until `cancel_operation` with `tailId` stops it or the UI closes the
+/// channel. Returns the number of lines sent.
+#[tauri::command]
+pub async fn tail_file(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    lines: Option<usize>,
+    follow: bool,
+    tailId: Option<String>,
+    onLines: Channel<TailLines>,
+) -> Result<usize, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let (_registration, token) = cancellable(tailId);
+    let lines = lines.unwrap_or(tail::DEFAULT_TAIL_LINES);
+    // Following runs until stopped, so only the initial read is bounded.
+    if follow {
+        return tail::tail(&op, &path, lines, true, &token, |batch| {
+            onLines.send(batch).is_ok()
+        })
+        .await;
+    }
+    workers::run("tail_file", workers::READ_TIMEOUT, async move {
+        tail::tail(&op, &path, lines, false, &token, |batch| {
+            onLines.send(batch).is_ok()
+        })
+        .await
+    })
+    .await
+}
+
+/// First `rows` rows of a CSV, TSV, JSON Lines or Parquet file as columns
+/// and rows, read without downloading the whole file.
+#[tauri::command]
+pub async fn preview_table(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    rows: Option<usize>,
+) -> Result<TablePreview, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("preview_table", workers::READ_TIMEOUT, async move {
+        tabular::preview_table(&op, &path, rows.unwrap_or(tabular::DEFAULT_PREVIEW_ROWS)).await
+    })
+    .await
+}
+
+/// Tables of a SQLite database with their row counts and first `rows` rows
+/// each. Remote databases are downloaded into the preview cache first.
+#[tauri::command]
+pub async fn preview_sqlite(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    rows: Option<usize>,
+) -> Result<Vec<SqliteTable>, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("preview_sqlite", workers::READ_TIMEOUT, async move {
+        tabular::preview_sqlite(
+            &op,
+            &path,
+            rows.unwrap_or(tabular::DEFAULT_SQLITE_SAMPLE_ROWS),
+        )
+        .await
+    })
+    .await
+}
+
+/// A JSON document as a tree of at most `nodes` nodes, with long strings
+/// cut short and the lengths of truncated arrays and objects kept.
+#[tauri::command]
+pub async fn preview_json(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    nodes: Option<usize>,
+) -> Result<JsonPreview, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("preview_json", workers::READ_TIMEOUT, async move {
+        structured::preview_json(&op, &path, nodes.unwrap_or(structured::DEFAULT_JSON_NODES)).await
+    })
+    .await
+}
+
+/// The cells of a Jupyter notebook with text outputs truncated and images
+/// and other rich outputs reduced to their type and size.
+#[tauri::command]
+pub async fn preview_notebook(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+) -> Result<NotebookPreview, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("preview_notebook", workers::READ_TIMEOUT, async move {
+        structured::preview_notebook(&op, &path).await
+    })
+    .await
+}
+
+/// Names, glyph count and style of a TrueType/OpenType font, with `sample`
+/// (a pangram by default) rendered to a PNG.
+#[tauri::command]
+pub async fn preview_font(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    sample: Option<String>,
+) -> Result<FontPreview, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("preview_font", workers::READ_TIMEOUT, async move {
+        let sample = sample.as_deref().unwrap_or(media::DEFAULT_FONT_SAMPLE);
+        media::preview_font(&op, &path, sample).await
+    })
+    .await
+}
+
+/// Vertex and face counts and bounding box of an STL, OBJ, PLY or glTF
+/// model; with `thumbnail`, STL and OBJ meshes are also rendered to a PNG.
+#[tauri::command]
+pub async fn preview_model(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    thumbnail: Option<bool>,
+) -> Result<ModelPreview, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("preview_model", workers::READ_TIMEOUT, async move {
+        media::preview_model(&op, &path, thumbnail.unwrap_or(false)).await
+    })
+    .await
+}
+
+/// Headers, text and HTML bodies and attachment list of an `.eml` or
+/// Outlook `.msg` file.
+#[tauri::command]
+pub async fn preview_email(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+) -> Result<EmailPreview, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("preview_email", workers::READ_TIMEOUT, async move {
+        mail::preview_email(&op, &path).await
+    })
+    .await
+}
+
+/// Save the attachments of an email (all, or those at `indexes`) into
+/// `targetDir` on `toSourceId`. Returns the paths written.
+#[tauri::command]
+pub async fn extract_email_attachments(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    indexes: Option<Vec<usize>>,
+    toSourceId: String,
+    targetDir: String,
+) -> Result<Vec<String>, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let to_op = state.operator_for_storage_id(&toSourceId).await?;
+    workers::run(
+        "extract_email_attachments",
+        workers::READ_TIMEOUT,
+        async move {
+            mail::extract_attachments(&op, &path, indexes.as_deref(), &to_op, &targetDir).await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn write_file(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    data: Vec<u8>,
+    options: Option<WriteOptions>,
+) -> Result<(), CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    operations::write_full(&op, &path, &data, &options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn create_directory(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+) -> Result<(), CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    operations::create_dir(&op, &path).await
+}
+
+/// Whether deleting `path` should go to the source's own trash rather than
+/// removing it: unless asked to delete permanently, for paths outside the
+/// trash on sources that can hold one.
+fn deletes_to_trash(op: &opendal::Operator, path: &str, permanent: bool) -> bool {
+    !permanent && !trash::is_trash_path(path) && operations::capabilities(op).trash
+}
+
+#[tauri::command]
+pub async fn delete_path(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    operationId: Option<String>,
+    permanent: Option<bool>,
+) -> Result<(), CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let permanent = permanent.unwrap_or(false);
+    if !permanent && state.storage_uses_os_trash(&sourceId)? {
+        return operations::move_to_os_trash(&op, &path).await;
+    }
+    if deletes_to_trash(&op, &path, permanent) {
+        return trash::move_to_trash(&op, &path).await.map(|_| ());
+    }
+    let (_registration, token) = cancellable(operationId);
+    operations::delete_cancellable(&op, &path, &token).await
+}
+
+/// Delete many files and folders in as few requests as the backend allows.
+/// Every path ends up in the report as deleted or failed.
+#[tauri::command]
+pub async fn delete_paths(
+    state: State<'_, AppState>,
+    sourceId: String,
+    paths: Vec<String>,
+    operationId: Option<String>,
+    permanent: Option<bool>,
+) -> Result<DeleteReport, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let permanent = permanent.unwrap_or(false);
+    if !permanent && state.storage_uses_os_trash(&sourceId)? {
+        let mut report = DeleteReport::default();
+        for path in paths {
+            let result = operations::move_to_os_trash(&op, &path).await;
+            report.record(path, result);
+        }
+        return Ok(report);
+    }
+    let (_registration, token) = cancellable(operationId);
+    let (trashed, removed): (Vec<String>, Vec<String>) = paths
+        .into_iter()
+        .partition(|path| deletes_to_trash(&op, path, permanent));
+    let mut report = operations::delete_many(&op, removed, &token).await?;
+    for path in trashed {
+        let result = match token.check() {
+            Ok(()) => trash::move_to_trash(&op, &path).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        report.record(path, result);
+    }
+    Ok(report)
+}
+
+/// Items in a source's trash, most recently deleted first.
+#[tauri::command]
+pub async fn list_trash(
+    state: State<'_, AppState>,
+    sourceId: String,
+) -> Result<Vec<TrashItem>, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    trash::list_trash(&op).await
+}
+
+/// Put a trashed entry back; returns the path it was restored to.
+#[tauri::command]
+pub async fn restore_from_trash(
+    state: State<'_, AppState>,
+    sourceId: String,
+    id: String,
+) -> Result<String, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    trash::restore_from_trash(&op, &id).await
+}
+
+#[tauri::command]
+pub async fn empty_trash(state: State<'_, AppState>, sourceId: String) -> Result<usize, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    trash::empty_trash(&op).await
+}
+
+#[tauri::command]
+pub async fn create_symlink(
+    state: State<'_, AppState>,
+    sourceId: String,
+    target: String,
+    linkPath: String,
+) -> Result<(), CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    operations::create_symlink(&op, &target, &linkPath).await
+}
+
+#[tauri::command]
+pub async fn create_hardlink(
+    state: State<'_, AppState>,
+    sourceId: String,
+    existing: String,
+    linkPath: String,
+) -> Result<(), CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    operations::create_hardlink(&op, &existing, &linkPath).await
+}
+
+#[tauri::command]
+pub async fn upload_dropped_files(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    sourceId: String,
+    paths: Vec<String>,
+    targetDir: String,
+    conflictPolicy: String,
+    preserveAttributes: Option<bool>,
+) -> Result<TransferReport, CoreError> {
+    let policy = parse_conflict_policy(&conflictPolicy)?;
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let mut report = TransferReport::new("upload", "local", &sourceId);
+    let preferences = config::load_preferences().unwrap_or_default();
+    report.set_preserve_attributes(preserveAttributes.unwrap_or(true));
+    report.set_upload_concurrency(preferences.upload_concurrency);
+    report.set_filter(preferences.transfer_filter());
+    let _registration = track(app, &mut report);
+    let keep_links = preferences.copy_symlinks_as_links;
+    let result = operations::upload(&mut report, &op, paths, &targetDir, policy, keep_links).await;
+    finish_report(&state, report, result)
 }
 
+/// Uploads to a source that were interrupted and can continue where they
+/// stopped.
 #[tauri::command]
-pub async fn read_file(
+pub async fn list_resumable_uploads(
     state: State<'_, AppState>,
     sourceId: String,
-    path: String,
-) -> Result<Vec<u8>, CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
-    operations::read_full(&op, &path).await
+) -> Result<Vec<ResumableUpload>, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    Ok(resume::pending_for(&op))
 }
 
+/// Continue every interrupted upload to a source whose local file is
+/// unchanged; the others are discarded.
 #[tauri::command]
-pub async fn write_file(
+pub async fn resume_interrupted_uploads(
+    app: AppHandle,
     state: State<'_, AppState>,
     sourceId: String,
-    path: String,
-    data: Vec<u8>,
-) -> Result<(), CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
-    operations::write_full(&op, &path, &data).await
+) -> Result<TransferReport, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let mut report = TransferReport::new("upload", "local", &sourceId);
+    let _registration = track(app, &mut report);
+    let result = resume::resume_pending(&mut report, &op).await;
+    finish_report(&state, report, result)
 }
 
+/// Give up on an interrupted upload, deleting what it stored so far.
+/// Returns whether there was one for `path`.
 #[tauri::command]
-pub async fn create_directory(
+pub async fn discard_resumable_upload(
     state: State<'_, AppState>,
     sourceId: String,
     path: String,
-) -> Result<(), CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
-    operations::create_directory(&op, &path).await
+) -> Result<bool, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    resume::discard(&op, &path).await
 }
 
+/// Files shared with the app from the OS ("Send to", "Open with") that
+/// still need a destination; each is returned once.
 #[tauri::command]
-pub async fn delete_path(
-    state: State<'_, AppState>,
-    sourceId: String,
-    path: String,
-) -> Result<(), CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
-    operations::delete(&op, &path).await
+pub fn take_shared_files() -> Vec<String> {
+    share::take_pending()
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect()
 }
 
+/// Upload shared files into `prefix` on the chosen source. Returns how many
+/// items were uploaded.
 #[tauri::command]
-pub async fn upload_dropped_files(
+pub async fn intake_shared_files(
     state: State<'_, AppState>,
     sourceId: String,
+    prefix: String,
     paths: Vec<String>,
-    targetDir: String,
-) -> Result<(), CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
-    operations::upload_files_from_paths(&op, paths, targetDir).await
+) -> Result<usize, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    share::intake(&op, &paths, &prefix).await
+}
+
+pub const TRANSFER_PROGRESS_EVENT: &str = "transfer-progress";
+
+/// Forwards a transfer's progress to the UI as [`TRANSFER_PROGRESS_EVENT`].
+fn progress_sink(app: AppHandle) -> ProgressSink {
+    Arc::new(move |progress: &TransferProgress| {
+        if let Err(error) = app.emit(TRANSFER_PROGRESS_EVENT, progress) {
+            eprintln!("failed to emit transfer progress: {error}");
+        }
+    })
+}
+
+/// Streams `report`'s progress to the UI and lets [`cancel_operation`]
+/// stop it by task id while the returned registration is alive.
+fn track(app: AppHandle, report: &mut TransferReport) -> cancel::Registration {
+    report.set_progress_sink(progress_sink(app));
+    let registration = cancel::register(report.task_id.clone());
+    report.set_cancel_token(registration.token());
+    registration
+}
+
+/// Finish `report` and keep it in the task history. A cancelled job is
+/// kept too, so what it did not get to can be retried.
+fn finish_report(
+    state: &AppState,
+    mut report: TransferReport,
+    result: Result<(), CoreError>,
+) -> Result<TransferReport, CoreError> {
+    report.finish();
+    match result {
+        Ok(()) | Err(CoreError::Cancelled) => state.store_task_report(report.clone()),
+        Err(_) => {}
+    }
+    result.map(|()| report)
 }
 
+/// Cancel the upload, download, transfer, delete or search running under
+/// `operationId` (a transfer's task id, or the id the UI gave the job).
+/// Returns whether one was running.
+#[tauri::command]
+pub fn cancel_operation(operationId: String) -> bool {
+    cancel::cancel(&operationId)
+}
+
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn transfer_entries(
+    app: AppHandle,
     state: State<'_, AppState>,
     fromSourceId: String,
     toSourceId: String,
@@ -117,9 +1038,10 @@ pub async fn transfer_entries(
     targetDir: String,
     operation: String,
     conflictPolicy: String,
-) -> Result<(), CoreError> {
-    let from_op = state.operator_for_storage_id(&fromSourceId)?;
-    let to_op = state.operator_for_storage_id(&toSourceId)?;
+    preserveAttributes: Option<bool>,
+) -> Result<TransferReport, CoreError> {
+    let from_op = state.operator_for_storage_id(&fromSourceId).await?;
+    let to_op = state.operator_for_storage_id(&toSourceId).await?;
 
     let op = match operation.as_str() {
         "copy" => operations::TransferOperation::Copy,
@@ -132,19 +1054,18 @@ pub async fn transfer_entries(
         }
     };
 
-    let policy = match conflictPolicy.as_str() {
-        "fail" => operations::TransferConflictPolicy::Fail,
-        "overwrite" => operations::TransferConflictPolicy::Overwrite,
-        "skip" | "discard" => operations::TransferConflictPolicy::Skip,
-        _ => {
-            return Err(CoreError::Config(format!(
-                "invalid transfer conflict policy: {}",
-                conflictPolicy
-            )));
-        }
-    };
+    let policy = parse_conflict_policy(&conflictPolicy)?;
 
-    operations::transfer_entries(
+    let mut report = TransferReport::new(op.as_str(), &fromSourceId, &toSourceId);
+    report.set_preserve_attributes(preserveAttributes.unwrap_or(true));
+    report.set_filter(
+        config::load_preferences()
+            .unwrap_or_default()
+            .transfer_filter(),
+    );
+    let _registration = track(app, &mut report);
+    let result = operations::transfer_entries(
+        &mut report,
         &from_op,
         &to_op,
         paths,
@@ -153,9 +1074,201 @@ pub async fn transfer_entries(
         fromSourceId == toSourceId,
         policy,
     )
+    .await;
+    finish_report(&state, report, result)
+}
+
+fn parse_conflict_policy(policy: &str) -> Result<operations::TransferConflictPolicy, CoreError> {
+    match policy {
+        "fail" => Ok(operations::TransferConflictPolicy::Fail),
+        "overwrite" => Ok(operations::TransferConflictPolicy::Overwrite),
+        "skip" | "discard" => Ok(operations::TransferConflictPolicy::Skip),
+        "rename" => Ok(operations::TransferConflictPolicy::Rename),
+        _ => Err(CoreError::Config(format!(
+            "invalid transfer conflict policy: {}",
+            policy
+        ))),
+    }
+}
+
+/// Download files and folders to a local folder picked by the user, or the
+/// Downloads folder when `localDir` is not given. Large downloads need
+/// `confirmed`; see [`thresholds`].
+#[tauri::command]
+pub async fn download_entries(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    sourceId: String,
+    paths: Vec<String>,
+    localDir: Option<String>,
+    conflictPolicy: String,
+    confirmed: Option<bool>,
+    preserveAttributes: Option<bool>,
+) -> Result<TransferReport, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let policy = parse_conflict_policy(&conflictPolicy)?;
+    let preferences = config::load_preferences().unwrap_or_default();
+    let filter = preferences.transfer_filter();
+    thresholds::check_download_paths(
+        &op,
+        &paths,
+        filter,
+        &preferences.size_thresholds,
+        confirmed.unwrap_or(false),
+    )
+    .await?;
+    let local_dir = match localDir {
+        Some(dir) => PathBuf::from(dir),
+        None => app
+            .path()
+            .download_dir()
+            .map_err(|e| CoreError::Config(format!("no Downloads folder: {e}")))?,
+    };
+
+    let mut report = TransferReport::new("download", &sourceId, local_dir.display().to_string());
+    report.set_preserve_attributes(preserveAttributes.unwrap_or(true));
+    report.set_filter(filter);
+    let _registration = track(app, &mut report);
+    let mut result = Ok(());
+    for path in &paths {
+        result = operations::download(&mut report, &op, path, &local_dir, policy).await;
+        if result.is_err() {
+            break;
+        }
+    }
+    finish_report(&state, report, result)
+}
+
+/// Stream one file or folder from one source to another, e.g. local to S3.
+#[tauri::command]
+pub async fn transfer_between_sources(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    fromSourceId: String,
+    fromPath: String,
+    toSourceId: String,
+    toPath: String,
+    preserveAttributes: Option<bool>,
+) -> Result<TransferReport, CoreError> {
+    let from_op = state.operator_for_storage_id(&fromSourceId).await?;
+    let to_op = state.operator_for_storage_id(&toSourceId).await?;
+
+    let mut report = TransferReport::new(
+        operations::TransferOperation::Copy.as_str(),
+        &fromSourceId,
+        &toSourceId,
+    );
+    report.set_preserve_attributes(preserveAttributes.unwrap_or(true));
+    report.set_filter(
+        config::load_preferences()
+            .unwrap_or_default()
+            .transfer_filter(),
+    );
+    let _registration = track(app, &mut report);
+    let result = operations::transfer(&mut report, &from_op, &fromPath, &to_op, &toPath).await;
+    finish_report(&state, report, result)
+}
+
+/// Re-attempt only the failed items of a finished transfer.
+#[tauri::command]
+pub async fn retry_failed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    taskId: String,
+) -> Result<TransferReport, CoreError> {
+    let previous = state
+        .task_report(&taskId)
+        .ok_or_else(|| CoreError::Config(format!("no report for task '{taskId}'")))?;
+    let from_op = state.operator_for_storage_id(&previous.from_source).await?;
+    let to_op = state.operator_for_storage_id(&previous.to_source).await?;
+
+    let mut report = previous.retry();
+    let _registration = track(app, &mut report);
+    let result = operations::retry_failed(
+        &mut report,
+        &previous,
+        &from_op,
+        &to_op,
+        previous.from_source == previous.to_source,
+    )
+    .await;
+    finish_report(&state, report, result)
+}
+
+#[tauri::command]
+pub fn list_organize_rules() -> Result<Vec<OrganizeRule>, CoreError> {
+    organize::load_rules()
+}
+
+/// Replace the saved organization rules; enabled ones are applied in the
+/// background from then on.
+#[tauri::command]
+pub fn save_organize_rules(rules: Vec<OrganizeRule>) -> Result<(), CoreError> {
+    organize::save_rules(&rules)
+}
+
+/// Dry run: the moves `rule` would make now, without moving anything.
+#[tauri::command]
+pub async fn preview_organize_rule(
+    state: State<'_, AppState>,
+    rule: OrganizeRule,
+) -> Result<Vec<PlannedMove>, CoreError> {
+    let op = state.operator_for_storage_id(&rule.source_id).await?;
+    workers::run("organize preview", workers::LIST_TIMEOUT, async move {
+        organize::plan(&op, &rule).await
+    })
     .await
 }
 
+/// Apply `rule` right away instead of waiting for the background run.
+#[tauri::command]
+pub async fn run_organize_rule(
+    state: State<'_, AppState>,
+    rule: OrganizeRule,
+) -> Result<TransferReport, CoreError> {
+    let op = state.operator_for_storage_id(&rule.source_id).await?;
+    let report = organize::apply(&op, &rule).await?;
+    state.store_task_report(report.clone());
+    Ok(report)
+}
+
+/// Pause every transfer and background job; stays paused across restarts.
+#[tauri::command]
+pub fn pause_all_transfers() -> Result<bool, CoreError> {
+    pause::pause_all()?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn resume_all_transfers() -> Result<bool, CoreError> {
+    pause::resume_all()?;
+    Ok(false)
+}
+
+#[tauri::command]
+pub fn transfers_paused() -> bool {
+    pause::is_paused()
+}
+
+/// Render a stored transfer report as `"csv"` or `"json"`.
+#[tauri::command]
+pub fn export_task_report(
+    state: State<'_, AppState>,
+    taskId: String,
+    format: String,
+) -> Result<String, CoreError> {
+    let report = state
+        .task_report(&taskId)
+        .ok_or_else(|| CoreError::Config(format!("no report for task '{taskId}'")))?;
+    match format.as_str() {
+        "csv" => Ok(report.to_csv()),
+        "json" => report.to_json(),
+        _ => Err(CoreError::Config(format!(
+            "invalid report format: {format}"
+        ))),
+    }
+}
+
 #[tauri::command]
 pub fn list_storages(state: State<'_, AppState>) -> Result<Vec<StorageRecord>, McpError> {
     state.list_storages()
@@ -174,12 +1287,115 @@ pub fn add_storage(
         record.enabled = storage.enabled;
         record.mcp_exposed = storage.mcp_exposed;
         record.read_only = storage.read_only;
+        state.apply_credential_set(&mut record)?;
+        record_capabilities(&mut record);
         storages.push(record.clone());
         Ok(record)
     })?;
     Ok(record)
 }
 
+/// "Connect without saving": a storage that lives only until the app quits.
+#[tauri::command]
+pub fn add_session_storage(
+    state: State<'_, AppState>,
+    storage: StorageDraft,
+) -> Result<StorageRecord, McpError> {
+    validate_storage_draft(&storage)?;
+    let name = validate_storage_name(&storage.name)?;
+    let mut record = StorageRecord::new(name, storage.backend, storage.config);
+    record.enabled = storage.enabled;
+    // The MCP server only sees the registry file, so never advertise it there.
+    record.mcp_exposed = false;
+    record.read_only = storage.read_only;
+    state.apply_credential_set(&mut record)?;
+    record_capabilities(&mut record);
+    state.add_session_storage(record)
+}
+
+/// Add a read-only synthetic source with `entries` generated files, for
+/// checking listing and index performance on huge sources.
+#[tauri::command]
+pub fn add_synthetic_source(
+    state: State<'_, AppState>,
+    entries: u64,
+    entriesPerDir: Option<u64>,
+    fileSize: Option<u64>,
+    latencyMs: Option<u64>,
+) -> Result<StorageRecord, McpError> {
+    let mut config = Map::new();
+    config.insert("entries".into(), entries.into());
+    for (key, value) in [
+        ("entriesPerDir", entriesPerDir),
+        ("fileSize", fileSize),
+        ("latencyMs", latencyMs),
+    ] {
+        if let Some(value) = value {
+            config.insert(key.into(), value.into());
+        }
+    }
+    let mut record = StorageRecord::new(
+        format!("Synthetic ({entries} files)"),
+        synthetic::SYNTHETIC_BACKEND.to_string(),
+        Value::Object(config),
+    );
+    record.mcp_exposed = false;
+    record.read_only = true;
+    state.add_session_storage(record)
+}
+
+/// Add a read-only source browsing the restic repository at `path` on
+/// source `sourceId`, with one folder per snapshot. It lasts for this
+/// session only, so the password is never written to disk. The repository
+/// is unlocked first so a wrong password or path fails here.
+#[tauri::command]
+pub async fn add_restic_source(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    password: String,
+    name: Option<String>,
+) -> Result<StorageRecord, CoreError> {
+    let source = state
+        .find_storage_by_id(&sourceId)
+        .map_err(mcp_error_to_core_error)?;
+    let store = state.operator_for_storage_id(&sourceId).await?;
+    restic::operator(store, &path, &password).stat("/").await?;
+
+    let folder = path
+        .trim_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    let name = name.unwrap_or_else(|| match folder {
+        "" => format!("Restic: {}", source.name),
+        folder => format!("Restic: {folder}"),
+    });
+    let mut record = StorageRecord::new(
+        name,
+        restic::RESTIC_BACKEND.to_string(),
+        serde_json::json!({ "sourceId": sourceId, "path": path, "password": password }),
+    );
+    record.mcp_exposed = false;
+    record.read_only = true;
+    state
+        .add_session_storage(record)
+        .map_err(mcp_error_to_core_error)
+}
+
+/// Add the built-in "Demo" source: generated sample files kept in memory for
+/// this session, for trying the app without configuring real storage.
+#[tauri::command]
+pub fn add_demo_source(state: State<'_, AppState>) -> Result<StorageRecord, McpError> {
+    let mut record = StorageRecord::new(
+        "Demo".to_string(),
+        demo::DEMO_BACKEND.to_string(),
+        Value::Object(Map::new()),
+    );
+    record.mcp_exposed = false;
+    state.add_session_storage(record)
+}
+
 #[tauri::command]
 pub fn update_storage(
     state: State<'_, AppState>,
@@ -209,13 +1425,20 @@ pub fn update_storage(
         updated.mcp_exposed = storage.mcp_exposed;
         updated.read_only = storage.read_only;
         updated.updated_at = Utc::now().to_rfc3339();
+        state.apply_credential_set(&mut updated)?;
+        record_capabilities(&mut updated);
         storages[idx] = updated.clone();
         Ok(updated)
     })
 }
 
+/// Session storages are dropped right away; saved ones move to the recently
+/// removed list (see [`restore_storage`]).
 #[tauri::command]
 pub fn remove_storage(state: State<'_, AppState>, storageId: String) -> Result<(), McpError> {
+    if state.remove_session_storage(&storageId) {
+        return Ok(());
+    }
     state.registry.with_locked_mutation(|storages| {
         let original_len = storages.len();
         storages.retain(|storage| storage.id != storageId);
@@ -230,17 +1453,73 @@ pub fn remove_storage(state: State<'_, AppState>, storageId: String) -> Result<(
     })
 }
 
+/// Storages removed within the grace period, newest first.
+#[tauri::command]
+pub fn list_removed_storages(state: State<'_, AppState>) -> Result<Vec<RemovedStorage>, McpError> {
+    state.registry.list_removed()
+}
+
+/// Bring back a recently removed storage with its config and credentials.
 #[tauri::command]
-pub async fn verify_storage(storage: StorageDraft) -> Result<ValidateStorageOutput, McpError> {
+pub fn restore_storage(
+    state: State<'_, AppState>,
+    storageId: String,
+) -> Result<StorageRecord, McpError> {
+    state.registry.restore_removed(&storageId)
+}
+
+/// Forget a recently removed storage and its keyring secrets now.
+#[tauri::command]
+pub fn forget_removed_storage(
+    state: State<'_, AppState>,
+    storageId: String,
+) -> Result<(), McpError> {
+    state.registry.forget_removed(&storageId)
+}
+
+#[tauri::command]
+pub async fn verify_storage(
+    state: State<'_, AppState>,
+    storage: StorageDraft,
+) -> Result<ValidateStorageOutput, McpError> {
     validate_storage_draft(&storage)?;
     let name = validate_storage_name(&storage.name)?;
     let mut record = StorageRecord::new(name, storage.backend, storage.config);
     record.enabled = storage.enabled;
     record.mcp_exposed = storage.mcp_exposed;
     record.read_only = storage.read_only;
+    state.apply_credential_set(&mut record)?;
     validate_storage_record(&record).await
 }
 
+#[tauri::command]
+pub fn list_credential_sets(state: State<'_, AppState>) -> Result<Vec<CredentialSet>, McpError> {
+    state.credentials.list()
+}
+
+/// Create a credential set (no `credentialId`) or rotate an existing one.
+/// Every storage linked to the set picks up the new values.
+#[tauri::command]
+pub fn save_credential_set(
+    state: State<'_, AppState>,
+    credentialId: Option<String>,
+    name: String,
+    values: Map<String, Value>,
+) -> Result<CredentialSet, McpError> {
+    state
+        .credentials
+        .save(credentialId.as_deref(), &name, &values)
+}
+
+#[tauri::command]
+pub fn remove_credential_set(
+    state: State<'_, AppState>,
+    credentialId: String,
+) -> Result<(), McpError> {
+    let storages = state.list_storages()?;
+    state.credentials.remove(&credentialId, &storages)
+}
+
 #[tauri::command]
 pub async fn import_storage_config(
     state: State<'_, AppState>,
@@ -252,11 +1531,78 @@ pub async fn import_storage_config(
             json: request.json,
             mode: request.mode,
             on_conflict: request.on_conflict,
+            dry_run: request.dry_run,
         },
     )
     .await
 }
 
+/// Export storages, credential sets, MCP settings and preferences as one
+/// file for another machine. Secrets are never included.
+#[tauri::command]
+pub fn export_workspace(state: State<'_, AppState>) -> Result<ExportConfigOutput, McpError> {
+    let mut workspace =
+        workspace::export(&state.registry, &state.credentials, &state.settings_store)?;
+    let preferences = config::load_preferences().unwrap_or_default();
+    workspace.preferences = serde_json::to_value(preferences).unwrap_or_default();
+    Ok(ExportConfigOutput {
+        json: workspace.to_json()?,
+    })
+}
+
+/// Import a workspace file. `"merge"` keeps this machine's settings;
+/// `"replace"` also takes the MCP settings and preferences from the file.
+#[tauri::command]
+pub async fn import_workspace(
+    state: State<'_, AppState>,
+    request: ImportStoragesRequest,
+) -> Result<WorkspaceImportOutput, McpError> {
+    let imported = Workspace::from_json(&request.json)?;
+    let preferences = serde_json::from_value::<Preferences>(imported.preferences.clone()).ok();
+    let output = workspace::import(
+        &state.fs_context(),
+        &state.credentials,
+        &state.settings_store,
+        WorkspaceImportInput {
+            workspace: imported,
+            mode: request.mode,
+            on_conflict: request.on_conflict,
+            dry_run: request.dry_run,
+        },
+    )
+    .await?;
+
+    if output.settings_applied && !request.dry_run {
+        if let Some(preferences) = preferences {
+            apply_preferences(&state, &preferences).map_err(|error| {
+                err_with_details(
+                    McpErrorCode::ERR_INTERNAL,
+                    "failed to save imported preferences",
+                    serde_json::json!({ "error": error.to_string() }),
+                )
+            })?;
+        }
+    }
+    Ok(output)
+}
+
+/// Add the remotes of an rclone config as storages. `path` defaults to
+/// rclone's own config location.
+#[tauri::command]
+pub fn import_rclone_config(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<RcloneImportResult, CoreError> {
+    let path = path
+        .map(PathBuf::from)
+        .or_else(config::default_rclone_config_path)
+        .ok_or_else(|| CoreError::Config("no rclone config found".to_string()))?;
+    let import = config::import_rclone(&path)?;
+    state
+        .add_rclone_sources(import)
+        .map_err(mcp_error_to_core_error)
+}
+
 #[tauri::command]
 pub async fn export_storage_config(
     state: State<'_, AppState>,
@@ -271,18 +1617,225 @@ pub async fn export_storage_config(
     .await
 }
 
+/// Check the registry, credentials and caches, optionally probing every
+/// enabled storage, and suggest fixes for what is wrong.
+#[tauri::command]
+pub async fn run_doctor(state: State<'_, AppState>, probe: bool) -> Result<DoctorReport, McpError> {
+    Ok(doctor::run(&state.registry, &state.settings_store, probe).await)
+}
+
 #[tauri::command]
 pub fn list_storage_schemas() -> Result<Vec<StorageKindSchema>, CoreError> {
-    infimount_core::schema::list_storage_schemas()
+    infimount_core::schema::localized_storage_schemas()
+}
+
+/// Use `locale` (a BCP 47 tag such as `de-AT`) for backend messages.
+/// Returns the locale actually selected.
+#[tauri::command]
+pub fn set_locale(locale: String) -> String {
+    i18n::set_locale(&locale)
+}
+
+/// Text of a backend message id (e.g. `task-status-failed`) in the current
+/// locale.
+#[tauri::command]
+pub fn translate_message(messageId: String, args: BTreeMap<String, String>) -> Option<String> {
+    Message {
+        id: messageId,
+        args,
+    }
+    .translate()
+}
+
+#[tauri::command]
+pub async fn discover_local_roots() -> Result<Vec<LocalRoot>, CoreError> {
+    // Statting network mounts can block, so keep it off the async runtime.
+    tokio::task::spawn_blocking(volumes::discover_local_roots)
+        .await
+        .map_err(|e| CoreError::Io(std::io::Error::other(e)))
+}
+
+/// Common user folders to offer as Local sources on first launch. Empty once
+/// any storage is saved (inserted drives don't count).
+#[tauri::command]
+pub fn get_suggested_sources(
+    state: State<'_, AppState>,
+) -> Result<Vec<SuggestedSource>, CoreError> {
+    let storages = state.list_storages().map_err(mcp_error_to_core_error)?;
+    if storages.iter().any(|storage| !storage.ephemeral) {
+        return Ok(Vec::new());
+    }
+    Ok(volumes::suggested_sources())
+}
+
+/// Try a candidate source before it is saved: a cheap list/stat with its
+/// latency, or the reason it failed.
+#[tauri::command]
+pub async fn test_source(source: Source) -> ConnectionTest {
+    match operator_for_source(&source) {
+        Ok(op) => operations::test_connection(&op).await,
+        Err(error) => ConnectionTest::config_error(&error),
+    }
 }
 
 #[tauri::command]
-pub fn get_storage_capabilities(
+pub async fn get_storage_capabilities(
     state: State<'_, AppState>,
     storageId: String,
 ) -> Result<StorageBackendCapabilities, CoreError> {
-    let op = state.operator_for_storage_id(&storageId)?;
-    Ok(get_capabilities(&op))
+    let op = state.operator_for_storage_id(&storageId).await?;
+    let mut capabilities = get_capabilities(&op);
+    capabilities.delete_to_trash =
+        operations::supports_os_trash(&op) && state.storage_uses_os_trash(&storageId)?;
+    capabilities.links = operations::supports_links(&op);
+    Ok(capabilities)
+}
+
+/// Everything a source supports (presign, copy, append, versioning, ...) so
+/// the UI can hide actions that would fail.
+#[tauri::command]
+pub async fn get_source_capabilities(
+    state: State<'_, AppState>,
+    sourceId: String,
+) -> Result<SourceCapabilities, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    let mut capabilities = operations::capabilities(&op);
+    capabilities.os_trash = capabilities.os_trash && state.storage_uses_os_trash(&sourceId)?;
+    Ok(capabilities)
+}
+
+/// How listings of a source get their metadata, and the latency that
+/// choice is based on.
+#[tauri::command]
+pub async fn get_listing_diagnostics(
+    state: State<'_, AppState>,
+    sourceId: String,
+) -> Result<ListingDiagnostics, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    Ok(operations::listing_diagnostics(&op))
+}
+
+/// Worker pool load: threads, jobs queued and running, and how finished
+/// jobs ended.
+#[tauri::command]
+pub fn get_runtime_stats() -> RuntimeStats {
+    workers::stats()
+}
+
+/// Sidebar icon and accent for a source, plus the server's favicon for
+/// self-hosted endpoints when the `fetch_favicons` preference is on.
+#[tauri::command]
+pub async fn get_source_branding(
+    state: State<'_, AppState>,
+    sourceId: String,
+) -> Result<SourceBranding, CoreError> {
+    let storage = state
+        .find_storage_by_id(&sourceId)
+        .map_err(mcp_error_to_core_error)?;
+    if [
+        demo::DEMO_BACKEND,
+        synthetic::SYNTHETIC_BACKEND,
+        restic::RESTIC_BACKEND,
+    ]
+    .contains(&storage.backend.as_str())
+    {
+        return Ok(SourceBranding {
+            icon: storage.backend.clone(),
+            accent_hue: branding::accent_hue(&storage.id),
+            favicon: None,
+        });
+    }
+    let kind: SourceKind = serde_json::from_value(Value::String(storage.backend.clone()))?;
+    let settings: HashMap<String, String> = storage
+        .config
+        .as_object()
+        .map(|config| {
+            config
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut source_branding = branding::branding(&kind, &settings, &storage.id);
+    if config::load_preferences()
+        .unwrap_or_default()
+        .fetch_favicons
+    {
+        if let Some(endpoint) = branding::self_hosted_endpoint(&kind, &settings) {
+            source_branding.favicon = branding::favicon(&endpoint).await;
+        }
+    }
+    Ok(source_branding)
+}
+
+/// Run the Google Drive consent flow in the system browser. When `storageId`
+/// is given the resulting tokens are also saved into that storage's config.
+#[tauri::command]
+pub async fn authorize_gdrive(
+    state: State<'_, AppState>,
+    clientId: String,
+    clientSecret: String,
+    storageId: Option<String>,
+) -> Result<OAuthTokens, CoreError> {
+    let flow = AuthorizationFlow::start(
+        oauth::GOOGLE_DRIVE,
+        OAuthClient {
+            client_id: clientId,
+            client_secret: clientSecret,
+        },
+    )
+    .await?;
+    open_in_browser(&flow.authorization_url())?;
+
+    let tokens = flow.complete(OAUTH_CONSENT_TIMEOUT).await?;
+    if let Some(storage_id) = storageId {
+        state
+            .persist_oauth_tokens(&storage_id, &tokens)
+            .map_err(mcp_error_to_core_error)?;
+    }
+    Ok(tokens)
+}
+
+const OAUTH_CONSENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+fn open_in_browser(url: &str) -> Result<(), CoreError> {
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(url).spawn()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_preferences() -> Result<Preferences, CoreError> {
+    config::load_preferences()
+}
+
+#[tauri::command]
+pub fn update_preferences(
+    state: State<'_, AppState>,
+    preferences: Preferences,
+) -> Result<Preferences, CoreError> {
+    apply_preferences(&state, &preferences)?;
+    Ok(preferences)
+}
+
+fn apply_preferences(state: &AppState, preferences: &Preferences) -> Result<(), CoreError> {
+    config::save_preferences(preferences)?;
+    if !preferences.auto_add_removable_drives {
+        // Drop drives that were surfaced while the option was on.
+        state.sync_removable_volumes(volumes::discover_local_roots(), false);
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -333,7 +1886,7 @@ pub async fn list_versions(
     limit: Option<u32>,
     cursor: Option<String>,
 ) -> Result<Value, CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
+    let op = state.operator_for_storage_id(&sourceId).await?;
     let result =
         operations::list_file_versions(&op, &path, limit.unwrap_or(100), cursor.as_deref()).await?;
     Ok(serde_json::to_value(result).unwrap_or(Value::Null))
@@ -346,10 +1899,26 @@ pub async fn read_file_version(
     path: String,
     version: String,
 ) -> Result<Vec<u8>, CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
+    let op = state.operator_for_storage_id(&sourceId).await?;
     operations::read_file_version(&op, &path, &version).await
 }
 
+/// Make an earlier version of a file the current one again; it is written
+/// back as a new version, so nothing is lost. Returns the bytes written.
+#[tauri::command]
+pub async fn restore_version(
+    state: State<'_, AppState>,
+    sourceId: String,
+    path: String,
+    version: String,
+) -> Result<u64, CoreError> {
+    let op = state.operator_for_storage_id(&sourceId).await?;
+    workers::run("restore_version", workers::READ_TIMEOUT, async move {
+        operations::restore_file_version(&op, &path, &version).await
+    })
+    .await
+}
+
 #[tauri::command]
 pub async fn delete_version(
     state: State<'_, AppState>,
@@ -357,11 +1926,19 @@ pub async fn delete_version(
     path: String,
     version: String,
 ) -> Result<Value, CoreError> {
-    let op = state.operator_for_storage_id(&sourceId)?;
+    let op = state.operator_for_storage_id(&sourceId).await?;
     operations::delete_file_version(&op, &path, &version).await?;
     Ok(serde_json::json!({ "deleted": true, "path": path, "version": version }))
 }
 
+/// Probe what the backend supports and keep it with the storage, so later
+/// operations it lacks (e.g. rename on GCS) fail up front with a clear error.
+fn record_capabilities(record: &mut StorageRecord) {
+    record.capabilities = build_operator(record)
+        .ok()
+        .map(|op| operations::probe_capabilities(&op));
+}
+
 fn validate_storage_draft(storage: &StorageDraft) -> McpResult<()> {
     if !storage.config.is_object() {
         return Err(err_with_details(
@@ -373,7 +1950,18 @@ fn validate_storage_draft(storage: &StorageDraft) -> McpResult<()> {
 
     if !matches!(
         storage.backend.as_str(),
-        "local" | "s3" | "azure_blob" | "webdav" | "gcs"
+        "local"
+            | "s3"
+            | "azure_blob"
+            | "webdav"
+            | "gcs"
+            | "ftp"
+            | "gdrive"
+            | "b2"
+            | "hdfs"
+            | "ipfs"
+            | "ipmfs"
+            | "http"
     ) {
         return Err(err_with_details(
             McpErrorCode::ERR_BACKEND_UNSUPPORTED,
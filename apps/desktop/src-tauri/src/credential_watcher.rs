@@ -0,0 +1,53 @@
+//! Background refresh of expiring OAuth credentials.
+//!
+//! Every pass refreshes access tokens that are about to expire, so browsing
+//! never stalls on an expired token, and tells the UI about storages whose
+//! refresh failed and that the user has to authorize again. Passes are
+//! skipped while jobs are paused.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use infimount_core::pause;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::state::AppState;
+
+pub const REAUTH_REQUIRED_EVENT: &str = "reauth-required";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+pub fn spawn(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("credential-watcher".to_string())
+        .spawn(move || {
+            // Storages already reported, so the UI is told once per failure.
+            let mut reported = HashSet::new();
+            loop {
+                if pause::is_paused() {
+                    std::thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+                let failed = tauri::async_runtime::block_on(
+                    app.state::<AppState>().refresh_expiring_credentials(),
+                );
+                let failing: HashSet<String> = failed
+                    .iter()
+                    .map(|reauth| reauth.storage_id.clone())
+                    .collect();
+                for reauth in failed {
+                    if reported.contains(&reauth.storage_id) {
+                        continue;
+                    }
+                    if let Err(error) = app.emit(REAUTH_REQUIRED_EVENT, &reauth) {
+                        eprintln!("failed to emit re-authorization request: {error}");
+                    }
+                }
+                reported = failing;
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+    if let Err(error) = spawned {
+        eprintln!("failed to start credential watcher: {error}");
+    }
+}
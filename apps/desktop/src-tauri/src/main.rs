@@ -4,16 +4,39 @@
 )]
 
 mod commands;
+mod credential_watcher;
+mod organize_runner;
+mod registry_watcher;
 mod state;
+mod volume_watcher;
 
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use infimount_core::share;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::TrayIconBuilder;
-use tauri::Manager;
+use tauri::{AppHandle, Emitter, Manager, RunEvent};
+
+/// Tells the UI that files were shared with the running app.
+const SHARED_FILES_EVENT: &str = "shared-files";
 
 fn main() {
     let app_state = state::AppState::new().expect("failed to initialize desktop state");
+    // Files sent to the app from the OS ("Send to", "Open with").
+    let cwd = std::env::current_dir().unwrap_or_default();
+    share::offer(share::shared_paths(std::env::args_os().skip(1), &cwd));
 
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Registered first: a second launch hands its arguments to the running
+    // instance and exits before anything else is set up.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+        let args = args.into_iter().skip(1).map(OsString::from);
+        receive_shared(app, share::shared_paths(args, Path::new(&cwd)));
+    }));
+
+    builder
         .manage(app_state)
         .setup(|app| {
             #[cfg(desktop)]
@@ -53,26 +76,114 @@ fn main() {
                 });
             }
 
+            volume_watcher::spawn(app.handle().clone());
+            registry_watcher::spawn(app.handle().clone());
+            credential_watcher::spawn(app.handle().clone());
+            organize_runner::spawn(app.handle().clone());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::list_entries,
+            commands::list_entries_paged,
+            commands::list_recursive,
+            commands::search_entries,
+            commands::search_content,
+            commands::cancel_content_search,
+            commands::ocr_available,
+            commands::find_similar_images,
+            commands::cancel_operation,
+            commands::prefetch_adjacent,
             commands::stat_entry,
             commands::read_file,
+            commands::read_file_range,
+            commands::read_file_chunks,
+            commands::tail_file,
+            commands::read_hex_chunk,
+            commands::preview_table,
+            commands::preview_sqlite,
+            commands::preview_json,
+            commands::preview_notebook,
+            commands::preview_font,
+            commands::preview_model,
+            commands::preview_email,
+            commands::extract_email_attachments,
+            commands::checksum,
+            commands::freeze_prefix,
+            commands::list_audit_snapshots,
+            commands::verify_audit_snapshot,
+            commands::delete_audit_snapshot,
+            commands::scan_source_stats,
+            commands::get_source_stats,
             commands::write_file,
             commands::create_directory,
             commands::delete_path,
+            commands::delete_paths,
+            commands::list_trash,
+            commands::restore_from_trash,
+            commands::empty_trash,
+            commands::list_incomplete_uploads,
+            commands::abort_incomplete_uploads,
+            commands::get_lifecycle_rules,
+            commands::put_lifecycle_rules,
+            commands::get_bucket_access,
+            commands::get_object_access,
+            commands::find_public_objects,
+            commands::create_symlink,
+            commands::create_hardlink,
             commands::list_storages,
             commands::add_storage,
+            commands::add_session_storage,
+            commands::add_demo_source,
+            commands::add_synthetic_source,
+            commands::add_restic_source,
             commands::remove_storage,
+            commands::list_removed_storages,
+            commands::restore_storage,
+            commands::forget_removed_storage,
             commands::update_storage,
             commands::verify_storage,
+            commands::list_credential_sets,
+            commands::save_credential_set,
+            commands::remove_credential_set,
             commands::import_storage_config,
+            commands::import_rclone_config,
+            commands::run_doctor,
+            commands::test_source,
             commands::export_storage_config,
+            commands::export_workspace,
+            commands::import_workspace,
             commands::upload_dropped_files,
+            commands::list_resumable_uploads,
+            commands::resume_interrupted_uploads,
+            commands::discard_resumable_upload,
+            commands::take_shared_files,
+            commands::intake_shared_files,
             commands::transfer_entries,
+            commands::transfer_between_sources,
+            commands::download_entries,
+            commands::export_task_report,
+            commands::retry_failed,
+            commands::list_organize_rules,
+            commands::save_organize_rules,
+            commands::preview_organize_rule,
+            commands::run_organize_rule,
+            commands::pause_all_transfers,
+            commands::resume_all_transfers,
+            commands::transfers_paused,
             commands::list_storage_schemas,
+            commands::set_locale,
+            commands::translate_message,
+            commands::discover_local_roots,
+            commands::get_suggested_sources,
             commands::get_storage_capabilities,
+            commands::get_source_capabilities,
+            commands::get_listing_diagnostics,
+            commands::get_runtime_stats,
+            commands::get_source_branding,
+            commands::authorize_gdrive,
+            commands::get_preferences,
+            commands::update_preferences,
             commands::get_mcp_settings,
             commands::list_mcp_tools,
             commands::update_mcp_settings,
@@ -82,8 +193,38 @@ fn main() {
             commands::get_mcp_client_snippets,
             commands::list_versions,
             commands::read_file_version,
+            commands::restore_version,
             commands::delete_version,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(handle_run_event);
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+fn handle_run_event(app: &AppHandle, event: RunEvent) {
+    // Finder's "Open With" and files dropped on the Dock icon arrive as an
+    // event rather than as arguments.
+    #[cfg(target_os = "macos")]
+    if let RunEvent::Opened { urls } = event {
+        let args = urls.iter().map(|url| OsString::from(url.as_str()));
+        receive_shared(app, share::shared_paths(args, Path::new("/")));
+    }
+}
+
+/// Bring the window forward and hand `paths` to the UI, which asks where to
+/// upload them.
+fn receive_shared(app: &AppHandle, paths: Vec<PathBuf>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    if paths.is_empty() {
+        return;
+    }
+    share::offer(paths);
+    if let Err(error) = app.emit(SHARED_FILES_EVENT, ()) {
+        eprintln!("failed to emit shared files: {error}");
+    }
 }
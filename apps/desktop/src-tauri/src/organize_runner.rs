@@ -0,0 +1,88 @@
+//! Background runs of the enabled organization rules.
+//!
+//! Every pass applies each enabled rule of
+//! [`infimount_core::organize`] and tells the UI about rules that moved or
+//! failed to move files. Passes are skipped while jobs are paused.
+
+use std::time::Duration;
+
+use infimount_core::transfer_report::ItemStatus;
+use infimount_core::{organize, pause};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::state::AppState;
+
+pub const ORGANIZE_RUN_EVENT: &str = "organize-run";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Outcome of one background run of a rule, with the stored task report.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrganizeRun {
+    pub rule_id: String,
+    pub task_id: Option<String>,
+    pub moved: usize,
+    pub failed: usize,
+    pub error: Option<String>,
+}
+
+pub fn spawn(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("organize-runner".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(POLL_INTERVAL);
+            if pause::is_paused() {
+                continue;
+            }
+            let rules = match organize::load_rules() {
+                Ok(rules) => rules,
+                Err(error) => {
+                    eprintln!("failed to load organization rules: {}", error);
+                    continue;
+                }
+            };
+            for rule in rules.into_iter().filter(|rule| rule.enabled) {
+                let run = tauri::async_runtime::block_on(run_rule(&app, &rule));
+                if run.moved == 0 && run.failed == 0 && run.error.is_none() {
+                    continue;
+                }
+                if let Err(error) = app.emit(ORGANIZE_RUN_EVENT, &run) {
+                    eprintln!("failed to emit organization run: {error}");
+                }
+            }
+        });
+    if let Err(error) = spawned {
+        eprintln!("failed to start organization runner: {error}");
+    }
+}
+
+async fn run_rule(app: &AppHandle, rule: &organize::OrganizeRule) -> OrganizeRun {
+    let state = app.state::<AppState>();
+    let outcome = match state.operator_for_storage_id(&rule.source_id).await {
+        Ok(op) => organize::apply(&op, rule).await,
+        Err(error) => Err(error),
+    };
+    match outcome {
+        Ok(report) => {
+            let run = OrganizeRun {
+                rule_id: rule.id.clone(),
+                task_id: Some(report.task_id.clone()),
+                moved: report.count(ItemStatus::Completed),
+                failed: report.count(ItemStatus::Failed),
+                error: None,
+            };
+            if !report.items.is_empty() {
+                state.store_task_report(report);
+            }
+            run
+        }
+        Err(error) => OrganizeRun {
+            rule_id: rule.id.clone(),
+            task_id: None,
+            moved: 0,
+            failed: 0,
+            error: Some(error.to_string()),
+        },
+    }
+}
@@ -0,0 +1,45 @@
+//! Background polling of the storage registry file.
+//!
+//! MCP servers running as their own processes (and other app instances) edit
+//! `storages.json` too. Every pass rereads the file if it changed and tells
+//! the UI to refresh its storage list.
+
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::state::AppState;
+
+pub const STORAGES_CHANGED_EVENT: &str = "storages-changed";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub fn spawn(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("registry-watcher".to_string())
+        .spawn(move || {
+            // Report a broken file once, not on every pass.
+            let mut failing = false;
+            loop {
+                match app.state::<AppState>().registry.reload_if_changed() {
+                    Ok(changed) => {
+                        failing = false;
+                        if changed {
+                            if let Err(error) = app.emit(STORAGES_CHANGED_EVENT, ()) {
+                                eprintln!("failed to emit storage changes: {error}");
+                            }
+                        }
+                    }
+                    Err(error) if !failing => {
+                        failing = true;
+                        eprintln!("failed to reload storages: {}", error.message);
+                    }
+                    Err(_) => {}
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+    if let Err(error) = spawned {
+        eprintln!("failed to start registry watcher: {error}");
+    }
+}
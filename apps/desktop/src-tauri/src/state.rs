@@ -1,24 +1,61 @@
+use infimount_core::access::{self, AccessClient};
+use infimount_core::config::{RcloneImport, RemoteNote};
+use infimount_core::demo;
+use infimount_core::gcs_api::{GcsClient, GcsConfig};
+use infimount_core::oauth::{self, OAuthClient, OAuthTokens};
+use infimount_core::operations;
+use infimount_core::pause;
+use infimount_core::read_only;
+use infimount_core::resume;
+use infimount_core::s3_api::{S3Client, S3Config};
+use infimount_core::synthetic::{self, SyntheticConfig};
+use infimount_core::throttle::{BandwidthLimits, SourceLimiters};
+use infimount_core::transfer_report::TransferReport;
+use infimount_core::tuning::{self, TransferTuning};
+use infimount_core::volumes::{self, LocalRoot, VolumeChanges, VolumeKind};
 use infimount_core::{config, CoreError, Source, SourceKind};
+use infimount_mcp::credentials::{self, CredentialStore};
 use infimount_mcp::errors::{err_with_details, McpError, McpErrorCode, McpResult};
-use infimount_mcp::opendal_adapter::build_operator;
-use infimount_mcp::registry::{StorageRecord, StorageRegistry};
+use infimount_mcp::opendal_adapter::{
+    build_operator, resolve_gcs_settings, resolve_s3_settings, uses_os_trash,
+};
+use infimount_mcp::registry::{ensure_unique_name, StorageRecord, StorageRegistry};
+use infimount_mcp::restic;
 use infimount_mcp::runtime::{
     start_http_server_from_settings, McpHttpServerHandle, HTTP_ENDPOINT_PATH,
 };
+use infimount_mcp::secrets;
 use infimount_mcp::session::SessionManager;
 use infimount_mcp::settings::{McpSettings, McpSettingsStore, McpTransport};
 use infimount_mcp::tools_fs::FsToolsContext;
 use opendal::Operator;
 use serde::Serialize;
 use serde_json::{json, Map, Value};
+use std::collections::VecDeque;
 use tokio::sync::Mutex;
 
 pub struct AppState {
     pub registry: StorageRegistry,
+    /// Named credential sets shared between storages.
+    pub credentials: CredentialStore,
     pub settings_store: McpSettingsStore,
     http_runtime: Mutex<Option<McpHttpServerHandle>>,
+    /// In-memory storages that are never written to the registry.
+    session_storages: std::sync::Mutex<Vec<StorageRecord>>,
+    /// Volumes seen by the last volume-watcher pass.
+    known_volumes: std::sync::Mutex<Vec<LocalRoot>>,
+    /// Reports of the most recent transfers, newest last.
+    task_reports: std::sync::Mutex<VecDeque<TransferReport>>,
+    /// Serializes OAuth token refreshes so concurrent operations on one
+    /// storage don't each spend its refresh token.
+    oauth_refresh: Mutex<()>,
+    /// Bandwidth budgets, shared by every operator built for a storage.
+    limiters: SourceLimiters,
 }
 
+/// How many transfer reports are kept for export.
+const MAX_TASK_REPORTS: usize = 20;
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpRuntimeStatus {
@@ -28,6 +65,36 @@ pub struct McpRuntimeStatus {
     pub endpoint_display: String,
 }
 
+/// Storages added from an rclone config, plus the remotes that were left out
+/// or still need settings.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RcloneImportResult {
+    pub storages: Vec<StorageRecord>,
+    pub incomplete: Vec<RemoteNote>,
+    pub unmapped: Vec<RemoteNote>,
+}
+
+/// A storage whose OAuth tokens could not be refreshed; the user has to
+/// authorize it again.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReauthRequired {
+    pub storage_id: String,
+    pub storage_name: String,
+    pub reason: String,
+}
+
+impl ReauthRequired {
+    fn new(storage: &StorageRecord, reason: impl Into<String>) -> Self {
+        Self {
+            storage_id: storage.id.clone(),
+            storage_name: storage.name.clone(),
+            reason: reason.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct McpClientSnippets {
     pub stdio: String,
@@ -36,13 +103,25 @@ pub struct McpClientSnippets {
 
 impl AppState {
     pub fn new() -> McpResult<Self> {
+        if let Err(error) = config::migrate_legacy_config_dir() {
+            eprintln!("failed to move ~/.infimount to the config directory: {error}");
+        }
         let registry = StorageRegistry::new(None);
         migrate_legacy_sources_if_needed(&registry)?;
+        if let Err(error) = pause::restore() {
+            eprintln!("failed to restore paused transfers: {error}");
+        }
 
         Ok(Self {
+            credentials: CredentialStore::beside(registry.path()),
             registry,
             settings_store: McpSettingsStore::new(None),
             http_runtime: Mutex::new(None),
+            session_storages: std::sync::Mutex::new(Vec::new()),
+            known_volumes: std::sync::Mutex::new(Vec::new()),
+            task_reports: std::sync::Mutex::new(VecDeque::new()),
+            oauth_refresh: Mutex::new(()),
+            limiters: SourceLimiters::default(),
         })
     }
 
@@ -57,12 +136,13 @@ impl AppState {
     }
 
     pub fn list_storages(&self) -> McpResult<Vec<StorageRecord>> {
-        self.registry.load_all()
+        let mut storages = self.registry.load_all()?;
+        storages.extend(self.session_storages().iter().cloned());
+        Ok(storages)
     }
 
     pub fn find_storage_by_id(&self, storage_id: &str) -> McpResult<StorageRecord> {
-        self.registry
-            .load_all()?
+        self.list_storages()?
             .into_iter()
             .find(|storage| storage.id == storage_id)
             .ok_or_else(|| {
@@ -74,11 +154,396 @@ impl AppState {
             })
     }
 
-    pub fn operator_for_storage_id(&self, storage_id: &str) -> Result<Operator, CoreError> {
+    pub async fn operator_for_storage_id(&self, storage_id: &str) -> Result<Operator, CoreError> {
+        let mut storage = self
+            .find_storage_by_id(storage_id)
+            .map_err(mcp_error_to_core_error)?;
+        if storage.backend == demo::DEMO_BACKEND {
+            return demo::operator().await;
+        }
+        if storage.backend == restic::RESTIC_BACKEND {
+            let setting = |key: &str| {
+                storage
+                    .config
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let store = Box::pin(self.operator_for_storage_id(&setting("sourceId"))).await?;
+            return Ok(restic::source_operator(
+                storage_id,
+                store,
+                &setting("path"),
+                &setting("password"),
+            ));
+        }
+        if storage.backend == synthetic::SYNTHETIC_BACKEND {
+            return Ok(synthetic::operator(SyntheticConfig::from_config(
+                |key| match storage.config.get(key)? {
+                    Value::String(value) => Some(value.clone()),
+                    Value::Number(value) => Some(value.to_string()),
+                    _ => None,
+                },
+            )));
+        }
+        match self.refresh_oauth_if_expiring(&storage).await {
+            Ok(Some(refreshed)) => storage = refreshed,
+            Ok(None) => {}
+            // A token inside the refresh margin still works; only give up
+            // once it has actually expired.
+            Err(reauth) if token_expiry(&storage).is_some_and(|at| at <= chrono::Utc::now()) => {
+                return Err(CoreError::Config(format!(
+                    "'{}' needs to be authorized again: {}",
+                    reauth.storage_name, reauth.reason
+                )));
+            }
+            Err(_) => {}
+        }
+        let config_value = |key: &str| match storage.config.get(key)? {
+            Value::String(value) => Some(value.clone()),
+            Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        };
+        let op = build_operator(&storage).map_err(mcp_error_to_core_error)?;
+        let limits = BandwidthLimits::from_config(config_value);
+        let op = self.limiters.apply(storage_id, limits, op);
+        let op = if storage.read_only {
+            read_only::apply(op)
+        } else {
+            op
+        };
+        let manual_tuning = TransferTuning::override_from_config(config_value);
+        tuning::set_override(&op, manual_tuning);
+        operations::set_recorded_capabilities(&op, storage.capabilities.clone());
+        // Resumable parts bypass the operator, and with it the upload limit.
+        let resumable = storage.backend == "s3" && !storage.read_only && limits.upload.is_none();
+        resume::set_multipart_client(
+            &op,
+            resumable
+                .then(|| self.s3_client_for_storage_id(storage_id, true).ok())
+                .flatten(),
+        );
+        Ok(op)
+    }
+
+    /// Signed client for the bucket of an S3 storage, for bucket APIs the
+    /// operator does not cover. Read-only storages are refused when
+    /// `for_writing`.
+    pub fn s3_client_for_storage_id(
+        &self,
+        storage_id: &str,
+        for_writing: bool,
+    ) -> Result<S3Client, CoreError> {
+        let storage = self
+            .find_storage_by_id(storage_id)
+            .map_err(mcp_error_to_core_error)?;
+        let settings = resolve_s3_settings(&storage)
+            .map_err(mcp_error_to_core_error)?
+            .ok_or_else(|| {
+                CoreError::Unsupported(format!(
+                    "bucket maintenance on {} storages",
+                    storage.backend
+                ))
+            })?;
+        if for_writing && storage.read_only {
+            return Err(CoreError::ReadOnlySource(storage.name));
+        }
+        S3Client::new(S3Config {
+            bucket: settings.bucket.unwrap_or_default(),
+            region: settings.region,
+            endpoint: settings.endpoint,
+            virtual_host_style: settings.virtual_host_style,
+            access_key_id: settings.access_key_id,
+            secret_access_key: settings.secret_access_key,
+            session_token: None,
+        })
+    }
+
+    /// Bucket API client for inspecting who can read an S3 or GCS source.
+    pub fn access_client_for_storage_id(
+        &self,
+        storage_id: &str,
+    ) -> Result<AccessClient, CoreError> {
+        let storage = self
+            .find_storage_by_id(storage_id)
+            .map_err(mcp_error_to_core_error)?;
+        if storage.backend == "s3" {
+            return self
+                .s3_client_for_storage_id(storage_id, false)
+                .map(AccessClient::S3);
+        }
+        let settings = resolve_gcs_settings(&storage)
+            .map_err(mcp_error_to_core_error)?
+            .ok_or_else(|| access::unsupported(&storage.backend))?;
+        let client = GcsClient::new(GcsConfig {
+            bucket: settings.bucket.unwrap_or_default(),
+            endpoint: settings.endpoint,
+            credential: settings.credential,
+        })?;
+        let root = settings.root.unwrap_or_default();
+        let root = match root.trim_matches('/') {
+            "" => String::new(),
+            root => format!("{root}/"),
+        };
+        Ok(AccessClient::Gcs {
+            client: Box::new(client),
+            root,
+        })
+    }
+
+    fn session_storages(&self) -> std::sync::MutexGuard<'_, Vec<StorageRecord>> {
+        self.session_storages
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn task_reports(&self) -> std::sync::MutexGuard<'_, VecDeque<TransferReport>> {
+        self.task_reports
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn store_task_report(&self, report: TransferReport) {
+        let mut reports = self.task_reports();
+        if reports.len() == MAX_TASK_REPORTS {
+            reports.pop_front();
+        }
+        reports.push_back(report);
+    }
+
+    pub fn task_report(&self, task_id: &str) -> Option<TransferReport> {
+        self.task_reports()
+            .iter()
+            .find(|report| report.task_id == task_id)
+            .cloned()
+    }
+
+    /// Add the sources of an rclone import to the registry. Remotes whose
+    /// name is already taken are reported as unmapped instead.
+    pub fn add_rclone_sources(&self, import: RcloneImport) -> McpResult<RcloneImportResult> {
+        let RcloneImport {
+            sources,
+            incomplete,
+            mut unmapped,
+        } = import;
+        let storages = self.registry.with_locked_mutation(|storages| {
+            let mut added = Vec::new();
+            for source in sources {
+                let kind = source.kind.to_string();
+                let record = legacy_source_to_storage(source);
+                if ensure_unique_name(storages, &record.name, None).is_err() {
+                    unmapped.push(RemoteNote {
+                        reason: format!("a storage named '{}' already exists", record.name),
+                        name: record.name,
+                        remote_type: kind,
+                    });
+                    continue;
+                }
+                storages.push(record.clone());
+                added.push(record);
+            }
+            Ok(added)
+        })?;
+
+        let incomplete = incomplete
+            .into_iter()
+            .filter(|note| storages.iter().any(|storage| storage.name == note.name))
+            .collect();
+        Ok(RcloneImportResult {
+            storages,
+            incomplete,
+            unmapped,
+        })
+    }
+
+    /// If `record` names a credential set (`credentialId`), replace its
+    /// secret keys with references to the set's keyring entries.
+    pub fn apply_credential_set(&self, record: &mut StorageRecord) -> McpResult<()> {
+        let Some(id) = credentials::linked_set_id(record) else {
+            return Ok(());
+        };
+        let set = self.credentials.get(id)?;
+        credentials::link(record, &set);
+        Ok(())
+    }
+
+    /// Keep `record` in memory for this session only. It is never written to
+    /// the registry file, so its secrets never reach disk or the keyring.
+    pub fn add_session_storage(&self, mut record: StorageRecord) -> McpResult<StorageRecord> {
+        let mut all = self.registry.load_all()?;
+        let mut storages = self.session_storages();
+        all.extend(storages.iter().cloned());
+        ensure_unique_name(&all, &record.name, None)?;
+
+        record.ephemeral = true;
+        storages.push(record.clone());
+        Ok(record)
+    }
+
+    /// Remove an in-memory storage. Returns `false` if `storage_id` is not one.
+    pub fn remove_session_storage(&self, storage_id: &str) -> bool {
+        let mut storages = self.session_storages();
+        let before = storages.len();
+        storages.retain(|storage| storage.id != storage_id);
+        restic::forget(storage_id);
+        storages.len() != before
+    }
+
+    /// Diff the mounted volumes against the previous pass and, when
+    /// `auto_add` is set, mirror removable drives as ephemeral Local storages.
+    pub fn sync_removable_volumes(&self, current: Vec<LocalRoot>, auto_add: bool) -> VolumeChanges {
+        let removable: Vec<LocalRoot> = current
+            .iter()
+            .filter(|volume| volume.kind == VolumeKind::Removable)
+            .cloned()
+            .collect();
+        let changes = {
+            let mut known = self
+                .known_volumes
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let changes = volumes::diff_roots(&known, &current);
+            *known = current;
+            changes
+        };
+
+        let mut storages = self.session_storages();
+        // Drop drives that were ejected (or all of them once auto-add is off).
+        storages.retain(|storage| match removable_root(storage) {
+            Some(root) => auto_add && removable.iter().any(|volume| volume.path == root),
+            None => true,
+        });
+        if !auto_add {
+            return changes;
+        }
+
+        for volume in removable {
+            if storages
+                .iter()
+                .any(|storage| removable_root(storage) == Some(volume.path.as_str()))
+            {
+                continue;
+            }
+            let mut record = StorageRecord::new(
+                volume.label.clone(),
+                "local".to_string(),
+                json!({ "root": volume.path, "removableRoot": volume.path }),
+            );
+            record.mcp_exposed = false;
+            record.ephemeral = true;
+            storages.push(record);
+        }
+        changes
+    }
+
+    pub fn storage_uses_os_trash(&self, storage_id: &str) -> Result<bool, CoreError> {
         let storage = self
             .find_storage_by_id(storage_id)
             .map_err(mcp_error_to_core_error)?;
-        build_operator(&storage).map_err(mcp_error_to_core_error)
+        Ok(uses_os_trash(&storage))
+    }
+
+    /// Store freshly issued OAuth tokens in a storage's config so the next
+    /// operator built for it picks them up.
+    pub fn persist_oauth_tokens(&self, storage_id: &str, tokens: &OAuthTokens) -> McpResult<()> {
+        if let Some(storage) = self
+            .session_storages()
+            .iter_mut()
+            .find(|storage| storage.id == storage_id)
+        {
+            return apply_oauth_tokens(storage, tokens);
+        }
+
+        self.registry.with_locked_mutation(|storages| {
+            let storage = storages
+                .iter_mut()
+                .find(|storage| storage.id == storage_id)
+                .ok_or_else(|| {
+                    err_with_details(
+                        McpErrorCode::ERR_STORAGE_NOT_FOUND,
+                        format!("storage '{storage_id}' not found"),
+                        json!({ "storage_id": storage_id }),
+                    )
+                })?;
+            apply_oauth_tokens(storage, tokens)
+        })
+    }
+
+    /// Refresh `storage`'s OAuth access token if it expires within
+    /// [`oauth::REFRESH_MARGIN`]. Returns the updated record, or `None` when
+    /// no refresh was needed.
+    pub async fn refresh_oauth_if_expiring(
+        &self,
+        storage: &StorageRecord,
+    ) -> Result<Option<StorageRecord>, ReauthRequired> {
+        let Some(provider) = oauth_provider(storage) else {
+            return Ok(None);
+        };
+        if !oauth::needs_refresh(token_expiry(storage)) {
+            return Ok(None);
+        }
+
+        let _guard = self.oauth_refresh.lock().await;
+        // Another operation may have refreshed it while we waited.
+        let storage = self
+            .find_storage_by_id(&storage.id)
+            .map_err(|e| ReauthRequired::new(storage, e.message))?;
+        if !oauth::needs_refresh(token_expiry(&storage)) {
+            return Ok(Some(storage));
+        }
+
+        let resolved = secrets::expand_env(&storage)
+            .and_then(|expanded| secrets::resolve(&expanded))
+            .map_err(|e| ReauthRequired::new(&storage, e.message))?;
+        let value = |key: &str| {
+            resolved
+                .config
+                .get(key)
+                .and_then(Value::as_str)
+                .filter(|value| !value.trim().is_empty())
+                .map(str::to_string)
+        };
+        let (Some(refresh_token), Some(client_id), Some(client_secret)) = (
+            value("refreshToken"),
+            value("clientId"),
+            value("clientSecret"),
+        ) else {
+            return Err(ReauthRequired::new(
+                &storage,
+                "no refresh token or OAuth client is saved",
+            ));
+        };
+
+        let tokens = oauth::refresh_access_token(
+            &provider,
+            &OAuthClient {
+                client_id,
+                client_secret,
+            },
+            &refresh_token,
+        )
+        .await
+        .map_err(|e| ReauthRequired::new(&storage, e.to_string()))?;
+        self.persist_oauth_tokens(&storage.id, &tokens)
+            .and_then(|()| self.find_storage_by_id(&storage.id))
+            .map(Some)
+            .map_err(|e| ReauthRequired::new(&storage, e.message))
+    }
+
+    /// Refresh every storage whose OAuth token is about to expire. Returns
+    /// the storages that need interactive re-authorization.
+    pub async fn refresh_expiring_credentials(&self) -> Vec<ReauthRequired> {
+        let Ok(storages) = self.list_storages() else {
+            return Vec::new();
+        };
+        let mut failed = Vec::new();
+        for storage in storages.iter().filter(|storage| storage.enabled) {
+            if let Err(reauth) = self.refresh_oauth_if_expiring(storage).await {
+                failed.push(reauth);
+            }
+        }
+        failed
     }
 
     pub async fn apply_mcp_settings(&self, settings: McpSettings) -> McpResult<McpRuntimeStatus> {
@@ -243,6 +708,62 @@ fn migrate_legacy_sources_if_needed(registry: &StorageRegistry) -> McpResult<()>
     registry.save_all_atomic(&storages)
 }
 
+fn apply_oauth_tokens(storage: &mut StorageRecord, tokens: &OAuthTokens) -> McpResult<()> {
+    let Some(config) = storage.config.as_object_mut() else {
+        return Err(err_with_details(
+            McpErrorCode::ERR_INTERNAL,
+            "storage config must be a JSON object",
+            json!({ "storage_id": storage.id }),
+        ));
+    };
+
+    config.insert(
+        "accessToken".to_string(),
+        Value::String(tokens.access_token.clone()),
+    );
+    if let Some(refresh_token) = &tokens.refresh_token {
+        config.insert(
+            "refreshToken".to_string(),
+            Value::String(refresh_token.clone()),
+        );
+    }
+    if let Some(expires_at) = tokens.expires_at {
+        config.insert(
+            oauth::EXPIRES_AT_KEY.to_string(),
+            Value::String(expires_at.to_rfc3339()),
+        );
+    }
+    storage.updated_at = chrono::Utc::now().to_rfc3339();
+    Ok(())
+}
+
+/// OAuth provider a storage authenticates with, if it uses OAuth at all.
+fn oauth_provider(storage: &StorageRecord) -> Option<oauth::OAuthProvider> {
+    match storage.backend.as_str() {
+        "gdrive" => Some(oauth::GOOGLE_DRIVE),
+        _ => None,
+    }
+}
+
+fn token_expiry(storage: &StorageRecord) -> Option<chrono::DateTime<chrono::Utc>> {
+    let expires_at = storage.config.get(oauth::EXPIRES_AT_KEY)?.as_str()?;
+    if secrets::is_reference(expires_at) {
+        // Registries saved before the expiry stopped counting as a secret
+        // keep it in the keyring until the next refresh rewrites it.
+        let resolved = secrets::resolve(storage).ok()?;
+        return oauth::parse_expiry(resolved.config.get(oauth::EXPIRES_AT_KEY)?.as_str()?);
+    }
+    oauth::parse_expiry(expires_at)
+}
+
+/// Mount path of the removable drive an ephemeral storage mirrors, if any.
+fn removable_root(storage: &StorageRecord) -> Option<&str> {
+    if !storage.ephemeral {
+        return None;
+    }
+    storage.config.get("removableRoot").and_then(Value::as_str)
+}
+
 fn legacy_source_to_storage(source: Source) -> StorageRecord {
     let backend = match source.kind {
         SourceKind::Local => "local",
@@ -250,6 +771,13 @@ fn legacy_source_to_storage(source: Source) -> StorageRecord {
         SourceKind::WebDav => "webdav",
         SourceKind::AzureBlob => "azure_blob",
         SourceKind::Gcs => "gcs",
+        SourceKind::Ftp => "ftp",
+        SourceKind::Gdrive => "gdrive",
+        SourceKind::B2 => "b2",
+        SourceKind::Hdfs => "hdfs",
+        SourceKind::Ipfs => "ipfs",
+        SourceKind::Ipmfs => "ipmfs",
+        SourceKind::Http => "http",
     }
     .to_string();
 
@@ -264,7 +792,9 @@ fn legacy_source_to_storage(source: Source) -> StorageRecord {
             .or_insert(Value::String(source.root));
     }
 
-    StorageRecord::new(source.name, backend, Value::Object(config_map))
+    let mut record = StorageRecord::new(source.name, backend, Value::Object(config_map));
+    record.read_only = source.read_only;
+    record
 }
 
 pub fn mcp_error_to_core_error(err: McpError) -> CoreError {
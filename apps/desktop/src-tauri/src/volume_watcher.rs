@@ -0,0 +1,38 @@
+//! Background polling of mounted volumes.
+//!
+//! Every pass diffs the mounted volumes against the previous one, mirrors
+//! removable drives as ephemeral Local storages when the user opted in, and
+//! notifies the UI so it can refresh its storage list.
+
+use std::time::Duration;
+
+use infimount_core::{config, volumes};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::state::AppState;
+
+pub const VOLUMES_CHANGED_EVENT: &str = "volumes-changed";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+pub fn spawn(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("volume-watcher".to_string())
+        .spawn(move || loop {
+            let auto_add = config::load_preferences()
+                .map(|preferences| preferences.auto_add_removable_drives)
+                .unwrap_or(false);
+            let changes = app
+                .state::<AppState>()
+                .sync_removable_volumes(volumes::discover_local_roots(), auto_add);
+            if !changes.is_empty() {
+                if let Err(error) = app.emit(VOLUMES_CHANGED_EVENT, changes) {
+                    eprintln!("failed to emit volume changes: {error}");
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        });
+    if let Err(error) = spawned {
+        eprintln!("failed to start volume watcher: {error}");
+    }
+}
@@ -48,6 +48,8 @@ interface AddStorageDialogProps {
   open: boolean;
   onOpenChange: (open: boolean) => void;
   onAdd?: (config: StorageDraft) => Promise<void>;
+  /** Connect for this session only, without saving the storage or its secrets. */
+  onConnect?: (config: StorageDraft) => Promise<void>;
   onUpdate?: (id: string, config: StorageDraft) => Promise<void>;
   onVerify?: (config: StorageDraft) => Promise<StorageValidationResult>;
   initialStorage?: StorageConfig;
@@ -56,10 +58,29 @@ interface AddStorageDialogProps {
 
 const DEFAULT_TYPE: StorageType = "local-fs";
 
+/** Bandwidth limits, stored in the config in bytes per second. */
+const LIMIT_FIELDS: StorageFieldSchema[] = [
+  {
+    name: "uploadLimit",
+    label: "Upload limit (KB/s)",
+    input_type: "number",
+    required: false,
+    secret: false,
+  },
+  {
+    name: "downloadLimit",
+    label: "Download limit (KB/s)",
+    input_type: "number",
+    required: false,
+    secret: false,
+  },
+];
+
 export function AddStorageDialog({
   open,
   onOpenChange,
   onAdd,
+  onConnect,
   onUpdate,
   onVerify,
   initialStorage,
@@ -74,6 +95,7 @@ export function AddStorageDialog({
   const [enabled, setEnabled] = useState(true);
   const [mcpExposed, setMcpExposed] = useState(true);
   const [readOnly, setReadOnly] = useState(false);
+  const [limits, setLimits] = useState<Record<string, string>>({});
   const [revealSecrets, setRevealSecrets] = useState(false);
   const [formError, setFormError] = useState<string | null>(null);
   const [isSubmitting, setIsSubmitting] = useState(false);
@@ -124,8 +146,11 @@ export function AddStorageDialog({
       const schema = schemas.find((item) => item.id === nextType);
       const knownFieldNames = new Set(schema?.fields.map((field) => field.name) ?? []);
       const nextFieldValues = buildFieldValues(schema, initialStorage.config);
+      const limitFieldNames = new Set(LIMIT_FIELDS.map((field) => field.name));
       const preservedConfig = Object.fromEntries(
-        Object.entries(initialStorage.config).filter(([key]) => !knownFieldNames.has(key)),
+        Object.entries(initialStorage.config).filter(
+          ([key]) => !knownFieldNames.has(key) && !limitFieldNames.has(key),
+        ),
       );
 
       setName(initialStorage.name);
@@ -135,6 +160,7 @@ export function AddStorageDialog({
       setEnabled(initialStorage.enabled);
       setMcpExposed(initialStorage.mcpExposed);
       setReadOnly(initialStorage.readOnly);
+      setLimits(readLimits(initialStorage.config));
       setRevealSecrets(
         !(schema?.fields.some((field) => field.secret && nextFieldValues[field.name]) ?? false),
       );
@@ -151,6 +177,7 @@ export function AddStorageDialog({
     setEnabled(true);
     setMcpExposed(true);
     setReadOnly(false);
+    setLimits({});
     setRevealSecrets(true);
   }, [initialStorage, open, schemas]);
 
@@ -207,6 +234,23 @@ export function AddStorageDialog({
       config[field.name] = rawValue;
     }
 
+    for (const field of LIMIT_FIELDS) {
+      const rawValue = (limits[field.name] ?? "").trim();
+      if (!rawValue) continue;
+      const kilobytes = Number(rawValue);
+      if (!Number.isFinite(kilobytes) || kilobytes <= 0) {
+        setFormError(`${field.label} must be a positive number.`);
+        return null;
+      }
+      config[field.name] = Math.max(1, Math.round(kilobytes * 1024));
+    }
+
+    if (currentSchema.preset) {
+      config.provider = currentSchema.preset;
+    } else if (currentSchema.kind === "s3") {
+      delete config.provider;
+    }
+
     setFormError(null);
     return {
       name: trimmedName,
@@ -236,6 +280,20 @@ export function AddStorageDialog({
     }
   };
 
+  const handleConnect = async () => {
+    if (!onConnect) return;
+    const draft = buildDraft();
+    if (!draft) return;
+
+    setIsSubmitting(true);
+    try {
+      await onConnect(draft);
+      onOpenChange(false);
+    } finally {
+      setIsSubmitting(false);
+    }
+  };
+
   const handleVerify = async () => {
     if (!onVerify) return;
     const draft = buildDraft();
@@ -358,6 +416,24 @@ export function AddStorageDialog({
             />
           </div>
 
+          <div className="grid gap-4 rounded-xl border border-border/70 bg-card/40 p-4 md:grid-cols-2">
+            {LIMIT_FIELDS.map((field) => (
+              <StorageFieldInput
+                key={field.name}
+                field={field}
+                value={limits[field.name] ?? ""}
+                revealSecrets
+                onChange={(value) => {
+                  setLimits((current) => ({ ...current, [field.name]: value }));
+                  setFormError(null);
+                }}
+              />
+            ))}
+            <p className="text-[11px] text-muted-foreground md:col-span-2">
+              Caps transfer speed to and from this storage. Leave empty for no limit.
+            </p>
+          </div>
+
           <div className="space-y-3">
             <div className="flex items-center justify-between gap-3">
               <div>
@@ -469,6 +545,18 @@ export function AddStorageDialog({
             >
               Cancel
             </Button>
+            {!isEditing && onConnect ? (
+              <Button
+                type="button"
+                variant="outline"
+                className="border border-border hover:bg-sidebar-accent/30 hover:text-foreground"
+                onClick={handleConnect}
+                disabled={isSubmitting || !name.trim()}
+                title="Use this storage until Infimount quits without saving it or its credentials"
+              >
+                Connect without saving
+              </Button>
+            ) : null}
             <Button
               type="submit"
               className="bg-primary text-primary-foreground hover:bg-primary/90"
@@ -552,6 +640,10 @@ function ToggleRow({
 function mapStorageTypeToBackend(type: StorageType): StorageDraft["backend"] {
   switch (type) {
     case "aws-s3":
+    case "minio":
+    case "cloudflare-r2":
+    case "wasabi":
+    case "digitalocean-spaces":
       return "s3";
     case "azure-blob":
       return "azure_blob";
@@ -559,6 +651,20 @@ function mapStorageTypeToBackend(type: StorageType): StorageDraft["backend"] {
       return "webdav";
     case "gcs":
       return "gcs";
+    case "ftp":
+      return "ftp";
+    case "google-drive":
+      return "gdrive";
+    case "backblaze-b2":
+      return "b2";
+    case "hdfs":
+      return "hdfs";
+    case "ipfs":
+      return "ipfs";
+    case "ipmfs":
+      return "ipmfs";
+    case "http":
+      return "http";
     case "local-fs":
     default:
       return "local";
@@ -575,6 +681,18 @@ function buildFieldValues(
   );
 }
 
+/** Limits from the config (bytes per second) as KB/s form values. */
+function readLimits(config: Record<string, unknown>): Record<string, string> {
+  return Object.fromEntries(
+    LIMIT_FIELDS.flatMap((field) => {
+      const bytes = Number(config[field.name]);
+      return Number.isFinite(bytes) && bytes > 0
+        ? [[field.name, String(Math.round((bytes / 1024) * 100) / 100)]]
+        : [];
+    }),
+  );
+}
+
 function stringifyFieldValue(value: unknown): string {
   if (typeof value === "string") return value;
   if (typeof value === "number" || typeof value === "boolean") return String(value);
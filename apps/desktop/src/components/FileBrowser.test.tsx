@@ -8,11 +8,14 @@ import { AppZoomProvider } from "@/hooks/use-app-zoom";
 // Mock the api module
 vi.mock("@/lib/api", () => ({
     listEntries: vi.fn(),
+    prefetchAdjacent: vi.fn(),
     readFile: vi.fn(),
     writeFile: vi.fn(),
     createDirectory: vi.fn(),
     deletePath: vi.fn(),
     transferEntries: vi.fn(),
+    getSourceCapabilities: vi.fn().mockResolvedValue({ write: true }),
+    onTransferProgress: vi.fn().mockResolvedValue(() => undefined),
     TauriApiError: class extends Error {
         code: string;
         constructor(message: string, code: string) {
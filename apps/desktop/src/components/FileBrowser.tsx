@@ -12,6 +12,7 @@ import {
 } from "lucide-react";
 import { Input } from "@/components/ui/input";
 import { Button } from "@/components/ui/button";
+import { Progress } from "@/components/ui/progress";
 import {
   ResizableHandle,
   ResizablePanel,
@@ -22,16 +23,24 @@ import { FileGrid } from "./FileGrid";
 import { FileTable } from "./FileTable";
 import { UploadZone, type UploadFileLike, type UploadZoneRef } from "./UploadZone";
 import { FilePreviewPanel } from "./FilePreviewPanel";
-import { FileItem } from "@/types/storage";
+import { FileItem, type StorageBackend } from "@/types/storage";
 import {
   Entry,
   listEntries,
+  prefetchAdjacent,
   readFile,
   writeFile,
   createDirectory,
   deletePath,
+  deletePaths,
+  findPublicObjects,
   transferEntries,
+  retryFailed,
+  getSourceCapabilities,
+  onTransferProgress,
+  cancelOperation,
   TauriApiError,
+  type TransferProgress,
 } from "@/lib/api";
 import {
   AlertDialog,
@@ -44,6 +53,7 @@ import {
   AlertDialogTitle,
 } from "@/components/ui/alert-dialog";
 import { toast } from "@/hooks/use-toast";
+import { ToastAction } from "@/components/ui/toast";
 import {
   DropdownMenu,
   DropdownMenuContent,
@@ -189,6 +199,8 @@ async function collectFilesFromDataTransfer(
 interface FileBrowserProps {
   sourceId: string;
   storageName: string;
+  /** Listings of S3 and GCS sources flag publicly readable files. */
+  backend?: StorageBackend;
   refreshTick?: number;
   onPreviewVisibilityChange?: (visible: boolean) => void;
   onToggleSidebar?: () => void;
@@ -203,6 +215,7 @@ interface LoadError {
 export function FileBrowser({
   sourceId,
   storageName,
+  backend,
   refreshTick = 0,
   onPreviewVisibilityChange,
   onToggleSidebar,
@@ -226,6 +239,14 @@ export function FileBrowser({
   const [sortDirection, setSortDirection] = useState<SortDirection>("asc");
   const [previewFile, setPreviewFile] = useState<FileItem | null>(null);
   const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
+  // Hide write actions on read-only sources; assume writable until known.
+  const [canWrite, setCanWrite] = useState(true);
+  const [canListVersions, setCanListVersions] = useState(false);
+  // Latest transfer progress sentence, announced to screen readers.
+  const [transferStatus, setTransferStatus] = useState("");
+  // Running upload, download or copy shown as a progress bar; cleared once
+  // it finishes.
+  const [activeTransfer, setActiveTransfer] = useState<TransferProgress | null>(null);
   const [pasteConflict, setPasteConflict] = useState<{
     fromSourceId: string;
     toSourceId: string;
@@ -234,6 +255,7 @@ export function FileBrowser({
     operation: "copy" | "move";
   } | null>(null);
   const [editTargetId, setEditTargetId] = useState<string | null>(null);
+  const [historyTargetId, setHistoryTargetId] = useState<string | null>(null);
   const { theme: iconTheme, setTheme: setIconTheme } = useIconTheme();
   const { clipboard, setClipboard, clearClipboard } = useFileClipboard();
   const [isEditingPath, setIsEditingPath] = useState(false);
@@ -264,6 +286,11 @@ export function FileBrowser({
           title: "Access denied",
           detail: "You don't have permission to view this location.",
         };
+      case "TOO_LARGE":
+        return {
+          title: "Folder too large",
+          detail: shortMessage || "This folder has too many entries to list at once. Use search instead.",
+        };
       case "CONFIG_ERROR":
         return {
           title: "Can't connect to this storage",
@@ -310,8 +337,30 @@ export function FileBrowser({
     modified: entry.modified_at ? new Date(entry.modified_at) : null,
     owner: undefined,
     extension: !entry.is_dir ? entry.name.split(".").pop() : undefined,
+    onlineOnly: entry.online_only,
+    contentType: entry.content_type,
+    etag: entry.etag,
+    storageClass: entry.storage_class,
+    userMetadata: entry.user_metadata,
+    gitStatus: entry.git_status,
   });
 
+  const flagPublicObjects = (entries: Entry[]) => {
+    if (backend !== "s3" && backend !== "gcs") return;
+    const files = entries.filter((entry) => !entry.is_dir).map((entry) => entry.path);
+    if (files.length === 0) return;
+    findPublicObjects(sourceId, files)
+      .then((publicPaths) => {
+        if (publicPaths.length === 0) return;
+        const flagged = new Set(publicPaths);
+        setAllFiles((current) =>
+          current.map((file) => (flagged.has(file.id) ? { ...file, publiclyReadable: true } : file)),
+        );
+      })
+      // Flags are best-effort; a bucket whose ACLs cannot be read lists as usual.
+      .catch(() => undefined);
+  };
+
   const loadFiles = async (path: string) => {
     setLoading(true);
     setError(null);
@@ -322,6 +371,8 @@ export function FileBrowser({
       );
       setAllFiles(filtered.map(mapEntryToFileItem));
       setSelectedFiles(new Set());
+      prefetchAdjacent(sourceId, path);
+      flagPublicObjects(filtered);
     } catch (err) {
       if (err instanceof TauriApiError) {
         setError(describeLoadError(err));
@@ -349,6 +400,34 @@ export function FileBrowser({
     setNewEntryName("");
   }, [sourceId]);
 
+  useEffect(() => {
+    const unlisten = onTransferProgress((progress) => {
+      setTransferStatus(progress.description);
+      setActiveTransfer(progress.stage === "finished" ? null : progress);
+    });
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  useEffect(() => {
+    let cancelled = false;
+    setCanWrite(true);
+    setCanListVersions(false);
+    Promise.resolve()
+      .then(() => getSourceCapabilities(sourceId))
+      .then((capabilities) => {
+        if (!cancelled && capabilities) {
+          setCanWrite(capabilities.write);
+          setCanListVersions(capabilities.list_with_versions);
+        }
+      })
+      .catch(() => undefined);
+    return () => {
+      cancelled = true;
+    };
+  }, [sourceId]);
+
   useEffect(() => {
     void loadFiles(currentPath);
     // eslint-disable-next-line react-hooks/exhaustive-deps
@@ -488,6 +567,23 @@ export function FileBrowser({
     });
   };
 
+  const retryTransfer = async (taskId: string) => {
+    try {
+      const report = await retryFailed(taskId);
+      await loadFiles(currentPath);
+      toast({
+        title: "Retry complete",
+        description: `${report.items.length} item${report.items.length === 1 ? "" : "s"} transferred.`,
+      });
+    } catch (err) {
+      toast({
+        title: "Retry failed",
+        description: err instanceof Error ? err.message : String(err),
+        variant: "destructive",
+      });
+    }
+  };
+
   const pasteInto = async (targetDir?: string) => {
     if (!clipboard || clipboard.paths.length === 0) {
       return;
@@ -525,10 +621,16 @@ export function FileBrowser({
           });
           return;
         }
+        const taskId = err.taskId;
         toast({
           title: clipboard.operation === "copy" ? "Copy failed" : "Move failed",
           description: err.message,
           variant: "destructive",
+          action: taskId ? (
+            <ToastAction altText="Retry failed items" onClick={() => void retryTransfer(taskId)}>
+              Retry
+            </ToastAction>
+          ) : undefined,
         });
       } else {
         toast({
@@ -626,7 +728,7 @@ export function FileBrowser({
           description: `"${name}" was created.`,
         });
       } else {
-        await writeFile(sourceId, path, new Uint8Array());
+        await writeFile(sourceId, path, new Uint8Array(), { if_not_exists: true });
         toast({
           title: "File created",
           description: `"${name}" was created.`,
@@ -710,7 +812,8 @@ export function FileBrowser({
   const downloadOne = async (file: FileItem) => {
     if (file.type !== "file") return;
     try {
-      const data = await readFile(sourceId, file.id);
+      // Clicking download is the confirmation the preview threshold asks for.
+      const data = await readFile(sourceId, file.id, { confirmed: true });
       const arrayBuffer = data.buffer.slice(
         data.byteOffset,
         data.byteOffset + data.byteLength,
@@ -753,15 +856,32 @@ export function FileBrowser({
 
   const handleBulkDelete = async () => {
     const toDelete = filteredFiles.filter((f) => selectedFiles.has(f.id));
-    for (const file of toDelete) {
-      await deleteOne(file);
+    try {
+      const report = await deletePaths(
+        sourceId,
+        toDelete.map((file) => file.id),
+      );
+      await loadFiles(currentPath);
+      if (report.failed.length > 0) {
+        toast({
+          title: `${report.failed.length} item(s) could not be deleted`,
+          description: `${report.deleted.length} item(s) removed. ${report.failed[0].path}: ${report.failed[0].error}`,
+          variant: "destructive",
+        });
+      } else {
+        toast({
+          title: "Items deleted",
+          description: `${report.deleted.length} item(s) removed.`,
+          variant: "default",
+        });
+      }
+    } catch (error: unknown) {
+      toast({
+        title: "Delete failed",
+        description: error instanceof Error ? error.message : String(error),
+        variant: "destructive",
+      });
     }
-    await loadFiles(currentPath);
-    toast({
-      title: "Items deleted",
-      description: `${toDelete.length} item(s) removed.`,
-      variant: "default",
-    });
   };
 
   const handleOpenFile = (file: FileItem) => {
@@ -769,12 +889,21 @@ export function FileBrowser({
       handleNavigate(file.id);
     } else {
       setEditTargetId(null);
+      setHistoryTargetId(null);
       setPreviewFile(file);
     }
   };
 
+  const handleShowHistory = (file: FileItem) => {
+    if (file.type === "folder") return;
+    setEditTargetId(null);
+    setPreviewFile(file);
+    setHistoryTargetId(file.id);
+  };
+
   const handleEditFile = (file: FileItem) => {
     if (file.type === "folder") return;
+    setHistoryTargetId(null);
     setPreviewFile(file);
     setEditTargetId(file.id);
   };
@@ -883,6 +1012,36 @@ export function FileBrowser({
 
   return (
     <>
+      <div className="sr-only" role="status" aria-live="polite">
+        {transferStatus}
+      </div>
+      {activeTransfer && (
+        <div className="fixed bottom-4 right-4 z-50 w-72 rounded-md border bg-background p-3 shadow-lg">
+          <div className="mb-2 flex items-center gap-2">
+            <p
+              className="min-w-0 flex-1 truncate text-xs text-muted-foreground"
+              title={activeTransfer.description}
+            >
+              {activeTransfer.description}
+            </p>
+            <Button
+              type="button"
+              variant="ghost"
+              size="sm"
+              className="h-6 px-2 text-xs"
+              onClick={() => void cancelOperation(activeTransfer.task_id).catch(() => undefined)}
+            >
+              Cancel
+            </Button>
+          </div>
+          <Progress className="h-2" value={activeTransfer.percent ?? 0} />
+          {activeTransfer.items_total !== null && (
+            <p className="mt-1 text-right text-[11px] text-muted-foreground">
+              {activeTransfer.items_done} / {activeTransfer.items_total}
+            </p>
+          )}
+        </div>
+      )}
       <div
         className="relative flex h-full bg-background"
         onDragOver={(event: React.DragEvent<HTMLDivElement>) => {
@@ -980,18 +1139,20 @@ export function FileBrowser({
                   />
                 </div>
 
-                <label htmlFor="file-upload">
-                  <Button
-                    type="button"
-                    size="icon"
-                    variant="ghost"
-                    className="h-8 w-8 text-foreground/70 hover:bg-black/5 dark:hover:bg-white/5"
-                    title="Upload files"
-                    aria-label="Upload files"
-                  >
-                    <Upload className="h-4 w-4" />
-                  </Button>
-                </label>
+                {canWrite && (
+                  <label htmlFor="file-upload">
+                    <Button
+                      type="button"
+                      size="icon"
+                      variant="ghost"
+                      className="h-8 w-8 text-foreground/70 hover:bg-black/5 dark:hover:bg-white/5"
+                      title="Upload files"
+                      aria-label="Upload files"
+                    >
+                      <Upload className="h-4 w-4" />
+                    </Button>
+                  </label>
+                )}
                 <DropdownMenu>
                   <DropdownMenuTrigger asChild>
                     <Button
@@ -1127,6 +1288,7 @@ export function FileBrowser({
                             onSelectFiles={handleSelectFiles}
                             onOpenFile={handleOpenFile}
                             onEditFile={handleEditFile}
+                            onShowHistory={canListVersions ? handleShowHistory : undefined}
                             onDownloadFile={handleDownloadFile}
                             onDeleteFile={(file) => void deleteOne(file)}
                             onCutSelected={() => setClipboardFromSelection("move")}
@@ -1147,6 +1309,7 @@ export function FileBrowser({
                             onSelectFiles={handleSelectFiles}
                             onOpenFile={handleOpenFile}
                             onEditFile={handleEditFile}
+                            onShowHistory={canListVersions ? handleShowHistory : undefined}
                             onDownloadFile={handleDownloadFile}
                             onDeleteFile={(file) => void deleteOne(file)}
                             sortField={sortField}
@@ -1167,6 +1330,7 @@ export function FileBrowser({
                   </ContextMenuTrigger>
                   <ContextMenuContent className="border border-border bg-[hsl(var(--popover))] text-[hsl(var(--popover-foreground))] shadow-md">
                     <ContextMenuItem
+                      disabled={!canWrite}
                       onClick={() => {
                         openCreateTargetDialog("folder");
                       }}
@@ -1175,6 +1339,7 @@ export function FileBrowser({
                       <ContextMenuShortcut>⌘⇧N</ContextMenuShortcut>
                     </ContextMenuItem>
                     <ContextMenuItem
+                      disabled={!canWrite}
                       onClick={() => {
                         openCreateTargetDialog("file");
                       }}
@@ -1183,7 +1348,7 @@ export function FileBrowser({
                     </ContextMenuItem>
                     <ContextMenuSeparator />
                     <ContextMenuItem
-                      disabled={!clipboard}
+                      disabled={!clipboard || !canWrite}
                       onClick={() => {
                         void pasteInto();
                       }}
@@ -1279,7 +1444,9 @@ export function FileBrowser({
                         onClose={() => {
                           setPreviewFile(null);
                           setEditTargetId(null);
+                          setHistoryTargetId(null);
                         }}
+                        initialTab={historyTargetId === previewFile.id ? "versions" : "preview"}
                         startInEditMode={editTargetId === previewFile.id}
                         onEditModeChange={(editing) => {
                           setEditTargetId(editing ? previewFile.id : null);
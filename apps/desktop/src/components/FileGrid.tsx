@@ -1,5 +1,15 @@
 import { FileItem } from "@/types/storage";
-import { Eye, Download, Trash2, Edit3, Scissors, Copy, ClipboardPaste } from "lucide-react";
+import {
+  Eye,
+  Download,
+  Trash2,
+  Edit3,
+  Scissors,
+  Copy,
+  ClipboardPaste,
+  Globe,
+  History,
+} from "lucide-react";
 import { FileTypeIcon } from "./FileIcon";
 import { Card } from "@/components/ui/card";
 import {
@@ -53,6 +63,8 @@ interface FileGridProps {
   onSelectFiles?: (fileIds: string[]) => void;
   onOpenFile?: (file: FileItem) => void;
   onEditFile?: (file: FileItem) => void;
+  /** Shown as "History" on sources that keep object versions. */
+  onShowHistory?: (file: FileItem) => void;
   onDownloadFile?: (file: FileItem) => void;
   onDeleteFile?: (file: FileItem) => void;
   onCutSelected?: () => void;
@@ -123,6 +135,7 @@ export function FileGrid({
   onSelectFiles,
   onOpenFile,
   onEditFile,
+  onShowHistory,
   onDownloadFile,
   onDeleteFile,
   onCutSelected,
@@ -370,8 +383,16 @@ export function FileGrid({
                         }}
                       >
                         <div className="flex flex-col items-center gap-1 p-2 pb-1">
-                          <div className="flex h-9 w-9 items-center justify-center">
+                          <div className="relative flex h-9 w-9 items-center justify-center">
                             <FileTypeIcon item={file} className="h-8 w-8" />
+                            {file.publiclyReadable && (
+                              <span
+                                className="absolute -right-1 -top-1 rounded-full bg-background"
+                                title="Public: anyone with the link can read this object"
+                              >
+                                <Globe className="h-3.5 w-3.5 text-rose-500" />
+                              </span>
+                            )}
                           </div>
 
                           <div className="w-full text-center">
@@ -439,6 +460,12 @@ export function FileGrid({
                               Edit
                             </ContextMenuItem>
                           )}
+                          {onShowHistory && (
+                            <ContextMenuItem onClick={() => onShowHistory(file)}>
+                              <History className="mr-2 h-4 w-4" />
+                              History
+                            </ContextMenuItem>
+                          )}
                         </>
                       )}
                       <ContextMenuItem
@@ -6,11 +6,11 @@ import {
   DialogTitle,
   DialogDescription,
 } from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
 import type { FileItem } from "@/types/storage";
-import { readFile } from "@/lib/api";
+import { readFile, TauriApiError } from "@/lib/api";
 import infinityLoader from "@/assets/loading-infinity.apng";
 
-const MAX_PREVIEW_BYTES = 20 * 1024 * 1024;
 
 const TEXT_EXTENSIONS = new Set([
   "txt",
@@ -164,9 +164,14 @@ export const FilePreviewDialog: React.FC<FilePreviewDialogProps> = ({
   const [loading, setLoading] = useState(false);
 
   const [prevFileId, setPrevFileId] = useState<string | null>(null);
+  // Set once the user agreed to preview a file over the size threshold.
+  const [confirmed, setConfirmed] = useState(false);
+  const [needsConfirmation, setNeedsConfirmation] = useState(false);
 
   if (file?.id !== prevFileId) {
     setPrevFileId(file?.id ?? null);
+    setConfirmed(false);
+    setNeedsConfirmation(false);
     setContent("");
     setError(null);
     setMode(null);
@@ -177,10 +182,7 @@ export const FilePreviewDialog: React.FC<FilePreviewDialogProps> = ({
       const ext = (file.extension || file.name.split(".").pop() || "").toLowerCase();
       const isKnownBinary = BINARY_EXTENSIONS.has(ext);
 
-      if (file.size && file.size > MAX_PREVIEW_BYTES) {
-        setMode("unsupported");
-        setError(`File is too large to preview (${formatFileSize(file.size)}).`);
-      } else if (isKnownBinary) {
+      if (isKnownBinary) {
         setMode("unsupported");
         setError("Preview not available for this file type.");
       }
@@ -205,7 +207,7 @@ export const FilePreviewDialog: React.FC<FilePreviewDialogProps> = ({
     let cancelled = false;
 
     // setLoading(true); // Moved to render phase reset
-    readFile(sourceId, file.id)
+    readFile(sourceId, file.id, { confirmed })
       .then((data) => {
         if (cancelled) return;
 
@@ -240,6 +242,7 @@ export const FilePreviewDialog: React.FC<FilePreviewDialogProps> = ({
       })
       .catch((e: any) => {
         if (cancelled) return;
+        setNeedsConfirmation(e instanceof TauriApiError && e.code === "CONFIRMATION_REQUIRED");
         setMode("unsupported");
         setError(e?.message || String(e));
       })
@@ -251,7 +254,15 @@ export const FilePreviewDialog: React.FC<FilePreviewDialogProps> = ({
       cancelled = true;
       setLoading(false);
     };
-  }, [open, file, sourceId]);
+  }, [open, file, sourceId, confirmed]);
+
+  const previewAnyway = () => {
+    setNeedsConfirmation(false);
+    setError(null);
+    setMode(null);
+    setLoading(true);
+    setConfirmed(true);
+  };
 
   useEffect(() => {
     // Cleanup object URL when preview changes or dialog unmounts
@@ -291,7 +302,15 @@ export const FilePreviewDialog: React.FC<FilePreviewDialogProps> = ({
               </span>
             </div>
           )}
-          {!loading && error && (
+          {!loading && error && needsConfirmation && (
+            <div className="flex h-full flex-col items-center justify-center gap-3 px-4 text-center text-xs text-muted-foreground">
+              <span>{error}</span>
+              <Button type="button" size="sm" variant="outline" onClick={previewAnyway}>
+                Preview anyway
+              </Button>
+            </div>
+          )}
+          {!loading && error && !needsConfirmation && (
             <div className="flex h-full items-center justify-center px-4 text-xs text-destructive">
               {error}
             </div>
@@ -332,12 +351,3 @@ export const FilePreviewDialog: React.FC<FilePreviewDialogProps> = ({
     </Dialog>
   );
 };
-
-const formatFileSize = (bytes: number) => {
-  if (bytes < 1024) return `${bytes} B`;
-  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
-  if (bytes < 1024 * 1024 * 1024) {
-    return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
-  }
-  return `${(bytes / (1024 * 1024 * 1024)).toFixed(1)} GB`;
-};
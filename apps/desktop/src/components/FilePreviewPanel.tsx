@@ -15,12 +15,18 @@ import {
   AlertDialogTitle,
 } from "@/components/ui/alert-dialog";
 import { FileTypeIcon } from "./FileIcon";
-import { getStorageCapabilities, readFile, readFileVersion, statEntry, writeFile } from "@/lib/api";
+import {
+  getStorageCapabilities,
+  readFile,
+  readFileVersion,
+  statEntry,
+  TauriApiError,
+  writeFile,
+} from "@/lib/api";
 import { toast } from "@/hooks/use-toast";
 import infinityLoader from "@/assets/loading-infinity.apng";
 import { FileVersionsTab } from "./FileVersionsTab";
 
-const MAX_PREVIEW_BYTES = 20 * 1024 * 1024;
 
 const TEXT_EXTENSIONS = new Set([
   "txt",
@@ -161,6 +167,8 @@ interface FilePreviewPanelProps {
   startInEditMode?: boolean;
   onEditModeChange?: (editing: boolean) => void;
   onDownload: () => void;
+  /** Open on the Versions tab ("History") when the source keeps versions. */
+  initialTab?: "preview" | "versions";
 }
 
 export function FilePreviewPanel({
@@ -170,6 +178,7 @@ export function FilePreviewPanel({
   startInEditMode,
   onEditModeChange,
   onDownload,
+  initialTab = "preview",
 }: FilePreviewPanelProps) {
   const [content, setContent] = useState<string>("");
   const [previewUrl, setPreviewUrl] = useState<string | null>(null);
@@ -183,16 +192,23 @@ export function FilePreviewPanel({
   const [showOverwriteConfirm, setShowOverwriteConfirm] = useState(false);
   const [editBaselineMs, setEditBaselineMs] = useState<number | null>(null);
   const [editBaselineRaw, setEditBaselineRaw] = useState<string | null>(null);
+  // Version of the file when editing began, checked by the save itself.
+  const [editBaselineTag, setEditBaselineTag] = useState<string | null>(null);
   const [remoteModifiedAtLabel, setRemoteModifiedAtLabel] = useState<string | null>(null);
   const editorRef = useRef<HTMLTextAreaElement | null>(null);
-  const [activeTab, setActiveTab] = useState("preview");
+  const [activeTab, setActiveTab] = useState<string>(initialTab);
 
   const [prevFileId, setPrevFileId] = useState<string | null>(null);
+  // Set once the user agreed to preview a file over the size threshold.
+  const [confirmed, setConfirmed] = useState(false);
+  const [needsConfirmation, setNeedsConfirmation] = useState(false);
   const [versionsCapable, setVersionsCapable] = useState(false);
 
   if (file?.id !== prevFileId) {
     setPrevFileId(file?.id ?? null);
-    setActiveTab("preview");
+    setConfirmed(false);
+    setNeedsConfirmation(false);
+    setActiveTab(initialTab);
     setContent("");
     setError(null);
     setMode(null);
@@ -203,17 +219,14 @@ export function FilePreviewPanel({
     setOriginalContent("");
     setEditBaselineMs(null);
     setEditBaselineRaw(null);
+    setEditBaselineTag(null);
     setRemoteModifiedAtLabel(null);
 
     if (file && file.type === "file") {
       const ext = (file.extension || file.name.split(".").pop() || "").toLowerCase();
       const isKnownBinary = BINARY_EXTENSIONS.has(ext);
 
-      if (file.size && file.size > MAX_PREVIEW_BYTES) {
-        setLoading(false);
-        setMode("unsupported");
-        setError(`File is too large to preview (${formatFileSize(file.size)}).`);
-      } else if (isKnownBinary) {
+      if (isKnownBinary) {
         setLoading(false);
         setMode("unsupported");
         setError("Preview not available for this file type.");
@@ -243,7 +256,7 @@ export function FilePreviewPanel({
     let cancelled = false;
 
     // setLoading(true); // Moved to render phase reset
-    readFile(sourceId, file.id)
+    readFile(sourceId, file.id, { confirmed })
       .then((data) => {
         if (cancelled) return;
 
@@ -280,6 +293,7 @@ export function FilePreviewPanel({
       })
       .catch((e: unknown) => {
         if (cancelled) return;
+        setNeedsConfirmation(e instanceof TauriApiError && e.code === "CONFIRMATION_REQUIRED");
         setMode("unsupported");
         setError(e instanceof Error ? e.message : String(e));
       })
@@ -291,7 +305,19 @@ export function FilePreviewPanel({
       cancelled = true;
       setLoading(false);
     };
-  }, [file, sourceId]);
+  }, [file, sourceId, confirmed]);
+
+  const previewAnyway = () => {
+    setNeedsConfirmation(false);
+    setError(null);
+    setMode(null);
+    setLoading(true);
+    setConfirmed(true);
+  };
+
+  useEffect(() => {
+    setActiveTab(initialTab);
+  }, [initialTab]);
 
   useEffect(() => {
     if (!sourceId) return;
@@ -321,9 +347,11 @@ export function FilePreviewPanel({
 
     let baselineMs: number | null = null;
     let baselineRaw: string | null;
+    let baselineTag: string | null = null;
     try {
       const latest = await statEntry(sourceId, file.id);
       baselineRaw = latest.modified_at ?? null;
+      baselineTag = latest.etag ?? latest.modified_at ?? null;
       if (latest.modified_at) {
         const parsed = Date.parse(latest.modified_at);
         baselineMs = Number.isNaN(parsed) ? null : parsed;
@@ -335,6 +363,7 @@ export function FilePreviewPanel({
 
     setEditBaselineMs(baselineMs);
     setEditBaselineRaw(baselineRaw);
+    setEditBaselineTag(baselineTag);
   }, [isEditing, loading, error, mode, file, sourceId, onEditModeChange]);
 
   const attemptSave = useCallback(
@@ -379,12 +408,14 @@ export function FilePreviewPanel({
 
       try {
         const data = new TextEncoder().encode(draftContent);
-        await writeFile(sourceId, file.id, data);
+        const ifMatch = force ? null : editBaselineTag;
+        await writeFile(sourceId, file.id, data, { if_match: ifMatch ?? undefined });
         setContent(draftContent);
         setOriginalContent(draftContent);
         setIsEditing(false);
         setEditBaselineMs(null);
         setEditBaselineRaw(null);
+        setEditBaselineTag(null);
         onEditModeChange?.(false);
 
         try {
@@ -394,9 +425,11 @@ export function FilePreviewPanel({
             setEditBaselineMs(Number.isNaN(parsed) ? null : parsed);
             setEditBaselineRaw(updated.modified_at);
           }
+          setEditBaselineTag(updated.etag ?? updated.modified_at ?? null);
         } catch {
           setEditBaselineMs(null);
           setEditBaselineRaw(null);
+          setEditBaselineTag(null);
         }
 
         toast({
@@ -406,6 +439,12 @@ export function FilePreviewPanel({
           duration: 2000,
         });
       } catch (err: unknown) {
+        // Changed between the check above and the write.
+        if (err instanceof TauriApiError && err.code === "CONFLICT") {
+          setRemoteModifiedAtLabel(latestModifiedLabel ?? latestModifiedRaw);
+          setShowOverwriteConfirm(true);
+          return;
+        }
         toast({
           title: "Save failed",
           description: err instanceof Error ? err.message : String(err),
@@ -423,6 +462,7 @@ export function FilePreviewPanel({
       sourceId,
       editBaselineRaw,
       editBaselineMs,
+      editBaselineTag,
       onEditModeChange,
     ],
   );
@@ -531,7 +571,15 @@ export function FilePreviewPanel({
                 <span>Loading preview…</span>
               </div>
             )}
-            {!loading && error && (
+            {!loading && error && needsConfirmation && (
+              <div className="flex h-full flex-col items-center justify-center gap-3 px-4 text-center text-xs text-muted-foreground">
+                <span>{error}</span>
+                <Button type="button" size="sm" variant="outline" onClick={previewAnyway}>
+                  Preview anyway
+                </Button>
+              </div>
+            )}
+            {!loading && error && !needsConfirmation && (
               <div className="flex h-full items-center justify-center px-4 text-xs text-destructive">
                 {error}
               </div>
@@ -621,6 +669,34 @@ export function FilePreviewPanel({
                   <span>{file.owner}</span>
                 </div>
               )}
+              {file.contentType && (
+                <div className="flex justify-between gap-4">
+                  <span>Content type:</span>
+                  <span className="truncate">{file.contentType}</span>
+                </div>
+              )}
+              {file.storageClass && (
+                <div className="flex justify-between">
+                  <span>Storage class:</span>
+                  <span>{file.storageClass}</span>
+                </div>
+              )}
+              {file.etag && (
+                <div className="flex justify-between gap-4">
+                  <span>ETag:</span>
+                  <span className="truncate font-mono text-xs" title={file.etag}>
+                    {file.etag}
+                  </span>
+                </div>
+              )}
+              {Object.entries(file.userMetadata ?? {}).map(([key, value]) => (
+                <div key={key} className="flex justify-between gap-4">
+                  <span className="truncate">{key}:</span>
+                  <span className="truncate" title={value}>
+                    {value}
+                  </span>
+                </div>
+              ))}
             </div>
           </div>
 
@@ -648,6 +724,7 @@ export function FilePreviewPanel({
                     setIsEditing(false);
                     setEditBaselineMs(null);
                     setEditBaselineRaw(null);
+                    setEditBaselineTag(null);
                     onEditModeChange?.(false);
                   }}
                   disabled={isSaving}
@@ -1,5 +1,16 @@
 import { FileItem } from "@/types/storage";
-import { Eye, Download, Trash2, Edit3, Scissors, Copy, ClipboardPaste } from "lucide-react";
+import {
+  Eye,
+  Download,
+  Trash2,
+  Edit3,
+  Scissors,
+  Copy,
+  ClipboardPaste,
+  Cloud,
+  Globe,
+  History,
+} from "lucide-react";
 import {
   TableBody,
   TableCell,
@@ -55,6 +66,8 @@ interface FileTableProps {
   onSelectFiles?: (fileIds: string[]) => void;
   onOpenFile?: (file: FileItem) => void;
   onEditFile?: (file: FileItem) => void;
+  /** Shown as "History" on sources that keep object versions. */
+  onShowHistory?: (file: FileItem) => void;
   onDownloadFile?: (file: FileItem) => void;
   onDeleteFile?: (file: FileItem) => void;
   onCutSelected?: () => void;
@@ -80,6 +93,15 @@ type InternalTransferPayload = {
 
 const INTERNAL_TRANSFER_MIME = "application/x-infimount-transfer";
 
+const GIT_STATUS_STYLES: Record<
+  NonNullable<FileItem["gitStatus"]>,
+  { label: string; className: string }
+> = {
+  modified: { label: "M", className: "text-amber-500" },
+  untracked: { label: "U", className: "text-emerald-500" },
+  ignored: { label: "I", className: "text-muted-foreground" },
+};
+
 const isExternalFileDrag = (dt: DataTransfer) => {
   const types = Array.from(dt.types ?? []);
   if (types.includes("Files")) return true;
@@ -128,6 +150,7 @@ export function FileTable({
   onSelectFiles,
   onOpenFile,
   onEditFile,
+  onShowHistory,
   onDownloadFile,
   onDeleteFile,
   onCutSelected,
@@ -359,6 +382,24 @@ export function FileTable({
                         <span className="block truncate text-sm font-medium" title={file.name}>
                           {file.name}
                         </span>
+                        {file.onlineOnly && (
+                          <span title="Online-only: stored in the cloud, downloaded when opened">
+                            <Cloud className="h-4 w-4 shrink-0 text-muted-foreground" />
+                          </span>
+                        )}
+                        {file.publiclyReadable && (
+                          <span title="Public: anyone with the link can read this object">
+                            <Globe className="h-4 w-4 shrink-0 text-rose-500" />
+                          </span>
+                        )}
+                        {file.gitStatus && (
+                          <span
+                            title={`Git: ${file.gitStatus}`}
+                            className={`shrink-0 text-xs font-semibold ${GIT_STATUS_STYLES[file.gitStatus].className}`}
+                          >
+                            {GIT_STATUS_STYLES[file.gitStatus].label}
+                          </span>
+                        )}
                       </div>
                     </TableCell>
                     <TableCell className="w-[14%] min-w-[10ch] truncate text-xs text-muted-foreground align-top px-3 py-2">
@@ -421,6 +462,12 @@ export function FileTable({
                           Edit
                         </ContextMenuItem>
                       )}
+                      {onShowHistory && (
+                        <ContextMenuItem onClick={() => onShowHistory(file)}>
+                          <History className="mr-2 h-4 w-4" />
+                          History
+                        </ContextMenuItem>
+                      )}
                     </>
                   )}
                   <ContextMenuItem
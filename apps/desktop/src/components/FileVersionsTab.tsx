@@ -1,6 +1,6 @@
-import { FileVersion, listVersions, deleteFileVersion } from "@/lib/api";
+import { FileVersion, listVersions, deleteFileVersion, restoreFileVersion } from "@/lib/api";
 import { formatDistanceToNow } from "date-fns";
-import { Download, Trash2, Clock } from "lucide-react";
+import { Download, Trash2, Clock, RotateCcw } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import {
   AlertDialog,
@@ -28,6 +28,8 @@ export function FileVersionsTab({ sourceId, path, onVersionDownload }: FileVersi
   const [error, setError] = useState<string | null>(null);
   const [deleting, setDeleting] = useState<string | null>(null);
   const [pendingDeleteVersion, setPendingDeleteVersion] = useState<string | null>(null);
+  const [restoring, setRestoring] = useState<string | null>(null);
+  const [reloadTick, setReloadTick] = useState(0);
 
   useEffect(() => {
     let cancelled = false;
@@ -55,7 +57,27 @@ export function FileVersionsTab({ sourceId, path, onVersionDownload }: FileVersi
     return () => {
       cancelled = true;
     };
-  }, [sourceId, path]);
+  }, [sourceId, path, reloadTick]);
+
+  const restoreVersion = async (version: string) => {
+    setRestoring(version);
+    try {
+      await restoreFileVersion(sourceId, path, version);
+      toast({
+        title: "Version restored",
+        description: "It is now the current version; newer versions are kept.",
+      });
+      setReloadTick((tick) => tick + 1);
+    } catch (err: unknown) {
+      toast({
+        title: "Failed to restore",
+        description: err instanceof Error ? err.message : String(err),
+        variant: "destructive",
+      });
+    } finally {
+      setRestoring(null);
+    }
+  };
 
   const confirmDeleteVersion = async () => {
     if (!pendingDeleteVersion) return;
@@ -121,6 +143,11 @@ export function FileVersionsTab({ sourceId, path, onVersionDownload }: FileVersi
             <div className="flex flex-col gap-1 overflow-hidden">
               <div className="flex items-center gap-2 font-mono text-xs text-muted-foreground">
                 <span className="truncate">{v.version}</span>
+                {v.is_current && (
+                  <span className="shrink-0 rounded bg-muted px-1.5 py-0.5 font-sans text-[10px] uppercase">
+                    Current
+                  </span>
+                )}
               </div>
               <div className="text-xs text-muted-foreground">
                 {v.modified_at
@@ -139,6 +166,18 @@ export function FileVersionsTab({ sourceId, path, onVersionDownload }: FileVersi
               >
                 <Download className="h-4 w-4" />
               </Button>
+              {!v.is_current && (
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  title="Restore version"
+                  aria-label="Restore version"
+                  disabled={restoring !== null}
+                  onClick={() => void restoreVersion(v.version)}
+                >
+                  <RotateCcw className="h-4 w-4" />
+                </Button>
+              )}
               <Button
                 variant="ghost"
                 size="icon"
@@ -0,0 +1,139 @@
+import { useEffect, useState } from "react";
+
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import type { StorageConfig } from "@/types/storage";
+
+interface SharedFilesDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  /** Local paths shared with the app from the OS. */
+  paths: string[];
+  storages: StorageConfig[];
+  defaultStorageId: string | null;
+  onUpload: (storageId: string, prefix: string) => Promise<void>;
+}
+
+export function SharedFilesDialog({
+  open,
+  onOpenChange,
+  paths,
+  storages,
+  defaultStorageId,
+  onUpload,
+}: SharedFilesDialogProps) {
+  const writable = storages.filter((storage) => !storage.readOnly);
+  const [storageId, setStorageId] = useState<string>("");
+  const [prefix, setPrefix] = useState("/");
+  const [error, setError] = useState<string | null>(null);
+  const [isUploading, setIsUploading] = useState(false);
+
+  useEffect(() => {
+    if (!open) return;
+    const preferred = writable.find((storage) => storage.id === defaultStorageId);
+    setStorageId(preferred?.id ?? writable[0]?.id ?? "");
+    setError(null);
+  }, [open]);
+
+  const handleUpload = async () => {
+    setIsUploading(true);
+    setError(null);
+    try {
+      await onUpload(storageId, prefix.trim() || "/");
+      onOpenChange(false);
+    } catch (uploadError) {
+      setError(uploadError instanceof Error ? uploadError.message : String(uploadError));
+    } finally {
+      setIsUploading(false);
+    }
+  };
+
+  const names = paths.map((path) => path.split(/[\\/]/).filter(Boolean).pop() ?? path);
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-[520px] rounded-2xl border border-border bg-background text-foreground shadow-2xl">
+        <DialogHeader>
+          <DialogTitle className="text-left text-base font-normal text-[hsl(var(--card-foreground))]">
+            Upload shared {paths.length === 1 ? "file" : `files (${paths.length})`}
+          </DialogTitle>
+          <DialogDescription className="text-left text-xs text-muted-foreground">
+            {names.slice(0, 5).join(", ")}
+            {names.length > 5 ? ` and ${names.length - 5} more` : ""}
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="space-y-4">
+          <div className="space-y-2">
+            <Label className="text-xs font-normal text-muted-foreground">Storage</Label>
+            <Select value={storageId} onValueChange={setStorageId}>
+              <SelectTrigger className="border border-border bg-card text-sm text-[hsl(var(--card-foreground))]">
+                <SelectValue placeholder="Choose a storage" />
+              </SelectTrigger>
+              <SelectContent className="border border-border bg-[hsl(var(--popover))] text-[hsl(var(--popover-foreground))] shadow-md">
+                {writable.map((storage) => (
+                  <SelectItem key={storage.id} value={storage.id}>
+                    {storage.name}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="shared-prefix" className="text-xs font-normal text-muted-foreground">
+              Folder
+            </Label>
+            <Input
+              id="shared-prefix"
+              value={prefix}
+              onChange={(event) => setPrefix(event.target.value)}
+              placeholder="/inbox"
+              className="border border-border bg-[hsl(var(--card))] text-sm text-[hsl(var(--card-foreground))]"
+            />
+          </div>
+
+          {error ? (
+            <div className="rounded-md border border-rose-300/80 bg-rose-50 px-3 py-2 text-xs text-rose-700 dark:border-rose-700/60 dark:bg-rose-950/40 dark:text-rose-300">
+              {error}
+            </div>
+          ) : null}
+
+          <div className="flex justify-end gap-3 pt-2">
+            <Button
+              type="button"
+              variant="outline"
+              className="border border-border hover:bg-sidebar-accent/30 hover:text-foreground"
+              onClick={() => onOpenChange(false)}
+            >
+              Cancel
+            </Button>
+            <Button
+              type="button"
+              className="bg-primary text-primary-foreground hover:bg-primary/90"
+              onClick={handleUpload}
+              disabled={!storageId || isUploading}
+            >
+              {isUploading ? "Uploading..." : "Upload"}
+            </Button>
+          </div>
+        </div>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,553 @@
+import { useCallback, useEffect, useState } from "react";
+import { Globe, Plus, RefreshCw, Trash2 } from "lucide-react";
+
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Input } from "@/components/ui/input";
+import { Switch } from "@/components/ui/switch";
+import { useToast } from "@/hooks/use-toast";
+import {
+  abortIncompleteUploads,
+  getBucketAccess,
+  getLifecycleRules,
+  listIncompleteUploads,
+  putLifecycleRules,
+  type BucketAccess,
+  type IncompleteUpload,
+  type LifecycleRule,
+} from "@/lib/api";
+import type { StorageConfig } from "@/types/storage";
+
+interface SourceMaintenanceDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  storage: StorageConfig;
+}
+
+const errorText = (error: unknown): string =>
+  error instanceof Error ? error.message : String(error);
+
+const describeAge = (initiated?: string | null): string => {
+  if (!initiated) return "unknown age";
+  const days = Math.floor((Date.now() - new Date(initiated).getTime()) / 86_400_000);
+  if (days <= 0) return "started today";
+  return days === 1 ? "started 1 day ago" : `started ${days} days ago`;
+};
+
+const plural = (count: number, noun: string) => `${count} ${noun}${count === 1 ? "" : "s"}`;
+
+/** Which objects a lifecycle rule applies to, in words. */
+export const describeRuleScope = (rule: LifecycleRule): string => {
+  const parts = [rule.prefix ? `Objects under "${rule.prefix}"` : "All objects"];
+  const tags = Object.entries(rule.tags ?? {});
+  if (tags.length > 0) {
+    parts.push(`tagged ${tags.map(([key, value]) => `${key}=${value}`).join(", ")}`);
+  }
+  if (rule.min_size != null) parts.push(`larger than ${rule.min_size} bytes`);
+  if (rule.max_size != null) parts.push(`smaller than ${rule.max_size} bytes`);
+  return parts.join(", ");
+};
+
+/** What a lifecycle rule does, in words. */
+export const describeRuleActions = (rule: LifecycleRule): string[] => {
+  const actions = rule.transitions.map((transition) =>
+    transition.date
+      ? `move to ${transition.storage_class} on ${transition.date}`
+      : `move to ${transition.storage_class} after ${plural(transition.days ?? 0, "day")}`,
+  );
+  if (rule.expiration_date) actions.push(`delete on ${rule.expiration_date}`);
+  else if (rule.expiration_days != null) {
+    actions.push(`delete ${plural(rule.expiration_days, "day")} after upload`);
+  }
+  if (rule.expired_delete_marker) actions.push("remove expired delete markers");
+  for (const transition of rule.noncurrent_transitions) {
+    actions.push(
+      `move old versions to ${transition.storage_class} after ${plural(transition.days ?? 0, "day")}`,
+    );
+  }
+  if (rule.noncurrent_expiration_days != null) {
+    const kept = rule.noncurrent_versions_kept
+      ? ` (keeping the newest ${rule.noncurrent_versions_kept})`
+      : "";
+    actions.push(
+      `delete old versions ${plural(rule.noncurrent_expiration_days, "day")} after they are replaced${kept}`,
+    );
+  }
+  if (rule.abort_incomplete_upload_days != null) {
+    actions.push(
+      `abort unfinished uploads after ${plural(rule.abort_incomplete_upload_days, "day")}`,
+    );
+  }
+  return actions;
+};
+
+/** Why a bucket is or is not exposed, in words. */
+export const describeAccess = (access: BucketAccess): string[] => {
+  const facts = [
+    access.public ? "Anyone can list this bucket or read its objects." : "The bucket is not public.",
+  ];
+  if (access.public_access_blocked === true) facts.push("Public access is blocked for the bucket.");
+  else if (access.public_access_blocked === false) facts.push("Public access is not blocked.");
+  if (access.policy_public === true) facts.push("The bucket policy makes every object readable.");
+  else if (access.policy_public === null) facts.push("The provider does not report policy status.");
+  facts.push(
+    access.object_acls_ignored
+      ? "Object ACLs cannot make objects public."
+      : "Individual objects can still be made public through their ACLs.",
+  );
+  return facts;
+};
+
+const ErrorBox = ({ error }: { error: string | null }) =>
+  error ? (
+    <div className="rounded-md border border-rose-300/80 bg-rose-50 px-3 py-2 text-xs text-rose-700 dark:border-rose-700/60 dark:bg-rose-950/40 dark:text-rose-300">
+      {error}
+    </div>
+  ) : null;
+
+const SectionHeader = ({
+  title,
+  description,
+  isLoading,
+  onRefresh,
+}: {
+  title: string;
+  description: string;
+  isLoading: boolean;
+  onRefresh: () => void;
+}) => (
+  <div className="flex items-center justify-between gap-2">
+    <div>
+      <h3 className="text-sm text-[hsl(var(--card-foreground))]">{title}</h3>
+      <p className="text-xs text-muted-foreground">{description}</p>
+    </div>
+    <Button
+      type="button"
+      variant="ghost"
+      size="icon"
+      className="h-8 w-8 shrink-0"
+      onClick={onRefresh}
+      disabled={isLoading}
+      aria-label={`Refresh ${title.toLowerCase()}`}
+    >
+      <RefreshCw className={isLoading ? "h-4 w-4 animate-spin" : "h-4 w-4"} />
+    </Button>
+  </div>
+);
+
+function IncompleteUploadsSection({ storage }: { storage: StorageConfig }) {
+  const [uploads, setUploads] = useState<IncompleteUpload[] | null>(null);
+  const [selected, setSelected] = useState<Set<string>>(new Set());
+  const [isLoading, setIsLoading] = useState(false);
+  const [isAborting, setIsAborting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const { toast } = useToast();
+
+  const load = useCallback(async () => {
+    setIsLoading(true);
+    setError(null);
+    try {
+      const found = await listIncompleteUploads(storage.id);
+      setUploads(found);
+      setSelected(new Set(found.map((upload) => upload.upload_id)));
+    } catch (loadError) {
+      setError(errorText(loadError));
+    } finally {
+      setIsLoading(false);
+    }
+  }, [storage.id]);
+
+  useEffect(() => {
+    void load();
+  }, [load]);
+
+  const toggle = (uploadId: string, checked: boolean) => {
+    setSelected((current) => {
+      const next = new Set(current);
+      if (checked) next.add(uploadId);
+      else next.delete(uploadId);
+      return next;
+    });
+  };
+
+  const handleAbort = async () => {
+    if (!uploads) return;
+    const chosen = uploads.filter((upload) => selected.has(upload.upload_id));
+    setIsAborting(true);
+    setError(null);
+    try {
+      const report = await abortIncompleteUploads(storage.id, chosen);
+      toast({
+        title:
+          report.failed.length > 0
+            ? `Aborted ${report.aborted.length}, ${report.failed.length} failed`
+            : `Aborted ${report.aborted.length} upload(s)`,
+        description: report.failed[0]
+          ? `${report.failed[0].key}: ${report.failed[0].error}`
+          : undefined,
+        variant: report.failed.length > 0 ? "destructive" : undefined,
+      });
+      await load();
+    } catch (abortError) {
+      setError(errorText(abortError));
+    } finally {
+      setIsAborting(false);
+    }
+  };
+
+  return (
+    <section className="space-y-3">
+      <SectionHeader
+        title="Incomplete multipart uploads"
+        description="Parts of uploads that were never finished are stored and billed but never listed."
+        isLoading={isLoading}
+        onRefresh={() => void load()}
+      />
+
+      {uploads === null ? (
+        <p className="text-xs text-muted-foreground">{isLoading ? "Loading..." : null}</p>
+      ) : uploads.length === 0 ? (
+        <p className="text-xs text-muted-foreground">No incomplete uploads.</p>
+      ) : (
+        <ul className="max-h-48 space-y-1 overflow-y-auto rounded-md border border-border p-2">
+          {uploads.map((upload) => (
+            <li key={upload.upload_id} className="flex items-center gap-2 text-xs">
+              <Checkbox
+                checked={selected.has(upload.upload_id)}
+                onCheckedChange={(checked) => toggle(upload.upload_id, checked === true)}
+                aria-label={`Select ${upload.key}`}
+              />
+              <span className="min-w-0 flex-1 truncate" title={upload.key}>
+                {upload.key}
+              </span>
+              <span className="shrink-0 text-muted-foreground">
+                {describeAge(upload.initiated)}
+                {upload.storage_class ? ` · ${upload.storage_class}` : ""}
+              </span>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      <ErrorBox error={error} />
+
+      {uploads && uploads.length > 0 ? (
+        <div className="flex justify-end">
+          <Button
+            type="button"
+            variant="destructive"
+            onClick={handleAbort}
+            disabled={storage.readOnly || selected.size === 0 || isAborting || isLoading}
+          >
+            {isAborting ? "Aborting..." : `Abort ${selected.size} upload(s)`}
+          </Button>
+        </div>
+      ) : null}
+    </section>
+  );
+}
+
+function LifecycleSection({ storage }: { storage: StorageConfig }) {
+  const [rules, setRules] = useState<LifecycleRule[] | null>(null);
+  const [isDirty, setIsDirty] = useState(false);
+  const [isLoading, setIsLoading] = useState(false);
+  const [isSaving, setIsSaving] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [newPrefix, setNewPrefix] = useState("");
+  const [newDays, setNewDays] = useState("30");
+  const { toast } = useToast();
+
+  const load = useCallback(async () => {
+    setIsLoading(true);
+    setError(null);
+    try {
+      setRules(await getLifecycleRules(storage.id));
+      setIsDirty(false);
+    } catch (loadError) {
+      setError(errorText(loadError));
+    } finally {
+      setIsLoading(false);
+    }
+  }, [storage.id]);
+
+  useEffect(() => {
+    void load();
+  }, [load]);
+
+  const update = (next: LifecycleRule[]) => {
+    setRules(next);
+    setIsDirty(true);
+  };
+
+  const days = Number.parseInt(newDays, 10);
+  const handleAddExpiry = () => {
+    if (!rules || !(days > 0)) return;
+    const prefix = newPrefix.trim().replace(/^\/+/, "");
+    update([
+      ...rules,
+      {
+        id: `expire-${prefix || "all"}-${days}d`.slice(0, 255),
+        enabled: true,
+        prefix,
+        tags: {},
+        expired_delete_marker: false,
+        transitions: [],
+        noncurrent_transitions: [],
+        expiration_days: days,
+      },
+    ]);
+    setNewPrefix("");
+  };
+
+  const handleSave = async () => {
+    if (!rules) return;
+    setIsSaving(true);
+    setError(null);
+    try {
+      await putLifecycleRules(storage.id, rules);
+      toast({ title: "Lifecycle rules saved" });
+      await load();
+    } catch (saveError) {
+      setError(errorText(saveError));
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  const editable = !storage.readOnly && rules !== null;
+
+  return (
+    <section className="space-y-3">
+      <SectionHeader
+        title="Lifecycle rules"
+        description="The bucket deletes or archives matching objects on its own, even if nobody deletes them."
+        isLoading={isLoading}
+        onRefresh={() => void load()}
+      />
+
+      {rules === null ? (
+        <p className="text-xs text-muted-foreground">{isLoading ? "Loading..." : null}</p>
+      ) : rules.length === 0 ? (
+        <p className="text-xs text-muted-foreground">No lifecycle rules; objects are kept until deleted.</p>
+      ) : (
+        <ul className="max-h-56 space-y-2 overflow-y-auto rounded-md border border-border p-2">
+          {rules.map((rule, index) => (
+            <li key={`${rule.id}-${index}`} className="flex items-start gap-2 text-xs">
+              <Switch
+                checked={rule.enabled}
+                disabled={!editable}
+                onCheckedChange={(enabled) =>
+                  update(rules.map((item, i) => (i === index ? { ...item, enabled } : item)))
+                }
+                aria-label={`${rule.enabled ? "Disable" : "Enable"} ${rule.id || "rule"}`}
+              />
+              <div className="min-w-0 flex-1">
+                <div className="truncate text-[hsl(var(--card-foreground))]">
+                  {rule.id || `Rule ${index + 1}`}
+                </div>
+                <div className="text-muted-foreground">{describeRuleScope(rule)}:</div>
+                <ul className="list-disc pl-4 text-muted-foreground">
+                  {describeRuleActions(rule).map((action) => (
+                    <li key={action}>{action}</li>
+                  ))}
+                </ul>
+              </div>
+              <Button
+                type="button"
+                variant="ghost"
+                size="icon"
+                className="h-7 w-7 shrink-0"
+                disabled={!editable}
+                onClick={() => update(rules.filter((_, i) => i !== index))}
+                aria-label={`Remove ${rule.id || "rule"}`}
+              >
+                <Trash2 className="h-4 w-4" />
+              </Button>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      {editable ? (
+        <div className="flex items-center gap-2 text-xs">
+          <span className="shrink-0 text-muted-foreground">Delete objects under</span>
+          <Input
+            value={newPrefix}
+            onChange={(event) => setNewPrefix(event.target.value)}
+            placeholder="logs/ (empty for all)"
+            className="h-8 border border-border bg-[hsl(var(--card))] text-xs"
+          />
+          <span className="shrink-0 text-muted-foreground">after</span>
+          <Input
+            value={newDays}
+            onChange={(event) => setNewDays(event.target.value)}
+            inputMode="numeric"
+            className="h-8 w-16 border border-border bg-[hsl(var(--card))] text-xs"
+            aria-label="Days"
+          />
+          <span className="shrink-0 text-muted-foreground">days</span>
+          <Button
+            type="button"
+            variant="outline"
+            size="icon"
+            className="h-8 w-8 shrink-0"
+            onClick={handleAddExpiry}
+            disabled={!(days > 0)}
+            aria-label="Add expiration rule"
+          >
+            <Plus className="h-4 w-4" />
+          </Button>
+        </div>
+      ) : null}
+
+      <ErrorBox error={error} />
+
+      {isDirty ? (
+        <div className="flex justify-end gap-3">
+          <Button type="button" variant="outline" onClick={() => void load()} disabled={isSaving}>
+            Discard
+          </Button>
+          <Button
+            type="button"
+            className="bg-primary text-primary-foreground hover:bg-primary/90"
+            onClick={handleSave}
+            disabled={isSaving}
+          >
+            {isSaving ? "Saving..." : "Save rules"}
+          </Button>
+        </div>
+      ) : null}
+    </section>
+  );
+}
+
+function AccessSection({ storage }: { storage: StorageConfig }) {
+  const [access, setAccess] = useState<BucketAccess | null>(null);
+  const [isLoading, setIsLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    setIsLoading(true);
+    setError(null);
+    try {
+      setAccess(await getBucketAccess(storage.id));
+    } catch (loadError) {
+      setError(errorText(loadError));
+    } finally {
+      setIsLoading(false);
+    }
+  }, [storage.id]);
+
+  useEffect(() => {
+    void load();
+  }, [load]);
+
+  return (
+    <section className="space-y-3">
+      <SectionHeader
+        title="Access"
+        description="Who can read this bucket, from its ACL, public access settings and policy."
+        isLoading={isLoading}
+        onRefresh={() => void load()}
+      />
+      <ErrorBox error={error} />
+      {access ? (
+        <>
+          <ul
+            className={
+              access.public
+                ? "space-y-1 rounded-md border border-rose-300/80 bg-rose-50 px-3 py-2 text-xs text-rose-700 dark:border-rose-700/60 dark:bg-rose-950/40 dark:text-rose-300"
+                : "space-y-1 rounded-md border border-border px-3 py-2 text-xs text-muted-foreground"
+            }
+          >
+            {describeAccess(access).map((fact) => (
+              <li key={fact}>{fact}</li>
+            ))}
+          </ul>
+          {access.grants.length > 0 ? (
+            <div className="rounded-md border border-border">
+              {access.grants.map((grant) => (
+                <div
+                  key={`${grant.grantee}:${grant.permission}`}
+                  className="flex items-center gap-2 border-b border-border px-3 py-1.5 text-xs last:border-b-0"
+                >
+                  {grant.public ? (
+                    <Globe className="h-3.5 w-3.5 shrink-0 text-rose-500" aria-label="Public" />
+                  ) : null}
+                  <span className="min-w-0 flex-1 truncate" title={grant.grantee}>
+                    {grant.grantee || "(unnamed)"}
+                  </span>
+                  <span className="shrink-0 font-mono text-muted-foreground">{grant.permission}</span>
+                </div>
+              ))}
+            </div>
+          ) : null}
+          <div className="space-y-1 text-xs">
+            <p className="text-muted-foreground">
+              {access.cors.length === 0
+                ? "No CORS rules: browsers on other sites cannot fetch from this bucket."
+                : `${plural(access.cors.length, "CORS rule")}:`}
+            </p>
+            {access.cors.map((rule, index) => (
+              <p key={index} className="font-mono text-[11px]">
+                {rule.allowed_methods.join(", ") || "any method"} from{" "}
+                {rule.allowed_origins.join(", ") || "no origin"}
+                {rule.max_age_secs != null ? `, cached ${rule.max_age_secs}s` : ""}
+              </p>
+            ))}
+          </div>
+        </>
+      ) : null}
+    </section>
+  );
+}
+
+/** Housekeeping for one S3 or GCS source that the file browser does not show. */
+export function SourceMaintenanceDialog({
+  open,
+  onOpenChange,
+  storage,
+}: SourceMaintenanceDialogProps) {
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-[640px] max-h-[85vh] overflow-y-auto rounded-2xl border border-border bg-background text-foreground shadow-2xl">
+        <DialogHeader>
+          <DialogTitle className="text-left text-base font-normal text-[hsl(var(--card-foreground))]">
+            Maintenance: {storage.name}
+          </DialogTitle>
+          <DialogDescription className="text-left text-xs text-muted-foreground">
+            Housekeeping for this bucket that the file browser does not show.
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="space-y-6">
+          <AccessSection storage={storage} />
+          {storage.backend === "s3" ? (
+            <>
+              <LifecycleSection storage={storage} />
+              <IncompleteUploadsSection storage={storage} />
+            </>
+          ) : null}
+        </div>
+
+        <div className="flex justify-end pt-2">
+          <Button
+            type="button"
+            variant="outline"
+            className="border border-border hover:bg-sidebar-accent/30 hover:text-foreground"
+            onClick={() => onOpenChange(false)}
+          >
+            Close
+          </Button>
+        </div>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -14,6 +14,7 @@ vi.mock("@tauri-apps/plugin-updater", () => ({
 
 vi.mock("@/lib/api", () => ({
   transferEntries: vi.fn(),
+  getSourceBranding: vi.fn().mockRejectedValue(new Error("not in tests")),
   TauriApiError: class extends Error {
     code: string;
     constructor(message: string, code = "UNKNOWN") {
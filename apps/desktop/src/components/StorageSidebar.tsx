@@ -11,6 +11,7 @@ import {
   Sparkles,
   Cable,
   Braces,
+  Wrench,
 } from "lucide-react";
 import s3Icon from "@/assets/amazon-s3.svg";
 import azureIcon from "@/assets/azure-storage-blob.svg";
@@ -23,8 +24,8 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { cn } from "@/lib/utils";
-import { StorageConfig } from "@/types/storage";
-import { transferEntries, TauriApiError } from "@/lib/api";
+import { SourceBranding, StorageConfig } from "@/types/storage";
+import { getSourceBranding, transferEntries, TauriApiError } from "@/lib/api";
 import {
   DropdownMenu,
   DropdownMenuContent,
@@ -58,6 +59,8 @@ interface StorageSidebarProps {
   onEditStorage: (id: string) => void;
   onDeleteStorage: (id: string) => void;
   onRefreshStorage: (id: string) => void;
+  /** Bucket housekeeping; offered for S3 storages only. */
+  onOpenMaintenance?: (id: string) => void;
   onImportStorages?: () => void;
   onEditStorageConfig?: () => void;
   onExportStorages?: () => void;
@@ -77,6 +80,10 @@ const normalizeVersion = (value: string): string => value.trim().replace(/^v/i,
 const getStorageIcon = (type: string) => {
   switch (type) {
     case "aws-s3":
+    case "minio":
+    case "cloudflare-r2":
+    case "wasabi":
+    case "digitalocean-spaces":
       return s3Icon;
     case "azure-blob":
       return azureIcon;
@@ -99,6 +106,7 @@ export function StorageSidebar({
   onEditStorage,
   onDeleteStorage,
   onRefreshStorage,
+  onOpenMaintenance,
   onImportStorages,
   onEditStorageConfig,
   onExportStorages,
@@ -120,8 +128,35 @@ export function StorageSidebar({
     paths: string[];
     operation: "copy" | "move";
   } | null>(null);
+  const [brandings, setBrandings] = useState<Record<string, SourceBranding>>({});
   const { toast } = useToast();
 
+  const storageIds = storages.map((storage) => storage.id).join("\n");
+  useEffect(() => {
+    let cancelled = false;
+    const ids = storageIds ? storageIds.split("\n") : [];
+    void Promise.all(
+      ids.map(async (id) => [id, await getSourceBranding(id).catch(() => null)] as const),
+    ).then((entries) => {
+      if (cancelled) return;
+      const loaded: Record<string, SourceBranding> = {};
+      for (const [id, branding] of entries) {
+        if (branding) loaded[id] = branding;
+      }
+      setBrandings(loaded);
+    });
+    return () => {
+      cancelled = true;
+    };
+  }, [storageIds]);
+
+  // Tint only sources that would otherwise share an icon (e.g. several MinIO servers).
+  const iconCounts = new Map<string, number>();
+  for (const storage of storages) {
+    const icon = brandings[storage.id]?.icon ?? storage.type;
+    iconCounts.set(icon, (iconCounts.get(icon) ?? 0) + 1);
+  }
+
   const INTERNAL_TRANSFER_MIME = "application/x-infimount-transfer";
 
   const parseInternalTransfer = (dt: DataTransfer) => {
@@ -481,7 +516,10 @@ export function StorageSidebar({
         ) : (
           <div className="space-y-1">
             {visibleStorages.map((storage) => {
-              const iconSrc = getStorageIcon(storage.type);
+              const branding = brandings[storage.id];
+              const icon = branding?.icon ?? storage.type;
+              const iconSrc = branding?.favicon ?? getStorageIcon(icon);
+              const showAccent = branding !== undefined && (iconCounts.get(icon) ?? 0) > 1;
               const isDragTarget = dragTargetId === storage.id;
               return (
                 <ContextMenu key={storage.id}>
@@ -527,13 +565,23 @@ export function StorageSidebar({
                       }}
                     >
                       <div className="flex w-full flex-1 items-center gap-2 overflow-hidden text-left text-sm font-normal min-w-0">
-                        <img
-                          src={iconSrc}
-                          alt=""
-                          aria-hidden="true"
-                          draggable={false}
-                          className="h-5 w-5 shrink-0"
-                        />
+                        <span className="relative shrink-0">
+                          <img
+                            src={iconSrc}
+                            alt=""
+                            aria-hidden="true"
+                            draggable={false}
+                            className="h-5 w-5"
+                          />
+                          {branding && showAccent && (
+                            <span
+                              aria-hidden="true"
+                              data-testid="storage-accent"
+                              className="absolute -bottom-0.5 -right-0.5 h-2 w-2 rounded-full ring-1 ring-sidebar"
+                              style={{ backgroundColor: `hsl(${branding.accent_hue} 65% 50%)` }}
+                            />
+                          )}
+                        </span>
                         <div className="flex-1 min-w-0">
                           <span className="block truncate text-[13px] font-normal leading-snug">
                             {storage.name}
@@ -551,6 +599,12 @@ export function StorageSidebar({
                       <Edit className="mr-2 h-4 w-4" />
                       Edit
                     </ContextMenuItem>
+                    {onOpenMaintenance && (storage.backend === "s3" || storage.backend === "gcs") ? (
+                      <ContextMenuItem onClick={() => onOpenMaintenance(storage.id)}>
+                        <Wrench className="mr-2 h-4 w-4" />
+                        Maintenance…
+                      </ContextMenuItem>
+                    ) : null}
                     <ContextMenuItem
                       onClick={() => onDeleteStorage(storage.id)}
                       className="text-foreground focus:text-foreground"
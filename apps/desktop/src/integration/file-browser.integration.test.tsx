@@ -6,10 +6,13 @@ import { createDirectory, listEntries } from "@/lib/api";
 
 vi.mock("@/lib/api", () => ({
   listEntries: vi.fn(),
+  prefetchAdjacent: vi.fn(),
   readFile: vi.fn(),
   createDirectory: vi.fn(),
   deletePath: vi.fn(),
   transferEntries: vi.fn(),
+  getSourceCapabilities: vi.fn().mockResolvedValue({ write: true }),
+  onTransferProgress: vi.fn().mockResolvedValue(() => undefined),
   TauriApiError: class extends Error {
     code: string;
     constructor(message: string, code: string) {
@@ -1,4 +1,5 @@
-import { invoke as tauriInvoke } from "@tauri-apps/api/core";
+import { Channel, invoke as tauriInvoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 import type {
   McpClientSnippets,
@@ -6,10 +7,13 @@ import type {
   McpSettings,
   McpToolDefinition,
   StorageCapabilities,
+  SourceBranding,
+  SourceCapabilities,
   StorageConfig,
   StorageDraft,
   StorageValidationResult,
 } from "@/types/storage";
+import type { Source } from "@/types/source";
 
 export interface Entry {
   path: string;
@@ -17,6 +21,108 @@ export interface Entry {
   is_dir: boolean;
   size: number;
   modified_at: string | null;
+  online_only?: boolean;
+  content_type?: string;
+  etag?: string;
+  storage_class?: string;
+  /** Custom metadata stored with the object (`x-amz-meta-*` and the like). */
+  user_metadata?: Record<string, string>;
+  /** Status in the enclosing git working tree (Local sources, when `git_annotations` is on). */
+  git_status?: "modified" | "untracked" | "ignored";
+  /** A symbolic link (Local sources); `link_target` is where it points. */
+  is_symlink?: boolean;
+  link_target?: string;
+}
+
+export interface EntryPage {
+  entries: Entry[];
+  /** Cursor for the next page; `null` on the last one. */
+  next_cursor: string | null;
+}
+
+export interface GrepOptions {
+  /** Treat the query as a regular expression. */
+  regex?: boolean;
+  case_sensitive?: boolean;
+  /** Larger files are skipped (default 10 MiB). */
+  max_file_size?: number;
+  /** Stop after this many matching lines (default 1000). */
+  max_matches?: number;
+  /** Also search the text of Word, Excel, PowerPoint and PDF documents. */
+  documents?: boolean;
+  /** Larger documents are skipped (default 50 MiB). */
+  max_document_size?: number;
+  /**
+   * Also search text recognized in images and scanned PDFs. Slow; only
+   * works when `ocrAvailable()` is true.
+   */
+  ocr?: boolean;
+}
+
+export interface SimilarImage {
+  path: string;
+  size: number;
+  /** Perceptual hash, 16 hex digits. */
+  hash: string;
+  /** Hash bits differing from the first image of the group. */
+  distance: number;
+}
+
+export interface SimilarGroup {
+  /** Largest file first, the likeliest original. */
+  images: SimilarImage[];
+}
+
+export interface WriteOptions {
+  /** MIME type to store; guessed from the extension by default. */
+  content_type?: string;
+  /**
+   * Only overwrite while the file is still this version (its `etag`, or
+   * `modified_at` where the source reports no etag); fails with code
+   * `CONFLICT` otherwise.
+   */
+  if_match?: string;
+  /** Only create the file; fails with code `CONFLICT` if it exists. */
+  if_not_exists?: boolean;
+}
+
+export interface GrepMatch {
+  path: string;
+  /** 1-based. */
+  line_number: number;
+  line: string;
+}
+
+export interface RuntimeStats {
+  worker_threads: number;
+  max_in_flight: number;
+  /** Jobs waiting for a free slot. */
+  queued: number;
+  running: number;
+  completed: number;
+  failed: number;
+  timed_out: number;
+}
+
+export type SortKey = "name" | "size" | "modified";
+
+export interface ListQuery {
+  /** Omitted keeps the backend's order. */
+  sort?: SortKey | null;
+  descending?: boolean;
+  /** Only files with this extension (`"pdf"` or `".pdf"`, any case). */
+  extension?: string | null;
+  /** Only entries whose name contains this, ignoring case. */
+  name_contains?: string | null;
+  dirs_only?: boolean;
+}
+
+export type ListingStrategy = "stat_per_entry" | "list_metadata" | "index";
+
+export interface ListingDiagnostics {
+  strategy: ListingStrategy;
+  /** Smoothed round-trip time; `null` until a listing was measured. */
+  latency_ms: number | null;
 }
 
 export interface ApiError {
@@ -26,6 +132,12 @@ export interface ApiError {
 
 export class TauriApiError extends Error {
   code: string;
+  /** Backend message id (see `crates/core/locales`), when the error has one. */
+  messageId?: string;
+  /** Arguments of the backend message, e.g. `action`/`size`/`threshold` of `CONFIRMATION_REQUIRED`. */
+  args?: Record<string, string>;
+  /** Transfer task with failed items, for `retryFailed` / `exportTaskReport`. */
+  taskId?: string;
 
   constructor(message: string, code = "UNKNOWN") {
     super(message);
@@ -42,144 +154,1549 @@ export interface StorageFieldSchema {
   secret?: boolean;
 }
 
-export interface StorageKindSchema {
-  id: string;
-  label: string;
-  kind: string;
-  fields: StorageFieldSchema[];
+export interface StorageKindSchema {
+  id: string;
+  label: string;
+  kind: string;
+  preset?: string;
+  fields: StorageFieldSchema[];
+}
+
+export type TransferOperation = "copy" | "move";
+export type TransferConflictPolicy = "fail" | "overwrite" | "skip" | "rename";
+export type TransferReportFormat = "csv" | "json";
+
+export interface TransferItem {
+  source_path: string;
+  target_path: string;
+  status: "completed" | "skipped" | "failed";
+  bytes: number;
+  duration_ms: number;
+  error: string | null;
+  verified: boolean | null;
+  /** How an already existing destination was dealt with. */
+  conflict: "overwritten" | "skipped" | "renamed" | null;
+}
+
+export interface TransferReport {
+  task_id: string;
+  operation: TransferOperation;
+  from_source: string;
+  to_source: string;
+  started_at: string;
+  finished_at: string | null;
+  retry_of?: string;
+  items: TransferItem[];
+}
+
+export interface OrganizeRule {
+  id: string;
+  name: string;
+  source_id: string;
+  /** Folder whose files (not subfolders) are organized. */
+  watch: string;
+  /** Extensions without the dot; empty matches every file. */
+  extensions: string[];
+  /** Target folder; `{yyyy}`, `{mm}`, `{dd}` and `{ext}` are filled in per file. */
+  destination: string;
+  enabled: boolean;
+}
+
+export interface PlannedMove {
+  from: string;
+  to_dir: string;
+}
+
+/** Payload of the `organize-run` event sent after a background rule run. */
+export interface OrganizeRun {
+  rule_id: string;
+  task_id: string | null;
+  moved: number;
+  failed: number;
+  error: string | null;
+}
+
+export interface ImportStoragesRequest {
+  json: string;
+  mode: "merge" | "replace";
+  onConflict: "error" | "overwrite" | "rename";
+  /** Report conflicts without changing anything. */
+  dryRun?: boolean;
+}
+
+export interface ImportConflict {
+  name: string;
+  kind: "name" | "id";
+  resolution: "error" | "overwrite" | "rename" | "new_id";
+  renamed_to?: string;
+}
+
+export interface ImportStoragesResult {
+  imported: number;
+  dry_run: boolean;
+  conflicts: ImportConflict[];
+}
+
+export interface RemoteNote {
+  name: string;
+  remote_type: string;
+  reason: string;
+}
+
+export interface RcloneImportResult {
+  storages: StorageConfig[];
+  /** Imported, but a bucket, password, etc. still has to be filled in. */
+  incomplete: RemoteNote[];
+  /** Remotes that were not imported. */
+  unmapped: RemoteNote[];
+}
+
+export interface DoctorCheck {
+  subject: string;
+  status: "ok" | "warning" | "error";
+  message: string;
+  fix?: string;
+}
+
+export interface DoctorReport {
+  checks: DoctorCheck[];
+}
+
+export interface ConnectionTest {
+  ok: boolean;
+  latency_ms: number;
+  error?: string;
+  error_kind?: "config" | "unauthorized" | "not_found" | "timeout" | "other";
+}
+
+export interface ExportStoragesResult {
+  json: string;
+}
+
+export interface WorkspaceImportResult {
+  storages: ImportStoragesResult;
+  /** Credential sets added from the file; their values must be entered again. */
+  restored_credential_sets: CredentialSet[];
+  settings_applied: boolean;
+}
+
+export interface LocalRoot {
+  path: string;
+  label: string;
+  kind: "fixed" | "removable" | "network";
+  total_bytes: number | null;
+  available_bytes: number | null;
+}
+
+/** A common user folder offered as a Local source on first launch. */
+export interface SuggestedSource {
+  id: "home" | "documents" | "downloads" | "desktop" | "pictures";
+  name: string;
+  path: string;
+}
+
+export interface VolumeChanges {
+  added: LocalRoot[];
+  removed: LocalRoot[];
+}
+
+export interface ReauthRequired {
+  storageId: string;
+  storageName: string;
+  reason: string;
+}
+
+export interface TransferProgress {
+  task_id: string;
+  operation: string;
+  stage: "preparing" | "transferring" | "paused" | "finished";
+  current_item: string | null;
+  items_done: number;
+  items_total: number | null;
+  bytes_done: number;
+  bytes_total: number | null;
+  bytes_per_sec: number | null;
+  eta_secs: number | null;
+  percent: number | null;
+  /** The fields above as a sentence in the app's locale. */
+  description: string;
+}
+
+export interface Preferences {
+  auto_add_removable_drives: boolean;
+  include_system_files: boolean;
+  fetch_favicons: boolean;
+  prefetch_adjacent: boolean;
+  git_annotations: boolean;
+  honor_gitignore: boolean;
+  /** Recreate symbolic links in uploaded folders as links on Local targets. */
+  copy_symlinks_as_links: boolean;
+  /** Most entries listed in one call; omitted for the default (100 000). */
+  max_listing_entries?: number | null;
+  /** Files uploaded at once; omitted for the default (4). */
+  upload_concurrency?: number | null;
+  size_thresholds: SizeThresholds;
+}
+
+/** Sizes past which downloads and previews fail with `CONFIRMATION_REQUIRED` until retried with `confirmed`. */
+export interface SizeThresholds {
+  confirm_download_bytes: number;
+  confirm_preview_bytes: number;
+  /** Bigger files are never read whole into the app (`TOO_LARGE`). */
+  block_webview_bytes: number;
+}
+
+export interface OAuthTokens {
+  access_token: string;
+  refresh_token: string | null;
+  expires_at: string | null;
+}
+
+async function handleError(error: unknown): Promise<never> {
+  console.error("API Error:", error);
+  if (typeof error === "object" && error !== null && "code" in error && "message" in error) {
+    const apiErr = error as {
+      code: string;
+      message: string;
+      message_id?: string;
+      args?: Record<string, string>;
+      localized?: string;
+    };
+    const apiError = new TauriApiError(apiErr.localized ?? apiErr.message, apiErr.code);
+    apiError.messageId = apiErr.message_id;
+    apiError.args = apiErr.args;
+    throw apiError;
+  }
+
+  const message =
+    typeof error === "string"
+      ? error
+      : error instanceof Error
+        ? error.message
+        : String(error);
+  throw new TauriApiError(message);
+}
+
+/** Entries of a folder, optionally filtered and sorted by the backend. */
+export async function listEntries(
+  sourceId: string,
+  path: string,
+  query?: ListQuery,
+): Promise<Entry[]> {
+  try {
+    return await tauriInvoke<Entry[]>("list_entries", { sourceId, path, query: query ?? null });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Everything below a folder, flattened; `maxDepth` 1 lists direct children only. */
+export async function listRecursive(
+  sourceId: string,
+  path: string,
+  maxDepth?: number,
+): Promise<Entry[]> {
+  try {
+    return await tauriInvoke<Entry[]>("list_recursive", {
+      sourceId,
+      path,
+      maxDepth: maxDepth ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/**
+ * Search below `root` for paths matching a glob such as `*.pdf` (a leading
+ * `**` segment matches any depth); `onMatch` is called for each match as it
+ * is found. Resolves to the number of matches.
+ */
+export async function searchEntries(
+  sourceId: string,
+  root: string,
+  pattern: string,
+  onMatch: (entry: Entry) => void,
+  /** Lets `cancelOperation(searchId)` stop the search. */
+  searchId?: string,
+): Promise<number> {
+  const channel = new Channel<Entry>();
+  channel.onmessage = onMatch;
+  try {
+    return await tauriInvoke<number>("search_entries", {
+      sourceId,
+      root,
+      pattern,
+      searchId: searchId ?? null,
+      onMatch: channel,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/**
+ * Search the text files below `root` for `query`; `onMatch` is called for
+ * each matching line as it is found. Pass the same `searchId` to
+ * `cancelContentSearch` to stop. Resolves to the number of matching lines.
+ */
+export async function searchContent(
+  sourceId: string,
+  root: string,
+  query: string,
+  searchId: string,
+  onMatch: (match: GrepMatch) => void,
+  options?: GrepOptions,
+): Promise<number> {
+  const channel = new Channel<GrepMatch>();
+  channel.onmessage = onMatch;
+  try {
+    return await tauriInvoke<number>("search_content", {
+      sourceId,
+      root,
+      query,
+      options: options ?? null,
+      searchId,
+      onMatch: channel,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function cancelContentSearch(searchId: string): Promise<void> {
+  try {
+    return await tauriInvoke("cancel_content_search", { searchId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Whether this build can search text in images and scans (`GrepOptions.ocr`). */
+export async function ocrAvailable(): Promise<boolean> {
+  try {
+    return await tauriInvoke<boolean>("ocr_available");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/**
+ * Groups of images below `root` that look alike: resized or recompressed
+ * copies of the same photo. `maxDistance` (default 10, out of 64) is how many
+ * perceptual-hash bits two images may differ in. Pass the same `searchId` to
+ * `cancelOperation` to stop.
+ */
+export async function findSimilarImages(
+  sourceId: string,
+  root: string,
+  searchId: string,
+  maxDistance?: number,
+): Promise<SimilarGroup[]> {
+  try {
+    return await tauriInvoke<SimilarGroup[]>("find_similar_images", {
+      sourceId,
+      root,
+      maxDistance: maxDistance ?? null,
+      searchId,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/**
+ * Cancel the job running under `operationId`: a transfer's `task_id` (as
+ * sent with its progress events), or the id given to a search or delete.
+ * Resolves to whether one was running; the job itself fails with
+ * `CANCELLED`.
+ */
+export async function cancelOperation(operationId: string): Promise<boolean> {
+  try {
+    return await tauriInvoke<boolean>("cancel_operation", { operationId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** List a folder page by page; pass the previous page's `next_cursor`. */
+export async function listEntriesPaged(
+  sourceId: string,
+  path: string,
+  cursor?: string | null,
+  limit?: number,
+): Promise<EntryPage> {
+  try {
+    return await tauriInvoke<EntryPage>("list_entries_paged", {
+      sourceId,
+      path,
+      cursor: cursor ?? null,
+      limit: limit ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/**
+ * Ask the backend to prefetch the folders around `path` (when the
+ * `prefetch_adjacent` preference is on). Fire-and-forget; never throws.
+ */
+export function prefetchAdjacent(sourceId: string, path: string): void {
+  tauriInvoke("prefetch_adjacent", { sourceId, path }).catch(() => undefined);
+}
+
+export async function statEntry(sourceId: string, path: string): Promise<Entry> {
+  try {
+    return await tauriInvoke<Entry>("stat_entry", { sourceId, path });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/**
+ * Whole contents of a file. Files over the preview threshold fail with
+ * `CONFIRMATION_REQUIRED` unless `confirmed` is set.
+ */
+export async function readFile(
+  sourceId: string,
+  path: string,
+  options: { confirmed?: boolean } = {},
+): Promise<Uint8Array> {
+  try {
+    const data = await tauriInvoke<number[]>("read_file", {
+      sourceId,
+      path,
+      confirmed: options.confirmed ?? null,
+    });
+    return new Uint8Array(data);
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/**
+ * Read up to `length` bytes from `offset`. Past the end of the file fewer
+ * bytes (or none) are returned; use the entry size to read a log's tail.
+ */
+export async function readFileRange(
+  sourceId: string,
+  path: string,
+  offset: number,
+  length: number,
+): Promise<Uint8Array> {
+  try {
+    const data = await tauriInvoke<ArrayBuffer>("read_file_range", {
+      sourceId,
+      path,
+      offset,
+      length,
+    });
+    return new Uint8Array(data);
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface ReadChunksOptions {
+  /** Bytes per chunk; the backend defaults to 1 MiB. */
+  chunkSize?: number;
+  /** Stop after this many bytes, e.g. for a preview. */
+  maxBytes?: number;
+}
+
+/**
+ * Stream a file in chunks instead of one IPC message, so large files can be
+ * previewed or saved without loading them whole. Resolves with the number of
+ * bytes delivered to `onChunk`.
+ */
+export async function readFileChunks(
+  sourceId: string,
+  path: string,
+  onChunk: (chunk: Uint8Array) => void,
+  options: ReadChunksOptions = {},
+): Promise<number> {
+  const channel = new Channel<ArrayBuffer>();
+  channel.onmessage = (chunk) => onChunk(new Uint8Array(chunk));
+  try {
+    return await tauriInvoke<number>("read_file_chunks", {
+      sourceId,
+      path,
+      chunkSize: options.chunkSize ?? null,
+      maxBytes: options.maxBytes ?? null,
+      onChunk: channel,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface HexRow {
+  offset: number;
+  /** Two-digit hex bytes, space separated, with a wider gap after the eighth. */
+  hex: string;
+  /** Printable ASCII, other bytes as `.`. */
+  ascii: string;
+}
+
+export interface HexChunk {
+  /** Start of the window: the requested offset rounded down to a 16-byte row. */
+  offset: number;
+  file_size: number;
+  rows: HexRow[];
+}
+
+/** One hex viewer window (4 KiB by default, at most 64 KiB) read with a ranged read. */
+export async function readHexChunk(
+  sourceId: string,
+  path: string,
+  offset: number,
+  length?: number,
+): Promise<HexChunk> {
+  try {
+    return await tauriInvoke<HexChunk>("read_hex_chunk", {
+      sourceId,
+      path,
+      offset,
+      length: length ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface TailLines {
+  lines: string[];
+  /** The file shrank (rotated or truncated) and is read again from the start. */
+  reset: boolean;
+}
+
+/**
+ * Last `lines` lines of a log file (100 by default), passed to `onLines`.
+ * With `follow`, appended lines keep arriving until
+ * `cancelOperation(tailId)`. Resolves with the number of lines sent.
+ */
+export async function tailFile(
+  sourceId: string,
+  path: string,
+  onLines: (batch: TailLines) => void,
+  options: { lines?: number; follow?: boolean; tailId?: string } = {},
+): Promise<number> {
+  const channel = new Channel<TailLines>();
+  channel.onmessage = onLines;
+  try {
+    return await tauriInvoke<number>("tail_file", {
+      sourceId,
+      path,
+      lines: options.lines ?? null,
+      follow: options.follow ?? false,
+      tailId: options.tailId ?? null,
+      onLines: channel,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface TableColumn {
+  name: string;
+  /** Type recorded in the file (Parquet) or declared (SQLite); null for text formats. */
+  data_type: string | null;
+}
+
+export interface TablePreview {
+  format: "csv" | "tsv" | "jsonl" | "parquet";
+  columns: TableColumn[];
+  /** One value per column. */
+  rows: unknown[][];
+  /** Rows in the whole file, when the format records it (Parquet). */
+  total_rows: number | null;
+  /** More rows follow the ones returned. */
+  truncated: boolean;
+}
+
+/**
+ * First rows of a CSV, TSV, JSON Lines or Parquet file, read without
+ * downloading it. The backend returns 100 rows unless `rows` is given.
+ */
+export async function previewTable(
+  sourceId: string,
+  path: string,
+  rows?: number,
+): Promise<TablePreview> {
+  try {
+    return await tauriInvoke<TablePreview>("preview_table", {
+      sourceId,
+      path,
+      rows: rows ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface SqliteTable {
+  name: string;
+  columns: TableColumn[];
+  /** Null for virtual tables, which keep no rows in the file. */
+  row_count: number | null;
+  /** One value per column; blobs are base64 strings. */
+  rows: unknown[][];
+  truncated: boolean;
+}
+
+/**
+ * Tables of a SQLite database with row counts and sample rows. Remote
+ * databases are downloaded into the cache first. The backend returns 20
+ * rows per table unless `rows` is given.
+ */
+export async function previewSqlite(
+  sourceId: string,
+  path: string,
+  rows?: number,
+): Promise<SqliteTable[]> {
+  try {
+    return await tauriInvoke<SqliteTable[]>("preview_sqlite", {
+      sourceId,
+      path,
+      rows: rows ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export type JsonNode =
+  | { type: "null" }
+  | { type: "bool"; value: boolean }
+  | { type: "number"; value: number }
+  /** `value` is cut short when `length` (in characters) is larger. */
+  | { type: "string"; value: string; length: number }
+  /** `items` are the first elements of `length`. */
+  | { type: "array"; items: JsonNode[]; length: number }
+  /** `entries` are the first members of `length`, in document order. */
+  | { type: "object"; entries: { key: string; value: JsonNode }[]; length: number };
+
+export interface JsonPreview {
+  root: JsonNode;
+  nodes: number;
+  /** Elements or members were left out to stay within the node cap. */
+  truncated: boolean;
+}
+
+/**
+ * A JSON document as a bounded tree. The backend keeps 5000 nodes unless
+ * `nodes` is given.
+ */
+export async function previewJson(
+  sourceId: string,
+  path: string,
+  nodes?: number,
+): Promise<JsonPreview> {
+  try {
+    return await tauriInvoke<JsonPreview>("preview_json", {
+      sourceId,
+      path,
+      nodes: nodes ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export type CellOutput =
+  | { type: "text"; name: string | null; text: string; truncated: boolean }
+  | { type: "error"; name: string; value: string; traceback: string; truncated: boolean }
+  /** Images, HTML and widgets: only the MIME type and encoded size. */
+  | { type: "rich"; mime_type: string; bytes: number };
+
+export interface NotebookCell {
+  kind: "code" | "markdown" | "raw";
+  source: string;
+  source_truncated: boolean;
+  execution_count: number | null;
+  outputs: CellOutput[];
+  omitted_outputs: number;
+}
+
+export interface NotebookPreview {
+  language: string | null;
+  nbformat: number | null;
+  cells: NotebookCell[];
+  total_cells: number;
+}
+
+/** Cells of a Jupyter notebook with outputs truncated or reduced to their size. */
+export async function previewNotebook(sourceId: string, path: string): Promise<NotebookPreview> {
+  try {
+    return await tauriInvoke<NotebookPreview>("preview_notebook", { sourceId, path });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface FontPreview {
+  family: string | null;
+  subfamily: string | null;
+  full_name: string | null;
+  postscript_name: string | null;
+  version: string | null;
+  glyph_count: number;
+  units_per_em: number;
+  bold: boolean;
+  italic: boolean;
+  monospaced: boolean;
+  variable: boolean;
+  faces: number;
+  sample_text: string;
+  /** Base64 PNG of `sample_text`; null for fonts without outlines. */
+  sample_png: string | null;
+  sample_width: number;
+  sample_height: number;
+}
+
+/** Names and style of a TrueType/OpenType font, with `sample` rendered to PNG. */
+export async function previewFont(
+  sourceId: string,
+  path: string,
+  sample?: string,
+): Promise<FontPreview> {
+  try {
+    return await tauriInvoke<FontPreview>("preview_font", { sourceId, path, sample });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export type ModelFormat = "stl" | "obj" | "ply" | "gltf" | "glb";
+
+export interface ModelPreview {
+  format: ModelFormat;
+  vertices: number;
+  faces: number;
+  meshes?: number;
+  bounding_box: { min: [number, number, number]; max: [number, number, number] } | null;
+  /** Base64 PNG, for STL and OBJ when a thumbnail was asked for. */
+  thumbnail_png?: string;
+}
+
+/** Vertex and face counts and bounding box of a 3D model, optionally with a thumbnail. */
+export async function previewModel(
+  sourceId: string,
+  path: string,
+  thumbnail = false,
+): Promise<ModelPreview> {
+  try {
+    return await tauriInvoke<ModelPreview>("preview_model", { sourceId, path, thumbnail });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface EmailAttachment {
+  index: number;
+  filename: string | null;
+  content_type: string;
+  size: number;
+  /** Shown within the HTML body (by `content_id`) rather than attached. */
+  inline: boolean;
+  content_id: string | null;
+}
+
+export interface EmailPreview {
+  format: "eml" | "msg";
+  subject: string | null;
+  from: string | null;
+  to: string | null;
+  cc: string | null;
+  date: string | null;
+  headers: { name: string; value: string }[];
+  text: string | null;
+  html: string | null;
+  truncated: boolean;
+  attachments: EmailAttachment[];
+}
+
+/** Headers, bodies and attachments of an `.eml` or Outlook `.msg` file. */
+export async function previewEmail(sourceId: string, path: string): Promise<EmailPreview> {
+  try {
+    return await tauriInvoke<EmailPreview>("preview_email", { sourceId, path });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Save an email's attachments (all, or those at `indexes`) into a folder; returns the written paths. */
+export async function extractEmailAttachments(
+  sourceId: string,
+  path: string,
+  toSourceId: string,
+  targetDir: string,
+  indexes?: number[],
+): Promise<string[]> {
+  try {
+    return await tauriInvoke<string[]>("extract_email_attachments", {
+      sourceId,
+      path,
+      indexes,
+      toSourceId,
+      targetDir,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export type ChecksumAlgorithm = "md5" | "sha256" | "xxh64";
+
+/** Lowercase hex digest of a file, for checking it against a published hash. */
+export async function checksum(
+  sourceId: string,
+  path: string,
+  algorithm: ChecksumAlgorithm,
+): Promise<string> {
+  try {
+    return await tauriInvoke<string>("checksum", { sourceId, path, algorithm });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface AuditSnapshotSummary {
+  id: string;
+  source_id: string;
+  prefix: string;
+  created_at: string;
+  files: number;
+  total_bytes: number;
+}
+
+export interface DriftReport {
+  snapshot_id: string;
+  checked_at: string;
+  /** False when the stored manifest was edited after it was sealed. */
+  manifest_intact: boolean;
+  modified: string[];
+  removed: string[];
+  added: string[];
+}
+
+/** Record the files below `prefix` with their SHA-256 for later drift checks. */
+export async function freezePrefix(
+  sourceId: string,
+  prefix: string,
+): Promise<AuditSnapshotSummary> {
+  try {
+    return await tauriInvoke<AuditSnapshotSummary>("freeze_prefix", { sourceId, prefix });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function listAuditSnapshots(): Promise<AuditSnapshotSummary[]> {
+  try {
+    return await tauriInvoke<AuditSnapshotSummary[]>("list_audit_snapshots");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function verifyAuditSnapshot(snapshotId: string): Promise<DriftReport> {
+  try {
+    return await tauriInvoke<DriftReport>("verify_audit_snapshot", { snapshotId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function deleteAuditSnapshot(snapshotId: string): Promise<void> {
+  try {
+    return await tauriInvoke("delete_audit_snapshot", { snapshotId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface FolderUsage {
+  name: string;
+  objects: number;
+  bytes: number;
+}
+
+export interface SourceStats {
+  source_id: string;
+  scanned_at: string;
+  objects: number;
+  total_bytes: number;
+  root_objects: number;
+  root_bytes: number;
+  /** Per top-level folder, largest first. */
+  folders: FolderUsage[];
+}
+
+export interface SourceStatsStatus {
+  stats: SourceStats | null;
+  scanning: { started_at: string; objects: number; bytes: number } | null;
+  error: string | null;
+}
+
+/** Start a background usage scan of a source; false when one is already running. */
+export async function scanSourceStats(sourceId: string, scanId?: string): Promise<boolean> {
+  try {
+    return await tauriInvoke<boolean>("scan_source_stats", { sourceId, scanId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Last usage scan of a source, with the progress of one still running. */
+export async function getSourceStats(sourceId: string): Promise<SourceStatsStatus> {
+  try {
+    return await tauriInvoke<SourceStatsStatus>("get_source_stats", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function writeFile(
+  sourceId: string,
+  path: string,
+  data: Uint8Array,
+  options?: WriteOptions,
+): Promise<void> {
+  try {
+    return await tauriInvoke("write_file", {
+      sourceId,
+      path,
+      data: Array.from(data),
+      options: options ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function createDirectory(sourceId: string, path: string): Promise<void> {
+  try {
+    return await tauriInvoke("create_directory", { sourceId, path });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function uploadDroppedFiles(
+  sourceId: string,
+  paths: string[],
+  targetDir: string,
+  conflictPolicy: TransferConflictPolicy = "overwrite",
+  preserveAttributes = true,
+): Promise<TransferReport> {
+  try {
+    return await tauriInvoke<TransferReport>("upload_dropped_files", {
+      sourceId,
+      paths,
+      targetDir,
+      conflictPolicy,
+      preserveAttributes,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export type ResumeProgress =
+  | {
+      kind: "multipart";
+      upload_id: string;
+      part_size: number;
+      parts: { number: number; etag: string }[];
+    }
+  | { kind: "append"; offset: number };
+
+/** An upload that was interrupted and can continue where it stopped. */
+export interface ResumableUpload {
+  destination: string;
+  target_path: string;
+  local_path: string;
+  size: number;
+  modified_ms: number;
+  uploaded: number;
+  started_at: string;
+  progress: ResumeProgress;
+}
+
+export async function listResumableUploads(sourceId: string): Promise<ResumableUpload[]> {
+  try {
+    return await tauriInvoke<ResumableUpload[]>("list_resumable_uploads", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function resumeInterruptedUploads(sourceId: string): Promise<TransferReport> {
+  try {
+    return await tauriInvoke<TransferReport>("resume_interrupted_uploads", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function discardResumableUpload(sourceId: string, path: string): Promise<boolean> {
+  try {
+    return await tauriInvoke<boolean>("discard_resumable_upload", { sourceId, path });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Files shared with the app from the OS that still need a destination. */
+export async function takeSharedFiles(): Promise<string[]> {
+  try {
+    return await tauriInvoke<string[]>("take_shared_files");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Upload shared files into `prefix` on a storage; resolves to the item count. */
+export async function intakeSharedFiles(
+  sourceId: string,
+  prefix: string,
+  paths: string[],
+): Promise<number> {
+  try {
+    return await tauriInvoke<number>("intake_shared_files", { sourceId, prefix, paths });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface DeleteReport {
+  deleted: string[];
+  failed: { path: string; error: string }[];
+}
+
+/** Delete many paths in as few backend requests as possible; folders end in `/`. */
+export async function deletePaths(
+  sourceId: string,
+  paths: string[],
+  operationId?: string,
+  permanent = false,
+): Promise<DeleteReport> {
+  try {
+    return await tauriInvoke<DeleteReport>("delete_paths", {
+      sourceId,
+      paths,
+      operationId: operationId ?? null,
+      permanent,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** An entry in a source's `.infimount-trash/` folder. */
+export interface TrashItem {
+  id: string;
+  original_path: string;
+  deleted_at: string;
+  is_dir: boolean;
+  /** Size of a file; 0 for folders. */
+  size: number;
+}
+
+export async function listTrash(sourceId: string): Promise<TrashItem[]> {
+  try {
+    return await tauriInvoke<TrashItem[]>("list_trash", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Put a trashed entry back; resolves to the path it was restored to. */
+export async function restoreFromTrash(sourceId: string, id: string): Promise<string> {
+  try {
+    return await tauriInvoke<string>("restore_from_trash", { sourceId, id });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Permanently delete everything in a source's trash; resolves to the item count. */
+export async function emptyTrash(sourceId: string): Promise<number> {
+  try {
+    return await tauriInvoke<number>("empty_trash", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface IncompleteUpload {
+  key: string;
+  upload_id: string;
+  initiated?: string | null;
+  storage_class?: string | null;
+}
+
+export interface AbortReport {
+  aborted: string[];
+  failed: { key: string; upload_id: string; error: string }[];
+}
+
+/** Multipart uploads on an S3 source that were never completed or aborted. */
+export async function listIncompleteUploads(sourceId: string): Promise<IncompleteUpload[]> {
+  try {
+    return await tauriInvoke<IncompleteUpload[]>("list_incomplete_uploads", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function abortIncompleteUploads(
+  sourceId: string,
+  uploads: IncompleteUpload[],
+): Promise<AbortReport> {
+  try {
+    return await tauriInvoke<AbortReport>("abort_incomplete_uploads", { sourceId, uploads });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface LifecycleTransition {
+  days?: number | null;
+  /** `YYYY-MM-DD`; not used by noncurrent-version transitions. */
+  date?: string | null;
+  storage_class: string;
+}
+
+export interface LifecycleRule {
+  id: string;
+  enabled: boolean;
+  /** Empty for the whole bucket. */
+  prefix: string;
+  tags: Record<string, string>;
+  min_size?: number | null;
+  max_size?: number | null;
+  expiration_days?: number | null;
+  expiration_date?: string | null;
+  expired_delete_marker: boolean;
+  transitions: LifecycleTransition[];
+  noncurrent_expiration_days?: number | null;
+  noncurrent_versions_kept?: number | null;
+  noncurrent_transitions: LifecycleTransition[];
+  abort_incomplete_upload_days?: number | null;
+}
+
+/** Expiration and transition rules of an S3 source's bucket. */
+export async function getLifecycleRules(sourceId: string): Promise<LifecycleRule[]> {
+  try {
+    return await tauriInvoke<LifecycleRule[]>("get_lifecycle_rules", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Replace the bucket's lifecycle rules; an empty list removes them all. */
+export async function putLifecycleRules(sourceId: string, rules: LifecycleRule[]): Promise<void> {
+  try {
+    return await tauriInvoke("put_lifecycle_rules", { sourceId, rules });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export interface AccessGrant {
+  /** Display name, canonical ID, e-mail, group URI or IAM member. */
+  grantee: string;
+  permission: string;
+  /** Granted to everyone or to any authenticated user. */
+  public: boolean;
+}
+
+export interface CorsRule {
+  allowed_origins: string[];
+  allowed_methods: string[];
+  allowed_headers: string[];
+  expose_headers: string[];
+  max_age_secs: number | null;
+}
+
+export interface BucketAccess {
+  owner: string | null;
+  grants: AccessGrant[];
+  public_access_blocked: boolean | null;
+  object_acls_ignored: boolean;
+  policy_public: boolean | null;
+  cors: CorsRule[];
+  public: boolean;
+}
+
+export interface ObjectAccess {
+  path: string;
+  owner: string | null;
+  grants: AccessGrant[];
+  public: boolean;
+}
+
+/** ACLs, public access settings and CORS rules of an S3 or GCS bucket. */
+export async function getBucketAccess(sourceId: string): Promise<BucketAccess> {
+  try {
+    return await tauriInvoke<BucketAccess>("get_bucket_access", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function getObjectAccess(sourceId: string, path: string): Promise<ObjectAccess> {
+  try {
+    return await tauriInvoke<ObjectAccess>("get_object_access", { sourceId, path });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** The files among `paths` anyone can read; S3 and GCS sources only. */
+export async function findPublicObjects(sourceId: string, paths: string[]): Promise<string[]> {
+  try {
+    return await tauriInvoke<string[]>("find_public_objects", { sourceId, paths });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function deletePath(
+  sourceId: string,
+  path: string,
+  operationId?: string,
+  permanent = false,
+): Promise<void> {
+  try {
+    return await tauriInvoke("delete_path", {
+      sourceId,
+      path,
+      operationId: operationId ?? null,
+      permanent,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function createSymlink(
+  sourceId: string,
+  target: string,
+  linkPath: string,
+): Promise<void> {
+  try {
+    return await tauriInvoke("create_symlink", { sourceId, target, linkPath });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function createHardlink(
+  sourceId: string,
+  existing: string,
+  linkPath: string,
+): Promise<void> {
+  try {
+    return await tauriInvoke("create_hardlink", { sourceId, existing, linkPath });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function transferEntries(
+  fromSourceId: string,
+  toSourceId: string,
+  paths: string[],
+  targetDir: string,
+  operation: TransferOperation,
+  conflictPolicy: TransferConflictPolicy,
+  preserveAttributes = true,
+): Promise<TransferReport> {
+  let report: TransferReport;
+  try {
+    report = await tauriInvoke<TransferReport>("transfer_entries", {
+      fromSourceId,
+      toSourceId,
+      paths,
+      targetDir,
+      operation,
+      conflictPolicy,
+      preserveAttributes,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+  return ensureTransferComplete(report);
+}
+
+/**
+ * Stream a file or folder from one source to a path on another source.
+ * Modification times and permissions come along unless `preserveAttributes`
+ * is `false`, as with the other transfers.
+ */
+export async function transferBetweenSources(
+  fromSourceId: string,
+  fromPath: string,
+  toSourceId: string,
+  toPath: string,
+  preserveAttributes = true,
+): Promise<TransferReport> {
+  let report: TransferReport;
+  try {
+    report = await tauriInvoke<TransferReport>("transfer_between_sources", {
+      fromSourceId,
+      fromPath,
+      toSourceId,
+      toPath,
+      preserveAttributes,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+  return ensureTransferComplete(report);
+}
+
+/**
+ * Download files and folders to `localDir`, or to the Downloads folder when
+ * it is omitted. Downloads over the size threshold fail with
+ * `CONFIRMATION_REQUIRED` unless `confirmed` is set.
+ */
+export async function downloadEntries(
+  sourceId: string,
+  paths: string[],
+  conflictPolicy: TransferConflictPolicy,
+  localDir?: string,
+  confirmed = false,
+  preserveAttributes = true,
+): Promise<TransferReport> {
+  let report: TransferReport;
+  try {
+    report = await tauriInvoke<TransferReport>("download_entries", {
+      sourceId,
+      paths,
+      localDir: localDir ?? null,
+      conflictPolicy,
+      confirmed,
+      preserveAttributes,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+  return ensureTransferComplete(report);
+}
+
+// Failed files don't abort a transfer; surface them like any other error.
+// The task id is kept on the error so the caller can offer a retry.
+function ensureTransferComplete(report: TransferReport): TransferReport {
+  const failed = report.items.filter((item) => item.status === "failed");
+  if (failed.length > 0) {
+    const error = new TauriApiError(
+      `${failed.length} of ${report.items.length} files failed to ${report.operation}: ${failed[0].error ?? failed[0].source_path}`,
+      "TRANSFER_INCOMPLETE",
+    );
+    error.taskId = report.task_id;
+    throw error;
+  }
+  return report;
+}
+
+export async function retryFailed(taskId: string): Promise<TransferReport> {
+  let report: TransferReport;
+  try {
+    report = await tauriInvoke<TransferReport>("retry_failed", { taskId });
+  } catch (error) {
+    return handleError(error);
+  }
+  return ensureTransferComplete(report);
 }
 
-export type TransferOperation = "copy" | "move";
-export type TransferConflictPolicy = "fail" | "overwrite" | "skip";
+export async function listOrganizeRules(): Promise<OrganizeRule[]> {
+  try {
+    return await tauriInvoke<OrganizeRule[]>("list_organize_rules");
+  } catch (error) {
+    return handleError(error);
+  }
+}
 
-export interface ImportStoragesRequest {
-  json: string;
-  mode: "merge" | "replace";
-  onConflict: "error" | "overwrite" | "rename";
+export async function saveOrganizeRules(rules: OrganizeRule[]): Promise<void> {
+  try {
+    return await tauriInvoke("save_organize_rules", { rules });
+  } catch (error) {
+    return handleError(error);
+  }
 }
 
-export interface ImportStoragesResult {
-  imported: number;
+/** Dry run: the moves `rule` would make now. */
+export async function previewOrganizeRule(rule: OrganizeRule): Promise<PlannedMove[]> {
+  try {
+    return await tauriInvoke<PlannedMove[]>("preview_organize_rule", { rule });
+  } catch (error) {
+    return handleError(error);
+  }
 }
 
-export interface ExportStoragesResult {
-  json: string;
+export async function runOrganizeRule(rule: OrganizeRule): Promise<TransferReport> {
+  try {
+    return await tauriInvoke<TransferReport>("run_organize_rule", { rule });
+  } catch (error) {
+    return handleError(error);
+  }
 }
 
-async function handleError(error: unknown): Promise<never> {
-  console.error("API Error:", error);
-  if (typeof error === "object" && error !== null && "code" in error && "message" in error) {
-    const apiErr = error as { code: string; message: string };
-    throw new TauriApiError(apiErr.message, apiErr.code);
+/** Pause every transfer and background job; returns whether jobs are paused. */
+export async function pauseAllTransfers(): Promise<boolean> {
+  try {
+    return await tauriInvoke<boolean>("pause_all_transfers");
+  } catch (error) {
+    return handleError(error);
   }
+}
 
-  const message =
-    typeof error === "string"
-      ? error
-      : error instanceof Error
-        ? error.message
-        : String(error);
-  throw new TauriApiError(message);
+export async function resumeAllTransfers(): Promise<boolean> {
+  try {
+    return await tauriInvoke<boolean>("resume_all_transfers");
+  } catch (error) {
+    return handleError(error);
+  }
 }
 
-export async function listEntries(sourceId: string, path: string): Promise<Entry[]> {
+export async function transfersPaused(): Promise<boolean> {
   try {
-    return await tauriInvoke<Entry[]>("list_entries", { sourceId, path });
+    return await tauriInvoke<boolean>("transfers_paused");
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function statEntry(sourceId: string, path: string): Promise<Entry> {
+export async function exportTaskReport(
+  taskId: string,
+  format: TransferReportFormat,
+): Promise<string> {
   try {
-    return await tauriInvoke<Entry>("stat_entry", { sourceId, path });
+    return await tauriInvoke<string>("export_task_report", { taskId, format });
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function readFile(sourceId: string, path: string): Promise<Uint8Array> {
+export async function listStorages(): Promise<StorageConfig[]> {
   try {
-    const data = await tauriInvoke<number[]>("read_file", { sourceId, path });
-    return new Uint8Array(data);
+    return await tauriInvoke<StorageConfig[]>("list_storages");
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function writeFile(
-  sourceId: string,
-  path: string,
-  data: Uint8Array,
-): Promise<void> {
+export async function addStorage(storage: StorageDraft): Promise<StorageConfig> {
   try {
-    return await tauriInvoke("write_file", { sourceId, path, data: Array.from(data) });
+    return await tauriInvoke<StorageConfig>("add_storage", { storage });
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function createDirectory(sourceId: string, path: string): Promise<void> {
+/** Connect a storage for this session only; it is never saved to disk. */
+export async function addSessionStorage(storage: StorageDraft): Promise<StorageConfig> {
   try {
-    return await tauriInvoke("create_directory", { sourceId, path });
+    return await tauriInvoke<StorageConfig>("add_session_storage", { storage });
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function uploadDroppedFiles(
-  sourceId: string,
-  paths: string[],
-  targetDir: string,
-): Promise<void> {
+/** Add the in-memory "Demo" source with generated sample files. */
+export async function addDemoSource(): Promise<StorageConfig> {
   try {
-    return await tauriInvoke("upload_dropped_files", { sourceId, paths, targetDir });
+    return await tauriInvoke<StorageConfig>("add_demo_source");
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function deletePath(sourceId: string, path: string): Promise<void> {
+/** Options for a synthetic stress-test source; unset fields use defaults. */
+export interface SyntheticSourceOptions {
+  entries: number;
+  entriesPerDir?: number;
+  fileSize?: number;
+  latencyMs?: number;
+}
+
+/** Add a read-only source with generated entries for performance testing. */
+export async function addSyntheticSource(
+  options: SyntheticSourceOptions,
+): Promise<StorageConfig> {
   try {
-    return await tauriInvoke("delete_path", { sourceId, path });
+    return await tauriInvoke<StorageConfig>("add_synthetic_source", { ...options });
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function transferEntries(
-  fromSourceId: string,
-  toSourceId: string,
-  paths: string[],
-  targetDir: string,
-  operation: TransferOperation,
-  conflictPolicy: TransferConflictPolicy,
-): Promise<void> {
+/**
+ * Add a read-only session source browsing the restic repository at `path`
+ * on source `sourceId`; each snapshot appears as a top-level folder.
+ */
+export async function addResticSource(
+  sourceId: string,
+  path: string,
+  password: string,
+  name?: string,
+): Promise<StorageConfig> {
   try {
-    return await tauriInvoke("transfer_entries", {
-      fromSourceId,
-      toSourceId,
-      paths,
-      targetDir,
-      operation,
-      conflictPolicy,
+    return await tauriInvoke<StorageConfig>("add_restic_source", {
+      sourceId,
+      path,
+      password,
+      name: name ?? null,
     });
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function listStorages(): Promise<StorageConfig[]> {
+export interface CredentialSet {
+  id: string;
+  name: string;
+  keys: string[];
+  createdAt: string;
+  updatedAt: string;
+}
+
+export async function listCredentialSets(): Promise<CredentialSet[]> {
   try {
-    return await tauriInvoke<StorageConfig[]>("list_storages");
+    return await tauriInvoke<CredentialSet[]>("list_credential_sets");
   } catch (error) {
     return handleError(error);
   }
 }
 
-export async function addStorage(storage: StorageDraft): Promise<StorageConfig> {
+/**
+ * Create a credential set, or rotate it when `credentialId` is given. Storages
+ * opt in with a `credentialId` config key.
+ */
+export async function saveCredentialSet(
+  name: string,
+  values: Record<string, string>,
+  credentialId?: string,
+): Promise<CredentialSet> {
   try {
-    return await tauriInvoke<StorageConfig>("add_storage", { storage });
+    return await tauriInvoke<CredentialSet>("save_credential_set", {
+      credentialId: credentialId ?? null,
+      name,
+      values,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function removeCredentialSet(credentialId: string): Promise<void> {
+  try {
+    return await tauriInvoke("remove_credential_set", { credentialId });
   } catch (error) {
     return handleError(error);
   }
@@ -204,6 +1721,50 @@ export async function removeStorage(storageId: string): Promise<void> {
   }
 }
 
+/** A removed storage that can still be restored. */
+export interface RemovedStorage {
+  id: string;
+  name: string;
+  backend: string;
+  removed_at: string;
+}
+
+/** Storages removed in the last 30 days, newest first. */
+export async function listRemovedStorages(): Promise<RemovedStorage[]> {
+  try {
+    return await tauriInvoke<RemovedStorage[]>("list_removed_storages");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Restore a removed storage with its settings and credentials. */
+export async function restoreStorage(storageId: string): Promise<StorageConfig> {
+  try {
+    return await tauriInvoke<StorageConfig>("restore_storage", { storageId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Permanently forget a removed storage and its saved credentials. */
+export async function forgetRemovedStorage(storageId: string): Promise<void> {
+  try {
+    return await tauriInvoke("forget_removed_storage", { storageId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Cheap list/stat against a source that has not been saved yet. Never throws for connection problems. */
+export async function testSource(source: Source): Promise<ConnectionTest> {
+  try {
+    return await tauriInvoke<ConnectionTest>("test_source", { source });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
 export async function verifyStorage(storage: StorageDraft): Promise<StorageValidationResult> {
   try {
     return await tauriInvoke<StorageValidationResult>("verify_storage", { storage });
@@ -212,6 +1773,15 @@ export async function verifyStorage(storage: StorageDraft): Promise<StorageValid
   }
 }
 
+/** Add the remotes of an rclone config; `path` defaults to rclone's own config file. */
+export async function importRcloneConfig(path?: string): Promise<RcloneImportResult> {
+  try {
+    return await tauriInvoke<RcloneImportResult>("import_rclone_config", { path: path ?? null });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
 export async function importStorageConfig(
   request: ImportStoragesRequest,
 ): Promise<ImportStoragesResult> {
@@ -221,6 +1791,34 @@ export async function importStorageConfig(
         json: request.json,
         mode: request.mode,
         onConflict: request.onConflict,
+        dryRun: request.dryRun ?? false,
+      },
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Export storages, credential sets and settings (without secrets) for another machine. */
+export async function exportWorkspace(): Promise<ExportStoragesResult> {
+  try {
+    return await tauriInvoke<ExportStoragesResult>("export_workspace");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Import a workspace file; `replace` also takes its settings and preferences. */
+export async function importWorkspace(
+  request: ImportStoragesRequest,
+): Promise<WorkspaceImportResult> {
+  try {
+    return await tauriInvoke<WorkspaceImportResult>("import_workspace", {
+      request: {
+        json: request.json,
+        mode: request.mode,
+        onConflict: request.onConflict,
+        dryRun: request.dryRun ?? false,
       },
     });
   } catch (error) {
@@ -228,6 +1826,15 @@ export async function importStorageConfig(
   }
 }
 
+/** Check config files, credentials and caches; `probe` also contacts every enabled storage. */
+export async function runDoctor(probe = true): Promise<DoctorReport> {
+  try {
+    return await tauriInvoke<DoctorReport>("run_doctor", { probe });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
 export async function exportStorageConfig(
   includeSecrets: boolean,
 ): Promise<ExportStoragesResult> {
@@ -248,6 +1855,56 @@ export async function listStorageSchemas(): Promise<StorageKindSchema[]> {
   }
 }
 
+/** Show backend messages in `locale` (e.g. `navigator.language`); resolves to the locale used. */
+export async function setLocale(locale: string): Promise<string> {
+  try {
+    return await tauriInvoke<string>("set_locale", { locale });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Translate a backend message id such as `task-status-failed`. */
+export async function translateMessage(
+  messageId: string,
+  args: Record<string, string> = {},
+): Promise<string | null> {
+  try {
+    return await tauriInvoke<string | null>("translate_message", { messageId, args });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function discoverLocalRoots(): Promise<LocalRoot[]> {
+  try {
+    return await tauriInvoke<LocalRoot[]>("discover_local_roots");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Folders to offer during onboarding; empty once a storage is saved. */
+export async function getSuggestedSources(): Promise<SuggestedSource[]> {
+  try {
+    return await tauriInvoke<SuggestedSource[]>("get_suggested_sources");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** One-click add of a suggested folder as a Local storage. */
+export async function addSuggestedSource(suggestion: SuggestedSource): Promise<StorageConfig> {
+  return addStorage({
+    name: suggestion.name,
+    backend: "local",
+    config: { rootPath: suggestion.path },
+    enabled: true,
+    mcpExposed: true,
+    readOnly: false,
+  });
+}
+
 export async function getStorageCapabilities(
   storageId: string,
 ): Promise<StorageCapabilities> {
@@ -260,6 +1917,100 @@ export async function getStorageCapabilities(
   }
 }
 
+/** Everything a source supports, for hiding actions that would fail. */
+export async function getSourceCapabilities(sourceId: string): Promise<SourceCapabilities> {
+  try {
+    return await tauriInvoke<SourceCapabilities>("get_source_capabilities", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** How listings of a source get their metadata, and the measured latency. */
+export async function getListingDiagnostics(sourceId: string): Promise<ListingDiagnostics> {
+  try {
+    return await tauriInvoke<ListingDiagnostics>("get_listing_diagnostics", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Worker pool load, for diagnostics. */
+export async function getRuntimeStats(): Promise<RuntimeStats> {
+  try {
+    return await tauriInvoke<RuntimeStats>("get_runtime_stats");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Sidebar icon id, accent hue and (if enabled) favicon for a source. */
+export async function getSourceBranding(sourceId: string): Promise<SourceBranding> {
+  try {
+    return await tauriInvoke<SourceBranding>("get_source_branding", { sourceId });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function authorizeGdrive(
+  clientId: string,
+  clientSecret: string,
+  storageId?: string,
+): Promise<OAuthTokens> {
+  try {
+    return await tauriInvoke<OAuthTokens>("authorize_gdrive", {
+      clientId,
+      clientSecret,
+      storageId: storageId ?? null,
+    });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function getPreferences(): Promise<Preferences> {
+  try {
+    return await tauriInvoke<Preferences>("get_preferences");
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+export async function updatePreferences(preferences: Preferences): Promise<Preferences> {
+  try {
+    return await tauriInvoke<Preferences>("update_preferences", { preferences });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
+/** Fires when drives are mounted or ejected; removable drives may have been added/removed. */
+export function onVolumesChanged(handler: (changes: VolumeChanges) => void): Promise<UnlistenFn> {
+  return listen<VolumeChanges>("volumes-changed", (event) => handler(event.payload));
+}
+
+/** Fires when files are shared with the running app; fetch them with `takeSharedFiles`. */
+export function onSharedFiles(handler: () => void): Promise<UnlistenFn> {
+  return listen("shared-files", () => handler());
+}
+
+/** Fires when another process, such as a standalone MCP server, changed the saved storages. */
+export function onStoragesChanged(handler: () => void): Promise<UnlistenFn> {
+  return listen("storages-changed", () => handler());
+}
+
+/** Fires when a storage's OAuth tokens could not be refreshed and it has to be authorized again. */
+export function onReauthRequired(handler: (reauth: ReauthRequired) => void): Promise<UnlistenFn> {
+  return listen<ReauthRequired>("reauth-required", (event) => handler(event.payload));
+}
+
+export function onTransferProgress(
+  handler: (progress: TransferProgress) => void,
+): Promise<UnlistenFn> {
+  return listen<TransferProgress>("transfer-progress", (event) => handler(event.payload));
+}
+
 export async function getMcpSettings(): Promise<McpSettings> {
   try {
     return await tauriInvoke<McpSettings>("get_mcp_settings");
@@ -321,6 +2072,8 @@ export interface FileVersion {
   size_bytes: number | null;
   modified_at: string | null;
   etag: string | null;
+  /** Whether this is the version plain reads return, when the backend says. */
+  is_current?: boolean | null;
 }
 
 export interface ListVersionsResult {
@@ -364,6 +2117,22 @@ export async function readFileVersion(
   }
 }
 
+/**
+ * Make an earlier version the current one again. It is written back as a new
+ * version, so the versions in between stay. Resolves with the bytes written.
+ */
+export async function restoreFileVersion(
+  sourceId: string,
+  path: string,
+  version: string,
+): Promise<number> {
+  try {
+    return await tauriInvoke<number>("restore_version", { sourceId, path, version });
+  } catch (error) {
+    return handleError(error);
+  }
+}
+
 export interface DeleteVersionResult {
   path: string;
   version: string;
@@ -1,8 +1,12 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import { App } from "./App";
+import { setLocale } from "./lib/api";
 import "./index.css";
 
+// Backend errors and storage labels follow the system language.
+void setLocale(navigator.language).catch(() => undefined);
+
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
     <App />
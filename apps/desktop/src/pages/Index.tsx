@@ -3,18 +3,26 @@ import { lazy, Suspense, useCallback, useEffect, useState } from "react";
 import { FileBrowser } from "@/components/FileBrowser";
 import { StorageSidebar } from "@/components/StorageSidebar";
 import { ResizableHandle, ResizablePanel, ResizablePanelGroup } from "@/components/ui/resizable";
+import { ToastAction } from "@/components/ui/toast";
 import { toast } from "@/hooks/use-toast";
 import {
   addStorage as apiAddStorage,
+  addSessionStorage as apiAddSessionStorage,
   exportStorageConfig,
   getMcpClientSnippets,
   getMcpStatus,
   listMcpTools,
   importStorageConfig,
+  intakeSharedFiles,
   listStorages,
+  onReauthRequired,
+  onSharedFiles,
+  onStoragesChanged,
+  onVolumesChanged,
   removeStorage as apiRemoveStorage,
   startMcpHttp,
   stopMcpHttp,
+  takeSharedFiles,
   updateMcpSettings,
   updateStorage as apiUpdateStorage,
   verifyStorage as apiVerifyStorage,
@@ -44,6 +52,16 @@ const McpSettingsDialog = lazy(() =>
     default: module.McpSettingsDialog,
   })),
 );
+const SharedFilesDialog = lazy(() =>
+  import("@/components/SharedFilesDialog").then((module) => ({
+    default: module.SharedFilesDialog,
+  })),
+);
+const SourceMaintenanceDialog = lazy(() =>
+  import("@/components/SourceMaintenanceDialog").then((module) => ({
+    default: module.SourceMaintenanceDialog,
+  })),
+);
 const StorageConfigEditorDialog = lazy(() =>
   import("@/components/StorageConfigEditorDialog").then((module) => ({
     default: module.StorageConfigEditorDialog,
@@ -56,14 +74,38 @@ const BACKEND_TO_TYPE: Record<StorageBackend, StorageType> = {
   azure_blob: "azure-blob",
   webdav: "webdav",
   gcs: "gcs",
+  ftp: "ftp",
+  gdrive: "google-drive",
+  b2: "backblaze-b2",
+  hdfs: "hdfs",
+  ipfs: "ipfs",
+  ipmfs: "ipmfs",
+  http: "http",
 };
 
+const S3_PROVIDER_TO_TYPE: Record<string, StorageType> = {
+  minio: "minio",
+  r2: "cloudflare-r2",
+  wasabi: "wasabi",
+  digitalocean: "digitalocean-spaces",
+};
+
+function storageTypeFor(storage: StorageRecordWire): StorageType {
+  if (storage.backend === "s3" && isRecord(storage.config)) {
+    const provider = storage.config.provider;
+    if (typeof provider === "string" && S3_PROVIDER_TO_TYPE[provider]) {
+      return S3_PROVIDER_TO_TYPE[provider];
+    }
+  }
+  return BACKEND_TO_TYPE[storage.backend] ?? "local-fs";
+}
+
 function mapWireStorage(storage: StorageRecordWire): StorageConfig {
   return {
     id: storage.id,
     name: storage.name,
     backend: storage.backend,
-    type: BACKEND_TO_TYPE[storage.backend] ?? "local-fs",
+    type: storageTypeFor(storage),
     config: isRecord(storage.config) ? storage.config : {},
     enabled: storage.enabled,
     mcpExposed: storage.mcp_exposed,
@@ -71,6 +113,8 @@ function mapWireStorage(storage: StorageRecordWire): StorageConfig {
     connected: true,
     createdAt: storage.created_at,
     updatedAt: storage.updated_at,
+    ephemeral: storage.ephemeral ?? false,
+    capabilities: storage.capabilities,
   };
 }
 
@@ -110,6 +154,8 @@ interface StorageRecordWire {
   read_only: boolean;
   created_at: string;
   updated_at: string;
+  ephemeral?: boolean;
+  capabilities?: string[];
 }
 
 interface McpSettingsWire {
@@ -139,11 +185,13 @@ const Index = () => {
   const [editingStorage, setEditingStorage] = useState<StorageConfig | null>(null);
   const [isStorageConfigEditorOpen, setIsStorageConfigEditorOpen] = useState(false);
   const [isMcpDialogOpen, setIsMcpDialogOpen] = useState(false);
+  const [maintenanceStorageId, setMaintenanceStorageId] = useState<string | null>(null);
   const [mcpStatus, setMcpStatus] = useState<McpRuntimeStatus | null>(null);
   const [mcpSnippets, setMcpSnippets] = useState<McpClientSnippets | null>(null);
   const [mcpTools, setMcpTools] = useState<McpToolDefinition[]>([]);
   const [isPreviewVisible, setIsPreviewVisible] = useState(false);
   const [isSidebarOpen, setIsSidebarOpen] = useState(true);
+  const [sharedFiles, setSharedFiles] = useState<string[]>([]);
 
   const reloadMcpStatus = useCallback(async () => {
     try {
@@ -193,6 +241,56 @@ const Index = () => {
     void reloadStorages();
   }, [reloadStorages]);
 
+  useEffect(() => {
+    const unlisten = onVolumesChanged(() => {
+      void reloadStorages();
+    });
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, [reloadStorages]);
+
+  useEffect(() => {
+    const unlisten = onStoragesChanged(() => {
+      void reloadStorages();
+    });
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, [reloadStorages]);
+
+  useEffect(() => {
+    const unlisten = onReauthRequired((reauth) => {
+      toast({
+        title: `Sign in to ${reauth.storageName} again`,
+        description: reauth.reason,
+        variant: "destructive",
+      });
+    });
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  useEffect(() => {
+    const loadSharedFiles = () => {
+      void takeSharedFiles()
+        .then((paths) =>
+          setSharedFiles((current) => [
+            ...current,
+            ...paths.filter((path) => !current.includes(path)),
+          ]),
+        )
+        .catch((error) => console.error("Failed to load shared files", error));
+    };
+    loadSharedFiles();
+    // Files shared while the app is already open.
+    const unlisten = onSharedFiles(loadSharedFiles);
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
   useEffect(() => {
     if (!isMcpDialogOpen) return;
     void reloadMcpStatus();
@@ -226,6 +324,20 @@ const Index = () => {
     }
   }, [isPreviewVisible]);
 
+  const handleUploadSharedFiles = async (storageId: string, prefix: string) => {
+    const count = await intakeSharedFiles(storageId, prefix, sharedFiles);
+    setSharedFiles([]);
+    setSelectedStorage(storageId);
+    setStorageRefreshTick((current) => ({
+      ...current,
+      [storageId]: (current[storageId] ?? 0) + 1,
+    }));
+    toast({
+      title: "Upload complete",
+      description: `Uploaded ${count} shared item(s).`,
+    });
+  };
+
   const handleAddStorage = async (draft: StorageDraft) => {
     try {
       const added = (await apiAddStorage(
@@ -247,6 +359,27 @@ const Index = () => {
     }
   };
 
+  const handleConnectStorage = async (draft: StorageDraft) => {
+    try {
+      const added = (await apiAddSessionStorage(
+        mapDraftForBackend(draft),
+      )) as unknown as StorageRecordWire;
+      await reloadStorages();
+      setSelectedStorage(added.id);
+      toast({
+        title: "Storage connected",
+        description: `"${draft.name}" is available until you quit Infimount and was not saved.`,
+      });
+    } catch (error: unknown) {
+      toast({
+        title: "Failed to connect storage",
+        description: error instanceof Error ? error.message : String(error),
+        variant: "destructive",
+      });
+      throw error;
+    }
+  };
+
   const handleEditStorage = (id: string) => {
     const storage = storages.find((item) => item.id === id) ?? null;
     if (!storage) return;
@@ -331,15 +464,36 @@ const Index = () => {
         const text = (loadEvent.target?.result as string) ?? "";
         void (async () => {
           try {
-            const result = await importStorageConfig({
-              json: text,
-              mode: "replace",
-              onConflict: "overwrite",
-            });
-            await reloadStorages();
+            // Profiles come from other machines: merge them in, and check
+            // for clashes with existing storages before changing anything.
+            const request = { json: text, mode: "merge", onConflict: "rename" } as const;
+            const preview = await importStorageConfig({ ...request, dryRun: true });
+            const apply = async () => {
+              const result = await importStorageConfig(request);
+              await reloadStorages();
+              toast({
+                title: "Import successful",
+                description: `Imported ${result.imported} storage configuration(s).`,
+              });
+            };
+            if (preview.conflicts.length === 0) {
+              await apply();
+              return;
+            }
             toast({
-              title: "Import successful",
-              description: `Imported ${result.imported} storage configuration(s).`,
+              title: `${preview.conflicts.length} imported storage(s) clash with existing ones`,
+              description: preview.conflicts
+                .map((conflict) =>
+                  conflict.renamed_to
+                    ? `${conflict.name} → ${conflict.renamed_to}`
+                    : `${conflict.name} gets a new ID`,
+                )
+                .join(", "),
+              action: (
+                <ToastAction altText="Import anyway" onClick={() => void apply()}>
+                  Import anyway
+                </ToastAction>
+              ),
             });
           } catch (error: unknown) {
             toast({
@@ -460,6 +614,7 @@ const Index = () => {
   };
 
   const currentStorage = storages.find((storage) => storage.id === selectedStorage);
+  const maintenanceStorage = storages.find((storage) => storage.id === maintenanceStorageId);
 
   const toggleSidebar = () => setIsSidebarOpen((current) => !current);
   const closeSidebar = () => setIsSidebarOpen(false);
@@ -489,6 +644,7 @@ const Index = () => {
                 onEditStorage={handleEditStorage}
                 onDeleteStorage={handleDeleteStorage}
                 onRefreshStorage={handleRefreshStorage}
+                onOpenMaintenance={setMaintenanceStorageId}
                 onImportStorages={handleImportStorages}
                 onEditStorageConfig={() => setIsStorageConfigEditorOpen(true)}
                 onExportStorages={handleExportStorages}
@@ -524,6 +680,7 @@ const Index = () => {
             onEditStorage={handleEditStorage}
             onDeleteStorage={handleDeleteStorage}
             onRefreshStorage={handleRefreshStorage}
+            onOpenMaintenance={setMaintenanceStorageId}
             onImportStorages={handleImportStorages}
             onEditStorageConfig={() => setIsStorageConfigEditorOpen(true)}
             onExportStorages={handleExportStorages}
@@ -538,6 +695,7 @@ const Index = () => {
               <FileBrowser
                 sourceId={currentStorage.id}
                 storageName={currentStorage.name}
+                backend={currentStorage.backend}
                 refreshTick={storageRefreshTick[currentStorage.id] ?? 0}
                 onPreviewVisibilityChange={setIsPreviewVisible}
                 onToggleSidebar={toggleSidebar}
@@ -561,6 +719,7 @@ const Index = () => {
               if (!open) setEditingStorage(null);
             }}
             onAdd={handleAddStorage}
+            onConnect={handleConnectStorage}
             onUpdate={handleUpdateStorage}
             onVerify={handleVerifyStorage}
             initialStorage={editingStorage ?? undefined}
@@ -580,6 +739,29 @@ const Index = () => {
           />
         ) : null}
 
+        {sharedFiles.length > 0 && !isStoragesLoading ? (
+          <SharedFilesDialog
+            open
+            onOpenChange={(open) => {
+              if (!open) setSharedFiles([]);
+            }}
+            paths={sharedFiles}
+            storages={storages}
+            defaultStorageId={selectedStorage}
+            onUpload={handleUploadSharedFiles}
+          />
+        ) : null}
+
+        {maintenanceStorage ? (
+          <SourceMaintenanceDialog
+            open
+            onOpenChange={(open) => {
+              if (!open) setMaintenanceStorageId(null);
+            }}
+            storage={maintenanceStorage}
+          />
+        ) : null}
+
         {isStorageConfigEditorOpen ? (
           <StorageConfigEditorDialog
             open={isStorageConfigEditorOpen}
@@ -3,7 +3,14 @@ export type SourceKind =
   | "s3"
   | "webdav"
   | "azure_blob"
-  | "gcs";
+  | "gcs"
+  | "ftp"
+  | "gdrive"
+  | "b2"
+  | "hdfs"
+  | "ipfs"
+  | "ipmfs"
+  | "http";
 
 export interface Source {
   id: string;
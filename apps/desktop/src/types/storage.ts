@@ -1,5 +1,33 @@
-export type StorageType = "aws-s3" | "azure-blob" | "webdav" | "gcs" | "local-fs";
-export type StorageBackend = "s3" | "azure_blob" | "webdav" | "gcs" | "local";
+export type StorageType =
+  | "aws-s3"
+  | "minio"
+  | "cloudflare-r2"
+  | "wasabi"
+  | "digitalocean-spaces"
+  | "azure-blob"
+  | "webdav"
+  | "gcs"
+  | "ftp"
+  | "google-drive"
+  | "backblaze-b2"
+  | "hdfs"
+  | "ipfs"
+  | "ipmfs"
+  | "http"
+  | "local-fs";
+export type StorageBackend =
+  | "s3"
+  | "azure_blob"
+  | "webdav"
+  | "gcs"
+  | "ftp"
+  | "gdrive"
+  | "b2"
+  | "hdfs"
+  | "ipfs"
+  | "ipmfs"
+  | "http"
+  | "local";
 export type McpTransport = "stdio" | "http";
 
 export interface StorageDraft {
@@ -15,6 +43,10 @@ export interface StorageConfig extends StorageDraft {
   id: string;
   type: StorageType;
   connected: boolean;
+  /** Session-only storage (e.g. an inserted USB drive); never saved. */
+  ephemeral?: boolean;
+  /** Operations probed when the storage was added (`"rename"`, ...). */
+  capabilities?: string[];
   createdAt: string;
   updatedAt: string;
 }
@@ -66,6 +98,41 @@ export interface StorageCapabilities {
   list_with_versions: boolean;
   read_with_version: boolean;
   delete_with_version: boolean;
+  versioning_disabled?: boolean;
+  delete_to_trash?: boolean;
+  links?: boolean;
+}
+
+/** Everything a source supports; mutating actions are false on read-only sources. */
+export interface SourceCapabilities {
+  read_only: boolean;
+  list: boolean;
+  stat: boolean;
+  read: boolean;
+  write: boolean;
+  append: boolean;
+  create_dir: boolean;
+  delete: boolean;
+  copy: boolean;
+  rename: boolean;
+  presign_read: boolean;
+  presign_write: boolean;
+  list_with_versions: boolean;
+  read_with_version: boolean;
+  delete_with_version: boolean;
+  os_trash: boolean;
+  trash: boolean;
+  links: boolean;
+}
+
+/** How a source is drawn in the sidebar; see `get_source_branding`. */
+export interface SourceBranding {
+  /** Storage type id, e.g. "minio" or "aws-s3", or "demo". */
+  icon: StorageType | "demo";
+  /** Hue (0-359) derived from the endpoint host. */
+  accent_hue: number;
+  /** The server's favicon as a data URL. */
+  favicon?: string;
 }
 
 export interface FileItem {
@@ -76,5 +143,14 @@ export interface FileItem {
   modified: Date | null;
   owner?: string;
   extension?: string;
+  onlineOnly?: boolean;
+  /** Anyone can read this object (S3 and GCS sources). */
+  publiclyReadable?: boolean;
+  contentType?: string;
+  etag?: string;
+  storageClass?: string;
+  userMetadata?: Record<string, string>;
+  /** Status in the enclosing git working tree (Local sources). */
+  gitStatus?: "modified" | "untracked" | "ignored";
   capabilities?: StorageCapabilities;
 }
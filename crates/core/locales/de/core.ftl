@@ -0,0 +1,95 @@
+## Errors
+
+error-source-not-found = Quelle nicht gefunden: { $id }
+error-unsupported-source-kind = Nicht unterstützte Quellenart: { $kind }
+error-config = Konfigurationsfehler: { $detail }
+error-unsupported = Nicht unterstützter Vorgang: { $detail }
+error-read-only-source = { $source } ist schreibgeschützt
+error-too-many-entries = { $path } enthält mehr als { $limit } Einträge; nutze die Suche oder blättere seitenweise
+error-timeout = Zeitüberschreitung: { $operation }
+error-cancelled = Abgebrochen
+error-confirmation-required = { $action ->
+    [download] Der Download von { $size } überschreitet die Grenze von { $threshold }; zum Fortfahren bestätigen
+   *[other] Die Vorschau einer Datei mit { $size } überschreitet die Grenze von { $threshold }; zum Fortfahren bestätigen
+}
+error-file-too-large = { $path } ist zu groß, um hier geöffnet zu werden ({ $size }, Grenze { $limit }); lade sie stattdessen herunter
+error-not-found = Nicht gefunden: { $detail }
+error-permission-denied = Zugriff verweigert: { $detail }
+error-already-exists = Existiert bereits: { $detail }
+error-conflict = Anderswo geändert: { $detail }
+error-storage = Speicherfehler: { $detail }
+error-io = E/A-Fehler: { $detail }
+error-serde = Ungültige Daten: { $detail }
+
+## Transfer task status
+
+task-status-completed = Abgeschlossen
+task-status-skipped = Übersprungen
+task-status-failed = Fehlgeschlagen
+
+## Transfer progress, read out by screen readers
+
+progress-preparing = Elemente werden gezählt
+progress-paused = Pausiert
+progress-copying = { $item } wird kopiert
+progress-moving = { $item } wird verschoben
+progress-uploading = { $item } wird hochgeladen
+progress-downloading = { $item } wird heruntergeladen
+progress-items = { $done } von { $total } Elementen
+progress-items-no-total = { $done ->
+    [one] 1 Element erledigt
+   *[other] { $done } Elemente erledigt
+}
+progress-eta-seconds = { $count ->
+    [one] noch etwa 1 Sekunde
+   *[other] noch etwa { $count } Sekunden
+}
+progress-eta-minutes = { $count ->
+    [one] noch etwa 1 Minute
+   *[other] noch etwa { $count } Minuten
+}
+progress-finished = Fertig: { $completed } abgeschlossen, { $skipped } übersprungen, { $failed } fehlgeschlagen
+
+## Suggested Local sources during onboarding
+
+suggested-source-home = Persönlicher Ordner
+suggested-source-documents = Dokumente
+suggested-source-downloads = Downloads
+suggested-source-desktop = Schreibtisch
+suggested-source-pictures = Bilder
+
+## Storage schemas
+
+schema-kind-local-fs = Lokales Dateisystem
+schema-kind-ipfs = IPFS-Gateway (nur lesen)
+schema-kind-http = HTTP (nur lesen)
+schema-field-accessKeyId = Zugriffsschlüssel-ID
+schema-field-secretAccessKey = Geheimer Zugriffsschlüssel
+schema-field-accessToken = Zugriffstoken
+schema-field-refreshToken = Aktualisierungstoken
+schema-field-accountId = Konto-ID
+schema-field-accountKey = Kontoschlüssel
+schema-field-accountName = Kontoname
+schema-field-applicationKeyId = Anwendungsschlüssel-ID
+schema-field-applicationKey = Anwendungsschlüssel
+schema-field-bucket = Bucket
+schema-backblaze-b2-bucket = Bucket-Name
+schema-field-bucketId = Bucket-ID
+schema-field-bucketName = Bucket-Name
+schema-digitalocean-spaces-bucketName = Space-Name
+schema-field-containerName = Container-Name
+schema-field-credential = Dienstkonto-JSON
+schema-field-endpoint = Endpunkt-URL
+schema-http-endpoint = Basis-URL
+schema-field-password = Passwort
+schema-field-username = Benutzername
+schema-field-userName = Benutzername
+schema-field-region = Region
+schema-minio-region = Region (Standard: us-east-1)
+schema-wasabi-region = Region (Standard: us-east-1)
+schema-digitalocean-spaces-region = Region (Standard: nyc3)
+schema-field-rootPath = Stammpfad
+schema-local-fs-rootPath = Stammordner
+schema-field-serverUrl = Server-URL
+schema-field-tls = TLS verwenden (true/false)
+schema-field-token = Bearer-Token
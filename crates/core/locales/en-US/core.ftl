@@ -0,0 +1,63 @@
+# Strings produced by infimount_core. Message ids are stable; the UI may
+# match on them. Storage schema labels come from storage_schemas.json and
+# only need entries here in other languages.
+
+## Errors
+
+error-source-not-found = Source not found: { $id }
+error-unsupported-source-kind = Unsupported source kind: { $kind }
+error-config = Configuration error: { $detail }
+error-unsupported = Unsupported operation: { $detail }
+error-read-only-source = { $source } is read-only
+error-too-many-entries = { $path } has more than { $limit } entries; use search or page through it
+error-timeout = Timed out: { $operation }
+error-cancelled = Cancelled
+error-confirmation-required = { $action ->
+    [download] Downloading { $size } is over the { $threshold } limit; confirm to continue
+   *[other] Previewing a { $size } file is over the { $threshold } limit; confirm to continue
+}
+error-file-too-large = { $path } is too large to open here ({ $size }, limit { $limit }); download it instead
+error-not-found = Not found: { $detail }
+error-permission-denied = Permission denied: { $detail }
+error-already-exists = Already exists: { $detail }
+error-conflict = Changed elsewhere: { $detail }
+error-storage = Storage error: { $detail }
+error-io = I/O error: { $detail }
+error-serde = Invalid data: { $detail }
+
+## Transfer task status
+
+task-status-completed = Completed
+task-status-skipped = Skipped
+task-status-failed = Failed
+
+## Transfer progress, read out by screen readers
+
+progress-preparing = Counting items
+progress-paused = Paused
+progress-copying = Copying { $item }
+progress-moving = Moving { $item }
+progress-uploading = Uploading { $item }
+progress-downloading = Downloading { $item }
+progress-items = { $done } of { $total } items
+progress-items-no-total = { $done ->
+    [one] 1 item done
+   *[other] { $done } items done
+}
+progress-eta-seconds = { $count ->
+    [one] about 1 second left
+   *[other] about { $count } seconds left
+}
+progress-eta-minutes = { $count ->
+    [one] about 1 minute left
+   *[other] about { $count } minutes left
+}
+progress-finished = Done: { $completed } completed, { $skipped } skipped, { $failed } failed
+
+## Suggested Local sources during onboarding
+
+suggested-source-home = Home
+suggested-source-documents = Documents
+suggested-source-downloads = Downloads
+suggested-source-desktop = Desktop
+suggested-source-pictures = Pictures
@@ -0,0 +1,553 @@
+//! Who can read a bucket and its objects: ACL grants, public access
+//! blocks, bucket policy or IAM status and CORS rules of S3 and GCS
+//! sources, so accidental exposure can be audited from the app.
+//!
+//! An object counts as public when anonymous or any-authenticated users
+//! can read it, through its own ACL or through the bucket policy, after
+//! the bucket's public access settings are applied.
+
+use std::collections::BTreeMap;
+
+use futures::stream::{self, StreamExt};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::gcs_api::GcsClient;
+use crate::models::{CoreError, ErrorCode, Result};
+use crate::s3_api::{parse_xml, S3Client};
+
+/// Objects whose ACLs are fetched concurrently.
+const CHECK_CONCURRENCY: usize = 8;
+/// Paths checked per [`AccessClient::public_objects`] call; listings past
+/// this are left unflagged rather than costing thousands of requests.
+pub const MAX_OBJECT_CHECKS: usize = 500;
+
+const S3_ALL_USERS: &str = "http://acs.amazonaws.com/groups/global/AllUsers";
+const S3_AUTHENTICATED_USERS: &str = "http://acs.amazonaws.com/groups/global/AuthenticatedUsers";
+const GCS_PUBLIC_MEMBERS: [&str; 2] = ["allUsers", "allAuthenticatedUsers"];
+/// IAM roles that let a member read object data.
+const GCS_OBJECT_READ_ROLES: [&str; 6] = [
+    "roles/storage.objectViewer",
+    "roles/storage.objectUser",
+    "roles/storage.objectAdmin",
+    "roles/storage.admin",
+    "roles/storage.legacyObjectReader",
+    "roles/storage.legacyObjectOwner",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grant {
+    /// Display name, canonical ID, e-mail, group URI or IAM member.
+    pub grantee: String,
+    /// ACL permission or role (`READ`, `READER`, `roles/storage.admin`).
+    pub permission: String,
+    /// Granted to everyone or to any authenticated user.
+    pub public: bool,
+}
+
+impl Grant {
+    /// Whether this public grant exposes object data.
+    fn exposes_objects(&self) -> bool {
+        self.public
+            && (matches!(
+                self.permission.as_str(),
+                "READ" | "FULL_CONTROL" | "READER" | "WRITER" | "OWNER"
+            ) || GCS_OBJECT_READ_ROLES.contains(&self.permission.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorsRule {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub expose_headers: Vec<String>,
+    pub max_age_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketAccess {
+    pub owner: Option<String>,
+    /// Bucket ACL grants, plus IAM bindings on GCS.
+    pub grants: Vec<Grant>,
+    /// S3 Block Public Access with all four settings on, or GCS public
+    /// access prevention enforced. `None` when the provider does not say.
+    pub public_access_blocked: Option<bool>,
+    /// Object ACLs do not grant access (S3 `IgnorePublicAcls` for public
+    /// grants, GCS uniform bucket-level access for all of them).
+    pub object_acls_ignored: bool,
+    /// The bucket policy (S3) or IAM policy (GCS) lets anyone read every
+    /// object. `None` when the provider does not say.
+    pub policy_public: Option<bool>,
+    pub cors: Vec<CorsRule>,
+    /// Anyone can list the bucket or read every object in it.
+    pub public: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectAccess {
+    pub path: String,
+    pub owner: Option<String>,
+    pub grants: Vec<Grant>,
+    pub public: bool,
+}
+
+/// Bucket API client of an S3 or GCS source. Paths are relative to the
+/// source root, as in listings.
+pub enum AccessClient {
+    S3(S3Client),
+    Gcs {
+        client: Box<GcsClient>,
+        root: String,
+    },
+}
+
+impl AccessClient {
+    pub async fn bucket_access(&self) -> Result<BucketAccess> {
+        match self {
+            AccessClient::S3(client) => s3_bucket_access(client).await,
+            AccessClient::Gcs { client, .. } => gcs_bucket_access(client).await,
+        }
+    }
+
+    pub async fn object_access(&self, path: &str) -> Result<ObjectAccess> {
+        let bucket = self.bucket_access().await?;
+        self.object_access_in(&bucket, path).await
+    }
+
+    /// The files among `paths` (at most [`MAX_OBJECT_CHECKS`]) anyone can
+    /// read. Objects whose ACL cannot be read are left out.
+    pub async fn public_objects(&self, paths: Vec<String>) -> Result<Vec<String>> {
+        let bucket = self.bucket_access().await?;
+        let files = paths
+            .into_iter()
+            .filter(|path| !path.ends_with('/'))
+            .take(MAX_OBJECT_CHECKS);
+        if bucket.policy_public == Some(true) {
+            return Ok(files.collect());
+        }
+        if bucket.object_acls_ignored {
+            return Ok(Vec::new());
+        }
+        let bucket = &bucket;
+        let mut public: Vec<String> = stream::iter(files)
+            .map(|path| async move {
+                match self.object_access_in(bucket, &path).await {
+                    Ok(access) if access.public => Some(path),
+                    _ => None,
+                }
+            })
+            .buffer_unordered(CHECK_CONCURRENCY)
+            .filter_map(|path| async move { path })
+            .collect()
+            .await;
+        public.sort();
+        Ok(public)
+    }
+
+    async fn object_access_in(&self, bucket: &BucketAccess, path: &str) -> Result<ObjectAccess> {
+        let (owner, grants) = match self {
+            AccessClient::S3(client) => {
+                let key = path.trim_start_matches('/');
+                let body = client
+                    .request(Method::GET, key, &[("acl", "")], &[], Vec::new())
+                    .await?;
+                s3_acl(&body)?
+            }
+            // Under uniform access objects have no ACL to read.
+            AccessClient::Gcs { .. } if bucket.object_acls_ignored => (None, Vec::new()),
+            AccessClient::Gcs { client, root } => {
+                let name = format!(
+                    "{}{}",
+                    root.trim_start_matches('/'),
+                    path.trim_start_matches('/')
+                );
+                let acl: GcsAcl = client.get(Some(&name), "acl", &[]).await?;
+                (
+                    None,
+                    acl.items.into_iter().map(GcsAclEntry::grant).collect(),
+                )
+            }
+        };
+        let public = bucket.policy_public == Some(true)
+            || !bucket.object_acls_ignored && grants.iter().any(Grant::exposes_objects);
+        Ok(ObjectAccess {
+            path: path.to_string(),
+            owner,
+            grants,
+            public,
+        })
+    }
+}
+
+/// `Ok(Some(default))` for a sub-resource the bucket has not configured,
+/// `Ok(None)` for one the provider does not implement.
+fn configured_or_default<T: Default>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(error) if error.code() == ErrorCode::NotFound => Ok(Some(T::default())),
+        Err(error) if error.code() == ErrorCode::Unsupported => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct AccessControlPolicy {
+    owner: Option<S3Grantee>,
+    access_control_list: S3GrantList,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct S3GrantList {
+    #[serde(rename = "Grant")]
+    grants: Vec<S3Grant>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct S3Grant {
+    grantee: S3Grantee,
+    permission: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct S3Grantee {
+    #[serde(rename = "ID")]
+    id: Option<String>,
+    display_name: Option<String>,
+    #[serde(rename = "URI")]
+    uri: Option<String>,
+    email_address: Option<String>,
+}
+
+impl S3Grantee {
+    fn name(self) -> String {
+        self.display_name
+            .filter(|name| !name.is_empty())
+            .or(self.uri)
+            .or(self.email_address)
+            .or(self.id)
+            .unwrap_or_default()
+    }
+}
+
+fn s3_acl(body: &str) -> Result<(Option<String>, Vec<Grant>)> {
+    let policy: AccessControlPolicy = parse_xml(body)?;
+    let grants = policy
+        .access_control_list
+        .grants
+        .into_iter()
+        .map(|grant| {
+            let public = matches!(
+                grant.grantee.uri.as_deref(),
+                Some(S3_ALL_USERS | S3_AUTHENTICATED_USERS)
+            );
+            Grant {
+                grantee: grant.grantee.name(),
+                permission: grant.permission,
+                public,
+            }
+        })
+        .collect();
+    Ok((policy.owner.map(S3Grantee::name), grants))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct PublicAccessBlockConfiguration {
+    block_public_acls: bool,
+    ignore_public_acls: bool,
+    block_public_policy: bool,
+    restrict_public_buckets: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct PolicyStatus {
+    is_public: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CorsConfiguration {
+    #[serde(rename = "CORSRule")]
+    rules: Vec<S3CorsRule>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct S3CorsRule {
+    #[serde(rename = "AllowedOrigin")]
+    allowed_origins: Vec<String>,
+    #[serde(rename = "AllowedMethod")]
+    allowed_methods: Vec<String>,
+    #[serde(rename = "AllowedHeader")]
+    allowed_headers: Vec<String>,
+    #[serde(rename = "ExposeHeader")]
+    expose_headers: Vec<String>,
+    #[serde(rename = "MaxAgeSeconds")]
+    max_age_secs: Option<u64>,
+}
+
+async fn s3_sub_resource<T: Default + serde::de::DeserializeOwned>(
+    client: &S3Client,
+    name: &str,
+) -> Result<Option<T>> {
+    let body = client
+        .request(Method::GET, "", &[(name, "")], &[], Vec::new())
+        .await;
+    configured_or_default(body.and_then(|body| parse_xml(&body)))
+}
+
+async fn s3_bucket_access(client: &S3Client) -> Result<BucketAccess> {
+    let body = client
+        .request(Method::GET, "", &[("acl", "")], &[], Vec::new())
+        .await?;
+    let (owner, grants) = s3_acl(&body)?;
+    let block: Option<PublicAccessBlockConfiguration> =
+        s3_sub_resource(client, "publicAccessBlock").await?;
+    let status: Option<PolicyStatus> = s3_sub_resource(client, "policyStatus").await?;
+    let cors: Option<CorsConfiguration> = s3_sub_resource(client, "cors").await?;
+    Ok(s3_evaluate(owner, grants, block, status, cors))
+}
+
+fn s3_evaluate(
+    owner: Option<String>,
+    grants: Vec<Grant>,
+    block: Option<PublicAccessBlockConfiguration>,
+    status: Option<PolicyStatus>,
+    cors: Option<CorsConfiguration>,
+) -> BucketAccess {
+    let block = block.unwrap_or_default();
+    let object_acls_ignored = block.ignore_public_acls;
+    let policy_public = status.map(|status| status.is_public && !block.restrict_public_buckets);
+    let public = policy_public == Some(true)
+        || !object_acls_ignored && grants.iter().any(Grant::exposes_objects);
+    BucketAccess {
+        owner,
+        grants,
+        public_access_blocked: Some(
+            block.block_public_acls
+                && block.ignore_public_acls
+                && block.block_public_policy
+                && block.restrict_public_buckets,
+        ),
+        object_acls_ignored,
+        policy_public,
+        cors: cors
+            .unwrap_or_default()
+            .rules
+            .into_iter()
+            .map(|rule| CorsRule {
+                allowed_origins: rule.allowed_origins,
+                allowed_methods: rule.allowed_methods,
+                allowed_headers: rule.allowed_headers,
+                expose_headers: rule.expose_headers,
+                max_age_secs: rule.max_age_secs,
+            })
+            .collect(),
+        public,
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct GcsBucket {
+    owner: Option<GcsOwner>,
+    acl: Vec<GcsAclEntry>,
+    cors: Vec<GcsCorsRule>,
+    iam_configuration: GcsIamConfiguration,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GcsOwner {
+    entity: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GcsAcl {
+    items: Vec<GcsAclEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GcsAclEntry {
+    entity: String,
+    role: String,
+}
+
+impl GcsAclEntry {
+    fn grant(self) -> Grant {
+        Grant {
+            public: GCS_PUBLIC_MEMBERS.contains(&self.entity.as_str()),
+            grantee: self.entity,
+            permission: self.role,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct GcsCorsRule {
+    origin: Vec<String>,
+    method: Vec<String>,
+    response_header: Vec<String>,
+    max_age_seconds: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct GcsIamConfiguration {
+    uniform_bucket_level_access: GcsEnabled,
+    public_access_prevention: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GcsEnabled {
+    enabled: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GcsIamPolicy {
+    bindings: Vec<GcsBinding>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GcsBinding {
+    role: String,
+    members: Vec<String>,
+}
+
+async fn gcs_bucket_access(client: &GcsClient) -> Result<BucketAccess> {
+    let bucket: GcsBucket = client.get(None, "", &[("projection", "full")]).await?;
+    let iam: Option<GcsIamPolicy> = configured_or_default(client.get(None, "iam", &[]).await)?;
+    Ok(gcs_evaluate(bucket, iam))
+}
+
+fn gcs_evaluate(bucket: GcsBucket, iam: Option<GcsIamPolicy>) -> BucketAccess {
+    let prevented =
+        bucket.iam_configuration.public_access_prevention.as_deref() == Some("enforced");
+    let object_acls_ignored = bucket.iam_configuration.uniform_bucket_level_access.enabled;
+    let mut grants: Vec<Grant> = bucket.acl.into_iter().map(GcsAclEntry::grant).collect();
+    // One grant per member, keeping IAM roles apart from ACL roles.
+    let mut members: BTreeMap<(String, String), bool> = BTreeMap::new();
+    for binding in iam.iter().flat_map(|iam| &iam.bindings) {
+        for member in &binding.members {
+            let public = GCS_PUBLIC_MEMBERS.contains(&member.as_str());
+            members.insert((member.clone(), binding.role.clone()), public);
+        }
+    }
+    grants.extend(
+        members
+            .into_iter()
+            .map(|((grantee, permission), public)| Grant {
+                grantee,
+                permission,
+                public,
+            }),
+    );
+    let policy_public = iam.as_ref().map(|iam| {
+        !prevented
+            && iam.bindings.iter().any(|binding| {
+                GCS_OBJECT_READ_ROLES.contains(&binding.role.as_str())
+                    && binding
+                        .members
+                        .iter()
+                        .any(|member| GCS_PUBLIC_MEMBERS.contains(&member.as_str()))
+            })
+    });
+    let public =
+        !prevented && (policy_public == Some(true) || grants.iter().any(|grant| grant.public));
+    BucketAccess {
+        owner: bucket.owner.map(|owner| owner.entity),
+        grants,
+        public_access_blocked: Some(prevented),
+        object_acls_ignored: object_acls_ignored || prevented,
+        policy_public,
+        cors: bucket
+            .cors
+            .into_iter()
+            .map(|rule| CorsRule {
+                allowed_origins: rule.origin,
+                allowed_methods: rule.method,
+                allowed_headers: Vec::new(),
+                expose_headers: rule.response_header,
+                max_age_secs: rule.max_age_seconds,
+            })
+            .collect(),
+        public,
+    }
+}
+
+/// Reject sources [`AccessClient`] cannot inspect.
+pub fn unsupported(backend: &str) -> CoreError {
+    CoreError::Unsupported(format!("access inspection on {backend} storages"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_grants_are_flagged_unless_blocked() {
+        let acl = r#"<AccessControlPolicy>
+            <Owner><ID>abc</ID><DisplayName>owner</DisplayName></Owner>
+            <AccessControlList>
+                <Grant>
+                    <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="CanonicalUser">
+                        <ID>abc</ID><DisplayName>owner</DisplayName>
+                    </Grantee>
+                    <Permission>FULL_CONTROL</Permission>
+                </Grant>
+                <Grant>
+                    <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="Group">
+                        <URI>http://acs.amazonaws.com/groups/global/AllUsers</URI>
+                    </Grantee>
+                    <Permission>READ</Permission>
+                </Grant>
+            </AccessControlList>
+        </AccessControlPolicy>"#;
+        let (owner, grants) = s3_acl(acl).unwrap();
+        assert_eq!(owner.as_deref(), Some("owner"));
+        assert_eq!(grants.iter().filter(|g| g.public).count(), 1);
+
+        let open = s3_evaluate(owner.clone(), grants.clone(), None, None, None);
+        assert!(open.public);
+        assert_eq!(open.policy_public, None);
+        let block = PublicAccessBlockConfiguration {
+            ignore_public_acls: true,
+            ..Default::default()
+        };
+        let cors: CorsConfiguration = parse_xml(
+            "<CORSConfiguration><CORSRule><AllowedOrigin>*</AllowedOrigin>\
+             <AllowedMethod>GET</AllowedMethod><MaxAgeSeconds>300</MaxAgeSeconds>\
+             </CORSRule></CORSConfiguration>",
+        )
+        .unwrap();
+        let blocked = s3_evaluate(owner, grants, Some(block), None, Some(cors));
+        assert!(!blocked.public);
+        assert_eq!(blocked.public_access_blocked, Some(false));
+        assert_eq!(blocked.cors[0].allowed_origins, ["*"]);
+        assert_eq!(blocked.cors[0].max_age_secs, Some(300));
+
+        let bucket: GcsBucket = serde_json::from_str(
+            r#"{"iamConfiguration": {"uniformBucketLevelAccess": {"enabled": true}}}"#,
+        )
+        .unwrap();
+        let iam: GcsIamPolicy = serde_json::from_str(
+            r#"{"bindings": [{"role": "roles/storage.objectViewer", "members": ["allUsers"]}]}"#,
+        )
+        .unwrap();
+        let gcs = gcs_evaluate(bucket, Some(iam));
+        assert_eq!((gcs.policy_public, gcs.public), (Some(true), true));
+        assert!(gcs.object_acls_ignored);
+    }
+}
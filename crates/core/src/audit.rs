@@ -0,0 +1,251 @@
+//! Audit snapshots ("freeze") of a prefix for legal holds and compliance
+//! archives.
+//!
+//! [`freeze`] records every file below a prefix with its size and SHA-256
+//! in a manifest stored under `audit-snapshots/` next to the config.
+//! [`verify`] later re-reads the prefix and reports drift: files that were
+//! modified, removed or added since. Each manifest carries a seal, a hash
+//! over its own contents, so an edited manifest is reported as well.
+//! Snapshots only observe; they do not stop anyone from changing the files.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config;
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_list_path, ChecksumAlgorithm};
+
+/// Files hashed at the same time.
+const HASH_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub size: u64,
+    /// Lowercase hex SHA-256.
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditSnapshot {
+    pub id: String,
+    pub source_id: String,
+    pub prefix: String,
+    pub created_at: DateTime<Utc>,
+    /// By path relative to the source root.
+    pub files: BTreeMap<String, ManifestEntry>,
+    /// SHA-256 over the fields above; see [`AuditSnapshot::compute_seal`].
+    pub seal: String,
+}
+
+impl AuditSnapshot {
+    fn compute_seal(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!(
+            "{}\n{}\n{}\n{}\n",
+            self.id,
+            self.source_id,
+            self.prefix,
+            self.created_at.to_rfc3339()
+        ));
+        for (path, entry) in &self.files {
+            hasher.update(format!("{path}\t{}\t{}\n", entry.size, entry.sha256));
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    pub fn summary(&self) -> AuditSnapshotSummary {
+        AuditSnapshotSummary {
+            id: self.id.clone(),
+            source_id: self.source_id.clone(),
+            prefix: self.prefix.clone(),
+            created_at: self.created_at,
+            files: self.files.len(),
+            total_bytes: self.files.values().map(|entry| entry.size).sum(),
+        }
+    }
+}
+
+/// A snapshot without its file list, for listing snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditSnapshotSummary {
+    pub id: String,
+    pub source_id: String,
+    pub prefix: String,
+    pub created_at: DateTime<Utc>,
+    pub files: usize,
+    pub total_bytes: u64,
+}
+
+/// What changed below a snapshot's prefix since it was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DriftReport {
+    pub snapshot_id: String,
+    pub checked_at: DateTime<Utc>,
+    /// The manifest still matches its seal.
+    pub manifest_intact: bool,
+    /// Files whose size or checksum differs.
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+impl DriftReport {
+    pub fn is_clean(&self) -> bool {
+        self.manifest_intact
+            && self.modified.is_empty()
+            && self.removed.is_empty()
+            && self.added.is_empty()
+    }
+}
+
+/// Size and checksum of every file below `prefix`.
+async fn hash_prefix(op: &Operator, prefix: &str) -> Result<BTreeMap<String, ManifestEntry>> {
+    let files: Vec<(String, u64)> =
+        operations::list_recursive(op, prefix, None, operations::DEFAULT_MAX_LISTING_ENTRIES)
+            .await?
+            .into_iter()
+            .filter(|entry| !entry.is_dir)
+            .map(|entry| (entry.path, entry.size))
+            .collect();
+    stream::iter(files)
+        .map(|(path, size)| async move {
+            let sha256 = operations::checksum(op, &path, ChecksumAlgorithm::Sha256).await?;
+            Ok::<_, CoreError>((path, ManifestEntry { size, sha256 }))
+        })
+        .buffer_unordered(HASH_CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+/// Record the files below `prefix` of `source_id` and store the snapshot.
+pub async fn freeze(op: &Operator, source_id: &str, prefix: &str) -> Result<AuditSnapshot> {
+    let prefix = normalize_list_path(prefix);
+    let mut snapshot = AuditSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        source_id: source_id.to_string(),
+        files: hash_prefix(op, &prefix).await?,
+        prefix,
+        created_at: Utc::now(),
+        seal: String::new(),
+    };
+    snapshot.seal = snapshot.compute_seal();
+    save_snapshot(&snapshot)?;
+    Ok(snapshot)
+}
+
+/// Compare the files below the snapshot's prefix with the manifest.
+pub async fn verify(op: &Operator, snapshot: &AuditSnapshot) -> Result<DriftReport> {
+    let mut current = hash_prefix(op, &snapshot.prefix).await?;
+    let mut report = DriftReport {
+        snapshot_id: snapshot.id.clone(),
+        checked_at: Utc::now(),
+        manifest_intact: snapshot.seal == snapshot.compute_seal(),
+        modified: Vec::new(),
+        removed: Vec::new(),
+        added: Vec::new(),
+    };
+    for (path, recorded) in &snapshot.files {
+        match current.remove(path) {
+            Some(found) if found == *recorded => {}
+            Some(_) => report.modified.push(path.clone()),
+            None => report.removed.push(path.clone()),
+        }
+    }
+    report.added = current.into_keys().collect();
+    Ok(report)
+}
+
+fn snapshots_dir() -> PathBuf {
+    config::data_dir().join("audit-snapshots")
+}
+
+fn snapshot_path(id: &str) -> Result<PathBuf> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(CoreError::Config(format!("invalid snapshot id: {id}")));
+    }
+    Ok(snapshots_dir().join(format!("{id}.json")))
+}
+
+fn save_snapshot(snapshot: &AuditSnapshot) -> Result<()> {
+    let data = serde_json::to_string_pretty(snapshot)?;
+    config::write_atomic(&snapshot_path(&snapshot.id)?, data.as_bytes())
+}
+
+pub fn load_snapshot(id: &str) -> Result<AuditSnapshot> {
+    let data = fs::read_to_string(snapshot_path(id)?)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+/// Stored snapshots, newest first. Unreadable files are skipped.
+pub fn list_snapshots() -> Result<Vec<AuditSnapshotSummary>> {
+    let dir = snapshots_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots: Vec<AuditSnapshotSummary> = fs::read_dir(dir)?
+        .filter_map(|entry| fs::read_to_string(entry.ok()?.path()).ok())
+        .filter_map(|data| serde_json::from_str::<AuditSnapshot>(&data).ok())
+        .map(|snapshot| snapshot.summary())
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+    Ok(snapshots)
+}
+
+pub fn delete_snapshot(id: &str) -> Result<()> {
+    fs::remove_file(snapshot_path(id)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    #[tokio::test]
+    async fn verify_reports_drift_since_the_snapshot() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("case/a.pdf", "contract").await.unwrap();
+        op.write("case/mail/b.eml", "hello").await.unwrap();
+        op.write("case/c.txt", "note").await.unwrap();
+        op.write("other.txt", "outside").await.unwrap();
+
+        let files = hash_prefix(&op, "/case").await.unwrap();
+        let mut snapshot = AuditSnapshot {
+            id: "test".to_string(),
+            source_id: "mem".to_string(),
+            prefix: "case/".to_string(),
+            created_at: Utc::now(),
+            files,
+            seal: String::new(),
+        };
+        snapshot.seal = snapshot.compute_seal();
+        assert_eq!(snapshot.summary().files, 3);
+        assert!(verify(&op, &snapshot).await.unwrap().is_clean());
+
+        // Same size, different content.
+        op.write("case/a.pdf", "CONTRACT").await.unwrap();
+        op.delete("case/mail/b.eml").await.unwrap();
+        op.write("case/d.txt", "new").await.unwrap();
+        op.write("other.txt", "changed").await.unwrap();
+        let drift = verify(&op, &snapshot).await.unwrap();
+        assert!(drift.manifest_intact);
+        assert_eq!(drift.modified, ["case/a.pdf"]);
+        assert_eq!(drift.removed, ["case/mail/b.eml"]);
+        assert_eq!(drift.added, ["case/d.txt"]);
+
+        snapshot.files.remove("case/a.pdf");
+        assert!(!verify(&op, &snapshot).await.unwrap().manifest_intact);
+        assert!(snapshot_path("../escape").is_err());
+    }
+}
@@ -1,73 +1,86 @@
-use opendal::{
-    services::{Azblob, Gcs, Webdav, S3},
-    Operator,
-};
-use std::error::Error;
-
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    println!("Starting storage verification...");
-
-    // 1. Verify GCS
-    println!("\n--- Verifying GCS ---");
-    let gcs = Gcs::default()
-        .bucket("test-bucket")
-        .endpoint("http://localhost:4443")
-        .allow_anonymous()
-        .disable_vm_metadata()
-        .disable_config_load();
-
-    let op_gcs = Operator::new(gcs)?.finish();
-    match op_gcs.list("/").await {
-        Ok(_) => println!("✅ GCS: Connection successful (Bucket 'test-bucket' found)"),
-        Err(e) => println!("❌ GCS: Failed - {}", e),
-    }
+//! Smoke test against the local storage emulators (fake-gcs-server,
+//! SeaweedFS S3, Azurite, a WebDAV server) using the library's connection
+//! test, the same one the app runs before a source is saved.
 
-    // 2. Verify S3
-    println!("\n--- Verifying S3 ---");
-    let s3 = S3::default()
-        .bucket("test-bucket")
-        .endpoint("http://localhost:8333")
-        .region("us-east-1")
-        .access_key_id("admin")
-        .secret_access_key("password123");
+use std::collections::HashMap;
+use std::error::Error;
 
-    let op_s3 = Operator::new(s3)?.finish();
+use infimount_core::operations;
+use infimount_core::registry::operator_for_source;
+use infimount_core::{Source, SourceKind};
 
-    match op_s3.write("test_file.txt", "Hello S3").await {
-        Ok(_) => println!("✅ S3: Write successful (Bucket 'test-bucket' accessible)"),
-        Err(e) => println!("❌ S3: Write Failed - {}", e),
+fn emulator(name: &str, kind: SourceKind, settings: &[(&str, &str)]) -> Source {
+    Source {
+        id: name.to_lowercase(),
+        name: name.to_string(),
+        kind,
+        root: String::new(),
+        config: Some(
+            settings
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+        ),
+        read_only: false,
     }
+}
 
-    // 3. Verify Azure
-    println!("\n--- Verifying Azure ---");
-    let az = Azblob::default()
-        .account_name("devstoreaccount1")
-        .container("test-container")
-        .endpoint("http://127.0.0.1:10000/devstoreaccount1")
-        .account_key("Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==");
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    println!("Starting storage verification...");
 
-    let op_az = Operator::new(az)?.finish();
+    let emulators = [
+        emulator(
+            "GCS",
+            SourceKind::Gcs,
+            &[("bucket", "test-bucket"), ("endpoint", "http://localhost:4443")],
+        ),
+        emulator(
+            "S3",
+            SourceKind::S3,
+            &[
+                ("bucketName", "test-bucket"),
+                ("region", "us-east-1"),
+                ("endpoint", "http://localhost:8333"),
+                ("accessKeyId", "admin"),
+                ("secretAccessKey", "password123"),
+            ],
+        ),
+        emulator(
+            "Azure",
+            SourceKind::AzureBlob,
+            &[
+                ("accountName", "devstoreaccount1"),
+                ("containerName", "test-container"),
+                ("endpoint", "http://127.0.0.1:10000/devstoreaccount1"),
+                ("accountKey", "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw=="),
+            ],
+        ),
+        emulator(
+            "WebDAV",
+            SourceKind::WebDav,
+            &[("serverUrl", "http://localhost:7333"), ("rootPath", "/")],
+        ),
+    ];
 
-    match op_az.write("test_file.txt", "Hello Azure").await {
-        Ok(_) => println!("✅ Azure: Write successful"),
-        Err(e) => {
-            println!("⚠️ Azure: Write failed - {}", e);
-            println!("Note: You may need to create the container 'test-container' manually if Azurite doesn't auto-create it.");
+    for source in &emulators {
+        println!("\n--- Verifying {} ---", source.name);
+        let op = match operator_for_source(source) {
+            Ok(op) => op,
+            Err(e) => {
+                println!("❌ {}: Invalid settings - {}", source.name, e);
+                continue;
+            }
+        };
+        let test = operations::test_connection(&op).await;
+        match test.error {
+            None => println!(
+                "✅ {}: Connection successful ({} ms)",
+                source.name, test.latency_ms
+            ),
+            Some(error) => println!("❌ {}: Failed - {}", source.name, error),
         }
     }
 
-    // 4. Verify WebDAV
-    println!("\n--- Verifying WebDAV ---");
-    let webdav = Webdav::default()
-        .endpoint("http://localhost:7333")
-        .root("/");
-
-    let op_webdav = Operator::new(webdav)?.finish();
-    match op_webdav.list("/").await {
-        Ok(_) => println!("✅ WebDAV: Connection successful"),
-        Err(e) => println!("❌ WebDAV: Failed - {}", e),
-    }
-
     Ok(())
 }
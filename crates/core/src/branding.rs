@@ -0,0 +1,266 @@
+//! Per-source icons for the sidebar.
+//!
+//! The icon id comes from the source kind and, for S3-compatible services,
+//! the provider preset; ids match the storage schema ids (`minio`,
+//! `cloudflare-r2`, ...). Several sources of one provider would still look
+//! alike, so each also gets an accent hue derived from its endpoint host, and
+//! self-hosted servers can show their own favicon. Favicons are only fetched
+//! when the `fetch_favicons` preference is on, and are cached in
+//! `favicons/` under [`config::data_dir`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config;
+use crate::models::SourceKind;
+
+/// How long a fetched (or missing) favicon is reused before asking again.
+const FAVICON_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const FAVICON_TIMEOUT: Duration = Duration::from_secs(5);
+/// Larger responses are not icons worth inlining.
+const MAX_FAVICON_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceBranding {
+    /// Icon id, e.g. `"minio"`, `"aws-s3"` or `"webdav"`.
+    pub icon: String,
+    /// Hue (0-359) to tint the icon with; stable for a given endpoint.
+    pub accent_hue: u16,
+    /// The server's own favicon as a `data:` URL, when fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+}
+
+/// Icon id for a source kind and optional S3 provider preset.
+pub fn icon_id(kind: &SourceKind, provider: Option<&str>) -> &'static str {
+    match kind {
+        SourceKind::Local => "local-fs",
+        SourceKind::S3 => match provider {
+            Some("minio") => "minio",
+            Some("r2") => "cloudflare-r2",
+            Some("wasabi") => "wasabi",
+            Some("digitalocean") => "digitalocean-spaces",
+            _ => "aws-s3",
+        },
+        SourceKind::WebDav => "webdav",
+        SourceKind::AzureBlob => "azure-blob",
+        SourceKind::Gcs => "gcs",
+        SourceKind::Ftp => "ftp",
+        SourceKind::Gdrive => "google-drive",
+        SourceKind::B2 => "backblaze-b2",
+        SourceKind::Hdfs => "hdfs",
+        SourceKind::Ipfs => "ipfs",
+        SourceKind::Ipmfs => "ipmfs",
+        SourceKind::Http => "http",
+    }
+}
+
+/// URL of a server the user runs themselves, if the source points at one.
+/// Hosted services (AWS, R2, Drive, ...) have no endpoint of interest.
+pub fn self_hosted_endpoint(
+    kind: &SourceKind,
+    settings: &HashMap<String, String>,
+) -> Option<String> {
+    let key = match kind {
+        SourceKind::S3 | SourceKind::AzureBlob => "endpoint",
+        SourceKind::WebDav => "serverUrl",
+        SourceKind::Http => "endpoint",
+        SourceKind::Hdfs => "nameNodeUrl",
+        SourceKind::Ipfs => "gatewayUrl",
+        SourceKind::Ipmfs => "apiUrl",
+        _ => return None,
+    };
+    settings
+        .get(key)
+        .map(|url| url.trim())
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+        .map(str::to_string)
+}
+
+/// Icon and accent for a source. `seed` (usually the source id) picks the
+/// hue when there is no endpoint to derive it from.
+pub fn branding(
+    kind: &SourceKind,
+    settings: &HashMap<String, String>,
+    seed: &str,
+) -> SourceBranding {
+    let provider = settings.get("provider").map(String::as_str);
+    let hue_key = self_hosted_endpoint(kind, settings)
+        .and_then(|endpoint| origin(&endpoint))
+        .unwrap_or_else(|| seed.to_string());
+    SourceBranding {
+        icon: icon_id(kind, provider).to_string(),
+        accent_hue: accent_hue(&hue_key),
+        favicon: None,
+    }
+}
+
+/// Stable hue for `key` (FNV-1a), so the same server keeps its color.
+pub fn accent_hue(key: &str) -> u16 {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    (hash % 360) as u16
+}
+
+/// `scheme://host[:port]` of `url`, without credentials or path.
+fn origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if host.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}://{}",
+        scheme.to_ascii_lowercase(),
+        host.to_ascii_lowercase()
+    ))
+}
+
+/// Favicon of the server behind `endpoint` as a `data:` URL. Results,
+/// including "no favicon", are cached for [`FAVICON_TTL`].
+pub async fn favicon(endpoint: &str) -> Option<String> {
+    let origin = origin(endpoint)?;
+    let cache_path = favicon_cache_path(&origin);
+    if let Some(cached) = read_cached(&cache_path) {
+        return Some(cached).filter(|url| !url.is_empty());
+    }
+
+    let fetched = fetch_favicon(&origin).await;
+    if let Some(parent) = cache_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(&cache_path, fetched.as_deref().unwrap_or_default());
+    fetched
+}
+
+fn favicon_cache_path(origin: &str) -> PathBuf {
+    let digest = Sha256::digest(origin.as_bytes());
+    let name: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+    config::data_dir().join("favicons").join(name)
+}
+
+fn read_cached(path: &Path) -> Option<String> {
+    let age = std::fs::metadata(path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    if age > FAVICON_TTL {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+async fn fetch_favicon(origin: &str) -> Option<String> {
+    let client = reqwest::Client::builder()
+        .timeout(FAVICON_TIMEOUT)
+        .build()
+        .ok()?;
+    let response = client
+        .get(format!("{origin}/favicon.ico"))
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        })
+        .filter(|value| value.starts_with("image/"))
+        .unwrap_or_else(|| "image/x-icon".to_string());
+    let body = response.bytes().await.ok()?;
+    if body.is_empty() || body.len() > MAX_FAVICON_BYTES || looks_like_html(&body) {
+        return None;
+    }
+    Some(format!(
+        "data:{content_type};base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(&body)
+    ))
+}
+
+// Some servers answer every path with their web UI instead of a 404.
+fn looks_like_html(body: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&body[..body.len().min(64)]).to_ascii_lowercase();
+    let start = start.trim_start();
+    start.starts_with("<!doctype") || start.starts_with("<html")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn same_provider_sources_get_distinct_accents() {
+        let minio = |endpoint: &str| {
+            branding(
+                &SourceKind::S3,
+                &settings(&[("provider", "minio"), ("endpoint", endpoint)]),
+                "id",
+            )
+        };
+        let nas = minio("http://nas.local:9000");
+        let lab = minio("https://minio.lab.example.com");
+        assert_eq!(nas.icon, "minio");
+        assert_eq!(lab.icon, "minio");
+        assert_ne!(nas.accent_hue, lab.accent_hue);
+
+        // Only the server counts: other buckets on it share the color.
+        assert_eq!(
+            minio("http://user:pw@NAS.local:9000/bucket").accent_hue,
+            nas.accent_hue
+        );
+        assert_eq!(
+            branding(&SourceKind::S3, &settings(&[]), "id").icon,
+            "aws-s3"
+        );
+    }
+
+    #[test]
+    fn hosted_services_have_no_self_hosted_endpoint() {
+        assert_eq!(
+            self_hosted_endpoint(&SourceKind::Gdrive, &settings(&[("endpoint", "https://x")])),
+            None
+        );
+        assert_eq!(
+            self_hosted_endpoint(
+                &SourceKind::WebDav,
+                &settings(&[("serverUrl", " https://dav.example.com/remote.php ")])
+            )
+            .as_deref(),
+            Some("https://dav.example.com/remote.php")
+        );
+        assert_eq!(
+            origin("https://dav.example.com"),
+            Some("https://dav.example.com".into())
+        );
+        assert!(looks_like_html(b"  <!DOCTYPE html><html>"));
+        assert!(!looks_like_html(&[0, 0, 1, 0]));
+    }
+}
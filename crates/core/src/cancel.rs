@@ -0,0 +1,111 @@
+//! Cancelling long-running operations by id.
+//!
+//! A job registers under an id the UI knows (a transfer's task id, or the
+//! id the UI gave a search or delete) and checks its [`CancelToken`]
+//! between steps; [`cancel`] with that id stops it at the next check.
+//! Transfers check between chunks and abort the write in flight, so a
+//! cancelled file is not left half-written where the backend allows it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::models::{CoreError, Result};
+
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// [`CoreError::Cancelled`] once cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(CoreError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn running() -> std::sync::MutexGuard<'static, HashMap<String, CancelToken>> {
+    static RUNNING: OnceLock<Mutex<HashMap<String, CancelToken>>> = OnceLock::new();
+    RUNNING
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Keeps an operation cancellable by id until dropped.
+#[derive(Debug)]
+pub struct Registration {
+    id: String,
+    token: CancelToken,
+}
+
+impl Registration {
+    pub fn token(&self) -> CancelToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut running = running();
+        // The id may have been reused by a newer operation.
+        if running
+            .get(&self.id)
+            .is_some_and(|token| Arc::ptr_eq(&token.0, &self.token.0))
+        {
+            running.remove(&self.id);
+        }
+    }
+}
+
+/// Make the operation `id` cancellable through [`cancel`].
+pub fn register(id: impl Into<String>) -> Registration {
+    let id = id.into();
+    let token = CancelToken::new();
+    running().insert(id.clone(), token.clone());
+    Registration { id, token }
+}
+
+/// Cancel the running operation `id`. Returns whether one was running.
+pub fn cancel(id: &str) -> bool {
+    match running().get(id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ErrorCode;
+
+    #[test]
+    fn operations_are_cancelled_by_id_while_registered() {
+        let registration = register("job-1");
+        let token = registration.token();
+        assert!(token.check().is_ok());
+
+        assert!(cancel("job-1"));
+        assert_eq!(token.check().unwrap_err().code(), ErrorCode::Cancelled);
+
+        drop(registration);
+        assert!(!cancel("job-1"));
+    }
+}
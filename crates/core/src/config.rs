@@ -1,18 +1,56 @@
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::models::{Result, Source};
+use fs2::FileExt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::{CoreError, Result, Source};
+use crate::secrets;
+use crate::thresholds::SizeThresholds;
+use crate::transfer_report::TransferFilter;
+
+mod rclone;
+
+pub use rclone::{default_rclone_config_path, import_rclone, RcloneImport, RemoteNote};
+
+/// Format version of `config.json` written by this build.
+///
+/// - 1: bare JSON array of sources.
+/// - 2: `{ "version": 2, "sources": [...] }`.
+pub const CONFIG_VERSION: u64 = 2;
+
+/// Upgrades from version `n` (index `n - 1`) to `n + 1`.
+const MIGRATIONS: &[fn(Value) -> Result<Value>] = &[migrate_v1_to_v2];
+
+#[derive(Serialize)]
+struct ConfigFile<'a> {
+    version: u64,
+    sources: &'a [Source],
+}
+
+#[derive(Deserialize)]
+struct LoadedConfigFile {
+    sources: Vec<Source>,
+}
+
+/// How long to wait for another process holding the config lock.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Location of the configuration file.
 ///
-/// Uses `~/.infimount/config.json` by default, or a custom
-/// path via the `INFIMOUNT_CONFIG` env var.
-fn config_path() -> PathBuf {
+/// Uses `config.json` in [`config_dir`] by default, or a custom path via the
+/// `INFIMOUNT_CONFIG` env var.
+pub fn config_path() -> PathBuf {
     if let Ok(p) = std::env::var("INFIMOUNT_CONFIG") {
         return PathBuf::from(p);
     }
 
-    default_config_path().unwrap_or_else(|| PathBuf::from(".infimount").join("config.json"))
+    config_dir().join("config.json")
 }
 
 fn non_empty_env(name: &str) -> Option<String> {
@@ -26,32 +64,410 @@ fn home_dir() -> Option<String> {
     non_empty_env("HOME").or_else(|| non_empty_env("USERPROFILE"))
 }
 
-fn default_config_path() -> Option<PathBuf> {
-    home_dir().map(|home| PathBuf::from(home).join(".infimount").join("config.json"))
+/// Per-user config directory: `~/.config/infimount` on Linux,
+/// `~/Library/Application Support/infimount` on macOS and
+/// `%APPDATA%\infimount` on Windows.
+///
+/// Earlier releases used `~/.infimount`; until [`migrate_legacy_config_dir`]
+/// has moved it, an existing legacy directory keeps being used.
+pub fn config_dir() -> PathBuf {
+    resolve_config_dir(platform_config_dir(), legacy_config_dir())
+}
+
+fn platform_config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("infimount"))
+}
+
+fn legacy_config_dir() -> Option<PathBuf> {
+    home_dir().map(|home| PathBuf::from(home).join(".infimount"))
+}
+
+fn resolve_config_dir(platform: Option<PathBuf>, legacy: Option<PathBuf>) -> PathBuf {
+    let Some(platform) = platform else {
+        return legacy.unwrap_or_else(|| PathBuf::from(".infimount"));
+    };
+    match legacy {
+        Some(legacy) if !platform.exists() && legacy.is_dir() => legacy,
+        _ => platform,
+    }
+}
+
+/// Move the legacy `~/.infimount` directory to the platform location of
+/// [`config_dir`]. Run once at startup, before anything reads the config;
+/// does nothing when there is no legacy directory or the new one exists.
+pub fn migrate_legacy_config_dir() -> std::io::Result<()> {
+    relocate_legacy_dir(platform_config_dir(), legacy_config_dir())
+}
+
+fn relocate_legacy_dir(platform: Option<PathBuf>, legacy: Option<PathBuf>) -> std::io::Result<()> {
+    let (Some(platform), Some(legacy)) = (platform, legacy) else {
+        return Ok(());
+    };
+    if platform.exists() || !legacy.is_dir() {
+        return Ok(());
+    }
+    if let Some(parent) = platform.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(legacy, platform)
+}
+
+/// Per-user cache directory (`~/.cache/infimount` on Linux), for files that
+/// can be fetched again, such as downloaded previews.
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .map(|dir| dir.join("infimount"))
+        .unwrap_or_else(|| std::env::temp_dir().join("infimount-cache"))
+}
+
+/// Directory for app-managed state next to the config file
+/// ([`config_dir`] by default).
+pub fn data_dir() -> PathBuf {
+    config_path()
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_else(config_dir)
+}
+
+fn config_version(value: &Value) -> Result<u64> {
+    match value {
+        Value::Array(_) => Ok(1),
+        Value::Object(map) => map
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| CoreError::Config("config file has no valid \"version\"".to_string())),
+        _ => Err(CoreError::Config(
+            "config file must be a JSON object or array".to_string(),
+        )),
+    }
+}
+
+fn migrate_v1_to_v2(value: Value) -> Result<Value> {
+    Ok(serde_json::json!({ "version": 2, "sources": value }))
+}
+
+/// Run `value` through the migration pipeline up to [`CONFIG_VERSION`].
+/// Returns the upgraded document and the version it started at.
+fn migrate(mut value: Value) -> Result<(Value, u64)> {
+    let original = config_version(&value)?;
+    if original > CONFIG_VERSION {
+        return Err(CoreError::Config(format!(
+            "config file version {original} was written by a newer Infimount (this build reads up to {CONFIG_VERSION})"
+        )));
+    }
+
+    let mut version = original;
+    while version < CONFIG_VERSION {
+        let step = MIGRATIONS.get(version as usize - 1).ok_or_else(|| {
+            CoreError::Config(format!("no migration from config version {version}"))
+        })?;
+        value = step(value)?;
+        version = config_version(&value)?;
+    }
+    Ok((value, original))
+}
+
+/// Advisory lock on a config file (`config.lock` next to `config.json`),
+/// held while reading or rewriting it so concurrent app instances don't
+/// interleave. Released on drop.
+pub struct ConfigLock {
+    file: fs::File,
+}
+
+impl ConfigLock {
+    pub fn acquire(config: &Path) -> Result<Self> {
+        ensure_parent(config)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(config.with_extension("lock"))?;
+
+        let start = Instant::now();
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => return Ok(Self { file }),
+                Err(_) if start.elapsed() >= LOCK_TIMEOUT => {
+                    return Err(CoreError::Config(format!(
+                        "timed out waiting for the lock on {}",
+                        config.display()
+                    )))
+                }
+                Err(_) => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+}
+
+impl Drop for ConfigLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Modification time and size of a config file, used to notice when another
+/// process rewrote it. `None` if the file does not exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+pub fn stamp(path: &Path) -> Option<ConfigStamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some(ConfigStamp {
+        modified: meta.modified().ok()?,
+        len: meta.len(),
+    })
+}
+
+fn ensure_parent(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace `path` with `data` via a temp file in the same directory and a
+/// rename, so a crash mid-write never leaves a truncated file behind.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    ensure_parent(path)?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(
+        ".{file_name}.tmp.{}.{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+
+    let result = (|| {
+        let mut tmp = fs::File::create(&tmp_path)?;
+        tmp.write_all(data)?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    Ok(result?)
 }
 
 /// Load all configured sources.
 pub fn load_sources() -> Result<Vec<Source>> {
-    let path = config_path();
+    load_sources_from(&config_path())
+}
+
+/// Load sources from a specific config file.
+pub fn load_sources_from(path: &Path) -> Result<Vec<Source>> {
+    let _lock = ConfigLock::acquire(path)?;
+    read_sources(path)
+}
+
+/// Read (and upgrade, if needed) a config file. The caller holds its
+/// [`ConfigLock`].
+pub fn read_sources(path: &Path) -> Result<Vec<Source>> {
     if !path.exists() {
         // No config yet; start with an empty list of sources.
         return Ok(Vec::new());
     }
 
-    let data = fs::read_to_string(&path)?;
-    let sources: Vec<Source> = serde_json::from_str(&data)?;
-    Ok(sources)
+    let data = fs::read_to_string(path)?;
+    let (value, original) = migrate(serde_json::from_str(&data)?)?;
+    if original < CONFIG_VERSION {
+        // Keep the old file around in case the upgrade needs to be undone.
+        fs::copy(path, path.with_extension(format!("json.v{original}.bak")))?;
+        write_atomic(path, serde_json::to_string_pretty(&value)?.as_bytes())?;
+    }
+
+    let config: LoadedConfigFile = serde_json::from_value(value)?;
+    Ok(config.sources)
 }
 
-/// Persist the current list of sources.
+/// Persist the current list of sources. Secret fields are stored in the OS
+/// keyring and only referenced from the file.
 pub fn save_sources(sources: &[Source]) -> Result<()> {
-    let path = config_path();
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() {
-            fs::create_dir_all(parent)?;
+    save_sources_to(&config_path(), sources)
+}
+
+/// Persist sources to a specific config file.
+pub fn save_sources_to(path: &Path, sources: &[Source]) -> Result<()> {
+    let _lock = ConfigLock::acquire(path)?;
+    write_sources(path, sources)
+}
+
+/// Atomically rewrite a config file. The caller holds its [`ConfigLock`].
+pub fn write_sources(path: &Path, sources: &[Source]) -> Result<()> {
+    let mut sources = sources.to_vec();
+    for source in &mut sources {
+        secrets::externalize(source)?;
+    }
+
+    let data = serde_json::to_string_pretty(&ConfigFile {
+        version: CONFIG_VERSION,
+        sources: &sources,
+    })?;
+    write_atomic(path, data.as_bytes())
+}
+
+/// App-wide preferences, stored in `preferences.json` next to the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Surface inserted removable drives as temporary Local sources.
+    pub auto_add_removable_drives: bool,
+    /// Keep `.DS_Store`, `Thumbs.db` and similar files in recursive uploads
+    /// and folder transfers instead of skipping them.
+    pub include_system_files: bool,
+    /// Show the favicon of self-hosted servers (MinIO, WebDAV, ...) in the
+    /// sidebar; fetching it contacts the server outside of file operations.
+    pub fetch_favicons: bool,
+    /// List the subfolders and sibling folders of an opened folder (and read
+    /// its small files) in the background, so navigating on slow sources is
+    /// instant at the cost of extra requests.
+    pub prefetch_adjacent: bool,
+    /// Mark entries of Local sources inside a git repository as modified,
+    /// untracked or ignored.
+    pub git_annotations: bool,
+    /// Skip what a repository's `.gitignore` excludes when uploading folders
+    /// or transferring them out of a Local source.
+    pub honor_gitignore: bool,
+    /// Recreate symbolic links inside uploaded folders when the target is a
+    /// Local source, instead of copying what they point to.
+    pub copy_symlinks_as_links: bool,
+    /// Most entries listed in one call; bigger folders are reported as too
+    /// large and read page by page. `None` means
+    /// [`crate::operations::DEFAULT_MAX_LISTING_ENTRIES`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_listing_entries: Option<usize>,
+    /// Files uploaded at once. `None` means
+    /// [`crate::operations::DEFAULT_UPLOAD_CONCURRENCY`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_concurrency: Option<usize>,
+    /// When downloads and previews need confirmation, and which files are
+    /// too large to open in the app; see [`crate::thresholds`].
+    pub size_thresholds: SizeThresholds,
+}
+
+impl Preferences {
+    /// [`Self::max_listing_entries`], or the default when unset.
+    pub fn listing_limit(&self) -> usize {
+        self.max_listing_entries
+            .unwrap_or(crate::operations::DEFAULT_MAX_LISTING_ENTRIES)
+    }
+
+    /// What recursive transfers skip under these preferences.
+    pub fn transfer_filter(&self) -> TransferFilter {
+        TransferFilter {
+            skip_system_files: !self.include_system_files,
+            honor_gitignore: self.honor_gitignore,
         }
     }
-    let data = serde_json::to_string_pretty(sources)?;
-    fs::write(path, data)?;
-    Ok(())
+}
+
+fn preferences_path() -> PathBuf {
+    data_dir().join("preferences.json")
+}
+
+/// Load preferences, falling back to defaults when none were saved yet.
+pub fn load_preferences() -> Result<Preferences> {
+    let path = preferences_path();
+    if !path.exists() {
+        return Ok(Preferences::default());
+    }
+
+    let data = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+/// Persist preferences.
+pub fn save_preferences(preferences: &Preferences) -> Result<()> {
+    let data = serde_json::to_string_pretty(preferences)?;
+    write_atomic(&preferences_path(), data.as_bytes())
+}
+
+/// State the app keeps between runs that is not a user preference, stored
+/// in `runtime-state.json` next to the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeState {
+    /// Transfers and background jobs were paused with
+    /// [`crate::pause::pause_all`].
+    pub transfers_paused: bool,
+}
+
+fn runtime_state_path() -> PathBuf {
+    data_dir().join("runtime-state.json")
+}
+
+pub fn load_runtime_state() -> Result<RuntimeState> {
+    let path = runtime_state_path();
+    if !path.exists() {
+        return Ok(RuntimeState::default());
+    }
+
+    let data = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn save_runtime_state(state: &RuntimeState) -> Result<()> {
+    let data = serde_json::to_string_pretty(state)?;
+    write_atomic(&runtime_state_path(), data.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_configs_are_migrated_to_the_current_version() {
+        let v1 = serde_json::json!([
+            { "id": "a", "name": "A", "kind": "local", "root": "/tmp" }
+        ]);
+        let (value, original) = migrate(v1).unwrap();
+        assert_eq!(original, 1);
+        assert_eq!(value["version"], CONFIG_VERSION);
+        let config: LoadedConfigFile = serde_json::from_value(value).unwrap();
+        assert_eq!(config.sources[0].id, "a");
+
+        let newer = serde_json::json!({ "version": CONFIG_VERSION + 1, "sources": [] });
+        assert!(migrate(newer).is_err());
+    }
+
+    #[test]
+    fn legacy_config_dir_is_used_until_relocated() {
+        let base = std::env::temp_dir().join(format!("infimount-cfgdir-{}", std::process::id()));
+        let legacy = base.join(".infimount");
+        let platform = base.join("config").join("infimount");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("config.json"), "[]").unwrap();
+
+        // Looking the directory up moves nothing.
+        let dir = resolve_config_dir(Some(platform.clone()), Some(legacy.clone()));
+        assert_eq!(dir, legacy);
+        assert!(legacy.join("config.json").exists());
+        assert!(!platform.exists());
+
+        relocate_legacy_dir(Some(platform.clone()), Some(legacy.clone())).unwrap();
+        assert!(platform.join("config.json").exists());
+        assert!(!legacy.exists());
+        assert_eq!(
+            resolve_config_dir(Some(platform.clone()), Some(legacy.clone())),
+            platform
+        );
+
+        // Once relocated, a recreated legacy dir is left alone.
+        fs::create_dir_all(&legacy).unwrap();
+        relocate_legacy_dir(Some(platform.clone()), Some(legacy.clone())).unwrap();
+        assert!(legacy.exists());
+        assert_eq!(
+            resolve_config_dir(Some(platform.clone()), Some(legacy)),
+            platform
+        );
+        let _ = fs::remove_dir_all(base);
+    }
 }
@@ -0,0 +1,302 @@
+//! Import of rclone remotes (`rclone.conf`) as sources.
+//!
+//! rclone remotes describe a whole account rather than one bucket, and
+//! rclone stores passwords obscured, so imported sources may still need a
+//! bucket or password filled in; those are listed in
+//! [`RcloneImport::incomplete`].
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::models::{CoreError, Result, Source, SourceKind};
+use crate::schema;
+
+/// Prefix of an rclone config encrypted with `rclone config encryption`.
+const ENCRYPTED_PREFIX: &str = "RCLONE_ENCRYPT_V0:";
+
+/// Outcome of [`import_rclone`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RcloneImport {
+    pub sources: Vec<Source>,
+    /// Imported sources that need more settings before they connect.
+    pub incomplete: Vec<RemoteNote>,
+    /// Remotes that have no Infimount equivalent.
+    pub unmapped: Vec<RemoteNote>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemoteNote {
+    pub name: String,
+    pub remote_type: String,
+    pub reason: String,
+}
+
+/// Default location of rclone's config: `$RCLONE_CONFIG`, else
+/// `rclone/rclone.conf` in the platform config directory.
+pub fn default_rclone_config_path() -> Option<PathBuf> {
+    if let Some(path) = super::non_empty_env("RCLONE_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    dirs::config_dir().map(|dir| dir.join("rclone").join("rclone.conf"))
+}
+
+/// Read an `rclone.conf` and map its remotes to sources.
+pub fn import_rclone(path: &Path) -> Result<RcloneImport> {
+    parse_rclone(&fs::read_to_string(path)?)
+}
+
+fn parse_rclone(data: &str) -> Result<RcloneImport> {
+    if data.trim_start().starts_with(ENCRYPTED_PREFIX) {
+        return Err(CoreError::Config(
+            "rclone config is encrypted; export a decrypted copy with `rclone config show` first"
+                .to_string(),
+        ));
+    }
+
+    let mut import = RcloneImport::default();
+    for (name, settings) in parse_sections(data) {
+        let remote_type = settings.get("type").cloned().unwrap_or_default();
+        let note = |reason: String| RemoteNote {
+            name: name.clone(),
+            remote_type: remote_type.clone(),
+            reason,
+        };
+        match map_remote(&name, &remote_type, &settings) {
+            Ok(mapped) => {
+                let mut reasons = Vec::new();
+                if let Err(error) = schema::validate_source_settings(&mapped.source) {
+                    reasons.push(error.to_string());
+                }
+                if mapped.obscured_password {
+                    reasons.push("rclone stores the password obscured; enter it again".to_string());
+                }
+                if !reasons.is_empty() {
+                    import.incomplete.push(note(reasons.join("; ")));
+                }
+                import.sources.push(mapped.source);
+            }
+            Err(reason) => import.unmapped.push(note(reason)),
+        }
+    }
+    Ok(import)
+}
+
+/// Split INI text into `[section]`s of `key = value` pairs, in file order.
+fn parse_sections(data: &str) -> Vec<(String, HashMap<String, String>)> {
+    let mut sections: Vec<(String, HashMap<String, String>)> = Vec::new();
+    for line in data.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name.trim().to_string(), HashMap::new()));
+            continue;
+        }
+        let (Some((_, settings)), Some((key, value))) = (sections.last_mut(), line.split_once('='))
+        else {
+            continue;
+        };
+        settings.insert(key.trim().to_string(), value.trim().to_string());
+    }
+    sections
+}
+
+struct MappedRemote {
+    source: Source,
+    obscured_password: bool,
+}
+
+fn map_remote(
+    name: &str,
+    remote_type: &str,
+    settings: &HashMap<String, String>,
+) -> std::result::Result<MappedRemote, String> {
+    let get = |key: &str| settings.get(key).filter(|value| !value.is_empty());
+    let mut config = HashMap::new();
+    let mut copy = |pairs: &[(&str, &str)]| {
+        for (from, to) in pairs {
+            if let Some(value) = get(from) {
+                config.insert(to.to_string(), value.clone());
+            }
+        }
+    };
+    let mut root = String::new();
+
+    let kind = match remote_type {
+        "s3" => {
+            copy(&[
+                ("access_key_id", "accessKeyId"),
+                ("secret_access_key", "secretAccessKey"),
+                ("region", "region"),
+                ("endpoint", "endpoint"),
+            ]);
+            let preset = match get("provider").map(String::as_str) {
+                Some("Minio") => Some("minio"),
+                Some("Cloudflare") => Some("r2"),
+                Some("Wasabi") => Some("wasabi"),
+                Some("DigitalOcean") => Some("digitalocean"),
+                _ => None,
+            };
+            if let Some(preset) = preset {
+                config.insert("provider".to_string(), preset.to_string());
+            }
+            SourceKind::S3
+        }
+        "webdav" => {
+            copy(&[("url", "serverUrl"), ("user", "username")]);
+            SourceKind::WebDav
+        }
+        "azureblob" => {
+            copy(&[
+                ("account", "accountName"),
+                ("key", "accountKey"),
+                ("endpoint", "endpoint"),
+            ]);
+            SourceKind::AzureBlob
+        }
+        "google cloud storage" => {
+            copy(&[
+                ("service_account_file", "credentialPath"),
+                ("service_account_credentials", "credential"),
+            ]);
+            SourceKind::Gcs
+        }
+        "ftp" => {
+            copy(&[
+                ("host", "host"),
+                ("port", "port"),
+                ("user", "username"),
+                ("tls", "tls"),
+            ]);
+            SourceKind::Ftp
+        }
+        "drive" => {
+            copy(&[("client_id", "clientId"), ("client_secret", "clientSecret")]);
+            if let Some(token) = get("token") {
+                config.extend(drive_token(token));
+            }
+            SourceKind::Gdrive
+        }
+        "b2" => {
+            copy(&[("account", "applicationKeyId"), ("key", "applicationKey")]);
+            SourceKind::B2
+        }
+        "http" => {
+            copy(&[("url", "endpoint")]);
+            SourceKind::Http
+        }
+        "alias" => {
+            // Only aliases of a local directory stand on their own.
+            let target = get("remote").ok_or("alias has no target")?;
+            if !Path::new(target).is_absolute() {
+                return Err(format!("alias of '{target}' is not a local directory"));
+            }
+            root = target.clone();
+            config.insert("rootPath".to_string(), target.clone());
+            SourceKind::Local
+        }
+        "" => return Err("remote has no type".to_string()),
+        "local" => {
+            return Err(
+                "local remotes have no fixed directory; add a Local source instead".to_string(),
+            )
+        }
+        "hdfs" => {
+            return Err(
+                "rclone connects to HDFS over RPC; Infimount needs a WebHDFS URL".to_string(),
+            )
+        }
+        other => return Err(format!("'{other}' remotes are not supported")),
+    };
+
+    Ok(MappedRemote {
+        obscured_password: matches!(kind, SourceKind::WebDav | SourceKind::Ftp)
+            && get("pass").is_some(),
+        source: Source {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            kind,
+            root,
+            config: Some(config),
+            read_only: false,
+        },
+    })
+}
+
+/// Settings from the OAuth token JSON rclone keeps for Google Drive.
+fn drive_token(token: &str) -> HashMap<String, String> {
+    #[derive(serde::Deserialize)]
+    struct Token {
+        access_token: Option<String>,
+        refresh_token: Option<String>,
+        expiry: Option<chrono::DateTime<chrono::FixedOffset>>,
+    }
+
+    let Ok(token) = serde_json::from_str::<Token>(token) else {
+        return HashMap::new();
+    };
+    [
+        ("accessToken", token.access_token),
+        ("refreshToken", token.refresh_token),
+        (
+            crate::oauth::EXPIRES_AT_KEY,
+            token
+                .expiry
+                .map(|at| at.with_timezone(&chrono::Utc).to_rfc3339()),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some((key.to_string(), value?)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remotes_are_mapped_and_gaps_reported() {
+        let conf = r#"
+# managed by rclone
+[minio]
+type = s3
+provider = Minio
+access_key_id = key
+secret_access_key = secret
+endpoint = http://localhost:9000
+
+[nas]
+type = webdav
+url = https://nas.local/dav
+user = me
+pass = obscured-value
+
+[photos]
+type = alias
+remote = /srv/photos
+
+[box]
+type = box
+"#;
+        let import = parse_rclone(conf).unwrap();
+        let names: Vec<&str> = import.sources.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["minio", "nas", "photos"]);
+
+        let minio = import.sources[0].config.as_ref().unwrap();
+        assert_eq!(minio["provider"], "minio");
+        assert_eq!(minio["accessKeyId"], "key");
+        assert_eq!(import.sources[2].kind, SourceKind::Local);
+
+        // Bucket missing for minio, password obscured for nas.
+        let incomplete: Vec<&str> = import.incomplete.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(incomplete, ["minio", "nas"]);
+        assert_eq!(import.unmapped.len(), 1);
+        assert_eq!(import.unmapped[0].remote_type, "box");
+
+        assert!(parse_rclone("RCLONE_ENCRYPT_V0:abc").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! MIME types for written files, so objects on S3, GCS and the like are
+//! served with the right `Content-Type` rather than
+//! `application/octet-stream`, which browsers download instead of showing.
+//! The type goes by the file extension; callers that know better pass their
+//! own.
+
+use opendal::Operator;
+
+use crate::util::extract_filename;
+
+/// Extensions (lowercase) and their types. Text types name UTF-8 so served
+/// pages and scripts don't fall back to Latin-1.
+const TYPES: &[(&str, &str)] = &[
+    ("7z", "application/x-7z-compressed"),
+    ("aac", "audio/aac"),
+    ("avif", "image/avif"),
+    ("bmp", "image/bmp"),
+    ("css", "text/css; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("doc", "application/msword"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    ("eml", "message/rfc822"),
+    ("epub", "application/epub+zip"),
+    ("flac", "audio/flac"),
+    ("gif", "image/gif"),
+    ("gz", "application/gzip"),
+    ("heic", "image/heic"),
+    ("htm", "text/html; charset=utf-8"),
+    ("html", "text/html; charset=utf-8"),
+    ("ico", "image/vnd.microsoft.icon"),
+    ("ics", "text/calendar; charset=utf-8"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("json", "application/json"),
+    ("jsonl", "application/jsonl"),
+    ("m4a", "audio/mp4"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("mkv", "video/x-matroska"),
+    ("mov", "video/quicktime"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("oga", "audio/ogg"),
+    ("ogg", "audio/ogg"),
+    ("ogv", "video/ogg"),
+    ("otf", "font/otf"),
+    ("parquet", "application/vnd.apache.parquet"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("ppt", "application/vnd.ms-powerpoint"),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    ("rtf", "application/rtf"),
+    ("svg", "image/svg+xml"),
+    ("tar", "application/x-tar"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("toml", "application/toml"),
+    ("tsv", "text/tab-separated-values; charset=utf-8"),
+    ("ttf", "font/ttf"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("wasm", "application/wasm"),
+    ("wav", "audio/wav"),
+    ("weba", "audio/webm"),
+    ("webm", "video/webm"),
+    ("webmanifest", "application/manifest+json"),
+    ("webp", "image/webp"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("xls", "application/vnd.ms-excel"),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("zip", "application/zip"),
+];
+
+/// The MIME type of `path`, going by its extension; `None` for unknown or
+/// missing extensions, which backends store as `application/octet-stream`.
+pub fn guess(path: &str) -> Option<&'static str> {
+    let name = extract_filename(path);
+    let (_, ext) = name.rsplit_once('.')?;
+    let ext = ext.to_ascii_lowercase();
+    TYPES
+        .binary_search_by(|(known, _)| known.cmp(&ext.as_str()))
+        .ok()
+        .map(|index| TYPES[index].1)
+}
+
+/// The type to store a file written to `path` on `op` with: `requested` if
+/// given, else the guess for its extension. `None` on backends that keep no
+/// content type (local folders, WebDAV, ...).
+pub(crate) fn for_write<'a>(
+    op: &Operator,
+    path: &str,
+    requested: Option<&'a str>,
+) -> Option<&'a str> {
+    if !op.info().full_capability().write_with_content_type {
+        return None;
+    }
+    requested
+        .map(str::trim)
+        .filter(|requested| !requested.is_empty())
+        .or_else(|| guess(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_sorted_for_binary_search() {
+        assert!(TYPES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn guesses_by_extension() {
+        assert_eq!(guess("site/index.HTML"), Some("text/html; charset=utf-8"));
+        assert_eq!(guess("photos/2024/beach.jpg"), Some("image/jpeg"));
+        assert_eq!(guess("/report.pdf"), Some("application/pdf"));
+        assert_eq!(guess("archive.tar.gz"), Some("application/gzip"));
+        assert_eq!(guess("Makefile"), None);
+        assert_eq!(guess("v1.2/data.unknown"), None);
+        assert_eq!(guess("folder.d/README"), None);
+    }
+
+    #[test]
+    fn requested_types_win_where_the_backend_keeps_them() {
+        let s3 = Operator::new(
+            opendal::services::S3::default()
+                .bucket("bucket")
+                .region("us-east-1")
+                .endpoint("http://127.0.0.1:1")
+                .disable_config_load(),
+        )
+        .unwrap()
+        .finish();
+        assert_eq!(for_write(&s3, "a.png", None), Some("image/png"));
+        assert_eq!(
+            for_write(&s3, "a.png", Some("image/x-custom")),
+            Some("image/x-custom")
+        );
+        assert_eq!(for_write(&s3, "a.png", Some(" ")), Some("image/png"));
+
+        let local = Operator::new(opendal::services::Fs::default().root("/tmp"))
+            .unwrap()
+            .finish();
+        assert_eq!(for_write(&local, "a.png", None), None);
+    }
+}
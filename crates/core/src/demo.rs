@@ -0,0 +1,287 @@
+//! Built-in "Demo" source with generated content.
+//!
+//! New users (and UI tests) can browse, preview, search and transfer without
+//! configuring real storage. The tree is generated deterministically into an
+//! in-memory operator shared by the whole process: documents, CSV/JSON data,
+//! BMP and SVG images, a WAV tone, source code, logs, a folder with many
+//! files and a few larger binaries. Changes last until the app quits.
+
+use opendal::services::Memory;
+use opendal::Operator;
+use tokio::sync::OnceCell;
+
+use crate::models::Result;
+
+/// Backend id of the demo source in storage records.
+pub const DEMO_BACKEND: &str = "demo";
+
+/// Files in the folder that exercises long listings.
+const INVOICE_COUNT: usize = 150;
+
+/// The shared demo operator, generated on first use.
+pub async fn operator() -> Result<Operator> {
+    static DEMO: OnceCell<Operator> = OnceCell::const_new();
+    DEMO.get_or_try_init(|| async {
+        let op = Operator::new(Memory::default())?.finish();
+        populate(&op).await?;
+        Ok(op)
+    })
+    .await
+    .cloned()
+}
+
+/// Write the demo tree into `op`.
+pub async fn populate(op: &Operator) -> Result<()> {
+    for (path, data) in demo_tree() {
+        op.write(&path, data).await?;
+    }
+    Ok(())
+}
+
+/// Every file of the demo tree as `(path, content)`. The same on every call.
+pub fn demo_tree() -> Vec<(String, Vec<u8>)> {
+    let mut rng = Lcg(0x1f2e_3d4c);
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        ("README.md".into(), README.as_bytes().to_vec()),
+        (
+            "Documents/Quarterly report.md".into(),
+            report(&mut rng).into_bytes(),
+        ),
+        (
+            "Documents/Budget 2024.csv".into(),
+            budget(&mut rng).into_bytes(),
+        ),
+        (
+            "Documents/Meeting notes.txt".into(),
+            MEETING_NOTES.as_bytes().to_vec(),
+        ),
+        (
+            "Documents/settings.json".into(),
+            SETTINGS_JSON.as_bytes().to_vec(),
+        ),
+        (
+            "Code/hello-infimount/Cargo.toml".into(),
+            CARGO_TOML.as_bytes().to_vec(),
+        ),
+        (
+            "Code/hello-infimount/src/main.rs".into(),
+            MAIN_RS.as_bytes().to_vec(),
+        ),
+        (
+            "Code/hello-infimount/.gitignore".into(),
+            b"/target\n".to_vec(),
+        ),
+        ("Music/A440 tone.wav".into(), sine_wav(440.0, 1)),
+        ("Logs/app.log".into(), log(&mut rng, 2_000).into_bytes()),
+        (
+            "Projects/alpha/beta/gamma/deeply nested.txt".into(),
+            b"Found me! Nested folders work too.\n".to_vec(),
+        ),
+        (
+            "Archive/backup-small.bin".into(),
+            noise(&mut rng, 256 * 1024),
+        ),
+        (
+            "Archive/backup-large.bin".into(),
+            noise(&mut rng, 4 * 1024 * 1024),
+        ),
+        ("Archive/empty.dat".into(), Vec::new()),
+    ];
+
+    for (year, count) in [(2023, 4), (2024, 6)] {
+        for n in 1..=count {
+            let hue = rng.next() % 360;
+            files.push((
+                format!("Photos/{year}/IMG_{year}{n:03}.bmp"),
+                gradient_bmp(96, 64, hue),
+            ));
+        }
+    }
+    for (name, color) in [("circle", "#3b82f6"), ("square", "#f97316")] {
+        files.push((format!("Photos/Shapes/{name}.svg"), shape_svg(name, color)));
+    }
+    for n in 1..=INVOICE_COUNT {
+        let amount = 50 + rng.next() % 5_000;
+        files.push((
+            format!("Documents/Invoices/invoice-{n:04}.txt"),
+            format!("Invoice #{n:04}\nCustomer: ACME Corp\nAmount due: ${amount}.00\n")
+                .into_bytes(),
+        ));
+    }
+    files
+}
+
+/// Small deterministic generator; the content only has to look varied.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        self.0 >> 33
+    }
+}
+
+const README: &str = "# Welcome to the Infimount demo\n\n\
+This source lives in memory and is generated on first use. Browse the \
+folders, open previews, search for files or drag items to another source.\n\n\
+Changes are kept until the app quits.\n";
+
+const MEETING_NOTES: &str = "Weekly sync\n===========\n\n\
+- Ship the demo source\n- Review transfer progress UI\n- Plan next release\n";
+
+const SETTINGS_JSON: &str = "{\n  \"theme\": \"dark\",\n  \"language\": \"en-US\",\n  \
+\"recentSources\": [\"demo\", \"local\"],\n  \"autoUpdate\": true\n}\n";
+
+const CARGO_TOML: &str =
+    "[package]\nname = \"hello-infimount\"\nversion = \"0.1.0\"\nedition = \"2021\"\n";
+
+const MAIN_RS: &str = "fn main() {\n    println!(\"Hello from the Infimount demo!\");\n}\n";
+
+fn report(rng: &mut Lcg) -> String {
+    let mut out =
+        String::from("# Quarterly report\n\n| Quarter | Revenue | Growth |\n|---|---|---|\n");
+    for quarter in 1..=4 {
+        let revenue = 100_000 + rng.next() % 50_000;
+        let growth = rng.next() % 20;
+        out.push_str(&format!("| Q{quarter} | ${revenue} | {growth}% |\n"));
+    }
+    out
+}
+
+fn budget(rng: &mut Lcg) -> String {
+    const CATEGORIES: &[&str] = &["Rent", "Salaries", "Travel", "Software", "Hardware"];
+    let mut out = String::from("month,category,amount\n");
+    for month in 1..=12 {
+        for category in CATEGORIES {
+            let amount = 500 + rng.next() % 20_000;
+            out.push_str(&format!("2024-{month:02},{category},{amount}\n"));
+        }
+    }
+    out
+}
+
+fn log(rng: &mut Lcg, lines: usize) -> String {
+    const LEVELS: &[&str] = &["INFO", "INFO", "INFO", "DEBUG", "WARN", "ERROR"];
+    let mut out = String::new();
+    for line in 0..lines {
+        let level = LEVELS[(rng.next() % LEVELS.len() as u64) as usize];
+        let (minute, second) = ((line / 60) % 60, line % 60);
+        out.push_str(&format!(
+            "2024-06-01T12:{minute:02}:{second:02}Z {level} request {} took {}ms\n",
+            rng.next() % 10_000,
+            rng.next() % 500
+        ));
+    }
+    out
+}
+
+fn noise(rng: &mut Lcg, len: usize) -> Vec<u8> {
+    (0..len).map(|_| rng.next() as u8).collect()
+}
+
+/// Uncompressed 24-bit BMP with a horizontal gradient around `hue`.
+fn gradient_bmp(width: u32, height: u32, hue: u64) -> Vec<u8> {
+    let row = (width * 3).div_ceil(4) * 4;
+    let pixels = row * height;
+    let mut out = Vec::with_capacity(54 + pixels as usize);
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&(54 + pixels).to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&54u32.to_le_bytes());
+    out.extend_from_slice(&40u32.to_le_bytes());
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&24u16.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&pixels.to_le_bytes());
+    out.extend_from_slice(&[0; 16]);
+    let base = [hue % 256, (hue * 7) % 256, (hue * 13) % 256];
+    for y in 0..height {
+        for x in 0..width {
+            let shade = (x * 255 / width.max(1)) as u64;
+            let v = (y * 255 / height.max(1)) as u64;
+            out.extend_from_slice(&[
+                ((base[0] + shade) / 2) as u8,
+                ((base[1] + v) / 2) as u8,
+                ((base[2] + 255 - shade) / 2) as u8,
+            ]);
+        }
+        out.resize(out.len() + (row - width * 3) as usize, 0);
+    }
+    out
+}
+
+fn shape_svg(shape: &str, color: &str) -> Vec<u8> {
+    let body = match shape {
+        "circle" => format!("<circle cx=\"32\" cy=\"32\" r=\"28\" fill=\"{color}\"/>"),
+        _ => format!("<rect x=\"6\" y=\"6\" width=\"52\" height=\"52\" fill=\"{color}\"/>"),
+    };
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"64\" height=\"64\" viewBox=\"0 0 64 64\">{body}</svg>\n"
+    )
+    .into_bytes()
+}
+
+/// 8 kHz mono 16-bit PCM sine tone.
+fn sine_wav(frequency: f64, seconds: u32) -> Vec<u8> {
+    const RATE: u32 = 8_000;
+    let samples = RATE * seconds;
+    let data_len = samples * 2;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&RATE.to_le_bytes());
+    out.extend_from_slice(&(RATE * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for n in 0..samples {
+        let t = f64::from(n) / f64::from(RATE);
+        let sample = (t * frequency * std::f64::consts::TAU).sin() * f64::from(i16::MAX) * 0.3;
+        out.extend_from_slice(&(sample as i16).to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::list_entries;
+
+    #[tokio::test]
+    async fn demo_source_has_a_browsable_tree() {
+        let op = operator().await.unwrap();
+        let root: Vec<_> = list_entries(&op, "/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        for folder in ["Documents", "Photos", "Music", "Code", "Archive"] {
+            assert!(root.iter().any(|name| name == folder), "{folder}");
+        }
+        let invoices = list_entries(&op, "Documents/Invoices").await.unwrap();
+        assert_eq!(invoices.len(), INVOICE_COUNT);
+
+        let bmp = op
+            .read("Photos/2024/IMG_2024001.bmp")
+            .await
+            .unwrap()
+            .to_vec();
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(
+            u32::from_le_bytes(bmp[2..6].try_into().unwrap()) as usize,
+            bmp.len()
+        );
+        assert_eq!(demo_tree(), demo_tree());
+    }
+}
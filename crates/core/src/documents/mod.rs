@@ -0,0 +1,87 @@
+//! Plain text of office documents, so content search can look inside them.
+//!
+//! Word (`.docx`), Excel (`.xlsx`) and PowerPoint (`.pptx`) files are zip
+//! archives of XML parts, read by `ooxml`: paragraphs, spreadsheet rows and
+//! slide paragraphs each become a line. PDF text is extracted by `pdf`;
+//! scanned pages come out empty. Every part read and the text returned are
+//! bounded, so a crafted archive cannot exhaust memory.
+
+mod ooxml;
+mod pdf;
+
+#[cfg(test)]
+pub(crate) use pdf::build as build_pdf;
+
+use crate::util::extract_filename;
+
+/// Most bytes one decompressed part (an XML file) may take.
+const MAX_PART_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Most bytes of text extracted from one document.
+const MAX_TEXT_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Docx,
+    Xlsx,
+    Pptx,
+    Pdf,
+}
+
+impl DocumentKind {
+    /// The kind of document `path` names, going by its extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let name = extract_filename(path);
+        let (_, ext) = name.rsplit_once('.')?;
+        match ext.to_ascii_lowercase().as_str() {
+            "docx" | "docm" => Some(Self::Docx),
+            "xlsx" | "xlsm" => Some(Self::Xlsx),
+            "pptx" | "pptm" => Some(Self::Pptx),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// The text of a document of `kind`, one paragraph, row or text line per
+/// line, cut to its first [`MAX_TEXT_BYTES`].
+pub fn extract_text(kind: DocumentKind, data: &[u8]) -> std::result::Result<String, String> {
+    let mut text = match kind {
+        DocumentKind::Docx => ooxml::docx_text(&mut ooxml::open(data)?)?,
+        DocumentKind::Xlsx => ooxml::xlsx_text(&mut ooxml::open(data)?)?,
+        DocumentKind::Pptx => ooxml::pptx_text(&mut ooxml::open(data)?)?,
+        DocumentKind::Pdf => pdf::text(data)?,
+    };
+    if text.len() > MAX_TEXT_BYTES {
+        let mut end = MAX_TEXT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_follow_the_extension() {
+        assert_eq!(
+            DocumentKind::from_path("contracts/Lease.DOCX"),
+            Some(DocumentKind::Docx)
+        );
+        assert_eq!(
+            DocumentKind::from_path("/q3.xlsx"),
+            Some(DocumentKind::Xlsx)
+        );
+        assert_eq!(
+            DocumentKind::from_path("deck.pptx"),
+            Some(DocumentKind::Pptx)
+        );
+        assert_eq!(DocumentKind::from_path("scan.pdf"), Some(DocumentKind::Pdf));
+        assert_eq!(DocumentKind::from_path("notes.txt"), None);
+        assert_eq!(DocumentKind::from_path("pdf"), None);
+    }
+}
@@ -0,0 +1,290 @@
+//! Text of Office Open XML documents. The parts are read from the zip
+//! archive with `zip` and scanned event by event with `quick-xml` rather
+//! than parsed into a tree: only the text runs matter, and the elements that
+//! end a paragraph, a cell or a row.
+
+use std::io::{Cursor, Read};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use super::MAX_PART_BYTES;
+
+pub(super) type Archive<'a> = ZipArchive<Cursor<&'a [u8]>>;
+
+pub(super) fn open(data: &[u8]) -> Result<Archive<'_>, String> {
+    ZipArchive::new(Cursor::new(data)).map_err(|e| e.to_string())
+}
+
+enum Token<'a> {
+    /// Local name (without namespace prefix), the `t` attribute (a cell's
+    /// type in spreadsheets), and whether the tag closes itself.
+    Open(&'a str, Option<&'a str>, bool),
+    Close(&'a str),
+    Text(&'a str),
+}
+
+/// Call `f` with the tokens of `xml`, entity and character references
+/// resolved.
+fn each_token(xml: &str, mut f: impl FnMut(Token<'_>)) -> Result<(), String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().check_end_names = false;
+    let malformed = |e: &dyn std::fmt::Display| format!("malformed XML: {e}");
+    loop {
+        match reader.read_event().map_err(|e| malformed(&e))? {
+            Event::Start(tag) => open_tag(&tag, false, &mut f),
+            Event::Empty(tag) => open_tag(&tag, true, &mut f),
+            Event::End(tag) => {
+                let name = tag.local_name();
+                f(Token::Close(
+                    std::str::from_utf8(name.as_ref()).unwrap_or_default(),
+                ));
+            }
+            Event::Text(text) => f(Token::Text(&text.xml_content().map_err(|e| malformed(&e))?)),
+            Event::CData(text) => f(Token::Text(&text.decode().map_err(|e| malformed(&e))?)),
+            Event::GeneralRef(reference) => {
+                let resolved = match reference.resolve_char_ref().map_err(|e| malformed(&e))? {
+                    Some(c) => Some(c.to_string()),
+                    None => {
+                        let name = reference.decode().map_err(|e| malformed(&e))?;
+                        quick_xml::escape::resolve_predefined_entity(&name).map(str::to_string)
+                    }
+                };
+                if let Some(text) = resolved {
+                    f(Token::Text(&text));
+                }
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+fn open_tag(tag: &BytesStart, self_closing: bool, f: &mut impl FnMut(Token<'_>)) {
+    let kind = tag
+        .try_get_attribute("t")
+        .ok()
+        .flatten()
+        .and_then(|attr| attr.unescape_value().ok());
+    let name = tag.local_name();
+    let name = std::str::from_utf8(name.as_ref()).unwrap_or_default();
+    f(Token::Open(name, kind.as_deref(), self_closing));
+}
+
+fn read_part(archive: &mut Archive, name: &str) -> Result<Option<String>, String> {
+    let part = match archive.by_name(name) {
+        Ok(part) => part,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("{name}: {e}")),
+    };
+    if part.size() > MAX_PART_BYTES {
+        return Err(format!("{name} is too large"));
+    }
+    let mut data = Vec::new();
+    part.take(MAX_PART_BYTES)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("{name}: {e}"))?;
+    Ok(Some(String::from_utf8_lossy(&data).into_owned()))
+}
+
+/// Text inside `text_element`s, with a line break after each
+/// `paragraph_element`, tabs and explicit breaks kept.
+fn paragraphs(
+    xml: &str,
+    text_element: &str,
+    paragraph_element: &str,
+    out: &mut String,
+) -> Result<(), String> {
+    let mut in_text = false;
+    each_token(xml, |token| match token {
+        Token::Open(name, _, self_closing) if name == text_element => in_text = !self_closing,
+        Token::Open("tab", _, _) => out.push('\t'),
+        Token::Open("br" | "cr", _, _) => out.push('\n'),
+        Token::Close(name) if name == text_element => in_text = false,
+        Token::Close(name) if name == paragraph_element => out.push('\n'),
+        Token::Text(text) if in_text => out.push_str(text),
+        _ => {}
+    })
+}
+
+/// Parts named `{prefix}{n}.xml`, in the order of `n`.
+fn numbered_parts(archive: &Archive, prefix: &str) -> Vec<String> {
+    let mut parts: Vec<(u32, &str)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let n = name.strip_prefix(prefix)?.strip_suffix(".xml")?;
+            Some((n.parse().ok()?, name))
+        })
+        .collect();
+    parts.sort();
+    parts
+        .into_iter()
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+pub(super) fn docx_text(archive: &mut Archive) -> Result<String, String> {
+    let xml = read_part(archive, "word/document.xml")?.ok_or("no word/document.xml")?;
+    let mut out = String::new();
+    paragraphs(&xml, "t", "p", &mut out)?;
+    Ok(out)
+}
+
+pub(super) fn pptx_text(archive: &mut Archive) -> Result<String, String> {
+    let slides = numbered_parts(archive, "ppt/slides/slide");
+    if slides.is_empty() {
+        return Err("no slides".to_string());
+    }
+    let mut out = String::new();
+    for slide in slides {
+        if let Some(xml) = read_part(archive, &slide)? {
+            paragraphs(&xml, "t", "p", &mut out)?;
+        }
+    }
+    Ok(out)
+}
+
+/// The shared strings table cells refer to by index.
+fn shared_strings(archive: &mut Archive) -> Result<Vec<String>, String> {
+    let Some(xml) = read_part(archive, "xl/sharedStrings.xml")? else {
+        return Ok(Vec::new());
+    };
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    // Phonetic guides (`rPh`) hold readings, not part of the string.
+    let mut in_phonetic = false;
+    each_token(&xml, |token| match token {
+        Token::Open("si", _, self_closing) => {
+            current.clear();
+            if self_closing {
+                strings.push(String::new());
+            }
+        }
+        Token::Close("si") => strings.push(std::mem::take(&mut current)),
+        Token::Open("rPh", _, self_closing) => in_phonetic = !self_closing,
+        Token::Close("rPh") => in_phonetic = false,
+        Token::Open("t", _, self_closing) => in_text = !self_closing && !in_phonetic,
+        Token::Close("t") => in_text = false,
+        Token::Text(text) if in_text => current.push_str(text),
+        _ => {}
+    })?;
+    Ok(strings)
+}
+
+pub(super) fn xlsx_text(archive: &mut Archive) -> Result<String, String> {
+    let sheets = numbered_parts(archive, "xl/worksheets/sheet");
+    if sheets.is_empty() {
+        return Err("no worksheets".to_string());
+    }
+    let shared = shared_strings(archive)?;
+    let mut out = String::new();
+    for sheet in sheets {
+        let Some(xml) = read_part(archive, &sheet)? else {
+            continue;
+        };
+        let mut row: Vec<String> = Vec::new();
+        let mut cell_type = String::new();
+        let mut value = String::new();
+        let mut in_value = false;
+        each_token(&xml, |token| match token {
+            Token::Open("c", kind, _) => {
+                cell_type = kind.unwrap_or_default().to_string();
+                value.clear();
+            }
+            Token::Open("v" | "t", _, self_closing) => in_value = !self_closing,
+            Token::Close("v" | "t") => in_value = false,
+            Token::Text(text) if in_value => value.push_str(text),
+            Token::Close("c") => {
+                let text = match cell_type.as_str() {
+                    "s" => value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| shared.get(index))
+                        .cloned()
+                        .unwrap_or_default(),
+                    _ => std::mem::take(&mut value),
+                };
+                if !text.is_empty() {
+                    row.push(text);
+                }
+            }
+            Token::Close("row") if !row.is_empty() => {
+                out.push_str(&row.join("\t"));
+                out.push('\n');
+                row.clear();
+            }
+            _ => {}
+        })?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// A zip archive of `files`, deflated.
+    fn build(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn docx_paragraphs_become_lines() {
+        let data = build(&[(
+            "word/document.xml",
+            r#"<?xml version="1.0"?><w:document xmlns:w="x"><w:body>
+            <w:p><w:r><w:t>Lease between </w:t></w:r><w:r><w:t xml:space="preserve">A &amp; B</w:t></w:r></w:p>
+            <w:p><w:r><w:t>Rent:</w:t><w:tab/><w:t>&#8364;900</w:t></w:r></w:p>
+            </w:body></w:document>"#,
+        )]);
+        let text = docx_text(&mut open(&data).unwrap()).unwrap();
+        assert_eq!(text, "Lease between A & B\nRent:\t\u{20ac}900\n");
+        assert!(open(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn pptx_slides_are_read_in_order() {
+        let slide = |text: &str| format!("<p:sld><a:p><a:r><a:t>{text}</a:t></a:r></a:p></p:sld>");
+        let (one, two, ten) = (slide("one"), slide("two"), slide("ten"));
+        let data = build(&[
+            ("ppt/slides/slide10.xml", ten.as_str()),
+            ("ppt/slides/slide2.xml", two.as_str()),
+            ("ppt/slides/slide1.xml", one.as_str()),
+            ("ppt/slides/_rels/slide1.xml.rels", "<Relationships/>"),
+        ]);
+        let text = pptx_text(&mut open(&data).unwrap()).unwrap();
+        assert_eq!(text, "one\ntwo\nten\n");
+    }
+
+    #[test]
+    fn xlsx_rows_resolve_shared_strings() {
+        let data = build(&[
+            (
+                "xl/sharedStrings.xml",
+                r#"<sst><si><t>Client</t></si><si><r><t>Acme </t></r><r><t>Corp</t></r><rPh><t>x</t></rPh></si></sst>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData>
+                <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="inlineStr"><is><t>Total</t></is></c></row>
+                <row r="2"><c r="A2" t="s"><v>1</v></c><c r="B2"><v>1250.5</v></c></row>
+                </sheetData></worksheet>"#,
+            ),
+        ]);
+        let text = xlsx_text(&mut open(&data).unwrap()).unwrap();
+        assert_eq!(text, "Client\tTotal\nAcme Corp\t1250.5\n");
+    }
+}
@@ -0,0 +1,80 @@
+//! Text of PDF files, extracted by `pdf-extract`, which follows each page's
+//! fonts and their encodings. Scanned pages come out empty.
+
+/// The text of the PDF `data`.
+pub(super) fn text(data: &[u8]) -> Result<String, String> {
+    // Some malformed files make pdf-extract panic rather than fail.
+    match std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(data)) {
+        Ok(Ok(text)) => Ok(text),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("unreadable PDF".to_string()),
+    }
+}
+
+/// A one-page PDF showing `lines` in Helvetica, compressed, for tests.
+#[cfg(test)]
+pub(crate) fn build(lines: &[&str]) -> Vec<u8> {
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Document, Object, Stream};
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+    let mut operations = vec![
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["F1".into(), 12.into()]),
+        Operation::new("Td", vec![72.into(), 720.into()]),
+    ];
+    for line in lines {
+        operations.push(Operation::new("Tj", vec![Object::string_literal(*line)]));
+        operations.push(Operation::new("Td", vec![0.into(), (-14).into()]));
+    }
+    operations.push(Operation::new("ET", vec![]));
+    let content = Content { operations };
+    let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.compress();
+    let mut out = Vec::new();
+    doc.save_to(&mut out).unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_of_compressed_pages() {
+        let pdf = build(&["Contract with Acme", "Payment due (net 30)"]);
+        let text = text(&pdf).unwrap();
+        assert!(text.contains("Contract with Acme"), "{text:?}");
+        assert!(text.contains("Payment due (net 30)"), "{text:?}");
+        assert!(super::text(b"plain text").is_err());
+    }
+}
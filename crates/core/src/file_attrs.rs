@@ -0,0 +1,198 @@
+//! Modification times and POSIX permissions carried along with copied
+//! files, unless the job turned this off with
+//! [`crate::transfer_report::TransferReport::set_preserve_attributes`].
+//!
+//! Files written to a Local source get them set directly. Backends that
+//! store user metadata get them as `mtime` (seconds since the epoch) and
+//! `mode` (octal) entries, the keys rclone uses, and files read back from
+//! such an object take their times from there rather than from when the
+//! object was written. Other backends keep only their own time of the write.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use opendal::Operator;
+
+use crate::models::Result;
+use crate::operations::{is_local_fs, local_fs_path};
+
+const MTIME_KEY: &str = "mtime";
+const MODE_KEY: &str = "mode";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct FileAttrs {
+    pub modified: Option<SystemTime>,
+    /// Permission bits (`0o7777` at most); only known on Unix.
+    pub mode: Option<u32>,
+}
+
+impl FileAttrs {
+    pub(crate) fn from_local(meta: &std::fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+            Some(meta.permissions().mode() & 0o7777)
+        };
+        #[cfg(not(unix))]
+        let mode = None;
+        Self {
+            modified: meta.modified().ok(),
+            mode,
+        }
+    }
+
+    /// Attributes of an object as its backend reports them.
+    pub(crate) fn from_metadata(meta: &opendal::Metadata) -> Self {
+        let user = meta.user_metadata();
+        let stored = |key: &str| user.and_then(|user| user.get(key));
+        Self {
+            modified: stored(MTIME_KEY)
+                .map(String::as_str)
+                .and_then(parse_mtime)
+                .or_else(|| {
+                    let at = meta.last_modified()?.to_string();
+                    DateTime::parse_from_rfc3339(&at)
+                        .ok()
+                        .map(|at| SystemTime::from(at.with_timezone(&Utc)))
+                }),
+            mode: stored(MODE_KEY)
+                .and_then(|mode| u32::from_str_radix(mode, 8).ok())
+                .map(|mode| mode & 0o7777),
+        }
+    }
+
+    /// Attributes of `path` on `op`, with its `meta`; read from disk on
+    /// Local sources, which OpenDAL reports no permissions for.
+    pub(crate) async fn of(op: &Operator, path: &str, meta: &opendal::Metadata) -> Self {
+        if is_local_fs(op) {
+            if let Ok(local) = local_fs_path(op, path) {
+                if let Ok(local_meta) = tokio::fs::metadata(&local).await {
+                    return Self::from_local(&local_meta);
+                }
+            }
+        }
+        Self::from_metadata(meta)
+    }
+
+    /// The attributes as user metadata entries.
+    pub(crate) fn user_metadata(&self) -> HashMap<String, String> {
+        let mut user = HashMap::new();
+        if let Some(since_epoch) = self
+            .modified
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+        {
+            user.insert(
+                MTIME_KEY.to_string(),
+                format!(
+                    "{}.{:09}",
+                    since_epoch.as_secs(),
+                    since_epoch.subsec_nanos()
+                ),
+            );
+        }
+        if let Some(mode) = self.mode {
+            user.insert(MODE_KEY.to_string(), format!("{mode:o}"));
+        }
+        user
+    }
+
+    /// Set the attributes on the local file `path`.
+    async fn apply_local(self, path: PathBuf) -> std::io::Result<()> {
+        tokio::task::spawn_blocking(move || {
+            if let Some(modified) = self.modified {
+                std::fs::File::options()
+                    .write(true)
+                    .open(&path)?
+                    .set_modified(modified)?;
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Some(mode) = self.mode {
+                    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+                }
+            }
+            Ok(())
+        })
+        .await
+        .map_err(std::io::Error::other)?
+    }
+}
+
+/// `1700000000.5` style seconds since the epoch.
+fn parse_mtime(value: &str) -> Option<SystemTime> {
+    let (secs, fraction) = value.trim().split_once('.').unwrap_or((value.trim(), ""));
+    let secs = secs.parse().ok()?;
+    let nanos = match fraction.get(..fraction.len().min(9)) {
+        Some("") | None => 0,
+        Some(digits) => digits.parse::<u32>().ok()? * 10u32.pow(9 - digits.len() as u32),
+    };
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+}
+
+/// Give the file just written at `path` on `op` the attributes `attrs`,
+/// when `op` is a Local source. Files on other backends got them, if at
+/// all, as user metadata while being written.
+pub(crate) async fn apply(op: &Operator, path: &str, attrs: FileAttrs) -> Result<()> {
+    if is_local_fs(op) {
+        attrs.apply_local(local_fs_path(op, path)?).await?;
+    }
+    Ok(())
+}
+
+/// Whether files written to `op` can carry attributes as user metadata.
+pub(crate) fn stores_in_user_metadata(op: &Operator) -> bool {
+    !is_local_fs(op) && op.info().full_capability().write_with_user_metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_metadata_round_trips() {
+        let attrs = FileAttrs {
+            modified: Some(UNIX_EPOCH + Duration::new(1_700_000_000, 250_000_000)),
+            mode: Some(0o640),
+        };
+        let user = attrs.user_metadata();
+        assert_eq!(user["mtime"], "1700000000.250000000");
+        assert_eq!(user["mode"], "640");
+
+        let meta = opendal::Metadata::new(opendal::EntryMode::FILE).with_user_metadata(user);
+        assert_eq!(FileAttrs::from_metadata(&meta), attrs);
+        assert_eq!(
+            parse_mtime("1700000000.5"),
+            Some(UNIX_EPOCH + Duration::new(1_700_000_000, 500_000_000))
+        );
+        assert_eq!(
+            parse_mtime("12"),
+            Some(UNIX_EPOCH + Duration::from_secs(12))
+        );
+        assert_eq!(parse_mtime("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn applies_to_local_files() {
+        let root = std::env::temp_dir().join(format!("infimount-attrs-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let op = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+        op.write("report.txt", "q3").await.unwrap();
+
+        let modified = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let attrs = FileAttrs {
+            modified: Some(modified),
+            mode: Some(0o600),
+        };
+        apply(&op, "report.txt", attrs).await.unwrap();
+        let meta = std::fs::metadata(root.join("report.txt")).unwrap();
+        assert_eq!(meta.modified().unwrap(), modified);
+        #[cfg(unix)]
+        assert_eq!(FileAttrs::from_local(&meta), attrs);
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
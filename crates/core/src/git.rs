@@ -0,0 +1,202 @@
+//! Git awareness for Local sources.
+//!
+//! Entries inside a git working tree can be annotated with their status
+//! (`git_annotations` preference, see
+//! [`crate::operations::annotate_git_status`]), and recursive uploads and
+//! folder transfers out of one can skip what its `.gitignore` files exclude
+//! (`honor_gitignore`, see [`crate::transfer_report::TransferFilter`]).
+//! Both read the repository with libgit2; with the `git` feature disabled
+//! folders simply look like they are not in a repository.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Entry;
+
+/// How git sees an entry. Clean, tracked entries have no status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitStatus {
+    /// Changed, added, deleted, renamed or conflicted (for folders: something
+    /// inside is).
+    Modified,
+    /// Not tracked and not ignored.
+    Untracked,
+    /// Excluded by a `.gitignore` (or `info/exclude`) rule.
+    Ignored,
+}
+
+/// Status of the direct children of `dir`, keyed by name. Empty when `dir`
+/// is not inside a working tree.
+pub async fn dir_status(dir: &Path) -> HashMap<String, GitStatus> {
+    let mut out = HashMap::new();
+    for (rel, status) in statuses(dir).await.unwrap_or_default() {
+        match rel.split_once('/') {
+            // The child itself (folders are reported as `name/`).
+            None | Some((_, "")) => {
+                let name = rel.trim_end_matches('/');
+                out.insert(name.to_string(), status);
+            }
+            // Something inside a child folder; ignored content does not make
+            // the folder itself interesting.
+            Some((name, _)) if status != GitStatus::Ignored => {
+                let current = out.entry(name.to_string()).or_insert(status);
+                if status == GitStatus::Modified {
+                    *current = GitStatus::Modified;
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    out
+}
+
+/// Set [`Entry::git_status`] on `entries`, listed from the local folder `dir`.
+pub async fn annotate(dir: &Path, entries: &mut [Entry]) {
+    let statuses = dir_status(dir).await;
+    if statuses.is_empty() {
+        return;
+    }
+    for entry in entries {
+        entry.git_status = statuses.get(&entry.name).copied();
+    }
+}
+
+/// What the `.gitignore` rules of the working tree exclude below a folder.
+#[derive(Debug, Default)]
+pub struct GitIgnored {
+    paths: HashSet<String>,
+}
+
+impl GitIgnored {
+    /// Ignored paths below `dir`; empty when it is not in a working tree.
+    pub async fn load(dir: &Path) -> Self {
+        let paths = statuses(dir)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, status)| *status == GitStatus::Ignored)
+            .map(|(rel, _)| rel)
+            .collect();
+        Self { paths }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Whether `rel` (relative to the loaded folder, `/`-separated, folders
+    /// with a trailing `/`) is ignored itself or lies in an ignored folder.
+    pub fn contains(&self, rel: &str) -> bool {
+        self.paths.contains(rel)
+            || rel
+                .match_indices('/')
+                .any(|(end, _)| self.paths.contains(&rel[..=end]))
+    }
+}
+
+/// Everything below `dir` that is not clean, relative to it, as `git status
+/// --ignored` lists it: untracked and ignored folders are reported once as
+/// `name/` rather than file by file. `None` outside a working tree.
+#[cfg(feature = "git")]
+async fn statuses(dir: &Path) -> Option<Vec<(String, GitStatus)>> {
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let repo = git2::Repository::discover(&dir).ok()?;
+        let workdir = repo.workdir()?.canonicalize().ok()?;
+        let prefix: Vec<_> = dir
+            .canonicalize()
+            .ok()?
+            .strip_prefix(&workdir)
+            .ok()?
+            .components()
+            .map(|part| format!("{}/", part.as_os_str().to_string_lossy()))
+            .collect();
+        let prefix = prefix.concat();
+
+        let mut options = git2::StatusOptions::new();
+        options
+            .include_untracked(true)
+            .include_ignored(true)
+            .recurse_untracked_dirs(false)
+            .recurse_ignored_dirs(false);
+        if !prefix.is_empty() {
+            options.pathspec(&prefix);
+        }
+        let statuses = repo.statuses(Some(&mut options)).ok()?;
+        let found = statuses
+            .iter()
+            .filter_map(|entry| {
+                let path = String::from_utf8_lossy(entry.path_bytes());
+                let rel = path.strip_prefix(prefix.as_str())?.to_string();
+                let flags = entry.status();
+                let status = if flags.is_ignored() {
+                    GitStatus::Ignored
+                } else if flags == git2::Status::WT_NEW {
+                    GitStatus::Untracked
+                } else {
+                    GitStatus::Modified
+                };
+                Some((rel, status))
+            })
+            .collect();
+        Some(found)
+    })
+    .await
+    .ok()?
+}
+
+#[cfg(not(feature = "git"))]
+async fn statuses(_dir: &Path) -> Option<Vec<(String, GitStatus)>> {
+    None
+}
+
+#[cfg(all(test, feature = "git"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_status_and_ignored_paths() {
+        let dir = std::env::temp_dir().join(format!("infimount-git-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        let repo = git2::Repository::init(&dir).unwrap();
+        std::fs::write(dir.join(".gitignore"), "target/\n*.log\n").unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "old").unwrap();
+        std::fs::write(dir.join("README"), "readme").unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["."], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let author = git2::Signature::now("t", "t@t").unwrap();
+        repo.commit(Some("HEAD"), &author, &author, "init", &tree, &[])
+            .unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "new").unwrap();
+        std::fs::write(dir.join("notes.txt"), "new file").unwrap();
+        std::fs::write(dir.join("run.log"), "log").unwrap();
+        std::fs::create_dir_all(dir.join("target/debug")).unwrap();
+        std::fs::write(dir.join("target/debug/app"), "bin").unwrap();
+
+        let status = dir_status(&dir).await;
+        assert_eq!(status.get("src"), Some(&GitStatus::Modified));
+        assert_eq!(status.get("notes.txt"), Some(&GitStatus::Untracked));
+        assert_eq!(status.get("run.log"), Some(&GitStatus::Ignored));
+        assert_eq!(status.get("target"), Some(&GitStatus::Ignored));
+        assert_eq!(status.get("README"), None);
+
+        let sub = dir_status(&dir.join("src")).await;
+        assert_eq!(sub.get("lib.rs"), Some(&GitStatus::Modified));
+
+        let ignored = GitIgnored::load(&dir).await;
+        assert!(ignored.contains("run.log"));
+        assert!(ignored.contains("target/"));
+        assert!(ignored.contains("target/debug/app"));
+        assert!(!ignored.contains("notes.txt"));
+        assert!(!ignored.contains("src/lib.rs"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+//! Glob patterns for filename search.
+//!
+//! Patterns match `/`-separated paths relative to the folder searched: `*`
+//! matches any run of characters within a segment, `?` a single character,
+//! `[abc]`, `[a-z]` and `[!abc]` one character of a class, and a `**` segment
+//! any number of folders (including none), so `**/*.pdf` finds PDFs at any
+//! depth. Matching ignores case, as file searches in the UI do. Patterns are
+//! compiled by `globset` into a regular expression, so a match takes time
+//! linear in the path however many `*` the pattern has.
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::models::{CoreError, Result};
+
+#[derive(Debug, Clone)]
+pub struct Glob {
+    matcher: GlobMatcher,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim().trim_start_matches('/');
+        if pattern.is_empty() {
+            return Err(CoreError::Config("empty search pattern".to_string()));
+        }
+        // Empty segments are dropped, and `**` only spans folders as a
+        // segment of its own; elsewhere it is a plain `*`.
+        let pattern = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment {
+                "**" => segment.to_string(),
+                _ => collapse_stars(segment),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let matcher = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .case_insensitive(true)
+            .build()
+            .map_err(|_| CoreError::Config(format!("invalid search pattern: {pattern}")))?
+            .compile_matcher();
+        Ok(Self { matcher })
+    }
+
+    /// Whether `path` (relative, `/`-separated) matches the whole pattern.
+    pub fn matches(&self, path: &str) -> bool {
+        self.matcher.is_match(path.trim_matches('/'))
+    }
+}
+
+fn collapse_stars(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for c in segment.chars() {
+        if c != '*' || !out.ends_with('*') {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_segments_and_any_depth() {
+        let pdfs = Glob::new("**/*.pdf").unwrap();
+        assert!(pdfs.matches("report.PDF"));
+        assert!(pdfs.matches("a/b/c/report.pdf"));
+        assert!(!pdfs.matches("a/report.pdf.txt"));
+
+        let top = Glob::new("*.txt").unwrap();
+        assert!(top.matches("notes.txt"));
+        assert!(!top.matches("docs/notes.txt"));
+
+        let photos = Glob::new("photos/**/IMG_20[0-2]?.jpg").unwrap();
+        assert!(photos.matches("photos/IMG_2019.jpg"));
+        assert!(photos.matches("Photos/2024/trip/img_2023.jpg"));
+        assert!(!photos.matches("photos/IMG_2031.jpg"));
+        assert!(Glob::new("[!a]*").unwrap().matches("beta"));
+        assert!(!Glob::new("[!a]*").unwrap().matches("alpha"));
+
+        assert!(Glob::new("").is_err());
+        assert!(Glob::new("file[ab").is_err());
+    }
+
+    #[test]
+    fn many_stars_do_not_backtrack() {
+        let glob = Glob::new("*a*a*a*a*a*a*a*a*a*a*a*a*b").unwrap();
+        let name = "a".repeat(200);
+        assert!(!glob.matches(&name));
+        assert!(glob.matches(&format!("{name}b")));
+
+        let deep = Glob::new("**/**/**/**/**/**/**/**/x").unwrap();
+        let path = vec!["d"; 60].join("/");
+        assert!(!deep.matches(&path));
+        assert!(deep.matches(&format!("{path}/x")));
+    }
+}
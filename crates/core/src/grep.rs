@@ -0,0 +1,334 @@
+//! Content search ("grep") over the text files of a subtree.
+//!
+//! Folders are walked breadth-first and the files of each are read a few at
+//! a time; files above [`GrepOptions::max_file_size`], placeholders of
+//! OS-synced folders and files that look binary are skipped. With
+//! [`GrepOptions::documents`], the text of office documents and PDFs is
+//! searched too, see [`crate::documents`], and with [`GrepOptions::ocr`]
+//! the text recognized in images and scanned PDFs, see [`crate::ocr`].
+//! Every matching line is handed to the caller as it is found, so the
+//! desktop app can show results while the search goes on, and the search
+//! stops once [`GrepOptions::max_matches`] lines matched or it is cancelled.
+
+use std::collections::VecDeque;
+
+use futures::stream::{self, StreamExt};
+use opendal::Operator;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::cancel::CancelToken;
+use crate::documents::{self, DocumentKind};
+use crate::junk;
+use crate::models::{CoreError, Result};
+use crate::ocr;
+use crate::operations::{self, normalize_list_path, SEARCH_CONCURRENCY};
+
+/// Bytes inspected to decide whether a file is binary.
+const SNIFF_BYTES: usize = 8 * 1024;
+/// Matched lines are cut to this many characters.
+const MAX_LINE_CHARS: usize = 500;
+/// Compiled size limit for user-supplied regular expressions.
+const MAX_REGEX_SIZE: usize = 1 << 20;
+/// Folders below the root searched at most this deep.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrepOptions {
+    /// Treat the query as a regular expression instead of plain text.
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Larger files are not searched.
+    pub max_file_size: u64,
+    /// Stop after this many matching lines.
+    pub max_matches: usize,
+    /// Also search the text of Word, Excel, PowerPoint and PDF documents;
+    /// a match's line number is then the paragraph, row or text line.
+    pub documents: bool,
+    /// Larger documents are not searched.
+    pub max_document_size: u64,
+    /// Also search the text recognized in images and in PDFs without text
+    /// of their own. Slow, and only works in builds with the `ocr` feature;
+    /// otherwise those files are skipped.
+    pub ocr: bool,
+    /// Also search OS metadata files such as `desktop.ini`, see
+    /// [`crate::junk`]; normally `Preferences::include_system_files`.
+    pub include_system_files: bool,
+}
+
+impl Default for GrepOptions {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            case_sensitive: false,
+            max_file_size: 10 * 1024 * 1024,
+            max_matches: 1_000,
+            documents: false,
+            max_document_size: 50 * 1024 * 1024,
+            ocr: false,
+            include_system_files: false,
+        }
+    }
+}
+
+/// One matching line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrepMatch {
+    pub path: String,
+    /// 1-based.
+    pub line_number: usize,
+    pub line: String,
+}
+
+/// Search the text files below `root` for lines matching `query`, calling
+/// `on_match` for each; return `false` from it to stop. Cancelling `cancel`
+/// stops the search before the next file. Folders and files that cannot be
+/// read below `root` are skipped. Returns the number of matching lines.
+pub async fn grep(
+    op: &Operator,
+    root: &str,
+    query: &str,
+    options: &GrepOptions,
+    cancel: &CancelToken,
+    mut on_match: impl FnMut(GrepMatch) -> bool,
+) -> Result<usize> {
+    let matcher = matcher(query, options)?;
+    let root = normalize_list_path(root);
+    let mut pending = VecDeque::from([(root.clone(), 0)]);
+    let mut found = 0;
+
+    while let Some((dir, depth)) = pending.pop_front() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let entries = match operations::list_entries(op, &dir).await {
+            Ok(entries) => entries,
+            Err(error) if dir == root => return Err(error),
+            Err(_) => continue,
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            if !options.include_system_files && junk::is_junk(&entry.name) {
+                continue;
+            }
+            let entry_dir = normalize_list_path(&entry.path);
+            if entry.is_dir {
+                if entry_dir != dir && depth < MAX_DEPTH {
+                    pending.push_back((entry_dir, depth + 1));
+                }
+            } else if !entry.online_only && entry.size <= size_limit(&entry.path, options) {
+                files.push(entry.path);
+            }
+        }
+
+        let mut reads = stream::iter(files)
+            .map(|path| async move {
+                let text = file_text(op, &path, options).await;
+                (path, text)
+            })
+            .buffered(SEARCH_CONCURRENCY);
+        while let Some((path, text)) = reads.next().await {
+            if cancel.is_cancelled() {
+                return Ok(found);
+            }
+            let Some(text) = text else {
+                continue;
+            };
+            for (index, line) in text.lines().enumerate() {
+                if !matcher.is_match(line) {
+                    continue;
+                }
+                found += 1;
+                let hit = GrepMatch {
+                    path: path.clone(),
+                    line_number: index + 1,
+                    line: line.chars().take(MAX_LINE_CHARS).collect(),
+                };
+                if !on_match(hit) || found >= options.max_matches {
+                    return Ok(found);
+                }
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn size_limit(path: &str, options: &GrepOptions) -> u64 {
+    if document_kind(path, options).is_some() || (options.ocr && ocr::is_image(path)) {
+        options.max_document_size
+    } else {
+        options.max_file_size
+    }
+}
+
+/// The kind of `path` when its text is to be extracted rather than read.
+fn document_kind(path: &str, options: &GrepOptions) -> Option<DocumentKind> {
+    DocumentKind::from_path(path)
+        .filter(|&kind| options.documents || (options.ocr && kind == DocumentKind::Pdf))
+}
+
+/// The text to search in `path`: the extracted or recognized text of a
+/// document or image when `options` ask for it, otherwise its contents
+/// unless they look binary.
+async fn file_text(op: &Operator, path: &str, options: &GrepOptions) -> Option<String> {
+    let data = operations::read_full(op, path).await.ok()?;
+    if options.ocr && ocr::is_image(path) {
+        return ocr::image_text(&data).await.ok();
+    }
+    if let Some(kind) = document_kind(path, options) {
+        let (text, data) =
+            tokio::task::spawn_blocking(move || (documents::extract_text(kind, &data), data))
+                .await
+                .ok()?;
+        let text = text.ok().filter(|text| !text.trim().is_empty());
+        // Scanned PDFs have no text of their own.
+        if text.is_none() && options.ocr && kind == DocumentKind::Pdf {
+            return ocr::pdf_text(&data).await.ok();
+        }
+        return text;
+    }
+    if looks_binary(&data) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&data).into_owned())
+}
+
+fn matcher(query: &str, options: &GrepOptions) -> Result<Regex> {
+    if query.is_empty() {
+        return Err(CoreError::Config("empty search query".to_string()));
+    }
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .size_limit(MAX_REGEX_SIZE)
+        .build()
+        .map_err(|error| CoreError::Config(format!("invalid search pattern: {error}")))
+}
+
+/// NUL bytes, or mostly control characters, in the first [`SNIFF_BYTES`].
+fn looks_binary(data: &[u8]) -> bool {
+    let head = &data[..data.len().min(SNIFF_BYTES)];
+    if head.contains(&0) {
+        return true;
+    }
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0c))
+        .count();
+    control * 10 > head.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    #[tokio::test]
+    async fn grep_finds_lines_in_text_files_only() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("src/main.rs", "fn main() {\n    todo!()\n}\n")
+            .await
+            .unwrap();
+        op.write("src/lib/util.rs", "// TODO: tidy\nfn util() {}\n")
+            .await
+            .unwrap();
+        op.write("src/blob.bin", b"todo\0\x01\x02".to_vec())
+            .await
+            .unwrap();
+        op.write("src/big.txt", "todo ".repeat(100)).await.unwrap();
+
+        let options = GrepOptions {
+            max_file_size: 100,
+            ..Default::default()
+        };
+        let cancel = CancelToken::new();
+        let mut hits = Vec::new();
+        let count = grep(&op, "/src", "todo", &options, &cancel, |hit| {
+            hits.push((hit.path, hit.line_number));
+            true
+        })
+        .await
+        .unwrap();
+        hits.sort();
+        assert_eq!(count, 2);
+        assert_eq!(
+            hits,
+            [
+                ("src/lib/util.rs".to_string(), 1),
+                ("src/main.rs".to_string(), 2)
+            ]
+        );
+
+        let regex = GrepOptions {
+            regex: true,
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let count = grep(&op, "src", r"^fn \w+\(\)", &regex, &cancel, |_| true)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(grep(&op, "src", "(", &regex, &cancel, |_| true)
+            .await
+            .is_err());
+
+        cancel.cancel();
+        assert_eq!(
+            grep(&op, "src", "todo", &options, &cancel, |_| true)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_searches_documents_when_asked() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        let pdf = documents::build_pdf(&["Lease: Acme Corp"]);
+        op.write("contracts/lease.pdf", pdf).await.unwrap();
+        op.write("contracts/notes.txt", "acme called")
+            .await
+            .unwrap();
+
+        let cancel = CancelToken::new();
+        let search = |options: GrepOptions| {
+            let (op, cancel) = (&op, &cancel);
+            async move {
+                let mut hits = Vec::new();
+                grep(op, "contracts", "acme", &options, cancel, |hit| {
+                    hits.push((hit.path, hit.line));
+                    true
+                })
+                .await
+                .unwrap();
+                hits.sort();
+                hits
+            }
+        };
+        // Without `documents` the PDF is only searched as it is stored,
+        // where its text is compressed.
+        assert_eq!(
+            search(GrepOptions::default()).await,
+            [("contracts/notes.txt".to_string(), "acme called".to_string())]
+        );
+        let documents = GrepOptions {
+            documents: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            search(documents).await,
+            [
+                (
+                    "contracts/lease.pdf".to_string(),
+                    "Lease: Acme Corp".to_string()
+                ),
+                ("contracts/notes.txt".to_string(), "acme called".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Persistent cache of computed checksums.
+//!
+//! Entries are keyed by source id, path and algorithm and remember the
+//! etag/size the object had when it was hashed. A lookup only hits when the
+//! current etag and size still match, so changed objects are re-read while
+//! unchanged remote objects are not.
+
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::models::Result;
+use crate::operations::{self, normalize_opendal_path, ChecksumAlgorithm};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedHash {
+    pub etag: Option<String>,
+    pub size: u64,
+    pub digest: String,
+}
+
+#[derive(Debug, Default)]
+pub struct HashCache {
+    path: Option<PathBuf>,
+    entries: HashMap<String, CachedHash>,
+    dirty: bool,
+}
+
+fn cache_key(source_id: &str, path: &str, algorithm: &str) -> String {
+    format!("{source_id}\n{algorithm}\n{}", normalize_opendal_path(path))
+}
+
+impl HashCache {
+    /// In-memory cache that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the cache stored at `path`. A missing or unreadable file yields an
+    /// empty cache: losing cached hashes only costs a re-read.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// Load the cache from `hash-cache.json` in the config directory.
+    pub fn load_default() -> Self {
+        Self::load(config::data_dir().join("hash-cache.json"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Cached digest, if the object still has the etag and size it was hashed with.
+    pub fn get(
+        &self,
+        source_id: &str,
+        path: &str,
+        algorithm: &str,
+        etag: Option<&str>,
+        size: u64,
+    ) -> Option<&str> {
+        self.entries
+            .get(&cache_key(source_id, path, algorithm))
+            .filter(|cached| cached.size == size && cached.etag.as_deref() == etag)
+            .map(|cached| cached.digest.as_str())
+    }
+
+    pub fn insert(
+        &mut self,
+        source_id: &str,
+        path: &str,
+        algorithm: &str,
+        etag: Option<String>,
+        size: u64,
+        digest: String,
+    ) {
+        self.entries.insert(
+            cache_key(source_id, path, algorithm),
+            CachedHash { etag, size, digest },
+        );
+        self.dirty = true;
+    }
+
+    /// Drop every cached digest for `path` (all algorithms).
+    pub fn invalidate(&mut self, source_id: &str, path: &str) {
+        let normalized = normalize_opendal_path(path);
+        let prefix = format!("{source_id}\n");
+        let before = self.entries.len();
+        self.entries.retain(|key, _| {
+            !(key.starts_with(&prefix) && key.rsplit('\n').next() == Some(normalized.as_str()))
+        });
+        self.dirty |= self.entries.len() != before;
+    }
+
+    /// Drop every cached digest belonging to a source.
+    pub fn invalidate_source(&mut self, source_id: &str) {
+        let prefix = format!("{source_id}\n");
+        let before = self.entries.len();
+        self.entries.retain(|key, _| !key.starts_with(&prefix));
+        self.dirty |= self.entries.len() != before;
+    }
+
+    /// Return the cached digest for `path`, or compute it with `compute` and
+    /// remember the result. The object is stat'ed to get its etag and size.
+    pub async fn get_or_compute<F, Fut>(
+        &mut self,
+        op: &Operator,
+        source_id: &str,
+        path: &str,
+        algorithm: &str,
+        compute: F,
+    ) -> Result<String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let meta = op.stat(&normalize_opendal_path(path)).await?;
+        let etag = meta.etag().map(str::to_string);
+        let size = meta.content_length();
+
+        if let Some(digest) = self.get(source_id, path, algorithm, etag.as_deref(), size) {
+            return Ok(digest.to_string());
+        }
+
+        let digest = compute().await?;
+        self.insert(source_id, path, algorithm, etag, size, digest.clone());
+        Ok(digest)
+    }
+
+    /// Write the cache back to disk if it changed.
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+        write_entries(path, &self.entries)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// [`operations::checksum`] through the process-wide cache stored in
+/// `hash-cache.json`, so unchanged objects are not read again. The cache is
+/// not locked while the object is hashed.
+pub async fn cached_checksum(
+    op: &Operator,
+    source_id: &str,
+    path: &str,
+    algorithm: ChecksumAlgorithm,
+) -> Result<String> {
+    static CACHE: OnceLock<Mutex<HashCache>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashCache::load_default()));
+    let lock = || cache.lock().unwrap_or_else(|e| e.into_inner());
+
+    let meta = op.stat(&normalize_opendal_path(path)).await?;
+    let etag = meta.etag().map(str::to_string);
+    let size = meta.content_length();
+    if let Some(digest) = lock().get(source_id, path, algorithm.as_str(), etag.as_deref(), size) {
+        return Ok(digest.to_string());
+    }
+
+    let digest = operations::checksum(op, path, algorithm).await?;
+    let mut cache = lock();
+    cache.insert(
+        source_id,
+        path,
+        algorithm.as_str(),
+        etag,
+        size,
+        digest.clone(),
+    );
+    cache.save()?;
+    Ok(digest)
+}
+
+fn write_entries(path: &Path, entries: &HashMap<String, CachedHash>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let data = serde_json::to_string(entries)?;
+    fs::write(path, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn lookups_miss_when_etag_or_size_change() {
+        let mut cache = HashCache::in_memory();
+        cache.insert(
+            "src",
+            "/a.bin",
+            "sha256",
+            Some("\"v1\"".to_string()),
+            10,
+            "abc".to_string(),
+        );
+
+        assert_eq!(
+            cache.get("src", "a.bin", "sha256", Some("\"v1\""), 10),
+            Some("abc")
+        );
+        assert_eq!(
+            cache.get("src", "a.bin", "sha256", Some("\"v2\""), 10),
+            None
+        );
+        assert_eq!(
+            cache.get("src", "a.bin", "sha256", Some("\"v1\""), 11),
+            None
+        );
+        assert_eq!(cache.get("src", "a.bin", "md5", Some("\"v1\""), 10), None);
+        assert_eq!(
+            cache.get("other", "a.bin", "sha256", Some("\"v1\""), 10),
+            None
+        );
+
+        cache.invalidate("src", "a.bin");
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_or_compute_reuses_digest_until_object_changes() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("file.txt", "hello").await.unwrap();
+
+        let calls = AtomicUsize::new(0);
+        let mut cache = HashCache::in_memory();
+        for _ in 0..2 {
+            let digest = cache
+                .get_or_compute(&op, "mem", "file.txt", "len", || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok("5".to_string())
+                })
+                .await
+                .unwrap();
+            assert_eq!(digest, "5");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        op.write("file.txt", "hello world").await.unwrap();
+        let digest = cache
+            .get_or_compute(&op, "mem", "file.txt", "len", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok("11".to_string())
+            })
+            .await
+            .unwrap();
+        assert_eq!(digest, "11");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn cache_round_trips_through_disk() {
+        let dir = std::env::temp_dir().join(format!("infimount-hash-cache-{}", std::process::id()));
+        let path = dir.join("hash-cache.json");
+
+        let mut cache = HashCache::load(&path);
+        cache.insert("src", "a", "md5", None, 3, "d41d".to_string());
+        cache.save().unwrap();
+
+        let reloaded = HashCache::load(&path);
+        assert_eq!(reloaded.get("src", "a", "md5", None, 3), Some("d41d"));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,165 @@
+//! Directory listing for plain HTTP sources.
+//!
+//! OpenDAL's HTTP service can stat and read but not list. Static file servers
+//! (nginx/Apache autoindex, `python -m http.server`, artifact mirrors) publish
+//! an HTML index per directory instead, so listing fetches that page and
+//! collects its relative links.
+
+use std::time::Duration;
+
+use opendal::Operator;
+
+use crate::models::{CoreError, Entry, Result};
+use crate::util::extract_filename;
+
+const INDEX_URL_TTL: Duration = Duration::from_secs(60);
+
+/// Whether `op` is an HTTP source that needs index-page listing.
+pub fn needs_index_listing(op: &Operator) -> bool {
+    op.info().scheme() == "http" && !op.info().full_capability().list
+}
+
+/// List `dir` (normalized, empty for the root or ending with `/`) by parsing
+/// the server's HTML index page.
+pub async fn list(op: &Operator, dir: &str) -> Result<Vec<Entry>> {
+    // Presigning is how the HTTP service exposes the URL it would fetch; it is
+    // only available for anonymous endpoints.
+    if !op.info().full_capability().presign_read {
+        return Err(CoreError::Unsupported(
+            "listing authenticated HTTP sources is not supported".to_string(),
+        ));
+    }
+    let request = op.presign_read(dir, INDEX_URL_TTL).await?;
+
+    let response = reqwest::get(request.uri().to_string())
+        .await
+        .map_err(|e| CoreError::Config(format!("failed to fetch index page: {e}")))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(opendal::Error::new(
+            opendal::ErrorKind::NotFound,
+            format!("no index page at {dir}"),
+        )
+        .into());
+    }
+    if !status.is_success() {
+        return Err(CoreError::Config(format!(
+            "index page request returned {status}"
+        )));
+    }
+    let html = response
+        .text()
+        .await
+        .map_err(|e| CoreError::Config(format!("failed to fetch index page: {e}")))?;
+
+    Ok(parse_index(&html, dir))
+}
+
+/// Extract the immediate children linked from an index page. Links that leave
+/// the directory (absolute URLs, `../`, sort/query links, nested paths) are
+/// ignored.
+fn parse_index(html: &str, dir: &str) -> Vec<Entry> {
+    let mut out: Vec<Entry> = Vec::new();
+    let mut rest = html;
+
+    while let Some(pos) = find_href(rest) {
+        rest = &rest[pos..];
+        let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            continue;
+        };
+        let value_start = &rest[1..];
+        let Some(end) = value_start.find(quote) else {
+            break;
+        };
+        let href = &value_start[..end];
+        rest = &value_start[end..];
+
+        let href = href.split(['?', '#']).next().unwrap_or_default();
+        let href = href.strip_prefix("./").unwrap_or(href);
+        if href.is_empty()
+            || href.starts_with('/')
+            || href.starts_with("..")
+            || href.contains(':')
+            || href.trim_end_matches('/').contains('/')
+        {
+            continue;
+        }
+
+        let is_dir = href.ends_with('/');
+        let name = percent_decode(href.trim_end_matches('/'));
+        let path = if is_dir {
+            format!("{dir}{name}/")
+        } else {
+            format!("{dir}{name}")
+        };
+        if out.iter().any(|e| e.path == path) {
+            continue;
+        }
+
+        out.push(Entry {
+            name: extract_filename(&path),
+            path,
+            is_dir,
+            ..Default::default()
+        });
+    }
+
+    out
+}
+
+/// Offset just past the next `href=` (case-insensitive), if any.
+fn find_href(haystack: &str) -> Option<usize> {
+    let lower = haystack.to_ascii_lowercase();
+    lower.find("href=").map(|pos| pos + "href=".len())
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(decoded) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(decoded);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn autoindex_links_become_entries() {
+        let html = r#"<html><body><h1>Index of /data/</h1><pre>
+<a href="?C=N;O=D">Name</a>
+<a href="../">../</a>
+<a href="images/">images/</a>     01-Jan-2024 10:00    -
+<a href="report%202024.csv">report 2024.csv</a>  01-Jan-2024 10:00  12K
+<A HREF='notes.txt'>notes.txt</A>
+<a href="https://example.com/elsewhere">elsewhere</a>
+<a href="/absolute/path">abs</a>
+<a href="images/">images/</a>
+</pre></body></html>"#;
+
+        let entries = parse_index(html, "data/");
+        let paths: Vec<_> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["data/images/", "data/report 2024.csv", "data/notes.txt"]
+        );
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[0].name, "images");
+        assert_eq!(entries[1].name, "report 2024.csv");
+        assert!(!entries[2].is_dir);
+    }
+}
@@ -0,0 +1,178 @@
+//! Translations of strings that originate in the backend (error messages,
+//! storage schema labels, transfer status).
+//!
+//! Strings are [Fluent](https://projectfluent.org) messages embedded from
+//! `locales/<lang>/core.ftl`. Backend values carry a [`Message`] (an id plus
+//! arguments) next to their English text, so the UI can show them in the
+//! user's language. Missing translations fall back to English.
+
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use serde::Serialize;
+use unic_langid::LanguageIdentifier;
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+const RESOURCES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US/core.ftl")),
+    ("de", include_str!("../locales/de/core.ftl")),
+];
+
+/// A translatable string: a Fluent message id and its arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Message {
+    pub id: String,
+    pub args: BTreeMap<String, String>,
+}
+
+impl Message {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            args: BTreeMap::new(),
+        }
+    }
+
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.args.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Text in the current locale, falling back to English.
+    pub fn translate(&self) -> Option<String> {
+        self.translate_in(&locale())
+    }
+
+    pub fn translate_in(&self, locale: &str) -> Option<String> {
+        let requested = negotiate(locale);
+        format(&requested, self).or_else(|| format(&default_langid(), self))
+    }
+}
+
+struct Bundle {
+    langid: LanguageIdentifier,
+    bundle: FluentBundle<FluentResource>,
+}
+
+fn bundles() -> &'static [Bundle] {
+    static BUNDLES: OnceLock<Vec<Bundle>> = OnceLock::new();
+    BUNDLES.get_or_init(|| {
+        RESOURCES
+            .iter()
+            .map(|(lang, source)| {
+                let langid: LanguageIdentifier = lang.parse().expect("valid locale id");
+                let resource = FluentResource::try_new(source.to_string())
+                    .unwrap_or_else(|_| panic!("locales/{lang}/core.ftl does not parse"));
+                let mut bundle = FluentBundle::new_concurrent(vec![langid.clone()]);
+                // The UI renders plain text; bidi isolation marks would show.
+                bundle.set_use_isolating(false);
+                bundle
+                    .add_resource(resource)
+                    .unwrap_or_else(|_| panic!("duplicate message ids in locales/{lang}"));
+                Bundle { langid, bundle }
+            })
+            .collect()
+    })
+}
+
+fn default_langid() -> LanguageIdentifier {
+    DEFAULT_LOCALE.parse().expect("valid locale id")
+}
+
+fn current() -> &'static RwLock<LanguageIdentifier> {
+    static CURRENT: OnceLock<RwLock<LanguageIdentifier>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(default_langid()))
+}
+
+fn format(langid: &LanguageIdentifier, message: &Message) -> Option<String> {
+    let bundle = &bundles().iter().find(|b| &b.langid == langid)?.bundle;
+    let pattern = bundle.get_message(&message.id)?.value()?;
+    let mut args = FluentArgs::new();
+    for (name, value) in &message.args {
+        // Integers are passed as numbers so plural variants can match.
+        match value.parse::<i64>() {
+            Ok(number) => args.set(name.as_str(), number),
+            Err(_) => args.set(name.as_str(), value.as_str()),
+        }
+    }
+    let mut errors = Vec::new();
+    let text = bundle.format_pattern(pattern, Some(&args), &mut errors);
+    errors.is_empty().then(|| text.into_owned())
+}
+
+/// Locales with translations, e.g. `["en-US", "de"]`.
+pub fn available_locales() -> Vec<String> {
+    bundles().iter().map(|b| b.langid.to_string()).collect()
+}
+
+/// Best available locale for a BCP 47 tag such as `de-AT`: an exact match,
+/// else one of the same language, else English.
+pub fn negotiate(requested: &str) -> LanguageIdentifier {
+    let Ok(requested) = requested.parse::<LanguageIdentifier>() else {
+        return default_langid();
+    };
+    let bundles = bundles();
+    bundles
+        .iter()
+        .find(|b| b.langid == requested)
+        .or_else(|| {
+            bundles
+                .iter()
+                .find(|b| b.langid.language == requested.language)
+        })
+        .map(|b| b.langid.clone())
+        .unwrap_or_else(default_langid)
+}
+
+/// Switch the locale used by [`Message::translate`]. Returns the locale
+/// actually selected.
+pub fn set_locale(requested: &str) -> String {
+    let langid = negotiate(requested);
+    *current().write().unwrap_or_else(|e| e.into_inner()) = langid.clone();
+    langid.to_string()
+}
+
+pub fn locale() -> String {
+    current()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_translate_with_english_fallback() {
+        assert_eq!(negotiate("de-AT").to_string(), "de");
+        assert_eq!(negotiate("ja").to_string(), "en-US");
+        assert_eq!(negotiate("not a locale!").to_string(), "en-US");
+
+        let message = Message::new("error-read-only-source").arg("source", "prod-bucket");
+        assert_eq!(
+            message.translate_in("de-DE").as_deref(),
+            Some("prod-bucket ist schreibgeschützt")
+        );
+        assert_eq!(
+            message.translate_in("fr").as_deref(),
+            Some("prod-bucket is read-only")
+        );
+        assert_eq!(Message::new("no-such-message").translate_in("de"), None);
+
+        // Every other locale translates every English message.
+        let english_ids = RESOURCES[0]
+            .1
+            .lines()
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(id, _)| id);
+        for id in english_ids {
+            for entry in bundles() {
+                assert!(entry.bundle.has_message(id), "{} lacks {id}", entry.langid);
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! OS metadata files that should not travel with recursive operations.
+//!
+//! Finder, Explorer and friends drop `.DS_Store`, `Thumbs.db`, `desktop.ini`
+//! and AppleDouble `._*` files next to user data. Recursive uploads and folder
+//! transfers skip them unless their
+//! [`TransferFilter`](crate::transfer_report::TransferFilter) says otherwise
+//! (the `include_system_files` preference). The `.infimount-trash` folder of
+//! [`crate::trash`] is treated the same way.
+
+const JUNK_NAMES: &[&str] = &[
+    ".DS_Store",
+    ".localized",
+    ".Spotlight-V100",
+    ".Trashes",
+    ".fseventsd",
+    "Thumbs.db",
+    "ehthumbs.db",
+    "desktop.ini",
+    crate::trash::TRASH_DIR,
+];
+
+/// Whether `name` (a single path component) is a known system/junk file.
+pub fn is_junk(name: &str) -> bool {
+    JUNK_NAMES
+        .iter()
+        .any(|junk| name.eq_ignore_ascii_case(junk))
+        || (name.starts_with("._") && name.len() > 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_system_files() {
+        for name in [
+            ".DS_Store",
+            "Thumbs.db",
+            "thumbs.db",
+            "desktop.ini",
+            "._photo.jpg",
+        ] {
+            assert!(is_junk(name), "{name}");
+        }
+        for name in [
+            "photo.jpg",
+            "._",
+            ".gitignore",
+            "DS_Store",
+            "desktop.ini.bak",
+        ] {
+            assert!(!is_junk(name), "{name}");
+        }
+    }
+}
@@ -1,9 +1,58 @@
+pub mod access;
+pub mod audit;
+pub mod branding;
+pub mod cancel;
 pub mod config;
+pub mod content_type;
+pub mod demo;
+pub mod documents;
+pub mod file_attrs;
+pub mod gcs_api;
+pub mod git;
+pub mod glob;
+pub mod grep;
+pub mod hash_cache;
+pub mod hex;
+pub mod http_index;
+pub mod i18n;
+pub mod junk;
+pub mod lifecycle;
+pub mod mail;
+pub mod media;
 pub mod models;
+pub mod multipart;
+pub mod oauth;
+pub mod ocr;
 pub mod operations;
+pub mod organize;
+pub mod pause;
+pub(crate) mod per_operator;
+pub mod placeholder;
+pub mod prefetch;
+pub mod preview_cache;
+pub mod progress;
+pub mod read_only;
 pub mod registry;
+pub mod resume;
+pub mod s3_api;
 pub mod schema;
+pub mod secrets;
+pub mod share;
+pub mod similar_images;
+pub mod structured;
+pub mod symlinks;
+pub mod synthetic;
+pub mod tail;
+pub mod tabular;
+pub mod thresholds;
+pub mod throttle;
+pub mod transfer_report;
+pub mod trash;
+pub mod usage;
+pub mod tuning;
 pub mod util;
+pub mod volumes;
+pub mod workers;
 
 pub use crate::models::{CoreError, Entry, Result, Source, SourceKind};
 pub use crate::registry::OperatorRegistry;
@@ -0,0 +1,413 @@
+//! Lifecycle (expiration and transition) rules of S3-compatible buckets.
+//!
+//! Lifecycle rules are why objects can vanish or move to a colder storage
+//! class without anyone deleting them. [`get_rules`] reads a bucket's rules
+//! in a flat form the UI can show and edit; [`put_rules`] replaces them.
+//! Every filter and action S3 defines for rules is kept, so reading and
+//! writing back a configuration does not lose anything.
+
+use std::collections::BTreeMap;
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use md5::{Digest, Md5};
+use quick_xml::escape::escape;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoreError, ErrorCode, Result};
+use crate::s3_api::{self, S3Client};
+
+/// S3's limit on rules per bucket.
+const MAX_RULES: usize = 1000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transition {
+    pub days: Option<u32>,
+    /// `YYYY-MM-DD`; not used by noncurrent-version transitions.
+    pub date: Option<String>,
+    pub storage_class: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleRule {
+    pub id: String,
+    pub enabled: bool,
+    /// Key prefix the rule applies to; empty for the whole bucket.
+    pub prefix: String,
+    /// Object tags that must all match.
+    pub tags: BTreeMap<String, String>,
+    /// Only objects bigger than this many bytes.
+    pub min_size: Option<u64>,
+    /// Only objects smaller than this many bytes.
+    pub max_size: Option<u64>,
+    /// Delete current versions this many days after creation...
+    pub expiration_days: Option<u32>,
+    /// ...or on this date (`YYYY-MM-DD`).
+    pub expiration_date: Option<String>,
+    /// Remove delete markers that have no versions left behind them.
+    pub expired_delete_marker: bool,
+    pub transitions: Vec<Transition>,
+    /// Delete versions this many days after they stopped being current.
+    pub noncurrent_expiration_days: Option<u32>,
+    /// Keep this many noncurrent versions regardless of their age.
+    pub noncurrent_versions_kept: Option<u32>,
+    pub noncurrent_transitions: Vec<Transition>,
+    /// Abort multipart uploads this many days after they were started.
+    pub abort_incomplete_upload_days: Option<u32>,
+}
+
+impl LifecycleRule {
+    fn has_action(&self) -> bool {
+        self.expiration_days.is_some()
+            || self.expiration_date.is_some()
+            || self.expired_delete_marker
+            || !self.transitions.is_empty()
+            || self.noncurrent_expiration_days.is_some()
+            || !self.noncurrent_transitions.is_empty()
+            || self.abort_incomplete_upload_days.is_some()
+    }
+
+    fn filter_xml(&self) -> String {
+        let mut conditions = Vec::new();
+        if !self.prefix.is_empty() {
+            conditions.push(element("Prefix", &self.prefix));
+        }
+        for (key, value) in &self.tags {
+            conditions.push(format!(
+                "<Tag>{}{}</Tag>",
+                element("Key", key),
+                element("Value", value)
+            ));
+        }
+        if let Some(size) = self.min_size {
+            conditions.push(element("ObjectSizeGreaterThan", &size.to_string()));
+        }
+        if let Some(size) = self.max_size {
+            conditions.push(element("ObjectSizeLessThan", &size.to_string()));
+        }
+        if conditions.len() > 1 {
+            format!("<Filter><And>{}</And></Filter>", conditions.concat())
+        } else {
+            format!("<Filter>{}</Filter>", conditions.concat())
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("<Rule>");
+        if !self.id.is_empty() {
+            xml += &element("ID", &self.id);
+        }
+        xml += &self.filter_xml();
+        xml += element("Status", if self.enabled { "Enabled" } else { "Disabled" }).as_str();
+        for transition in &self.transitions {
+            xml += "<Transition>";
+            if let Some(date) = &transition.date {
+                xml += &element("Date", &format!("{date}T00:00:00Z"));
+            } else if let Some(days) = transition.days {
+                xml += &element("Days", &days.to_string());
+            }
+            xml += &element("StorageClass", &transition.storage_class);
+            xml += "</Transition>";
+        }
+        if self.expiration_days.is_some()
+            || self.expiration_date.is_some()
+            || self.expired_delete_marker
+        {
+            xml += "<Expiration>";
+            if let Some(date) = &self.expiration_date {
+                xml += &element("Date", &format!("{date}T00:00:00Z"));
+            } else if let Some(days) = self.expiration_days {
+                xml += &element("Days", &days.to_string());
+            } else {
+                xml += &element("ExpiredObjectDeleteMarker", "true");
+            }
+            xml += "</Expiration>";
+        }
+        for transition in &self.noncurrent_transitions {
+            xml += "<NoncurrentVersionTransition>";
+            xml += &element("NoncurrentDays", &transition.days.unwrap_or(0).to_string());
+            xml += &element("StorageClass", &transition.storage_class);
+            xml += "</NoncurrentVersionTransition>";
+        }
+        if let Some(days) = self.noncurrent_expiration_days {
+            xml += "<NoncurrentVersionExpiration>";
+            xml += &element("NoncurrentDays", &days.to_string());
+            if let Some(kept) = self.noncurrent_versions_kept {
+                xml += &element("NewerNoncurrentVersions", &kept.to_string());
+            }
+            xml += "</NoncurrentVersionExpiration>";
+        }
+        if let Some(days) = self.abort_incomplete_upload_days {
+            xml += "<AbortIncompleteMultipartUpload>";
+            xml += &element("DaysAfterInitiation", &days.to_string());
+            xml += "</AbortIncompleteMultipartUpload>";
+        }
+        xml + "</Rule>"
+    }
+}
+
+fn element(name: &str, text: &str) -> String {
+    format!("<{name}>{}</{name}>", escape(text))
+}
+
+fn to_xml(rules: &[LifecycleRule]) -> String {
+    let rules: String = rules.iter().map(LifecycleRule::to_xml).collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <LifecycleConfiguration xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">{rules}</LifecycleConfiguration>"
+    )
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct LifecycleConfiguration {
+    rule: Vec<RuleElement>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct RuleElement {
+    #[serde(rename = "ID")]
+    id: String,
+    status: String,
+    /// Rules written before filters existed put the prefix here.
+    prefix: Option<String>,
+    filter: Option<FilterElement>,
+    transition: Vec<TransitionElement>,
+    expiration: Option<ExpirationElement>,
+    noncurrent_version_transition: Vec<TransitionElement>,
+    noncurrent_version_expiration: Option<TransitionElement>,
+    abort_incomplete_multipart_upload: Option<AbortElement>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct FilterElement {
+    prefix: Option<String>,
+    tag: Vec<TagElement>,
+    object_size_greater_than: Option<u64>,
+    object_size_less_than: Option<u64>,
+    and: Option<Box<FilterElement>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct TagElement {
+    key: String,
+    value: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct TransitionElement {
+    days: Option<u32>,
+    noncurrent_days: Option<u32>,
+    newer_noncurrent_versions: Option<u32>,
+    date: Option<String>,
+    storage_class: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct ExpirationElement {
+    days: Option<u32>,
+    date: Option<String>,
+    expired_object_delete_marker: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct AbortElement {
+    days_after_initiation: Option<u32>,
+}
+
+/// `YYYY-MM-DD` of an S3 timestamp such as `2025-01-01T00:00:00.000Z`.
+fn day(date: String) -> String {
+    date.get(..10).map(str::to_string).unwrap_or(date)
+}
+
+impl From<RuleElement> for LifecycleRule {
+    fn from(element: RuleElement) -> Self {
+        let mut rule = LifecycleRule {
+            id: element.id,
+            enabled: element.status == "Enabled",
+            prefix: element.prefix.unwrap_or_default(),
+            ..Default::default()
+        };
+        if let Some(mut filter) = element.filter {
+            if let Some(and) = filter.and.take() {
+                filter = *and;
+            }
+            if let Some(prefix) = filter.prefix {
+                rule.prefix = prefix;
+            }
+            rule.tags = filter
+                .tag
+                .into_iter()
+                .map(|tag| (tag.key, tag.value))
+                .collect();
+            rule.min_size = filter.object_size_greater_than;
+            rule.max_size = filter.object_size_less_than;
+        }
+        rule.transitions = element
+            .transition
+            .into_iter()
+            .map(|transition| Transition {
+                days: transition.days,
+                date: transition.date.map(day),
+                storage_class: transition.storage_class,
+            })
+            .collect();
+        if let Some(expiration) = element.expiration {
+            rule.expiration_days = expiration.days;
+            rule.expiration_date = expiration.date.map(day);
+            rule.expired_delete_marker = expiration.expired_object_delete_marker == Some(true);
+        }
+        rule.noncurrent_transitions = element
+            .noncurrent_version_transition
+            .into_iter()
+            .map(|transition| Transition {
+                days: transition.noncurrent_days,
+                date: None,
+                storage_class: transition.storage_class,
+            })
+            .collect();
+        if let Some(expiration) = element.noncurrent_version_expiration {
+            rule.noncurrent_expiration_days = expiration.noncurrent_days;
+            rule.noncurrent_versions_kept = expiration.newer_noncurrent_versions;
+        }
+        rule.abort_incomplete_upload_days = element
+            .abort_incomplete_multipart_upload
+            .and_then(|abort| abort.days_after_initiation);
+        rule
+    }
+}
+
+fn parse(body: &str) -> Result<Vec<LifecycleRule>> {
+    let config: LifecycleConfiguration = s3_api::parse_xml(body)?;
+    Ok(config.rule.into_iter().map(LifecycleRule::from).collect())
+}
+
+/// Refuse rule sets S3 would reject, with a message naming the rule.
+fn validate(rules: &[LifecycleRule]) -> Result<()> {
+    if rules.len() > MAX_RULES {
+        return Err(CoreError::Config(format!(
+            "a bucket can have at most {MAX_RULES} lifecycle rules"
+        )));
+    }
+    let mut ids = std::collections::HashSet::new();
+    for (index, rule) in rules.iter().enumerate() {
+        let name = if rule.id.is_empty() {
+            format!("rule {}", index + 1)
+        } else {
+            format!("rule \"{}\"", rule.id)
+        };
+        if rule.id.len() > 255 {
+            return Err(CoreError::Config(format!(
+                "{name} has an ID over 255 characters"
+            )));
+        }
+        if !rule.id.is_empty() && !ids.insert(rule.id.as_str()) {
+            return Err(CoreError::Config(format!("{name} is defined twice")));
+        }
+        if !rule.has_action() {
+            return Err(CoreError::Config(format!("{name} has no action")));
+        }
+        if rule.expiration_days == Some(0) {
+            return Err(CoreError::Config(format!(
+                "{name} must expire objects at least one day after creation"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The bucket's lifecycle rules; none when it has no configuration.
+pub async fn get_rules(client: &S3Client) -> Result<Vec<LifecycleRule>> {
+    match client
+        .request(Method::GET, "", &[("lifecycle", "")], &[], Vec::new())
+        .await
+    {
+        Ok(body) => parse(&body),
+        Err(error) if error.code() == ErrorCode::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Replace the bucket's lifecycle rules; an empty list removes the
+/// configuration.
+pub async fn put_rules(client: &S3Client, rules: &[LifecycleRule]) -> Result<()> {
+    if rules.is_empty() {
+        client
+            .request(Method::DELETE, "", &[("lifecycle", "")], &[], Vec::new())
+            .await?;
+        return Ok(());
+    }
+    validate(rules)?;
+    let body = to_xml(rules).into_bytes();
+    // S3 requires an MD5 of lifecycle configurations.
+    let md5 = BASE64_STANDARD.encode(Md5::digest(&body));
+    client
+        .request(
+            Method::PUT,
+            "",
+            &[("lifecycle", "")],
+            &[("content-md5", &md5), ("content-type", "application/xml")],
+            body,
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_survive_a_round_trip_through_xml() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<LifecycleConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Rule>
+    <ID>archive logs</ID>
+    <Filter><And><Prefix>logs/</Prefix><Tag><Key>keep</Key><Value>no</Value></Tag><ObjectSizeGreaterThan>1024</ObjectSizeGreaterThan></And></Filter>
+    <Status>Enabled</Status>
+    <Transition><Days>30</Days><StorageClass>STANDARD_IA</StorageClass></Transition>
+    <Transition><Days>90</Days><StorageClass>GLACIER</StorageClass></Transition>
+    <Expiration><Days>365</Days></Expiration>
+    <NoncurrentVersionExpiration><NoncurrentDays>30</NoncurrentDays><NewerNoncurrentVersions>2</NewerNoncurrentVersions></NoncurrentVersionExpiration>
+  </Rule>
+  <Rule>
+    <ID>legacy</ID>
+    <Prefix>tmp/</Prefix>
+    <Status>Disabled</Status>
+    <Expiration><Date>2030-01-01T00:00:00.000Z</Date></Expiration>
+    <AbortIncompleteMultipartUpload><DaysAfterInitiation>7</DaysAfterInitiation></AbortIncompleteMultipartUpload>
+  </Rule>
+</LifecycleConfiguration>"#;
+        let rules = parse(body).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].prefix, "logs/");
+        assert_eq!(rules[0].tags["keep"], "no");
+        assert_eq!(rules[0].min_size, Some(1024));
+        assert_eq!(rules[0].transitions[1].storage_class, "GLACIER");
+        assert_eq!(rules[0].expiration_days, Some(365));
+        assert_eq!(rules[0].noncurrent_versions_kept, Some(2));
+        assert!(!rules[1].enabled);
+        assert_eq!(rules[1].prefix, "tmp/");
+        assert_eq!(rules[1].expiration_date.as_deref(), Some("2030-01-01"));
+        assert_eq!(rules[1].abort_incomplete_upload_days, Some(7));
+
+        assert_eq!(parse(&to_xml(&rules)).unwrap(), rules);
+        assert!(validate(&rules).is_ok());
+
+        let no_action = LifecycleRule {
+            id: "empty".to_string(),
+            ..Default::default()
+        };
+        assert!(validate(&[no_action]).is_err());
+        assert!(validate(&[rules[0].clone(), rules[0].clone()]).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Internet messages (RFC 5322) with MIME bodies, parsed by `mail-parser`.
+//!
+//! The first plain-text and the first HTML part that are not attachments
+//! become the bodies, every attachment (including attached messages) is
+//! listed. Parsing is lenient, as archived mail often bends the rules; it
+//! never fails, a message without headers is just a text body.
+
+use mail_parser::{HeaderValue, Message, MessageParser, MessagePart, MimeHeaders, PartType};
+
+use super::{Attachment, EmailHeader, ParsedMail};
+
+/// MIME headers are parsed for their structure, every other header as text
+/// with encoded words (`=?utf-8?B?...?=`) decoded.
+fn parser() -> MessageParser {
+    MessageParser::new()
+        .with_mime_headers()
+        .default_header_text()
+}
+
+pub(super) fn parse(data: &[u8]) -> ParsedMail {
+    let Some(message) = parser().parse(data) else {
+        return ParsedMail {
+            text: Some(String::from_utf8_lossy(data).trim_end().to_string()),
+            ..Default::default()
+        };
+    };
+    let mut mail = ParsedMail {
+        headers: headers(&message),
+        ..Default::default()
+    };
+    mail.subject = mail.header("Subject");
+    mail.from = mail.header("From");
+    mail.to = mail.header("To");
+    mail.cc = mail.header("Cc");
+    mail.date = mail.header("Date");
+
+    let parts = |ids: &[u32]| -> Vec<&MessagePart> {
+        ids.iter()
+            .filter_map(|&id| message.part(id))
+            .filter(|part| !is_attachment(part))
+            .collect()
+    };
+    mail.text = parts(&message.text_body)
+        .into_iter()
+        .find_map(|part| match &part.body {
+            PartType::Text(text) => Some(text.trim_end().to_string()),
+            _ => None,
+        });
+    mail.html = parts(&message.html_body)
+        .into_iter()
+        .find_map(|part| match &part.body {
+            PartType::Html(html) => Some(html.trim_end().to_string()),
+            _ => None,
+        });
+    mail.attachments = message.attachments().map(|part| attachment(part)).collect();
+    mail
+}
+
+/// Decoded headers of the message `data` (transport headers of an Outlook
+/// message, say), in order.
+pub(super) fn parse_headers(data: &[u8]) -> Vec<EmailHeader> {
+    parser()
+        .parse_headers(data)
+        .map(|message| headers(&message))
+        .unwrap_or_default()
+}
+
+fn headers(message: &Message) -> Vec<EmailHeader> {
+    let raw = message.raw_message();
+    message
+        .headers()
+        .iter()
+        .map(|header| {
+            let value = match header.value() {
+                HeaderValue::Text(text) => text.trim().to_string(),
+                HeaderValue::Empty => String::new(),
+                // Structured MIME headers are shown as written, unfolded.
+                _ => {
+                    let (start, end) = (header.offset_start(), header.offset_end());
+                    let value = raw.get(start as usize..end as usize).unwrap_or_default();
+                    String::from_utf8_lossy(value)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }
+            };
+            EmailHeader {
+                name: header.name().to_string(),
+                value,
+            }
+        })
+        .collect()
+}
+
+fn is_attachment(part: &MessagePart) -> bool {
+    part.content_disposition()
+        .is_some_and(|disposition| disposition.is_attachment())
+        || part.attachment_name().is_some()
+}
+
+fn attachment(part: &MessagePart) -> Attachment {
+    let content_type = part
+        .content_type()
+        .map(|ctype| match ctype.subtype() {
+            Some(subtype) => format!("{}/{subtype}", ctype.ctype()),
+            None => ctype.ctype().to_string(),
+        })
+        .map(|ctype| ctype.to_ascii_lowercase())
+        .unwrap_or_else(|| match part.body {
+            PartType::Message(_) => "message/rfc822".to_string(),
+            _ => "application/octet-stream".to_string(),
+        });
+    let filename = part.attachment_name().map(str::to_string).or_else(|| {
+        part.message().map(|inner| {
+            let subject = inner.subject().unwrap_or("message");
+            format!("{subject}.eml")
+        })
+    });
+    let inline = part
+        .content_disposition()
+        .is_some_and(|disposition| disposition.is_inline());
+    Attachment {
+        filename,
+        content_type,
+        inline,
+        content_id: part.content_id().map(str::to_string),
+        data: part.contents().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_encoded_words_and_folded_headers() {
+        let mail = parse(
+            b"Subject: =?UTF-8?B?SMOpbGxv?=\r\n =?iso-8859-1?Q?_w=F6rld?= !\r\nX-Empty:\r\n\r\nbody",
+        );
+        assert_eq!(mail.header("subject").as_deref(), Some("Héllo wörld !"));
+        assert_eq!(mail.header("X-Empty").as_deref(), Some(""));
+        assert_eq!(mail.text.as_deref(), Some("body"));
+    }
+
+    #[test]
+    fn picks_bodies_and_attachments_from_nested_multiparts() {
+        let message = "Subject: Hi\n\
+            Content-Type: multipart/mixed; boundary=outer\n\
+            \n\
+            preamble\n\
+            --outer\n\
+            Content-Type: multipart/alternative; boundary=\"inner\"\n\
+            \n\
+            --inner\n\
+            Content-Type: text/plain; charset=iso-8859-1\n\
+            Content-Transfer-Encoding: quoted-printable\n\
+            \n\
+            Gr=FC=DFe, long =\n\
+            line\n\
+            --inner\n\
+            Content-Type: text/html\n\
+            \n\
+            <p>Hi</p>\n\
+            --inner--\n\
+            --outer\n\
+            Content-Type: image/png\n\
+            Content-Disposition: inline\n\
+            Content-ID: <logo@x>\n\
+            Content-Transfer-Encoding: base64\n\
+            \n\
+            iVBORw0K\n\
+            GgA=\n\
+            --outer\n\
+            Content-Type: application/pdf\n\
+            Content-Disposition: attachment; filename*=utf-8''r%C3%A9sum%C3%A9.pdf\n\
+            \n\
+            %PDF\n\
+            --outer--\n";
+        let mail = parse(message.as_bytes());
+        assert_eq!(mail.subject.as_deref(), Some("Hi"));
+        assert_eq!(mail.text.as_deref(), Some("Grüße, long line"));
+        assert_eq!(mail.html.as_deref(), Some("<p>Hi</p>"));
+        assert_eq!(mail.attachments.len(), 2);
+        let logo = &mail.attachments[0];
+        assert!(logo.inline);
+        assert_eq!(logo.content_id.as_deref(), Some("logo@x"));
+        assert_eq!(logo.content_type, "image/png");
+        assert_eq!(logo.data, b"\x89PNG\r\n\x1a\x00");
+        let pdf = &mail.attachments[1];
+        assert_eq!(pdf.filename.as_deref(), Some("résumé.pdf"));
+        assert!(!pdf.inline);
+        assert_eq!(pdf.data, b"%PDF");
+    }
+}
@@ -0,0 +1,277 @@
+//! Previews of saved emails, for mail archives kept in buckets.
+//!
+//! Internet messages (`.eml`, MIME) are parsed by `mime` and Outlook
+//! messages (`.msg`, properties in an OLE compound file) by `msg`; both
+//! come out as an [`EmailPreview`] with the common headers decoded, the text
+//! and HTML bodies, and the attachments listed by index. [`extract_attachments`]
+//! writes chosen attachments to a folder on any source.
+
+mod mime;
+mod msg;
+
+use opendal::Operator;
+use serde::Serialize;
+
+use crate::models::{CoreError, Result};
+use crate::operations::{self, ensure_writable, normalize_opendal_path, unique_destination_path};
+
+/// Largest message previewed.
+pub const MAX_EMAIL_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Characters kept of the text and of the HTML body.
+const MAX_BODY_CHARS: usize = 200_000;
+
+/// Headers kept of a message.
+const MAX_HEADERS: usize = 500;
+
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailFormat {
+    Eml,
+    Msg,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmailHeader {
+    pub name: String,
+    /// Unfolded, with encoded words (`=?utf-8?B?...?=`) decoded.
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmailAttachment {
+    /// What [`extract_attachments`] is given to pick it.
+    pub index: usize,
+    pub filename: Option<String>,
+    pub content_type: String,
+    pub size: u64,
+    /// Shown within the body (e.g. an image the HTML refers to by
+    /// `content_id`) rather than attached.
+    pub inline: bool,
+    pub content_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmailPreview {
+    pub format: EmailFormat,
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub cc: Option<String>,
+    pub date: Option<String>,
+    /// All headers in order; for `.msg` files the transport headers, when
+    /// the message was received rather than drafted.
+    pub headers: Vec<EmailHeader>,
+    pub text: Option<String>,
+    pub html: Option<String>,
+    /// `text` or `html` was cut to its first characters.
+    pub truncated: bool,
+    pub attachments: Vec<EmailAttachment>,
+}
+
+/// A message as parsed, attachments with their contents.
+#[derive(Debug, Default)]
+struct ParsedMail {
+    headers: Vec<EmailHeader>,
+    subject: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    cc: Option<String>,
+    date: Option<String>,
+    text: Option<String>,
+    html: Option<String>,
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Debug)]
+struct Attachment {
+    filename: Option<String>,
+    content_type: String,
+    inline: bool,
+    content_id: Option<String>,
+    data: Vec<u8>,
+}
+
+impl ParsedMail {
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.clone())
+    }
+}
+
+fn truncate(text: Option<String>, truncated: &mut bool) -> Option<String> {
+    text.map(|text| match text.char_indices().nth(MAX_BODY_CHARS) {
+        Some((end, _)) => {
+            *truncated = true;
+            text[..end].to_string()
+        }
+        None => text,
+    })
+}
+
+fn parse(data: &[u8]) -> std::result::Result<(EmailFormat, ParsedMail), String> {
+    if msg::is_compound_file(data) {
+        Ok((EmailFormat::Msg, msg::parse(data)?))
+    } else {
+        Ok((EmailFormat::Eml, mime::parse(data)))
+    }
+}
+
+async fn read_message(op: &Operator, path: &str) -> Result<(EmailFormat, ParsedMail)> {
+    let p = normalize_opendal_path(path);
+    let size = op.stat(&p).await?.content_length();
+    if size > MAX_EMAIL_BYTES {
+        return Err(CoreError::FileTooLarge(
+            path.to_string(),
+            size,
+            MAX_EMAIL_BYTES,
+        ));
+    }
+    let mut data = Vec::with_capacity(size as usize);
+    operations::read_chunks(op, &p, READ_CHUNK_SIZE, |chunk| {
+        data.extend_from_slice(chunk);
+        true
+    })
+    .await?;
+    parse(&data).map_err(|e| CoreError::Unsupported(format!("{path}: {e}")))
+}
+
+/// Headers, bodies and attachment list of the `.eml` or `.msg` file at
+/// `path`, with very long bodies cut short.
+pub async fn preview_email(op: &Operator, path: &str) -> Result<EmailPreview> {
+    let (format, mut mail) = read_message(op, path).await?;
+    mail.headers.truncate(MAX_HEADERS);
+    let mut truncated = false;
+    Ok(EmailPreview {
+        format,
+        text: truncate(mail.text, &mut truncated),
+        html: truncate(mail.html, &mut truncated),
+        truncated,
+        subject: mail.subject,
+        from: mail.from,
+        to: mail.to,
+        cc: mail.cc,
+        date: mail.date,
+        headers: mail.headers,
+        attachments: mail
+            .attachments
+            .iter()
+            .enumerate()
+            .map(|(index, attachment)| EmailAttachment {
+                index,
+                filename: attachment.filename.clone(),
+                content_type: attachment.content_type.clone(),
+                size: attachment.data.len() as u64,
+                inline: attachment.inline,
+                content_id: attachment.content_id.clone(),
+            })
+            .collect(),
+    })
+}
+
+/// A file name for an attachment that stays inside the target folder.
+fn attachment_file_name(attachment: &Attachment, index: usize) -> String {
+    let name = attachment
+        .filename
+        .as_deref()
+        .and_then(|name| name.rsplit(['/', '\\']).next())
+        .map(|name| name.trim().replace(|c: char| c.is_control(), ""))
+        .unwrap_or_default();
+    if name.is_empty() || name == "." || name == ".." {
+        format!("attachment-{}", index + 1)
+    } else {
+        name
+    }
+}
+
+/// Write the attachments of the message at `path` to `target_dir` on
+/// `target`, all of them or those at `indexes`. Names already taken get a
+/// " copy" suffix instead of being overwritten. Returns the written paths.
+pub async fn extract_attachments(
+    op: &Operator,
+    path: &str,
+    indexes: Option<&[usize]>,
+    target: &Operator,
+    target_dir: &str,
+) -> Result<Vec<String>> {
+    ensure_writable(target)?;
+    let (_, mail) = read_message(op, path).await?;
+    if let Some(missing) = indexes
+        .unwrap_or_default()
+        .iter()
+        .find(|&&index| index >= mail.attachments.len())
+    {
+        return Err(CoreError::Unsupported(format!(
+            "{path} has no attachment {missing}"
+        )));
+    }
+    let target_dir = normalize_opendal_path(target_dir);
+    let mut written = Vec::new();
+    for (index, attachment) in mail.attachments.iter().enumerate() {
+        if indexes.is_some_and(|indexes| !indexes.contains(&index)) {
+            continue;
+        }
+        let name = attachment_file_name(attachment, index);
+        let dest = unique_destination_path(target, &target_dir, &name, false).await?;
+        target.write(&dest, attachment.data.clone()).await?;
+        written.push(dest);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    const MESSAGE: &str = "From: Ann <ann@example.com>\r\n\
+        To: bob@example.com\r\n\
+        Subject: Report\r\n\
+        MIME-Version: 1.0\r\n\
+        Content-Type: multipart/mixed; boundary=\"b1\"\r\n\
+        \r\n\
+        --b1\r\n\
+        Content-Type: text/plain\r\n\
+        \r\n\
+        See attached.\r\n\
+        --b1\r\n\
+        Content-Type: text/csv; name=\"../q3.csv\"\r\n\
+        Content-Disposition: attachment; filename=\"../q3.csv\"\r\n\
+        Content-Transfer-Encoding: base64\r\n\
+        \r\n\
+        YSxiCjEsMgo=\r\n\
+        --b1--\r\n";
+
+    #[tokio::test]
+    async fn previews_and_extracts_attachments() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("mail/report.eml", MESSAGE).await.unwrap();
+
+        let preview = preview_email(&op, "/mail/report.eml").await.unwrap();
+        assert_eq!(preview.format, EmailFormat::Eml);
+        assert_eq!(preview.subject.as_deref(), Some("Report"));
+        assert_eq!(preview.text.as_deref(), Some("See attached."));
+        assert_eq!(preview.attachments.len(), 1);
+        assert_eq!(preview.attachments[0].size, 8);
+
+        let written = extract_attachments(&op, "mail/report.eml", None, &op, "out")
+            .await
+            .unwrap();
+        assert_eq!(written, ["out/q3.csv"]);
+        assert_eq!(op.read("out/q3.csv").await.unwrap().to_vec(), b"a,b\n1,2\n");
+
+        let again = extract_attachments(&op, "mail/report.eml", Some(&[0][..]), &op, "out")
+            .await
+            .unwrap();
+        assert_eq!(again, ["out/q3 copy.csv"]);
+        assert!(
+            extract_attachments(&op, "mail/report.eml", Some(&[3][..]), &op, "out")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Outlook `.msg` files: MAPI properties stored as streams of an OLE
+//! compound file (MS-OXMSG), read with `cfb`.
+//!
+//! Only the streams a preview needs are used: subject, sender, display
+//! recipients, bodies, transport headers, the submit time, and per
+//! attachment its names, MIME type and data. Attached messages (stored as
+//! nested storages rather than data) are left out.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use cfb::CompoundFile;
+use chrono::DateTime;
+
+use super::mime::parse_headers;
+use super::{Attachment, ParsedMail};
+
+const MAGIC: &[u8] = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1";
+
+const PT_STRING8: u16 = 0x001E;
+const PT_UNICODE: u16 = 0x001F;
+const PT_BINARY: u16 = 0x0102;
+const PT_SYSTIME: u16 = 0x0040;
+
+const PR_SUBJECT: u16 = 0x0037;
+const PR_CLIENT_SUBMIT_TIME: u16 = 0x0039;
+const PR_TRANSPORT_MESSAGE_HEADERS: u16 = 0x007D;
+const PR_SENDER_NAME: u16 = 0x0C1A;
+const PR_SENDER_EMAIL_ADDRESS: u16 = 0x0C1F;
+const PR_DISPLAY_CC: u16 = 0x0E03;
+const PR_DISPLAY_TO: u16 = 0x0E04;
+const PR_MESSAGE_DELIVERY_TIME: u16 = 0x0E06;
+const PR_BODY: u16 = 0x1000;
+const PR_HTML: u16 = 0x1013;
+const PR_DISPLAY_NAME: u16 = 0x3001;
+const PR_ATTACH_DATA_BIN: u16 = 0x3701;
+const PR_ATTACH_FILENAME: u16 = 0x3704;
+const PR_ATTACH_LONG_FILENAME: u16 = 0x3707;
+const PR_ATTACH_MIME_TAG: u16 = 0x370E;
+const PR_ATTACH_CONTENT_ID: u16 = 0x3712;
+const PR_SENDER_SMTP_ADDRESS: u16 = 0x5D01;
+
+/// Seconds from 1601-01-01 (FILETIME's epoch) to 1970-01-01.
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+pub(super) fn is_compound_file(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn utf16(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// The property streams of one MAPI object (the message or an attachment),
+/// by uppercased name.
+struct Properties {
+    streams: HashMap<String, Vec<u8>>,
+}
+
+impl Properties {
+    /// The streams directly inside `storage`.
+    fn read(file: &mut CompoundFile<Cursor<&[u8]>>, storage: &Path) -> std::io::Result<Self> {
+        let paths: Vec<_> = file
+            .read_storage(storage)?
+            .filter(|entry| entry.is_stream())
+            .map(|entry| entry.path().to_path_buf())
+            .collect();
+        let mut streams = HashMap::new();
+        for path in paths {
+            let mut data = Vec::new();
+            file.open_stream(&path)?.read_to_end(&mut data)?;
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_ascii_uppercase())
+                .unwrap_or_default();
+            streams.insert(name, data);
+        }
+        Ok(Self { streams })
+    }
+
+    fn raw(&self, id: u16, kind: u16) -> Option<&[u8]> {
+        self.streams
+            .get(&format!("__SUBSTG1.0_{id:04X}{kind:04X}"))
+            .map(Vec::as_slice)
+    }
+
+    fn string(&self, id: u16) -> Option<String> {
+        let text = match self.raw(id, PT_UNICODE) {
+            Some(data) => utf16(data),
+            None => String::from_utf8_lossy(self.raw(id, PT_STRING8)?)
+                .trim_end_matches('\0')
+                .to_string(),
+        };
+        Some(text).filter(|text| !text.is_empty())
+    }
+
+    /// A fixed-size property from the `__properties_version1.0` stream,
+    /// whose header is `header_len` bytes.
+    fn fixed(&self, id: u16, kind: u16, header_len: usize) -> Option<u64> {
+        let data = self.streams.get("__PROPERTIES_VERSION1.0")?;
+        let tag = (u32::from(id) << 16) | u32::from(kind);
+        data.get(header_len..)?
+            .chunks_exact(16)
+            .find(|property| property[..4] == tag.to_le_bytes())
+            .map(|property| u64::from_le_bytes(property[8..].try_into().unwrap_or_default()))
+    }
+}
+
+fn filetime(value: u64) -> Option<String> {
+    let secs = (value / 10_000_000) as i64 - FILETIME_UNIX_OFFSET;
+    let nanos = (value % 10_000_000) as u32 * 100;
+    DateTime::from_timestamp(secs, nanos)
+        .map(|time| time.format("%a, %d %b %Y %H:%M:%S %z").to_string())
+}
+
+pub(super) fn parse(data: &[u8]) -> Result<ParsedMail, String> {
+    let invalid = |_| "not a valid Outlook message".to_string();
+    let mut file = CompoundFile::open(Cursor::new(data)).map_err(invalid)?;
+    let message = Properties::read(&mut file, Path::new("/")).map_err(invalid)?;
+    if !message
+        .streams
+        .keys()
+        .any(|name| name.starts_with("__SUBSTG1.0_"))
+    {
+        return Err("not an Outlook message".to_string());
+    }
+
+    let mut mail = ParsedMail::default();
+    if let Some(headers) = message.string(PR_TRANSPORT_MESSAGE_HEADERS) {
+        mail.headers = parse_headers(headers.as_bytes());
+    }
+    mail.subject = message
+        .string(PR_SUBJECT)
+        .or_else(|| mail.header("Subject"));
+    let sender_email = message
+        .string(PR_SENDER_SMTP_ADDRESS)
+        .or_else(|| message.string(PR_SENDER_EMAIL_ADDRESS))
+        .filter(|address| address.contains('@'));
+    mail.from = match (message.string(PR_SENDER_NAME), sender_email) {
+        (Some(name), Some(email)) if name != email => Some(format!("{name} <{email}>")),
+        (name, email) => name.or(email),
+    }
+    .or_else(|| mail.header("From"));
+    mail.to = message.string(PR_DISPLAY_TO).or_else(|| mail.header("To"));
+    mail.cc = message.string(PR_DISPLAY_CC).or_else(|| mail.header("Cc"));
+    mail.date = mail.header("Date").or_else(|| {
+        [PR_CLIENT_SUBMIT_TIME, PR_MESSAGE_DELIVERY_TIME]
+            .into_iter()
+            .find_map(|id| message.fixed(id, PT_SYSTIME, 32))
+            .and_then(filetime)
+    });
+    mail.text = message.string(PR_BODY);
+    mail.html = message
+        .raw(PR_HTML, PT_BINARY)
+        .map(|html| String::from_utf8_lossy(html).into_owned())
+        .or_else(|| message.string(PR_HTML));
+
+    let mut attachments: Vec<_> = file
+        .read_root_storage()
+        .filter(|entry| {
+            entry.is_storage()
+                && entry
+                    .name()
+                    .to_ascii_uppercase()
+                    .starts_with("__ATTACH_VERSION1.0_#")
+        })
+        .map(|entry| entry.path().to_path_buf())
+        .collect();
+    attachments.sort();
+    for storage in attachments {
+        let properties = Properties::read(&mut file, &storage).map_err(invalid)?;
+        let Some(data) = properties
+            .raw(PR_ATTACH_DATA_BIN, PT_BINARY)
+            .map(<[u8]>::to_vec)
+        else {
+            continue;
+        };
+        let content_id = properties.string(PR_ATTACH_CONTENT_ID);
+        mail.attachments.push(Attachment {
+            filename: properties
+                .string(PR_ATTACH_LONG_FILENAME)
+                .or_else(|| properties.string(PR_ATTACH_FILENAME))
+                .or_else(|| properties.string(PR_DISPLAY_NAME)),
+            content_type: properties
+                .string(PR_ATTACH_MIME_TAG)
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            inline: content_id.is_some(),
+            content_id,
+            data,
+        });
+    }
+    Ok(mail)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn unicode(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    /// A compound file holding `top` streams and one attachment storage.
+    fn compound_file(top: &[(&str, Vec<u8>)], attachment: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut file = CompoundFile::create(Cursor::new(Vec::new())).unwrap();
+        let storage = Path::new("/__attach_version1.0_#00000000");
+        file.create_storage(storage).unwrap();
+        let streams = top
+            .iter()
+            .map(|(name, data)| (Path::new("/").join(name), data))
+            .chain(
+                attachment
+                    .iter()
+                    .map(|(name, data)| (storage.join(name), data)),
+            );
+        for (path, data) in streams {
+            file.create_stream(&path).unwrap().write_all(data).unwrap();
+        }
+        file.flush().unwrap();
+        file.into_inner().into_inner()
+    }
+
+    #[test]
+    fn reads_message_properties_and_attachments() {
+        let mut properties = vec![0; 32];
+        properties.extend(((u32::from(PR_CLIENT_SUBMIT_TIME) << 16) | 0x0040).to_le_bytes());
+        properties.extend(0u32.to_le_bytes());
+        properties.extend(133_486_382_450_000_000u64.to_le_bytes());
+        let data = compound_file(
+            &[
+                ("__substg1.0_0037001F", unicode("Quarterly")),
+                ("__substg1.0_0C1A001F", unicode("Ann")),
+                ("__substg1.0_5D01001F", unicode("ann@example.com")),
+                ("__substg1.0_0E04001F", unicode("Bob")),
+                (
+                    "__substg1.0_1000001F",
+                    unicode(&"Numbers attached. ".repeat(40)),
+                ),
+                ("__properties_version1.0", properties),
+            ],
+            &[
+                ("__substg1.0_3707001F", unicode("q3.csv")),
+                ("__substg1.0_370E001F", unicode("text/csv")),
+                ("__substg1.0_37010102", b"a,b\n1,2\n".to_vec()),
+            ],
+        );
+        assert!(is_compound_file(&data));
+
+        let mail = parse(&data).unwrap();
+        assert_eq!(mail.subject.as_deref(), Some("Quarterly"));
+        assert_eq!(mail.from.as_deref(), Some("Ann <ann@example.com>"));
+        assert_eq!(mail.to.as_deref(), Some("Bob"));
+        assert_eq!(
+            mail.date.as_deref(),
+            Some("Tue, 02 Jan 2024 03:04:05 +0000")
+        );
+        assert_eq!(mail.text.map(|text| text.len()), Some(18 * 40));
+        assert_eq!(mail.attachments.len(), 1);
+        let attachment = &mail.attachments[0];
+        assert_eq!(attachment.filename.as_deref(), Some("q3.csv"));
+        assert_eq!(attachment.content_type, "text/csv");
+        assert_eq!(attachment.data, b"a,b\n1,2\n");
+
+        assert!(parse(&data[..600]).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Font metadata and a rendered sample line.
+
+use ab_glyph_rasterizer::{point, Point, Rasterizer};
+use opendal::Operator;
+use serde::Serialize;
+use ttf_parser::{name_id, Face, GlyphId, OutlineBuilder};
+
+use super::{png_base64, read_limited, PngColor};
+use crate::models::{CoreError, Result};
+
+/// Largest font file previewed.
+pub const MAX_FONT_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Text rendered when the caller has none.
+pub const DEFAULT_FONT_SAMPLE: &str = "The quick brown fox jumps over the lazy dog 0123456789";
+
+/// Height of the sample's em square, in pixels.
+const SAMPLE_PX: f32 = 32.0;
+/// The sample stops at the glyph that would cross this width.
+const MAX_SAMPLE_WIDTH: u32 = 1200;
+/// Blank pixels around the sample.
+const PADDING: u32 = 8;
+/// Characters of the sample rendered at most.
+const MAX_SAMPLE_CHARS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FontPreview {
+    pub family: Option<String>,
+    /// Style within the family, e.g. "Bold Italic".
+    pub subfamily: Option<String>,
+    pub full_name: Option<String>,
+    pub postscript_name: Option<String>,
+    pub version: Option<String>,
+    pub glyph_count: u16,
+    pub units_per_em: u16,
+    pub bold: bool,
+    pub italic: bool,
+    pub monospaced: bool,
+    /// Has variation axes (weight, width, ...).
+    pub variable: bool,
+    /// Fonts in the file; above 1 for collections (`.ttc`), of which the
+    /// first is described.
+    pub faces: u32,
+    /// The rendered text, shorter than asked when it did not fit.
+    pub sample_text: String,
+    /// Base64 PNG of the sample, dark on white; absent when the font has no
+    /// outlines to draw (bitmap-only and color fonts).
+    pub sample_png: Option<String>,
+    pub sample_width: u32,
+    pub sample_height: u32,
+}
+
+/// Names, glyph count and style of the font at `path`, and `sample`
+/// rendered with it as a PNG.
+pub async fn preview_font(op: &Operator, path: &str, sample: &str) -> Result<FontPreview> {
+    let data = read_limited(op, path, MAX_FONT_BYTES).await?;
+    font_preview(&data, sample).map_err(|e| CoreError::Unsupported(format!("{path}: {e}")))
+}
+
+fn font_preview(data: &[u8], sample: &str) -> std::result::Result<FontPreview, String> {
+    if data.starts_with(b"wOFF") || data.starts_with(b"wOF2") {
+        return Err("WOFF web fonts cannot be previewed".to_string());
+    }
+    let faces = ttf_parser::fonts_in_collection(data).unwrap_or(1);
+    let face =
+        Face::parse(data, 0).map_err(|e| format!("not a TrueType or OpenType font ({e})"))?;
+
+    let name = |id: u16| {
+        face.names()
+            .into_iter()
+            .filter(|name| name.name_id == id)
+            .find_map(|name| name.to_string())
+    };
+    let sample: String = sample.chars().take(MAX_SAMPLE_CHARS).collect();
+    let rendered = render_sample(&face, &sample);
+    Ok(FontPreview {
+        family: name(name_id::TYPOGRAPHIC_FAMILY).or_else(|| name(name_id::FAMILY)),
+        subfamily: name(name_id::TYPOGRAPHIC_SUBFAMILY).or_else(|| name(name_id::SUBFAMILY)),
+        full_name: name(name_id::FULL_NAME),
+        postscript_name: name(name_id::POST_SCRIPT_NAME),
+        version: name(name_id::VERSION),
+        glyph_count: face.number_of_glyphs(),
+        units_per_em: face.units_per_em(),
+        bold: face.is_bold(),
+        italic: face.is_italic(),
+        monospaced: face.is_monospaced(),
+        variable: face.is_variable(),
+        faces,
+        sample_text: rendered
+            .as_ref()
+            .map_or_else(String::new, |r| r.text.clone()),
+        sample_width: rendered.as_ref().map_or(0, |r| r.width),
+        sample_height: rendered.as_ref().map_or(0, |r| r.height),
+        sample_png: rendered.map(|r| r.png),
+    })
+}
+
+struct RenderedSample {
+    text: String,
+    png: String,
+    width: u32,
+    height: u32,
+}
+
+/// Feeds glyph outlines, in font units, to the rasterizer in pixels.
+struct GlyphPen<'a> {
+    rasterizer: &'a mut Rasterizer,
+    scale: f32,
+    origin_x: f32,
+    baseline: f32,
+    start: Point,
+    last: Point,
+}
+
+impl GlyphPen<'_> {
+    fn at(&self, x: f32, y: f32) -> Point {
+        point(
+            self.origin_x + x * self.scale,
+            self.baseline - y * self.scale,
+        )
+    }
+}
+
+impl OutlineBuilder for GlyphPen<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.at(x, y);
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.at(x, y);
+        self.rasterizer.draw_line(self.last, to);
+        self.last = to;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let to = self.at(x, y);
+        self.rasterizer.draw_quad(self.last, self.at(x1, y1), to);
+        self.last = to;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let to = self.at(x, y);
+        self.rasterizer
+            .draw_cubic(self.last, self.at(x1, y1), self.at(x2, y2), to);
+        self.last = to;
+    }
+
+    fn close(&mut self) {
+        self.rasterizer.draw_line(self.last, self.start);
+        self.last = self.start;
+    }
+}
+
+/// Lay `sample` out on one line and rasterize it. Characters the font
+/// lacks are drawn as its missing-glyph box.
+fn render_sample(face: &Face, sample: &str) -> Option<RenderedSample> {
+    let scale = SAMPLE_PX / f32::from(face.units_per_em().max(1));
+    let ascent = f32::from(face.ascender()) * scale;
+    let descent = f32::from(face.descender()) * scale;
+
+    let mut glyphs = Vec::new();
+    let mut text = String::new();
+    let mut x = PADDING as f32;
+    for c in sample.chars() {
+        let id = face.glyph_index(c).unwrap_or(GlyphId(0));
+        let advance = f32::from(face.glyph_hor_advance(id).unwrap_or(0)) * scale;
+        if x + advance > (MAX_SAMPLE_WIDTH - PADDING) as f32 {
+            break;
+        }
+        glyphs.push((id, x));
+        text.push(c);
+        x += advance;
+    }
+    let width = (x.ceil() as u32 + PADDING).max(1);
+    let height = ((ascent - descent).ceil() as u32 + 2 * PADDING).max(1);
+
+    let mut rasterizer = Rasterizer::new(width as usize, height as usize);
+    let mut drawn = false;
+    for (id, origin_x) in glyphs {
+        let mut pen = GlyphPen {
+            rasterizer: &mut rasterizer,
+            scale,
+            origin_x,
+            baseline: PADDING as f32 + ascent,
+            start: point(0.0, 0.0),
+            last: point(0.0, 0.0),
+        };
+        drawn |= face.outline_glyph(id, &mut pen).is_some();
+    }
+    if !drawn && !text.trim().is_empty() {
+        return None;
+    }
+
+    let mut pixels = vec![255u8; (width * height) as usize];
+    rasterizer.for_each_pixel_2d(|px, py, coverage| {
+        let ink = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+        pixels[(py * width + px) as usize] = 255 - ink;
+    });
+    Some(RenderedSample {
+        text,
+        png: png_base64(width, height, PngColor::Gray, &pixels),
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_files_that_are_not_fonts() {
+        let err = font_preview(b"GIF89a not a font", DEFAULT_FONT_SAMPLE).unwrap_err();
+        assert!(err.contains("not a TrueType or OpenType font"), "{err}");
+        let err = font_preview(b"wOF2\0\x01\0\0", DEFAULT_FONT_SAMPLE).unwrap_err();
+        assert!(err.contains("WOFF"), "{err}");
+    }
+}
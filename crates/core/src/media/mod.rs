@@ -0,0 +1,116 @@
+//! Previews of fonts and 3D models, beyond the images and text the webview
+//! can show on its own.
+//!
+//! Fonts (TrueType, OpenType and collections) report their names, glyph
+//! count and style, with a sample line rendered to PNG; see [`font`]. 3D
+//! models (STL, OBJ, PLY, glTF and GLB) report vertex and face counts and
+//! their bounding box, and STL and OBJ meshes can be rendered to a shaded
+//! PNG thumbnail; see [`model`]. Files are read whole, so both are capped
+//! in size.
+
+mod font;
+mod model;
+
+pub use font::{preview_font, FontPreview, DEFAULT_FONT_SAMPLE};
+pub use model::{preview_model, BoundingBox, ModelFormat, ModelPreview};
+
+use std::io::Write;
+
+use opendal::Operator;
+
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_opendal_path};
+
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The whole file at `path`, refused above `max_bytes`.
+async fn read_limited(op: &Operator, path: &str, max_bytes: u64) -> Result<Vec<u8>> {
+    let p = normalize_opendal_path(path);
+    let size = op.stat(&p).await?.content_length();
+    if size > max_bytes {
+        return Err(CoreError::FileTooLarge(path.to_string(), size, max_bytes));
+    }
+    let mut data = Vec::with_capacity(size as usize);
+    operations::read_chunks(op, &p, READ_CHUNK_SIZE, |chunk| {
+        data.extend_from_slice(chunk);
+        true
+    })
+    .await?;
+    Ok(data)
+}
+
+/// Pixel layouts [`encode_png`] writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PngColor {
+    Gray,
+    Rgb,
+}
+
+/// Encode 8-bit pixels, row by row without padding, as a PNG.
+fn encode_png(width: u32, height: u32, color: PngColor, pixels: &[u8]) -> Vec<u8> {
+    let (color_type, channels) = match color {
+        PngColor::Gray => (0u8, 1usize),
+        PngColor::Rgb => (2, 3),
+    };
+    let row_len = width as usize * channels;
+    let mut raw = Vec::with_capacity((row_len + 1) * height as usize);
+    for row in pixels.chunks(row_len).take(height as usize) {
+        // Filter type 0: the row as is.
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    // Writing to a Vec cannot fail.
+    let _ = zlib.write_all(&raw);
+    let compressed = zlib.finish().unwrap_or_default();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [
+        (b"IHDR", header.as_slice()),
+        (b"IDAT", compressed.as_slice()),
+        (b"IEND", &[][..]),
+    ] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let mut crc = flate2::Crc::new();
+        crc.update(kind);
+        crc.update(data);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+    }
+    png
+}
+
+/// [`encode_png`] as base64, the form previews hand to the webview.
+fn png_base64(width: u32, height: u32, color: PngColor, pixels: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(encode_png(width, height, color, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn encodes_a_valid_png() {
+        let png = encode_png(2, 2, PngColor::Gray, &[0, 255, 128, 64]);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 2]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut raw = Vec::new();
+        flate2::read::ZlibDecoder::new(&png[41..41 + idat_len])
+            .read_to_end(&mut raw)
+            .unwrap();
+        assert_eq!(raw, [0, 0, 255, 0, 128, 64]);
+    }
+}
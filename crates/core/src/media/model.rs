@@ -0,0 +1,635 @@
+//! 3D model statistics and an optional shaded thumbnail.
+
+use opendal::Operator;
+use serde::Serialize;
+use serde_json::Value as Json;
+
+use super::{png_base64, read_limited, PngColor};
+use crate::models::{CoreError, Result};
+
+/// Largest model file previewed.
+pub const MAX_MODEL_BYTES: u64 = 128 * 1024 * 1024;
+
+/// Width and height of thumbnails.
+const THUMBNAIL_SIZE: u32 = 256;
+/// Triangles drawn into a thumbnail at most; larger meshes are sampled.
+const MAX_THUMBNAIL_TRIANGLES: usize = 500_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelFormat {
+    Stl,
+    Obj,
+    Ply,
+    Gltf,
+    Glb,
+}
+
+impl ModelFormat {
+    /// Format of `path`, by extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let (_, ext) = path.rsplit_once('.')?;
+        match ext.to_ascii_lowercase().as_str() {
+            "stl" => Some(ModelFormat::Stl),
+            "obj" => Some(ModelFormat::Obj),
+            "ply" => Some(ModelFormat::Ply),
+            "gltf" => Some(ModelFormat::Gltf),
+            "glb" => Some(ModelFormat::Glb),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BoundingBox {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl BoundingBox {
+    fn of(points: impl IntoIterator<Item = [f64; 3]>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| {
+            (
+                std::array::from_fn(|axis| min[axis].min(p[axis])),
+                std::array::from_fn(|axis| max[axis].max(p[axis])),
+            )
+        });
+        Some(BoundingBox { min, max })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelPreview {
+    pub format: ModelFormat,
+    pub vertices: u64,
+    /// Triangles for STL, polygons for OBJ and PLY, triangles of all
+    /// primitives for glTF.
+    pub faces: u64,
+    /// Meshes in a glTF scene; objects and groups (`o`/`g`) in an OBJ file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meshes: Option<u64>,
+    /// In model units; for glTF the union of the meshes' own bounds, before
+    /// node transforms.
+    pub bounding_box: Option<BoundingBox>,
+    /// Base64 PNG of the mesh seen from above at an angle, when asked for
+    /// and the format is STL or OBJ.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_png: Option<String>,
+}
+
+type Triangle = [[f64; 3]; 3];
+
+/// Counts and bounds of the 3D model at `path`, with a rendered thumbnail
+/// when `thumbnail` is set and the format carries plain triangles.
+pub async fn preview_model(op: &Operator, path: &str, thumbnail: bool) -> Result<ModelPreview> {
+    let format = ModelFormat::from_path(path).ok_or_else(|| {
+        CoreError::Unsupported(format!("{path} is not an STL, OBJ, PLY or glTF model"))
+    })?;
+    let data = read_limited(op, path, MAX_MODEL_BYTES).await?;
+    model_preview(format, &data, thumbnail)
+        .map_err(|e| CoreError::Unsupported(format!("{path}: {e}")))
+}
+
+fn model_preview(
+    format: ModelFormat,
+    data: &[u8],
+    thumbnail: bool,
+) -> std::result::Result<ModelPreview, String> {
+    let (mut preview, triangles) = match format {
+        ModelFormat::Stl => stl(format, data)?,
+        ModelFormat::Obj => obj(format, data),
+        ModelFormat::Ply => (ply(format, data)?, Vec::new()),
+        ModelFormat::Gltf => {
+            let json = serde_json::from_slice(data).map_err(|e| format!("invalid glTF: {e}"))?;
+            (gltf(format, &json), Vec::new())
+        }
+        ModelFormat::Glb => (gltf(format, &glb_json(data)?), Vec::new()),
+    };
+    if thumbnail && !triangles.is_empty() {
+        preview.thumbnail_png = Some(render_thumbnail(&triangles));
+    }
+    Ok(preview)
+}
+
+fn empty_preview(format: ModelFormat) -> ModelPreview {
+    ModelPreview {
+        format,
+        vertices: 0,
+        faces: 0,
+        meshes: None,
+        bounding_box: None,
+        thumbnail_png: None,
+    }
+}
+
+fn stl(
+    format: ModelFormat,
+    data: &[u8],
+) -> std::result::Result<(ModelPreview, Vec<Triangle>), String> {
+    // Binary STL: 80-byte header, triangle count, 50 bytes per triangle.
+    // Some binary files start their header with "solid" too, so the size
+    // decides.
+    let binary_count = data
+        .get(80..84)
+        .map(|count| u32::from_le_bytes(count.try_into().unwrap_or_default()) as usize);
+    let triangles: Vec<Triangle> = match binary_count {
+        Some(count) if data.len() == 84 + count * 50 => data[84..]
+            .chunks_exact(50)
+            .map(|record| {
+                let f = |i: usize| {
+                    let at = 12 + i * 4;
+                    f64::from(f32::from_le_bytes(
+                        record[at..at + 4].try_into().unwrap_or_default(),
+                    ))
+                };
+                [[f(0), f(1), f(2)], [f(3), f(4), f(5)], [f(6), f(7), f(8)]]
+            })
+            .collect(),
+        _ if data.starts_with(b"solid") => {
+            let text = String::from_utf8_lossy(data);
+            let vertices: Vec<[f64; 3]> = text
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("vertex "))
+                .filter_map(parse_xyz)
+                .collect();
+            vertices
+                .chunks_exact(3)
+                .map(|v| [v[0], v[1], v[2]])
+                .collect()
+        }
+        _ => return Err("not an STL file".to_string()),
+    };
+    let preview = ModelPreview {
+        vertices: triangles.len() as u64 * 3,
+        faces: triangles.len() as u64,
+        bounding_box: BoundingBox::of(triangles.iter().flatten().copied()),
+        ..empty_preview(format)
+    };
+    Ok((preview, triangles))
+}
+
+fn parse_xyz(text: &str) -> Option<[f64; 3]> {
+    let mut values = text.split_whitespace().map(|v| v.parse::<f64>().ok());
+    Some([values.next()??, values.next()??, values.next()??])
+}
+
+fn obj(format: ModelFormat, data: &[u8]) -> (ModelPreview, Vec<Triangle>) {
+    let text = String::from_utf8_lossy(data);
+    let mut vertices: Vec<[f64; 3]> = Vec::new();
+    let mut triangles = Vec::new();
+    let mut faces = 0;
+    let mut objects = 0;
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("v ") {
+            if let Some(v) = parse_xyz(rest) {
+                vertices.push(v);
+            }
+        } else if let Some(rest) = line.strip_prefix("f ") {
+            faces += 1;
+            // `f 1 2 3`, `f 1/1/1 ...` or `f -3 -2 -1` (relative).
+            let corners: Vec<[f64; 3]> = rest
+                .split_whitespace()
+                .filter_map(|corner| corner.split('/').next()?.parse::<i64>().ok())
+                .filter_map(|index| {
+                    let index = if index < 0 {
+                        vertices.len() as i64 + index
+                    } else {
+                        index - 1
+                    };
+                    vertices.get(usize::try_from(index).ok()?).copied()
+                })
+                .collect();
+            for pair in corners.get(1..).unwrap_or_default().windows(2) {
+                triangles.push([corners[0], pair[0], pair[1]]);
+            }
+        } else if line.starts_with("o ") || line.starts_with("g ") {
+            objects += 1;
+        }
+    }
+    let preview = ModelPreview {
+        vertices: vertices.len() as u64,
+        faces,
+        meshes: (objects > 0).then_some(objects),
+        bounding_box: BoundingBox::of(vertices),
+        ..empty_preview(format)
+    };
+    (preview, triangles)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlyEncoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+/// Size of a PLY scalar type in bytes.
+fn ply_type_size(name: &str) -> Option<usize> {
+    match name {
+        "char" | "uchar" | "int8" | "uint8" => Some(1),
+        "short" | "ushort" | "int16" | "uint16" => Some(2),
+        "int" | "uint" | "float" | "int32" | "uint32" | "float32" => Some(4),
+        "double" | "float64" => Some(8),
+        _ => None,
+    }
+}
+
+fn ply_read(bytes: &[u8], name: &str, encoding: PlyEncoding) -> f64 {
+    macro_rules! read {
+        ($t:ty) => {{
+            let raw = bytes.try_into().unwrap_or_default();
+            if encoding == PlyEncoding::BigEndian {
+                f64::from(<$t>::from_be_bytes(raw))
+            } else {
+                f64::from(<$t>::from_le_bytes(raw))
+            }
+        }};
+    }
+    match name {
+        "char" | "int8" => read!(i8),
+        "uchar" | "uint8" => read!(u8),
+        "short" | "int16" => read!(i16),
+        "ushort" | "uint16" => read!(u16),
+        "int" | "int32" => read!(i32),
+        "uint" | "uint32" => read!(u32),
+        "float" | "float32" => read!(f32),
+        _ => {
+            let raw = bytes.try_into().unwrap_or_default();
+            if encoding == PlyEncoding::BigEndian {
+                f64::from_be_bytes(raw)
+            } else {
+                f64::from_le_bytes(raw)
+            }
+        }
+    }
+}
+
+/// Counts from the header; the bounding box is read from the vertices
+/// when every vertex property is a scalar.
+fn ply(format: ModelFormat, data: &[u8]) -> std::result::Result<ModelPreview, String> {
+    if !data.starts_with(b"ply") {
+        return Err("not a PLY file".to_string());
+    }
+    let header_end = data
+        .windows(b"end_header".len())
+        .position(|w| w == b"end_header")
+        .ok_or("PLY header has no end_header")?;
+    let header = String::from_utf8_lossy(&data[..header_end]);
+    let body_start = data[header_end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(data.len(), |i| header_end + i + 1);
+
+    let mut encoding = PlyEncoding::Ascii;
+    let mut vertices = 0u64;
+    let mut faces = 0u64;
+    let mut vertex_props: Vec<(String, String)> = Vec::new();
+    let mut vertex_first = false;
+    let mut in_vertex = false;
+    let mut seen_element = false;
+    for line in header.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["format", "binary_little_endian", ..] => encoding = PlyEncoding::LittleEndian,
+            ["format", "binary_big_endian", ..] => encoding = PlyEncoding::BigEndian,
+            ["element", name, count] => {
+                let count = count.parse().unwrap_or(0);
+                in_vertex = *name == "vertex";
+                if in_vertex {
+                    vertices = count;
+                    vertex_first = !seen_element;
+                } else if *name == "face" {
+                    faces = count;
+                }
+                seen_element = true;
+            }
+            ["property", kind, name] if in_vertex => {
+                vertex_props.push((kind.to_string(), name.to_string()));
+            }
+            ["property", "list", ..] if in_vertex => {
+                vertex_props.push(("list".to_string(), String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    let axis = |name: &str| vertex_props.iter().position(|(_, prop)| prop == name);
+    let scalar = vertex_props
+        .iter()
+        .all(|(kind, _)| ply_type_size(kind).is_some());
+    let mut preview = ModelPreview {
+        vertices,
+        faces,
+        ..empty_preview(format)
+    };
+    let (Some(x), Some(y), Some(z)) = (axis("x"), axis("y"), axis("z")) else {
+        return Ok(preview);
+    };
+    // Vertices after other elements would need those parsed first.
+    if !vertex_first || !scalar {
+        return Ok(preview);
+    }
+    let body = &data[body_start..];
+    let points: Vec<[f64; 3]> = match encoding {
+        PlyEncoding::Ascii => String::from_utf8_lossy(body)
+            .lines()
+            .take(vertices as usize)
+            .filter_map(|line| {
+                let values: Vec<f64> = line
+                    .split_whitespace()
+                    .filter_map(|v| v.parse().ok())
+                    .collect();
+                Some([*values.get(x)?, *values.get(y)?, *values.get(z)?])
+            })
+            .collect(),
+        _ => {
+            let mut offsets = Vec::with_capacity(vertex_props.len());
+            let mut stride = 0;
+            for (kind, _) in &vertex_props {
+                offsets.push(stride);
+                stride += ply_type_size(kind).unwrap_or(0);
+            }
+            let value = |record: &[u8], i: usize| {
+                let kind = &vertex_props[i].0;
+                let size = ply_type_size(kind).unwrap_or(0);
+                ply_read(&record[offsets[i]..offsets[i] + size], kind, encoding)
+            };
+            body.chunks_exact(stride.max(1))
+                .take(vertices as usize)
+                .map(|record| [value(record, x), value(record, y), value(record, z)])
+                .collect()
+        }
+    };
+    preview.bounding_box = BoundingBox::of(points);
+    Ok(preview)
+}
+
+/// The JSON chunk of a binary glTF file.
+fn glb_json(data: &[u8]) -> std::result::Result<Json, String> {
+    let word = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap_or_default()) as usize)
+    };
+    if !data.starts_with(b"glTF") {
+        return Err("not a GLB file".to_string());
+    }
+    let chunk_len = word(12).ok_or("truncated GLB file")?;
+    if data.get(16..20) != Some(&b"JSON"[..]) {
+        return Err("GLB file does not start with a JSON chunk".to_string());
+    }
+    let chunk = data.get(20..20 + chunk_len).ok_or("truncated GLB file")?;
+    serde_json::from_slice(chunk).map_err(|e| format!("invalid glTF: {e}"))
+}
+
+fn gltf(format: ModelFormat, json: &Json) -> ModelPreview {
+    let accessors = json["accessors"].as_array().cloned().unwrap_or_default();
+    let accessor = |index: &Json| index.as_u64().and_then(|i| accessors.get(i as usize));
+    let count = |accessor: &Json| accessor["count"].as_u64().unwrap_or(0);
+    let xyz = |value: &Json| -> Option<[f64; 3]> {
+        let v = value.as_array()?;
+        Some([
+            v.first()?.as_f64()?,
+            v.get(1)?.as_f64()?,
+            v.get(2)?.as_f64()?,
+        ])
+    };
+
+    let meshes = json["meshes"].as_array().cloned().unwrap_or_default();
+    let mut vertices = 0;
+    let mut faces = 0;
+    let mut corners = Vec::new();
+    for primitive in meshes
+        .iter()
+        .filter_map(|mesh| mesh["primitives"].as_array())
+        .flatten()
+    {
+        let Some(positions) = accessor(&primitive["attributes"]["POSITION"]) else {
+            continue;
+        };
+        vertices += count(positions);
+        corners.extend(xyz(&positions["min"]));
+        corners.extend(xyz(&positions["max"]));
+        // Mode 4 (triangles) is the default; strips and fans give one
+        // triangle per index past the second.
+        let indexed = accessor(&primitive["indices"]).map_or(count(positions), count);
+        faces += match primitive["mode"].as_u64().unwrap_or(4) {
+            4 => indexed / 3,
+            5 | 6 => indexed.saturating_sub(2),
+            _ => 0,
+        };
+    }
+    ModelPreview {
+        vertices,
+        faces,
+        meshes: Some(meshes.len() as u64),
+        bounding_box: BoundingBox::of(corners),
+        ..empty_preview(format)
+    }
+}
+
+/// Flat-shaded render of `triangles` seen from the front, above and to the
+/// right, fitted into a square PNG on a light background.
+fn render_thumbnail(triangles: &[Triangle]) -> String {
+    let (yaw, pitch) = (35f64.to_radians(), 25f64.to_radians());
+    let rotate = |[x, y, z]: [f64; 3]| {
+        let (x, z) = (x * yaw.cos() - z * yaw.sin(), x * yaw.sin() + z * yaw.cos());
+        let (y, z) = (
+            y * pitch.cos() - z * pitch.sin(),
+            y * pitch.sin() + z * pitch.cos(),
+        );
+        [x, y, z]
+    };
+    let step = triangles.len().div_ceil(MAX_THUMBNAIL_TRIANGLES).max(1);
+    let rotated: Vec<Triangle> = triangles
+        .iter()
+        .step_by(step)
+        .map(|t| [rotate(t[0]), rotate(t[1]), rotate(t[2])])
+        .collect();
+
+    let size = THUMBNAIL_SIZE as usize;
+    let mut pixels = vec![240u8; size * size * 3];
+    let Some(bounds) = BoundingBox::of(rotated.iter().flatten().copied()) else {
+        return png_base64(THUMBNAIL_SIZE, THUMBNAIL_SIZE, PngColor::Rgb, &pixels);
+    };
+    let extent = (bounds.max[0] - bounds.min[0]).max(bounds.max[1] - bounds.min[1]);
+    let scale = if extent > 0.0 {
+        (size as f64 * 0.9) / extent
+    } else {
+        1.0
+    };
+    let center = [
+        (bounds.min[0] + bounds.max[0]) / 2.0,
+        (bounds.min[1] + bounds.max[1]) / 2.0,
+    ];
+    let to_screen = |[x, y, z]: [f64; 3]| {
+        [
+            (x - center[0]) * scale + size as f64 / 2.0,
+            size as f64 / 2.0 - (y - center[1]) * scale,
+            z,
+        ]
+    };
+
+    let light = {
+        let l: [f64; 3] = [0.3, 0.5, 1.0];
+        let len: f64 = (l[0] * l[0] + l[1] * l[1] + l[2] * l[2]).sqrt();
+        [l[0] / len, l[1] / len, l[2] / len]
+    };
+    let mut depth = vec![f64::NEG_INFINITY; size * size];
+    for triangle in &rotated {
+        let [a, b, c] = *triangle;
+        let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let n = [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ];
+        let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if len == 0.0 {
+            continue;
+        }
+        // Both sides lit: meshes are not always consistently wound.
+        let lit = ((n[0] * light[0] + n[1] * light[1] + n[2] * light[2]) / len).abs();
+        let shade = 0.25 + 0.75 * lit;
+        let color = [
+            (70.0 * shade) as u8,
+            (130.0 * shade) as u8,
+            (200.0 * shade) as u8,
+        ];
+
+        let [p0, p1, p2] = [to_screen(a), to_screen(b), to_screen(c)];
+        let area = (p1[0] - p0[0]) * (p2[1] - p0[1]) - (p2[0] - p0[0]) * (p1[1] - p0[1]);
+        if area.abs() < f64::EPSILON {
+            continue;
+        }
+        let min_x = p0[0].min(p1[0]).min(p2[0]).floor().max(0.0) as usize;
+        let max_x = (p0[0].max(p1[0]).max(p2[0]).ceil() as usize).min(size - 1);
+        let min_y = p0[1].min(p1[1]).min(p2[1]).floor().max(0.0) as usize;
+        let max_y = (p0[1].max(p1[1]).max(p2[1]).ceil() as usize).min(size - 1);
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let (x, y) = (px as f64 + 0.5, py as f64 + 0.5);
+                let w0 = ((p1[0] - x) * (p2[1] - y) - (p2[0] - x) * (p1[1] - y)) / area;
+                let w1 = ((p2[0] - x) * (p0[1] - y) - (p0[0] - x) * (p2[1] - y)) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+                let z = w0 * p0[2] + w1 * p1[2] + w2 * p2[2];
+                let at = py * size + px;
+                // The camera looks down -z: larger z is nearer.
+                if z > depth[at] {
+                    depth[at] = z;
+                    pixels[at * 3..at * 3 + 3].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+    png_base64(THUMBNAIL_SIZE, THUMBNAIL_SIZE, PngColor::Rgb, &pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBE_OBJ: &str = "o cube
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 2
+v 1 0 2
+v 1 1 2
+v 0 1 2
+f 1 2 3 4
+f 5/1 6/1 7/1 8/1
+f 1 2 6 5
+f 2 3 7 6
+f 3 4 8 7
+f -8 -4 -1 -5
+";
+
+    #[test]
+    fn reads_obj_counts_bounds_and_thumbnail() {
+        let preview = model_preview(ModelFormat::Obj, CUBE_OBJ.as_bytes(), true).unwrap();
+        assert_eq!(preview.vertices, 8);
+        assert_eq!(preview.faces, 6);
+        assert_eq!(preview.meshes, Some(1));
+        assert_eq!(
+            preview.bounding_box,
+            Some(BoundingBox {
+                min: [0.0, 0.0, 0.0],
+                max: [1.0, 1.0, 2.0]
+            })
+        );
+        assert!(preview.thumbnail_png.is_some());
+    }
+
+    #[test]
+    fn reads_binary_and_ascii_stl() {
+        let mut binary = vec![0u8; 80];
+        binary.extend_from_slice(&1u32.to_le_bytes());
+        binary.extend_from_slice(&[0; 12]);
+        for value in [0f32, 0., 0., 2., 0., 0., 0., 3., -1.] {
+            binary.extend_from_slice(&value.to_le_bytes());
+        }
+        binary.extend_from_slice(&[0; 2]);
+        let preview = model_preview(ModelFormat::Stl, &binary, false).unwrap();
+        assert_eq!((preview.vertices, preview.faces), (3, 1));
+        assert_eq!(preview.bounding_box.unwrap().min, [0.0, 0.0, -1.0]);
+
+        let ascii = "solid t\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendfacet\nendsolid t\n";
+        let preview = model_preview(ModelFormat::Stl, ascii.as_bytes(), false).unwrap();
+        assert_eq!(preview.faces, 1);
+        assert_eq!(preview.bounding_box.unwrap().max, [1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn reads_ply_headers_and_vertices() {
+        let ascii = "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\nproperty float z\nelement face 0\nproperty list uchar int vertex_indices\nend_header\n1 2 3\n-1 5 0\n";
+        let preview = model_preview(ModelFormat::Ply, ascii.as_bytes(), false).unwrap();
+        assert_eq!((preview.vertices, preview.faces), (2, 0));
+        assert_eq!(
+            preview.bounding_box,
+            Some(BoundingBox {
+                min: [-1.0, 2.0, 0.0],
+                max: [1.0, 5.0, 3.0]
+            })
+        );
+
+        let mut binary = b"ply\nformat binary_little_endian 1.0\nelement vertex 1\nproperty double x\nproperty double y\nproperty double z\nproperty uchar red\nend_header\n".to_vec();
+        for value in [4f64, 5., 6.] {
+            binary.extend_from_slice(&value.to_le_bytes());
+        }
+        binary.push(255);
+        let preview = model_preview(ModelFormat::Ply, &binary, false).unwrap();
+        assert_eq!(preview.bounding_box.unwrap().max, [4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn reads_gltf_accessors() {
+        let gltf = serde_json::json!({
+            "accessors": [
+                {"count": 24, "min": [-1, -1, -1], "max": [1, 1, 1]},
+                {"count": 36}
+            ],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}]
+        });
+        let mut glb = b"glTF".to_vec();
+        let json = serde_json::to_vec(&gltf).unwrap();
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&((20 + json.len()) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+
+        let preview = model_preview(ModelFormat::Glb, &glb, true).unwrap();
+        assert_eq!((preview.vertices, preview.faces), (24, 12));
+        assert_eq!(preview.meshes, Some(1));
+        assert_eq!(preview.bounding_box.unwrap().max, [1.0, 1.0, 1.0]);
+        assert!(preview.thumbnail_png.is_none());
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
+use crate::i18n::Message;
+use crate::thresholds::SizeAction;
+use crate::util::format_size;
+
 /// Core error type used across the backend.
 #[derive(thiserror::Error, Debug)]
 pub enum CoreError {
@@ -13,6 +18,27 @@ pub enum CoreError {
     #[error("config error: {0}")]
     Config(String),
 
+    #[error("unsupported operation: {0}")]
+    Unsupported(String),
+
+    #[error("source is read-only: {0}")]
+    ReadOnlySource(String),
+
+    #[error("{0} has more than {1} entries")]
+    TooManyEntries(String, usize),
+
+    #[error("timed out: {0}")]
+    Timeout(String),
+
+    #[error("cancelled")]
+    Cancelled,
+
+    #[error("{0} of {1} bytes needs confirmation (threshold {2} bytes)")]
+    ConfirmationRequired(SizeAction, u64, u64),
+
+    #[error("{0} is too large ({1} bytes, limit {2} bytes)")]
+    FileTooLarge(String, u64, u64),
+
     #[error("storage error: {0}")]
     Storage(#[from] opendal::Error),
 
@@ -29,7 +55,15 @@ pub enum ErrorCode {
     NotFound,
     PermissionDenied,
     AlreadyExists,
+    /// A conditional write found the file changed or already there.
+    Conflict,
     ConfigError,
+    Unsupported,
+    ReadOnly,
+    TooLarge,
+    Timeout,
+    Cancelled,
+    ConfirmationRequired,
     IoError,
     Unknown,
 }
@@ -40,10 +74,19 @@ impl CoreError {
             CoreError::SourceNotFound(_) => ErrorCode::NotFound,
             CoreError::UnsupportedSourceKind(_) => ErrorCode::ConfigError,
             CoreError::Config(_) => ErrorCode::ConfigError,
+            CoreError::Unsupported(_) => ErrorCode::Unsupported,
+            CoreError::ReadOnlySource(_) => ErrorCode::ReadOnly,
+            CoreError::TooManyEntries(..) => ErrorCode::TooLarge,
+            CoreError::Timeout(_) => ErrorCode::Timeout,
+            CoreError::Cancelled => ErrorCode::Cancelled,
+            CoreError::ConfirmationRequired(..) => ErrorCode::ConfirmationRequired,
+            CoreError::FileTooLarge(..) => ErrorCode::TooLarge,
             CoreError::Storage(e) => match e.kind() {
                 opendal::ErrorKind::NotFound => ErrorCode::NotFound,
                 opendal::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
                 opendal::ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+                opendal::ErrorKind::ConditionNotMatch => ErrorCode::Conflict,
+                opendal::ErrorKind::Unsupported => ErrorCode::Unsupported,
                 _ => ErrorCode::Unknown,
             },
             CoreError::Io(e) => match e.kind() {
@@ -55,6 +98,52 @@ impl CoreError {
             CoreError::Serde(_) => ErrorCode::Unknown,
         }
     }
+
+    /// Translatable form of this error; see [`crate::i18n`].
+    pub fn message(&self) -> Message {
+        match self {
+            CoreError::SourceNotFound(id) => Message::new("error-source-not-found").arg("id", id),
+            CoreError::UnsupportedSourceKind(kind) => {
+                Message::new("error-unsupported-source-kind").arg("kind", kind)
+            }
+            CoreError::Config(detail) => Message::new("error-config").arg("detail", detail),
+            CoreError::Unsupported(detail) => {
+                Message::new("error-unsupported").arg("detail", detail)
+            }
+            CoreError::ReadOnlySource(source) => {
+                Message::new("error-read-only-source").arg("source", source)
+            }
+            CoreError::TooManyEntries(path, limit) => Message::new("error-too-many-entries")
+                .arg("path", path)
+                .arg("limit", limit),
+            CoreError::Timeout(operation) => {
+                Message::new("error-timeout").arg("operation", operation)
+            }
+            CoreError::Cancelled => Message::new("error-cancelled"),
+            CoreError::ConfirmationRequired(action, size, threshold) => {
+                Message::new("error-confirmation-required")
+                    .arg("action", action)
+                    .arg("size", format_size(*size))
+                    .arg("threshold", format_size(*threshold))
+            }
+            CoreError::FileTooLarge(path, size, limit) => Message::new("error-file-too-large")
+                .arg("path", path)
+                .arg("size", format_size(*size))
+                .arg("limit", format_size(*limit)),
+            CoreError::Storage(e) => {
+                let id = match e.kind() {
+                    opendal::ErrorKind::NotFound => "error-not-found",
+                    opendal::ErrorKind::PermissionDenied => "error-permission-denied",
+                    opendal::ErrorKind::AlreadyExists => "error-already-exists",
+                    opendal::ErrorKind::ConditionNotMatch => "error-conflict",
+                    _ => "error-storage",
+                };
+                Message::new(id).arg("detail", e)
+            }
+            CoreError::Io(e) => Message::new("error-io").arg("detail", e),
+            CoreError::Serde(e) => Message::new("error-serde").arg("detail", e),
+        }
+    }
 }
 
 impl Serialize for CoreError {
@@ -63,43 +152,106 @@ impl Serialize for CoreError {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("CoreError", 2)?;
+        let message = self.message();
+        let localized = message.translate().unwrap_or_else(|| self.to_string());
+        let mut state = serializer.serialize_struct("CoreError", 5)?;
         state.serialize_field("code", &self.code())?;
         state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("message_id", &message.id)?;
+        state.serialize_field("args", &message.args)?;
+        state.serialize_field("localized", &localized)?;
         state.end()
     }
 }
 
 pub type Result<T> = std::result::Result<T, CoreError>;
 
-/// A configured storage source (currently only local filesystem).
+/// A configured storage source.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Source {
     pub id: String,
     pub name: String,
     pub kind: SourceKind,
-    /// Root path for this source (for local filesystem).
+    /// Directory for local sources. Other kinds accept a legacy shorthand
+    /// here (e.g. `bucket@region` for S3); see [`Source::settings`].
     pub root: String,
-    /// Configuration for the source (credentials, endpoint, etc.).
-    pub config: Option<std::collections::HashMap<String, String>>,
+    /// Backend settings keyed by the `StorageFieldSchema` field names of the
+    /// source kind (credentials, endpoint, etc.).
+    pub config: Option<HashMap<String, String>>,
+    /// Reject writes, deletes and moves even when the credentials allow
+    /// them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+}
+
+impl Source {
+    /// Backend settings keyed by schema field name. Values encoded in the
+    /// legacy `root` string fill in keys that `config` leaves unset.
+    pub fn settings(&self) -> HashMap<String, String> {
+        let mut settings = self.config.clone().unwrap_or_default();
+        let root = self.root.trim();
+        if root.is_empty() {
+            return settings;
+        }
+
+        let from_root: Vec<(&str, &str)> = match self.kind {
+            SourceKind::Local | SourceKind::Gdrive => vec![("rootPath", root)],
+            SourceKind::S3 => match root.split_once('@') {
+                Some((bucket, region)) => vec![("bucketName", bucket), ("region", region)],
+                None => vec![("bucketName", root)],
+            },
+            SourceKind::AzureBlob => match root.split_once('/') {
+                Some((account, container)) => {
+                    vec![("accountName", account), ("containerName", container)]
+                }
+                None => vec![("accountName", root)],
+            },
+            SourceKind::WebDav => vec![("serverUrl", root)],
+            SourceKind::Gcs | SourceKind::B2 => vec![("bucket", root)],
+            SourceKind::Ftp => vec![("host", root)],
+            SourceKind::Hdfs => vec![("nameNodeUrl", root)],
+            SourceKind::Ipfs => vec![("gatewayUrl", root)],
+            SourceKind::Ipmfs => vec![("apiUrl", root)],
+            SourceKind::Http => vec![("endpoint", root)],
+        };
+        for (key, value) in from_root {
+            if !value.is_empty() {
+                settings
+                    .entry(key.to_string())
+                    .or_insert_with(|| value.to_string());
+            }
+        }
+        settings
+    }
 }
 
 /// Types of storage that can back a Source.
-///
-/// Only `Local` is implemented initially; other variants are
-/// placeholders for future backends like S3, WebDAV, Azure Blob, etc.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SourceKind {
-    #[serde(rename = "local")]
+    #[serde(rename = "local", alias = "fs")]
     Local,
     #[serde(rename = "s3")]
     S3,
     #[serde(rename = "webdav")]
     WebDav,
-    #[serde(rename = "azure_blob")]
+    #[serde(rename = "azure_blob", alias = "azblob")]
     AzureBlob,
     #[serde(rename = "gcs")]
     Gcs,
+    #[serde(rename = "ftp")]
+    Ftp,
+    #[serde(rename = "gdrive")]
+    Gdrive,
+    #[serde(rename = "b2")]
+    B2,
+    #[serde(rename = "hdfs")]
+    Hdfs,
+    #[serde(rename = "ipfs")]
+    Ipfs,
+    #[serde(rename = "ipmfs")]
+    Ipmfs,
+    #[serde(rename = "http")]
+    Http,
 }
 
 impl fmt::Display for SourceKind {
@@ -110,18 +262,124 @@ impl fmt::Display for SourceKind {
             SourceKind::WebDav => write!(f, "webdav"),
             SourceKind::AzureBlob => write!(f, "azure_blob"),
             SourceKind::Gcs => write!(f, "gcs"),
+            SourceKind::Ftp => write!(f, "ftp"),
+            SourceKind::Gdrive => write!(f, "gdrive"),
+            SourceKind::B2 => write!(f, "b2"),
+            SourceKind::Hdfs => write!(f, "hdfs"),
+            SourceKind::Ipfs => write!(f, "ipfs"),
+            SourceKind::Ipmfs => write!(f, "ipmfs"),
+            SourceKind::Http => write!(f, "http"),
         }
     }
 }
 
 /// A single entry returned from listing or stat operations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Entry {
     pub path: String,
     pub name: String,
     pub is_dir: bool,
     pub size: u64,
     pub modified_at: Option<String>,
+    /// Placeholder in an OS-synced folder (iCloud, OneDrive) whose content is
+    /// not on disk; reading it makes the sync client download it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub online_only: bool,
+    /// MIME type the backend stores for the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// Object storage class (e.g. S3 `GLACIER`). Reserved: opendal does not
+    /// report it yet, so it is currently never set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+    /// Custom metadata stored with the object (`x-amz-meta-*` and the like).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<HashMap<String, String>>,
+    /// Status in the git working tree a Local source entry lives in, once
+    /// [`crate::operations::annotate_git_status`] filled it in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_status: Option<crate::git::GitStatus>,
+    /// A symbolic link on a Local source. The other fields describe what it
+    /// points to, and are empty when that does not exist.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_symlink: bool,
+    /// Where the link points, as stored in it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_target: Option<String>,
+}
+
+/// What [`ListQuery`] sorts by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Name,
+    Size,
+    Modified,
+}
+
+/// Filters and ordering applied to a listing before it is returned, so the
+/// UI does not have to receive a huge folder to narrow or sort it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListQuery {
+    /// `None` keeps the order the backend listed entries in.
+    pub sort: Option<SortKey>,
+    pub descending: bool,
+    /// Only files with this extension (`"pdf"` or `".pdf"`, any case).
+    pub extension: Option<String>,
+    /// Only entries whose name contains this, ignoring case.
+    pub name_contains: Option<String>,
+    pub dirs_only: bool,
+}
+
+impl ListQuery {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if self.dirs_only && !entry.is_dir {
+            return false;
+        }
+        if let Some(extension) = &self.extension {
+            let wanted = extension.trim_start_matches('.');
+            let has = entry
+                .name
+                .rsplit_once('.')
+                .is_some_and(|(_, ext)| ext.eq_ignore_ascii_case(wanted));
+            if entry.is_dir || !has {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.name_contains {
+            if !entry.name.to_lowercase().contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Drop the entries that do not match and sort the rest.
+    pub fn apply(&self, mut entries: Vec<Entry>) -> Vec<Entry> {
+        entries.retain(|entry| self.matches(entry));
+        if let Some(key) = self.sort {
+            let by_name = |a: &Entry, b: &Entry| a.name.to_lowercase().cmp(&b.name.to_lowercase());
+            entries.sort_by(|a, b| {
+                let order = match key {
+                    SortKey::Name => by_name(a, b),
+                    SortKey::Size => a.size.cmp(&b.size).then_with(|| by_name(a, b)),
+                    SortKey::Modified => a
+                        .modified_at
+                        .cmp(&b.modified_at)
+                        .then_with(|| by_name(a, b)),
+                };
+                if self.descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+        }
+        entries
+    }
 }
 
 /// Request to list entries under a path.
@@ -159,14 +417,83 @@ mod tests {
             json,
             json!({
                 "code": "NOT_FOUND",
-                "message": "source not found: foo"
+                "message": "source not found: foo",
+                "message_id": "error-source-not-found",
+                "args": { "id": "foo" },
+                "localized": "Source not found: foo"
             })
         );
     }
 
+    #[test]
+    fn list_query_filters_and_sorts() {
+        let entry = |name: &str, is_dir: bool, size: u64| Entry {
+            path: name.to_string(),
+            name: name.to_string(),
+            is_dir,
+            size,
+            ..Default::default()
+        };
+        let entries = vec![
+            entry("b.PDF", false, 30),
+            entry("docs", true, 0),
+            entry("a.pdf", false, 10),
+            entry("notes.txt", false, 20),
+        ];
+        let names = |query: ListQuery| -> Vec<String> {
+            query
+                .apply(entries.clone())
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect()
+        };
+
+        let pdfs = ListQuery {
+            sort: Some(SortKey::Name),
+            extension: Some(".pdf".into()),
+            ..Default::default()
+        };
+        assert_eq!(names(pdfs), ["a.pdf", "b.PDF"]);
+        let largest = ListQuery {
+            sort: Some(SortKey::Size),
+            descending: true,
+            ..Default::default()
+        };
+        assert_eq!(names(largest), ["b.PDF", "notes.txt", "a.pdf", "docs"]);
+        let dirs = ListQuery {
+            dirs_only: true,
+            ..Default::default()
+        };
+        assert_eq!(names(dirs), ["docs"]);
+        let named = ListQuery {
+            name_contains: Some("NOTE".into()),
+            ..Default::default()
+        };
+        assert_eq!(names(named), ["notes.txt"]);
+    }
+
     #[test]
     fn test_error_code_mapping() {
         let err = CoreError::Config("bad config".to_string());
         assert_eq!(err.code(), ErrorCode::ConfigError);
     }
+
+    #[test]
+    fn settings_fill_unset_keys_from_legacy_root() {
+        let source = Source {
+            id: "s3".to_string(),
+            name: "Bucket".to_string(),
+            kind: SourceKind::S3,
+            root: "media@eu-west-1".to_string(),
+            config: Some(HashMap::from([(
+                "region".to_string(),
+                "us-east-1".to_string(),
+            )])),
+            read_only: false,
+        };
+
+        let settings = source.settings();
+        assert_eq!(settings["bucketName"], "media");
+        assert_eq!(settings["region"], "us-east-1");
+    }
 }
@@ -0,0 +1,204 @@
+//! Incomplete multipart uploads on S3-compatible sources.
+//!
+//! An upload that was started but never completed or aborted keeps its
+//! parts stored, and billed, while its object never shows up in listings.
+//! [`list_incomplete`] finds such uploads and [`abort_many`] discards them.
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{ErrorCode, Result};
+use crate::s3_api::{self, S3Client};
+
+/// Uploads aborted at the same time.
+const ABORT_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncompleteUpload {
+    pub key: String,
+    pub upload_id: String,
+    #[serde(default)]
+    pub initiated: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub storage_class: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct ListMultipartUploadsResult {
+    upload: Vec<UploadElement>,
+    is_truncated: bool,
+    next_key_marker: String,
+    next_upload_id_marker: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct UploadElement {
+    key: String,
+    upload_id: String,
+    #[serde(default)]
+    initiated: String,
+    #[serde(default)]
+    storage_class: Option<String>,
+}
+
+/// Key and upload id markers of a listing page.
+type Markers = (String, String);
+
+/// The uploads of one listing page and the markers of the next page.
+fn parse_page(body: &str) -> Result<(Vec<IncompleteUpload>, Option<Markers>)> {
+    let page: ListMultipartUploadsResult = s3_api::parse_xml(body)?;
+    let uploads = page
+        .upload
+        .into_iter()
+        .map(|upload| IncompleteUpload {
+            key: upload.key,
+            upload_id: upload.upload_id,
+            initiated: DateTime::parse_from_rfc3339(&upload.initiated)
+                .ok()
+                .map(|at| at.with_timezone(&Utc)),
+            storage_class: upload.storage_class,
+        })
+        .collect();
+    let next = (page.is_truncated && !page.next_key_marker.is_empty())
+        .then_some((page.next_key_marker, page.next_upload_id_marker));
+    Ok((uploads, next))
+}
+
+/// Every multipart upload of the bucket that was neither completed nor
+/// aborted, oldest key first as S3 lists them.
+pub async fn list_incomplete(client: &S3Client) -> Result<Vec<IncompleteUpload>> {
+    let mut uploads = Vec::new();
+    let mut markers: Option<Markers> = None;
+    loop {
+        let mut query = vec![("uploads", "")];
+        if let Some((key_marker, upload_id_marker)) = &markers {
+            query.push(("key-marker", key_marker));
+            query.push(("upload-id-marker", upload_id_marker));
+        }
+        let body = client
+            .request(Method::GET, "", &query, &[], Vec::new())
+            .await?;
+        let (page, next) = parse_page(&body)?;
+        uploads.extend(page);
+        match next {
+            Some(next) => markers = Some(next),
+            None => return Ok(uploads),
+        }
+    }
+}
+
+/// Discard an upload and its parts. Uploads that are already gone count
+/// as aborted.
+pub async fn abort(client: &S3Client, key: &str, upload_id: &str) -> Result<()> {
+    match client
+        .request(
+            Method::DELETE,
+            key,
+            &[("uploadId", upload_id)],
+            &[],
+            Vec::new(),
+        )
+        .await
+    {
+        Err(error) if error.code() == ErrorCode::NotFound => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AbortFailure {
+    pub key: String,
+    pub upload_id: String,
+    pub error: String,
+}
+
+/// Outcome of [`abort_many`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AbortReport {
+    /// Upload ids.
+    pub aborted: Vec<String>,
+    pub failed: Vec<AbortFailure>,
+}
+
+/// Abort several uploads, a few at a time. A failure does not stop the
+/// others.
+pub async fn abort_many(client: &S3Client, uploads: Vec<IncompleteUpload>) -> AbortReport {
+    let results: Vec<(IncompleteUpload, Result<()>)> = stream::iter(uploads)
+        .map(|upload| async move {
+            let result = abort(client, &upload.key, &upload.upload_id).await;
+            (upload, result)
+        })
+        .buffer_unordered(ABORT_CONCURRENCY)
+        .collect()
+        .await;
+    let mut report = AbortReport::default();
+    for (upload, result) in results {
+        match result {
+            Ok(()) => report.aborted.push(upload.upload_id),
+            Err(error) => report.failed.push(AbortFailure {
+                key: upload.key,
+                upload_id: upload.upload_id,
+                error: error.to_string(),
+            }),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing_pages_are_parsed_with_their_markers() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListMultipartUploadsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Bucket>bucket</Bucket>
+  <KeyMarker></KeyMarker>
+  <UploadIdMarker></UploadIdMarker>
+  <NextKeyMarker>my-movie.m2ts</NextKeyMarker>
+  <NextUploadIdMarker>YW55IGlkZWEgd2h5</NextUploadIdMarker>
+  <MaxUploads>2</MaxUploads>
+  <IsTruncated>true</IsTruncated>
+  <Upload>
+    <Key>my-divisor</Key>
+    <UploadId>XMgbGlrZSBlbHZpbmcncyBub3QgaGF2aW5nIG11Y2ggbHVjaw</UploadId>
+    <Initiator><ID>arn:aws:iam::111122223333:user/user1</ID></Initiator>
+    <StorageClass>REDUCED_REDUNDANCY</StorageClass>
+    <Initiated>2010-11-10T20:48:33.000Z</Initiated>
+  </Upload>
+  <Upload>
+    <Key>my-movie.m2ts</Key>
+    <UploadId>YW55IGlkZWEgd2h5</UploadId>
+    <StorageClass>STANDARD</StorageClass>
+    <Initiated>2010-11-10T20:48:33.000Z</Initiated>
+  </Upload>
+</ListMultipartUploadsResult>"#;
+        let (uploads, next) = parse_page(body).unwrap();
+        assert_eq!(uploads.len(), 2);
+        assert_eq!(uploads[0].key, "my-divisor");
+        assert_eq!(
+            uploads[0].storage_class.as_deref(),
+            Some("REDUCED_REDUNDANCY")
+        );
+        assert_eq!(
+            uploads[1].initiated.unwrap().to_rfc3339(),
+            "2010-11-10T20:48:33+00:00"
+        );
+        assert_eq!(
+            next,
+            Some(("my-movie.m2ts".to_string(), "YW55IGlkZWEgd2h5".to_string()))
+        );
+
+        let (uploads, next) = parse_page(
+            "<ListMultipartUploadsResult><IsTruncated>false</IsTruncated></ListMultipartUploadsResult>",
+        )
+        .unwrap();
+        assert!(uploads.is_empty());
+        assert!(next.is_none());
+    }
+}
@@ -0,0 +1,388 @@
+//! OAuth2 authorization-code flow for sources that authenticate with a
+//! user account instead of static keys (currently Google Drive).
+//!
+//! The flow follows RFC 8252 for native apps: a loopback listener on
+//! `127.0.0.1` receives the redirect, PKCE (S256) protects the code
+//! exchange, and the resulting refresh token is stored in the source config
+//! so OpenDAL can refresh access tokens on its own.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use crate::models::{CoreError, Result};
+
+/// Endpoints and scopes of an OAuth2 provider.
+#[derive(Debug, Clone, Copy)]
+pub struct OAuthProvider {
+    pub authorize_url: &'static str,
+    pub token_url: &'static str,
+    pub scope: &'static str,
+}
+
+/// Google Drive with full drive access (browse, upload, delete).
+pub const GOOGLE_DRIVE: OAuthProvider = OAuthProvider {
+    authorize_url: "https://accounts.google.com/o/oauth2/v2/auth",
+    token_url: "https://oauth2.googleapis.com/token",
+    scope: "https://www.googleapis.com/auth/drive",
+};
+
+/// OAuth client registration (desktop app client id/secret).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+/// Tokens returned by the provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+impl From<TokenResponse> for OAuthTokens {
+    fn from(resp: TokenResponse) -> Self {
+        Self {
+            access_token: resp.access_token,
+            refresh_token: resp.refresh_token,
+            expires_at: resp
+                .expires_in
+                .map(|secs| Utc::now() + chrono::Duration::seconds(secs)),
+        }
+    }
+}
+
+/// An authorization in progress: the user still has to visit
+/// [`AuthorizationFlow::authorization_url`] and grant consent.
+pub struct AuthorizationFlow {
+    provider: OAuthProvider,
+    client: OAuthClient,
+    listener: TcpListener,
+    redirect_uri: String,
+    state: String,
+    code_verifier: String,
+}
+
+impl AuthorizationFlow {
+    /// Bind the loopback redirect listener and prepare state/PKCE values.
+    pub async fn start(provider: OAuthProvider, client: OAuthClient) -> Result<Self> {
+        if client.client_id.trim().is_empty() {
+            return Err(CoreError::Config("OAuth client id is required".to_string()));
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let port = listener.local_addr()?.port();
+
+        Ok(Self {
+            provider,
+            client,
+            listener,
+            redirect_uri: format!("http://127.0.0.1:{port}"),
+            state: uuid::Uuid::new_v4().simple().to_string(),
+            code_verifier: format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            ),
+        })
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// URL the user must open in a browser to grant consent.
+    pub fn authorization_url(&self) -> String {
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()));
+        let params = [
+            ("client_id", self.client.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", self.provider.scope),
+            ("state", self.state.as_str()),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+            // Ask for a refresh token even if the user consented before.
+            ("access_type", "offline"),
+            ("prompt", "consent"),
+        ];
+        let query = params
+            .iter()
+            .map(|(k, v)| format!("{k}={}", percent_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        format!("{}?{}", self.provider.authorize_url, query)
+    }
+
+    /// Wait for the browser redirect, then exchange the code for tokens.
+    pub async fn complete(self, wait: Duration) -> Result<OAuthTokens> {
+        let code = tokio::time::timeout(wait, self.accept_redirect())
+            .await
+            .map_err(|_| CoreError::Config("timed out waiting for OAuth consent".to_string()))??;
+
+        request_tokens(
+            &self.provider,
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", code.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("client_id", self.client.client_id.as_str()),
+                ("client_secret", self.client.client_secret.as_str()),
+                ("code_verifier", self.code_verifier.as_str()),
+            ],
+        )
+        .await
+    }
+
+    async fn accept_redirect(&self) -> Result<String> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+            let mut request_line = String::new();
+            BufReader::new(&mut stream)
+                .read_line(&mut request_line)
+                .await?;
+
+            // Browsers also probe for /favicon.ico; only the redirect carries a query.
+            let Some(params) = parse_redirect_query(&request_line) else {
+                let _ = stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                    .await;
+                continue;
+            };
+
+            let outcome = if params.get("state") != Some(&self.state) {
+                Err(CoreError::Config("OAuth state mismatch".to_string()))
+            } else if let Some(error) = params.get("error") {
+                Err(CoreError::Config(format!("OAuth consent denied: {error}")))
+            } else {
+                params
+                    .get("code")
+                    .cloned()
+                    .ok_or_else(|| CoreError::Config("OAuth redirect missing code".to_string()))
+            };
+
+            let body = if outcome.is_ok() {
+                "Infimount is now authorized. You can close this tab."
+            } else {
+                "Infimount authorization failed. You can close this tab and try again."
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            return outcome;
+        }
+    }
+}
+
+/// Config key recording when a storage's access token expires, as RFC 3339.
+pub const EXPIRES_AT_KEY: &str = "tokenExpiresAt";
+
+/// Expiry recorded under [`EXPIRES_AT_KEY`], if it parses.
+pub fn parse_expiry(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// How long before expiry an access token is refreshed proactively.
+pub const REFRESH_MARGIN: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Whether a token expiring at `expires_at` should be refreshed now. Tokens
+/// without a known expiry are left alone.
+pub fn needs_refresh(expires_at: Option<DateTime<Utc>>) -> bool {
+    expires_at.is_some_and(|expires_at| expires_at - REFRESH_MARGIN <= Utc::now())
+}
+
+/// Exchange a refresh token for a fresh access token.
+pub async fn refresh_access_token(
+    provider: &OAuthProvider,
+    client: &OAuthClient,
+    refresh_token: &str,
+) -> Result<OAuthTokens> {
+    let mut tokens = request_tokens(
+        provider,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", client.client_id.as_str()),
+            ("client_secret", client.client_secret.as_str()),
+        ],
+    )
+    .await?;
+    // Providers usually omit the refresh token on refresh; keep the old one.
+    if tokens.refresh_token.is_none() {
+        tokens.refresh_token = Some(refresh_token.to_string());
+    }
+    Ok(tokens)
+}
+
+async fn request_tokens(provider: &OAuthProvider, form: &[(&str, &str)]) -> Result<OAuthTokens> {
+    let response = reqwest::Client::new()
+        .post(provider.token_url)
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| CoreError::Config(format!("OAuth token request failed: {e}")))?;
+
+    let status = response.status();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| CoreError::Config(format!("OAuth token request failed: {e}")))?;
+    if !status.is_success() {
+        return Err(CoreError::Config(format!(
+            "OAuth token endpoint returned {status}: {}",
+            String::from_utf8_lossy(&body)
+        )));
+    }
+
+    let parsed: TokenResponse = serde_json::from_slice(&body)?;
+    Ok(parsed.into())
+}
+
+/// Parse the query string of an HTTP request line such as
+/// `GET /?code=abc&state=xyz HTTP/1.1`.
+fn parse_redirect_query(request_line: &str) -> Option<HashMap<String, String>> {
+    let target = request_line.split_whitespace().nth(1)?;
+    let (_, query) = target.split_once('?')?;
+    Some(
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| (percent_decode(k), percent_decode(v)))
+            .collect(),
+    )
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => match u8::from_str_radix(&value[i + 1..i + 3], 16) {
+                Ok(decoded) => {
+                    out.push(decoded);
+                    i += 2;
+                }
+                Err(_) => out.push(b'%'),
+            },
+            other => out.push(other),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_refreshed_shortly_before_expiry() {
+        assert!(!needs_refresh(None));
+        assert!(!needs_refresh(Some(
+            Utc::now() + chrono::Duration::hours(1)
+        )));
+        assert!(needs_refresh(Some(
+            Utc::now() + chrono::Duration::minutes(1)
+        )));
+        assert!(needs_refresh(Some(
+            Utc::now() - chrono::Duration::minutes(1)
+        )));
+    }
+
+    #[test]
+    fn redirect_query_is_decoded() {
+        let params =
+            parse_redirect_query("GET /?state=abc&code=4%2F0Ad-x%20y HTTP/1.1\r\n").unwrap();
+        assert_eq!(params["state"], "abc");
+        assert_eq!(params["code"], "4/0Ad-x y");
+        assert!(parse_redirect_query("GET /favicon.ico HTTP/1.1").is_none());
+    }
+
+    #[tokio::test]
+    async fn authorization_url_carries_pkce_and_loopback_redirect() {
+        let flow = AuthorizationFlow::start(
+            GOOGLE_DRIVE,
+            OAuthClient {
+                client_id: "client.apps.googleusercontent.com".to_string(),
+                client_secret: "secret".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let url = flow.authorization_url();
+        assert!(url.starts_with(GOOGLE_DRIVE.authorize_url));
+        assert!(url.contains("code_challenge_method=S256"));
+        assert!(url.contains(&format!(
+            "redirect_uri={}",
+            percent_encode(flow.redirect_uri())
+        )));
+        assert!(flow.redirect_uri().starts_with("http://127.0.0.1:"));
+    }
+
+    #[tokio::test]
+    async fn redirect_with_wrong_state_is_rejected() {
+        let flow = AuthorizationFlow::start(
+            GOOGLE_DRIVE,
+            OAuthClient {
+                client_id: "id".to_string(),
+                client_secret: "secret".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let addr = flow
+            .redirect_uri()
+            .trim_start_matches("http://")
+            .to_string();
+
+        let browser = tokio::spawn(async move {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"GET /?state=forged&code=abc HTTP/1.1\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let err = flow.complete(Duration::from_secs(5)).await.unwrap_err();
+        assert!(err.to_string().contains("state mismatch"));
+        browser.await.unwrap();
+    }
+}
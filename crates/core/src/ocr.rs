@@ -0,0 +1,209 @@
+//! Text of images and scanned PDFs by optical character recognition, so
+//! photographed receipts and scans turn up in content search.
+//!
+//! Recognition runs the `tesseract` executable on images; PDF pages are
+//! first rendered to images with `pdftoppm` (Poppler). Both are slow and
+//! need the programs installed, so this sits behind the `ocr` feature, which
+//! is off by default. Without it, or without the programs, recognition
+//! fails with [`CoreError::Unsupported`].
+
+use crate::models::{CoreError, Result};
+use crate::util::extract_filename;
+
+/// Pages of a PDF recognized at most.
+pub const MAX_OCR_PAGES: u32 = 20;
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "tif", "tiff", "bmp", "webp"];
+
+/// Whether this build can recognize text at all.
+pub fn is_available() -> bool {
+    cfg!(feature = "ocr")
+}
+
+/// Whether `path` names an image recognition can read, going by its
+/// extension.
+pub fn is_image(path: &str) -> bool {
+    extract_filename(path)
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// The text recognized in the image `data`.
+pub async fn image_text(data: &[u8]) -> Result<String> {
+    engine::image_text(data).await
+}
+
+/// The text recognized on the first [`MAX_OCR_PAGES`] pages of the PDF
+/// `data`, one page after the other.
+pub async fn pdf_text(data: &[u8]) -> Result<String> {
+    engine::pdf_text(data).await
+}
+
+#[cfg(feature = "ocr")]
+mod engine {
+    use std::ffi::OsStr;
+    use std::path::{Path, PathBuf};
+    use std::process::Stdio;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    use tokio::sync::Semaphore;
+
+    use super::*;
+
+    /// Longest one program may run, for one image or for rendering a PDF.
+    const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+    /// Programs run at once; each keeps a core busy.
+    const CONCURRENT_RUNS: usize = 2;
+
+    /// A temporary folder, removed with everything in it when dropped.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new() -> Result<Self> {
+            let dir = std::env::temp_dir().join(format!("infimount-ocr-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir)?;
+            Ok(Self(dir))
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    async fn run(program: &str, args: &[&OsStr]) -> Result<Vec<u8>> {
+        static RUNS: OnceLock<Semaphore> = OnceLock::new();
+        let _permit = RUNS
+            .get_or_init(|| Semaphore::new(CONCURRENT_RUNS))
+            .acquire()
+            .await
+            .map_err(std::io::Error::other)?;
+        let output = tokio::process::Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output();
+        let output = match tokio::time::timeout(COMMAND_TIMEOUT, output).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(CoreError::Unsupported(format!(
+                    "OCR needs {program}, which is not installed"
+                )));
+            }
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("{program} took longer than {COMMAND_TIMEOUT:?}"),
+                )
+                .into());
+            }
+        };
+        if !output.status.success() {
+            return Err(CoreError::Unsupported(format!(
+                "{program} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    async fn recognize(image: &Path) -> Result<String> {
+        let text = run("tesseract", &[image.as_os_str(), OsStr::new("stdout")]).await?;
+        Ok(String::from_utf8_lossy(&text).into_owned())
+    }
+
+    pub(super) async fn image_text(data: &[u8]) -> Result<String> {
+        let scratch = Scratch::new()?;
+        // Tesseract goes by the contents, not the name.
+        let image = scratch.0.join("image");
+        tokio::fs::write(&image, data).await?;
+        recognize(&image).await
+    }
+
+    pub(super) async fn pdf_text(data: &[u8]) -> Result<String> {
+        let scratch = Scratch::new()?;
+        let pdf = scratch.0.join("document.pdf");
+        tokio::fs::write(&pdf, data).await?;
+        let last_page = MAX_OCR_PAGES.to_string();
+        run(
+            "pdftoppm",
+            &[
+                OsStr::new("-r"),
+                OsStr::new("300"),
+                OsStr::new("-png"),
+                OsStr::new("-l"),
+                OsStr::new(&last_page),
+                pdf.as_os_str(),
+                scratch.0.join("page").as_os_str(),
+            ],
+        )
+        .await?;
+
+        // `page-01.png`, `page-02.png`, ...: numbers are padded alike, so
+        // they sort by name.
+        let mut pages = Vec::new();
+        let mut dir = tokio::fs::read_dir(&scratch.0).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with("page") && name.ends_with(".png") {
+                pages.push(entry.path());
+            }
+        }
+        pages.sort();
+
+        let mut text = String::new();
+        for page in pages {
+            text.push_str(&recognize(&page).await?);
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+        Ok(text)
+    }
+}
+
+#[cfg(not(feature = "ocr"))]
+mod engine {
+    use super::*;
+
+    fn unavailable() -> CoreError {
+        CoreError::Unsupported("this build has no OCR support".to_string())
+    }
+
+    pub(super) async fn image_text(_data: &[u8]) -> Result<String> {
+        Err(unavailable())
+    }
+
+    pub(super) async fn pdf_text(_data: &[u8]) -> Result<String> {
+        Err(unavailable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_go_by_extension() {
+        assert!(is_image("receipts/2024-03.JPG"));
+        assert!(is_image("scan.tiff"));
+        assert!(!is_image("scan.pdf"));
+        assert!(!is_image("png"));
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[tokio::test]
+    async fn recognition_is_unsupported_without_the_feature() {
+        assert!(!is_available());
+        assert!(matches!(
+            image_text(b"\x89PNG").await,
+            Err(CoreError::Unsupported(_))
+        ));
+    }
+}
@@ -1,10 +1,29 @@
-use futures::io::AsyncWriteExt;
+use futures::io::AsyncReadExt;
+use futures::stream::{self, FuturesUnordered, StreamExt};
 use futures::TryStreamExt;
 use opendal::{ErrorKind, Operator};
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tokio::fs;
 
-use crate::models::{Entry, Result};
+use crate::cancel::CancelToken;
+use crate::content_type;
+use crate::file_attrs::{self, FileAttrs};
+use crate::git;
+use crate::glob::Glob;
+use crate::http_index;
+use crate::models::{CoreError, Entry, ListQuery, Result};
+use crate::pause;
+use crate::per_operator::PerOperator;
+use crate::placeholder;
+use crate::prefetch;
+use crate::read_only;
+use crate::resume;
+use crate::symlinks;
+use crate::transfer_report::{Conflict, ItemStatus, TransferFilter, TransferItem, TransferReport};
+use crate::trash;
+use crate::tuning;
 use crate::util::extract_filename;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,6 +32,15 @@ pub enum TransferOperation {
     Move,
 }
 
+impl TransferOperation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TransferOperation::Copy => "copy",
+            TransferOperation::Move => "move",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TransferConflictPolicy {
     /// Fail fast if any destination exists (no partial transfer).
@@ -21,9 +49,11 @@ pub enum TransferConflictPolicy {
     Overwrite,
     /// Skip entries whose destinations already exist.
     Skip,
+    /// Keep both: write under a free name with a ` copy` suffix.
+    Rename,
 }
 
-fn normalize_opendal_path(path: &str) -> String {
+pub(crate) fn normalize_opendal_path(path: &str) -> String {
     let trimmed = path.trim();
     if trimmed.is_empty() || trimmed == "/" {
         return String::new();
@@ -31,7 +61,7 @@ fn normalize_opendal_path(path: &str) -> String {
     trimmed.trim_start_matches('/').to_string()
 }
 
-fn normalize_list_path(path: &str) -> String {
+pub(crate) fn normalize_list_path(path: &str) -> String {
     let mut p = normalize_opendal_path(path);
     if !p.is_empty() && !p.ends_with('/') {
         p.push('/');
@@ -39,9 +69,102 @@ fn normalize_list_path(path: &str) -> String {
     p
 }
 
-/// List entries at the given path using the provided operator.
+/// How [`list_entries`] fills in entry metadata for a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListingStrategy {
+    /// Stat every entry: the most complete metadata, cheap on local and
+    /// nearby sources.
+    StatPerEntry,
+    /// Use the size and date the listing itself returns, with no extra
+    /// round trip per entry.
+    ListMetadata,
+    /// Parse the server's HTML index pages (plain HTTP sources).
+    Index,
+}
+
+/// Above this measured latency a stat per entry costs more than it adds.
+const STAT_PER_ENTRY_MAX_LATENCY: Duration = Duration::from_millis(20);
+
+/// Backends whose listings already carry size and modification time.
+const LISTS_WITH_METADATA: &[&str] = &[
+    "s3",
+    "gcs",
+    "azblob",
+    "b2",
+    "webdav",
+    "ftp",
+    "webhdfs",
+    crate::synthetic::SYNTHETIC_BACKEND,
+];
+
+/// The strategy [`list_entries`] uses for `op`. Sources whose listings carry
+/// metadata skip the per-entry stat unless they answer quickly; latency is
+/// measured on every listing, so the choice follows the connection.
+pub fn listing_strategy(op: &Operator) -> ListingStrategy {
+    if http_index::needs_index_listing(op) {
+        return ListingStrategy::Index;
+    }
+    if !LISTS_WITH_METADATA.contains(&op.info().scheme()) {
+        return ListingStrategy::StatPerEntry;
+    }
+    match tuning::measured_latency(op) {
+        Some(latency) if latency < STAT_PER_ENTRY_MAX_LATENCY => ListingStrategy::StatPerEntry,
+        _ => ListingStrategy::ListMetadata,
+    }
+}
+
+/// Listing strategy of a source and the latency it was chosen from.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListingDiagnostics {
+    pub strategy: ListingStrategy,
+    /// Smoothed round-trip time, once something was measured.
+    pub latency_ms: Option<f64>,
+}
+
+pub fn listing_diagnostics(op: &Operator) -> ListingDiagnostics {
+    ListingDiagnostics {
+        strategy: listing_strategy(op),
+        latency_ms: tuning::measured_latency(op).map(|latency| latency.as_secs_f64() * 1000.0),
+    }
+}
+
+/// Entries one listing call returns at most unless configured otherwise
+/// (`Preferences::max_listing_entries`).
+pub const DEFAULT_MAX_LISTING_ENTRIES: usize = 100_000;
+
+/// List entries at the given path using the provided operator. A folder with
+/// more than [`DEFAULT_MAX_LISTING_ENTRIES`] entries fails with
+/// [`CoreError::TooManyEntries`] once the limit is passed, without fetching
+/// the rest; such folders are read with [`list_entries_paged`] or searched.
 pub async fn list_entries(op: &Operator, path: &str) -> Result<Vec<Entry>> {
+    list_entries_capped(op, path, DEFAULT_MAX_LISTING_ENTRIES).await
+}
+
+/// [`list_entries`] with at most `max_entries` entries instead of the
+/// default, filtered and sorted by `query`.
+pub async fn list_entries_query(
+    op: &Operator,
+    path: &str,
+    query: &ListQuery,
+    max_entries: usize,
+) -> Result<Vec<Entry>> {
+    Ok(query.apply(list_entries_capped(op, path, max_entries).await?))
+}
+
+async fn list_entries_capped(op: &Operator, path: &str, limit: usize) -> Result<Vec<Entry>> {
+    let limit = limit.max(1);
     let p = normalize_list_path(path);
+    let too_many = || CoreError::TooManyEntries(format!("/{p}"), limit);
+    let strategy = listing_strategy(op);
+    if strategy == ListingStrategy::Index {
+        let entries = http_index::list(op, &p).await?;
+        if entries.len() > limit {
+            return Err(too_many());
+        }
+        return Ok(entries);
+    }
+    let started = Instant::now();
     let mut lister = if p.is_empty() {
         match op.lister("").await {
             Ok(l) => l,
@@ -53,92 +176,1333 @@ pub async fn list_entries(op: &Operator, path: &str) -> Result<Vec<Entry>> {
     };
     let mut out = Vec::new();
 
+    let first = lister.try_next().await?;
+    tuning::record_latency(op, started.elapsed());
+    let mut next = first;
+    while let Some(obj) = next {
+        // The folder itself may be listed too, so allow one extra.
+        if out.len() > limit {
+            return Err(too_many());
+        }
+        out.push(listed_entry(op, obj, strategy).await?);
+        next = lister.try_next().await?;
+    }
+
+    if p.is_empty() {
+        out.retain(|entry| !trash::is_trash_path(&entry.path));
+    }
+    Ok(out)
+}
+
+/// Fill in [`Entry::git_status`] for entries listed from `dir`, when the
+/// source is local; listings leave it unset. Callers do this when the
+/// `git_annotations` preference is on.
+pub async fn annotate_git_status(op: &Operator, dir: &str, entries: &mut [Entry]) {
+    if let Ok(local) = local_fs_path(op, dir) {
+        git::annotate(&local, entries).await;
+    }
+}
+
+async fn listed_entry(
+    op: &Operator,
+    obj: opendal::Entry,
+    strategy: ListingStrategy,
+) -> Result<Entry> {
+    let (full_path, listed) = obj.into_parts();
+    if strategy == ListingStrategy::ListMetadata {
+        return Ok(entry_from_metadata(op, full_path, Some(&listed)));
+    }
+    // Use op.stat on the full path to ensure we get full metadata.
+    // If the entry no longer exists (e.g., broken symlink), keep the
+    // entry but leave size/modified blank instead of failing or skipping.
+    match op.stat(&full_path).await {
+        Ok(meta) => Ok(entry_from_metadata(op, full_path, Some(&meta))),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(entry_from_metadata(op, full_path, None)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// An [`Entry`] for `path` with whatever `meta` the backend reported.
+fn entry_from_metadata(op: &Operator, path: String, meta: Option<&opendal::Metadata>) -> Entry {
+    let is_dir = meta.is_some_and(|meta| meta.is_dir());
+    let link_target = local_link_target(op, &path);
+    Entry {
+        name: extract_filename(&path),
+        online_only: !is_dir && is_online_only(op, &path),
+        is_dir,
+        size: meta.map_or(0, |meta| meta.content_length()),
+        modified_at: meta
+            .and_then(|meta| meta.last_modified())
+            .map(|dt| dt.to_string()),
+        content_type: meta
+            .and_then(|meta| meta.content_type())
+            .map(str::to_string),
+        etag: meta.and_then(|meta| meta.etag()).map(str::to_string),
+        storage_class: None,
+        user_metadata: meta
+            .and_then(|meta| meta.user_metadata())
+            .filter(|user| !user.is_empty())
+            .cloned(),
+        git_status: None,
+        is_symlink: link_target.is_some(),
+        link_target,
+        path,
+    }
+}
+
+/// Where `path` points if it is a symbolic link on a local source.
+fn local_link_target(op: &Operator, path: &str) -> Option<String> {
+    if !is_local_fs(op) {
+        return None;
+    }
+    let local = local_fs_path(op, path).ok()?;
+    symlinks::link_target(&local).map(|target| target.to_string_lossy().into_owned())
+}
+
+/// Page size [`list_entries_paged`] callers use when they have no preference.
+pub const DEFAULT_PAGE_SIZE: usize = 1000;
+
+/// One page of a folder listing.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryPage {
+    pub entries: Vec<Entry>,
+    /// Pass back to [`list_entries_paged`] for the next page; `None` on the
+    /// last one.
+    pub next_cursor: Option<String>,
+}
+
+/// List up to `limit` entries of a folder (at most `max_entries`), continuing
+/// after `cursor` (from a previous page). Large folders can be shown incrementally instead of
+/// listing and stat-ing every entry up front. The cursor is opaque: on
+/// backends that can resume a listing after a key (S3, GCS, ...) it holds
+/// the last key, elsewhere how many entries were already returned.
+pub async fn list_entries_paged(
+    op: &Operator,
+    path: &str,
+    cursor: Option<&str>,
+    limit: usize,
+    max_entries: usize,
+) -> Result<EntryPage> {
+    let p = normalize_list_path(path);
+    let limit = limit.clamp(1, max_entries.max(1));
+    let (start_after, mut skip) = match cursor.map(|c| c.split_once(':')) {
+        None => (None, 0),
+        Some(Some(("k", key))) => (Some(key), 0),
+        Some(Some(("o", offset))) => match offset.parse::<usize>() {
+            Ok(offset) => (None, offset),
+            Err(_) => return Err(CoreError::Config(format!("invalid cursor: {offset}"))),
+        },
+        Some(_) => {
+            return Err(CoreError::Config(format!(
+                "invalid cursor: {}",
+                cursor.unwrap_or_default()
+            )))
+        }
+    };
+
+    let strategy = listing_strategy(op);
+    if strategy == ListingStrategy::Index {
+        let all = http_index::list(op, &p).await?;
+        let next_cursor = (all.len() > skip + limit).then(|| format!("o:{}", skip + limit));
+        let entries = all.into_iter().skip(skip).take(limit).collect();
+        return Ok(EntryPage {
+            entries,
+            next_cursor,
+        });
+    }
+
+    let by_key = op.info().full_capability().list_with_start_after;
+    let list_path = if p.is_empty() { "/" } else { p.as_str() };
+    let mut lister = match start_after {
+        Some(key) if by_key => op.lister_with(list_path).start_after(key).await?,
+        Some(_) => {
+            return Err(CoreError::Config(
+                "cursor does not match this source".to_string(),
+            ))
+        }
+        None => op.lister(list_path).await?,
+    };
+
+    let offset = skip;
+    let mut entries = Vec::new();
     while let Some(obj) = lister.try_next().await? {
-        let full_path = obj.path().to_string();
-        let name = extract_filename(&full_path);
-
-        // Use op.stat on the full path to ensure we get full metadata.
-        // If the entry no longer exists (e.g., broken symlink), keep the
-        // entry but leave size/modified blank instead of failing or skipping.
-        let (is_dir, size, modified_at) = match op.stat(&full_path).await {
-            Ok(meta) => (
-                meta.is_dir(),
-                meta.content_length(),
-                meta.last_modified().map(|dt| dt.to_string()),
-            ),
-            Err(e) if e.kind() == ErrorKind::NotFound => (false, 0, None),
-            Err(e) => return Err(e.into()),
-        };
+        if skip > 0 {
+            skip -= 1;
+            continue;
+        }
+        if entries.len() == limit {
+            let next_cursor = if by_key {
+                entries
+                    .last()
+                    .map(|entry: &Entry| format!("k:{}", entry.path))
+            } else {
+                Some(format!("o:{}", offset + limit))
+            };
+            return Ok(EntryPage {
+                entries,
+                next_cursor,
+            });
+        }
+        entries.push(listed_entry(op, obj, strategy).await?);
+    }
+    Ok(EntryPage {
+        entries,
+        next_cursor: None,
+    })
+}
 
-        let entry = Entry {
-            path: full_path,
-            name,
-            is_dir,
-            size,
-            modified_at,
+/// Folders nested deeper than this are not descended into.
+const MAX_RECURSIVE_DEPTH: usize = 64;
+
+/// Everything below `path`, flattened: folders come before their contents
+/// and each level is listed with [`list_entries`]. `max_depth` 1 returns the
+/// direct children only, 2 adds theirs, and so on; `None` walks the whole
+/// tree. Linked folders are listed but not descended into, so a symbolic
+/// link back up the tree cannot loop. Used for folder sizes, search and
+/// sync planning. Fails with
+/// [`CoreError::TooManyEntries`] past `max_entries` in total.
+pub async fn list_recursive(
+    op: &Operator,
+    path: &str,
+    max_depth: Option<usize>,
+    max_entries: usize,
+) -> Result<Vec<Entry>> {
+    let max_depth = max_depth
+        .unwrap_or(MAX_RECURSIVE_DEPTH)
+        .min(MAX_RECURSIVE_DEPTH);
+    let limit = max_entries.max(1);
+    let mut out = Vec::new();
+    let mut pending = VecDeque::from([(normalize_list_path(path), 1)]);
+    while let Some((dir, depth)) = pending.pop_front() {
+        if depth > max_depth {
+            continue;
+        }
+        for entry in list_entries_capped(op, &dir, limit).await? {
+            let entry_dir = normalize_list_path(&entry.path);
+            if entry_dir == dir {
+                continue;
+            }
+            if entry.is_dir && !entry.is_symlink {
+                pending.push_back((entry_dir, depth + 1));
+            }
+            out.push(entry);
+        }
+        if out.len() > limit {
+            return Err(CoreError::TooManyEntries(
+                format!("/{}", normalize_list_path(path)),
+                limit,
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Folders [`search`] lists at the same time.
+pub const SEARCH_CONCURRENCY: usize = 8;
+
+/// Find the entries below `root` whose path relative to it matches the glob
+/// `pattern` (see [`crate::glob`]), e.g. `**/*.pdf`. Folders are walked with
+/// up to [`SEARCH_CONCURRENCY`] listings in flight and every match is handed
+/// to `on_match` as soon as its folder is listed; return `false` from it to
+/// stop early, or cancel `cancel`. Folders that cannot be listed (or are
+/// too large to) below `root` are skipped, and linked folders are not
+/// descended into. Returns the number of matches.
+pub async fn search(
+    op: &Operator,
+    root: &str,
+    pattern: &str,
+    cancel: &CancelToken,
+    mut on_match: impl FnMut(Entry) -> bool,
+) -> Result<usize> {
+    let glob = Glob::new(pattern)?;
+    let root = normalize_list_path(root);
+    let mut pending = VecDeque::from([root.clone()]);
+    let mut running = FuturesUnordered::new();
+    let mut found = 0;
+    loop {
+        while running.len() < SEARCH_CONCURRENCY {
+            let Some(dir) = pending.pop_front() else {
+                break;
+            };
+            running.push(list_dir(op, dir));
+        }
+        let Some((dir, listed)) = running.next().await else {
+            break;
         };
+        if cancel.is_cancelled() {
+            break;
+        }
+        let entries = match listed {
+            Ok(entries) => entries,
+            Err(error) if dir == root => return Err(error),
+            Err(_) => continue,
+        };
+        for entry in entries {
+            let entry_dir = normalize_list_path(&entry.path);
+            if entry_dir == dir {
+                continue;
+            }
+            let relative = entry_dir[root.len()..].trim_end_matches('/');
+            if entry.is_dir
+                && !entry.is_symlink
+                && relative.matches('/').count() < MAX_RECURSIVE_DEPTH
+            {
+                pending.push_back(entry_dir.clone());
+            }
+            if glob.matches(relative) {
+                found += 1;
+                if !on_match(entry) {
+                    return Ok(found);
+                }
+            }
+        }
+    }
+    Ok(found)
+}
+
+async fn list_dir(op: &Operator, dir: String) -> (String, Result<Vec<Entry>>) {
+    let listed = list_entries(op, &dir).await;
+    (dir, listed)
+}
+
+/// Stat a single entry.
+pub async fn stat_entry(op: &Operator, path: &str) -> Result<Entry> {
+    let p = normalize_opendal_path(path);
+    let meta = op.stat(&p).await?;
+    Ok(entry_from_metadata(op, p, Some(&meta)))
+}
+
+/// Read the full contents of a file.
+pub async fn read_full(op: &Operator, path: &str) -> Result<Vec<u8>> {
+    let p = normalize_opendal_path(path);
+    let data = op.read(&p).await?;
+    Ok(data.to_vec())
+}
+
+/// Read up to `len` bytes starting at `offset`, e.g. the head of a file for a
+/// preview, the tail of a log or the part of a video the player seeks to.
+/// The range is clamped to the file, so reading past the end returns fewer
+/// bytes (none when `offset` is beyond it) rather than an error.
+pub async fn read_range(op: &Operator, path: &str, offset: u64, len: u64) -> Result<Vec<u8>> {
+    let p = normalize_opendal_path(path);
+    let size = op.stat(&p).await?.content_length();
+    let end = offset.saturating_add(len).min(size);
+    if offset >= end {
+        return Ok(Vec::new());
+    }
+    let data = op.read_with(&p).range(offset..end).await?;
+    Ok(data.to_vec())
+}
+
+/// Chunk size [`read_chunks`] uses when the caller does not pick one.
+pub const DEFAULT_READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Stream a file to `on_chunk` in pieces of `chunk_size` bytes (the last one
+/// may be shorter) without holding the whole object in memory. `on_chunk`
+/// returns `false` to stop early, e.g. once a preview has enough. Returns the
+/// number of bytes handed out.
+pub async fn read_chunks<F>(
+    op: &Operator,
+    path: &str,
+    chunk_size: usize,
+    mut on_chunk: F,
+) -> Result<u64>
+where
+    F: FnMut(&[u8]) -> bool,
+{
+    let p = normalize_opendal_path(path);
+    let size = op.stat(&p).await?.content_length();
+    let read_tuning = tuning::tuning_for(op);
+    let mut reader = op
+        .reader_with(&p)
+        .chunk(read_tuning.chunk_size)
+        .concurrent(read_tuning.concurrency)
+        .await?
+        .into_futures_async_read(0..size)
+        .await?;
+
+    let mut buf = vec![0; chunk_size.max(1)];
+    let mut delivered = 0;
+    loop {
+        let mut filled = 0;
+        while filled < buf.len() {
+            let n = reader.read(&mut buf[filled..]).await?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        if filled == 0 {
+            break;
+        }
+        delivered += filled as u64;
+        if !on_chunk(&buf[..filled]) || filled < buf.len() {
+            break;
+        }
+    }
+    Ok(delivered)
+}
+
+/// Hash functions [`checksum`] supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha256,
+    /// 64-bit xxHash (XXH64, seed 0).
+    Xxh64,
+}
+
+impl ChecksumAlgorithm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Xxh64 => "xxh64",
+        }
+    }
+}
+
+/// Lowercase hex digest of a file, computed while streaming it so large
+/// objects are never held in memory.
+pub async fn checksum(op: &Operator, path: &str, algorithm: ChecksumAlgorithm) -> Result<String> {
+    use sha2::Digest;
+
+    let digest: Vec<u8> = match algorithm {
+        ChecksumAlgorithm::Md5 => {
+            let mut hasher = md5::Md5::new();
+            read_chunks(op, path, DEFAULT_READ_CHUNK_SIZE, |chunk| {
+                hasher.update(chunk);
+                true
+            })
+            .await?;
+            hasher.finalize().to_vec()
+        }
+        ChecksumAlgorithm::Sha256 => {
+            let mut hasher = sha2::Sha256::new();
+            read_chunks(op, path, DEFAULT_READ_CHUNK_SIZE, |chunk| {
+                hasher.update(chunk);
+                true
+            })
+            .await?;
+            hasher.finalize().to_vec()
+        }
+        ChecksumAlgorithm::Xxh64 => {
+            let mut hasher = xxhash_rust::xxh64::Xxh64::new(0);
+            read_chunks(op, path, DEFAULT_READ_CHUNK_SIZE, |chunk| {
+                hasher.update(chunk);
+                true
+            })
+            .await?;
+            hasher.digest().to_be_bytes().to_vec()
+        }
+    };
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// How [`write_full`] writes a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteOptions {
+    /// MIME type stored on backends that keep one; by default the type its
+    /// extension suggests (see [`content_type::guess`]).
+    pub content_type: Option<String>,
+    /// Only overwrite the file while it is still this version: the `etag`
+    /// of its [`Entry`], or its `modified_at` on backends that report no
+    /// etag. Guards an editor against clobbering changes made elsewhere
+    /// since the file was opened.
+    pub if_match: Option<String>,
+    /// Only write a file that does not exist yet.
+    pub if_not_exists: bool,
+}
 
-        out.push(entry);
+/// Write the full contents of a file, overwriting if it exists. A write whose
+/// `if_match` or `if_not_exists` condition fails leaves the file alone and
+/// fails with an [`ErrorKind::ConditionNotMatch`] error.
+///
+/// Backends that check conditions themselves (S3, GCS, Azure, ...) do so
+/// atomically. Elsewhere the file is looked up just before writing, which
+/// catches changes made while it was open but not ones racing the write.
+pub async fn write_full(
+    op: &Operator,
+    path: &str,
+    data: &[u8],
+    options: &WriteOptions,
+) -> Result<()> {
+    ensure_writable(op)?;
+    let p = normalize_opendal_path(path);
+    let capability = op.info().full_capability();
+    let native_if_match = capability.write_with_if_match;
+    let native_if_not_exists = capability.write_with_if_not_exists;
+    if (options.if_match.is_some() && !native_if_match)
+        || (options.if_not_exists && !native_if_not_exists)
+    {
+        check_write_condition(op, &p, options).await?;
+    }
+
+    let mut write = op.write_with(&p, data.to_vec());
+    if let Some(content_type) = content_type::for_write(op, &p, options.content_type.as_deref()) {
+        write = write.content_type(content_type);
+    }
+    if let Some(etag) = options.if_match.as_deref().filter(|_| native_if_match) {
+        write = write.if_match(etag);
+    }
+    if native_if_not_exists {
+        write = write.if_not_exists(options.if_not_exists);
+    }
+    write.await.map_err(|e| {
+        // Some backends (local folders) report an existing file as such.
+        if options.if_not_exists && e.kind() == ErrorKind::AlreadyExists {
+            opendal::Error::new(ErrorKind::ConditionNotMatch, format!("{p} already exists"))
+                .set_source(e)
+        } else {
+            e
+        }
+    })?;
+    Ok(())
+}
+
+/// Look `path` up and fail if it does not meet the conditions in `options`.
+async fn check_write_condition(op: &Operator, path: &str, options: &WriteOptions) -> Result<()> {
+    let meta = match op.stat(path).await {
+        Ok(meta) => Some(meta),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let failed = match (&meta, options.if_match.as_deref()) {
+        (Some(_), _) if options.if_not_exists => Some("already exists"),
+        (None, Some(_)) => Some("no longer exists"),
+        (Some(meta), Some(expected)) => {
+            let current = match meta.etag() {
+                Some(etag) => Some(etag.to_string()),
+                None => meta.last_modified().map(|at| at.to_string()),
+            };
+            (current.as_deref() != Some(expected)).then_some("changed since it was read")
+        }
+        _ => None,
+    };
+    match failed {
+        Some(reason) => Err(opendal::Error::new(
+            ErrorKind::ConditionNotMatch,
+            format!("{path} {reason}"),
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Create a directory at the given path.
+///
+/// Object stores (S3, GCS, Azure, ...) have no real directories: a folder is
+/// any prefix with objects under it, and an empty one is kept as a zero-byte
+/// `path/` marker. A prefix that already has objects counts as an existing
+/// folder and is left alone, and a file with the same name is rejected
+/// rather than shadowed by a marker next to it.
+pub async fn create_dir(op: &Operator, path: &str) -> Result<()> {
+    ensure_writable(op)?;
+    let p = normalize_list_path(path);
+    if p.is_empty() {
+        return Ok(());
+    }
+    if !op.info().full_capability().create_dir {
+        return Err(CoreError::Unsupported(format!(
+            "{} sources cannot hold empty folders; folders appear when files are written into them",
+            op.info().scheme()
+        )));
+    }
+    ensure_capability(op, "create_dir")?;
+
+    match op.stat(p.trim_end_matches('/')).await {
+        Ok(meta) if !meta.is_dir() => {
+            return Err(opendal::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("a file named '{}' already exists", p.trim_end_matches('/')),
+            )
+            .into());
+        }
+        Ok(_) => return Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    if op.exists(&p).await? {
+        return Ok(());
+    }
+
+    op.create_dir(&p).await?;
+    Ok(())
+}
+
+/// Delete a path (file or directory).
+pub async fn delete(op: &Operator, path: &str) -> Result<()> {
+    delete_cancellable(op, path, &CancelToken::default()).await
+}
+
+/// [`delete`] that stops handing objects to the backend once `cancel`
+/// fires. What was deleted by then stays deleted.
+pub async fn delete_cancellable(op: &Operator, path: &str, cancel: &CancelToken) -> Result<()> {
+    ensure_writable(op)?;
+    ensure_capability(op, "delete")?;
+    let p = normalize_opendal_path(path);
+    remove_all_cancellable(op, &p, cancel).await
+}
+
+/// `Operator::remove_all`, checking `cancel` between listed objects.
+async fn remove_all_cancellable(op: &Operator, path: &str, cancel: &CancelToken) -> Result<()> {
+    cancel.check()?;
+    match op.stat(path).await {
+        // Object stores may still hold keys under the file's path.
+        Ok(meta) if !meta.is_dir() => op.delete(path).await?,
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    let lister = op.lister_with(path).recursive(true).await?;
+    let entries = lister.take_while(|_| std::future::ready(!cancel.is_cancelled()));
+    op.delete_try_stream(entries).await?;
+    cancel.check()
+}
+
+/// Deletes [`delete_many`] runs at the same time on backends without batch
+/// delete.
+const DELETE_CONCURRENCY: usize = 8;
+
+/// A path [`delete_many`] could not delete.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeleteFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Outcome of [`delete_many`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DeleteReport {
+    pub deleted: Vec<String>,
+    pub failed: Vec<DeleteFailure>,
+}
+
+impl DeleteReport {
+    pub fn record(&mut self, path: String, result: Result<()>) {
+        match result {
+            Ok(()) => self.deleted.push(path),
+            Err(error) => self.failed.push(DeleteFailure {
+                path,
+                error: error.to_string(),
+            }),
+        }
+    }
+}
+
+/// Delete many files and folders. Files go through the backend's batch
+/// delete where it has one (S3 and friends take up to 1000 keys per
+/// request), otherwise a few at a time; folders (paths ending in `/`) are
+/// removed recursively. A failure does not stop the others: every path ends
+/// up in the report as deleted or failed. Once `cancel` fires, the paths
+/// not yet deleted fail as cancelled.
+pub async fn delete_many(
+    op: &Operator,
+    paths: Vec<String>,
+    cancel: &CancelToken,
+) -> Result<DeleteReport> {
+    ensure_writable(op)?;
+    ensure_capability(op, "delete")?;
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| normalize_opendal_path(path))
+        .collect();
+    // An empty path is the source root; removing it would empty the source.
+    if paths.iter().any(|path| path.is_empty() || path == "/") {
+        return Err(CoreError::Unsupported(
+            "cannot delete the source root".to_string(),
+        ));
+    }
+    let mut report = DeleteReport::default();
+    let (dirs, files): (Vec<String>, Vec<String>) =
+        paths.into_iter().partition(|path| path.ends_with('/'));
+
+    let batch_size = op.info().full_capability().delete_max_size.unwrap_or(1);
+    let mut unbatched = Vec::new();
+    if batch_size > 1 {
+        for batch in files.chunks(batch_size) {
+            if cancel.is_cancelled() {
+                unbatched.extend_from_slice(batch);
+                continue;
+            }
+            match delete_batch(op, batch).await {
+                Ok(()) => report.deleted.extend_from_slice(batch),
+                // The batch error does not say which paths failed; retry
+                // them one by one to find out.
+                Err(_) => unbatched.extend_from_slice(batch),
+            }
+        }
+    } else {
+        unbatched = files;
+    }
+
+    let mut singles = stream::iter(unbatched)
+        .map(|path| async move {
+            let result = match cancel.check() {
+                Ok(()) => op.delete(&path).await.map_err(CoreError::from),
+                Err(e) => Err(e),
+            };
+            (path, result)
+        })
+        .buffer_unordered(DELETE_CONCURRENCY);
+    while let Some((path, result)) = singles.next().await {
+        report.record(path, result);
+    }
+
+    for dir in dirs {
+        let result = remove_all_cancellable(op, &dir, cancel).await;
+        report.record(dir, result);
+    }
+    Ok(report)
+}
+
+async fn delete_batch(op: &Operator, paths: &[String]) -> Result<()> {
+    let mut deleter = op.deleter().await?;
+    deleter
+        .delete_iter(paths.iter().map(String::as_str))
+        .await?;
+    deleter.close().await?;
+    Ok(())
+}
+
+/// How long [`test_connection`] waits for the backend to answer.
+const CONNECTION_TEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Why a connection test failed, coarse enough for the UI to pick a hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionErrorKind {
+    /// The settings could not be turned into an operator.
+    Config,
+    Unauthorized,
+    NotFound,
+    Timeout,
+    Other,
+}
+
+/// Outcome of [`test_connection`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionTest {
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<ConnectionErrorKind>,
+}
+
+impl ConnectionTest {
+    fn failed(kind: ConnectionErrorKind, error: impl ToString, latency_ms: u64) -> Self {
+        Self {
+            ok: false,
+            latency_ms,
+            error: Some(error.to_string()),
+            error_kind: Some(kind),
+        }
+    }
+
+    /// A test that failed before reaching the backend.
+    pub fn config_error(error: &CoreError) -> Self {
+        Self::failed(ConnectionErrorKind::Config, error, 0)
+    }
+}
+
+/// Cheapest call that proves the endpoint, credentials and root are valid:
+/// open a listing of the root, or stat it on backends that cannot list.
+pub(crate) async fn probe(op: &Operator) -> Result<()> {
+    if !op.info().full_capability().list {
+        op.stat("").await?;
+        return Ok(());
+    }
+    let mut lister = match op.lister("").await {
+        Ok(lister) => lister,
+        Err(err) if err.kind() == ErrorKind::NotFound => op.lister("/").await?,
+        Err(err) => return Err(err.into()),
+    };
+    let _ = lister.try_next().await?;
+    Ok(())
+}
+
+/// Check that `op` can reach its backend, timing the round trip. Never fails;
+/// problems are reported in the returned [`ConnectionTest`].
+pub async fn test_connection(op: &Operator) -> ConnectionTest {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(CONNECTION_TEST_TIMEOUT, probe(op)).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match outcome {
+        Ok(Ok(())) => ConnectionTest {
+            ok: true,
+            latency_ms,
+            error: None,
+            error_kind: None,
+        },
+        Ok(Err(error)) => {
+            let kind = match &error {
+                CoreError::Storage(err) => match err.kind() {
+                    ErrorKind::PermissionDenied => ConnectionErrorKind::Unauthorized,
+                    ErrorKind::NotFound => ConnectionErrorKind::NotFound,
+                    ErrorKind::ConfigInvalid => ConnectionErrorKind::Config,
+                    _ => ConnectionErrorKind::Other,
+                },
+                _ => ConnectionErrorKind::Other,
+            };
+            ConnectionTest::failed(kind, error, latency_ms)
+        }
+        Err(_) => ConnectionTest::failed(
+            ConnectionErrorKind::Timeout,
+            format!("no response within {}s", CONNECTION_TEST_TIMEOUT.as_secs()),
+            latency_ms,
+        ),
+    }
+}
+
+/// Whether the operator accepts writes at all. Read-only backends (HTTP, IPFS
+/// gateways) would otherwise fail deep inside OpenDAL with a generic error.
+pub fn is_writable(op: &Operator) -> bool {
+    op.info().full_capability().write
+}
+
+pub(crate) fn ensure_writable(op: &Operator) -> Result<()> {
+    // Whatever is about to change may be in listings prefetched earlier.
+    prefetch::forget(op);
+    if read_only::is_read_only(op) {
+        return Err(CoreError::ReadOnlySource(format!(
+            "{}{}",
+            op.info().name(),
+            op.info().root()
+        )));
+    }
+    if is_writable(op) {
+        return ensure_capability(op, "write");
+    }
+    Err(CoreError::Unsupported(format!(
+        "{} sources are read-only",
+        op.info().scheme()
+    )))
+}
+
+static RECORDED_CAPABILITIES: PerOperator<BTreeSet<String>> = PerOperator::new();
+
+/// Remember the capability set probed when the source was added (see
+/// [`SourceCapabilities::supported`]). Operations outside it then fail
+/// up front with [`CoreError::Unsupported`] instead of erroring somewhere
+/// inside the backend. `None` forgets it and defers to the live backend.
+pub fn set_recorded_capabilities(op: &Operator, supported: Option<BTreeSet<String>>) {
+    RECORDED_CAPABILITIES.set(op, supported);
+}
+
+/// Fail fast when `capability` is missing from the set recorded for `op`.
+pub(crate) fn ensure_capability(op: &Operator, capability: &str) -> Result<()> {
+    match RECORDED_CAPABILITIES.with(op, |supported| supported.contains(capability)) {
+        Some(false) => Err(CoreError::Unsupported(format!(
+            "{} sources do not support {}",
+            op.info().scheme(),
+            capability.replace('_', " ")
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Same-source copies and moves use the backend's own copy and rename.
+fn ensure_native_transfer(
+    op: &Operator,
+    operation: TransferOperation,
+    same_source: bool,
+) -> Result<()> {
+    if !same_source {
+        return Ok(());
+    }
+    ensure_capability(
+        op,
+        match operation {
+            TransferOperation::Copy => "copy",
+            TransferOperation::Move => "rename",
+        },
+    )
+}
+
+/// What a source supports, so the UI can hide actions that would fail.
+/// Mutating operations are reported unsupported on read-only sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SourceCapabilities {
+    pub read_only: bool,
+    pub list: bool,
+    pub stat: bool,
+    pub read: bool,
+    pub write: bool,
+    pub append: bool,
+    pub create_dir: bool,
+    pub delete: bool,
+    pub copy: bool,
+    pub rename: bool,
+    pub presign_read: bool,
+    pub presign_write: bool,
+    pub list_with_versions: bool,
+    pub read_with_version: bool,
+    pub delete_with_version: bool,
+    pub os_trash: bool,
+    /// Deletions can go to the source's own trash; see [`crate::trash`].
+    pub trash: bool,
+    pub links: bool,
+}
+
+/// Capabilities of the backend behind `op`, as reported by OpenDAL and
+/// narrowed for read-only sources (see [`crate::read_only`]).
+pub fn capabilities(op: &Operator) -> SourceCapabilities {
+    backend_capabilities(op, read_only::is_read_only(op))
+}
+
+/// Probe what the backend behind `op` supports, ignoring whether it is read-only,
+/// to be persisted with the source when it is added.
+pub fn probe_capabilities(op: &Operator) -> BTreeSet<String> {
+    backend_capabilities(op, false).supported()
+}
+
+fn backend_capabilities(op: &Operator, read_only: bool) -> SourceCapabilities {
+    let full = op.info().full_capability();
+    let writable = !read_only && full.write;
+    SourceCapabilities {
+        read_only,
+        list: full.list,
+        stat: full.stat,
+        read: full.read,
+        write: writable,
+        append: writable && full.write_can_append,
+        create_dir: !read_only && full.create_dir,
+        delete: !read_only && full.delete,
+        copy: !read_only && full.copy,
+        rename: !read_only && full.rename,
+        presign_read: full.presign_read,
+        presign_write: !read_only && full.presign_write,
+        list_with_versions: full.list_with_versions,
+        read_with_version: full.read_with_version,
+        delete_with_version: !read_only && full.delete_with_version,
+        os_trash: !read_only && supports_os_trash(op),
+        trash: writable && full.list && full.read && full.delete,
+        links: !read_only && supports_links(op),
+    }
+}
+
+impl SourceCapabilities {
+    /// Names of the supported operations (`"list"`, `"rename"`, ...), the
+    /// form persisted with a source and passed to [`set_recorded_capabilities`].
+    pub fn supported(&self) -> BTreeSet<String> {
+        [
+            ("list", self.list),
+            ("stat", self.stat),
+            ("read", self.read),
+            ("write", self.write),
+            ("append", self.append),
+            ("create_dir", self.create_dir),
+            ("delete", self.delete),
+            ("copy", self.copy),
+            ("rename", self.rename),
+            ("presign_read", self.presign_read),
+            ("presign_write", self.presign_write),
+            ("list_with_versions", self.list_with_versions),
+            ("read_with_version", self.read_with_version),
+            ("delete_with_version", self.delete_with_version),
+            ("os_trash", self.os_trash),
+            ("trash", self.trash),
+            ("links", self.links),
+        ]
+        .into_iter()
+        .filter(|(_, supported)| *supported)
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+}
+
+/// Whether deletions on this operator can be routed to the OS trash.
+///
+/// Only local filesystem operators map onto paths the OS trash understands.
+pub fn supports_os_trash(op: &Operator) -> bool {
+    is_local_fs(op)
+}
+
+/// Move a path (file or directory) to the OS trash / recycle bin instead of
+/// deleting it permanently. Only supported for local filesystem operators.
+pub async fn move_to_os_trash(op: &Operator, path: &str) -> Result<()> {
+    ensure_writable(op)?;
+    let local_path = local_fs_path(op, path)?;
+    if normalize_opendal_path(path).is_empty() {
+        return Err(CoreError::Unsupported(
+            "cannot move the source root to the trash".to_string(),
+        ));
+    }
+
+    tokio::task::spawn_blocking(move || ::trash::delete(&local_path))
+        .await
+        .map_err(std::io::Error::other)?
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(())
+}
+
+pub(crate) fn is_local_fs(op: &Operator) -> bool {
+    op.info().scheme() == "fs"
+}
+
+/// Whether `path` on a local source is an online-only placeholder. Always
+/// false for remote sources.
+fn is_online_only(op: &Operator, path: &str) -> bool {
+    is_local_fs(op)
+        && local_fs_path(op, path)
+            .map(|local| placeholder::is_online_only(&local))
+            .unwrap_or(false)
+}
+
+/// Resolve a source-relative path to an absolute path on the local filesystem.
+pub(crate) fn local_fs_path(op: &Operator, path: &str) -> Result<PathBuf> {
+    if !is_local_fs(op) {
+        return Err(CoreError::Unsupported(format!(
+            "{} sources have no local filesystem path",
+            op.info().scheme()
+        )));
+    }
+
+    let relative = normalize_opendal_path(path);
+    let root = PathBuf::from(op.info().root());
+    Ok(root.join(relative.trim_end_matches('/')))
+}
+
+/// Whether symbolic and hard links can be created on this operator.
+/// OpenDAL has no link API, so this is limited to local filesystem sources.
+pub fn supports_links(op: &Operator) -> bool {
+    is_local_fs(op)
+}
+
+/// Create a symbolic link at `link_path` pointing to `target`. Both paths are
+/// relative to the source root; the link stores the absolute target path.
+pub async fn create_symlink(op: &Operator, target: &str, link_path: &str) -> Result<()> {
+    ensure_writable(op)?;
+    let target = local_fs_path(op, target)?;
+    let link = local_fs_path(op, link_path)?;
+    ensure_link_slot_free(op, link_path).await?;
+    symlinks::create(target, link).await?;
+    Ok(())
+}
+
+/// Create a hard link at `link_path` to the existing file `existing`. Both
+/// paths are relative to the source root.
+pub async fn create_hardlink(op: &Operator, existing: &str, link_path: &str) -> Result<()> {
+    ensure_writable(op)?;
+    let existing = local_fs_path(op, existing)?;
+    let link = local_fs_path(op, link_path)?;
+    ensure_link_slot_free(op, link_path).await?;
+
+    tokio::task::spawn_blocking(move || std::fs::hard_link(&existing, &link))
+        .await
+        .map_err(std::io::Error::other)??;
+    Ok(())
+}
+
+async fn ensure_link_slot_free(op: &Operator, link_path: &str) -> Result<()> {
+    let normalized = normalize_opendal_path(link_path);
+    if normalized.is_empty() || normalized.ends_with('/') {
+        return Err(CoreError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("link path must name a file: {link_path}"),
+        )));
+    }
+    if op.exists(&normalized).await? {
+        return Err(CoreError::Io(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{link_path} already exists"),
+        )));
+    }
+    Ok(())
+}
+
+/// Upload files from local paths to the target directory, stopping with an
+/// error if any file fails. `conflict_policy` decides what happens to files
+/// that already exist.
+pub async fn upload_files_from_paths(
+    op: &Operator,
+    paths: Vec<String>,
+    target_dir: String,
+    conflict_policy: TransferConflictPolicy,
+) -> Result<()> {
+    let mut report = TransferReport::new("upload", "local", op.info().name());
+    upload(&mut report, op, paths, &target_dir, conflict_policy, false).await?;
+    let failure = report.failed_items().next().map(|item| {
+        opendal::Error::new(
+            ErrorKind::Unexpected,
+            format!(
+                "failed to upload {}: {}",
+                item.source_path,
+                item.error.as_deref().unwrap_or_default()
+            ),
+        )
+    });
+    match failure {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+/// Files [`upload`] sends at once unless configured otherwise.
+pub const DEFAULT_UPLOAD_CONCURRENCY: usize = 4;
+
+const MAX_UPLOAD_CONCURRENCY: usize = 16;
+
+/// Upload local files and folders into `target_dir`, keeping their names.
+/// Folders are walked up front so progress has totals; the files are then
+/// streamed from disk, several at a time (see
+/// [`TransferReport::set_upload_concurrency`]), and recorded in `report`.
+/// A file that fails does not stop the others. Symbolic links
+/// inside folders are followed, or recreated as links on a Local target
+/// with `keep_links` (see `Preferences::copy_symlinks_as_links`).
+pub async fn upload(
+    report: &mut TransferReport,
+    op: &Operator,
+    paths: Vec<String>,
+    target_dir: &str,
+    conflict_policy: TransferConflictPolicy,
+    keep_links: bool,
+) -> Result<()> {
+    ensure_writable(op)?;
+    if report.wants_progress() {
+        report.start_preparing();
+    }
+    let keep_links = keep_links && is_local_fs(op);
+    let mut files = Vec::new();
+    let mut links = Vec::new();
+    for path in paths {
+        collect_upload_files(
+            Path::new(&path),
+            target_dir,
+            keep_links,
+            report.filter(),
+            &mut files,
+            &mut links,
+        )
+        .await?;
+    }
+    if conflict_policy == TransferConflictPolicy::Fail {
+        for (_, target, _) in &links {
+            if op.exists(target).await? {
+                return Err(already_exists(target));
+            }
+        }
+    }
+    upload_files(report, op, files, conflict_policy).await?;
+    upload_links(report, op, links, conflict_policy).await
+}
+
+/// Recreate symbolic links found by [`upload`] on a Local target, each
+/// pointing where the original does.
+async fn upload_links(
+    report: &mut TransferReport,
+    op: &Operator,
+    links: Vec<(PathBuf, String, PathBuf)>,
+    conflict_policy: TransferConflictPolicy,
+) -> Result<()> {
+    for (local, target, link_target) in links {
+        report.cancel_token().check()?;
+        let started = Instant::now();
+        let (target, conflict, result) =
+            match resolve_upload_target(op, target.clone(), conflict_policy).await {
+                Ok(Some((target, conflict))) => {
+                    let replace = conflict == Some(Conflict::Overwritten);
+                    let result = recreate_link(op, &target, link_target, replace).await;
+                    (target, conflict, result)
+                }
+                Ok(None) => (target, Some(Conflict::Skipped), Ok(())),
+                Err(e) => (target, None, Err(e)),
+            };
+        report.push(TransferItem {
+            source_path: local.to_string_lossy().into_owned(),
+            target_path: target,
+            status: match (&result, conflict) {
+                (Err(_), _) => ItemStatus::Failed,
+                (Ok(_), Some(Conflict::Skipped)) => ItemStatus::Skipped,
+                (Ok(_), _) => ItemStatus::Completed,
+            },
+            bytes: 0,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: result.err().map(|e| e.to_string()),
+            verified: None,
+            conflict,
+        });
     }
-
-    Ok(out)
+    Ok(())
 }
 
-/// Stat a single entry.
-pub async fn stat_entry(op: &Operator, path: &str) -> Result<Entry> {
-    let p = normalize_opendal_path(path);
-    let meta = op.stat(&p).await?;
-    let full_path = p.to_string();
-    let name = extract_filename(&full_path);
-
-    Ok(Entry {
-        path: full_path,
-        name,
-        is_dir: meta.is_dir(),
-        size: meta.content_length(),
-        modified_at: meta.last_modified().map(|dt| dt.to_string()),
-    })
+async fn recreate_link(
+    op: &Operator,
+    target: &str,
+    link_target: PathBuf,
+    replace: bool,
+) -> Result<()> {
+    let link = local_fs_path(op, target)?;
+    if replace {
+        fs::remove_file(&link).await?;
+    }
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    symlinks::create(link_target, link).await?;
+    Ok(())
 }
 
-/// Read the full contents of a file.
-pub async fn read_full(op: &Operator, path: &str) -> Result<Vec<u8>> {
-    let p = normalize_opendal_path(path);
-    let data = op.read(&p).await?;
-    Ok(data.to_vec())
-}
+/// Upload local files, given with their target paths and sizes.
+pub(crate) async fn upload_files(
+    report: &mut TransferReport,
+    op: &Operator,
+    files: Vec<(PathBuf, String, u64)>,
+    conflict_policy: TransferConflictPolicy,
+) -> Result<()> {
+    ensure_writable(op)?;
+    if conflict_policy == TransferConflictPolicy::Fail {
+        for (_, target, _) in &files {
+            if !resume::has_pending(op, target) && op.exists(target).await? {
+                return Err(already_exists(target));
+            }
+        }
+    }
+    if report.wants_progress() {
+        report.set_totals(files.len(), Some(files.iter().map(|f| f.2).sum()));
+    }
 
-/// Write the full contents of a file, overwriting if it exists.
-pub async fn write_full(op: &Operator, path: &str, data: &[u8]) -> Result<()> {
-    let p = normalize_opendal_path(path);
-    op.write(&p, data.to_vec()).await?;
+    // Many small files are dominated by per-request latency, so several are
+    // in flight at once. Only this loop touches `report`; the uploads send
+    // their progress back over a channel, tagged with their index.
+    let cancel = report.cancel_token().clone();
+    let preserve_attributes = report.preserves_attributes();
+    let concurrency = report
+        .upload_concurrency()
+        .unwrap_or(DEFAULT_UPLOAD_CONCURRENCY)
+        .clamp(1, MAX_UPLOAD_CONCURRENCY);
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut advanced = HashMap::new();
+    let mut pending = files.into_iter().enumerate();
+    let mut running = FuturesUnordered::new();
+    let mut cancelled = false;
+    loop {
+        while !cancelled && running.len() < concurrency {
+            let Some((index, (local, target, size))) = pending.next() else {
+                break;
+            };
+            if pause::is_paused() {
+                report.mark_paused();
+                pause::wait_while_paused_unless(&cancel).await;
+            }
+            if cancel.check().is_err() {
+                cancelled = true;
+                break;
+            }
+            let source_path = local.to_string_lossy().into_owned();
+            report.start_item(&source_path);
+            let (progress_tx, cancel) = (progress_tx.clone(), cancel.clone());
+            running.push(async move {
+                let started = Instant::now();
+                let (target, conflict, result) =
+                    match resolve_upload_target(op, target.clone(), conflict_policy).await {
+                        Ok(Some((target, conflict))) => {
+                            let result = upload_local_file(
+                                op,
+                                &local,
+                                &target,
+                                preserve_attributes,
+                                &mut |bytes| {
+                                    let _ = progress_tx.send((index, bytes));
+                                    cancel.check()
+                                },
+                            )
+                            .await;
+                            (target, conflict, result)
+                        }
+                        Ok(None) => (target, Some(Conflict::Skipped), Ok(0)),
+                        Err(e) => (target, None, Err(e)),
+                    };
+                let cancelled = matches!(result, Err(CoreError::Cancelled));
+                let item = TransferItem {
+                    source_path,
+                    target_path: target,
+                    status: match (&result, conflict) {
+                        (Err(_), _) => ItemStatus::Failed,
+                        (Ok(_), Some(Conflict::Skipped)) => ItemStatus::Skipped,
+                        (Ok(_), _) => ItemStatus::Completed,
+                    },
+                    bytes: size,
+                    duration_ms: started.elapsed().as_millis() as u64,
+                    error: result.err().map(|e| e.to_string()),
+                    verified: None,
+                    conflict,
+                };
+                (index, item, cancelled)
+            });
+        }
+
+        let finished = tokio::select! {
+            Some((index, bytes)) = progress_rx.recv() => {
+                note_upload_progress(report, &mut advanced, index, bytes);
+                continue;
+            }
+            finished = running.next() => finished,
+        };
+        let Some((index, item, item_cancelled)) = finished else {
+            break;
+        };
+        // Everything the upload reported was sent before it finished.
+        while let Ok((index, bytes)) = progress_rx.try_recv() {
+            note_upload_progress(report, &mut advanced, index, bytes);
+        }
+        cancelled |= item_cancelled;
+        report.push_advanced(item, advanced.remove(&index).unwrap_or(0));
+    }
+    if cancelled {
+        return Err(CoreError::Cancelled);
+    }
     Ok(())
 }
 
-/// Create a directory at the given path.
-pub async fn create_directory(op: &Operator, path: &str) -> Result<()> {
-    let p = normalize_list_path(path);
-    op.create_dir(&p).await?;
-    Ok(())
+/// Where an upload meant for `target` goes under `policy`, and how an
+/// existing file there was dealt with; `None` skips the file. A target that
+/// an interrupted upload is still filling does not count as existing.
+async fn resolve_upload_target(
+    op: &Operator,
+    target: String,
+    policy: TransferConflictPolicy,
+) -> Result<Option<(String, Option<Conflict>)>> {
+    if resume::has_pending(op, &target) || !op.exists(&target).await? {
+        return Ok(Some((target, None)));
+    }
+    match policy {
+        TransferConflictPolicy::Fail => Err(already_exists(&target)),
+        TransferConflictPolicy::Overwrite => Ok(Some((target, Some(Conflict::Overwritten)))),
+        TransferConflictPolicy::Skip => Ok(None),
+        TransferConflictPolicy::Rename => {
+            let (dir, name) = split_parent(&target);
+            let renamed = unique_destination_path(op, &dir, &name, false).await?;
+            Ok(Some((renamed, Some(Conflict::Renamed))))
+        }
+    }
 }
 
-/// Delete a path (file or directory).
-pub async fn delete(op: &Operator, path: &str) -> Result<()> {
-    let p = normalize_opendal_path(path);
-    op.remove_all(&p).await?;
-    Ok(())
+fn already_exists(path: &str) -> CoreError {
+    opendal::Error::new(ErrorKind::AlreadyExists, format!("'{path}' already exists")).into()
 }
 
-/// Upload files from local paths to the target directory.
-pub async fn upload_files_from_paths(
-    op: &Operator,
-    paths: Vec<String>,
-    target_dir: String,
-) -> Result<()> {
-    for path_str in paths {
-        let path = Path::new(&path_str);
-        upload_path_recursive(op, path, &target_dir).await?;
+/// Parent folder (with its trailing `/`, empty at the root) and name of a
+/// file path.
+fn split_parent(path: &str) -> (String, String) {
+    match path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{dir}/"), name.to_string()),
+        None => (String::new(), path.to_string()),
     }
-    Ok(())
+}
+
+fn note_upload_progress(
+    report: &mut TransferReport,
+    advanced: &mut HashMap<usize, u64>,
+    index: usize,
+    bytes: u64,
+) {
+    report.advance_item(bytes);
+    *advanced.entry(index).or_default() += bytes;
 }
 
 fn join_target_dir(base: &str, name: &str) -> String {
@@ -172,7 +1536,7 @@ fn parent_dir_path(path: &str) -> Option<String> {
     }
 }
 
-async fn ensure_parent_dir(op: &Operator, path: &str) -> Result<()> {
+pub(crate) async fn ensure_parent_dir(op: &Operator, path: &str) -> Result<()> {
     if let Some(parent) = parent_dir_path(path) {
         let parent_dir = ensure_dir_path(&parent);
         op.create_dir(&parent_dir).await?;
@@ -180,24 +1544,188 @@ async fn ensure_parent_dir(op: &Operator, path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Bytes copied between progress updates when streaming across sources.
+const COPY_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Stream `from` into `to` and report whether the destination ends up with
+/// the same size as the source. `progress` is told about every chunk
+/// written. With `preserve_attributes`, the copy gets the modification time
+/// and permissions of `from` where `to_op` can keep them.
 async fn copy_file_across_operators(
     from_op: &Operator,
     to_op: &Operator,
     from: &str,
     to: &str,
-) -> Result<()> {
+    preserve_attributes: bool,
+    progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+) -> Result<bool> {
+    let started = Instant::now();
     let meta = from_op.stat(from).await?;
+    tuning::record_latency(from_op, started.elapsed());
     let size = meta.content_length();
+    let attrs = if preserve_attributes {
+        Some(FileAttrs::of(from_op, from, &meta).await)
+    } else {
+        None
+    };
+
+    let read_tuning = tuning::tuning_for(from_op);
     let mut reader = from_op
-        .reader(from)
+        .reader_with(from)
+        .chunk(read_tuning.chunk_size)
+        .concurrent(read_tuning.concurrency)
         .await?
         .into_futures_async_read(0..size)
         .await?;
-    let mut writer = to_op.writer(to).await?.into_futures_async_write();
+    // Keep the source's type; it may know better than the extension.
+    let mut writer = tuned_writer(to_op, to, attrs.as_ref(), meta.content_type()).await?;
+
+    let started = Instant::now();
+    let mut buf = vec![0; COPY_BUFFER_SIZE];
+    loop {
+        let result = match reader.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => writer
+                .write(buf[..n].to_vec())
+                .await
+                .map_err(CoreError::from)
+                .and_then(|()| progress(n as u64)),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            discard_partial(to_op, to, &mut writer).await;
+            return Err(e);
+        }
+    }
+    writer.close().await?;
+    tuning::record_throughput(to_op, size, started.elapsed());
+    if let Some(attrs) = attrs {
+        file_attrs::apply(to_op, to, attrs).await?;
+    }
+
+    let written = to_op.stat(to).await?.content_length();
+    Ok(written == size)
+}
+
+/// Abort a write cut short, or remove what it wrote on backends that
+/// cannot abort (local folders without a temp dir).
+async fn discard_partial(op: &Operator, path: &str, writer: &mut opendal::Writer) {
+    if writer.abort().await.is_err() {
+        let _ = op.delete(path).await;
+    }
+}
+
+/// Open a writer using the chunk size and concurrency tuned for this backend.
+/// `attrs` are stored with the file on backends that keep user metadata, and
+/// `content_type` (or the type guessed from `path`) on those that keep one.
+async fn tuned_writer(
+    op: &Operator,
+    path: &str,
+    attrs: Option<&FileAttrs>,
+    content_type: Option<&str>,
+) -> Result<opendal::Writer> {
+    let tuning = tuning::tuning_for(op);
+    let mut writer = op
+        .writer_with(path)
+        .chunk(tuning.chunk_size)
+        .concurrent(tuning.concurrency);
+    if let Some(content_type) = content_type::for_write(op, path, content_type) {
+        writer = writer.content_type(content_type);
+    }
+    if let Some(attrs) = attrs.filter(|_| file_attrs::stores_in_user_metadata(op)) {
+        writer = writer.user_metadata(attrs.user_metadata());
+    }
+    Ok(writer.await?)
+}
+
+/// Stream `reader` into `path` without buffering it whole. Data is read in
+/// chunks of the backend's tuned size and handed to a tuned writer, which
+/// uploads them as multipart parts (or streams/appends on backends that
+/// support it), so memory stays at a few chunks however large the file is.
+/// An upload that fails midway is aborted. Returns the bytes written.
+pub async fn write_chunks<R>(op: &Operator, path: &str, reader: R) -> Result<u64>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    ensure_writable(op)?;
+    write_stream(op, &normalize_opendal_path(path), reader, None, &mut |_| {
+        Ok(())
+    })
+    .await
+}
 
-    futures::io::copy(&mut reader, &mut writer).await?;
+async fn write_stream<R>(
+    op: &Operator,
+    path: &str,
+    mut reader: R,
+    attrs: Option<&FileAttrs>,
+    progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+) -> Result<u64>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let chunk_size = tuning::tuning_for(op).chunk_size as u64;
+    let started = Instant::now();
+    let mut writer = tuned_writer(op, path, attrs, None).await?;
+    let mut written = 0;
+    loop {
+        let mut chunk = Vec::new();
+        let result = match (&mut reader).take(chunk_size).read_to_end(&mut chunk).await {
+            Ok(0) => break,
+            Ok(n) => {
+                written += n as u64;
+                writer
+                    .write(chunk)
+                    .await
+                    .map_err(CoreError::from)
+                    .and_then(|()| progress(n as u64))
+            }
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            discard_partial(op, path, &mut writer).await;
+            return Err(e);
+        }
+    }
     writer.close().await?;
-    Ok(())
+    tuning::record_throughput(op, written, started.elapsed());
+    Ok(written)
+}
+
+/// Upload the local file `src` to `path`, streaming it from disk.
+/// `progress` is told about every chunk written. Large files go through
+/// [`resume`] so an interrupted upload can continue later; those keep their
+/// attributes on Local targets only.
+async fn upload_local_file(
+    op: &Operator,
+    src: &Path,
+    path: &str,
+    preserve_attributes: bool,
+    progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+) -> Result<u64> {
+    let meta = fs::metadata(src).await.ok();
+    let size = meta.as_ref().map_or(0, |meta| meta.len());
+    let attrs = meta
+        .as_ref()
+        .filter(|_| preserve_attributes)
+        .map(FileAttrs::from_local);
+    let written = if resume::is_resumable(op, size) {
+        resume::upload_file(op, src, path, progress).await?
+    } else {
+        let file = fs::File::open(src).await.map_err(|e| {
+            opendal::Error::new(
+                ErrorKind::Unexpected,
+                format!("Failed to read local file {}: {}", src.display(), e),
+            )
+        })?;
+        write_stream(op, path, file, attrs.as_ref(), progress).await?
+    };
+    if let Some(attrs) = attrs {
+        file_attrs::apply(op, path, attrs).await?;
+    }
+    Ok(written)
 }
 
 fn split_file_name(name: &str) -> (String, String) {
@@ -215,7 +1743,7 @@ fn split_file_name(name: &str) -> (String, String) {
     }
 }
 
-async fn unique_destination_path(
+pub(crate) async fn unique_destination_path(
     op: &Operator,
     target_dir: &str,
     name: &str,
@@ -269,6 +1797,9 @@ async fn unique_destination_path(
     .into())
 }
 
+/// Returns the verification result: `Some(matched)` after a cross-source
+/// copy, `None` when the backend copied or renamed in place.
+#[allow(clippy::too_many_arguments)]
 async fn transfer_file(
     from_op: &Operator,
     to_op: &Operator,
@@ -276,31 +1807,131 @@ async fn transfer_file(
     to_path: &str,
     operation: TransferOperation,
     same_source: bool,
-) -> Result<()> {
+    preserve_attributes: bool,
+    progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+) -> Result<Option<bool>> {
     ensure_parent_dir(to_op, to_path).await?;
 
     match operation {
         TransferOperation::Copy => {
             if same_source {
                 from_op.copy(from_path, to_path).await?;
+                Ok(None)
             } else {
-                copy_file_across_operators(from_op, to_op, from_path, to_path).await?;
+                let verified = copy_file_across_operators(
+                    from_op,
+                    to_op,
+                    from_path,
+                    to_path,
+                    preserve_attributes,
+                    progress,
+                )
+                .await?;
+                Ok(Some(verified))
             }
         }
         TransferOperation::Move => {
             if same_source {
                 from_op.rename(from_path, to_path).await?;
+                Ok(None)
             } else {
-                copy_file_across_operators(from_op, to_op, from_path, to_path).await?;
-                from_op.remove_all(from_path).await?;
+                let verified = copy_file_across_operators(
+                    from_op,
+                    to_op,
+                    from_path,
+                    to_path,
+                    preserve_attributes,
+                    progress,
+                )
+                .await?;
+                // Keep the original when the copy does not check out.
+                if verified {
+                    from_op.remove_all(from_path).await?;
+                }
+                Ok(Some(verified))
             }
         }
     }
+}
 
-    Ok(())
+/// Transfer one file and record the outcome in `report`. Per-file errors do
+/// not abort the job; returns whether the file made it, or
+/// [`CoreError::Cancelled`] once the report's token is cancelled.
+#[allow(clippy::too_many_arguments)]
+async fn transfer_file_recorded(
+    report: &mut TransferReport,
+    from_op: &Operator,
+    to_op: &Operator,
+    from_path: &str,
+    to_path: &str,
+    size: u64,
+    operation: TransferOperation,
+    same_source: bool,
+) -> Result<bool> {
+    if pause::is_paused() {
+        report.mark_paused();
+        pause::wait_while_paused_unless(report.cancel_token()).await;
+    }
+    report.cancel_token().check()?;
+    report.start_item(from_path);
+    let started = Instant::now();
+    let preserve_attributes = report.preserves_attributes();
+    let result = transfer_file(
+        from_op,
+        to_op,
+        from_path,
+        to_path,
+        operation,
+        same_source,
+        preserve_attributes,
+        &mut |bytes| {
+            report.advance_item(bytes);
+            report.cancel_token().check()
+        },
+    )
+    .await;
+    let cancelled = matches!(result, Err(CoreError::Cancelled));
+    let (status, error, verified) = match result {
+        Ok(Some(false)) => (
+            ItemStatus::Failed,
+            Some("destination size does not match the source".to_string()),
+            Some(false),
+        ),
+        Ok(verified) => (ItemStatus::Completed, None, verified),
+        Err(e) => (ItemStatus::Failed, Some(e.to_string()), None),
+    };
+    report.push(TransferItem {
+        source_path: from_path.to_string(),
+        target_path: to_path.to_string(),
+        status,
+        bytes: size,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+        verified,
+        conflict: None,
+    });
+    if cancelled {
+        return Err(CoreError::Cancelled);
+    }
+    Ok(status == ItemStatus::Completed)
+}
+
+fn record_skipped(report: &mut TransferReport, from_path: &str, to_path: &str, size: u64) {
+    report.push(TransferItem {
+        source_path: from_path.to_string(),
+        target_path: to_path.to_string(),
+        status: ItemStatus::Skipped,
+        bytes: size,
+        duration_ms: 0,
+        error: None,
+        verified: None,
+        conflict: Some(Conflict::Skipped),
+    });
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn transfer_dir_recursive(
+    report: &mut TransferReport,
     from_op: &Operator,
     to_op: &Operator,
     from_dir: &str,
@@ -312,13 +1943,23 @@ async fn transfer_dir_recursive(
     let to_root = ensure_dir_path(to_dir);
     to_op.create_dir(&to_root).await?;
 
+    let filter = report.filter();
+    let ignored = source_git_ignored(from_op, &from_root, filter).await;
+    let mut all_transferred = true;
     let mut stack = vec![(from_root.clone(), to_root)];
     while let Some((from_base, to_base)) = stack.pop() {
         let mut lister = from_op.lister(&from_base).await?;
         while let Some(obj) = lister.try_next().await? {
             let child_path = obj.path().to_string();
-            let meta = from_op.stat(&child_path).await?;
+            // Some backends (fs) list the folder itself first.
+            if child_path == from_base {
+                continue;
+            }
             let name = extract_filename(&child_path);
+            if filter.skips(&name) || ignored.contains(relative_to(&from_root, &child_path)) {
+                continue;
+            }
+            let meta = from_op.stat(&child_path).await?;
 
             if meta.is_dir() {
                 let child_src_dir = ensure_dir_path(&child_path);
@@ -327,11 +1968,13 @@ async fn transfer_dir_recursive(
                 stack.push((child_src_dir, child_dst_dir));
             } else {
                 let child_dst_file = join_target_dir(&to_base, &name);
-                transfer_file(
+                all_transferred &= transfer_file_recorded(
+                    report,
                     from_op,
                     to_op,
                     &child_path,
                     &child_dst_file,
+                    meta.content_length(),
                     TransferOperation::Copy,
                     same_source,
                 )
@@ -340,7 +1983,8 @@ async fn transfer_dir_recursive(
         }
     }
 
-    if operation == TransferOperation::Move {
+    // A partially copied folder keeps its source so failed files can be retried.
+    if operation == TransferOperation::Move && all_transferred {
         from_op.remove_all(&from_root).await?;
     }
 
@@ -349,11 +1993,17 @@ async fn transfer_dir_recursive(
 
 /// Copy or move a set of file/folder paths into `target_dir`.
 ///
-/// Conflict handling is controlled by `conflict_policy`.
+/// Conflict handling is controlled by `conflict_policy`. Every file is
+/// recorded in `report`; a file that fails is logged there and the transfer
+/// carries on with the rest. Errors that stop the whole job (read-only
+/// destination, conflicts under [`TransferConflictPolicy::Fail`], copying a
+/// folder into itself) are returned.
 ///
 /// Note: copying an entry onto itself (same source + same path) is treated as a duplicate copy
 /// and the destination name is auto-deduplicated to avoid clobbering the source.
+#[allow(clippy::too_many_arguments)]
 pub async fn transfer_entries(
+    report: &mut TransferReport,
     from_op: &Operator,
     to_op: &Operator,
     paths: Vec<String>,
@@ -362,6 +2012,11 @@ pub async fn transfer_entries(
     same_source: bool,
     conflict_policy: TransferConflictPolicy,
 ) -> Result<()> {
+    ensure_writable(to_op)?;
+    if operation == TransferOperation::Move {
+        ensure_writable(from_op)?;
+    }
+    ensure_native_transfer(from_op, operation, same_source)?;
     if conflict_policy == TransferConflictPolicy::Fail {
         for from_path in &paths {
             let meta = from_op.stat(from_path).await?;
@@ -426,6 +2081,13 @@ pub async fn transfer_entries(
         }
     }
 
+    if report.wants_progress() {
+        report.start_preparing();
+        if let Some((items, bytes)) = count_transfer(from_op, &paths, report.filter()).await {
+            report.set_totals(items, Some(bytes));
+        }
+    }
+
     for from_path in paths {
         let meta = from_op.stat(&from_path).await?;
         if meta.is_dir() {
@@ -448,7 +2110,7 @@ pub async fn transfer_entries(
                 }
             }
 
-            let dest_dir = if operation == TransferOperation::Copy
+            let mut dest_dir = if operation == TransferOperation::Copy
                 && same_source
                 && normalized_src == normalized_dest
             {
@@ -457,6 +2119,7 @@ pub async fn transfer_entries(
                 base_dest_dir
             };
 
+            let mut conflict = None;
             if to_op.exists(&dest_dir).await? {
                 match conflict_policy {
                     TransferConflictPolicy::Fail => {
@@ -468,14 +2131,23 @@ pub async fn transfer_entries(
                     }
                     TransferConflictPolicy::Overwrite => {
                         to_op.remove_all(&dest_dir).await?;
+                        conflict = Some(Conflict::Overwritten);
                     }
                     TransferConflictPolicy::Skip => {
+                        record_skipped(report, &from_path, &dest_dir, 0);
                         continue;
                     }
+                    TransferConflictPolicy::Rename => {
+                        dest_dir =
+                            unique_destination_path(to_op, target_dir, &dir_name, true).await?;
+                        conflict = Some(Conflict::Renamed);
+                    }
                 }
             }
 
-            transfer_dir_recursive(
+            let start = report.items.len();
+            let result = transfer_dir_recursive(
+                report,
                 from_op,
                 to_op,
                 &ensure_dir_path(&from_path),
@@ -483,11 +2155,15 @@ pub async fn transfer_entries(
                 operation,
                 same_source,
             )
-            .await?;
+            .await;
+            if let Some(conflict) = conflict {
+                report.mark_conflict_since(start, conflict);
+            }
+            result?;
         } else {
             let file_name = extract_filename(&from_path);
             let base_dest_file = join_target_dir(target_dir, &file_name);
-            let dest_file = if operation == TransferOperation::Copy
+            let mut dest_file = if operation == TransferOperation::Copy
                 && same_source
                 && from_path == base_dest_file
             {
@@ -500,6 +2176,7 @@ pub async fn transfer_entries(
                 continue;
             }
 
+            let mut conflict = None;
             if to_op.exists(&dest_file).await? {
                 match conflict_policy {
                     TransferConflictPolicy::Fail => {
@@ -511,93 +2188,494 @@ pub async fn transfer_entries(
                     }
                     TransferConflictPolicy::Overwrite => {
                         to_op.remove_all(&dest_file).await?;
+                        conflict = Some(Conflict::Overwritten);
                     }
                     TransferConflictPolicy::Skip => {
+                        record_skipped(report, &from_path, &dest_file, meta.content_length());
                         continue;
                     }
+                    TransferConflictPolicy::Rename => {
+                        dest_file =
+                            unique_destination_path(to_op, target_dir, &file_name, false).await?;
+                        conflict = Some(Conflict::Renamed);
+                    }
                 }
             }
-            transfer_file(
+            let start = report.items.len();
+            let result = transfer_file_recorded(
+                report,
                 from_op,
                 to_op,
                 &from_path,
                 &dest_file,
+                meta.content_length(),
                 operation,
                 same_source,
             )
-            .await?;
+            .await;
+            if let Some(conflict) = conflict {
+                report.mark_conflict_since(start, conflict);
+            }
+            result?;
+        }
+    }
+
+    Ok(())
+}
+
+/// What the `.gitignore` rules exclude below `dir` of a Local source, when
+/// they are honored; nothing otherwise.
+async fn source_git_ignored(op: &Operator, dir: &str, filter: TransferFilter) -> git::GitIgnored {
+    if !filter.honor_gitignore {
+        return git::GitIgnored::default();
+    }
+    match local_fs_path(op, dir) {
+        Ok(local) => git::GitIgnored::load(&local).await,
+        Err(_) => git::GitIgnored::default(),
+    }
+}
+
+/// `path` relative to the folder `dir` it was listed from.
+fn relative_to<'a>(dir: &str, path: &'a str) -> &'a str {
+    let path = path.trim_start_matches('/');
+    path.strip_prefix(dir.trim_start_matches('/'))
+        .unwrap_or(path)
+}
+
+/// Files and bytes under `paths`, skipping what the transfer skips, for
+/// progress totals. `None` when a path can't be read; the transfer itself
+/// reports that error.
+pub(crate) async fn count_transfer(
+    op: &Operator,
+    paths: &[String],
+    filter: TransferFilter,
+) -> Option<(usize, u64)> {
+    let (mut items, mut bytes) = (0, 0);
+    for path in paths {
+        let meta = op.stat(path).await.ok()?;
+        if !meta.is_dir() {
+            items += 1;
+            bytes += meta.content_length();
+            continue;
+        }
+        let dir = ensure_dir_path(path);
+        let ignored = source_git_ignored(op, &dir, filter).await;
+        let mut lister = op.lister_with(&dir).recursive(true).await.ok()?;
+        while let Some(entry) = lister.try_next().await.ok()? {
+            if entry.metadata().is_dir()
+                || entry.path().split('/').any(|name| filter.skips(name))
+                || ignored.contains(relative_to(&dir, entry.path()))
+            {
+                continue;
+            }
+            items += 1;
+            // Some backends list without sizes.
+            bytes += match entry.metadata().content_length() {
+                0 => op.stat(entry.path()).await.ok()?.content_length(),
+                len => len,
+            };
+        }
+    }
+    Some((items, bytes))
+}
+
+/// Re-attempt the items that failed in `previous`, recording them in `report`
+/// (see [`TransferReport::retry`]). Destinations are overwritten since a failed
+/// item may have left a partial file behind.
+pub async fn retry_failed(
+    report: &mut TransferReport,
+    previous: &TransferReport,
+    from_op: &Operator,
+    to_op: &Operator,
+    same_source: bool,
+) -> Result<()> {
+    let operation = match previous.operation.as_str() {
+        "copy" => TransferOperation::Copy,
+        "move" => TransferOperation::Move,
+        other => {
+            return Err(CoreError::Config(format!(
+                "invalid transfer operation: {other}"
+            )))
+        }
+    };
+    ensure_writable(to_op)?;
+    if operation == TransferOperation::Move {
+        ensure_writable(from_op)?;
+    }
+    ensure_native_transfer(from_op, operation, same_source)?;
+
+    let failed: Vec<&TransferItem> = previous.failed_items().collect();
+    report.set_totals(
+        failed.len(),
+        Some(failed.iter().map(|item| item.bytes).sum()),
+    );
+
+    for item in failed {
+        let size = match from_op.stat(&item.source_path).await {
+            Ok(meta) => meta.content_length(),
+            Err(e) => {
+                report.push(TransferItem {
+                    error: Some(CoreError::from(e).to_string()),
+                    duration_ms: 0,
+                    verified: None,
+                    ..item.clone()
+                });
+                continue;
+            }
+        };
+        if to_op.exists(&item.target_path).await? {
+            to_op.remove_all(&item.target_path).await?;
+        }
+        transfer_file_recorded(
+            report,
+            from_op,
+            to_op,
+            &item.source_path,
+            &item.target_path,
+            size,
+            operation,
+            same_source,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Copy `src_path` from one source to `dst_path` on another (local to S3, S3
+/// to WebDAV, ...), recording every file in `report`. Files are streamed
+/// chunk by chunk rather than read into memory; folders are copied
+/// recursively into `dst_path`. Existing destinations are overwritten.
+pub async fn transfer(
+    report: &mut TransferReport,
+    src_op: &Operator,
+    src_path: &str,
+    dst_op: &Operator,
+    dst_path: &str,
+) -> Result<()> {
+    ensure_writable(dst_op)?;
+    let src_path = normalize_opendal_path(src_path);
+    let dst_path = normalize_opendal_path(dst_path);
+    let meta = src_op.stat(&src_path).await?;
+
+    if report.wants_progress() {
+        report.start_preparing();
+        if let Some((items, bytes)) =
+            count_transfer(src_op, std::slice::from_ref(&src_path), report.filter()).await
+        {
+            report.set_totals(items, Some(bytes));
+        }
+    }
+
+    if meta.is_dir() {
+        transfer_dir_recursive(
+            report,
+            src_op,
+            dst_op,
+            &src_path,
+            &dst_path,
+            TransferOperation::Copy,
+            false,
+        )
+        .await
+    } else {
+        if dst_path.is_empty() || dst_path.ends_with('/') {
+            return Err(CoreError::Config(format!(
+                "destination of file '{src_path}' must be a file path"
+            )));
+        }
+        transfer_file_recorded(
+            report,
+            src_op,
+            dst_op,
+            &src_path,
+            &dst_path,
+            meta.content_length(),
+            TransferOperation::Copy,
+            false,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Download `remote_path` (a file or a whole folder) from `op` into the local
+/// folder `local_dir`, keeping its name: `docs/` ends up in `local_dir/docs/`
+/// and the source root's contents directly in `local_dir`. Files are
+/// streamed to disk and recorded in `report`; system files inside folders are
+/// skipped. Under [`TransferConflictPolicy::Fail`] nothing is written if any
+/// local file already exists.
+pub async fn download(
+    report: &mut TransferReport,
+    op: &Operator,
+    remote_path: &str,
+    local_dir: &Path,
+    conflict_policy: TransferConflictPolicy,
+) -> Result<()> {
+    let local = Operator::new(opendal::services::Fs::default().root(&local_dir.to_string_lossy()))?
+        .finish();
+    let remote = normalize_opendal_path(remote_path);
+    let meta = match remote.as_str() {
+        "" => None,
+        // Object stores only know folders by their trailing slash.
+        path => match op.stat(path).await {
+            Err(e) if e.kind() == ErrorKind::NotFound && !path.ends_with('/') => {
+                Some(op.stat(&format!("{path}/")).await?)
+            }
+            result => Some(result?),
+        },
+    };
+
+    // (remote path, local path, size) of every file, plus folders to create
+    // so empty ones come along too.
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    match meta {
+        Some(meta) if !meta.is_dir() => {
+            files.push((
+                remote.clone(),
+                extract_filename(&remote),
+                meta.content_length(),
+            ));
+        }
+        _ => {
+            let dir = normalize_list_path(&remote);
+            let base = match extract_filename(&remote) {
+                name if name.is_empty() => String::new(),
+                name => format!("{name}/"),
+            };
+            if !base.is_empty() {
+                dirs.push(base.clone());
+            }
+            let mut lister = op
+                .lister_with(&ensure_dir_path(&dir))
+                .recursive(true)
+                .await?;
+            while let Some(entry) = lister.try_next().await? {
+                let path = entry.path().trim_start_matches('/');
+                let Some(rel) = path.strip_prefix(dir.as_str()) else {
+                    continue;
+                };
+                if rel.is_empty() || rel.split('/').any(|name| report.filter().skips(name)) {
+                    continue;
+                }
+                // Object keys may contain `..`; never let them escape `local_dir`.
+                if rel.split('/').any(|segment| segment == "..") {
+                    report.push(TransferItem {
+                        source_path: path.to_string(),
+                        target_path: String::new(),
+                        status: ItemStatus::Failed,
+                        bytes: 0,
+                        duration_ms: 0,
+                        error: Some("path leaves the download folder".to_string()),
+                        verified: None,
+                        conflict: None,
+                    });
+                    continue;
+                }
+                if entry.metadata().is_dir() {
+                    dirs.push(format!("{base}{rel}"));
+                } else {
+                    let size = entry.metadata().content_length();
+                    files.push((path.to_string(), format!("{base}{rel}"), size));
+                }
+            }
         }
     }
 
+    if conflict_policy == TransferConflictPolicy::Fail {
+        for (_, target, _) in &files {
+            if local.exists(target).await? {
+                return Err(opendal::Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("'{}' already exists", local_dir.join(target).display()),
+                )
+                .into());
+            }
+        }
+    }
+    if report.wants_progress() {
+        report.set_totals(files.len(), Some(files.iter().map(|f| f.2).sum()));
+    }
+    for dir in &dirs {
+        local.create_dir(dir).await?;
+    }
+    for (from, mut to, size) in files {
+        // Under `Fail` nothing existed a moment ago.
+        let mut conflict = None;
+        if conflict_policy != TransferConflictPolicy::Fail && local.exists(&to).await? {
+            match conflict_policy {
+                TransferConflictPolicy::Skip => {
+                    record_skipped(report, &from, &to, size);
+                    continue;
+                }
+                TransferConflictPolicy::Rename => {
+                    let (dir, name) = split_parent(&to);
+                    to = unique_destination_path(&local, &dir, &name, false).await?;
+                    conflict = Some(Conflict::Renamed);
+                }
+                _ => conflict = Some(Conflict::Overwritten),
+            }
+        }
+        let start = report.items.len();
+        let result = transfer_file_recorded(
+            report,
+            op,
+            &local,
+            &from,
+            &to,
+            size,
+            TransferOperation::Copy,
+            false,
+        )
+        .await;
+        if let Some(conflict) = conflict {
+            report.mark_conflict_since(start, conflict);
+        }
+        result?;
+    }
     Ok(())
 }
 
-async fn upload_path_recursive(op: &Operator, src: &Path, target_dir: &str) -> Result<()> {
+/// `path` below the local folder `root` as a `/`-separated relative path,
+/// folders with a trailing `/`.
+fn local_relative(root: &Path, path: &Path, is_dir: bool) -> String {
+    let mut rel = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if is_dir {
+        rel.push('/');
+    }
+    rel
+}
+
+/// Add (local path, target path, size) of the files at `src` to `files`.
+/// Files to upload for `src`, with their target paths and sizes. Folders are
+/// walked, entering each folder once even when symbolic links lead back to
+/// it; with `keep_links`, links inside them are collected into `links` (with
+/// their target path and where they point) instead of being followed.
+async fn collect_upload_files(
+    src: &Path,
+    target_dir: &str,
+    keep_links: bool,
+    filter: TransferFilter,
+    files: &mut Vec<(PathBuf, String, u64)>,
+    links: &mut Vec<(PathBuf, String, PathBuf)>,
+) -> Result<()> {
     let meta = fs::metadata(src).await.map_err(|e| {
         opendal::Error::new(
             ErrorKind::Unexpected,
-            &format!("Failed to stat local path {}: {}", src.display(), e),
+            format!("Failed to stat local path {}: {}", src.display(), e),
         )
     })?;
 
     if meta.is_file() {
         let filename = src
-            .file_name()
-            .ok_or_else(|| {
-                opendal::Error::new(ErrorKind::Unexpected, "Invalid file path (no filename)")
-            })?
-            .to_string_lossy();
-
-        let target_path = join_target_dir(target_dir, &filename);
-
-        let data = fs::read(src).await.map_err(|e| {
-            opendal::Error::new(
-                ErrorKind::Unexpected,
-                &format!("Failed to read local file {}: {}", src.display(), e),
-            )
-        })?;
+            .file_name()
+            .ok_or_else(|| {
+                opendal::Error::new(ErrorKind::Unexpected, "Invalid file path (no filename)")
+            })?
+            .to_string_lossy();
 
-        op.write(&target_path, data).await?;
+        files.push((
+            src.to_path_buf(),
+            join_target_dir(target_dir, &filename),
+            meta.len(),
+        ));
     } else if meta.is_dir() {
-        let mut stack: Vec<(std::path::PathBuf, String)> =
-            vec![(src.to_path_buf(), target_dir.to_string())];
+        let ignored = if filter.honor_gitignore {
+            git::GitIgnored::load(src).await
+        } else {
+            git::GitIgnored::default()
+        };
+        let canonical_src = fs::canonicalize(src)
+            .await
+            .unwrap_or_else(|_| src.to_path_buf());
+        let mut visited = HashSet::from([canonical_src.clone()]);
+        // Each folder with its resolved path, to spot folders seen before.
+        let mut stack: Vec<(PathBuf, PathBuf, String)> =
+            vec![(src.to_path_buf(), canonical_src, target_dir.to_string())];
 
-        while let Some((dir_path, dir_target)) = stack.pop() {
+        while let Some((dir_path, canonical_dir, dir_target)) = stack.pop() {
             let mut entries = fs::read_dir(&dir_path).await.map_err(|e| {
                 opendal::Error::new(
                     ErrorKind::Unexpected,
-                    &format!("Failed to read directory {}: {}", dir_path.display(), e),
+                    format!("Failed to read directory {}: {}", dir_path.display(), e),
                 )
             })?;
 
             while let Some(entry) = entries.next_entry().await.map_err(|e| {
                 opendal::Error::new(
                     ErrorKind::Unexpected,
-                    &format!("Failed to iterate directory {}: {}", dir_path.display(), e),
+                    format!("Failed to iterate directory {}: {}", dir_path.display(), e),
                 )
             })? {
                 let child_path = entry.path();
-                let child_meta = fs::metadata(&child_path).await.map_err(|e| {
-                    opendal::Error::new(
-                        ErrorKind::Unexpected,
-                        &format!("Failed to stat local path {}: {}", child_path.display(), e),
-                    )
-                })?;
+                if filter.skips(&entry.file_name().to_string_lossy()) {
+                    continue;
+                }
+                let is_link = entry.file_type().await.is_ok_and(|t| t.is_symlink());
+                if is_link && keep_links {
+                    if ignored.is_empty()
+                        || !ignored.contains(&local_relative(src, &child_path, false))
+                    {
+                        let filename = entry.file_name().to_string_lossy().to_string();
+                        let link_target = fs::read_link(&child_path).await?;
+                        links.push((
+                            child_path,
+                            join_target_dir(&dir_target, &filename),
+                            link_target,
+                        ));
+                    }
+                    continue;
+                }
+                let child_meta = match fs::metadata(&child_path).await {
+                    Ok(meta) => meta,
+                    // A broken link has nothing to upload.
+                    Err(_) if is_link => continue,
+                    Err(e) => {
+                        return Err(opendal::Error::new(
+                            ErrorKind::Unexpected,
+                            format!("Failed to stat local path {}: {}", child_path.display(), e),
+                        )
+                        .into())
+                    }
+                };
+
+                // Files picked explicitly are uploaded as-is; inside folders,
+                // skip placeholders so a recursive upload does not hydrate
+                // an entire cloud-synced tree.
+                if child_meta.is_file() && placeholder::is_online_only(&child_path) {
+                    continue;
+                }
+                if !ignored.is_empty()
+                    && ignored.contains(&local_relative(src, &child_path, child_meta.is_dir()))
+                {
+                    continue;
+                }
 
                 if child_meta.is_file() {
                     let filename = entry.file_name().to_string_lossy().to_string();
                     let target_path = join_target_dir(&dir_target, &filename);
-                    let data = fs::read(&child_path).await.map_err(|e| {
-                        opendal::Error::new(
-                            ErrorKind::Unexpected,
-                            &format!("Failed to read local file {}: {}", child_path.display(), e),
-                        )
-                    })?;
-                    op.write(&target_path, data).await?;
+                    files.push((child_path, target_path, child_meta.len()));
                 } else if child_meta.is_dir() {
+                    let canonical = if is_link {
+                        fs::canonicalize(&child_path)
+                            .await
+                            .unwrap_or_else(|_| child_path.clone())
+                    } else {
+                        canonical_dir.join(entry.file_name())
+                    };
+                    if !visited.insert(canonical.clone()) {
+                        continue;
+                    }
                     let dirname = entry.file_name().to_string_lossy().to_string();
                     let new_target = join_target_dir(&dir_target, &dirname);
-                    stack.push((child_path, new_target));
+                    stack.push((child_path, canonical, new_target));
                 }
             }
         }
@@ -614,6 +2692,10 @@ pub struct FileVersion {
     pub size_bytes: Option<u64>,
     pub modified_at: Option<String>,
     pub etag: Option<String>,
+    /// Whether this is the version reads get by default, when the backend
+    /// says.
+    #[serde(default)]
+    pub is_current: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -645,6 +2727,10 @@ pub async fn list_file_versions(
     };
 
     while let Some(entry) = lister.try_next().await? {
+        // The listing is by prefix: `a.txt` also lists `a.txt.bak`.
+        if entry.path() != normalized {
+            continue;
+        }
         let meta = entry.metadata();
         if let Some(version) = meta.version() {
             let modified_at = meta.last_modified().map(|dt| dt.to_string());
@@ -654,6 +2740,7 @@ pub async fn list_file_versions(
                 size_bytes: Some(meta.content_length()),
                 modified_at,
                 etag,
+                is_current: meta.is_current(),
             });
         }
     }
@@ -681,7 +2768,50 @@ pub async fn read_file_version(op: &Operator, path: &str, version: &str) -> Resu
     Ok(data.to_vec())
 }
 
+/// Make `version` of `path` the current content again by writing it back
+/// as a new version. The versions in between are kept, so a restore can be
+/// undone the same way. Returns the bytes written.
+pub async fn restore_file_version(op: &Operator, path: &str, version: &str) -> Result<u64> {
+    ensure_writable(op)?;
+    let normalized = normalize_opendal_path(path);
+    let size = op
+        .stat_with(&normalized)
+        .version(version)
+        .await?
+        .content_length();
+    let read_tuning = tuning::tuning_for(op);
+    let mut reader = op
+        .reader_with(&normalized)
+        .version(version)
+        .chunk(read_tuning.chunk_size)
+        .concurrent(read_tuning.concurrency)
+        .await?
+        .into_futures_async_read(0..size)
+        .await?;
+    let mut writer = tuned_writer(op, &normalized, None, None).await?;
+
+    let mut buf = vec![0; COPY_BUFFER_SIZE];
+    loop {
+        let result = match reader.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => writer
+                .write(buf[..n].to_vec())
+                .await
+                .map_err(CoreError::from),
+            Err(e) => Err(e.into()),
+        };
+        // Aborting leaves the current version as it was.
+        if let Err(e) = result {
+            let _ = writer.abort().await;
+            return Err(e);
+        }
+    }
+    writer.close().await?;
+    Ok(size)
+}
+
 pub async fn delete_file_version(op: &Operator, path: &str, version: &str) -> Result<()> {
+    ensure_writable(op)?;
     let normalized = normalize_opendal_path(path);
     op.delete_with(&normalized).version(version).await?;
     Ok(())
@@ -722,12 +2852,61 @@ mod tests {
         let path = "test.txt";
         let content = b"hello world";
 
-        write_full(&op, path, content).await.unwrap();
+        write_full(&op, path, content, &WriteOptions::default())
+            .await
+            .unwrap();
 
         let read_content = read_full(&op, path).await.unwrap();
         assert_eq!(read_content, content);
     }
 
+    #[tokio::test]
+    async fn test_conditional_writes() {
+        let root = std::env::temp_dir().join(format!("infimount-if-match-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let op = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+        let create = WriteOptions {
+            if_not_exists: true,
+            ..Default::default()
+        };
+        write_full(&op, "notes.md", b"draft", &create)
+            .await
+            .unwrap();
+        let err = write_full(&op, "notes.md", b"other", &create)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), crate::models::ErrorCode::Conflict);
+
+        // Local files have no etag; their modification time stands in.
+        let opened = stat_entry(&op, "notes.md").await.unwrap();
+        let version = opened.etag.or(opened.modified_at).unwrap();
+        let save = WriteOptions {
+            if_match: Some(version),
+            ..Default::default()
+        };
+        write_full(&op, "notes.md", b"mine", &save).await.unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(root.join("notes.md"))
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+            .unwrap();
+        let err = write_full(&op, "notes.md", b"stale", &save)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), crate::models::ErrorCode::Conflict);
+        assert_eq!(read_full(&op, "notes.md").await.unwrap(), b"mine");
+
+        op.delete("notes.md").await.unwrap();
+        let err = write_full(&op, "notes.md", b"stale", &save)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), crate::models::ErrorCode::Conflict);
+        let _ = std::fs::remove_dir_all(root);
+    }
+
     #[tokio::test]
     async fn test_delete_file() {
         let op = create_test_operator().await;
@@ -741,10 +2920,1152 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_create_directory() {
+    async fn test_read_chunks_streams_in_order() {
+        let op = create_test_operator().await;
+        let data: Vec<u8> = (0..10_000u32).map(|n| n as u8).collect();
+        op.write("big.bin", data.clone()).await.unwrap();
+
+        let mut chunks = Vec::new();
+        let total = read_chunks(&op, "/big.bin", 4096, |chunk| {
+            chunks.push(chunk.to_vec());
+            true
+        })
+        .await
+        .unwrap();
+        assert_eq!(total, 10_000);
+        assert_eq!(
+            chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+            [4096, 4096, 1808]
+        );
+        assert_eq!(chunks.concat(), data);
+
+        let mut seen = 0;
+        let total = read_chunks(&op, "big.bin", 4096, |_| {
+            seen += 1;
+            false
+        })
+        .await
+        .unwrap();
+        assert_eq!((seen, total), (1, 4096));
+    }
+
+    #[tokio::test]
+    async fn test_list_entries_paged() {
+        let op = create_test_operator().await;
+        for n in 0..25 {
+            op.write(&format!("big/f{n:02}.txt"), "x").await.unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = list_entries_paged(
+                &op,
+                "/big",
+                cursor.as_deref(),
+                10,
+                DEFAULT_MAX_LISTING_ENTRIES,
+            )
+            .await
+            .unwrap();
+            assert!(page.entries.len() <= 10);
+            seen.extend(page.entries.into_iter().map(|entry| entry.name));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        seen.retain(|name| name != "big");
+        seen.sort();
+        assert_eq!(seen.len(), 25);
+        seen.dedup();
+        assert_eq!(seen.len(), 25);
+        assert!(
+            list_entries_paged(&op, "big", Some("bogus"), 10, DEFAULT_MAX_LISTING_ENTRIES)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_listing_strategy_follows_latency() {
+        let memory = create_test_operator().await;
+        memory.write("a.txt", "abc").await.unwrap();
+        assert_eq!(listing_strategy(&memory), ListingStrategy::StatPerEntry);
+        assert_eq!(list_entries(&memory, "/").await.unwrap()[0].size, 3);
+        assert!(listing_diagnostics(&memory).latency_ms.is_some());
+
+        let remote = crate::synthetic::operator(crate::synthetic::SyntheticConfig {
+            entries: 5,
+            entries_per_dir: 5,
+            file_size: 7,
+            latency: Duration::ZERO,
+        });
+        for _ in 0..10 {
+            tuning::record_latency(&remote, Duration::from_secs(1));
+        }
+        assert_eq!(listing_strategy(&remote), ListingStrategy::ListMetadata);
+        let entries = list_entries(&remote, "/").await.unwrap();
+        assert_eq!(entries.len(), 5);
+        assert!(entries.iter().all(|entry| !entry.is_dir && entry.size == 7));
+    }
+
+    #[tokio::test]
+    async fn test_list_entries_stops_past_limit() {
+        let op = create_test_operator().await;
+        for n in 0..12 {
+            op.write(&format!("huge/f{n:02}"), "x").await.unwrap();
+        }
+        assert_eq!(
+            list_entries_capped(&op, "huge", 12).await.unwrap().len(),
+            12
+        );
+        let err = list_entries_capped(&op, "/huge/", 10).await.unwrap_err();
+        assert!(matches!(&err, CoreError::TooManyEntries(path, 10) if path == "/huge/"));
+        assert_eq!(err.code(), crate::models::ErrorCode::TooLarge);
+    }
+
+    #[tokio::test]
+    async fn test_list_recursive_depth() {
+        let op = create_test_operator().await;
+        op.write("tree/a.txt", "a").await.unwrap();
+        op.write("tree/sub/b.txt", "b").await.unwrap();
+        op.write("tree/sub/deeper/c.txt", "c").await.unwrap();
+
+        let names = |entries: Vec<Entry>| -> Vec<String> {
+            let mut names: Vec<_> = entries.into_iter().map(|entry| entry.path).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(
+                list_recursive(&op, "/tree", Some(1), DEFAULT_MAX_LISTING_ENTRIES)
+                    .await
+                    .unwrap()
+            ),
+            ["tree/a.txt", "tree/sub/"]
+        );
+        assert_eq!(
+            names(
+                list_recursive(&op, "tree", None, DEFAULT_MAX_LISTING_ENTRIES)
+                    .await
+                    .unwrap()
+            ),
+            [
+                "tree/a.txt",
+                "tree/sub/",
+                "tree/sub/b.txt",
+                "tree/sub/deeper/",
+                "tree/sub/deeper/c.txt"
+            ]
+        );
+        assert!(
+            list_recursive(&op, "tree", Some(0), DEFAULT_MAX_LISTING_ENTRIES)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_streams_glob_matches() {
+        let op = create_test_operator().await;
+        op.write("docs/a.pdf", "a").await.unwrap();
+        op.write("docs/2024/b.PDF", "b").await.unwrap();
+        op.write("docs/2024/c.txt", "c").await.unwrap();
+        op.write("other/d.pdf", "d").await.unwrap();
+
+        let mut found = Vec::new();
+        let count = search(&op, "/docs", "**/*.pdf", &CancelToken::default(), |entry| {
+            found.push(entry.path);
+            true
+        })
+        .await
+        .unwrap();
+        found.sort();
+        assert_eq!(count, 2);
+        assert_eq!(found, ["docs/2024/b.PDF", "docs/a.pdf"]);
+
+        let count = search(&op, "/", "*/2024", &CancelToken::default(), |entry| {
+            assert!(entry.is_dir);
+            true
+        })
+        .await
+        .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            search(&op, "", "**/*.pdf", &CancelToken::default(), |_| false)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(search(&op, "", "[", &CancelToken::default(), |_| true)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_range_clamps_to_file() {
+        let op = create_test_operator().await;
+        op.write("log.txt", "0123456789").await.unwrap();
+
+        assert_eq!(read_range(&op, "/log.txt", 0, 4).await.unwrap(), b"0123");
+        assert_eq!(read_range(&op, "log.txt", 7, 100).await.unwrap(), b"789");
+        assert!(read_range(&op, "log.txt", 10, 5).await.unwrap().is_empty());
+        assert!(read_range(&op, "log.txt", 3, 0).await.unwrap().is_empty());
+        assert!(read_range(&op, "missing.txt", 0, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_chunks_streams_past_one_chunk() {
+        let op = create_test_operator().await;
+        let chunk_size = tuning::tuning_for(&op).chunk_size;
+        let data: Vec<u8> = (0..chunk_size * 2 + 123).map(|n| (n % 251) as u8).collect();
+
+        let written = write_chunks(&op, "/uploads/big.bin", data.as_slice())
+            .await
+            .unwrap();
+        assert_eq!(written, data.len() as u64);
+        assert_eq!(op.read("uploads/big.bin").await.unwrap().to_vec(), data);
+
+        let root = std::env::temp_dir().join(format!("infimount-upload-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("local.bin"), &data[..1000]).unwrap();
+        upload_files_from_paths(
+            &op,
+            vec![root.join("local.bin").to_string_lossy().into_owned()],
+            "uploads".to_string(),
+            TransferConflictPolicy::Overwrite,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            op.stat("uploads/local.bin").await.unwrap().content_length(),
+            1000
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recorded_capabilities_fail_fast() {
+        let op = create_test_operator().await;
+        op.write("a.txt", "a").await.unwrap();
+        let mut supported = probe_capabilities(&op);
+        assert!(supported.contains("write") && !supported.contains("rename"));
+
+        supported.remove("delete");
+        set_recorded_capabilities(&op, Some(supported));
+        assert!(matches!(
+            delete(&op, "a.txt").await,
+            Err(CoreError::Unsupported(_))
+        ));
+        let mut report = TransferReport::new("move", "mem", "mem");
+        let moved = transfer_entries(
+            &mut report,
+            &op,
+            &op,
+            vec!["a.txt".into()],
+            "sub/",
+            TransferOperation::Move,
+            true,
+            TransferConflictPolicy::Fail,
+        )
+        .await;
+        assert!(matches!(moved, Err(CoreError::Unsupported(msg)) if msg.contains("rename")));
+        assert!(op.exists("a.txt").await.unwrap());
+
+        set_recorded_capabilities(&op, None);
+        delete(&op, "a.txt").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_create_dir() {
         let op = create_test_operator().await;
-        create_directory(&op, "new-folder").await.unwrap();
+        create_dir(&op, "new-folder").await.unwrap();
         let exists = op.exists("new-folder/").await.unwrap();
         assert!(exists);
+
+        // Implicit folders (a prefix with objects) already exist.
+        op.write("photos/a.jpg", "a").await.unwrap();
+        create_dir(&op, "/photos").await.unwrap();
+        assert_eq!(list_entries(&op, "photos").await.unwrap().len(), 1);
+
+        op.write("notes", "n").await.unwrap();
+        let err = create_dir(&op, "notes").await.unwrap_err();
+        assert_eq!(err.code(), crate::models::ErrorCode::AlreadyExists);
+        assert!(!op.exists("notes/").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_os_trash_rejects_non_local_operator() {
+        let op = create_test_operator().await;
+        op.write("keep.txt", "data".as_bytes()).await.unwrap();
+
+        assert!(!supports_os_trash(&op));
+        let err = move_to_os_trash(&op, "keep.txt").await.unwrap_err();
+        assert!(matches!(err, CoreError::Unsupported(_)));
+        assert!(op.exists("keep.txt").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_writes_to_read_only_source_are_rejected() {
+        let http = Operator::new(opendal::services::Http::default().endpoint("http://127.0.0.1:9"))
+            .unwrap()
+            .finish();
+        let mem = create_test_operator().await;
+        mem.write("a.txt", "a").await.unwrap();
+
+        let err = write_full(&http, "a.txt", b"a", &WriteOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CoreError::Unsupported(_)));
+        assert!(matches!(
+            delete(&http, "a.txt").await.unwrap_err(),
+            CoreError::Unsupported(_)
+        ));
+        let err = transfer_entries(
+            &mut TransferReport::new("copy", "from", "to"),
+            &mem,
+            &http,
+            vec!["a.txt".to_string()],
+            "",
+            TransferOperation::Copy,
+            false,
+            TransferConflictPolicy::Overwrite,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), crate::models::ErrorCode::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_sources_marked_read_only_reject_mutations() {
+        let root = std::env::temp_dir().join(format!("infimount-ro-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let fs = || {
+            Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+                .unwrap()
+                .finish()
+        };
+        let writable = fs();
+        writable.write("report.csv", "a,b").await.unwrap();
+
+        // A second source on the same folder stays writable.
+        let op = read_only::apply(fs());
+        assert!(read_only::is_read_only(&op));
+        assert!(!read_only::is_read_only(&writable));
+        for err in [
+            write_full(&op, "report.csv", b"c,d", &WriteOptions::default())
+                .await
+                .unwrap_err(),
+            delete(&op, "report.csv").await.unwrap_err(),
+            create_dir(&op, "new").await.unwrap_err(),
+        ] {
+            assert!(matches!(err, CoreError::ReadOnlySource(_)));
+            assert_eq!(err.code(), crate::models::ErrorCode::ReadOnly);
+        }
+        assert_eq!(read_full(&op, "report.csv").await.unwrap(), b"a,b");
+
+        delete(&writable, "report.csv").await.unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_download_folder_and_conflicts() {
+        let root = std::env::temp_dir().join(format!("infimount-dl-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let op = create_test_operator().await;
+        op.write("docs/a.txt", "a").await.unwrap();
+        op.write("docs/sub/b.txt", "bb").await.unwrap();
+        op.write("docs/.DS_Store", "x").await.unwrap();
+        op.create_dir("docs/empty/").await.unwrap();
+        op.write("top.txt", "top").await.unwrap();
+
+        let mut report = TransferReport::new("download", "mem", "local");
+        download(
+            &mut report,
+            &op,
+            "/docs",
+            &root,
+            TransferConflictPolicy::Fail,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.count(ItemStatus::Completed), 2);
+        assert_eq!(std::fs::read(root.join("docs/sub/b.txt")).unwrap(), b"bb");
+        assert!(root.join("docs/empty").is_dir());
+        assert!(!root.join("docs/.DS_Store").exists());
+
+        let mut report = TransferReport::new("download", "mem", "local");
+        download(
+            &mut report,
+            &op,
+            "top.txt",
+            &root,
+            TransferConflictPolicy::Fail,
+        )
+        .await
+        .unwrap();
+        assert!(download(
+            &mut report,
+            &op,
+            "top.txt",
+            &root,
+            TransferConflictPolicy::Fail
+        )
+        .await
+        .is_err());
+        download(
+            &mut report,
+            &op,
+            "top.txt",
+            &root,
+            TransferConflictPolicy::Skip,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.count(ItemStatus::Skipped), 1);
+        assert_eq!(std::fs::read(root.join("top.txt")).unwrap(), b"top");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_folder_copy_skips_system_files() {
+        let from = create_test_operator().await;
+        let to = create_test_operator().await;
+        from.write("photos/a.jpg", "a").await.unwrap();
+        from.write("photos/.DS_Store", "x").await.unwrap();
+        from.write("photos/._a.jpg", "x").await.unwrap();
+
+        transfer_entries(
+            &mut TransferReport::new("copy", "from", "to"),
+            &from,
+            &to,
+            vec!["photos/".to_string()],
+            "",
+            TransferOperation::Copy,
+            false,
+            TransferConflictPolicy::Overwrite,
+        )
+        .await
+        .unwrap();
+
+        assert!(to.exists("photos/a.jpg").await.unwrap());
+        assert!(!to.exists("photos/.DS_Store").await.unwrap());
+        assert!(!to.exists("photos/._a.jpg").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_reports_structured_progress() {
+        use crate::progress::{ProgressStage, TransferProgress};
+        use std::sync::{Arc, Mutex};
+
+        let from = create_test_operator().await;
+        let to = create_test_operator().await;
+        from.write("album/one.flac", "1111").await.unwrap();
+        from.write("album/two.flac", "2222").await.unwrap();
+        from.write("cover.jpg", "cc").await.unwrap();
+
+        let updates: Arc<Mutex<Vec<TransferProgress>>> = Arc::default();
+        let mut report = TransferReport::new("copy", "from", "to");
+        let sink = updates.clone();
+        report.set_progress_sink(Arc::new(move |progress| {
+            sink.lock().unwrap().push(progress.clone());
+        }));
+        transfer_entries(
+            &mut report,
+            &from,
+            &to,
+            vec!["album/".to_string(), "cover.jpg".to_string()],
+            "",
+            TransferOperation::Copy,
+            false,
+            TransferConflictPolicy::Overwrite,
+        )
+        .await
+        .unwrap();
+        report.finish();
+
+        let updates = updates.lock().unwrap();
+        assert_eq!(updates[0].stage, ProgressStage::Preparing);
+        let first_item = updates
+            .iter()
+            .find(|u| u.stage == ProgressStage::Transferring)
+            .unwrap();
+        assert_eq!(first_item.items_total, Some(3));
+        assert_eq!(first_item.bytes_total, Some(10));
+        assert!(first_item.current_item.is_some());
+        assert!(first_item.description.starts_with("Copying "));
+
+        let last = updates.last().unwrap();
+        assert_eq!(last.stage, ProgressStage::Finished);
+        assert_eq!((last.items_done, last.bytes_done), (3, 10));
+        assert_eq!(last.percent, Some(100));
+        assert_eq!(last.description, "Done: 3 completed, 0 skipped, 0 failed");
+    }
+
+    #[tokio::test]
+    async fn test_upload_reports_progress_per_file_and_byte() {
+        use crate::progress::{ProgressStage, TransferProgress};
+        use std::sync::{Arc, Mutex};
+
+        let root = std::env::temp_dir().join(format!("infimount-upload-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("album")).unwrap();
+        std::fs::write(root.join("album/one.flac"), "1111").unwrap();
+        std::fs::write(root.join("album/.DS_Store"), "junk").unwrap();
+        std::fs::write(root.join("cover.jpg"), "cc").unwrap();
+        let op = create_test_operator().await;
+
+        let updates: Arc<Mutex<Vec<TransferProgress>>> = Arc::default();
+        let mut report = TransferReport::new("upload", "local", "memory");
+        let sink = updates.clone();
+        report.set_progress_sink(Arc::new(move |progress| {
+            sink.lock().unwrap().push(progress.clone());
+        }));
+        let paths = ["album", "cover.jpg", "missing.txt"]
+            .map(|name| root.join(name).to_string_lossy().into_owned());
+        assert!(upload(
+            &mut report,
+            &op,
+            paths.to_vec(),
+            "/backup",
+            TransferConflictPolicy::Overwrite,
+            false,
+        )
+        .await
+        .is_err());
+        upload(
+            &mut report,
+            &op,
+            paths[..2].to_vec(),
+            "/backup",
+            TransferConflictPolicy::Overwrite,
+            false,
+        )
+        .await
+        .unwrap();
+        report.finish();
+
+        assert_eq!(op.read("backup/one.flac").await.unwrap().to_vec(), b"1111");
+        assert!(!op.exists("backup/.DS_Store").await.unwrap());
+        let updates = updates.lock().unwrap();
+        let first_item = updates
+            .iter()
+            .find(|u| u.stage == ProgressStage::Transferring)
+            .unwrap();
+        assert_eq!(first_item.items_total, Some(2));
+        assert!(first_item.description.starts_with("Uploading "));
+        let last = updates.last().unwrap();
+        assert_eq!((last.items_done, last.bytes_done), (2, 6));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upload_keeps_system_files_unless_filtered() {
+        let root = std::env::temp_dir().join(format!("infimount-upload-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("album")).unwrap();
+        std::fs::write(root.join("album/.DS_Store"), "junk").unwrap();
+        let op = create_test_operator().await;
+
+        let mut report = TransferReport::new("upload", "local", "memory");
+        report.set_filter(TransferFilter {
+            skip_system_files: false,
+            ..TransferFilter::default()
+        });
+        let paths = vec![root.join("album").to_string_lossy().into_owned()];
+        upload(
+            &mut report,
+            &op,
+            paths,
+            "/backup",
+            TransferConflictPolicy::Overwrite,
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert!(op.exists("backup/.DS_Store").await.unwrap());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upload_sends_files_concurrently() {
+        let root = std::env::temp_dir().join(format!("infimount-upload-{}", uuid::Uuid::new_v4()));
+        for i in 0..40 {
+            let dir = root.join(format!("photos/{}", i % 3));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(format!("{i}.jpg")), vec![b'x'; i]).unwrap();
+        }
+        let op = create_test_operator().await;
+
+        let mut report = TransferReport::new("upload", "local", "memory");
+        report.set_upload_concurrency(Some(8));
+        let paths = vec![root.join("photos").to_string_lossy().into_owned()];
+        upload(
+            &mut report,
+            &op,
+            paths,
+            "/backup",
+            TransferConflictPolicy::Overwrite,
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.count(ItemStatus::Completed), 40);
+        assert_eq!(report.total_bytes(), (0..40).sum::<u64>());
+        for i in 0..40 {
+            let path = format!("backup/{}/{i}.jpg", i % 3);
+            assert_eq!(op.read(&path).await.unwrap().len(), i, "{path}");
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upload_conflict_policies() {
+        let root =
+            std::env::temp_dir().join(format!("infimount-conflict-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("notes.txt"), "new").unwrap();
+        let paths = vec![root.join("notes.txt").to_string_lossy().into_owned()];
+        let op = create_test_operator().await;
+        op.write("docs/notes.txt", "old").await.unwrap();
+
+        let mut report = TransferReport::new("upload", "local", "memory");
+        let result = upload(
+            &mut report,
+            &op,
+            paths.clone(),
+            "docs",
+            TransferConflictPolicy::Fail,
+            false,
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(report.items.is_empty());
+
+        let mut report = TransferReport::new("upload", "local", "memory");
+        upload(
+            &mut report,
+            &op,
+            paths.clone(),
+            "docs",
+            TransferConflictPolicy::Skip,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.items[0].status, ItemStatus::Skipped);
+        assert_eq!(report.items[0].conflict, Some(Conflict::Skipped));
+        assert_eq!(op.read("docs/notes.txt").await.unwrap().to_vec(), b"old");
+
+        let mut report = TransferReport::new("upload", "local", "memory");
+        upload(
+            &mut report,
+            &op,
+            paths.clone(),
+            "docs",
+            TransferConflictPolicy::Rename,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.items[0].target_path, "docs/notes copy.txt");
+        assert_eq!(report.items[0].conflict, Some(Conflict::Renamed));
+        assert_eq!(
+            op.read("docs/notes copy.txt").await.unwrap().to_vec(),
+            b"new"
+        );
+
+        let mut report = TransferReport::new("upload", "local", "memory");
+        upload(
+            &mut report,
+            &op,
+            paths,
+            "docs",
+            TransferConflictPolicy::Overwrite,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.items[0].status, ItemStatus::Completed);
+        assert_eq!(report.items[0].conflict, Some(Conflict::Overwritten));
+        assert_eq!(op.read("docs/notes.txt").await.unwrap().to_vec(), b"new");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_transfer_removes_partial_file_and_stops() {
+        use crate::cancel;
+        use std::sync::Arc;
+
+        let root = std::env::temp_dir().join(format!("infimount-cancel-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let local = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+        let remote = create_test_operator().await;
+        remote
+            .write("big.bin", vec![7u8; COPY_BUFFER_SIZE * 3])
+            .await
+            .unwrap();
+        remote.write("small.txt", "s").await.unwrap();
+
+        let mut report = TransferReport::new("download", "remote", "local");
+        let registration = cancel::register(report.task_id.clone());
+        report.set_cancel_token(registration.token());
+        let task_id = report.task_id.clone();
+        // Cancel once the first file starts; its first chunk is still written.
+        report.set_progress_sink(Arc::new(move |progress| {
+            if progress.current_item.is_some() {
+                cancel::cancel(&task_id);
+            }
+        }));
+        let result = transfer_entries(
+            &mut report,
+            &remote,
+            &local,
+            vec!["big.bin".to_string(), "small.txt".to_string()],
+            "/",
+            TransferOperation::Copy,
+            false,
+            TransferConflictPolicy::Overwrite,
+        )
+        .await;
+
+        assert!(matches!(result, Err(CoreError::Cancelled)));
+        assert_eq!(report.items.len(), 1);
+        assert_eq!(report.items[0].status, ItemStatus::Failed);
+        assert!(!local.exists("big.bin").await.unwrap());
+        assert!(!local.exists("small.txt").await.unwrap());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transfer_streams_between_sources() {
+        let root = std::env::temp_dir().join(format!("infimount-xfer-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let local = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+        let remote = create_test_operator().await;
+        local.write("photos/a.jpg", "aaaa").await.unwrap();
+        local.write("photos/2024/b.jpg", "bb").await.unwrap();
+
+        let mut report = TransferReport::new("copy", "local", "remote");
+        transfer(&mut report, &local, "photos", &remote, "backup/photos")
+            .await
+            .unwrap();
+        assert_eq!(report.count(ItemStatus::Completed), 2);
+        assert_eq!(
+            remote.read("backup/photos/a.jpg").await.unwrap().to_vec(),
+            b"aaaa"
+        );
+        assert_eq!(
+            remote
+                .read("backup/photos/2024/b.jpg")
+                .await
+                .unwrap()
+                .to_vec(),
+            b"bb"
+        );
+
+        let mut report = TransferReport::new("copy", "remote", "local");
+        transfer(
+            &mut report,
+            &remote,
+            "/backup/photos/a.jpg",
+            &local,
+            "restored.jpg",
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.items[0].verified, Some(true));
+        assert_eq!(local.read("restored.jpg").await.unwrap().to_vec(), b"aaaa");
+
+        let mut report = TransferReport::new("copy", "remote", "local");
+        assert!(
+            transfer(&mut report, &remote, "backup/photos/a.jpg", &local, "dir/")
+                .await
+                .is_err()
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transfer_report_records_each_file() {
+        let from = create_test_operator().await;
+        let to = create_test_operator().await;
+        from.write("a.txt", "aaa").await.unwrap();
+        from.write("b.txt", "bb").await.unwrap();
+        to.write("b.txt", "old").await.unwrap();
+
+        let mut report = TransferReport::new("copy", "from", "to");
+        transfer_entries(
+            &mut report,
+            &from,
+            &to,
+            vec!["a.txt".to_string(), "b.txt".to_string()],
+            "",
+            TransferOperation::Copy,
+            false,
+            TransferConflictPolicy::Skip,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.items.len(), 2);
+        let copied = &report.items[0];
+        assert_eq!(copied.status, ItemStatus::Completed);
+        assert_eq!(copied.bytes, 3);
+        assert_eq!(copied.verified, Some(true));
+        assert_eq!(report.items[1].status, ItemStatus::Skipped);
+        assert_eq!(to.read("b.txt").await.unwrap().to_vec(), b"old");
+    }
+
+    #[tokio::test]
+    async fn test_retry_failed_reruns_only_failed_items() {
+        let from = create_test_operator().await;
+        let to = create_test_operator().await;
+        from.write("a.txt", "aaa").await.unwrap();
+        from.write("b.txt", "bb").await.unwrap();
+        to.write("a.txt", "partial").await.unwrap();
+
+        let mut previous = TransferReport::new("copy", "from", "to");
+        for (path, status) in [
+            ("a.txt", ItemStatus::Failed),
+            ("b.txt", ItemStatus::Completed),
+        ] {
+            previous.push(TransferItem {
+                source_path: path.to_string(),
+                target_path: path.to_string(),
+                status,
+                bytes: 0,
+                duration_ms: 0,
+                error: None,
+                verified: None,
+                conflict: None,
+            });
+        }
+
+        let mut report = previous.retry();
+        retry_failed(&mut report, &previous, &from, &to, false)
+            .await
+            .unwrap();
+
+        assert_eq!(report.retry_of.as_deref(), Some(previous.task_id.as_str()));
+        assert_eq!(report.items.len(), 1);
+        assert_eq!(report.items[0].status, ItemStatus::Completed);
+        assert_eq!(to.read("a.txt").await.unwrap().to_vec(), b"aaa");
+        assert!(!to.exists("b.txt").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_links_on_local_source() {
+        let root = std::env::temp_dir().join(format!("infimount-links-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let op = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+        op.write("albums/track.flac", "audio").await.unwrap();
+
+        assert!(supports_links(&op));
+        create_symlink(&op, "albums/track.flac", "playlist-track.flac")
+            .await
+            .unwrap();
+        create_hardlink(&op, "albums/track.flac", "hard.flac")
+            .await
+            .unwrap();
+
+        assert!(std::fs::symlink_metadata(root.join("playlist-track.flac"))
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(
+            op.read("playlist-track.flac").await.unwrap().to_vec(),
+            b"audio"
+        );
+        assert_eq!(op.read("hard.flac").await.unwrap().to_vec(), b"audio");
+        assert!(create_hardlink(&op, "albums/track.flac", "hard.flac")
+            .await
+            .is_err());
+
+        let memory = create_test_operator().await;
+        assert!(!supports_links(&memory));
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_loops_and_copying_links() {
+        let root = std::env::temp_dir().join(format!("infimount-loops-{}", std::process::id()));
+        let src = root.join("src");
+        std::fs::create_dir_all(src.join("docs")).unwrap();
+        std::fs::write(src.join("docs/a.txt"), "aaa").unwrap();
+        std::os::unix::fs::symlink("..", src.join("docs/up")).unwrap();
+        std::os::unix::fs::symlink("a.txt", src.join("docs/alias.txt")).unwrap();
+        let op = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+
+        let entries = list_recursive(&op, "src", None, DEFAULT_MAX_LISTING_ENTRIES)
+            .await
+            .unwrap();
+        let up = entries.iter().find(|e| e.name == "up").unwrap();
+        assert!(up.is_symlink);
+        assert_eq!(up.link_target.as_deref(), Some(".."));
+        assert!(!entries.iter().any(|e| e.path.contains("up/")));
+
+        let src_path = src.to_string_lossy().into_owned();
+        let mut report = TransferReport::new("upload", "local", "fs");
+        upload(
+            &mut report,
+            &op,
+            vec![src_path.clone()],
+            "followed",
+            TransferConflictPolicy::Fail,
+            false,
+        )
+        .await
+        .unwrap();
+        // The folder's contents land straight under the target.
+        assert_eq!(
+            op.read("followed/docs/alias.txt").await.unwrap().to_vec(),
+            b"aaa"
+        );
+        assert!(!op.exists("followed/docs/up/docs/a.txt").await.unwrap());
+
+        let mut report = TransferReport::new("upload", "local", "fs");
+        upload(
+            &mut report,
+            &op,
+            vec![src_path],
+            "linked",
+            TransferConflictPolicy::Fail,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            std::fs::read_link(root.join("linked/docs/up")).unwrap(),
+            Path::new("..")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("linked/docs/alias.txt")).unwrap(),
+            "aaa"
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_transfers_keep_modification_times() {
+        let root = std::env::temp_dir().join(format!("infimount-mtime-{}", std::process::id()));
+        let local = root.join("local");
+        std::fs::create_dir_all(&local).unwrap();
+        let file = local.join("notes.txt");
+        std::fs::write(&file, "notes").unwrap();
+        let modified = std::time::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let op = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+        let mtime = |path: &Path| std::fs::metadata(path).unwrap().modified().unwrap();
+
+        let paths = vec![file.to_string_lossy().into_owned()];
+        let mut report = TransferReport::new("upload", "local", "fs");
+        upload(
+            &mut report,
+            &op,
+            paths.clone(),
+            "kept",
+            TransferConflictPolicy::Fail,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(mtime(&root.join("kept/notes.txt")), modified);
+
+        let mut report = TransferReport::new("download", "fs", "local");
+        download(
+            &mut report,
+            &op,
+            "kept/notes.txt",
+            &root.join("down"),
+            TransferConflictPolicy::Fail,
+        )
+        .await
+        .unwrap();
+        assert_eq!(mtime(&root.join("down/notes.txt")), modified);
+
+        let mut report = TransferReport::new("upload", "local", "fs");
+        report.set_preserve_attributes(false);
+        upload(
+            &mut report,
+            &op,
+            paths,
+            "fresh",
+            TransferConflictPolicy::Fail,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_ne!(mtime(&root.join("fresh/notes.txt")), modified);
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_cross_operator_copy_uses_tuned_writer() {
+        let from = create_test_operator().await;
+        let to = Operator::new(Memory::default().root("/tuned-destination"))
+            .unwrap()
+            .finish();
+        tuning::set_override(
+            &to,
+            Some(tuning::TransferTuning {
+                chunk_size: 5 * 1024 * 1024,
+                concurrency: 4,
+            }),
+        );
+        from.write("big.bin", vec![7u8; 6 * 1024 * 1024])
+            .await
+            .unwrap();
+
+        transfer_entries(
+            &mut TransferReport::new("copy", "from", "to"),
+            &from,
+            &to,
+            vec!["big.bin".to_string()],
+            "",
+            TransferOperation::Copy,
+            false,
+            TransferConflictPolicy::Fail,
+        )
+        .await
+        .unwrap();
+
+        let copied = to.read("big.bin").await.unwrap();
+        assert_eq!(copied.len(), 6 * 1024 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_connection_reports_success_and_failure() {
+        let op = create_test_operator().await;
+        let test = test_connection(&op).await;
+        assert!(test.ok);
+        assert!(test.error.is_none());
+
+        // Nothing listens on port 1.
+        let unreachable = Operator::new(
+            opendal::services::S3::default()
+                .bucket("bucket")
+                .region("us-east-1")
+                .endpoint("http://127.0.0.1:1")
+                .access_key_id("key")
+                .secret_access_key("secret")
+                .disable_config_load(),
+        )
+        .unwrap()
+        .finish();
+        let test = test_connection(&unreachable).await;
+        assert!(!test.ok);
+        assert!(test.error.is_some());
+        assert_eq!(test.error_kind, Some(ConnectionErrorKind::Other));
+    }
+
+    #[tokio::test]
+    async fn test_checksum_streams_supported_algorithms() {
+        let op = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+        op.write("release.tar", "abc").await.unwrap();
+
+        assert_eq!(
+            checksum(&op, "/release.tar", ChecksumAlgorithm::Md5)
+                .await
+                .unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            checksum(&op, "release.tar", ChecksumAlgorithm::Sha256)
+                .await
+                .unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            checksum(&op, "release.tar", ChecksumAlgorithm::Xxh64)
+                .await
+                .unwrap(),
+            "44bc2cf5ad770999"
+        );
+        assert!(checksum(&op, "missing", ChecksumAlgorithm::Md5)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_entries_carry_backend_metadata() {
+        let op = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+        let meta = opendal::Metadata::new(opendal::EntryMode::FILE)
+            .with_content_length(42)
+            .with_content_type("application/pdf".to_string())
+            .with_etag("\"abc\"".to_string())
+            .with_user_metadata(HashMap::from([("case".to_string(), "1234".to_string())]));
+        let entry = entry_from_metadata(&op, "docs/report.pdf".to_string(), Some(&meta));
+        assert_eq!(entry.name, "report.pdf");
+        assert_eq!(entry.size, 42);
+        assert_eq!(entry.content_type.as_deref(), Some("application/pdf"));
+        assert_eq!(entry.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(entry.user_metadata.unwrap()["case"], "1234");
+
+        let missing = entry_from_metadata(&op, "gone.txt".to_string(), None);
+        let json = serde_json::to_value(&missing).unwrap();
+        assert!(json.get("etag").is_none());
+        assert!(json.get("user_metadata").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_many_reports_each_path() {
+        let op = create_test_operator().await;
+        op.write("a.txt", "a").await.unwrap();
+        op.write("b.txt", "b").await.unwrap();
+        op.write("dir/c.txt", "c").await.unwrap();
+        op.write("keep.txt", "k").await.unwrap();
+
+        let report = delete_many(
+            &op,
+            vec![
+                "/a.txt".to_string(),
+                "b.txt".to_string(),
+                "/dir/".to_string(),
+            ],
+            &CancelToken::default(),
+        )
+        .await
+        .unwrap();
+        assert!(report.failed.is_empty());
+        assert_eq!(report.deleted.len(), 3);
+        assert!(!op.exists("a.txt").await.unwrap());
+        assert!(!op.exists("dir/c.txt").await.unwrap());
+        assert!(op.exists("keep.txt").await.unwrap());
+
+        for root in ["", "/"] {
+            let err = delete_many(
+                &op,
+                vec!["keep.txt".to_string(), root.to_string()],
+                &CancelToken::default(),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.code(), crate::models::ErrorCode::Unsupported);
+        }
+        assert!(op.exists("keep.txt").await.unwrap());
     }
 }
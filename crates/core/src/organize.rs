@@ -0,0 +1,223 @@
+//! Automatic organization rules ("file-type routing").
+//!
+//! A rule watches one folder of a source and moves the files that land in
+//! it into subfolders chosen by a destination pattern, e.g. photos from
+//! `/inbox` to `/photos/{yyyy}/{mm}`. [`plan`] is the dry run the UI shows
+//! before a rule is enabled; [`apply`] performs the same moves and is what
+//! the desktop app's background runner calls for every enabled rule.
+//! Existing files at the destination are never overwritten.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_list_path, TransferConflictPolicy, TransferOperation};
+use crate::transfer_report::TransferReport;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrganizeRule {
+    pub id: String,
+    pub name: String,
+    pub source_id: String,
+    /// Folder whose files (not subfolders) are organized.
+    pub watch: String,
+    /// File extensions, e.g. `jpg`; empty matches every file.
+    pub extensions: Vec<String>,
+    /// Target folder; `{yyyy}`, `{mm}` and `{dd}` are replaced with the
+    /// file's modification date and `{ext}` with its extension.
+    pub destination: String,
+    pub enabled: bool,
+}
+
+impl Default for OrganizeRule {
+    fn default() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: String::new(),
+            source_id: String::new(),
+            watch: "/".to_string(),
+            extensions: Vec::new(),
+            destination: String::new(),
+            enabled: false,
+        }
+    }
+}
+
+impl OrganizeRule {
+    fn matches(&self, name: &str) -> bool {
+        self.extensions.is_empty()
+            || self.extensions.iter().any(|ext| {
+                ext.trim_start_matches('.')
+                    .eq_ignore_ascii_case(extension(name))
+            })
+    }
+
+    /// Target folder for a file named `name` modified at `modified_at`.
+    fn target_dir(&self, name: &str, modified_at: Option<&str>) -> String {
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        // Listings report RFC 3339-style timestamps starting `YYYY-MM-DD`.
+        let date = modified_at
+            .filter(|date| date.len() >= 10 && date.is_char_boundary(10))
+            .map(|date| &date[..10])
+            .unwrap_or(&today);
+        let ext = extension(name).to_lowercase();
+        let ext = if ext.is_empty() { "other" } else { &ext };
+        let dir = self
+            .destination
+            .replace("{yyyy}", &date[..4])
+            .replace("{mm}", &date[5..7])
+            .replace("{dd}", &date[8..10])
+            .replace("{ext}", ext);
+        normalize_list_path(&dir)
+    }
+}
+
+fn extension(name: &str) -> &str {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => ext,
+        _ => "",
+    }
+}
+
+/// A move a rule would make.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedMove {
+    pub from: String,
+    /// Folder the file is moved into.
+    pub to_dir: String,
+}
+
+/// The moves `rule` would make right now, without touching anything.
+pub async fn plan(op: &Operator, rule: &OrganizeRule) -> Result<Vec<PlannedMove>> {
+    if rule.destination.trim().is_empty() {
+        return Err(CoreError::Config(format!(
+            "organization rule \"{}\" has no destination",
+            rule.name
+        )));
+    }
+    let watch = normalize_list_path(&rule.watch);
+    let mut moves = Vec::new();
+    for entry in operations::list_entries(op, &watch).await? {
+        if entry.is_dir || entry.online_only || !rule.matches(&entry.name) {
+            continue;
+        }
+        let to_dir = rule.target_dir(&entry.name, entry.modified_at.as_deref());
+        if to_dir == watch {
+            continue;
+        }
+        moves.push(PlannedMove {
+            from: entry.path,
+            to_dir,
+        });
+    }
+    Ok(moves)
+}
+
+/// Carry out `rule`: move the planned files, skipping any whose name is
+/// already taken at the destination.
+pub async fn apply(op: &Operator, rule: &OrganizeRule) -> Result<TransferReport> {
+    let mut by_dir: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for planned in plan(op, rule).await? {
+        by_dir.entry(planned.to_dir).or_default().push(planned.from);
+    }
+
+    let mut report = TransferReport::new(
+        TransferOperation::Move.as_str(),
+        &rule.source_id,
+        &rule.source_id,
+    );
+    for (to_dir, paths) in by_dir {
+        operations::transfer_entries(
+            &mut report,
+            op,
+            op,
+            paths,
+            &to_dir,
+            TransferOperation::Move,
+            true,
+            TransferConflictPolicy::Skip,
+        )
+        .await?;
+    }
+    report.finish();
+    Ok(report)
+}
+
+fn rules_path() -> PathBuf {
+    config::data_dir().join("organize-rules.json")
+}
+
+/// Saved rules, or none when nothing was saved yet.
+pub fn load_rules() -> Result<Vec<OrganizeRule>> {
+    let path = rules_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let data = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn save_rules(rules: &[OrganizeRule]) -> Result<()> {
+    let data = serde_json::to_string_pretty(rules)?;
+    config::write_atomic(&rules_path(), data.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rules_preview_then_move_matching_files() {
+        let root =
+            std::env::temp_dir().join(format!("infimount-organize-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let op = Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish();
+        op.write("inbox/IMG_1.JPG", "a").await.unwrap();
+        op.write("inbox/notes.txt", "b").await.unwrap();
+        op.write("inbox/album/IMG_2.jpg", "c").await.unwrap();
+
+        let rule = OrganizeRule {
+            source_id: "local".to_string(),
+            watch: "/inbox".to_string(),
+            extensions: vec!["jpg".to_string(), ".png".to_string()],
+            destination: "/photos/{yyyy}/{mm}".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            rule.target_dir("a.jpg", Some("2024-03-09T10:00:00Z")),
+            "photos/2024/03/"
+        );
+
+        let moves = plan(&op, &rule).await.unwrap();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].from, "inbox/IMG_1.JPG");
+        assert!(moves[0].to_dir.starts_with("photos/"));
+        assert!(op.exists("inbox/IMG_1.JPG").await.unwrap());
+
+        let report = apply(&op, &rule).await.unwrap();
+        assert_eq!(report.items.len(), 1);
+        assert!(op
+            .exists(&format!("{}IMG_1.JPG", moves[0].to_dir))
+            .await
+            .unwrap());
+        assert!(!op.exists("inbox/IMG_1.JPG").await.unwrap());
+        assert!(op.exists("inbox/notes.txt").await.unwrap());
+        assert!(plan(&op, &rule).await.unwrap().is_empty());
+
+        let no_destination = OrganizeRule {
+            destination: " ".to_string(),
+            ..rule
+        };
+        assert!(plan(&op, &no_destination).await.is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
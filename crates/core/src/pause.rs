@@ -0,0 +1,85 @@
+//! App-wide pause switch for transfers and background jobs.
+//!
+//! Pausing is one flag flip, so every running job stops at the same time: a
+//! transfer finishes the file in flight and waits before starting the next
+//! one, and background jobs skip their passes until resumed. The state is
+//! saved with [`crate::config::save_runtime_state`] so a paused queue stays
+//! paused across restarts.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::cancel::CancelToken;
+use crate::config;
+use crate::models::Result;
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// How often a paused job checks whether it may go on.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::SeqCst);
+}
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::SeqCst)
+}
+
+/// Return once jobs are not paused.
+pub async fn wait_while_paused() {
+    while is_paused() {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// [`wait_while_paused`] that also returns once `cancel` fires, so a
+/// paused job can be cancelled.
+pub async fn wait_while_paused_unless(cancel: &CancelToken) {
+    while is_paused() && !cancel.is_cancelled() {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Pause all transfers and background jobs, and remember it.
+pub fn pause_all() -> Result<()> {
+    set_paused(true);
+    save(true)
+}
+
+pub fn resume_all() -> Result<()> {
+    set_paused(false);
+    save(false)
+}
+
+/// Re-apply the pause state saved by the previous run.
+pub fn restore() -> Result<bool> {
+    let paused = config::load_runtime_state()?.transfers_paused;
+    set_paused(paused);
+    Ok(paused)
+}
+
+fn save(paused: bool) -> Result<()> {
+    let mut state = config::load_runtime_state().unwrap_or_default();
+    state.transfers_paused = paused;
+    config::save_runtime_state(&state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn paused_jobs_wait_until_resumed() {
+        set_paused(true);
+        let waiter = tokio::spawn(wait_while_paused());
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        assert!(!waiter.is_finished());
+
+        set_paused(false);
+        tokio::time::timeout(POLL_INTERVAL * 5, waiter)
+            .await
+            .expect("resumed job goes on")
+            .unwrap();
+    }
+}
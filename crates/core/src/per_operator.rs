@@ -0,0 +1,81 @@
+//! Settings attached to one built operator rather than to its backend.
+//!
+//! Two sources can point at the same bucket or folder with different
+//! settings, so these are keyed by the operator's own accessor: clones of an
+//! operator, and layers added on top of it, share an entry, while an
+//! operator built separately for the same backend does not.
+
+use std::sync::{Arc, Mutex, Weak};
+
+use opendal::raw::{Access, AccessorInfo};
+use opendal::Operator;
+
+pub(crate) struct PerOperator<T> {
+    entries: Mutex<Vec<(Weak<AccessorInfo>, T)>>,
+}
+
+impl<T> PerOperator<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Attach `value` to `op`, or detach what was attached with `None`.
+    pub(crate) fn set(&self, op: &Operator, value: Option<T>) {
+        let info = Arc::downgrade(&op.inner().info());
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop entries of operators that are gone along with this one's.
+        entries.retain(|(owner, _)| owner.strong_count() > 0 && !owner.ptr_eq(&info));
+        if let Some(value) = value {
+            entries.push((info, value));
+        }
+    }
+
+    /// Call `f` with the value attached to `op`, if any.
+    pub(crate) fn with<R>(&self, op: &Operator, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let info = Arc::downgrade(&op.inner().info());
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .iter()
+            .find(|(owner, _)| owner.ptr_eq(&info))
+            .map(|(_, value)| f(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_operator() -> Operator {
+        Operator::new(opendal::services::Memory::default().root("/shared"))
+            .unwrap()
+            .finish()
+    }
+
+    #[test]
+    fn values_stay_with_the_operator_they_were_set_on() {
+        let map = PerOperator::new();
+        let first = memory_operator();
+        let second = memory_operator();
+        map.set(&first, Some(1));
+        map.set(&second, Some(2));
+
+        assert_eq!(map.with(&first.clone(), |v| *v), Some(1));
+        assert_eq!(map.with(&second, |v| *v), Some(2));
+        assert_eq!(map.with(&memory_operator(), |v| *v), None);
+
+        map.set(&first, None);
+        assert_eq!(map.with(&first, |v| *v), None);
+        assert_eq!(map.with(&second, |v| *v), Some(2));
+    }
+
+    #[test]
+    fn entries_of_dropped_operators_are_pruned() {
+        let map = PerOperator::new();
+        map.set(&memory_operator(), Some(1));
+        let op = memory_operator();
+        map.set(&op, Some(2));
+        assert_eq!(map.entries.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,87 @@
+//! Online-only ("placeholder") files in OS-synced folders.
+//!
+//! iCloud Drive, OneDrive and similar clients keep cloud-only files on disk as
+//! placeholders that are downloaded ("hydrated") on first read. Listing them
+//! is free, but a recursive upload would read every one and pull the whole
+//! cloud folder down. These helpers detect such files without opening them.
+
+use std::path::Path;
+
+/// macOS `SF_DATALESS`: file content is not present locally (APFS dataless file).
+#[cfg(target_os = "macos")]
+const SF_DATALESS: u32 = 0x4000_0000;
+
+/// Windows attributes set by the Cloud Files API on dehydrated placeholders.
+#[cfg(windows)]
+const FILE_ATTRIBUTE_OFFLINE: u32 = 0x0000_1000;
+#[cfg(windows)]
+const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+#[cfg(windows)]
+const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+
+/// Legacy iCloud stub for an evicted file: `foo.pdf` becomes `.foo.pdf.icloud`.
+pub fn is_icloud_stub(name: &str) -> bool {
+    name.len() > ".icloud".len() + 1 && name.starts_with('.') && name.ends_with(".icloud")
+}
+
+/// Whether `path` is a placeholder whose content lives only in the cloud.
+/// Errors (missing file, permissions) are reported as "not online-only".
+pub fn is_online_only(path: &Path) -> bool {
+    if path
+        .file_name()
+        .is_some_and(|name| is_icloud_stub(&name.to_string_lossy()))
+    {
+        return true;
+    }
+
+    // symlink_metadata never follows the link and does not trigger hydration.
+    std::fs::symlink_metadata(path)
+        .map(|meta| metadata_is_online_only(&meta))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn metadata_is_online_only(meta: &std::fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    meta.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(windows)]
+fn metadata_is_online_only(meta: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    meta.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE
+            | FILE_ATTRIBUTE_RECALL_ON_OPEN
+            | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn metadata_is_online_only(_meta: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icloud_stubs_are_online_only() {
+        assert!(is_icloud_stub(".report.pdf.icloud"));
+        assert!(!is_icloud_stub("report.pdf"));
+        assert!(!is_icloud_stub(".icloud"));
+        assert!(!is_icloud_stub("notes.icloud"));
+
+        let dir =
+            std::env::temp_dir().join(format!("infimount-placeholder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let stub = dir.join(".photo.jpg.icloud");
+        let regular = dir.join("photo.jpg");
+        std::fs::write(&stub, b"").unwrap();
+        std::fs::write(&regular, b"data").unwrap();
+
+        assert!(is_online_only(&stub));
+        assert!(!is_online_only(&regular));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
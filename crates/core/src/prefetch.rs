@@ -0,0 +1,211 @@
+//! Background prefetch of the folders around the one being viewed.
+//!
+//! When a folder is opened, the listings of its subfolders and siblings, and
+//! the contents of its small files (for previews), are fetched at low
+//! priority and parked here. The next [`take_listing`] / [`take_preview`]
+//! for one of them returns the parked result instead of asking the backend,
+//! which makes browsing a high-latency source feel instant. Parked results
+//! are handed out once and expire after [`PREFETCH_TTL`]; any write through
+//! [`crate::operations`] drops everything parked for that source.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use opendal::Operator;
+use tokio::sync::Semaphore;
+
+use crate::models::{Entry, Result};
+use crate::operations::{self, normalize_list_path, normalize_opendal_path};
+use crate::tuning;
+
+/// How long a prefetched result stays usable.
+pub const PREFETCH_TTL: Duration = Duration::from_secs(30);
+/// Neighbouring folders listed per prefetch, subfolders first.
+const MAX_DIRS: usize = 8;
+/// Files up to this size get their content prefetched for previews.
+const MAX_PREVIEW_BYTES: u64 = 64 * 1024;
+const MAX_PREVIEWS: usize = 16;
+/// Parked listings/previews kept at most; the oldest go first.
+const MAX_PARKED: usize = 256;
+
+struct Parked<T> {
+    value: T,
+    at: Instant,
+}
+
+#[derive(Default)]
+struct Cache {
+    listings: HashMap<(String, String), Parked<Vec<Entry>>>,
+    previews: HashMap<(String, String), Parked<Vec<u8>>>,
+    /// Bumped per source by every prefetch, so a stale run stops early.
+    generations: HashMap<String, u64>,
+}
+
+fn cache() -> &'static Mutex<Cache> {
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Cache> {
+    cache().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn park<T>(map: &mut HashMap<(String, String), Parked<T>>, key: (String, String), value: T) {
+    map.retain(|_, parked| parked.at.elapsed() < PREFETCH_TTL);
+    if map.len() >= MAX_PARKED {
+        if let Some(oldest) = map
+            .iter()
+            .min_by_key(|(_, parked)| parked.at)
+            .map(|(key, _)| key.clone())
+        {
+            map.remove(&oldest);
+        }
+    }
+    map.insert(
+        key,
+        Parked {
+            value,
+            at: Instant::now(),
+        },
+    );
+}
+
+fn take<T>(map: &mut HashMap<(String, String), Parked<T>>, key: &(String, String)) -> Option<T> {
+    map.remove(key)
+        .filter(|parked| parked.at.elapsed() < PREFETCH_TTL)
+        .map(|parked| parked.value)
+}
+
+/// The prefetched listing of folder `path`, if there is a fresh one.
+pub fn take_listing(op: &Operator, path: &str) -> Option<Vec<Entry>> {
+    let key = (tuning::operator_key(op), normalize_list_path(path));
+    take(&mut lock().listings, &key)
+}
+
+/// The prefetched content of file `path`, if there is a fresh one.
+pub fn take_preview(op: &Operator, path: &str) -> Option<Vec<u8>> {
+    let key = (tuning::operator_key(op), normalize_opendal_path(path));
+    take(&mut lock().previews, &key)
+}
+
+/// Drop everything prefetched for the source behind `op`, e.g. after a write.
+pub fn forget(op: &Operator) {
+    let source = tuning::operator_key(op);
+    let mut cache = lock();
+    cache.listings.retain(|(key, _), _| *key != source);
+    cache.previews.retain(|(key, _), _| *key != source);
+}
+
+/// Prefetch the subfolders and sibling folders of `path` and the contents of
+/// its small files. Only one prefetch runs at a time, and a newer call for
+/// the same source makes an older one stop, so this never competes much
+/// with what the user is doing. Listing errors are ignored.
+pub async fn prefetch_adjacent(op: &Operator, path: &str) -> Result<()> {
+    static RUNNING: Semaphore = Semaphore::const_new(1);
+
+    let source = tuning::operator_key(op);
+    let generation = {
+        let mut cache = lock();
+        let generation = cache.generations.entry(source.clone()).or_default();
+        *generation += 1;
+        *generation
+    };
+    let superseded = || lock().generations.get(&source) != Some(&generation);
+    let Ok(_permit) = RUNNING.acquire().await else {
+        return Ok(());
+    };
+    if superseded() {
+        return Ok(());
+    }
+
+    let current = normalize_list_path(path);
+    let entries = operations::list_entries(op, &current).await?;
+    let mut dirs: Vec<String> = entries
+        .iter()
+        .filter(|entry| entry.is_dir)
+        .map(|entry| normalize_list_path(&entry.path))
+        .filter(|dir| *dir != current)
+        .collect();
+    if let Some(parent) = parent_of(&current) {
+        if let Ok(siblings) = operations::list_entries(op, &parent).await {
+            dirs.extend(
+                siblings
+                    .iter()
+                    .filter(|entry| entry.is_dir)
+                    .map(|entry| normalize_list_path(&entry.path))
+                    .filter(|dir| *dir != current && *dir != parent),
+            );
+        }
+    }
+
+    for dir in dirs.into_iter().take(MAX_DIRS) {
+        tokio::task::yield_now().await;
+        if superseded() {
+            return Ok(());
+        }
+        if let Ok(listing) = operations::list_entries(op, &dir).await {
+            park(&mut lock().listings, (source.clone(), dir), listing);
+        }
+    }
+
+    let small_files = entries
+        .iter()
+        .filter(|entry| {
+            !entry.is_dir && !entry.online_only && entry.size > 0 && entry.size <= MAX_PREVIEW_BYTES
+        })
+        .take(MAX_PREVIEWS);
+    for file in small_files {
+        tokio::task::yield_now().await;
+        if superseded() {
+            return Ok(());
+        }
+        if let Ok(data) = operations::read_full(op, &file.path).await {
+            let key = (source.clone(), normalize_opendal_path(&file.path));
+            park(&mut lock().previews, key, data);
+        }
+    }
+    Ok(())
+}
+
+/// `a/b/` -> `a/`, `a/` -> `` (the root); `None` for the root itself.
+fn parent_of(dir: &str) -> Option<String> {
+    if dir.is_empty() {
+        return None;
+    }
+    let trimmed = dir.trim_end_matches('/');
+    Some(match trimmed.rfind('/') {
+        Some(idx) => trimmed[..=idx].to_string(),
+        None => String::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    #[tokio::test]
+    async fn prefetched_neighbours_are_taken_once() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("photos/2023/a.jpg", "a").await.unwrap();
+        op.write("photos/2024/b.jpg", "b").await.unwrap();
+        op.write("photos/notes.txt", "hello").await.unwrap();
+        op.write("music/song.mp3", "x").await.unwrap();
+
+        prefetch_adjacent(&op, "/photos").await.unwrap();
+        let listing = take_listing(&op, "photos/2024").unwrap();
+        assert_eq!(listing.len(), 1);
+        assert!(take_listing(&op, "photos/2024/").is_none());
+        assert!(take_listing(&op, "/music/").is_some());
+        assert_eq!(take_preview(&op, "/photos/notes.txt").unwrap(), b"hello");
+
+        prefetch_adjacent(&op, "photos").await.unwrap();
+        operations::write_full(&op, "photos/2023/c.jpg", b"c", &Default::default())
+            .await
+            .unwrap();
+        assert!(take_listing(&op, "photos/2023/").is_none());
+        assert_eq!(parent_of("a/b/"), Some("a/".into()));
+        assert_eq!(parent_of(""), None);
+    }
+}
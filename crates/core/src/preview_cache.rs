@@ -0,0 +1,117 @@
+//! Local copies of remote files for previews that need random access, such
+//! as SQLite databases.
+//!
+//! Copies live in `previews/` under [`config::cache_dir`], named after the
+//! source and path, next to a stamp with the size, etag and modification
+//! time they were downloaded at. An unchanged file is not downloaded again;
+//! the oldest copies are evicted once the cache outgrows
+//! [`MAX_CACHE_BYTES`].
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use opendal::Operator;
+use sha2::{Digest, Sha256};
+
+use crate::config;
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_opendal_path};
+use crate::tuning;
+
+/// Total size of cached copies kept.
+pub const MAX_CACHE_BYTES: u64 = 1024 * 1024 * 1024;
+
+const DOWNLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// A local path holding the contents of `path`, at most `max_bytes` long.
+/// Local sources return the file itself; others download it into the
+/// cache unless an up-to-date copy is there.
+pub async fn local_copy(op: &Operator, path: &str, max_bytes: u64) -> Result<PathBuf> {
+    let p = normalize_opendal_path(path);
+    let meta = op.stat(&p).await?;
+    if operations::is_local_fs(op) {
+        return operations::local_fs_path(op, &p);
+    }
+    let size = meta.content_length();
+    if size > max_bytes {
+        return Err(CoreError::FileTooLarge(path.to_string(), size, max_bytes));
+    }
+
+    let dir = config::cache_dir().join("previews");
+    let key: String = Sha256::digest(format!("{}\n{p}", tuning::operator_key(op)))
+        .iter()
+        .take(16)
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    let copy = dir.join(&key);
+    let stamp_path = dir.join(format!("{key}.stamp"));
+    let stamp = format!(
+        "{size}\n{}\n{}",
+        meta.etag().unwrap_or_default(),
+        meta.last_modified()
+            .map(|at| at.to_string())
+            .unwrap_or_default()
+    );
+    if copy.exists() && fs::read_to_string(&stamp_path).is_ok_and(|saved| saved == stamp) {
+        return Ok(copy);
+    }
+
+    fs::create_dir_all(&dir)?;
+    let partial = dir.join(format!("{key}.part"));
+    let mut file = fs::File::create(&partial)?;
+    let mut write_error = None;
+    let read = operations::read_chunks(op, &p, DOWNLOAD_CHUNK_SIZE, |chunk| {
+        match file.write_all(chunk) {
+            Ok(()) => true,
+            Err(e) => {
+                write_error = Some(e);
+                false
+            }
+        }
+    })
+    .await;
+    drop(file);
+    let result = match (read, write_error) {
+        (Err(e), _) => Err(e),
+        (_, Some(e)) => Err(e.into()),
+        (Ok(_), None) => fs::rename(&partial, &copy).map_err(CoreError::from),
+    };
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::write(&stamp_path, stamp)?;
+    evict(&dir, &copy);
+    Ok(copy)
+}
+
+/// Remove the least recently downloaded copies, other than `keep`, until the
+/// cache fits [`MAX_CACHE_BYTES`].
+fn evict(dir: &Path, keep: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut copies: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_none())
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((meta.modified().ok()?, meta.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = copies.iter().map(|(_, len, _)| len).sum();
+    copies.sort();
+    for (_, len, path) in copies {
+        if total <= MAX_CACHE_BYTES {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        if fs::remove_file(&path).is_ok() {
+            let _ = fs::remove_file(path.with_extension("stamp"));
+            total -= len;
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Live progress of a copy/move job.
+//!
+//! A [`TransferReport`](crate::transfer_report::TransferReport) with a
+//! [`ProgressSink`] attached reports a [`TransferProgress`] as the job moves
+//! along: the stage, the item being transferred, items and bytes done out of
+//! the totals counted up front, a rolling transfer speed and an ETA derived
+//! from it. Each update also carries a
+//! sentence in the current locale, so screen readers can announce more than
+//! a percentage.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::i18n::Message;
+use crate::util::extract_filename;
+
+/// Receives progress updates; called on the task running the transfer.
+pub type ProgressSink = Arc<dyn Fn(&TransferProgress) + Send + Sync>;
+
+/// Updates within a stage are at most this frequent.
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Time constant of the speed average: a sample this old weighs 1/e of a
+/// fresh one, so the speed follows changes within a few seconds without
+/// jumping on every small file.
+const SPEED_TIME_CONSTANT: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStage {
+    /// Counting the files and bytes to transfer.
+    Preparing,
+    Transferring,
+    /// Waiting for [`crate::pause::resume_all`].
+    Paused,
+    Finished,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransferProgress {
+    pub task_id: String,
+    pub operation: String,
+    pub stage: ProgressStage,
+    /// File name of the item being transferred.
+    pub current_item: Option<String>,
+    pub items_done: usize,
+    pub items_total: Option<usize>,
+    pub bytes_done: u64,
+    pub bytes_total: Option<u64>,
+    /// Exponentially weighted transfer speed.
+    pub bytes_per_sec: Option<u64>,
+    pub eta_secs: Option<u64>,
+    /// Whole percent, when the totals are known.
+    pub percent: Option<u8>,
+    /// The above as a sentence in the current locale.
+    pub description: String,
+}
+
+/// Progress bookkeeping kept alongside a transfer report.
+#[derive(Clone, Default)]
+pub(crate) struct ProgressState {
+    pub(crate) sink: Option<ProgressSink>,
+    pub(crate) stage: Option<ProgressStage>,
+    pub(crate) items_total: Option<usize>,
+    pub(crate) bytes_total: Option<u64>,
+    pub(crate) current_item: Option<String>,
+    /// Bytes of the items in flight transferred so far.
+    pub(crate) item_bytes: u64,
+    pub(crate) transfer_started: Option<Instant>,
+    pub(crate) last_emit: Option<Instant>,
+    /// Bytes transferred since `last_sample`, not yet in `speed`.
+    pending_bytes: u64,
+    last_sample: Option<Instant>,
+    speed: Option<f64>,
+}
+
+impl fmt::Debug for ProgressState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressState")
+            .field("stage", &self.stage)
+            .field("items_total", &self.items_total)
+            .field("bytes_total", &self.bytes_total)
+            .field("current_item", &self.current_item)
+            .field("speed", &self.speed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProgressState {
+    /// Whether an update should go out now: always on a stage change,
+    /// otherwise throttled to [`MIN_INTERVAL`].
+    pub(crate) fn due(&mut self, stage: ProgressStage) -> bool {
+        if self.sink.is_none() {
+            return false;
+        }
+        let now = Instant::now();
+        let due = self.stage != Some(stage)
+            || self
+                .last_emit
+                .is_none_or(|last| now.duration_since(last) >= MIN_INTERVAL);
+        if due {
+            if self.stage == Some(ProgressStage::Paused) {
+                // Time spent paused says nothing about the speed.
+                self.last_sample = Some(now);
+            }
+            if stage == ProgressStage::Transferring && self.transfer_started.is_none() {
+                self.transfer_started = Some(now);
+            }
+            self.stage = Some(stage);
+            self.last_emit = Some(now);
+        }
+        due
+    }
+
+    /// Account for `bytes` just transferred in the rolling speed.
+    pub(crate) fn record(&mut self, bytes: u64) {
+        self.record_at(bytes, Instant::now());
+    }
+
+    fn record_at(&mut self, bytes: u64, now: Instant) {
+        self.pending_bytes += bytes;
+        let Some(since) = self.last_sample.or(self.transfer_started) else {
+            self.last_sample = Some(now);
+            return;
+        };
+        let elapsed = now.duration_since(since).as_secs_f64();
+        // Files finishing back to back would give absurd rates; pool them.
+        if elapsed < 0.05 {
+            return;
+        }
+        let rate = self.pending_bytes as f64 / elapsed;
+        self.speed = Some(match self.speed {
+            Some(speed) => {
+                let weight = 1.0 - (-elapsed / SPEED_TIME_CONSTANT).exp();
+                speed + weight * (rate - speed)
+            }
+            None => rate,
+        });
+        self.pending_bytes = 0;
+        self.last_sample = Some(now);
+    }
+}
+
+/// Counts of a job at one point in time.
+pub(crate) struct Snapshot<'a> {
+    pub task_id: &'a str,
+    pub operation: &'a str,
+    pub items_done: usize,
+    pub bytes_done: u64,
+    pub completed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl TransferProgress {
+    pub(crate) fn new(state: &ProgressState, stage: ProgressStage, snapshot: Snapshot<'_>) -> Self {
+        let elapsed = state
+            .transfer_started
+            .map(|started| started.elapsed().as_secs_f64());
+        let fraction = match (state.bytes_total, state.items_total) {
+            (Some(bytes_total), _) if bytes_total > 0 => {
+                Some(snapshot.bytes_done as f64 / bytes_total as f64)
+            }
+            (_, Some(items_total)) if items_total > 0 => {
+                Some(snapshot.items_done as f64 / items_total as f64)
+            }
+            _ => None,
+        }
+        .map(|fraction| fraction.clamp(0.0, 1.0));
+
+        let speed = state.speed.filter(|speed| *speed > 0.0);
+
+        let (percent, eta_secs) = match stage {
+            ProgressStage::Finished => (Some(100), Some(0)),
+            ProgressStage::Preparing => (None, None),
+            ProgressStage::Paused => (
+                fraction.map(|fraction| (fraction * 100.0).floor() as u8),
+                None,
+            ),
+            ProgressStage::Transferring => (
+                fraction.map(|fraction| (fraction * 100.0).floor() as u8),
+                match (state.bytes_total, speed, fraction, elapsed) {
+                    (Some(bytes_total), Some(speed), _, _) => Some(
+                        (bytes_total.saturating_sub(snapshot.bytes_done) as f64 / speed).round()
+                            as u64,
+                    ),
+                    // Without sizes, assume the rest goes as fast as so far.
+                    (_, _, Some(fraction), Some(elapsed)) if fraction > 0.0 => {
+                        Some((elapsed * (1.0 - fraction) / fraction).round() as u64)
+                    }
+                    _ => None,
+                },
+            ),
+        };
+        let current_item = match stage {
+            ProgressStage::Transferring => state.current_item.as_deref().map(extract_filename),
+            _ => None,
+        };
+
+        let mut progress = Self {
+            task_id: snapshot.task_id.to_string(),
+            operation: snapshot.operation.to_string(),
+            stage,
+            current_item,
+            items_done: snapshot.items_done,
+            items_total: state.items_total,
+            bytes_done: snapshot.bytes_done,
+            bytes_total: state.bytes_total,
+            bytes_per_sec: match stage {
+                ProgressStage::Transferring => speed.map(|speed| speed.round() as u64),
+                _ => None,
+            },
+            eta_secs,
+            percent,
+            description: String::new(),
+        };
+        progress.description = progress.describe(&snapshot);
+        progress
+    }
+
+    fn describe(&self, snapshot: &Snapshot<'_>) -> String {
+        let messages = match self.stage {
+            ProgressStage::Preparing => vec![Message::new("progress-preparing")],
+            ProgressStage::Finished => vec![Message::new("progress-finished")
+                .arg("completed", snapshot.completed)
+                .arg("skipped", snapshot.skipped)
+                .arg("failed", snapshot.failed)],
+            ProgressStage::Transferring | ProgressStage::Paused => {
+                let mut messages = Vec::new();
+                if self.stage == ProgressStage::Paused {
+                    messages.push(Message::new("progress-paused"));
+                }
+                if let Some(item) = &self.current_item {
+                    let id = match self.operation.as_str() {
+                        "move" => "progress-moving",
+                        "upload" => "progress-uploading",
+                        "download" => "progress-downloading",
+                        _ => "progress-copying",
+                    };
+                    messages.push(Message::new(id).arg("item", item));
+                }
+                messages.push(match self.items_total {
+                    Some(total) => Message::new("progress-items")
+                        .arg("done", self.items_done)
+                        .arg("total", total),
+                    None => Message::new("progress-items-no-total").arg("done", self.items_done),
+                });
+                match self.eta_secs {
+                    Some(secs) if secs >= 90 => messages
+                        .push(Message::new("progress-eta-minutes").arg("count", secs.div_ceil(60))),
+                    Some(secs) if secs > 0 => {
+                        messages.push(Message::new("progress-eta-seconds").arg("count", secs))
+                    }
+                    _ => {}
+                }
+                messages
+            }
+        };
+        messages
+            .iter()
+            .filter_map(Message::translate)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_is_a_rolling_average() {
+        let start = Instant::now();
+        let at = |secs: f64| start + Duration::from_secs_f64(secs);
+        let mut state = ProgressState {
+            transfer_started: Some(start),
+            ..ProgressState::default()
+        };
+
+        state.record_at(1_000, at(1.0));
+        assert_eq!(state.speed, Some(1_000.0));
+
+        // Small files finishing together count as one sample.
+        state.record_at(500, at(1.01));
+        assert_eq!(state.speed, Some(1_000.0));
+        state.record_at(2_500, at(2.0));
+        let speed = state.speed.unwrap();
+        assert!(speed > 1_000.0 && speed < 3_000.0, "{speed}");
+
+        // A stall pulls the average down gradually, not to zero.
+        state.record_at(0, at(5.0));
+        let stalled = state.speed.unwrap();
+        assert!(stalled > 0.0 && stalled < speed, "{stalled}");
+    }
+}
@@ -0,0 +1,141 @@
+//! Read-only sources.
+//!
+//! A source marked `readOnly` gets its operator wrapped in [`ReadOnlyLayer`],
+//! which rejects every write, delete, rename, copy and directory creation and
+//! drops them from the operator's capabilities. The restriction travels with
+//! the operator itself, so another source on the same bucket or folder stays
+//! writable.
+
+use opendal::raw::{
+    Access, Layer, LayeredAccess, OpCopy, OpCreateDir, OpList, OpPresign, OpRead, OpRename,
+    OpWrite, PresignOperation, RpCopy, RpCreateDir, RpDelete, RpList, RpPresign, RpRead, RpRename,
+    RpWrite,
+};
+use opendal::{Error, ErrorKind, Operator};
+
+/// Wrap `op` so that it refuses to change anything on the backend. OpenDAL
+/// layers share capabilities with what they wrap, so pass a freshly built
+/// operator rather than one still in use elsewhere.
+pub fn apply(op: Operator) -> Operator {
+    op.layer(ReadOnlyLayer)
+}
+
+/// Whether `op` was wrapped by [`apply`]: the backend can write, but the
+/// operator no longer offers it.
+pub fn is_read_only(op: &Operator) -> bool {
+    let info = op.info();
+    info.native_capability().write && !info.full_capability().write
+}
+
+fn rejected() -> Error {
+    Error::new(ErrorKind::PermissionDenied, "source is read-only")
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ReadOnlyLayer;
+
+impl<A: Access> Layer<A> for ReadOnlyLayer {
+    type LayeredAccess = ReadOnlyAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        inner.info().update_full_capability(|mut cap| {
+            cap.write = false;
+            cap.write_can_append = false;
+            cap.write_can_multi = false;
+            cap.write_can_empty = false;
+            cap.create_dir = false;
+            cap.delete = false;
+            cap.delete_with_version = false;
+            cap.copy = false;
+            cap.rename = false;
+            cap.presign_write = false;
+            cap
+        });
+        ReadOnlyAccessor { inner }
+    }
+}
+
+#[derive(Debug)]
+struct ReadOnlyAccessor<A> {
+    inner: A,
+}
+
+impl<A: Access> LayeredAccess for ReadOnlyAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type Writer = A::Writer;
+    type Lister = A::Lister;
+    type Deleter = A::Deleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, _: &str, _: OpCreateDir) -> opendal::Result<RpCreateDir> {
+        Err(rejected())
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, _: &str, _: OpWrite) -> opendal::Result<(RpWrite, Self::Writer)> {
+        Err(rejected())
+    }
+
+    async fn copy(&self, _: &str, _: &str, _: OpCopy) -> opendal::Result<RpCopy> {
+        Err(rejected())
+    }
+
+    async fn rename(&self, _: &str, _: &str, _: OpRename) -> opendal::Result<RpRename> {
+        Err(rejected())
+    }
+
+    async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+        Err(rejected())
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> opendal::Result<RpPresign> {
+        match args.operation() {
+            PresignOperation::Write(_) | PresignOperation::Delete(_) => Err(rejected()),
+            _ => self.inner.presign(path, args).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fs_operator(root: &std::path::Path) -> Operator {
+        Operator::new(opendal::services::Fs::default().root(&root.to_string_lossy()))
+            .unwrap()
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn only_the_wrapped_operator_is_read_only() {
+        let root = std::env::temp_dir().join(format!("infimount-ro-layer-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let writable = fs_operator(&root);
+        let read_only = apply(fs_operator(&root));
+        writable.write("a.txt", "a").await.unwrap();
+
+        assert!(is_read_only(&read_only));
+        assert!(!is_read_only(&writable));
+        assert_eq!(read_only.read("a.txt").await.unwrap().to_vec(), b"a");
+        assert!(read_only.write("a.txt", "b").await.is_err());
+        assert!(read_only.delete("a.txt").await.is_err());
+        assert!(read_only.rename("a.txt", "b.txt").await.is_err());
+        assert!(read_only.copy("a.txt", "b.txt").await.is_err());
+        assert!(read_only.create_dir("dir/").await.is_err());
+
+        writable.write("a.txt", "c").await.unwrap();
+        assert_eq!(read_only.read("a.txt").await.unwrap().to_vec(), b"c");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,29 +1,45 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
-use futures::TryStreamExt;
 use indexmap::IndexMap;
-use opendal::services::{Azblob, Fs, Gcs, Webdav, S3};
-use opendal::ErrorKind;
+use opendal::services::{Azblob, Fs, Ftp, Gcs, Gdrive, Http, Ipfs, Ipmfs, Webdav, Webhdfs, B2, S3};
 use opendal::Operator;
 use tokio::sync::RwLock;
 
-use crate::config;
+use crate::config::{self, ConfigLock, ConfigStamp};
 use crate::models::{CoreError, Result, Source, SourceKind};
+use crate::operations::{self, SourceCapabilities};
+use crate::read_only;
+use crate::schema;
+use crate::secrets;
+use crate::throttle::BandwidthLimits;
+use crate::tuning::{self, TransferTuning};
 
 /// Registry that maps source IDs to OpenDAL operators.
 ///
 /// Operators are built lazily from `Source` configuration and cached.
+/// Mutations take the config file lock and first pick up changes other
+/// processes made to the file, so concurrent app instances don't clobber
+/// each other.
 pub struct OperatorRegistry {
     sources: RwLock<IndexMap<String, Source>>,
     operators: RwLock<HashMap<String, Operator>>,
+    config_path: PathBuf,
+    /// Config file stamp as of our last read or write.
+    synced: std::sync::Mutex<Option<ConfigStamp>>,
 }
 
 impl OperatorRegistry {
-    /// Create a new registry from a list of configured sources.
+    /// Create a new registry from a list of configured sources, persisted to
+    /// the default config file.
     pub fn new(sources: Vec<Source>) -> Self {
+        Self::with_config_path(sources, config::config_path())
+    }
+
+    /// Create a registry persisted to `config_path`.
+    pub fn with_config_path(sources: Vec<Source>, config_path: PathBuf) -> Self {
         let mut map = IndexMap::new();
         for src in sources {
             map.insert(src.id.clone(), src);
@@ -32,11 +48,15 @@ impl OperatorRegistry {
         Self {
             sources: RwLock::new(map),
             operators: RwLock::new(HashMap::new()),
+            synced: std::sync::Mutex::new(config::stamp(&config_path)),
+            config_path,
         }
     }
 
     /// Return all known sources.
     pub async fn list_sources(&self) -> Vec<Source> {
+        // Best effort: on failure the in-memory list is still usable.
+        let _ = self.reload_if_changed().await;
         self.sources
             .read()
             .await
@@ -45,7 +65,49 @@ impl OperatorRegistry {
             .collect::<Vec<_>>()
     }
 
-    async fn persist_sources(&self) -> Result<()> {
+    fn synced(&self) -> std::sync::MutexGuard<'_, Option<ConfigStamp>> {
+        self.synced
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Reload sources if another process rewrote the config file since we
+    /// last read or wrote it. Returns whether anything was reloaded.
+    pub async fn reload_if_changed(&self) -> Result<bool> {
+        if config::stamp(&self.config_path) == *self.synced() {
+            return Ok(false);
+        }
+        let _lock = ConfigLock::acquire(&self.config_path)?;
+        self.reload_locked().await
+    }
+
+    async fn reload_locked(&self) -> Result<bool> {
+        if config::stamp(&self.config_path) == *self.synced() {
+            return Ok(false);
+        }
+
+        let loaded = config::read_sources(&self.config_path)?;
+        {
+            let mut sources = self.sources.write().await;
+            sources.clear();
+            for source in loaded {
+                sources.insert(source.id.clone(), source);
+            }
+        }
+        self.operators.write().await.clear();
+        // Reading may have upgraded the file in place.
+        *self.synced() = config::stamp(&self.config_path);
+        Ok(true)
+    }
+
+    /// Lock the config file and sync with it ahead of a mutation.
+    async fn lock_for_update(&self) -> Result<ConfigLock> {
+        let lock = ConfigLock::acquire(&self.config_path)?;
+        self.reload_locked().await?;
+        Ok(lock)
+    }
+
+    async fn persist_sources(&self, _lock: &ConfigLock) -> Result<()> {
         let all_sources = self
             .sources
             .read()
@@ -53,7 +115,8 @@ impl OperatorRegistry {
             .values()
             .cloned()
             .collect::<Vec<_>>();
-        config::save_sources(&all_sources)?;
+        config::write_sources(&self.config_path, &all_sources)?;
+        *self.synced() = config::stamp(&self.config_path);
         Ok(())
     }
 
@@ -64,6 +127,7 @@ impl OperatorRegistry {
             validate_source(source)?;
         }
 
+        let lock = ConfigLock::acquire(&self.config_path)?;
         {
             let mut srcs = self.sources.write().await;
             srcs.clear();
@@ -78,12 +142,13 @@ impl OperatorRegistry {
             ops.clear();
         }
 
-        self.persist_sources().await
+        self.persist_sources(&lock).await
     }
 
     /// Add a new source and persist configuration.
     pub async fn add_source(&self, source: Source) -> Result<()> {
         validate_source(&source)?;
+        let lock = self.lock_for_update().await?;
 
         {
             let mut sources = self.sources.write().await;
@@ -98,14 +163,17 @@ impl OperatorRegistry {
         }
 
         // Persist the updated list.
-        self.persist_sources().await
+        self.persist_sources(&lock).await
     }
 
     /// Remove a source by id and persist configuration.
     pub async fn remove_source(&self, source_id: &str) -> Result<()> {
+        let lock = self.lock_for_update().await?;
         {
             let mut sources = self.sources.write().await;
-            sources.shift_remove(source_id);
+            if let Some(removed) = sources.shift_remove(source_id) {
+                secrets::forget(&removed);
+            }
         }
 
         // Remove cached operator reference.
@@ -114,12 +182,13 @@ impl OperatorRegistry {
             ops.remove(source_id);
         }
 
-        self.persist_sources().await
+        self.persist_sources(&lock).await
     }
 
     /// Update an existing source (or add if missing) and persist.
     pub async fn update_source(&self, source: Source) -> Result<()> {
         validate_source(&source)?;
+        let lock = self.lock_for_update().await?;
 
         {
             let mut sources = self.sources.write().await;
@@ -132,7 +201,7 @@ impl OperatorRegistry {
             ops.remove(&source.id);
         }
 
-        self.persist_sources().await
+        self.persist_sources(&lock).await
     }
 
     /// Get (or lazily build) an operator for the given source ID.
@@ -152,7 +221,23 @@ impl OperatorRegistry {
         };
 
         // Build a new operator for this source.
-        let op = build_operator(&source)?;
+        let op = build_operator(&secrets::resolve(&secrets::expand_env(&source)?)?)?;
+        let op = BandwidthLimits::from_config(|key| {
+            source
+                .config
+                .as_ref()
+                .and_then(|config| config.get(key).cloned())
+        })
+        .apply(op);
+        let manual_tuning = source.config.as_ref().and_then(|config| {
+            TransferTuning::override_from_config(|key| config.get(key).cloned())
+        });
+        tuning::set_override(&op, manual_tuning);
+        let op = if source.read_only {
+            read_only::apply(op)
+        } else {
+            op
+        };
 
         // Cache and return.
         let mut ops = self.operators.write().await;
@@ -160,21 +245,27 @@ impl OperatorRegistry {
         Ok(op)
     }
 
+    /// What the source supports (presign, copy, append, versioning, ...).
+    pub async fn get_capabilities(&self, source_id: &str) -> Result<SourceCapabilities> {
+        Ok(operations::capabilities(
+            &self.get_operator(source_id).await?,
+        ))
+    }
+
     /// Verify whether a source configuration is reachable and valid.
     pub async fn verify_source(&self, source: &Source) -> Result<()> {
-        validate_source(source)?;
-        let op = build_operator(source)?;
-        // Trigger a lightweight backend call to validate auth/endpoint/root.
-        let mut lister = match op.lister("").await {
-            Ok(l) => l,
-            Err(err) if err.kind() == ErrorKind::NotFound => op.lister("/").await?,
-            Err(err) => return Err(err.into()),
-        };
-        let _ = lister.try_next().await?;
-        Ok(())
+        operations::probe(&operator_for_source(source)?).await
     }
 }
 
+/// Build an operator for a source that need not be registered (e.g. one the
+/// user is still filling in). Settings are validated against the schema and
+/// `${VAR}` placeholders and keyring references are resolved.
+pub fn operator_for_source(source: &Source) -> Result<Operator> {
+    validate_source(source)?;
+    build_operator(&secrets::resolve(&secrets::expand_env(source)?)?)
+}
+
 fn build_operator(source: &Source) -> Result<Operator> {
     match source.kind {
         SourceKind::Local => build_local_operator(&source.root),
@@ -182,6 +273,13 @@ fn build_operator(source: &Source) -> Result<Operator> {
         SourceKind::WebDav => build_webdav_operator(source),
         SourceKind::AzureBlob => build_azure_blob_operator(source),
         SourceKind::Gcs => build_gcs_operator(source),
+        SourceKind::Ftp => build_ftp_operator(source),
+        SourceKind::Gdrive => build_gdrive_operator(source),
+        SourceKind::B2 => build_b2_operator(source),
+        SourceKind::Hdfs => build_hdfs_operator(source),
+        SourceKind::Ipfs => build_ipfs_operator(source),
+        SourceKind::Ipmfs => build_ipmfs_operator(source),
+        SourceKind::Http => build_http_operator(source),
     }
 }
 
@@ -189,7 +287,7 @@ fn validate_source(source: &Source) -> Result<()> {
     if matches!(source.kind, SourceKind::Local) {
         validate_local_root(&source.root)?;
     }
-    Ok(())
+    schema::validate_source_settings(source)
 }
 
 fn validate_local_root(root: &str) -> Result<()> {
@@ -269,6 +367,21 @@ fn build_s3_operator(source: &Source) -> Result<Operator> {
 
     // Config overrides
     if let Some(config) = &source.config {
+        if let Some(preset) = config.get("provider").and_then(|p| s3_preset(p)) {
+            let region = config
+                .get("region")
+                .map(String::as_str)
+                .filter(|r| !r.is_empty())
+                .unwrap_or(preset.region);
+            builder = builder.region(region);
+            if let Some(template) = preset.endpoint {
+                let account_id = config.get("accountId").map(String::as_str);
+                builder = builder.endpoint(&s3_preset_endpoint(template, region, account_id));
+            }
+            if preset.virtual_host_style {
+                builder = builder.enable_virtual_host_style();
+            }
+        }
         if let Some(bucket) = config.get("bucketName") {
             builder = builder.bucket(bucket);
         }
@@ -290,6 +403,49 @@ fn build_s3_operator(source: &Source) -> Result<Operator> {
     Ok(op)
 }
 
+/// Defaults for S3-compatible providers selected with `config.provider`.
+/// Explicit `region` / `endpoint` config values still win.
+struct S3Preset {
+    region: &'static str,
+    /// Endpoint template; `{region}` and `{accountId}` are substituted.
+    endpoint: Option<&'static str>,
+    virtual_host_style: bool,
+}
+
+fn s3_preset(provider: &str) -> Option<S3Preset> {
+    let preset = match provider {
+        // MinIO is self-hosted, so the endpoint always comes from the user.
+        "minio" => S3Preset {
+            region: "us-east-1",
+            endpoint: None,
+            virtual_host_style: false,
+        },
+        "r2" => S3Preset {
+            region: "auto",
+            endpoint: Some("https://{accountId}.r2.cloudflarestorage.com"),
+            virtual_host_style: false,
+        },
+        "wasabi" => S3Preset {
+            region: "us-east-1",
+            endpoint: Some("https://s3.{region}.wasabisys.com"),
+            virtual_host_style: false,
+        },
+        "digitalocean" => S3Preset {
+            region: "nyc3",
+            endpoint: Some("https://{region}.digitaloceanspaces.com"),
+            virtual_host_style: true,
+        },
+        _ => return None,
+    };
+    Some(preset)
+}
+
+fn s3_preset_endpoint(template: &str, region: &str, account_id: Option<&str>) -> String {
+    template
+        .replace("{region}", region.trim())
+        .replace("{accountId}", account_id.unwrap_or_default().trim())
+}
+
 fn build_webdav_operator(source: &Source) -> Result<Operator> {
     let mut builder = Webdav::default();
 
@@ -396,6 +552,232 @@ fn build_gcs_operator(source: &Source) -> Result<Operator> {
     Ok(op)
 }
 
+fn build_ftp_operator(source: &Source) -> Result<Operator> {
+    let mut builder = Ftp::default();
+
+    // root format: "host" or "ftp://host:port"
+    if !source.root.is_empty() {
+        builder = builder.endpoint(&ftp_endpoint(&source.root, None, None));
+    }
+
+    if let Some(config) = &source.config {
+        if let Some(host) = config.get("host").filter(|h| !h.is_empty()) {
+            let port = config.get("port").map(String::as_str);
+            let tls = config.get("tls").map(|v| v.eq_ignore_ascii_case("true"));
+            builder = builder.endpoint(&ftp_endpoint(host, port, tls));
+        }
+        if let Some(username) = config.get("username") {
+            builder = builder.user(username);
+        }
+        if let Some(password) = config.get("password") {
+            builder = builder.password(password);
+        }
+        if let Some(root_path) = config.get("rootPath") {
+            builder = builder.root(root_path);
+        }
+    }
+
+    let op = Operator::new(builder).map_err(CoreError::Storage)?.finish();
+    Ok(op)
+}
+
+fn build_gdrive_operator(source: &Source) -> Result<Operator> {
+    let mut builder = Gdrive::default();
+
+    // root format: folder path inside "My Drive", e.g. "/Projects"
+    if !source.root.is_empty() {
+        builder = builder.root(&source.root);
+    }
+
+    if let Some(config) = &source.config {
+        if let Some(root_path) = config.get("rootPath") {
+            builder = builder.root(root_path);
+        }
+        // With a refresh token and client credentials OpenDAL refreshes the
+        // access token itself; a bare access token works until it expires.
+        if let Some(refresh_token) = config.get("refreshToken").filter(|t| !t.is_empty()) {
+            builder = builder.refresh_token(refresh_token);
+        }
+        if let Some(access_token) = config.get("accessToken").filter(|t| !t.is_empty()) {
+            builder = builder.access_token(access_token);
+        }
+        if let Some(client_id) = config.get("clientId") {
+            builder = builder.client_id(client_id);
+        }
+        if let Some(client_secret) = config.get("clientSecret") {
+            builder = builder.client_secret(client_secret);
+        }
+    }
+
+    let op = Operator::new(builder).map_err(CoreError::Storage)?.finish();
+    Ok(op)
+}
+
+fn build_b2_operator(source: &Source) -> Result<Operator> {
+    let mut builder = B2::default();
+
+    // root format: bucket name
+    if !source.root.is_empty() {
+        builder = builder.bucket(&source.root);
+    }
+
+    if let Some(config) = &source.config {
+        if let Some(bucket) = config.get("bucket") {
+            builder = builder.bucket(bucket);
+        }
+        if let Some(bucket_id) = config.get("bucketId") {
+            builder = builder.bucket_id(bucket_id);
+        }
+        if let Some(key_id) = config.get("applicationKeyId") {
+            builder = builder.application_key_id(key_id);
+        }
+        if let Some(key) = config.get("applicationKey") {
+            builder = builder.application_key(key);
+        }
+        if let Some(root_path) = config.get("rootPath") {
+            builder = builder.root(root_path);
+        }
+    }
+
+    let op = Operator::new(builder).map_err(CoreError::Storage)?.finish();
+    Ok(op)
+}
+
+/// HDFS is reached over WebHDFS (the NameNode's HTTP API), which needs no
+/// Hadoop client libraries or JVM on the user's machine.
+fn build_hdfs_operator(source: &Source) -> Result<Operator> {
+    let mut builder = Webhdfs::default();
+
+    // root format: NameNode URL, e.g. "http://namenode:9870"
+    if !source.root.is_empty() {
+        builder = builder.endpoint(&source.root);
+    }
+
+    if let Some(config) = &source.config {
+        if let Some(name_node) = config.get("nameNodeUrl") {
+            builder = builder.endpoint(name_node);
+        }
+        if let Some(user_name) = config.get("userName").filter(|u| !u.is_empty()) {
+            builder = builder.user_name(user_name);
+        }
+        if let Some(token) = config.get("delegationToken").filter(|t| !t.is_empty()) {
+            builder = builder.delegation(token);
+        }
+        if let Some(root_path) = config.get("rootPath") {
+            builder = builder.root(root_path);
+        }
+    }
+
+    let op = Operator::new(builder).map_err(CoreError::Storage)?.finish();
+    Ok(op)
+}
+
+/// Browse an immutable IPFS/IPNS path through an HTTP gateway. The gateway
+/// API is read-only, so writes fail with an unsupported-operation error.
+fn build_ipfs_operator(source: &Source) -> Result<Operator> {
+    let mut builder = Ipfs::default();
+
+    // root format: gateway URL, e.g. "https://ipfs.io"
+    if !source.root.is_empty() {
+        builder = builder.endpoint(&source.root);
+    }
+
+    if let Some(config) = &source.config {
+        if let Some(gateway) = config.get("gatewayUrl") {
+            builder = builder.endpoint(gateway);
+        }
+        if let Some(path) = config.get("contentPath") {
+            builder = builder.root(&ipfs_content_root(path));
+        }
+    }
+
+    let op = Operator::new(builder).map_err(CoreError::Storage)?.finish();
+    Ok(op)
+}
+
+/// Read-write access to a node's Mutable File System (MFS) via its RPC API.
+fn build_ipmfs_operator(source: &Source) -> Result<Operator> {
+    let mut builder = Ipmfs::default();
+
+    // root format: node API URL, e.g. "http://127.0.0.1:5001"
+    if !source.root.is_empty() {
+        builder = builder.endpoint(&source.root);
+    }
+
+    if let Some(config) = &source.config {
+        if let Some(api_url) = config.get("apiUrl") {
+            builder = builder.endpoint(api_url);
+        }
+        if let Some(root_path) = config.get("rootPath") {
+            builder = builder.root(root_path);
+        }
+    }
+
+    let op = Operator::new(builder).map_err(CoreError::Storage)?.finish();
+    Ok(op)
+}
+
+/// Browse-only access to a static file server. Listing goes through
+/// [`crate::http_index`]; writes are rejected by the operations layer.
+fn build_http_operator(source: &Source) -> Result<Operator> {
+    let mut builder = Http::default();
+
+    // root format: base URL, e.g. "https://artifacts.example.com"
+    if !source.root.is_empty() {
+        builder = builder.endpoint(&source.root);
+    }
+
+    if let Some(config) = &source.config {
+        if let Some(endpoint) = config.get("endpoint") {
+            builder = builder.endpoint(endpoint);
+        }
+        if let Some(root_path) = config.get("rootPath") {
+            builder = builder.root(root_path);
+        }
+        if let Some(username) = config.get("username").filter(|u| !u.is_empty()) {
+            builder = builder.username(username);
+        }
+        if let Some(password) = config.get("password").filter(|p| !p.is_empty()) {
+            builder = builder.password(password);
+        }
+        if let Some(token) = config.get("token").filter(|t| !t.is_empty()) {
+            builder = builder.token(token);
+        }
+    }
+
+    let op = Operator::new(builder).map_err(CoreError::Storage)?.finish();
+    Ok(op)
+}
+
+/// Turn a bare CID into `/ipfs/<cid>/`; `/ipfs/...` and `/ipns/...` paths
+/// are kept and given a trailing slash.
+fn ipfs_content_root(path: &str) -> String {
+    let path = path.trim().trim_matches('/');
+    if path.starts_with("ipfs/") || path.starts_with("ipns/") {
+        format!("/{path}/")
+    } else {
+        format!("/ipfs/{path}/")
+    }
+}
+
+/// Build an FTP endpoint URL. An explicit scheme on `host` wins; otherwise
+/// `tls` picks between `ftps://` (the default) and plain `ftp://`.
+fn ftp_endpoint(host: &str, port: Option<&str>, tls: Option<bool>) -> String {
+    let host = host.trim().trim_end_matches('/');
+    let mut endpoint = if host.contains("://") {
+        host.to_string()
+    } else if tls == Some(false) {
+        format!("ftp://{host}")
+    } else {
+        format!("ftps://{host}")
+    };
+
+    if let Some(port) = port.map(str::trim).filter(|p| !p.is_empty()) {
+        endpoint = format!("{endpoint}:{port}");
+    }
+    endpoint
+}
+
 fn normalize_gcs_credential(raw: &str) -> Option<String> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
@@ -434,9 +816,9 @@ mod tests {
     use std::fs;
     use std::path::PathBuf;
 
-    fn test_config_path() -> PathBuf {
+    fn test_config_path(name: &str) -> PathBuf {
         let mut p = env::temp_dir();
-        p.push("infimount_test_config.json");
+        p.push(format!("infimount_test_config_{name}.json"));
         p
     }
 
@@ -446,11 +828,10 @@ mod tests {
 
     #[tokio::test]
     async fn add_remove_source_persists() {
-        let cfg = test_config_path();
+        let cfg = test_config_path("add_remove_source_persists");
         reset_config_file(&cfg);
-        env::set_var("INFIMOUNT_CONFIG", &cfg);
 
-        let registry = OperatorRegistry::new(vec![]);
+        let registry = OperatorRegistry::with_config_path(vec![], cfg.clone());
 
         let s = Source {
             id: "test1".to_string(),
@@ -458,6 +839,7 @@ mod tests {
             kind: crate::models::SourceKind::Local,
             root: "/tmp".to_string(),
             config: None,
+            read_only: false,
         };
 
         registry.add_source(s.clone()).await.unwrap();
@@ -473,13 +855,43 @@ mod tests {
         let _ = fs::remove_file(cfg);
     }
 
+    #[tokio::test]
+    async fn capabilities_reflect_backend_and_read_only_flag() {
+        let cfg = test_config_path("capabilities_reflect_backend_and_read_only_flag");
+        reset_config_file(&cfg);
+        let root = env::temp_dir().join(format!("infimount-caps-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+
+        let registry = OperatorRegistry::with_config_path(vec![], cfg.clone());
+        registry
+            .add_source(Source {
+                id: "shared".to_string(),
+                name: "Shared".to_string(),
+                kind: crate::models::SourceKind::Local,
+                root: root.to_string_lossy().into_owned(),
+                config: None,
+                read_only: true,
+            })
+            .await
+            .unwrap();
+
+        let caps = registry.get_capabilities("shared").await.unwrap();
+        assert!(caps.read_only);
+        assert!(caps.list && caps.read);
+        assert!(!caps.write && !caps.delete && !caps.rename && !caps.links);
+        assert!(!caps.presign_read);
+        assert!(registry.get_capabilities("missing").await.is_err());
+
+        let _ = fs::remove_file(cfg);
+        let _ = fs::remove_dir_all(root);
+    }
+
     #[tokio::test]
     async fn sources_are_listed_newest_first() {
-        let cfg = test_config_path();
+        let cfg = test_config_path("sources_are_listed_newest_first");
         reset_config_file(&cfg);
-        env::set_var("INFIMOUNT_CONFIG", &cfg);
 
-        let registry = OperatorRegistry::new(vec![]);
+        let registry = OperatorRegistry::with_config_path(vec![], cfg.clone());
 
         let mk = |id: &str| Source {
             id: id.to_string(),
@@ -487,6 +899,7 @@ mod tests {
             kind: crate::models::SourceKind::Local,
             root: "/tmp".to_string(),
             config: None,
+            read_only: false,
         };
 
         registry.add_source(mk("a")).await.unwrap();
@@ -515,6 +928,39 @@ mod tests {
         let _ = fs::remove_file(cfg);
     }
 
+    #[tokio::test]
+    async fn changes_from_other_instances_are_not_clobbered() {
+        let cfg = test_config_path("changes_from_other_instances_are_not_clobbered");
+        reset_config_file(&cfg);
+
+        let mk = |id: &str| Source {
+            id: id.to_string(),
+            name: id.to_string(),
+            kind: crate::models::SourceKind::Local,
+            root: "/tmp".to_string(),
+            config: None,
+            read_only: false,
+        };
+
+        let first = OperatorRegistry::with_config_path(vec![], cfg.clone());
+        let second = OperatorRegistry::with_config_path(vec![], cfg.clone());
+        first.add_source(mk("from-first")).await.unwrap();
+        second.add_source(mk("from-second")).await.unwrap();
+
+        let ids = |sources: Vec<Source>| sources.into_iter().map(|s| s.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(config::load_sources_from(&cfg).unwrap()),
+            vec!["from-second", "from-first"]
+        );
+        assert_eq!(
+            ids(first.list_sources().await),
+            vec!["from-second", "from-first"]
+        );
+
+        let _ = fs::remove_file(cfg.with_extension("lock"));
+        let _ = fs::remove_file(cfg);
+    }
+
     #[test]
     fn normalize_gcs_credential_encodes_raw_json() {
         let raw = r#"{ "type": "service_account", "project_id": "demo" }"#;
@@ -544,6 +990,52 @@ mod tests {
         assert_eq!(normalize_gcs_credential(&with_ws).unwrap(), encoded);
     }
 
+    #[test]
+    fn s3_presets_fill_region_and_endpoint() {
+        let r2 = s3_preset("r2").unwrap();
+        assert_eq!(r2.region, "auto");
+        assert_eq!(
+            s3_preset_endpoint(r2.endpoint.unwrap(), r2.region, Some("abc123")),
+            "https://abc123.r2.cloudflarestorage.com"
+        );
+
+        let wasabi = s3_preset("wasabi").unwrap();
+        assert_eq!(
+            s3_preset_endpoint(wasabi.endpoint.unwrap(), "eu-central-1", None),
+            "https://s3.eu-central-1.wasabisys.com"
+        );
+
+        assert!(s3_preset("minio").unwrap().endpoint.is_none());
+        assert!(s3_preset("digitalocean").unwrap().virtual_host_style);
+        assert!(s3_preset("aws").is_none());
+    }
+
+    #[test]
+    fn ipfs_content_root_accepts_bare_cids_and_paths() {
+        assert_eq!(
+            ipfs_content_root("bafybeibozpulxtpv5nhfa2ue3dcjx23ndh3gwr5vwllk7ptoyfwnfjjr4q"),
+            "/ipfs/bafybeibozpulxtpv5nhfa2ue3dcjx23ndh3gwr5vwllk7ptoyfwnfjjr4q/"
+        );
+        assert_eq!(
+            ipfs_content_root("/ipns/opendal.apache.org"),
+            "/ipns/opendal.apache.org/"
+        );
+        assert_eq!(ipfs_content_root("/ipfs/Qm123/docs/"), "/ipfs/Qm123/docs/");
+    }
+
+    #[test]
+    fn ftp_endpoint_respects_tls_toggle_and_port() {
+        assert_eq!(ftp_endpoint("nas.local", None, None), "ftps://nas.local");
+        assert_eq!(
+            ftp_endpoint("nas.local", Some("2121"), Some(false)),
+            "ftp://nas.local:2121"
+        );
+        assert_eq!(
+            ftp_endpoint("ftp://legacy.example.com/", None, Some(true)),
+            "ftp://legacy.example.com"
+        );
+    }
+
     #[test]
     fn expand_tilde_home_expands_simple_prefix() {
         std::env::set_var("HOME", "/home/testuser");
@@ -553,11 +1045,10 @@ mod tests {
 
     #[tokio::test]
     async fn add_source_rejects_missing_local_directory() {
-        let cfg = test_config_path();
+        let cfg = test_config_path("add_source_rejects_missing_local_directory");
         reset_config_file(&cfg);
-        env::set_var("INFIMOUNT_CONFIG", &cfg);
 
-        let registry = OperatorRegistry::new(vec![]);
+        let registry = OperatorRegistry::with_config_path(vec![], cfg.clone());
 
         let s = Source {
             id: "missing-dir".to_string(),
@@ -565,6 +1056,7 @@ mod tests {
             kind: crate::models::SourceKind::Local,
             root: "/tmp/infimount-this-path-does-not-exist".to_string(),
             config: None,
+            read_only: false,
         };
 
         let err = registry.add_source(s).await.unwrap_err();
@@ -0,0 +1,707 @@
+//! Resumable uploads of large local files.
+//!
+//! Uploading a file of [`RESUMABLE_MIN_BYTES`] or more records its progress
+//! in `resumable-uploads.json` next to the config: the multipart upload id
+//! and finished parts on S3 sources that registered a client with
+//! [`set_multipart_client`], or the bytes written so far on backends that can
+//! append. An upload that fails, is cancelled or is cut short by quitting the
+//! app keeps its entry, and uploading the same, unchanged file to the same
+//! path again continues where it stopped instead of starting over.
+
+use std::collections::HashMap;
+use std::fs as std_fs;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Instant, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use opendal::{ErrorKind, Operator};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::config;
+use crate::content_type;
+use crate::models::{ErrorCode, Result};
+use crate::multipart;
+use crate::operations::{self, TransferConflictPolicy};
+use crate::s3_api::{self, S3Client};
+use crate::transfer_report::TransferReport;
+use crate::tuning;
+
+/// Files smaller than this are simply uploaded again.
+pub const RESUMABLE_MIN_BYTES: u64 = 64 * 1024 * 1024;
+/// Smallest multipart part; larger files use larger parts.
+const MIN_PART_SIZE: u64 = 16 * 1024 * 1024;
+/// S3 allows 10,000 parts; stay clear of the limit.
+const TARGET_MAX_PARTS: u64 = 9_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadedPart {
+    pub number: u32,
+    pub etag: String,
+}
+
+/// How far an interrupted upload got.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ResumeProgress {
+    /// An S3 multipart upload and the parts it has so far.
+    Multipart {
+        upload_id: String,
+        part_size: u64,
+        parts: Vec<UploadedPart>,
+    },
+    /// A file written by appending; the target holds the first `offset`
+    /// bytes.
+    Append { offset: u64 },
+}
+
+/// A journal entry: an upload that has not finished yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumableUpload {
+    /// Backend written to (scheme, name and root of its operator).
+    pub destination: String,
+    pub target_path: String,
+    pub local_path: String,
+    /// Size and modification time of the local file when the upload
+    /// started; a file that changed since starts over.
+    pub size: u64,
+    pub modified_ms: i64,
+    /// Bytes already stored at the destination.
+    pub uploaded: u64,
+    pub started_at: DateTime<Utc>,
+    pub progress: ResumeProgress,
+}
+
+/// The set of unfinished uploads, optionally backed by a file.
+#[derive(Debug, Default)]
+pub struct Journal {
+    path: Option<PathBuf>,
+    entries: Vec<ResumableUpload>,
+}
+
+impl Journal {
+    /// Journal that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the journal stored at `path`. A missing or unreadable file
+    /// yields an empty journal: the uploads it listed just start over.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = std_fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    /// Load the journal from `resumable-uploads.json` in the config directory.
+    pub fn load_default() -> Self {
+        Self::load(config::data_dir().join("resumable-uploads.json"))
+    }
+
+    pub fn entries(&self) -> &[ResumableUpload] {
+        &self.entries
+    }
+
+    fn find(&self, destination: &str, target_path: &str) -> Option<&ResumableUpload> {
+        self.entries
+            .iter()
+            .find(|e| e.destination == destination && e.target_path == target_path)
+    }
+
+    fn upsert(&mut self, entry: ResumableUpload) -> Result<()> {
+        self.entries.retain(|e| {
+            !(e.destination == entry.destination && e.target_path == entry.target_path)
+        });
+        self.entries.push(entry);
+        self.save()
+    }
+
+    fn remove(&mut self, destination: &str, target_path: &str) -> Result<Option<ResumableUpload>> {
+        let Some(index) = self
+            .entries
+            .iter()
+            .position(|e| e.destination == destination && e.target_path == target_path)
+        else {
+            return Ok(None);
+        };
+        let entry = self.entries.remove(index);
+        self.save()?;
+        Ok(Some(entry))
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let data = serde_json::to_string_pretty(&self.entries)?;
+        config::write_atomic(path, data.as_bytes())
+    }
+}
+
+fn journal() -> &'static Mutex<Journal> {
+    static JOURNAL: OnceLock<Mutex<Journal>> = OnceLock::new();
+    JOURNAL.get_or_init(|| Mutex::new(Journal::load_default()))
+}
+
+fn lock(journal: &Mutex<Journal>) -> std::sync::MutexGuard<'_, Journal> {
+    journal.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn multipart_clients() -> &'static Mutex<HashMap<String, S3Client>> {
+    static CLIENTS: OnceLock<Mutex<HashMap<String, S3Client>>> = OnceLock::new();
+    CLIENTS.get_or_init(Default::default)
+}
+
+/// Register (or clear) the bucket client used for resumable multipart
+/// uploads to the S3 backend behind `op`. Without one, uploads to it are
+/// not resumable.
+pub fn set_multipart_client(op: &Operator, client: Option<S3Client>) {
+    let key = tuning::operator_key(op);
+    let mut clients = multipart_clients()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    match client {
+        Some(client) => clients.insert(key, client),
+        None => clients.remove(&key),
+    };
+}
+
+fn multipart_client(op: &Operator) -> Option<S3Client> {
+    multipart_clients()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&tuning::operator_key(op))
+        .cloned()
+}
+
+/// Whether uploading `size` bytes to `op` goes through the journal.
+pub(crate) fn is_resumable(op: &Operator, size: u64) -> bool {
+    size >= RESUMABLE_MIN_BYTES && (multipart_client(op).is_some() || can_append(op))
+}
+
+fn can_append(op: &Operator) -> bool {
+    // Appending to Azure creates append blobs, which behave differently
+    // from the block blobs regular uploads create.
+    op.info().full_capability().write_can_append && op.info().scheme() != "azblob"
+}
+
+/// Every unfinished upload, oldest first.
+pub fn pending() -> Vec<ResumableUpload> {
+    lock(journal()).entries().to_vec()
+}
+
+/// Unfinished uploads to the backend behind `op`.
+pub fn pending_for(op: &Operator) -> Vec<ResumableUpload> {
+    let destination = tuning::operator_key(op);
+    lock(journal())
+        .entries()
+        .iter()
+        .filter(|e| e.destination == destination)
+        .cloned()
+        .collect()
+}
+
+/// Whether an unfinished upload to `target_path` on `op` is recorded.
+pub(crate) fn has_pending(op: &Operator, target_path: &str) -> bool {
+    lock(journal())
+        .find(&tuning::operator_key(op), target_path)
+        .is_some()
+}
+
+/// Finish every unfinished upload to `op` whose local file is unchanged.
+pub async fn resume_pending(report: &mut TransferReport, op: &Operator) -> Result<()> {
+    let mut files = Vec::new();
+    for entry in pending_for(op) {
+        match file_identity(Path::new(&entry.local_path)).await {
+            Ok((size, modified_ms)) if size == entry.size && modified_ms == entry.modified_ms => {
+                files.push((PathBuf::from(entry.local_path), entry.target_path, size));
+            }
+            // Gone or changed: what was uploaded is of no use any more.
+            _ => {
+                discard(op, &entry.target_path).await?;
+            }
+        }
+    }
+    operations::upload_files(report, op, files, TransferConflictPolicy::Overwrite).await
+}
+
+/// Forget the unfinished upload to `target_path` and delete what it stored
+/// so far. Returns whether there was one.
+pub async fn discard(op: &Operator, target_path: &str) -> Result<bool> {
+    let destination = tuning::operator_key(op);
+    let Some(entry) = lock(journal()).remove(&destination, target_path)? else {
+        return Ok(false);
+    };
+    clean_up(op, &entry).await;
+    Ok(true)
+}
+
+/// Best-effort removal of the stored remains of an abandoned upload.
+async fn clean_up(op: &Operator, entry: &ResumableUpload) {
+    match &entry.progress {
+        ResumeProgress::Multipart { upload_id, .. } => {
+            if let Some(client) = multipart_client(op) {
+                let _ =
+                    multipart::abort(&client, &object_key(op, &entry.target_path), upload_id).await;
+            }
+        }
+        ResumeProgress::Append { .. } => {
+            let _ = op.delete(&entry.target_path).await;
+        }
+    }
+}
+
+/// Upload `src` to `path`, continuing an earlier attempt recorded in the
+/// journal. `progress` is first told about the bytes already uploaded.
+pub(crate) async fn upload_file(
+    op: &Operator,
+    src: &Path,
+    path: &str,
+    progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+) -> Result<u64> {
+    upload_with(journal(), op, src, path, progress).await
+}
+
+async fn upload_with(
+    journal: &Mutex<Journal>,
+    op: &Operator,
+    src: &Path,
+    path: &str,
+    progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+) -> Result<u64> {
+    let (size, modified_ms) = file_identity(src).await?;
+    let destination = tuning::operator_key(op);
+    let local_path = src.to_string_lossy().into_owned();
+    let client = multipart_client(op);
+
+    let earlier = lock(journal).find(&destination, path).cloned();
+    let earlier = match earlier {
+        Some(entry)
+            if entry.local_path == local_path
+                && entry.size == size
+                && entry.modified_ms == modified_ms
+                && matches!(entry.progress, ResumeProgress::Multipart { .. })
+                    == client.is_some() =>
+        {
+            Some(entry)
+        }
+        Some(stale) => {
+            lock(journal).remove(&destination, path)?;
+            clean_up(op, &stale).await;
+            None
+        }
+        None => None,
+    };
+    let entry = ResumableUpload {
+        destination,
+        target_path: path.to_string(),
+        local_path,
+        size,
+        modified_ms,
+        uploaded: 0,
+        started_at: earlier
+            .as_ref()
+            .map_or_else(Utc::now, |entry| entry.started_at),
+        progress: ResumeProgress::Append { offset: 0 },
+    };
+
+    let started = Instant::now();
+    let sent = match client {
+        Some(client) => {
+            let upload = MultipartUpload {
+                journal,
+                client: &client,
+                key: object_key(op, path),
+                entry,
+            };
+            upload
+                .run(src, earlier.map(|e| e.progress), progress)
+                .await?
+        }
+        None => append_upload(journal, op, src, entry, earlier.is_some(), progress).await?,
+    };
+    tuning::record_throughput(op, sent, started.elapsed());
+    Ok(size)
+}
+
+/// Size and modification time (ms since the epoch) of a local file.
+async fn file_identity(src: &Path) -> Result<(u64, i64)> {
+    let meta = fs::metadata(src).await.map_err(|e| {
+        opendal::Error::new(
+            ErrorKind::Unexpected,
+            format!("Failed to stat local file {}: {}", src.display(), e),
+        )
+    })?;
+    let modified_ms = meta
+        .modified()
+        .ok()
+        .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_millis() as i64);
+    Ok((meta.len(), modified_ms))
+}
+
+async fn open_at(src: &Path, offset: u64) -> Result<fs::File> {
+    let mut file = fs::File::open(src).await.map_err(|e| {
+        opendal::Error::new(
+            ErrorKind::Unexpected,
+            format!("Failed to read local file {}: {}", src.display(), e),
+        )
+    })?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    Ok(file)
+}
+
+/// Write `src` by appending chunks, continuing after what the target holds
+/// when `resume` is set. Returns the bytes sent.
+async fn append_upload(
+    journal: &Mutex<Journal>,
+    op: &Operator,
+    src: &Path,
+    mut entry: ResumableUpload,
+    resume: bool,
+    progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+) -> Result<u64> {
+    // The target itself says how much made it, even if the journal was last
+    // saved a chunk earlier.
+    let mut offset = 0;
+    if resume {
+        if let Ok(meta) = op.stat(&entry.target_path).await {
+            if meta.content_length() <= entry.size {
+                offset = meta.content_length();
+            }
+        }
+    }
+    entry.uploaded = offset;
+    entry.progress = ResumeProgress::Append { offset };
+    lock(journal).upsert(entry.clone())?;
+    if offset > 0 {
+        progress(offset)?;
+    }
+
+    let chunk_size = tuning::tuning_for(op).chunk_size as u64;
+    let mut file = open_at(src, offset).await?;
+    let mut sent = 0;
+    loop {
+        let mut chunk = Vec::new();
+        let n = (&mut file).take(chunk_size).read_to_end(&mut chunk).await? as u64;
+        if n == 0 {
+            break;
+        }
+        if offset == 0 {
+            // Replaces whatever the target held before.
+            op.write(&entry.target_path, chunk).await?;
+        } else {
+            op.write_with(&entry.target_path, chunk)
+                .append(true)
+                .await?;
+        }
+        offset += n;
+        sent += n;
+        entry.uploaded = offset;
+        entry.progress = ResumeProgress::Append { offset };
+        lock(journal).upsert(entry.clone())?;
+        progress(n)?;
+    }
+    lock(journal).remove(&entry.destination, &entry.target_path)?;
+    Ok(sent)
+}
+
+/// Object key of `path` below the root of `op`.
+fn object_key(op: &Operator, path: &str) -> String {
+    format!(
+        "{}{}",
+        op.info().root().trim_start_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct InitiateMultipartUploadResult {
+    upload_id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct ListPartsResult {
+    part: Vec<PartElement>,
+    is_truncated: bool,
+    next_part_number_marker: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PartElement {
+    part_number: u32,
+    #[serde(rename = "ETag")]
+    etag: String,
+    size: u64,
+}
+
+struct MultipartUpload<'a> {
+    journal: &'a Mutex<Journal>,
+    client: &'a S3Client,
+    key: String,
+    entry: ResumableUpload,
+}
+
+impl MultipartUpload<'_> {
+    /// Upload the missing parts of `src` and complete the upload. Returns
+    /// the bytes sent.
+    async fn run(
+        mut self,
+        src: &Path,
+        earlier: Option<ResumeProgress>,
+        progress: &mut (dyn FnMut(u64) -> Result<()> + Send),
+    ) -> Result<u64> {
+        let size = self.entry.size;
+        let (upload_id, part_size, mut parts) = match earlier {
+            Some(ResumeProgress::Multipart {
+                upload_id,
+                part_size,
+                ..
+            }) => match self.stored_parts(&upload_id, part_size).await {
+                Ok(parts) => (upload_id, part_size, parts),
+                // Aborted or expired by a lifecycle rule.
+                Err(error) if error.code() == ErrorCode::NotFound => self.create().await?,
+                Err(error) => return Err(error),
+            },
+            _ => self.create().await?,
+        };
+        self.save(&upload_id, part_size, &parts)?;
+        let done: u64 = parts
+            .iter()
+            .map(|part| part_len(size, part_size, part.number))
+            .sum();
+        if done > 0 {
+            progress(done)?;
+        }
+
+        let mut sent = 0;
+        for number in 1..=part_count(size, part_size) {
+            if parts.iter().any(|part| part.number == number) {
+                continue;
+            }
+            let len = part_len(size, part_size, number);
+            let mut body = vec![0; len as usize];
+            open_at(src, u64::from(number - 1) * part_size)
+                .await?
+                .read_exact(&mut body)
+                .await?;
+            let part_number = number.to_string();
+            let (headers, _) = self
+                .client
+                .request_with_headers(
+                    Method::PUT,
+                    &self.key,
+                    &[("partNumber", &part_number), ("uploadId", &upload_id)],
+                    &[],
+                    body,
+                )
+                .await?;
+            let etag = headers
+                .get("etag")
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| {
+                    opendal::Error::new(ErrorKind::Unexpected, "S3 part upload returned no ETag")
+                })?;
+            parts.push(UploadedPart {
+                number,
+                etag: etag.to_string(),
+            });
+            sent += len;
+            self.save(&upload_id, part_size, &parts)?;
+            progress(len)?;
+        }
+
+        parts.sort_by_key(|part| part.number);
+        self.complete(&upload_id, &parts).await?;
+        lock(self.journal).remove(&self.entry.destination, &self.entry.target_path)?;
+        Ok(sent)
+    }
+
+    async fn create(&self) -> Result<(String, u64, Vec<UploadedPart>)> {
+        // The type is fixed when the upload starts, not when it completes.
+        let headers: Vec<(&str, &str)> = content_type::guess(&self.entry.target_path)
+            .map(|content_type| ("content-type", content_type))
+            .into_iter()
+            .collect();
+        let body = self
+            .client
+            .request(
+                Method::POST,
+                &self.key,
+                &[("uploads", "")],
+                &headers,
+                Vec::new(),
+            )
+            .await?;
+        let created: InitiateMultipartUploadResult = s3_api::parse_xml(&body)?;
+        if created.upload_id.is_empty() {
+            return Err(opendal::Error::new(
+                ErrorKind::Unexpected,
+                "S3 did not return an upload id",
+            )
+            .into());
+        }
+        let part_size = self
+            .entry
+            .size
+            .div_ceil(TARGET_MAX_PARTS)
+            .max(MIN_PART_SIZE);
+        Ok((created.upload_id, part_size, Vec::new()))
+    }
+
+    /// Parts S3 holds for the upload. Parts of an unexpected size (cut
+    /// short or from another attempt) are left out and uploaded again.
+    async fn stored_parts(&self, upload_id: &str, part_size: u64) -> Result<Vec<UploadedPart>> {
+        let mut parts = Vec::new();
+        let mut marker = String::new();
+        loop {
+            let mut query = vec![("uploadId", upload_id)];
+            if !marker.is_empty() {
+                query.push(("part-number-marker", &marker));
+            }
+            let body = self
+                .client
+                .request(Method::GET, &self.key, &query, &[], Vec::new())
+                .await?;
+            let page: ListPartsResult = s3_api::parse_xml(&body)?;
+            parts.extend(
+                page.part
+                    .into_iter()
+                    .filter(|part| {
+                        part.number_in(self.entry.size, part_size)
+                            && part.size == part_len(self.entry.size, part_size, part.part_number)
+                    })
+                    .map(|part| UploadedPart {
+                        number: part.part_number,
+                        etag: part.etag,
+                    }),
+            );
+            if !page.is_truncated || page.next_part_number_marker.is_empty() {
+                return Ok(parts);
+            }
+            marker = page.next_part_number_marker;
+        }
+    }
+
+    async fn complete(&self, upload_id: &str, parts: &[UploadedPart]) -> Result<()> {
+        let mut xml = String::from("<CompleteMultipartUpload>");
+        for part in parts {
+            xml.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part.number, part.etag
+            ));
+        }
+        xml.push_str("</CompleteMultipartUpload>");
+        let body = self
+            .client
+            .request(
+                Method::POST,
+                &self.key,
+                &[("uploadId", upload_id)],
+                &[("content-type", "application/xml")],
+                xml.into_bytes(),
+            )
+            .await?;
+        match s3_api::embedded_error(&body) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn save(&mut self, upload_id: &str, part_size: u64, parts: &[UploadedPart]) -> Result<()> {
+        self.entry.uploaded = parts
+            .iter()
+            .map(|part| part_len(self.entry.size, part_size, part.number))
+            .sum();
+        self.entry.progress = ResumeProgress::Multipart {
+            upload_id: upload_id.to_string(),
+            part_size,
+            parts: parts.to_vec(),
+        };
+        lock(self.journal).upsert(self.entry.clone())
+    }
+}
+
+impl PartElement {
+    fn number_in(&self, size: u64, part_size: u64) -> bool {
+        (1..=part_count(size, part_size)).contains(&self.part_number)
+    }
+}
+
+fn part_count(size: u64, part_size: u64) -> u32 {
+    size.div_ceil(part_size).max(1) as u32
+}
+
+/// Length of part `number` (1-based) of a `size`-byte file.
+fn part_len(size: u64, part_size: u64, number: u32) -> u64 {
+    let start = u64::from(number - 1) * part_size;
+    size.saturating_sub(start).min(part_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CoreError;
+    use crate::tuning::TransferTuning;
+
+    #[tokio::test]
+    async fn interrupted_append_upload_continues_where_it_stopped() {
+        let dir = std::env::temp_dir().join(format!("infimount-resume-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("target")).unwrap();
+        let src = dir.join("big.bin");
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&src, &data).unwrap();
+
+        let op = Operator::new(
+            opendal::services::Fs::default().root(&dir.join("target").to_string_lossy()),
+        )
+        .unwrap()
+        .finish();
+        tuning::set_override(
+            &op,
+            Some(TransferTuning {
+                chunk_size: 4096,
+                concurrency: 1,
+            }),
+        );
+        let journal = Mutex::new(Journal::in_memory());
+
+        // Give up after the first chunk, as if the app had quit.
+        let mut seen = 0;
+        let result = upload_with(&journal, &op, &src, "big.bin", &mut |bytes| {
+            seen += bytes;
+            Err(CoreError::Cancelled)
+        })
+        .await;
+        assert!(matches!(result, Err(CoreError::Cancelled)));
+        assert_eq!(seen, 4096);
+        let entry = lock(&journal).entries()[0].clone();
+        assert_eq!(entry.progress, ResumeProgress::Append { offset: 4096 });
+
+        let mut reported = Vec::new();
+        upload_with(&journal, &op, &src, "big.bin", &mut |bytes| {
+            reported.push(bytes);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(reported, vec![4096, 4096, 1808]);
+        assert_eq!(op.read("big.bin").await.unwrap().to_vec(), data);
+        assert!(lock(&journal).entries().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::models::{Result, SourceKind};
+use crate::i18n::Message;
+use crate::models::{CoreError, Result, Source, SourceKind};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageFieldSchema {
@@ -20,6 +23,10 @@ pub struct StorageKindSchema {
     pub id: String,
     pub label: String,
     pub kind: SourceKind,
+    /// Provider preset stored as `config.provider` for S3-compatible
+    /// services (e.g. "r2"); the storage still uses the S3 kind.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
     #[serde(default)]
     pub fields: Vec<StorageFieldSchema>,
 }
@@ -32,3 +39,145 @@ pub fn list_storage_schemas() -> Result<Vec<StorageKindSchema>> {
     let items: Vec<StorageKindSchema> = serde_json::from_str(JSON)?;
     Ok(items)
 }
+
+/// [`list_storage_schemas`] with kind and field labels in the current
+/// locale, where translated. Ids are `schema-kind-<kind id>`, then
+/// `schema-<kind id>-<field>` or `schema-field-<field>`.
+pub fn localized_storage_schemas() -> Result<Vec<StorageKindSchema>> {
+    let mut schemas = list_storage_schemas()?;
+    for schema in &mut schemas {
+        if let Some(label) = Message::new(format!("schema-kind-{}", schema.id)).translate() {
+            schema.label = label;
+        }
+        for field in &mut schema.fields {
+            let specific = Message::new(format!("schema-{}-{}", schema.id, field.name));
+            let generic = Message::new(format!("schema-field-{}", field.name));
+            if let Some(label) = specific.translate().or_else(|| generic.translate()) {
+                field.label = label;
+            }
+        }
+    }
+    Ok(schemas)
+}
+
+impl StorageKindSchema {
+    /// Required fields that are missing or blank in `settings`.
+    pub fn missing_required_fields(
+        &self,
+        settings: &HashMap<String, String>,
+    ) -> Vec<&StorageFieldSchema> {
+        self.fields
+            .iter()
+            .filter(|field| field.required)
+            .filter(|field| {
+                settings
+                    .get(&field.name)
+                    .is_none_or(|value| value.trim().is_empty())
+            })
+            .collect()
+    }
+}
+
+/// Schema describing `kind`. S3-compatible presets share the S3 kind and are
+/// picked by their `provider` value; `None` selects the plain schema.
+pub fn schema_for(kind: &SourceKind, provider: Option<&str>) -> Result<Option<StorageKindSchema>> {
+    Ok(list_storage_schemas()?
+        .into_iter()
+        .find(|schema| &schema.kind == kind && schema.preset.as_deref() == provider))
+}
+
+/// Check a source's settings against the schema for its kind.
+pub fn validate_source_settings(source: &Source) -> Result<()> {
+    let settings = source.settings();
+    let provider = settings.get("provider").map(String::as_str);
+    let Some(schema) = schema_for(&source.kind, provider)? else {
+        return Err(CoreError::Config(format!(
+            "no storage schema for {} sources{}",
+            source.kind,
+            provider
+                .map(|p| format!(" with provider '{p}'"))
+                .unwrap_or_default()
+        )));
+    };
+
+    let missing = schema.missing_required_fields(&settings);
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let labels = missing
+        .iter()
+        .map(|field| field.label.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(CoreError::Config(format!(
+        "{} source is missing required settings: {labels}",
+        schema.label
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(kind: SourceKind, root: &str, config: &[(&str, &str)]) -> Source {
+        Source {
+            id: "id".to_string(),
+            name: "name".to_string(),
+            kind,
+            root: root.to_string(),
+            config: Some(
+                config
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn every_source_kind_has_a_schema() {
+        let schemas = list_storage_schemas().unwrap();
+        for kind in [
+            SourceKind::Local,
+            SourceKind::S3,
+            SourceKind::WebDav,
+            SourceKind::AzureBlob,
+            SourceKind::Gcs,
+            SourceKind::Ftp,
+            SourceKind::Gdrive,
+            SourceKind::B2,
+            SourceKind::Hdfs,
+            SourceKind::Ipfs,
+            SourceKind::Ipmfs,
+            SourceKind::Http,
+        ] {
+            assert!(
+                schemas.iter().any(|s| s.kind == kind && s.preset.is_none()),
+                "{kind}"
+            );
+        }
+    }
+
+    #[test]
+    fn required_settings_are_enforced() {
+        let err = validate_source_settings(&source(SourceKind::Hdfs, "", &[])).unwrap_err();
+        assert!(err.to_string().contains("NameNode"), "{err}");
+
+        validate_source_settings(&source(SourceKind::Hdfs, "http://nn:9870", &[])).unwrap();
+        validate_source_settings(&source(
+            SourceKind::S3,
+            "",
+            &[("bucketName", "b"), ("region", "us-east-1")],
+        ))
+        .unwrap();
+
+        let err = validate_source_settings(&source(
+            SourceKind::S3,
+            "",
+            &[("provider", "r2"), ("bucketName", "b")],
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("Account ID"), "{err}");
+    }
+}
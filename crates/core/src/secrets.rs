@@ -0,0 +1,420 @@
+//! Source credentials kept in the OS keyring (macOS Keychain, Windows
+//! Credential Manager, Secret Service on Linux).
+//!
+//! Config values whose key names a credential (see [`is_secret_key`]) are
+//! moved into the keyring before sources are written to `config.json`; the
+//! file only keeps a `keyring:<account>` reference. The `*_values` functions
+//! work on the string values of any config map; `infimount_mcp::secrets`
+//! applies them to the storage registry, so migrated sources keep resolving.
+//!
+//! Values may also name environment variables as `${VAR}`; those stay in the
+//! config file as written and are expanded by [`expand_env`] when an operator
+//! is built, so headless deployments need no stored secrets at all.
+
+use crate::models::{CoreError, Result, Source};
+
+pub const KEYRING_SERVICE: &str = "infimount";
+pub const REFERENCE_PREFIX: &str = "keyring:";
+
+pub fn is_reference(value: &str) -> bool {
+    value.starts_with(REFERENCE_PREFIX)
+}
+
+/// Config keys holding credentials, lowercased without `_` or `-`: every
+/// field the storage schemas mark `secret: true`, plus the credentials of
+/// rclone imports, crypt remotes and MCP-only backends. Matched exactly, so
+/// metadata such as `tokenExpiresAt` or `credentialPath` stays in the file.
+const SECRET_KEYS: &[&str] = &[
+    "accesskey",
+    "accesskeyid",
+    "accesstoken",
+    "accountkey",
+    "applicationkey",
+    "clientsecret",
+    "credential",
+    "cryptpassword",
+    "cryptpassword2",
+    "delegationtoken",
+    "password",
+    "refreshtoken",
+    "secret",
+    "secretaccesskey",
+    "secretkey",
+    "serviceaccountcredentials",
+    "serviceaccountjson",
+    "sessiontoken",
+    "token",
+];
+
+/// Whether the config value under `key` is a credential kept in the keyring.
+pub fn is_secret_key(key: &str) -> bool {
+    let normalized: String = key
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    SECRET_KEYS.contains(&normalized.as_str())
+}
+
+/// Keyring entry holding the secret stored under `account`.
+pub fn entry(account: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, account)
+}
+
+/// Write `secret` to the keyring under `account`.
+pub fn store(account: &str, secret: &str) -> keyring::Result<()> {
+    entry(account)?.set_password(secret)
+}
+
+/// Delete a keyring entry. Best effort: a leftover entry is harmless.
+pub fn delete(account: &str) {
+    if let Ok(entry) = entry(account) {
+        let _ = entry.delete_credential();
+    }
+}
+
+/// Why the string values of a config map could not be externalized,
+/// resolved or expanded.
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("OS keyring operation failed: {0}")]
+    Keyring(#[from] keyring::Error),
+    #[error("secret '{key}' is missing from the OS keyring")]
+    Missing { key: String },
+    #[error("environment variable '{name}' used by '{key}' is not set")]
+    UnsetVariable { key: String, name: String },
+}
+
+/// Move the plaintext secrets among a config map's string `values` into the
+/// keyring under `<owner>/<key>` and replace them with references. Empty
+/// values, references and `${VAR}` placeholders are left alone.
+pub fn externalize_values<'a, K: AsRef<str>>(
+    owner: &str,
+    values: impl IntoIterator<Item = (K, &'a mut String)>,
+) -> std::result::Result<(), SecretError> {
+    for (key, value) in values {
+        let key = key.as_ref();
+        if !is_secret_key(key)
+            || value.is_empty()
+            || is_reference(value)
+            || has_env_placeholder(value)
+        {
+            continue;
+        }
+
+        let account = format!("{owner}/{key}");
+        store(&account, value)?;
+        *value = format!("{REFERENCE_PREFIX}{account}");
+    }
+    Ok(())
+}
+
+/// Replace every keyring reference among a config map's string `values`
+/// with its secret.
+pub fn resolve_values<'a, K: AsRef<str>>(
+    values: impl IntoIterator<Item = (K, &'a mut String)>,
+) -> std::result::Result<(), SecretError> {
+    for (key, value) in values {
+        let Some(account) = value.strip_prefix(REFERENCE_PREFIX) else {
+            continue;
+        };
+        *value = match entry(account)?.get_password() {
+            Ok(secret) => secret,
+            Err(keyring::Error::NoEntry) => {
+                return Err(SecretError::Missing {
+                    key: key.as_ref().to_string(),
+                })
+            }
+            Err(e) => return Err(e.into()),
+        };
+    }
+    Ok(())
+}
+
+/// Replace `${VAR}` placeholders among a config map's string `values` from
+/// the process environment. Fails if a variable is unset.
+pub fn expand_env_values<'a, K: AsRef<str>>(
+    values: impl IntoIterator<Item = (K, &'a mut String)>,
+) -> std::result::Result<(), SecretError> {
+    for (key, value) in values {
+        if has_env_placeholder(value) {
+            *value = expand_placeholders(value).map_err(|name| SecretError::UnsetVariable {
+                key: key.as_ref().to_string(),
+                name,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Keyring accounts referenced among a config map's string `values`.
+pub fn references<'a>(values: impl IntoIterator<Item = &'a str>) -> impl Iterator<Item = &'a str> {
+    values
+        .into_iter()
+        .filter_map(|value| value.strip_prefix(REFERENCE_PREFIX))
+}
+
+fn source_error(source: &Source, e: SecretError) -> CoreError {
+    CoreError::Config(match e {
+        SecretError::Keyring(e) => format!("OS keyring operation failed: {e}"),
+        SecretError::Missing { key } => format!(
+            "secret '{key}' of source '{}' is missing from the OS keyring",
+            source.name
+        ),
+        SecretError::UnsetVariable { key, name } => format!(
+            "environment variable '{name}' used by '{key}' of source '{}' is not set",
+            source.name
+        ),
+    })
+}
+
+/// Move plaintext values of the source's secret fields into the keyring and
+/// replace them with references.
+pub fn externalize(source: &mut Source) -> Result<()> {
+    let Some(config) = source.config.as_mut() else {
+        return Ok(());
+    };
+    externalize_values(&source.id, config.iter_mut()).map_err(|e| source_error(source, e))
+}
+
+/// Copy of `source` with every keyring reference replaced by its secret.
+pub fn resolve(source: &Source) -> Result<Source> {
+    let mut resolved = source.clone();
+    resolve_values(resolved.config.iter_mut().flatten()).map_err(|e| source_error(source, e))?;
+    Ok(resolved)
+}
+
+/// Copy of `source` with `${VAR}` placeholders in its root and config values
+/// replaced from the process environment. Fails if a variable is unset.
+pub fn expand_env(source: &Source) -> Result<Source> {
+    let mut expanded = source.clone();
+    let root = [("root", &mut expanded.root)];
+    expand_env_values(
+        root.into_iter().chain(
+            expanded
+                .config
+                .iter_mut()
+                .flatten()
+                .map(|(key, value)| (key.as_str(), value)),
+        ),
+    )
+    .map_err(|e| source_error(source, e))?;
+    Ok(expanded)
+}
+
+/// Whether `value` contains a `${VAR}` environment placeholder.
+pub fn has_env_placeholder(value: &str) -> bool {
+    value.contains("${")
+}
+
+/// Replace `${VAR}` placeholders in `value` with environment variables.
+/// Returns the name of the first unset variable as the error. `${` not
+/// followed by a valid variable name and `}` is kept literally.
+pub fn expand_placeholders(value: &str) -> std::result::Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let name = after.find('}').map(|end| &after[..end]).filter(|name| {
+            name.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        let Some(name) = name else {
+            out.push_str("${");
+            rest = after;
+            continue;
+        };
+        out.push_str(&std::env::var(name).map_err(|_| name.to_string())?);
+        rest = &after[name.len() + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Delete the keyring entries referenced by a source that is being removed.
+pub fn forget(source: &Source) {
+    for account in references(source.config.iter().flatten().map(|(_, v)| v.as_str())) {
+        delete(account);
+    }
+}
+
+/// In-memory keyring for tests; the CI environment has no Secret Service.
+#[cfg(any(test, feature = "test-support"))]
+pub mod memory_keyring {
+    use std::any::Any;
+    use std::collections::HashMap;
+    use std::sync::{Mutex, Once, OnceLock};
+
+    use keyring::credential::{Credential, CredentialApi, CredentialBuilderApi};
+
+    fn store() -> &'static Mutex<HashMap<String, Vec<u8>>> {
+        static STORE: OnceLock<Mutex<HashMap<String, Vec<u8>>>> = OnceLock::new();
+        STORE.get_or_init(Default::default)
+    }
+
+    #[derive(Debug)]
+    struct MemoryCredential(String);
+
+    impl CredentialApi for MemoryCredential {
+        fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+            store()
+                .lock()
+                .unwrap()
+                .insert(self.0.clone(), secret.to_vec());
+            Ok(())
+        }
+
+        fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+            store()
+                .lock()
+                .unwrap()
+                .get(&self.0)
+                .cloned()
+                .ok_or(keyring::Error::NoEntry)
+        }
+
+        fn delete_credential(&self) -> keyring::Result<()> {
+            store()
+                .lock()
+                .unwrap()
+                .remove(&self.0)
+                .map(|_| ())
+                .ok_or(keyring::Error::NoEntry)
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[derive(Debug)]
+    struct MemoryBuilder;
+
+    impl CredentialBuilderApi for MemoryBuilder {
+        fn build(
+            &self,
+            _target: Option<&str>,
+            service: &str,
+            user: &str,
+        ) -> keyring::Result<Box<Credential>> {
+            Ok(Box::new(MemoryCredential(format!("{service}\n{user}"))))
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    /// Route all keyring access in this process to the in-memory store.
+    pub fn install() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| keyring::set_default_credential_builder(Box::new(MemoryBuilder)));
+    }
+
+    /// Whether the in-memory store holds an entry for `account`.
+    pub fn contains(account: &str) -> bool {
+        store()
+            .lock()
+            .unwrap()
+            .contains_key(&format!("{}\n{account}", super::KEYRING_SERVICE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SourceKind;
+    use std::collections::HashMap;
+
+    fn source(config: &[(&str, &str)]) -> Source {
+        Source {
+            id: "dav".to_string(),
+            name: "NAS".to_string(),
+            kind: SourceKind::WebDav,
+            root: String::new(),
+            config: Some(
+                config
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn schema_secret_fields_are_secret_keys() {
+        for kind_schema in crate::schema::list_storage_schemas().unwrap() {
+            for field in &kind_schema.fields {
+                assert_eq!(
+                    is_secret_key(&field.name),
+                    field.secret,
+                    "{}.{}",
+                    kind_schema.id,
+                    field.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn credential_metadata_is_not_secret() {
+        assert!(is_secret_key("client_secret"));
+        assert!(is_secret_key("cryptPassword2"));
+        for key in ["tokenExpiresAt", "credentialPath", "credentialId"] {
+            assert!(!is_secret_key(key), "{key}");
+        }
+    }
+
+    #[test]
+    fn secret_fields_move_to_the_keyring() {
+        memory_keyring::install();
+        let mut source = source(&[
+            ("serverUrl", "https://nas.local"),
+            ("username", "me"),
+            ("password", "hunter2"),
+        ]);
+
+        externalize(&mut source).unwrap();
+        let config = source.config.as_ref().unwrap();
+        assert_eq!(config["password"], "keyring:dav/password");
+        assert_eq!(config["username"], "me");
+        assert!(memory_keyring::contains("dav/password"));
+
+        let resolved = resolve(&source).unwrap();
+        assert_eq!(resolved.config.unwrap()["password"], "hunter2");
+
+        forget(&source);
+        assert!(!memory_keyring::contains("dav/password"));
+        assert!(resolve(&source).is_err());
+    }
+
+    #[test]
+    fn env_placeholders_stay_in_the_config_and_expand_at_build_time() {
+        memory_keyring::install();
+        std::env::set_var("INFIMOUNT_TEST_SECRET_KEY", "from-env");
+        let mut source = source(&[
+            ("serverUrl", "https://${INFIMOUNT_TEST_SECRET_KEY}.local"),
+            ("password", "${INFIMOUNT_TEST_SECRET_KEY}"),
+            ("username", "literal-${not a var}"),
+        ]);
+
+        externalize(&mut source).unwrap();
+        assert_eq!(
+            source.config.as_ref().unwrap()["password"],
+            "${INFIMOUNT_TEST_SECRET_KEY}"
+        );
+
+        let config = expand_env(&source).unwrap().config.unwrap();
+        assert_eq!(config["password"], "from-env");
+        assert_eq!(config["serverUrl"], "https://from-env.local");
+        assert_eq!(config["username"], "literal-${not a var}");
+
+        source.root = "${INFIMOUNT_TEST_UNSET_VARIABLE}".to_string();
+        let error = expand_env(&source).unwrap_err().to_string();
+        assert!(error.contains("INFIMOUNT_TEST_UNSET_VARIABLE"));
+    }
+}
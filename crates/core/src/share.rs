@@ -0,0 +1,155 @@
+//! Intake of files shared with the app from elsewhere in the OS.
+//!
+//! The installers register Infimount as a "Send to" / "Open with" target,
+//! so the OS starts it with the shared files as arguments (plain paths or
+//! `file://` URLs). A second launch while the app is running forwards its
+//! arguments to the running instance. [`shared_paths`] picks them out of the
+//! command line and
+//! [`offer`] parks them until the UI asks for them with [`take_pending`],
+//! lets the user choose a destination source and folder, and hands them to
+//! [`intake`].
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use opendal::Operator;
+
+use crate::models::{CoreError, Result};
+use crate::operations::{self, TransferConflictPolicy};
+
+fn pending() -> &'static Mutex<Vec<PathBuf>> {
+    static PENDING: OnceLock<Mutex<Vec<PathBuf>>> = OnceLock::new();
+    PENDING.get_or_init(Default::default)
+}
+
+/// The existing files and folders among command line `args` (without the
+/// program name). Flags are ignored; `file://` URLs are turned into paths and
+/// relative paths are resolved against `cwd`, the working directory of the
+/// process that received the arguments.
+pub fn shared_paths(args: impl IntoIterator<Item = OsString>, cwd: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for arg in args {
+        let path = match arg.to_str() {
+            Some(text) if text.starts_with('-') => continue,
+            Some(text) if text.starts_with("file://") => match file_url_path(text) {
+                Some(path) => path,
+                None => continue,
+            },
+            _ => cwd.join(arg),
+        };
+        if path.exists() && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// `file:///home/me/a%20b.txt` -> `/home/me/a b.txt`.
+fn file_url_path(url: &str) -> Option<PathBuf> {
+    let rest = url.strip_prefix("file://")?;
+    // Skip the (usually empty) host.
+    let path = &rest[rest.find('/')?..];
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    let path = String::from_utf8(decoded).ok()?;
+    // `/C:/Users/...` on Windows.
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// Park shared `paths` until the UI takes them.
+pub fn offer(paths: Vec<PathBuf>) {
+    if paths.is_empty() {
+        return;
+    }
+    let mut pending = pending().lock().unwrap_or_else(|e| e.into_inner());
+    for path in paths {
+        if !pending.contains(&path) {
+            pending.push(path);
+        }
+    }
+}
+
+/// The shared paths waiting for a destination; each is returned once.
+pub fn take_pending() -> Vec<PathBuf> {
+    std::mem::take(&mut *pending().lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Upload externally provided files and folders into `prefix` on `op`.
+/// Every path must exist; nothing is uploaded otherwise. Returns how many
+/// top-level items were uploaded.
+pub async fn intake(op: &Operator, paths: &[PathBuf], prefix: &str) -> Result<usize> {
+    if let Some(missing) = paths.iter().find(|path| !path.exists()) {
+        return Err(CoreError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("shared file no longer exists: {}", missing.display()),
+        )));
+    }
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    let count = paths.len();
+    operations::upload_files_from_paths(
+        op,
+        paths,
+        prefix.to_string(),
+        TransferConflictPolicy::Overwrite,
+    )
+    .await?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    #[tokio::test]
+    async fn shared_files_are_uploaded_to_the_chosen_folder() {
+        let dir = std::env::temp_dir().join(format!("infimount-share-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("shared note.txt");
+        std::fs::write(&file, "hi").unwrap();
+
+        let url = format!("file://{}", file.to_string_lossy().replace(' ', "%20"));
+        let args = [
+            "--flag",
+            url.as_str(),
+            "/definitely/missing",
+            file.to_str().unwrap(),
+            "shared note.txt",
+        ]
+        .map(OsString::from);
+        let paths = shared_paths(args, &dir);
+        assert_eq!(paths, std::slice::from_ref(&file));
+
+        offer(paths.clone());
+        assert_eq!(take_pending(), paths);
+        assert!(take_pending().is_empty());
+
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        assert_eq!(intake(&op, &paths, "inbox").await.unwrap(), 1);
+        assert_eq!(
+            op.read("inbox/shared note.txt").await.unwrap().to_vec(),
+            b"hi"
+        );
+        assert!(intake(&op, &[dir.join("gone.txt")], "inbox").await.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
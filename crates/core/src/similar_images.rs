@@ -0,0 +1,373 @@
+//! Visually similar images, to find duplicate photos that differ only in
+//! resolution, format or recompression.
+//!
+//! Every image gets a 64-bit perceptual hash (pHash): it is shrunk to 32×32
+//! gray pixels, the lowest 8×8 frequencies of its discrete cosine transform
+//! are kept, and each bit says whether one of them lies above their median.
+//! Scaling and recompressing barely move those frequencies, so copies of a
+//! photo hash a few bits apart while different pictures differ in about
+//! half. No trained model is involved.
+//!
+//! Hashes go into the checksum cache (see [`crate::hash_cache`]) under the
+//! `phash` algorithm, keyed by etag and size like checksums, so searching a
+//! folder again only decodes images that are new or changed.
+
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+use std::sync::{Mutex, OnceLock};
+
+use futures::stream::{self, StreamExt};
+use opendal::Operator;
+use serde::Serialize;
+
+use crate::cancel::CancelToken;
+use crate::hash_cache::HashCache;
+use crate::junk;
+use crate::models::Result;
+use crate::operations::{self, normalize_list_path, SEARCH_CONCURRENCY};
+use crate::util::extract_filename;
+
+/// Larger images are skipped.
+pub const MAX_IMAGE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Differing bits up to which two images count as alike when the caller
+/// does not say. Out of 64; recompressed and resized copies usually stay
+/// below 6.
+pub const DEFAULT_MAX_DISTANCE: u32 = 10;
+
+/// Cache algorithm name the hashes are stored under.
+const CACHE_ALGORITHM: &str = "phash";
+
+/// Side of the gray thumbnail the transform runs on.
+const SAMPLE_SIZE: usize = 32;
+/// Side of the block of low frequencies the hash is made of.
+const HASH_SIZE: usize = 8;
+/// Folders below the root searched at most this deep.
+const MAX_DEPTH: usize = 64;
+
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff"];
+
+/// One image of a [`SimilarGroup`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimilarImage {
+    pub path: String,
+    pub size: u64,
+    /// Perceptual hash as 16 hex digits.
+    pub hash: String,
+    /// Bits the hash differs in from the first image of the group.
+    pub distance: u32,
+}
+
+/// Images that look alike.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimilarGroup {
+    /// Largest file first: the likeliest original, to keep when the others
+    /// are removed.
+    pub images: Vec<SimilarImage>,
+}
+
+/// Whether `path` names an image that can be hashed, going by its
+/// extension.
+pub fn is_image(path: &str) -> bool {
+    extract_filename(path)
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// The perceptual hash of the encoded image `data`.
+pub fn phash(data: &[u8]) -> std::result::Result<u64, String> {
+    let image = image::ImageReader::new(std::io::Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .decode()
+        .map_err(|e| e.to_string())?;
+    let gray = image
+        .thumbnail_exact(SAMPLE_SIZE as u32, SAMPLE_SIZE as u32)
+        .to_luma8();
+    let pixels: Vec<f64> = gray.pixels().map(|pixel| f64::from(pixel.0[0])).collect();
+    Ok(hash_of_samples(&pixels))
+}
+
+/// The hash of `SAMPLE_SIZE`² gray values, row by row.
+fn hash_of_samples(pixels: &[f64]) -> u64 {
+    // DCT-II basis for the lowest frequencies: `basis[u][x]`.
+    let basis: Vec<[f64; SAMPLE_SIZE]> = (0..HASH_SIZE)
+        .map(|u| {
+            std::array::from_fn(|x| {
+                ((2 * x + 1) as f64 * u as f64 * PI / (2 * SAMPLE_SIZE) as f64).cos()
+            })
+        })
+        .collect();
+    // Transform the rows, then the columns of what that gave.
+    let rows: Vec<[f64; HASH_SIZE]> = pixels
+        .chunks(SAMPLE_SIZE)
+        .map(|row| std::array::from_fn(|u| row.iter().zip(&basis[u]).map(|(p, c)| p * c).sum()))
+        .collect();
+    let mut coefficients = Vec::with_capacity(HASH_SIZE * HASH_SIZE);
+    for column_basis in &basis {
+        for u in 0..HASH_SIZE {
+            coefficients.push(
+                rows.iter()
+                    .zip(column_basis)
+                    .map(|(row, c)| row[u] * c)
+                    .sum::<f64>(),
+            );
+        }
+    }
+
+    let mut sorted = coefficients.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = (sorted[31] + sorted[32]) / 2.0;
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c > median)
+        .fold(0, |hash, (bit, _)| hash | (1 << bit))
+}
+
+/// Bits two hashes differ in.
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Groups of images under `root` whose hashes are at most `max_distance`
+/// bits apart, through the process-wide cache stored in `hash-cache.json`.
+/// Images that cannot be decoded are left out. Stops early, with the groups
+/// found among the images hashed so far, when `cancel` fires.
+pub async fn find_similar_images(
+    op: &Operator,
+    source_id: &str,
+    root: &str,
+    max_distance: u32,
+    cancel: &CancelToken,
+) -> Result<Vec<SimilarGroup>> {
+    static CACHE: OnceLock<Mutex<HashCache>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashCache::load_default()));
+    similar_groups(op, source_id, root, max_distance, cancel, cache).await
+}
+
+/// A hashed image.
+struct Hashed {
+    path: String,
+    size: u64,
+    hash: u64,
+}
+
+async fn similar_groups(
+    op: &Operator,
+    source_id: &str,
+    root: &str,
+    max_distance: u32,
+    cancel: &CancelToken,
+    cache: &Mutex<HashCache>,
+) -> Result<Vec<SimilarGroup>> {
+    let lock = || cache.lock().unwrap_or_else(|e| e.into_inner());
+    let root = normalize_list_path(root);
+    let mut pending = VecDeque::from([(root.clone(), 0)]);
+    let mut hashed = Vec::new();
+
+    'walk: while let Some((dir, depth)) = pending.pop_front() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let entries = match operations::list_entries(op, &dir).await {
+            Ok(entries) => entries,
+            Err(error) if dir == root => return Err(error),
+            Err(_) => continue,
+        };
+        let mut images = Vec::new();
+        for entry in entries {
+            // AppleDouble `._*` files and the trash are never photos to group.
+            if junk::is_junk(&entry.name) {
+                continue;
+            }
+            let entry_dir = normalize_list_path(&entry.path);
+            if entry.is_dir {
+                if entry_dir != dir && depth < MAX_DEPTH {
+                    pending.push_back((entry_dir, depth + 1));
+                }
+            } else if !entry.online_only && entry.size <= MAX_IMAGE_BYTES && is_image(&entry.path) {
+                let cached = lock()
+                    .get(
+                        source_id,
+                        &entry.path,
+                        CACHE_ALGORITHM,
+                        entry.etag.as_deref(),
+                        entry.size,
+                    )
+                    .and_then(|digest| u64::from_str_radix(digest, 16).ok());
+                match cached {
+                    Some(hash) => hashed.push(Hashed {
+                        path: entry.path,
+                        size: entry.size,
+                        hash,
+                    }),
+                    None => images.push(entry),
+                }
+            }
+        }
+
+        let mut hashes = stream::iter(images)
+            .map(|entry| async move {
+                let data = operations::read_full(op, &entry.path).await.ok();
+                let hash = match data {
+                    Some(data) => tokio::task::spawn_blocking(move || phash(&data).ok())
+                        .await
+                        .ok()
+                        .flatten(),
+                    None => None,
+                };
+                (entry, hash)
+            })
+            .buffered(SEARCH_CONCURRENCY);
+        while let Some((entry, hash)) = hashes.next().await {
+            if cancel.is_cancelled() {
+                break 'walk;
+            }
+            let Some(hash) = hash else {
+                continue;
+            };
+            lock().insert(
+                source_id,
+                &entry.path,
+                CACHE_ALGORITHM,
+                entry.etag,
+                entry.size,
+                format!("{hash:016x}"),
+            );
+            hashed.push(Hashed {
+                path: entry.path,
+                size: entry.size,
+                hash,
+            });
+        }
+    }
+
+    lock().save()?;
+    Ok(group(hashed, max_distance))
+}
+
+/// Images linked by chains of pairs at most `max_distance` apart, in groups
+/// of two or more. Compares every pair, which stays quick for the tens of
+/// thousands of photos a folder holds.
+fn group(mut images: Vec<Hashed>, max_distance: u32) -> Vec<SimilarGroup> {
+    images.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    let mut parent: Vec<usize> = (0..images.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..images.len() {
+        for j in i + 1..images.len() {
+            if distance(images[i].hash, images[j].hash) <= max_distance {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                // The larger image (lower index) stays the root.
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..images.len() {
+        let root = find(&mut parent, i);
+        members.entry(root).or_default().push(i);
+    }
+    let mut groups: Vec<SimilarGroup> = members
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let first = images[members[0]].hash;
+            SimilarGroup {
+                images: members
+                    .into_iter()
+                    .map(|i| SimilarImage {
+                        path: images[i].path.clone(),
+                        size: images[i].size,
+                        hash: format!("{:016x}", images[i].hash),
+                        distance: distance(first, images[i].hash),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.images[0].path.cmp(&b.images[0].path));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `size`² pixels of soft rings and waves, or their mirror image. Plain
+    /// gradients make poor test pictures: most of their frequencies are
+    /// near zero, so their bits flip with the slightest noise.
+    fn picture(size: u32, mirrored: bool, format: image::ImageFormat) -> Vec<u8> {
+        let image = image::GrayImage::from_fn(size, size, |x, y| {
+            let x = if mirrored { size - 1 - x } else { x };
+            let (u, v) = (x as f64 / size as f64, y as f64 / size as f64);
+            let value = 0.5
+                + 0.25 * (7.0 * (u - 0.3).hypot(v - 0.6)).cos()
+                + 0.2 * (5.0 * u * v + 2.0 * u).sin();
+            image::Luma([(value * 255.0) as u8])
+        });
+        let mut data = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut data, format).unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn copies_hash_close_and_other_pictures_far() {
+        let original = phash(&picture(256, false, image::ImageFormat::Png)).unwrap();
+        let resized = phash(&picture(96, false, image::ImageFormat::Jpeg)).unwrap();
+        let other = phash(&picture(256, true, image::ImageFormat::Png)).unwrap();
+        assert!(distance(original, resized) <= DEFAULT_MAX_DISTANCE);
+        assert!(distance(original, other) > 2 * DEFAULT_MAX_DISTANCE);
+        assert!(phash(b"not an image").is_err());
+    }
+
+    #[tokio::test]
+    async fn groups_lookalikes_under_the_root() {
+        let op = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+        let png = image::ImageFormat::Png;
+        op.write("photos/beach.png", picture(256, false, png))
+            .await
+            .unwrap();
+        op.write(
+            "photos/export/beach-small.jpg",
+            picture(96, false, image::ImageFormat::Jpeg),
+        )
+        .await
+        .unwrap();
+        op.write("photos/cliff.png", picture(256, true, png))
+            .await
+            .unwrap();
+        op.write("photos/broken.jpg", "not a jpeg").await.unwrap();
+        op.write("photos/notes.txt", "beach").await.unwrap();
+
+        let cache = Mutex::new(HashCache::in_memory());
+        let groups = similar_groups(
+            &op,
+            "src",
+            "photos",
+            DEFAULT_MAX_DISTANCE,
+            &CancelToken::new(),
+            &cache,
+        )
+        .await
+        .unwrap();
+        assert_eq!(groups.len(), 1);
+        let images = &groups[0].images;
+        let mut paths: Vec<&str> = images.iter().map(|image| image.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, ["photos/beach.png", "photos/export/beach-small.jpg"]);
+        assert!(images[0].size >= images[1].size);
+        assert_eq!(images[0].distance, 0);
+        // Three images decoded; the broken one is tried again next time.
+        assert_eq!(cache.lock().unwrap().len(), 3);
+    }
+}
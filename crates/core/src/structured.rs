@@ -0,0 +1,542 @@
+//! Structured previews of JSON documents and Jupyter notebooks, so the
+//! webview gets a bounded tree instead of a multi-megabyte blob.
+//!
+//! JSON is deserialized straight into a [`JsonNode`] tree of at most a
+//! given number of nodes; everything past the cap is skipped without being
+//! built, and containers keep their full length. Notebooks keep their
+//! cells' sources and text outputs, truncated, while images, HTML and other
+//! rich outputs are reduced to their type and size.
+
+use std::fmt;
+
+use opendal::Operator;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserializer, Serialize};
+use serde_json::Value as Json;
+
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_opendal_path};
+
+/// Nodes [`preview_json`] callers get when they have no preference.
+pub const DEFAULT_JSON_NODES: usize = 5_000;
+
+/// Most nodes one JSON preview returns.
+pub const MAX_JSON_NODES: usize = 100_000;
+
+/// Largest document either preview reads.
+pub const MAX_STRUCTURED_BYTES: u64 = 128 * 1024 * 1024;
+
+/// Characters kept of a JSON string.
+const MAX_STRING_CHARS: usize = 1_000;
+
+/// Cells kept of a notebook.
+const MAX_CELLS: usize = 500;
+
+/// Characters kept of a cell's source.
+const MAX_SOURCE_CHARS: usize = 20_000;
+
+/// Outputs kept per cell.
+const MAX_OUTPUTS_PER_CELL: usize = 10;
+
+/// Characters kept of a text output or traceback.
+const MAX_OUTPUT_CHARS: usize = 4_000;
+
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonNode {
+    Null,
+    Bool {
+        value: bool,
+    },
+    Number {
+        value: serde_json::Number,
+    },
+    /// `value` is cut to its first characters when `length` (in
+    /// characters) is larger.
+    String {
+        value: String,
+        length: usize,
+    },
+    /// `items` are the first elements of `length`.
+    Array {
+        items: Vec<JsonNode>,
+        length: usize,
+    },
+    /// `entries` are the first members of `length`, in document order.
+    Object {
+        entries: Vec<JsonEntry>,
+        length: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonEntry {
+    pub key: String,
+    pub value: JsonNode,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonPreview {
+    pub root: JsonNode,
+    /// Nodes in `root`.
+    pub nodes: usize,
+    /// Elements or members were left out to stay within the node cap.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellKind {
+    Code,
+    Markdown,
+    Raw,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CellOutput {
+    /// A stream (`name` is `stdout` or `stderr`) or a plain text result.
+    Text {
+        name: Option<String>,
+        text: String,
+        truncated: bool,
+    },
+    /// An exception, with its traceback stripped of terminal colours.
+    Error {
+        name: String,
+        value: String,
+        traceback: String,
+        truncated: bool,
+    },
+    /// A result without a text form, e.g. an image or a widget; only its
+    /// MIME type and encoded size are kept.
+    Rich { mime_type: String, bytes: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotebookCell {
+    pub kind: CellKind,
+    pub source: String,
+    pub source_truncated: bool,
+    pub execution_count: Option<u64>,
+    pub outputs: Vec<CellOutput>,
+    /// Outputs past the ones returned.
+    pub omitted_outputs: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotebookPreview {
+    /// Kernel language, e.g. `python`.
+    pub language: Option<String>,
+    pub nbformat: Option<u64>,
+    pub cells: Vec<NotebookCell>,
+    pub total_cells: usize,
+}
+
+/// The JSON document at `path` as a tree of up to `nodes` nodes (at most
+/// [`MAX_JSON_NODES`]).
+pub async fn preview_json(op: &Operator, path: &str, nodes: usize) -> Result<JsonPreview> {
+    let cap = nodes.clamp(1, MAX_JSON_NODES);
+    let data = read_document(op, path).await?;
+    json_tree(&data, cap)
+        .map_err(|e| CoreError::Unsupported(format!("{path} is not valid JSON: {e}")))
+}
+
+/// The cells of the Jupyter notebook at `path`, with sources and text
+/// outputs truncated and rich outputs reduced to their size.
+pub async fn preview_notebook(op: &Operator, path: &str) -> Result<NotebookPreview> {
+    let data = read_document(op, path).await?;
+    let notebook: Json = serde_json::from_slice(&data)
+        .map_err(|e| CoreError::Unsupported(format!("{path} is not valid JSON: {e}")))?;
+    notebook_preview(&notebook)
+        .ok_or_else(|| CoreError::Unsupported(format!("{path} is not a Jupyter notebook")))
+}
+
+async fn read_document(op: &Operator, path: &str) -> Result<Vec<u8>> {
+    let p = normalize_opendal_path(path);
+    let size = op.stat(&p).await?.content_length();
+    if size > MAX_STRUCTURED_BYTES {
+        return Err(CoreError::FileTooLarge(
+            path.to_string(),
+            size,
+            MAX_STRUCTURED_BYTES,
+        ));
+    }
+    let mut data = Vec::with_capacity(size as usize);
+    operations::read_chunks(op, &p, READ_CHUNK_SIZE, |chunk| {
+        data.extend_from_slice(chunk);
+        true
+    })
+    .await?;
+    Ok(data)
+}
+
+fn json_tree(data: &[u8], cap: usize) -> serde_json::Result<JsonPreview> {
+    let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+    let mut de = serde_json::Deserializer::from_slice(data);
+    let mut budget = Budget {
+        remaining: cap,
+        truncated: false,
+    };
+    let root = NodeSeed(&mut budget).deserialize(&mut de)?;
+    de.end()?;
+    Ok(JsonPreview {
+        root,
+        nodes: cap - budget.remaining,
+        truncated: budget.truncated,
+    })
+}
+
+struct Budget {
+    remaining: usize,
+    truncated: bool,
+}
+
+struct NodeSeed<'a>(&'a mut Budget);
+
+impl<'de> DeserializeSeed<'de> for NodeSeed<'_> {
+    type Value = JsonNode;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> std::result::Result<JsonNode, D::Error> {
+        self.0.remaining -= 1;
+        de.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for NodeSeed<'_> {
+    type Value = JsonNode;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<JsonNode, E> {
+        Ok(JsonNode::Null)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> std::result::Result<JsonNode, E> {
+        Ok(JsonNode::Bool { value })
+    }
+
+    fn visit_i64<E>(self, value: i64) -> std::result::Result<JsonNode, E> {
+        Ok(JsonNode::Number {
+            value: value.into(),
+        })
+    }
+
+    fn visit_u64<E>(self, value: u64) -> std::result::Result<JsonNode, E> {
+        Ok(JsonNode::Number {
+            value: value.into(),
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<JsonNode, E> {
+        serde_json::Number::from_f64(value)
+            .map(|value| JsonNode::Number { value })
+            .ok_or_else(|| E::custom("number out of range"))
+    }
+
+    fn visit_str<E>(self, value: &str) -> std::result::Result<JsonNode, E> {
+        let length = value.chars().count();
+        Ok(JsonNode::String {
+            value: value.chars().take(MAX_STRING_CHARS).collect(),
+            length,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<JsonNode, A::Error> {
+        let mut items = Vec::new();
+        let mut length = 0;
+        while self.0.remaining > 0 {
+            match seq.next_element_seed(NodeSeed(&mut *self.0))? {
+                Some(item) => items.push(item),
+                None => return Ok(JsonNode::Array { items, length }),
+            }
+            length += 1;
+        }
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            self.0.truncated = true;
+            length += 1;
+        }
+        Ok(JsonNode::Array { items, length })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<JsonNode, A::Error> {
+        let mut entries = Vec::new();
+        let mut length = 0;
+        while let Some(key) = map.next_key::<String>()? {
+            length += 1;
+            if self.0.remaining > 0 {
+                let value = map.next_value_seed(NodeSeed(&mut *self.0))?;
+                entries.push(JsonEntry { key, value });
+            } else {
+                map.next_value::<IgnoredAny>()?;
+                self.0.truncated = true;
+            }
+        }
+        Ok(JsonNode::Object { entries, length })
+    }
+}
+
+fn notebook_preview(notebook: &Json) -> Option<NotebookPreview> {
+    let object = notebook.as_object()?;
+    // nbformat 4 keeps cells at the top level, 3 in worksheets.
+    let cells: Vec<&Json> = match object.get("cells") {
+        Some(cells) => cells.as_array()?.iter().collect(),
+        None => object
+            .get("worksheets")?
+            .as_array()?
+            .iter()
+            .filter_map(|sheet| sheet.get("cells")?.as_array())
+            .flatten()
+            .collect(),
+    };
+    let metadata = object.get("metadata");
+    let language = metadata
+        .and_then(|m| {
+            m.pointer("/language_info/name")
+                .or_else(|| m.pointer("/kernelspec/language"))
+        })
+        .or_else(|| cells.iter().find_map(|cell| cell.get("language")))
+        .and_then(Json::as_str)
+        .map(str::to_string);
+    Some(NotebookPreview {
+        language,
+        nbformat: object.get("nbformat").and_then(Json::as_u64),
+        total_cells: cells.len(),
+        cells: cells
+            .into_iter()
+            .take(MAX_CELLS)
+            .map(notebook_cell)
+            .collect(),
+    })
+}
+
+fn notebook_cell(cell: &Json) -> NotebookCell {
+    let kind = match cell.get("cell_type").and_then(Json::as_str) {
+        Some("code") => CellKind::Code,
+        Some("markdown") | Some("heading") => CellKind::Markdown,
+        _ => CellKind::Raw,
+    };
+    let (source, source_truncated) = truncate(
+        &multiline(cell.get("source").or_else(|| cell.get("input"))),
+        MAX_SOURCE_CHARS,
+    );
+    let outputs = cell
+        .get("outputs")
+        .and_then(Json::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    NotebookCell {
+        kind,
+        source,
+        source_truncated,
+        execution_count: cell
+            .get("execution_count")
+            .or_else(|| cell.get("prompt_number"))
+            .and_then(Json::as_u64),
+        omitted_outputs: outputs.len().saturating_sub(MAX_OUTPUTS_PER_CELL),
+        outputs: outputs
+            .iter()
+            .take(MAX_OUTPUTS_PER_CELL)
+            .map(cell_output)
+            .collect(),
+    }
+}
+
+fn cell_output(output: &Json) -> CellOutput {
+    match output.get("output_type").and_then(Json::as_str) {
+        Some("stream") => {
+            let (text, truncated) = truncate(&multiline(output.get("text")), MAX_OUTPUT_CHARS);
+            CellOutput::Text {
+                name: output
+                    .get("name")
+                    .or_else(|| output.get("stream"))
+                    .and_then(Json::as_str)
+                    .map(str::to_string),
+                text,
+                truncated,
+            }
+        }
+        Some("error") | Some("pyerr") => {
+            let traceback = output
+                .get("traceback")
+                .and_then(Json::as_array)
+                .map(|lines| {
+                    lines
+                        .iter()
+                        .filter_map(Json::as_str)
+                        .map(strip_ansi)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            let (traceback, truncated) = truncate(&traceback, MAX_OUTPUT_CHARS);
+            let field = |name: &str| {
+                output
+                    .get(name)
+                    .and_then(Json::as_str)
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            CellOutput::Error {
+                name: field("ename"),
+                value: field("evalue"),
+                traceback,
+                truncated,
+            }
+        }
+        _ => {
+            // nbformat 4 nests results under `data`, 3 keeps them inline.
+            let data = output.get("data").unwrap_or(output);
+            if let Some(text) = data.get("text/plain").or_else(|| data.get("text")) {
+                let (text, truncated) = truncate(&multiline(Some(text)), MAX_OUTPUT_CHARS);
+                return CellOutput::Text {
+                    name: None,
+                    text,
+                    truncated,
+                };
+            }
+            let (mime_type, bytes) = data
+                .as_object()
+                .and_then(|data| {
+                    data.iter()
+                        .find(|(key, _)| key.contains('/') || *key == "png" || *key == "html")
+                        .map(|(key, value)| (key.clone(), multiline(Some(value)).len()))
+                })
+                .unwrap_or_default();
+            CellOutput::Rich { mime_type, bytes }
+        }
+    }
+}
+
+/// Notebook text fields are a string or a list of lines.
+fn multiline(value: Option<&Json>) -> String {
+    match value {
+        Some(Json::String(text)) => text.clone(),
+        Some(Json::Array(lines)) => lines.iter().filter_map(Json::as_str).collect(),
+        Some(other @ Json::Object(_)) => other.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> (String, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => (text[..end].to_string(), true),
+        None => (text.to_string(), false),
+    }
+}
+
+/// Drop the terminal colour codes IPython puts in tracebacks.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    #[tokio::test]
+    async fn previews_json_within_node_cap_and_notebook_cells() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        let long = "x".repeat(MAX_STRING_CHARS + 5);
+        op.write(
+            "data.json",
+            format!(r#"{{"name": "{long}", "items": [1, 2.5, true, null, [3]], "tail": {{}}}}"#),
+        )
+        .await
+        .unwrap();
+
+        let full = preview_json(&op, "data.json", 100).await.unwrap();
+        assert!(!full.truncated);
+        assert_eq!(full.nodes, 10);
+        let JsonNode::Object { entries, length } = &full.root else {
+            panic!("expected an object");
+        };
+        assert_eq!(*length, 3);
+        assert!(matches!(
+            &entries[0].value,
+            JsonNode::String { value, length } if value.len() == MAX_STRING_CHARS && *length == long.len()
+        ));
+
+        // Root, "name", "items" and two of its elements.
+        let capped = preview_json(&op, "data.json", 5).await.unwrap();
+        assert!(capped.truncated);
+        assert_eq!(capped.nodes, 5);
+        let JsonNode::Object { entries, length } = &capped.root else {
+            panic!("expected an object");
+        };
+        assert_eq!((entries.len(), *length), (2, 3));
+        assert!(matches!(
+            &entries[1].value,
+            JsonNode::Array { items, length: 5 } if items.len() == 2
+        ));
+
+        let notebook = serde_json::json!({
+            "nbformat": 4,
+            "metadata": {"language_info": {"name": "python"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n", "Intro"]},
+                {
+                    "cell_type": "code",
+                    "execution_count": 3,
+                    "source": "plot()",
+                    "outputs": [
+                        {"output_type": "stream", "name": "stdout", "text": ["done\n"]},
+                        {"output_type": "display_data", "data": {"image/png": "iVBORw0KGgo="}},
+                        {"output_type": "error", "ename": "ValueError", "evalue": "bad",
+                         "traceback": ["\u{1b}[0;31mValueError\u{1b}[0m: bad"]}
+                    ]
+                }
+            ]
+        });
+        op.write("analysis.ipynb", notebook.to_string())
+            .await
+            .unwrap();
+        let preview = preview_notebook(&op, "analysis.ipynb").await.unwrap();
+        assert_eq!(preview.language.as_deref(), Some("python"));
+        assert_eq!(preview.total_cells, 2);
+        assert_eq!(preview.cells[0].kind, CellKind::Markdown);
+        assert_eq!(preview.cells[0].source, "# Title\nIntro");
+        let code = &preview.cells[1];
+        assert_eq!(code.execution_count, Some(3));
+        assert_eq!(
+            code.outputs,
+            vec![
+                CellOutput::Text {
+                    name: Some("stdout".into()),
+                    text: "done\n".into(),
+                    truncated: false,
+                },
+                CellOutput::Rich {
+                    mime_type: "image/png".into(),
+                    bytes: 12,
+                },
+                CellOutput::Error {
+                    name: "ValueError".into(),
+                    value: "bad".into(),
+                    traceback: "ValueError: bad".into(),
+                    truncated: false,
+                },
+            ]
+        );
+
+        assert!(preview_notebook(&op, "data.json").await.is_err());
+    }
+}
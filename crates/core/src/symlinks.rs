@@ -0,0 +1,40 @@
+//! Symbolic links on Local sources.
+//!
+//! Listings mark links ([`crate::models::Entry::is_symlink`]) with where
+//! they point, and the recursive walks over a source (folder sizes, search)
+//! do not descend into linked folders, so a link back up the tree cannot
+//! loop. Folder uploads follow links to the files and folders they point
+//! to, entering each folder once; asked to keep links and with a Local
+//! target, they recreate the links instead.
+
+use std::path::{Path, PathBuf};
+
+/// Where `path` points, as stored in the link; `None` when it is not a
+/// symbolic link.
+pub fn link_target(path: &Path) -> Option<PathBuf> {
+    std::fs::read_link(path).ok()
+}
+
+/// Create a symbolic link at `link` pointing to `target`, stored as given: a
+/// relative target is resolved from the link's folder.
+pub(crate) async fn create(target: PathBuf, link: PathBuf) -> std::io::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&target, &link)
+        }
+        #[cfg(windows)]
+        {
+            let resolved = link
+                .parent()
+                .map_or_else(|| target.clone(), |dir| dir.join(&target));
+            if resolved.is_dir() {
+                std::os::windows::fs::symlink_dir(&target, &link)
+            } else {
+                std::os::windows::fs::symlink_file(&target, &link)
+            }
+        }
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
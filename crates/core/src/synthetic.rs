@@ -0,0 +1,336 @@
+//! Synthetic "stress" source for performance work.
+//!
+//! Nothing is stored: names, sizes and contents are computed from the entry
+//! number, so a source with millions of files costs no memory. Entries are
+//! spread over folders of `entries_per_dir` files each (`dir-000000/`,
+//! `dir-000001/`, ...), or sit directly in the root when a single folder
+//! holds them all. Every stat, read and listing page waits `latency` first
+//! to imitate a remote service. Used by benches and to check that the
+//! virtualized listing and the search index stay fast on huge sources.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use opendal::raw::{oio, Access, AccessorInfo, OpList, OpRead, OpStat, RpList, RpRead, RpStat};
+use opendal::{
+    Buffer, Capability, EntryMode, Error, ErrorKind, Metadata, Operator, OperatorBuilder,
+};
+
+/// Backend id of synthetic sources in storage records.
+pub const SYNTHETIC_BACKEND: &str = "synthetic";
+
+/// Entries returned per simulated listing page; each page pays `latency`.
+const PAGE_SIZE: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticConfig {
+    /// Total number of files.
+    pub entries: u64,
+    /// Files per folder. At least `entries` puts every file in the root.
+    pub entries_per_dir: u64,
+    /// Size of every file in bytes.
+    pub file_size: u64,
+    /// Delay before each stat, read and listing page.
+    pub latency: Duration,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            entries: 1_000_000,
+            entries_per_dir: 10_000,
+            file_size: 4 * 1024,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+impl SyntheticConfig {
+    /// Read `entries`, `entriesPerDir`, `fileSize` and `latencyMs` from source
+    /// config; missing or invalid keys keep their defaults.
+    pub fn from_config(get: impl Fn(&str) -> Option<String>) -> Self {
+        let number = |key: &str| get(key).and_then(|v| v.trim().parse::<u64>().ok());
+        let defaults = Self::default();
+        Self {
+            entries: number("entries").unwrap_or(defaults.entries),
+            entries_per_dir: number("entriesPerDir")
+                .unwrap_or(defaults.entries_per_dir)
+                .max(1),
+            file_size: number("fileSize").unwrap_or(defaults.file_size),
+            latency: number("latencyMs")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.latency),
+        }
+    }
+
+    fn flat(&self) -> bool {
+        self.entries_per_dir >= self.entries
+    }
+
+    fn dir_count(&self) -> u64 {
+        self.entries.div_ceil(self.entries_per_dir)
+    }
+
+    /// Files `start..end` of folder `dir`.
+    fn files_in(&self, dir: u64) -> (u64, u64) {
+        let start = dir.saturating_mul(self.entries_per_dir);
+        (start, (start + self.entries_per_dir).min(self.entries))
+    }
+}
+
+/// A read-only operator over the synthetic tree described by `config`.
+pub fn operator(config: SyntheticConfig) -> Operator {
+    let info = AccessorInfo::default();
+    info.set_scheme(SYNTHETIC_BACKEND);
+    info.set_name(SYNTHETIC_BACKEND);
+    info.set_root("/");
+    info.set_native_capability(Capability {
+        read: true,
+        stat: true,
+        list: true,
+        shared: false,
+        ..Default::default()
+    });
+    OperatorBuilder::new(SyntheticBackend {
+        config,
+        info: Arc::new(info),
+    })
+    .finish()
+}
+
+fn dir_name(dir: u64) -> String {
+    format!("dir-{dir:06}")
+}
+
+fn file_name(file: u64) -> String {
+    format!("file-{file:08}.bin")
+}
+
+/// Byte `offset` of file `file`; varies per file so reads can be checked.
+fn content_byte(file: u64, offset: u64) -> u8 {
+    (file.wrapping_mul(31).wrapping_add(offset) % 251) as u8
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Root,
+    Dir(u64),
+    File(u64),
+}
+
+#[derive(Debug)]
+struct SyntheticBackend {
+    config: SyntheticConfig,
+    info: Arc<AccessorInfo>,
+}
+
+impl SyntheticBackend {
+    fn resolve(&self, path: &str) -> opendal::Result<Node> {
+        let not_found = || Error::new(ErrorKind::NotFound, "synthetic source has no such entry");
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+            return Ok(Node::Root);
+        }
+        let config = &self.config;
+        let (parent, name) = match path.trim_end_matches('/').rsplit_once('/') {
+            Some((parent, name)) => (Some(parent), name),
+            None => (None, path.trim_end_matches('/')),
+        };
+        let number = |prefix: &str, suffix: &str| {
+            name.strip_prefix(prefix)?
+                .strip_suffix(suffix)?
+                .parse::<u64>()
+                .ok()
+        };
+
+        if path.ends_with('/') {
+            let dir = number("dir-", "").filter(|_| parent.is_none() && !config.flat());
+            return match dir {
+                Some(dir) if dir < config.dir_count() && name == dir_name(dir) => {
+                    Ok(Node::Dir(dir))
+                }
+                _ => Err(not_found()),
+            };
+        }
+
+        let file = number("file-", ".bin").filter(|&file| name == file_name(file));
+        let (start, end) = match parent {
+            None if config.flat() => (0, config.entries),
+            Some(parent) if !config.flat() => match self.resolve(&format!("{parent}/"))? {
+                Node::Dir(dir) => config.files_in(dir),
+                _ => return Err(not_found()),
+            },
+            _ => return Err(not_found()),
+        };
+        match file {
+            Some(file) if (start..end).contains(&file) => Ok(Node::File(file)),
+            _ => Err(not_found()),
+        }
+    }
+
+    async fn delay(&self) {
+        if !self.config.latency.is_zero() {
+            tokio::time::sleep(self.config.latency).await;
+        }
+    }
+}
+
+impl Access for SyntheticBackend {
+    type Reader = Buffer;
+    type Writer = ();
+    type Lister = SyntheticLister;
+    type Deleter = ();
+
+    fn info(&self) -> Arc<AccessorInfo> {
+        self.info.clone()
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> opendal::Result<RpStat> {
+        self.delay().await;
+        let metadata = match self.resolve(path)? {
+            Node::Root | Node::Dir(_) => Metadata::new(EntryMode::DIR),
+            Node::File(_) => {
+                Metadata::new(EntryMode::FILE).with_content_length(self.config.file_size)
+            }
+        };
+        Ok(RpStat::new(metadata))
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+        self.delay().await;
+        let Node::File(file) = self.resolve(path)? else {
+            return Err(Error::new(ErrorKind::IsADirectory, "cannot read a folder"));
+        };
+        let range = args.range();
+        let start = range.offset().min(self.config.file_size);
+        let end = range
+            .size()
+            .map_or(self.config.file_size, |size| start.saturating_add(size))
+            .min(self.config.file_size);
+        let data: Vec<u8> = (start..end).map(|at| content_byte(file, at)).collect();
+        Ok((RpRead::new(), Buffer::from(data)))
+    }
+
+    async fn list(&self, path: &str, _: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+        let config = self.config;
+        let (listing, next, end) = match self.resolve(path)? {
+            Node::Root if config.flat() => (
+                Listing::Files {
+                    prefix: String::new(),
+                },
+                0,
+                config.entries,
+            ),
+            Node::Root => (Listing::Dirs, 0, config.dir_count()),
+            Node::Dir(dir) => {
+                let (start, end) = config.files_in(dir);
+                let prefix = format!("{}/", dir_name(dir));
+                (Listing::Files { prefix }, start, end)
+            }
+            Node::File(_) => return Err(Error::new(ErrorKind::NotADirectory, "not a folder")),
+        };
+        Ok((
+            RpList::default(),
+            SyntheticLister {
+                config,
+                listing,
+                next,
+                end,
+                yielded: 0,
+            },
+        ))
+    }
+}
+
+enum Listing {
+    Dirs,
+    Files { prefix: String },
+}
+
+/// Generates one folder's entries on demand, pausing once per page.
+pub struct SyntheticLister {
+    config: SyntheticConfig,
+    listing: Listing,
+    next: u64,
+    end: u64,
+    yielded: u64,
+}
+
+impl oio::List for SyntheticLister {
+    async fn next(&mut self) -> opendal::Result<Option<oio::Entry>> {
+        if self.next >= self.end {
+            return Ok(None);
+        }
+        if self.yielded.is_multiple_of(PAGE_SIZE) && !self.config.latency.is_zero() {
+            tokio::time::sleep(self.config.latency).await;
+        }
+        let n = self.next;
+        self.next += 1;
+        self.yielded += 1;
+        let entry = match &self.listing {
+            Listing::Dirs => {
+                oio::Entry::new(&format!("{}/", dir_name(n)), Metadata::new(EntryMode::DIR))
+            }
+            Listing::Files { prefix } => oio::Entry::new(
+                &format!("{prefix}{}", file_name(n)),
+                Metadata::new(EntryMode::FILE).with_content_length(self.config.file_size),
+            ),
+        };
+        Ok(Some(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::list_entries;
+    use futures::TryStreamExt;
+
+    #[tokio::test]
+    async fn synthetic_source_generates_entries_lazily() {
+        let op = operator(SyntheticConfig {
+            entries: 2_000_000,
+            entries_per_dir: 50_000,
+            file_size: 100,
+            latency: Duration::ZERO,
+        });
+        let dirs = list_entries(&op, "/").await.unwrap();
+        assert_eq!(dirs.len(), 40);
+
+        let mut lister = op.lister("dir-000039/").await.unwrap();
+        let mut files = 0;
+        while let Some(entry) = lister.try_next().await.unwrap() {
+            if entry.metadata().is_file() {
+                files += 1;
+            }
+        }
+        assert_eq!(files, 50_000);
+
+        let path = "dir-000001/file-00050007.bin";
+        assert_eq!(op.stat(path).await.unwrap().content_length(), 100);
+        let tail = op.read_with(path).range(98..).await.unwrap().to_vec();
+        assert_eq!(
+            tail,
+            vec![content_byte(50_007, 98), content_byte(50_007, 99)]
+        );
+
+        // Files live only in the folder their number belongs to.
+        assert!(op.stat("dir-000000/file-00050007.bin").await.is_err());
+        assert!(op.stat("dir-000040/").await.is_err());
+        assert!(op.write("new.txt", "x").await.is_err());
+    }
+
+    #[test]
+    fn config_keys_override_defaults() {
+        let config = SyntheticConfig::from_config(|key| match key {
+            "entries" => Some("5000000".into()),
+            "latencyMs" => Some(" 25 ".into()),
+            "entriesPerDir" => Some("0".into()),
+            _ => None,
+        });
+        assert_eq!(config.entries, 5_000_000);
+        assert_eq!(config.entries_per_dir, 1);
+        assert_eq!(config.latency, Duration::from_millis(25));
+        assert_eq!(config.file_size, SyntheticConfig::default().file_size);
+    }
+}
@@ -0,0 +1,505 @@
+//! First rows of tabular files (CSV, TSV, JSON Lines and Parquet) as
+//! columns and rows, so datasets in buckets can be inspected without
+//! downloading them. Text formats are streamed until enough rows are read;
+//! Parquet is read with range requests, see [`parquet`]. SQLite databases
+//! need random access and are previewed from a local copy, see
+//! [`preview_sqlite`].
+
+mod parquet;
+mod sqlite;
+
+pub use sqlite::SqliteTable;
+
+use indexmap::IndexSet;
+use opendal::Operator;
+use serde::Serialize;
+use serde_json::Value as Json;
+
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_opendal_path};
+use crate::preview_cache;
+
+/// Rows [`preview_table`] callers get when they have no preference.
+pub const DEFAULT_PREVIEW_ROWS: usize = 100;
+
+/// Most rows one preview returns.
+pub const MAX_PREVIEW_ROWS: usize = 10_000;
+
+/// Rows per table [`preview_sqlite`] callers get when they have no
+/// preference.
+pub const DEFAULT_SQLITE_SAMPLE_ROWS: usize = 20;
+
+/// Largest database [`preview_sqlite`] downloads from a remote source.
+pub const MAX_SQLITE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Text read at most, so a file without line breaks is not read whole.
+const MAX_TEXT_BYTES: u64 = 16 * 1024 * 1024;
+
+const TEXT_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    Csv,
+    Tsv,
+    Jsonl,
+    Parquet,
+}
+
+impl TableFormat {
+    /// Format of `path`, by extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let (_, ext) = path.rsplit_once('.')?;
+        match ext.to_ascii_lowercase().as_str() {
+            "csv" => Some(TableFormat::Csv),
+            "tsv" | "tab" => Some(TableFormat::Tsv),
+            "jsonl" | "ndjson" => Some(TableFormat::Jsonl),
+            "parquet" | "pq" => Some(TableFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TableColumn {
+    pub name: String,
+    /// Type recorded in the file (Parquet) or declared for the column
+    /// (SQLite), e.g. `int64` or `decimal(10,2)`; `None` for text formats.
+    pub data_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TablePreview {
+    pub format: TableFormat,
+    pub columns: Vec<TableColumn>,
+    /// One value per column; text cells are strings, Parquet values are
+    /// typed (dates, timestamps and decimals as strings).
+    pub rows: Vec<Vec<Json>>,
+    /// Rows in the whole file, when the format records it (Parquet).
+    pub total_rows: Option<u64>,
+    /// More rows follow the ones returned.
+    pub truncated: bool,
+}
+
+/// Up to `rows` rows (at most [`MAX_PREVIEW_ROWS`]) of the table at `path`.
+pub async fn preview_table(op: &Operator, path: &str, rows: usize) -> Result<TablePreview> {
+    let limit = rows.clamp(1, MAX_PREVIEW_ROWS);
+    let p = normalize_opendal_path(path);
+    let format = TableFormat::from_path(&p).ok_or_else(|| {
+        CoreError::Unsupported(format!(
+            "{path} is not a CSV, TSV, JSON Lines or Parquet file"
+        ))
+    })?;
+    if format == TableFormat::Parquet {
+        let size = op.stat(&p).await?.content_length();
+        return parquet::preview(op, &p, size, limit).await;
+    }
+
+    let mut parser = match format {
+        TableFormat::Jsonl => TextParser::Jsonl(JsonlParser::default()),
+        _ => TextParser::Csv(CsvParser::new(
+            (format == TableFormat::Tsv).then_some(b'\t'),
+        )),
+    };
+    let mut complete = true;
+    let mut error = None;
+    operations::read_chunks(op, &p, TEXT_CHUNK_SIZE, |chunk| {
+        if let Err(e) = parser.feed(chunk) {
+            error = Some(e);
+            return false;
+        }
+        // One row past the limit tells whether there is more.
+        if parser.rows() > limit {
+            complete = false;
+            return false;
+        }
+        if parser.bytes() >= MAX_TEXT_BYTES {
+            complete = false;
+            return false;
+        }
+        true
+    })
+    .await?;
+    if let Some(line) = error {
+        return Err(CoreError::Unsupported(format!(
+            "{path}: line {line} is not valid JSON"
+        )));
+    }
+    if complete {
+        parser.finish().map_err(|line| {
+            CoreError::Unsupported(format!("{path}: line {line} is not valid JSON"))
+        })?;
+    }
+    let (columns, mut rows) = parser.into_table();
+    let truncated = !complete || rows.len() > limit;
+    rows.truncate(limit);
+    Ok(TablePreview {
+        format,
+        columns: columns
+            .into_iter()
+            .map(|name| TableColumn {
+                name,
+                data_type: None,
+            })
+            .collect(),
+        rows,
+        total_rows: None,
+        truncated,
+    })
+}
+
+/// The tables of the SQLite database at `path`, with their row counts and
+/// up to `rows` rows each (at most [`MAX_PREVIEW_ROWS`]). Remote databases
+/// of up to [`MAX_SQLITE_BYTES`] are downloaded into the preview cache
+/// first, along with their `-wal` file; the database is opened read-only.
+pub async fn preview_sqlite(op: &Operator, path: &str, rows: usize) -> Result<Vec<SqliteTable>> {
+    let limit = rows.clamp(1, MAX_PREVIEW_ROWS);
+    let local = preview_cache::local_copy(op, path, MAX_SQLITE_BYTES).await?;
+    if !operations::is_local_fs(op) {
+        copy_wal(op, path, &local).await?;
+    }
+    let tables = tokio::task::spawn_blocking(move || sqlite::read_tables(&local, limit))
+        .await
+        .map_err(|e| CoreError::Unsupported(format!("{path}: {e}")))?;
+    tables.map_err(|e| CoreError::Unsupported(format!("{path}: {e}")))
+}
+
+/// Put the `-wal` file of the remote database at `path` next to its copy
+/// `local`, where SQLite looks for it, or remove a stale one the database
+/// no longer has.
+async fn copy_wal(op: &Operator, path: &str, local: &std::path::Path) -> Result<()> {
+    let wal = format!("{}-wal", normalize_opendal_path(path));
+    let mut target = local.as_os_str().to_owned();
+    target.push("-wal");
+    if op.exists(&wal).await? {
+        let copy = preview_cache::local_copy(op, &wal, MAX_SQLITE_BYTES).await?;
+        tokio::fs::copy(&copy, &target).await?;
+    } else if let Err(e) = tokio::fs::remove_file(&target).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+enum TextParser {
+    Csv(CsvParser),
+    Jsonl(JsonlParser),
+}
+
+impl TextParser {
+    /// Errors carry the number of the line that could not be parsed.
+    fn feed(&mut self, chunk: &[u8]) -> std::result::Result<(), usize> {
+        match self {
+            TextParser::Csv(csv) => {
+                csv.feed(chunk);
+                Ok(())
+            }
+            TextParser::Jsonl(jsonl) => jsonl.feed(chunk),
+        }
+    }
+
+    fn finish(&mut self) -> std::result::Result<(), usize> {
+        match self {
+            TextParser::Csv(csv) => {
+                csv.finish();
+                Ok(())
+            }
+            TextParser::Jsonl(jsonl) => jsonl.finish(),
+        }
+    }
+
+    /// Data rows read so far (without a CSV header).
+    fn rows(&self) -> usize {
+        match self {
+            TextParser::Csv(csv) => csv.records.len().saturating_sub(1),
+            TextParser::Jsonl(jsonl) => jsonl.rows.len(),
+        }
+    }
+
+    fn bytes(&self) -> u64 {
+        match self {
+            TextParser::Csv(csv) => csv.bytes,
+            TextParser::Jsonl(jsonl) => jsonl.bytes,
+        }
+    }
+
+    fn into_table(self) -> (Vec<String>, Vec<Vec<Json>>) {
+        match self {
+            TextParser::Csv(csv) => csv.into_table(),
+            TextParser::Jsonl(jsonl) => jsonl.into_table(),
+        }
+    }
+}
+
+/// Streaming RFC 4180 reader on `csv_core`, the push parser behind the
+/// `csv` crate: quoted fields may hold delimiters, doubled quotes and line
+/// breaks. Without a fixed delimiter, the one used most in the first line
+/// (`,`, `;`, tab or `|`) is picked.
+struct CsvParser {
+    delimiter: Option<u8>,
+    reader: Box<csv_core::Reader>,
+    /// Start of the first line, kept until the delimiter is known.
+    pending: Vec<u8>,
+    records: Vec<Vec<String>>,
+    /// Fields of the record being read, and where each of them ends.
+    fields: Vec<u8>,
+    ends: Vec<usize>,
+    fields_len: usize,
+    ends_len: usize,
+    bytes: u64,
+}
+
+impl CsvParser {
+    fn new(delimiter: Option<u8>) -> Self {
+        Self {
+            delimiter,
+            reader: Box::new(
+                csv_core::ReaderBuilder::new()
+                    .delimiter(delimiter.unwrap_or(b','))
+                    .build(),
+            ),
+            pending: Vec::new(),
+            records: Vec::new(),
+            fields: vec![0; 1024],
+            ends: vec![0; 32],
+            fields_len: 0,
+            ends_len: 0,
+            bytes: 0,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        let first = self.bytes == 0;
+        self.bytes += chunk.len() as u64;
+        let chunk = match chunk.strip_prefix(b"\xef\xbb\xbf") {
+            Some(rest) if first => rest,
+            _ => chunk,
+        };
+        // An empty input would tell the reader the data has ended.
+        if chunk.is_empty() {
+            return;
+        }
+        if self.delimiter.is_none() {
+            self.pending.extend_from_slice(chunk);
+            if self.pending.contains(&b'\n') {
+                self.sniff();
+            }
+            return;
+        }
+        self.parse(chunk);
+    }
+
+    fn sniff(&mut self) {
+        let first_line = self.pending.split(|&b| b == b'\n').next().unwrap_or(&[]);
+        let delimiter = [b',', b';', b'\t', b'|']
+            .into_iter()
+            .max_by_key(|d| first_line.iter().filter(|&b| b == d).count())
+            .filter(|d| first_line.contains(d))
+            .unwrap_or(b',');
+        self.delimiter = Some(delimiter);
+        *self.reader = csv_core::ReaderBuilder::new().delimiter(delimiter).build();
+        let pending = std::mem::take(&mut self.pending);
+        if !pending.is_empty() {
+            self.parse(&pending);
+        }
+    }
+
+    /// Read the records in `input`; an empty input marks the end of the data.
+    fn parse(&mut self, mut input: &[u8]) {
+        use csv_core::ReadRecordResult;
+
+        loop {
+            let (result, read, written, ended) = self.reader.read_record(
+                input,
+                &mut self.fields[self.fields_len..],
+                &mut self.ends[self.ends_len..],
+            );
+            input = &input[read..];
+            self.fields_len += written;
+            self.ends_len += ended;
+            match result {
+                ReadRecordResult::InputEmpty | ReadRecordResult::End => return,
+                ReadRecordResult::OutputFull => {
+                    let len = self.fields.len() * 2;
+                    self.fields.resize(len, 0);
+                }
+                ReadRecordResult::OutputEndsFull => {
+                    let len = self.ends.len() * 2;
+                    self.ends.resize(len, 0);
+                }
+                ReadRecordResult::Record => self.end_record(),
+            }
+        }
+    }
+
+    fn end_record(&mut self) {
+        let mut start = 0;
+        let record: Vec<String> = self.ends[..self.ends_len]
+            .iter()
+            .map(|&end| {
+                let field = String::from_utf8_lossy(&self.fields[start..end]).into_owned();
+                start = end;
+                field
+            })
+            .collect();
+        self.fields_len = 0;
+        self.ends_len = 0;
+        // Blank lines separate nothing.
+        if !(record.len() == 1 && record[0].is_empty()) {
+            self.records.push(record);
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.delimiter.is_none() {
+            self.sniff();
+        }
+        self.parse(&[]);
+    }
+
+    fn into_table(self) -> (Vec<String>, Vec<Vec<Json>>) {
+        let mut records = self.records.into_iter();
+        let mut columns: Vec<String> = records.next().unwrap_or_default();
+        let rows: Vec<Vec<String>> = records.collect();
+        let width = rows
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(columns.len());
+        columns.resize(width, String::new());
+        for (i, name) in columns.iter_mut().enumerate() {
+            if name.trim().is_empty() {
+                *name = format!("column {}", i + 1);
+            }
+        }
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let mut row: Vec<Json> = row.into_iter().map(Json::String).collect();
+                row.resize(width, Json::Null);
+                row
+            })
+            .collect();
+        (columns, rows)
+    }
+}
+
+/// One JSON value per line; objects are spread over columns named by their
+/// keys, anything else goes to a `value` column.
+#[derive(Default)]
+struct JsonlParser {
+    partial: Vec<u8>,
+    line: usize,
+    rows: Vec<serde_json::Map<String, Json>>,
+    bytes: u64,
+}
+
+impl JsonlParser {
+    fn feed(&mut self, chunk: &[u8]) -> std::result::Result<(), usize> {
+        self.bytes += chunk.len() as u64;
+        self.partial.extend_from_slice(chunk);
+        let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        let rest = self.partial.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        for line in complete.split(|&b| b == b'\n') {
+            self.line(line)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::result::Result<(), usize> {
+        let rest = std::mem::take(&mut self.partial);
+        self.line(&rest)
+    }
+
+    fn line(&mut self, line: &[u8]) -> std::result::Result<(), usize> {
+        self.line += 1;
+        let line = line.strip_prefix(b"\xef\xbb\xbf").unwrap_or(line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+        let row = match serde_json::from_slice(line).map_err(|_| self.line)? {
+            Json::Object(map) => map,
+            other => serde_json::Map::from_iter([("value".to_string(), other)]),
+        };
+        self.rows.push(row);
+        Ok(())
+    }
+
+    fn into_table(self) -> (Vec<String>, Vec<Vec<Json>>) {
+        let columns: IndexSet<String> = self
+            .rows
+            .iter()
+            .flat_map(|row| row.keys().cloned())
+            .collect();
+        let rows = self
+            .rows
+            .into_iter()
+            .map(|mut row| {
+                columns
+                    .iter()
+                    .map(|name| row.remove(name).unwrap_or(Json::Null))
+                    .collect()
+            })
+            .collect();
+        (columns.into_iter().collect(), rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn previews_csv_and_json_lines() {
+        let op = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+        op.write(
+            "data/people.csv",
+            "\u{feff}name;city;note\r\nAda;London;\"said \"\"hi\"\"\nthen left\"\r\n\r\nLinus;Helsinki\r\nGrace;Arlington;x\r\n",
+        )
+        .await
+        .unwrap();
+        op.write(
+            "data/events.jsonl",
+            "{\"id\":1,\"kind\":\"open\"}\n{\"id\":2,\"user\":{\"name\":\"b\"}}\n\n7\n",
+        )
+        .await
+        .unwrap();
+
+        let csv = preview_table(&op, "/data/people.csv", 2).await.unwrap();
+        assert_eq!(csv.format, TableFormat::Csv);
+        let names: Vec<_> = csv.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["name", "city", "note"]);
+        assert_eq!(
+            csv.rows,
+            vec![
+                vec![
+                    Json::from("Ada"),
+                    Json::from("London"),
+                    Json::from("said \"hi\"\nthen left")
+                ],
+                vec![Json::from("Linus"), Json::from("Helsinki"), Json::Null],
+            ]
+        );
+        assert!(csv.truncated);
+
+        let jsonl = preview_table(&op, "data/events.jsonl", 10).await.unwrap();
+        let names: Vec<_> = jsonl.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "kind", "user", "value"]);
+        assert_eq!(jsonl.rows.len(), 3);
+        assert_eq!(jsonl.rows[1][2], serde_json::json!({"name": "b"}));
+        assert_eq!(jsonl.rows[2][3], Json::from(7));
+        assert!(!jsonl.truncated);
+
+        assert!(matches!(
+            preview_table(&op, "data/notes.txt", 10).await,
+            Err(CoreError::Unsupported(_))
+        ));
+    }
+}
@@ -0,0 +1,353 @@
+//! Reads the first rows of a Parquet file with range requests: the footer,
+//! then the column chunks of as many row groups as the preview needs, so a
+//! large dataset in a bucket is never downloaded whole. Decoding is left to
+//! the `parquet` crate.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use opendal::Operator;
+use parquet::basic::{ConvertedType, LogicalType, Repetition, Type as PhysicalType};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::ParquetMetaDataReader;
+use parquet::file::properties::ReaderProperties;
+use parquet::file::reader::{ChunkReader, Length, RowGroupReader};
+use parquet::file::serialized_reader::SerializedRowGroupReader;
+use parquet::file::FOOTER_SIZE;
+use parquet::schema::types::Type;
+
+use super::{TableColumn, TableFormat, TablePreview};
+use crate::models::{CoreError, Result};
+
+/// Largest footer or column chunk this reader is willing to fetch.
+const MAX_BLOCK_BYTES: u64 = 64 * 1024 * 1024;
+
+pub(super) async fn preview(
+    op: &Operator,
+    path: &str,
+    size: u64,
+    limit: usize,
+) -> Result<TablePreview> {
+    if size < 2 * FOOTER_SIZE as u64 {
+        return Err(invalid(path, "file is too small"));
+    }
+    let tail = read(op, path, size - FOOTER_SIZE as u64, size).await?;
+    let tail: &[u8; FOOTER_SIZE] = tail[..].try_into().expect("footer-sized read");
+    let footer = ParquetMetaDataReader::decode_footer_tail(tail).map_err(|e| error(path, e))?;
+    let meta_len = footer.metadata_length() as u64;
+    if meta_len + 2 * FOOTER_SIZE as u64 > size || meta_len > MAX_BLOCK_BYTES {
+        return Err(invalid(path, "bad footer length"));
+    }
+    let meta_end = size - FOOTER_SIZE as u64;
+    let meta = read(op, path, meta_end - meta_len, meta_end).await?;
+    let metadata = ParquetMetaDataReader::decode_metadata(&meta).map_err(|e| error(path, e))?;
+
+    // Fetch the column chunks of the row groups the preview reaches into.
+    let mut fetched = Fetched {
+        size,
+        ranges: Vec::new(),
+    };
+    let mut groups = 0;
+    let mut reached = 0u64;
+    for group in metadata.row_groups() {
+        if reached >= limit as u64 {
+            break;
+        }
+        for column in group.columns() {
+            let (start, len) = column.byte_range();
+            if len > MAX_BLOCK_BYTES {
+                return Err(invalid(path, "column chunk is too large to preview"));
+            }
+            let data = read(op, path, start, start + len).await?;
+            fetched.ranges.push((start, data));
+        }
+        groups += 1;
+        reached += u64::try_from(group.num_rows()).unwrap_or(0);
+    }
+
+    let fetched = Arc::new(fetched);
+    let props = Arc::new(ReaderProperties::builder().build());
+    let mut rows = Vec::new();
+    for group in &metadata.row_groups()[..groups] {
+        let reader = SerializedRowGroupReader::new(fetched.clone(), group, None, props.clone())
+            .map_err(|e| error(path, e))?;
+        for row in reader.get_row_iter(None).map_err(|e| error(path, e))? {
+            if rows.len() == limit {
+                break;
+            }
+            let row = row.map_err(|e| error(path, e))?;
+            rows.push(
+                row.get_column_iter()
+                    .map(|(_, field)| field.to_json_value())
+                    .collect(),
+            );
+        }
+    }
+
+    let total_rows = u64::try_from(metadata.file_metadata().num_rows()).ok();
+    let schema = metadata.file_metadata().schema_descr();
+    Ok(TablePreview {
+        format: TableFormat::Parquet,
+        columns: schema
+            .root_schema()
+            .get_fields()
+            .iter()
+            .map(|field| TableColumn {
+                name: field.name().to_string(),
+                data_type: Some(data_type(field)),
+            })
+            .collect(),
+        truncated: total_rows.is_some_and(|total| total > rows.len() as u64),
+        rows,
+        total_rows,
+    })
+}
+
+async fn read(op: &Operator, path: &str, start: u64, end: u64) -> Result<Bytes> {
+    Ok(op.read_with(path).range(start..end).await?.to_bytes())
+}
+
+fn invalid(path: &str, reason: &str) -> CoreError {
+    CoreError::Unsupported(format!("{path} is not a readable Parquet file: {reason}"))
+}
+
+fn error(path: &str, error: ParquetError) -> CoreError {
+    invalid(path, &error.to_string())
+}
+
+/// The byte ranges fetched from the file, served to the row group readers.
+struct Fetched {
+    size: u64,
+    ranges: Vec<(u64, Bytes)>,
+}
+
+impl Length for Fetched {
+    fn len(&self) -> u64 {
+        self.size
+    }
+}
+
+impl ChunkReader for Fetched {
+    type T = bytes::buf::Reader<Bytes>;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        // Ask for a byte so a chunk ending where the next begins isn't picked.
+        let (offset, data) = self.range(start, 1)?;
+        Ok(bytes::Buf::reader(data.slice(offset..)))
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        let (offset, data) = self.range(start, length)?;
+        Ok(data.slice(offset..offset + length))
+    }
+}
+
+impl Fetched {
+    /// The fetched range holding `length` bytes from `start`, and where in
+    /// it they begin.
+    fn range(&self, start: u64, length: usize) -> parquet::errors::Result<(usize, &Bytes)> {
+        self.ranges
+            .iter()
+            .find_map(|(begin, data)| {
+                let offset = usize::try_from(start.checked_sub(*begin)?).ok()?;
+                (offset + length <= data.len()).then_some((offset, data))
+            })
+            .ok_or_else(|| ParquetError::General(format!("bytes at {start} were not fetched")))
+    }
+}
+
+/// How a column's type is shown, e.g. `int64`, `decimal(7,2)` or
+/// `list<int32>`.
+fn data_type(field: &Type) -> String {
+    if field.get_basic_info().repetition() == Repetition::REPEATED {
+        format!("list<{}>", element_type(field))
+    } else {
+        element_type(field)
+    }
+}
+
+fn element_type(field: &Type) -> String {
+    let info = field.get_basic_info();
+    let logical = info.logical_type();
+    let converted = info.converted_type();
+    if field.is_group() {
+        let is_list =
+            matches!(logical, Some(LogicalType::List)) || converted == ConvertedType::LIST;
+        let is_map = matches!(logical, Some(LogicalType::Map))
+            || matches!(converted, ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE);
+        return match field.get_fields() {
+            [repeated] if is_list => match repeated.get_fields() {
+                // Three-level lists wrap each element in a repeated group.
+                [element] if repeated.is_group() => format!("list<{}>", data_type(element)),
+                _ => format!("list<{}>", element_type(repeated)),
+            },
+            _ if is_map => "map".to_string(),
+            _ => "struct".to_string(),
+        };
+    }
+    match logical {
+        Some(LogicalType::String | LogicalType::Enum | LogicalType::Json) => {
+            return "string".to_string()
+        }
+        Some(LogicalType::Date) => return "date".to_string(),
+        Some(LogicalType::Timestamp { .. }) => return "timestamp".to_string(),
+        Some(LogicalType::Uuid) => return "uuid".to_string(),
+        Some(LogicalType::Decimal { scale, precision }) => {
+            return format!("decimal({precision},{scale})")
+        }
+        Some(LogicalType::Integer {
+            bit_width,
+            is_signed: false,
+        }) => return if bit_width > 32 { "uint64" } else { "uint32" }.to_string(),
+        _ => {}
+    }
+    match converted {
+        ConvertedType::UTF8 | ConvertedType::ENUM | ConvertedType::JSON => {
+            return "string".to_string()
+        }
+        ConvertedType::DATE => return "date".to_string(),
+        ConvertedType::TIMESTAMP_MILLIS | ConvertedType::TIMESTAMP_MICROS => {
+            return "timestamp".to_string()
+        }
+        ConvertedType::DECIMAL => {
+            return format!("decimal({},{})", field.get_precision(), field.get_scale())
+        }
+        ConvertedType::UINT_8 | ConvertedType::UINT_16 | ConvertedType::UINT_32 => {
+            return "uint32".to_string()
+        }
+        ConvertedType::UINT_64 => return "uint64".to_string(),
+        _ => {}
+    }
+    match field {
+        Type::PrimitiveType {
+            physical_type,
+            type_length,
+            ..
+        } => match physical_type {
+            PhysicalType::BOOLEAN => "boolean".to_string(),
+            PhysicalType::INT32 => "int32".to_string(),
+            PhysicalType::INT64 => "int64".to_string(),
+            PhysicalType::INT96 => "timestamp".to_string(),
+            PhysicalType::FLOAT => "float".to_string(),
+            PhysicalType::DOUBLE => "double".to_string(),
+            PhysicalType::FIXED_LEN_BYTE_ARRAY => format!("fixed_len_binary({type_length})"),
+            PhysicalType::BYTE_ARRAY => "binary".to_string(),
+        },
+        Type::GroupType { .. } => "struct".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::basic::Compression;
+    use parquet::data_type::{ByteArray, ByteArrayType, Int32Type, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use serde_json::Value as Json;
+
+    /// id, an optional name, an optional list of optional ints and a
+    /// decimal, Snappy-compressed.
+    fn sample_file() -> Vec<u8> {
+        let schema = parse_message_type(
+            "message sales {
+                required int64 id;
+                optional binary name (UTF8);
+                optional group tags (LIST) {
+                    repeated group list {
+                        optional int32 element;
+                    }
+                }
+                required int32 price (DECIMAL(7,2));
+            }",
+        )
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut file = Vec::new();
+        let mut writer =
+            SerializedFileWriter::new(&mut file, Arc::new(schema), Arc::new(props)).unwrap();
+        let mut group = writer.next_row_group().unwrap();
+
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&[1, 2, 3, 4], None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        let mut column = group.next_column().unwrap().unwrap();
+        let names: Vec<ByteArray> = ["ada", "ada", "bob"].map(ByteArray::from).to_vec();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&names, Some(&[1, 0, 1, 1]), None)
+            .unwrap();
+        column.close().unwrap();
+
+        // [1, 2], [], null, [null, 7].
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int32Type>()
+            .write_batch(
+                &[1, 2, 7],
+                Some(&[3, 3, 1, 0, 2, 3]),
+                Some(&[0, 1, 0, 0, 0, 1]),
+            )
+            .unwrap();
+        column.close().unwrap();
+
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int32Type>()
+            .write_batch(&[1999, -5, 100, 0], None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        group.close().unwrap();
+        writer.close().unwrap();
+        file
+    }
+
+    #[tokio::test]
+    async fn reads_the_first_rows() {
+        let op = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+        op.write("sales.parquet", sample_file()).await.unwrap();
+
+        let table = super::super::preview_table(&op, "sales.parquet", 10)
+            .await
+            .unwrap();
+        let columns: Vec<_> = table
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("id", "int64"),
+                ("name", "string"),
+                ("tags", "list<int32>"),
+                ("price", "decimal(7,2)")
+            ]
+        );
+        let json = serde_json::json!([
+            [1, "ada", [1, 2], "19.99"],
+            [2, null, [], "-0.05"],
+            [3, "ada", null, "1.00"],
+            [4, "bob", [null, 7], "0.00"],
+        ]);
+        assert_eq!(serde_json::to_value(&table.rows).unwrap(), json);
+        assert_eq!((table.total_rows, table.truncated), (Some(4), false));
+
+        let head = super::super::preview_table(&op, "sales.parquet", 3)
+            .await
+            .unwrap();
+        assert_eq!(head.rows.len(), 3);
+        assert_eq!(head.rows[2][2], Json::Null);
+        assert!(head.truncated);
+    }
+}
@@ -0,0 +1,186 @@
+//! Read-only reader for SQLite database files.
+//!
+//! The database is opened with `SQLITE_OPEN_READ_ONLY`, so SQLite itself
+//! reads it, including changes still in a `-wal` file next to it. Tables
+//! come from the schema table; each is counted and its first rows selected.
+
+use std::path::Path;
+
+use base64::Engine;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde_json::Value as Json;
+
+use super::TableColumn;
+
+/// A table of the database and its first rows.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SqliteTable {
+    pub name: String,
+    pub columns: Vec<TableColumn>,
+    /// `None` for virtual tables, which keep no rows in the file.
+    pub row_count: Option<u64>,
+    /// One value per column; blobs are base64 strings.
+    pub rows: Vec<Vec<Json>>,
+    /// More rows follow the ones returned.
+    pub truncated: bool,
+}
+
+/// Every table of the database at `path` with up to `limit` rows each.
+pub fn read_tables(path: &Path, limit: usize) -> rusqlite::Result<Vec<SqliteTable>> {
+    let db = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    let schema = db
+        .prepare(
+            "SELECT name, sql FROM sqlite_master \
+             WHERE type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\' \
+             ORDER BY rowid",
+        )?
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut tables = Vec::new();
+    for (name, sql) in schema {
+        let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+        let is_virtual = sql.is_some_and(|sql| {
+            sql.trim_start()
+                .get(..20)
+                .is_some_and(|start| start.eq_ignore_ascii_case("CREATE VIRTUAL TABLE"))
+        });
+        if is_virtual {
+            // Their modules may not be built in; list the columns if they are.
+            let columns = db
+                .prepare(&format!("SELECT * FROM {quoted} LIMIT 0"))
+                .map(|select| columns(&select))
+                .unwrap_or_default();
+            tables.push(SqliteTable {
+                name,
+                columns,
+                row_count: None,
+                rows: Vec::new(),
+                truncated: false,
+            });
+            continue;
+        }
+
+        let row_count: i64 =
+            db.query_row(&format!("SELECT count(*) FROM {quoted}"), [], |row| {
+                row.get(0)
+            })?;
+        let row_count = u64::try_from(row_count).unwrap_or(0);
+        let mut select = db.prepare(&format!("SELECT * FROM {quoted} LIMIT ?1"))?;
+        let columns = columns(&select);
+        let width = columns.len();
+        let rows = select
+            .query_map([i64::try_from(limit).unwrap_or(i64::MAX)], |row| {
+                (0..width)
+                    .map(|i| row.get_ref(i).map(value))
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        tables.push(SqliteTable {
+            name,
+            columns,
+            row_count: Some(row_count),
+            truncated: row_count > rows.len() as u64,
+            rows,
+        });
+    }
+    Ok(tables)
+}
+
+/// The columns `select` returns, with their declared types.
+fn columns(select: &rusqlite::Statement<'_>) -> Vec<TableColumn> {
+    select
+        .columns()
+        .into_iter()
+        .map(|column| TableColumn {
+            name: column.name().to_string(),
+            data_type: column.decl_type().map(str::to_string),
+        })
+        .collect()
+}
+
+fn value(value: ValueRef<'_>) -> Json {
+    match value {
+        ValueRef::Null => Json::Null,
+        ValueRef::Integer(int) => Json::from(int),
+        ValueRef::Real(real) => serde_json::Number::from_f64(real).map_or(Json::Null, Json::Number),
+        ValueRef::Text(text) => Json::from(String::from_utf8_lossy(text)),
+        ValueRef::Blob(blob) => Json::from(base64::engine::general_purpose::STANDARD.encode(blob)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_tables_rows_and_uncheckpointed_changes() {
+        let path =
+            std::env::temp_dir().join(format!("infimount-sqlite-{}.db", uuid::Uuid::new_v4()));
+        let writer = Connection::open(&path).unwrap();
+        writer
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 PRAGMA wal_autocheckpoint = 0;
+                 CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT NOT NULL, score, avatar BLOB);
+                 INSERT INTO people (name, score, avatar) VALUES ('Ada', -7, x'0102');
+                 INSERT INTO people (name, score) VALUES ('Grace', 1.5);
+                 INSERT INTO people (name) VALUES ('Linus');",
+            )
+            .unwrap();
+        // The writer stays open, so the rows are still only in the -wal file.
+        let mut wal = path.clone().into_os_string();
+        wal.push("-wal");
+        assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+
+        let tables = read_tables(&path, 2).unwrap();
+        drop(writer);
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.clone().into_os_string();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
+        assert_eq!(tables.len(), 1);
+        let people = &tables[0];
+        assert_eq!(people.name, "people");
+        let columns: Vec<_> = people
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_deref()))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("id", Some("INTEGER")),
+                ("name", Some("TEXT")),
+                ("score", None),
+                ("avatar", Some("BLOB"))
+            ]
+        );
+        assert_eq!(people.row_count, Some(3));
+        assert!(people.truncated);
+        assert_eq!(
+            people.rows,
+            vec![
+                vec![
+                    Json::from(1),
+                    Json::from("Ada"),
+                    Json::from(-7),
+                    Json::from("AQI=")
+                ],
+                vec![
+                    Json::from(2),
+                    Json::from("Grace"),
+                    Json::from(1.5),
+                    Json::Null
+                ],
+            ]
+        );
+    }
+}
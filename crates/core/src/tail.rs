@@ -0,0 +1,226 @@
+//! `tail` and `tail -f` for log files on any source.
+//!
+//! [`tail`] reads the last lines of a file backwards in ranged blocks, so
+//! only the end of a large log is fetched. In follow mode it then polls the
+//! file's size and reads just the bytes appended since, handing out every
+//! completed line. A file that shrinks (rotated or truncated) is read again
+//! from the start.
+
+use std::time::Duration;
+
+use opendal::Operator;
+use serde::Serialize;
+
+use crate::cancel::CancelToken;
+use crate::models::Result;
+use crate::operations::normalize_opendal_path;
+
+/// Lines [`tail`] starts with unless asked for another number.
+pub const DEFAULT_TAIL_LINES: usize = 100;
+/// How often a followed file is checked for new data.
+pub const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Bytes read per ranged request when walking back from the end.
+const TAIL_BLOCK: u64 = 64 * 1024;
+/// The initial tail never reads further back than this.
+const MAX_TAIL_BYTES: u64 = 8 * 1024 * 1024;
+/// Appended bytes read per request while following.
+const FOLLOW_BLOCK: u64 = 1024 * 1024;
+/// An unterminated line this long is handed out anyway.
+const MAX_PENDING_BYTES: usize = 1024 * 1024;
+/// Cancellation is checked this often while waiting for the next poll.
+const CANCEL_POLL: Duration = Duration::from_millis(250);
+
+/// Lines handed out by [`tail`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TailLines {
+    pub lines: Vec<String>,
+    /// The file shrank and is read again from the start; earlier lines are
+    /// stale.
+    pub reset: bool,
+}
+
+/// Complete lines at the start of `data`, and how many bytes they took.
+/// Line endings (`\n` or `\r\n`) are stripped; invalid UTF-8 is replaced.
+fn split_lines(data: &[u8]) -> (Vec<String>, usize) {
+    let Some(end) = data.iter().rposition(|&b| b == b'\n') else {
+        return (Vec::new(), 0);
+    };
+    let lines = data[..end]
+        .split(|&b| b == b'\n')
+        .map(|line| {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            String::from_utf8_lossy(line).into_owned()
+        })
+        .collect();
+    (lines, end + 1)
+}
+
+/// Bytes from where the last `lines` lines of the first `size` bytes start,
+/// read backwards in blocks, and the offset they start at. The first line
+/// may be incomplete when the offset is not 0.
+async fn read_last_lines(
+    op: &Operator,
+    path: &str,
+    size: u64,
+    lines: usize,
+) -> Result<(Vec<u8>, u64)> {
+    let mut start = size;
+    let mut data = Vec::new();
+    while start > 0 && size - start < MAX_TAIL_BYTES {
+        let len = TAIL_BLOCK.min(start);
+        start -= len;
+        let mut block = op.read_with(path).range(start..start + len).await?.to_vec();
+        block.extend_from_slice(&data);
+        data = block;
+        // One newline more than lines wanted: the one ending the line before.
+        if data.iter().filter(|&&b| b == b'\n').count() > lines {
+            break;
+        }
+    }
+    Ok((data, start))
+}
+
+/// Hand the last `lines` lines of `path` to `on_lines`, then with `follow`
+/// keep polling the file and hand out each batch of lines appended to it,
+/// every [`FOLLOW_INTERVAL`], until `on_lines` returns `false` or `cancel`
+/// fires. While following, a trailing line without its newline is held
+/// back until it is complete. Returns the number of lines handed out.
+pub async fn tail(
+    op: &Operator,
+    path: &str,
+    lines: usize,
+    follow: bool,
+    cancel: &CancelToken,
+    mut on_lines: impl FnMut(TailLines) -> bool,
+) -> Result<usize> {
+    let p = normalize_opendal_path(path);
+    let size = op.stat(&p).await?.content_length();
+    let (data, start) = read_last_lines(op, &p, size, lines).await?;
+    let skip = if start > 0 {
+        data.iter()
+            .position(|&b| b == b'\n')
+            .map_or(data.len(), |i| i + 1)
+    } else {
+        0
+    };
+    let (mut last, consumed) = split_lines(&data[skip..]);
+    let mut pending = data[skip + consumed..].to_vec();
+    if !follow && !pending.is_empty() {
+        last.push(String::from_utf8_lossy(&pending).into_owned());
+        pending.clear();
+    }
+    let last = last.split_off(last.len().saturating_sub(lines));
+    let mut sent = last.len();
+    if !on_lines(TailLines {
+        lines: last,
+        reset: false,
+    }) || !follow
+    {
+        return Ok(sent);
+    }
+
+    let mut offset = size;
+    loop {
+        let mut waited = Duration::ZERO;
+        while waited < FOLLOW_INTERVAL {
+            if cancel.is_cancelled() {
+                return Ok(sent);
+            }
+            tokio::time::sleep(CANCEL_POLL).await;
+            waited += CANCEL_POLL;
+        }
+
+        let size = op.stat(&p).await?.content_length();
+        let reset = size < offset;
+        if reset {
+            offset = 0;
+            pending.clear();
+        }
+        let mut appended = Vec::new();
+        while offset < size && !cancel.is_cancelled() {
+            let end = size.min(offset + FOLLOW_BLOCK);
+            pending.extend_from_slice(&op.read_with(&p).range(offset..end).await?.to_vec());
+            offset = end;
+            let (lines, consumed) = split_lines(&pending);
+            appended.extend(lines);
+            pending.drain(..consumed);
+            if pending.len() > MAX_PENDING_BYTES {
+                appended.push(String::from_utf8_lossy(&pending).into_owned());
+                pending.clear();
+            }
+        }
+        if appended.is_empty() && !reset {
+            continue;
+        }
+        sent += appended.len();
+        if !on_lines(TailLines {
+            lines: appended,
+            reset,
+        }) {
+            return Ok(sent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    fn memory_operator() -> Operator {
+        Operator::new(Memory::default()).unwrap().finish()
+    }
+
+    fn numbered_log(lines: usize) -> String {
+        (1..=lines).map(|i| format!("line {i}\r\n")).collect()
+    }
+
+    #[test]
+    fn splits_complete_lines_only() {
+        let (lines, consumed) = split_lines(b"a\r\nb\n\nc");
+        assert_eq!(lines, ["a", "b", ""]);
+        assert_eq!(consumed, 6);
+        assert_eq!(split_lines(b"partial"), (Vec::new(), 0));
+    }
+
+    #[tokio::test]
+    async fn tails_across_blocks() {
+        let op = memory_operator();
+        op.write("app.log", numbered_log(20_000)).await.unwrap();
+
+        let mut seen = Vec::new();
+        let sent = tail(&op, "/app.log", 3, false, &CancelToken::new(), |batch| {
+            seen = batch.lines;
+            true
+        })
+        .await
+        .unwrap();
+        assert_eq!(sent, 3);
+        assert_eq!(seen, ["line 19998", "line 19999", "line 20000"]);
+    }
+
+    #[tokio::test]
+    async fn keeps_an_unterminated_last_line_unless_following() {
+        let op = memory_operator();
+        op.write("short.log", "one\ntwo\nthr").await.unwrap();
+        let cancel = CancelToken::new();
+
+        let mut seen = Vec::new();
+        tail(&op, "short.log", 10, false, &cancel, |batch| {
+            seen = batch.lines;
+            true
+        })
+        .await
+        .unwrap();
+        assert_eq!(seen, ["one", "two", "thr"]);
+
+        tail(&op, "short.log", 10, true, &cancel, |batch| {
+            seen = batch.lines;
+            false
+        })
+        .await
+        .unwrap();
+        assert_eq!(seen, ["one", "two"]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Size thresholds for actions on large files.
+//!
+//! Downloads and previews above a threshold are refused with
+//! [`CoreError::ConfirmationRequired`] until the caller repeats them with
+//! `confirmed` set, so the UI asks the user once instead of every command
+//! guessing its own limits. Callers pass the limits in effect, normally the
+//! `size_thresholds` preference. Files above
+//! [`SizeThresholds::block_webview_bytes`] are never loaded into the webview
+//! whole, confirmed or not; they can still be downloaded or streamed.
+
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_opendal_path};
+use crate::transfer_report::TransferFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizeThresholds {
+    /// Downloads of more bytes than this need confirmation.
+    pub confirm_download_bytes: u64,
+    /// Previews of bigger files need confirmation.
+    pub confirm_preview_bytes: u64,
+    /// Bigger files are never read whole into the webview.
+    pub block_webview_bytes: u64,
+}
+
+impl Default for SizeThresholds {
+    fn default() -> Self {
+        Self {
+            confirm_download_bytes: 2 * 1024 * 1024 * 1024,
+            confirm_preview_bytes: 50 * 1024 * 1024,
+            block_webview_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+/// What a [`CoreError::ConfirmationRequired`] asks about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeAction {
+    Download,
+    Preview,
+}
+
+impl SizeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SizeAction::Download => "download",
+            SizeAction::Preview => "preview",
+        }
+    }
+}
+
+impl std::fmt::Display for SizeAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Refuse downloading `bytes` in total past the threshold unless `confirmed`.
+pub fn check_download(limits: &SizeThresholds, bytes: u64, confirmed: bool) -> Result<()> {
+    let limit = limits.confirm_download_bytes;
+    if bytes > limit && !confirmed {
+        return Err(CoreError::ConfirmationRequired(
+            SizeAction::Download,
+            bytes,
+            limit,
+        ));
+    }
+    Ok(())
+}
+
+/// Refuse previewing a file of `size` bytes past the thresholds: above the
+/// webview limit always, above the preview threshold unless `confirmed`.
+pub fn check_preview(
+    limits: &SizeThresholds,
+    path: &str,
+    size: u64,
+    confirmed: bool,
+) -> Result<()> {
+    if size > limits.block_webview_bytes {
+        return Err(CoreError::FileTooLarge(
+            path.to_string(),
+            size,
+            limits.block_webview_bytes,
+        ));
+    }
+    if size > limits.confirm_preview_bytes && !confirmed {
+        return Err(CoreError::ConfirmationRequired(
+            SizeAction::Preview,
+            size,
+            limits.confirm_preview_bytes,
+        ));
+    }
+    Ok(())
+}
+
+/// [`check_download`] for the files and folders at `paths`, less what
+/// `filter` leaves out of folders. Paths that cannot be sized are let
+/// through; the download reports their errors.
+pub async fn check_download_paths(
+    op: &Operator,
+    paths: &[String],
+    filter: TransferFilter,
+    limits: &SizeThresholds,
+    confirmed: bool,
+) -> Result<()> {
+    if confirmed {
+        return Ok(());
+    }
+    let paths: Vec<String> = paths.iter().map(|p| normalize_opendal_path(p)).collect();
+    match operations::count_transfer(op, &paths, filter).await {
+        Some((_, bytes)) => check_download(limits, bytes, false),
+        None => Ok(()),
+    }
+}
+
+/// Whole contents of a file for the webview, enforcing [`check_preview`].
+pub async fn read_for_webview(
+    op: &Operator,
+    path: &str,
+    limits: &SizeThresholds,
+    confirmed: bool,
+) -> Result<Vec<u8>> {
+    let size = op
+        .stat(&normalize_opendal_path(path))
+        .await?
+        .content_length();
+    check_preview(limits, path, size, confirmed)?;
+    operations::read_full(op, path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ErrorCode;
+    use opendal::services::Memory;
+
+    #[tokio::test]
+    async fn large_reads_need_confirmation_or_are_blocked() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("small.txt", vec![b'a'; 10]).await.unwrap();
+        op.write("medium.log", vec![b'a'; 100]).await.unwrap();
+        op.write("dir/huge.bin", vec![0; 1000]).await.unwrap();
+        let limits = SizeThresholds {
+            confirm_download_bytes: 500,
+            confirm_preview_bytes: 50,
+            block_webview_bytes: 500,
+        };
+
+        assert_eq!(
+            read_for_webview(&op, "small.txt", &limits, false)
+                .await
+                .unwrap()
+                .len(),
+            10
+        );
+        let err = read_for_webview(&op, "medium.log", &limits, false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ConfirmationRequired);
+        assert_eq!(err.message().args["action"], "preview");
+        assert!(read_for_webview(&op, "medium.log", &limits, true)
+            .await
+            .is_ok());
+        let err = read_for_webview(&op, "dir/huge.bin", &limits, true)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::TooLarge);
+
+        let filter = TransferFilter::default();
+        let small = ["small.txt".to_string(), "medium.log".to_string()];
+        assert!(check_download_paths(&op, &small, filter, &limits, false)
+            .await
+            .is_ok());
+        let all = ["/dir/".to_string(), "small.txt".to_string()];
+        assert!(matches!(
+            check_download_paths(&op, &all, filter, &limits, false).await,
+            Err(CoreError::ConfirmationRequired(
+                SizeAction::Download,
+                1010,
+                500
+            ))
+        ));
+        assert!(check_download_paths(&op, &all, filter, &limits, true)
+            .await
+            .is_ok());
+    }
+}
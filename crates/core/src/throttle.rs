@@ -0,0 +1,290 @@
+//! Per-source bandwidth limits.
+//!
+//! A source's `uploadLimit` and `downloadLimit` config keys (bytes per
+//! second) cap how fast data is written to and read from it. Every reader
+//! and writer of the operator draws from the same budget, so parallel
+//! transfers share the limit instead of each getting it in full. Callers
+//! that build a fresh operator per command keep the budget in
+//! [`SourceLimiters`], so those operators share it too.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use opendal::raw::{
+    oio, Access, Layer, LayeredAccess, OpList, OpRead, OpWrite, RpDelete, RpList, RpRead, RpWrite,
+};
+use opendal::{Buffer, Metadata, Operator};
+use serde::{Deserialize, Serialize};
+
+/// Idle time that may be spent later as a burst above the limit.
+const BURST: Duration = Duration::from_secs(1);
+
+/// Upload and download limits in bytes per second; `None` is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthLimits {
+    pub upload: Option<u64>,
+    pub download: Option<u64>,
+}
+
+impl BandwidthLimits {
+    /// Read the `uploadLimit` and `downloadLimit` source config keys.
+    /// Missing, zero or unparsable values mean no limit.
+    pub fn from_config(get: impl Fn(&str) -> Option<String>) -> Self {
+        let limit = |key: &str| {
+            get(key)
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|&v| v > 0)
+        };
+        Self {
+            upload: limit("uploadLimit"),
+            download: limit("downloadLimit"),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.upload.is_none() && self.download.is_none()
+    }
+
+    /// Wrap `op` so its reads and writes stay within the limits.
+    pub fn apply(self, op: Operator) -> Operator {
+        if self.is_unlimited() {
+            return op;
+        }
+        op.layer(ThrottleLayer::new(self))
+    }
+}
+
+/// Rate limiters per source id. Every operator wrapped for a source draws
+/// from the same budget until the source's limits change.
+#[derive(Debug, Default)]
+pub struct SourceLimiters {
+    layers: Mutex<HashMap<String, (BandwidthLimits, ThrottleLayer)>>,
+}
+
+impl SourceLimiters {
+    /// Wrap `op`, built for `source_id`, so it stays within `limits` along
+    /// with every other operator wrapped for that source.
+    pub fn apply(&self, source_id: &str, limits: BandwidthLimits, op: Operator) -> Operator {
+        let mut layers = self.layers.lock().unwrap_or_else(|e| e.into_inner());
+        if limits.is_unlimited() {
+            layers.remove(source_id);
+            return op;
+        }
+        let layer = match layers.get(source_id) {
+            Some((current, layer)) if *current == limits => layer.clone(),
+            _ => {
+                let layer = ThrottleLayer::new(limits);
+                layers.insert(source_id.to_string(), (limits, layer.clone()));
+                layer
+            }
+        };
+        op.layer(layer)
+    }
+}
+
+#[derive(Debug)]
+struct RateLimiter {
+    bytes_per_sec: f64,
+    /// When the bytes reserved so far will have passed at the limit.
+    next_free: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec as f64,
+            next_free: Mutex::new(None),
+        }
+    }
+
+    /// Reserve `bytes` and return how long to wait before they may pass.
+    fn reserve(&self, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let mut next_free = self.next_free.lock().unwrap_or_else(|e| e.into_inner());
+        let earliest = now.checked_sub(BURST).unwrap_or(now);
+        let start = next_free.map_or(earliest, |next| next.max(earliest));
+        let end = start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec);
+        *next_free = Some(end);
+        end.saturating_duration_since(now)
+    }
+
+    async fn acquire(&self, bytes: usize) {
+        let wait = self.reserve(bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ThrottleLayer {
+    upload: Option<Arc<RateLimiter>>,
+    download: Option<Arc<RateLimiter>>,
+}
+
+impl ThrottleLayer {
+    fn new(limits: BandwidthLimits) -> Self {
+        Self {
+            upload: limits.upload.map(|rate| Arc::new(RateLimiter::new(rate))),
+            download: limits.download.map(|rate| Arc::new(RateLimiter::new(rate))),
+        }
+    }
+}
+
+impl<A: Access> Layer<A> for ThrottleLayer {
+    type LayeredAccess = ThrottleAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        ThrottleAccessor {
+            inner,
+            upload: self.upload.clone(),
+            download: self.download.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ThrottleAccessor<A> {
+    inner: A,
+    upload: Option<Arc<RateLimiter>>,
+    download: Option<Arc<RateLimiter>>,
+}
+
+struct Throttled<T> {
+    inner: T,
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<A: Access> LayeredAccess for ThrottleAccessor<A> {
+    type Inner = A;
+    type Reader = Throttled<A::Reader>;
+    type Writer = Throttled<A::Writer>;
+    type Lister = A::Lister;
+    type Deleter = A::Deleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+        let (rp, reader) = self.inner.read(path, args).await?;
+        Ok((
+            rp,
+            Throttled {
+                inner: reader,
+                limiter: self.download.clone(),
+            },
+        ))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> opendal::Result<(RpWrite, Self::Writer)> {
+        let (rp, writer) = self.inner.write(path, args).await?;
+        Ok((
+            rp,
+            Throttled {
+                inner: writer,
+                limiter: self.upload.clone(),
+            },
+        ))
+    }
+
+    async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+        self.inner.delete().await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+        self.inner.list(path, args).await
+    }
+}
+
+impl<R: oio::Read> oio::Read for Throttled<R> {
+    async fn read(&mut self) -> opendal::Result<Buffer> {
+        let buffer = self.inner.read().await?;
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(buffer.len()).await;
+        }
+        Ok(buffer)
+    }
+}
+
+impl<W: oio::Write> oio::Write for Throttled<W> {
+    async fn write(&mut self, bs: Buffer) -> opendal::Result<()> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(bs.len()).await;
+        }
+        self.inner.write(bs).await
+    }
+
+    async fn close(&mut self) -> opendal::Result<Metadata> {
+        self.inner.close().await
+    }
+
+    async fn abort(&mut self) -> opendal::Result<()> {
+        self.inner.abort().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn writes_are_held_to_the_upload_limit() {
+        let config = HashMap::from([("uploadLimit".to_string(), "100000".to_string())]);
+        let limits = BandwidthLimits::from_config(|k| config.get(k).cloned());
+        assert_eq!(
+            limits,
+            BandwidthLimits {
+                upload: Some(100_000),
+                download: None
+            }
+        );
+
+        let op = limits.apply(
+            Operator::new(opendal::services::Memory::default())
+                .unwrap()
+                .finish(),
+        );
+        let started = Instant::now();
+        op.write("a.bin", vec![0u8; 150_000]).await.unwrap();
+        // One second of burst, then 50 kB at 100 kB/s.
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(450), "{elapsed:?}");
+
+        // Reads are not limited.
+        let started = Instant::now();
+        op.read("a.bin").await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn operators_built_for_one_source_share_its_limit() {
+        let limiters = SourceLimiters::default();
+        let limits = BandwidthLimits {
+            upload: Some(100_000),
+            download: None,
+        };
+        let memory = || {
+            Operator::new(opendal::services::Memory::default())
+                .unwrap()
+                .finish()
+        };
+
+        let started = Instant::now();
+        let first = limiters.apply("source", limits, memory());
+        first.write("a.bin", vec![0u8; 100_000]).await.unwrap();
+        // The burst is spent; the next operator for the source has to wait.
+        let second = limiters.apply("source", limits, memory());
+        second.write("b.bin", vec![0u8; 50_000]).await.unwrap();
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(450), "{elapsed:?}");
+
+        // Other sources have budgets of their own.
+        let started = Instant::now();
+        let other = limiters.apply("other", limits, memory());
+        other.write("c.bin", vec![0u8; 50_000]).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,391 @@
+//! Per-item record of a copy/move job.
+//!
+//! [`crate::operations::transfer_entries`] fills a [`TransferReport`] as it
+//! goes: every file ends up completed, skipped or failed, with its size, how
+//! long it took and, for cross-source copies, whether the destination size
+//! matched. Reports can be exported as JSON or CSV.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cancel::CancelToken;
+use crate::i18n::Message;
+use crate::junk;
+use crate::models::Result;
+use crate::progress::{ProgressSink, ProgressStage, ProgressState, Snapshot, TransferProgress};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Completed,
+    Skipped,
+    Failed,
+}
+
+impl ItemStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ItemStatus::Completed => "completed",
+            ItemStatus::Skipped => "skipped",
+            ItemStatus::Failed => "failed",
+        }
+    }
+
+    /// Translatable label, e.g. "Completed"; see [`crate::i18n`].
+    pub fn message(self) -> Message {
+        Message::new(format!("task-status-{}", self.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferItem {
+    pub source_path: String,
+    pub target_path: String,
+    pub status: ItemStatus,
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// Destination size matched the source after a cross-source copy.
+    /// `None` when no check was made (same-source copy/rename, skipped items).
+    pub verified: Option<bool>,
+    /// How an already existing destination was dealt with; `None` when
+    /// there was none.
+    #[serde(default)]
+    pub conflict: Option<Conflict>,
+}
+
+/// What happened to an item whose destination already existed, following
+/// the job's [`crate::operations::TransferConflictPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Conflict {
+    Overwritten,
+    Skipped,
+    /// Written next to the existing one under a free name.
+    Renamed,
+}
+
+/// What recursive transfers leave out of the folders they walk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFilter {
+    /// Skip OS metadata files (see [`crate::junk`]); the inverse of
+    /// `Preferences::include_system_files`.
+    pub skip_system_files: bool,
+    /// Skip what the `.gitignore` files of a Local source exclude, see
+    /// [`crate::git`] and `Preferences::honor_gitignore`.
+    pub honor_gitignore: bool,
+}
+
+impl Default for TransferFilter {
+    fn default() -> Self {
+        Self {
+            skip_system_files: true,
+            honor_gitignore: false,
+        }
+    }
+}
+
+impl TransferFilter {
+    /// Whether a walk leaves out the entry called `name`.
+    pub(crate) fn skips(&self, name: &str) -> bool {
+        self.skip_system_files && junk::is_junk(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferReport {
+    pub task_id: String,
+    pub operation: String,
+    pub from_source: String,
+    pub to_source: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Task whose failed items this job re-attempted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_of: Option<String>,
+    pub items: Vec<TransferItem>,
+    #[serde(skip)]
+    progress: ProgressState,
+    #[serde(skip)]
+    cancel: CancelToken,
+    #[serde(skip)]
+    discard_attributes: bool,
+    #[serde(skip)]
+    upload_concurrency: Option<usize>,
+    #[serde(skip)]
+    filter: TransferFilter,
+}
+
+impl TransferReport {
+    pub fn new(
+        operation: impl Into<String>,
+        from_source: impl Into<String>,
+        to_source: impl Into<String>,
+    ) -> Self {
+        Self {
+            task_id: uuid::Uuid::new_v4().to_string(),
+            operation: operation.into(),
+            from_source: from_source.into(),
+            to_source: to_source.into(),
+            started_at: Utc::now(),
+            finished_at: None,
+            retry_of: None,
+            items: Vec::new(),
+            progress: ProgressState::default(),
+            cancel: CancelToken::default(),
+            discard_attributes: false,
+            upload_concurrency: None,
+            filter: TransferFilter::default(),
+        }
+    }
+
+    /// Empty report for re-running the failed items of `self`.
+    pub fn retry(&self) -> Self {
+        let mut report = Self::new(&self.operation, &self.from_source, &self.to_source);
+        report.retry_of = Some(self.task_id.clone());
+        report.discard_attributes = self.discard_attributes;
+        report.upload_concurrency = self.upload_concurrency;
+        report.filter = self.filter;
+        report
+    }
+
+    pub fn push(&mut self, item: TransferItem) {
+        let advanced = self.progress.item_bytes;
+        self.push_advanced(item, advanced);
+    }
+
+    /// Record one of several items transferred at once, `advanced` bytes of
+    /// which were already reported through `advance_item`.
+    pub(crate) fn push_advanced(&mut self, item: TransferItem, advanced: u64) {
+        // Bytes already reported through `advance_item` count only once.
+        self.progress.item_bytes = self.progress.item_bytes.saturating_sub(advanced);
+        self.progress.record(item.bytes.saturating_sub(advanced));
+        self.items.push(item);
+        self.emit(ProgressStage::Transferring);
+    }
+
+    /// Note `conflict` on the items recorded from index `start` on, such
+    /// as the files of a folder that replaced an existing one.
+    pub(crate) fn mark_conflict_since(&mut self, start: usize, conflict: Conflict) {
+        for item in self.items.iter_mut().skip(start) {
+            item.conflict = Some(conflict);
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.finished_at = Some(Utc::now());
+        self.emit(ProgressStage::Finished);
+    }
+
+    /// Send [`TransferProgress`] updates to `sink` while the job runs.
+    pub fn set_progress_sink(&mut self, sink: ProgressSink) {
+        self.progress.sink = Some(sink);
+    }
+
+    /// Stop the job between chunks once `token` is cancelled; see
+    /// [`crate::cancel`].
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.cancel = token;
+    }
+
+    pub(crate) fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+
+    /// Whether copied files keep their modification time and permissions,
+    /// as they do unless turned off here; see [`crate::file_attrs`].
+    pub fn set_preserve_attributes(&mut self, preserve: bool) {
+        self.discard_attributes = !preserve;
+    }
+
+    pub(crate) fn preserves_attributes(&self) -> bool {
+        !self.discard_attributes
+    }
+
+    /// How many files an upload sends at once (`None` for
+    /// [`crate::operations::DEFAULT_UPLOAD_CONCURRENCY`]); see
+    /// `Preferences::upload_concurrency`.
+    pub fn set_upload_concurrency(&mut self, limit: Option<usize>) {
+        self.upload_concurrency = limit;
+    }
+
+    pub(crate) fn upload_concurrency(&self) -> Option<usize> {
+        self.upload_concurrency
+    }
+
+    /// What the job leaves out of the folders it copies, see
+    /// `Preferences::transfer_filter`.
+    pub fn set_filter(&mut self, filter: TransferFilter) {
+        self.filter = filter;
+    }
+
+    pub(crate) fn filter(&self) -> TransferFilter {
+        self.filter
+    }
+
+    pub(crate) fn wants_progress(&self) -> bool {
+        self.progress.sink.is_some()
+    }
+
+    /// Start counting what the job will transfer.
+    pub(crate) fn start_preparing(&mut self) {
+        self.emit(ProgressStage::Preparing);
+    }
+
+    /// Totals to report progress against; `bytes` is `None` when the
+    /// backend did not report every size.
+    pub(crate) fn set_totals(&mut self, items: usize, bytes: Option<u64>) {
+        self.progress.items_total = Some(items);
+        self.progress.bytes_total = bytes;
+    }
+
+    /// Note the file about to be transferred.
+    pub(crate) fn start_item(&mut self, path: &str) {
+        self.progress.current_item = Some(path.to_string());
+        self.emit(ProgressStage::Transferring);
+    }
+
+    /// Note `bytes` more of an item in flight transferred, so large files
+    /// show progress before they are done.
+    pub(crate) fn advance_item(&mut self, bytes: u64) {
+        self.progress.item_bytes += bytes;
+        self.progress.record(bytes);
+        self.emit(ProgressStage::Transferring);
+    }
+
+    /// Note that the job is waiting for transfers to be resumed.
+    pub(crate) fn mark_paused(&mut self) {
+        self.emit(ProgressStage::Paused);
+    }
+
+    /// Progress as of now.
+    pub fn progress(&self) -> TransferProgress {
+        let stage = if self.finished_at.is_some() {
+            ProgressStage::Finished
+        } else {
+            self.progress.stage.unwrap_or(ProgressStage::Preparing)
+        };
+        TransferProgress::new(&self.progress, stage, self.snapshot())
+    }
+
+    fn emit(&mut self, stage: ProgressStage) {
+        if !self.progress.due(stage) {
+            return;
+        }
+        let progress = TransferProgress::new(&self.progress, stage, self.snapshot());
+        if let Some(sink) = &self.progress.sink {
+            sink(&progress);
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot<'_> {
+        Snapshot {
+            task_id: &self.task_id,
+            operation: &self.operation,
+            items_done: self.items.len(),
+            bytes_done: self.items.iter().map(|i| i.bytes).sum::<u64>() + self.progress.item_bytes,
+            completed: self.count(ItemStatus::Completed),
+            skipped: self.count(ItemStatus::Skipped),
+            failed: self.count(ItemStatus::Failed),
+        }
+    }
+
+    pub fn count(&self, status: ItemStatus) -> usize {
+        self.items.iter().filter(|i| i.status == status).count()
+    }
+
+    pub fn failed_items(&self) -> impl Iterator<Item = &TransferItem> {
+        self.items.iter().filter(|i| i.status == ItemStatus::Failed)
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.items
+            .iter()
+            .filter(|i| i.status == ItemStatus::Completed)
+            .map(|i| i.bytes)
+            .sum()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// One row per item; job-level fields are repeated so rows stand alone
+    /// when filtered in a spreadsheet.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "task_id,operation,from_source,to_source,source_path,target_path,status,bytes,duration_ms,verified,error\n",
+        );
+        for item in &self.items {
+            let verified = match item.verified {
+                Some(true) => "true",
+                Some(false) => "false",
+                None => "",
+            };
+            let row = [
+                csv_field(&self.task_id),
+                csv_field(&self.operation),
+                csv_field(&self.from_source),
+                csv_field(&self.to_source),
+                csv_field(&item.source_path),
+                csv_field(&item.target_path),
+                item.status.as_str().to_string(),
+                item.bytes.to_string(),
+                item.duration_ms.to_string(),
+                verified.to_string(),
+                csv_field(item.error.as_deref().unwrap_or_default()),
+            ];
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        let mut report = TransferReport::new("copy", "local", "s3");
+        report.push(TransferItem {
+            source_path: "a,b.txt".to_string(),
+            target_path: "backup/a,b.txt".to_string(),
+            status: ItemStatus::Failed,
+            bytes: 0,
+            duration_ms: 12,
+            error: Some("storage error: \"denied\"".to_string()),
+            verified: None,
+            conflict: None,
+        });
+        report.push(TransferItem {
+            source_path: "c.txt".to_string(),
+            target_path: "backup/c.txt".to_string(),
+            status: ItemStatus::Completed,
+            bytes: 3,
+            duration_ms: 4,
+            error: None,
+            verified: Some(true),
+            conflict: None,
+        });
+
+        let csv = report.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].contains(
+            "\"a,b.txt\",\"backup/a,b.txt\",failed,0,12,,\"storage error: \"\"denied\"\"\""
+        ));
+        assert!(rows[2].ends_with("c.txt,backup/c.txt,completed,3,4,true,"));
+        assert_eq!(report.count(ItemStatus::Failed), 1);
+        assert_eq!(report.total_bytes(), 3);
+    }
+}
@@ -0,0 +1,310 @@
+//! Per-source trash, so deletions can be undone.
+//!
+//! [`move_to_trash`] moves a file or folder under `.infimount-trash/` at the
+//! source root instead of removing it. Each trashed entry gets a folder of
+//! its own there, holding the entry under `content` and an `info.json` with
+//! its original path and deletion time. The trash lives on the source, so
+//! [`list_trash`] and [`restore_from_trash`] see the same items from every
+//! machine using it; [`empty_trash`] removes them for good. Local sources
+//! can use the OS trash instead, see [`operations::move_to_os_trash`].
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use opendal::{ErrorKind, Operator};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::models::{CoreError, Result};
+use crate::operations::{self, normalize_opendal_path};
+
+/// Folder at the source root that holds trashed entries. Hidden from root
+/// listings and skipped by recursive operations.
+pub const TRASH_DIR: &str = ".infimount-trash";
+
+const INFO_FILE: &str = "info.json";
+const CONTENT: &str = "content";
+
+/// An entry in the trash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub id: String,
+    /// Where the entry lived, relative to the source root.
+    pub original_path: String,
+    pub deleted_at: DateTime<Utc>,
+    pub is_dir: bool,
+    /// Size of a file; 0 for folders.
+    pub size: u64,
+}
+
+impl TrashItem {
+    fn content_path(&self) -> String {
+        let path = format!("{}{CONTENT}", item_dir(&self.id));
+        if self.is_dir {
+            path + "/"
+        } else {
+            path
+        }
+    }
+}
+
+/// Whether `path` is the trash folder or inside it.
+pub fn is_trash_path(path: &str) -> bool {
+    let p = normalize_opendal_path(path);
+    let p = p.trim_end_matches('/');
+    p == TRASH_DIR || p.starts_with(&format!("{TRASH_DIR}/"))
+}
+
+fn item_dir(id: &str) -> String {
+    format!("{TRASH_DIR}/{id}/")
+}
+
+fn not_found(id: &str) -> CoreError {
+    opendal::Error::new(ErrorKind::NotFound, format!("no trash item '{id}'")).into()
+}
+
+/// Move `path` (a file, or a folder with everything below it) into the
+/// trash. Entries already in the trash are rejected; [`empty_trash`]
+/// deletes those.
+pub async fn move_to_trash(op: &Operator, path: &str) -> Result<TrashItem> {
+    operations::ensure_writable(op)?;
+    operations::ensure_capability(op, "delete")?;
+    let p = normalize_opendal_path(path);
+    let trimmed = p.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Err(CoreError::Unsupported(
+            "cannot move the source root to the trash".to_string(),
+        ));
+    }
+    if is_trash_path(trimmed) {
+        return Err(CoreError::Unsupported(format!(
+            "'{trimmed}' is already in the trash"
+        )));
+    }
+
+    let (is_dir, size) = if p.ends_with('/') {
+        (true, 0)
+    } else {
+        match op.stat(trimmed).await {
+            Ok(meta) if meta.is_dir() => (true, 0),
+            Ok(meta) => (false, meta.content_length()),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                // Object stores only know the folder with its trailing slash.
+                if !op.exists(&format!("{trimmed}/")).await? {
+                    return Err(e.into());
+                }
+                (true, 0)
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+    let item = TrashItem {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        original_path: if is_dir {
+            format!("{trimmed}/")
+        } else {
+            trimmed.to_string()
+        },
+        deleted_at: Utc::now(),
+        is_dir,
+        size,
+    };
+
+    // The info goes first so a half-moved entry is still listed and can be
+    // restored or emptied.
+    op.write(
+        &format!("{}{INFO_FILE}", item_dir(&item.id)),
+        serde_json::to_vec_pretty(&item)?,
+    )
+    .await?;
+    if let Err(e) = move_entry(op, &item.original_path, &item.content_path(), is_dir).await {
+        if !op.exists(&item.content_path()).await.unwrap_or(true) {
+            let _ = op.remove_all(&item_dir(&item.id)).await;
+        }
+        return Err(e);
+    }
+    Ok(item)
+}
+
+async fn read_item(op: &Operator, id: &str) -> Result<TrashItem> {
+    if id.is_empty() || id.contains('/') || id == ".." {
+        return Err(not_found(id));
+    }
+    let data = match op.read(&format!("{}{INFO_FILE}", item_dir(id))).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Err(not_found(id)),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(serde_json::from_slice(&data.to_vec())?)
+}
+
+/// Items in the trash of `op`, most recently deleted first. Folders in the
+/// trash without a readable `info.json` are left out.
+pub async fn list_trash(op: &Operator) -> Result<Vec<TrashItem>> {
+    let root = format!("{TRASH_DIR}/");
+    let entries = match op.list(&root).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut items = Vec::new();
+    for entry in entries {
+        if !entry.metadata().is_dir() || entry.path() == root {
+            continue;
+        }
+        if let Ok(item) = read_item(op, entry.name().trim_end_matches('/')).await {
+            items.push(item);
+        }
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    Ok(items)
+}
+
+/// Move trash item `id` back to its original path, or next to it under a
+/// ` copy` name when that path is taken again. Returns the restored path.
+pub async fn restore_from_trash(op: &Operator, id: &str) -> Result<String> {
+    operations::ensure_writable(op)?;
+    let item = read_item(op, id).await?;
+    let original = item.original_path.trim_end_matches('/');
+    let (parent, name) = match original.rsplit_once('/') {
+        Some((parent, name)) => (format!("{parent}/"), name),
+        None => (String::new(), original),
+    };
+    let target = operations::unique_destination_path(op, &parent, name, item.is_dir).await?;
+    move_entry(op, &item.content_path(), &target, item.is_dir).await?;
+    op.remove_all(&item_dir(&item.id)).await?;
+    Ok(target)
+}
+
+/// Permanently delete everything in the trash of `op`. Returns how many
+/// items were removed.
+pub async fn empty_trash(op: &Operator) -> Result<usize> {
+    operations::ensure_writable(op)?;
+    operations::ensure_capability(op, "delete")?;
+    let removed = list_trash(op).await?.len();
+    op.remove_all(&format!("{TRASH_DIR}/")).await?;
+    Ok(removed)
+}
+
+/// Move a file or folder within one source. Local sources rename in place;
+/// other backends move file by file with rename, copy or read and write,
+/// whichever they support.
+async fn move_entry(op: &Operator, from: &str, to: &str, is_dir: bool) -> Result<()> {
+    if operations::is_local_fs(op) {
+        let from = operations::local_fs_path(op, from)?;
+        let to = operations::local_fs_path(op, to)?;
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(&from, &to).await?;
+        return Ok(());
+    }
+    if !is_dir {
+        return move_file(op, from, to).await;
+    }
+
+    // Collect first: moving files while the lister walks the folder may
+    // skip some on backends that list lazily.
+    let entries: Vec<opendal::Entry> = op
+        .lister_with(from)
+        .recursive(true)
+        .await?
+        .try_collect()
+        .await?;
+    let can_create_dir = op.info().full_capability().create_dir;
+    for entry in entries {
+        let relative = entry.path().strip_prefix(from).unwrap_or_default();
+        if relative.is_empty() {
+            continue;
+        }
+        let target = format!("{to}{relative}");
+        if entry.metadata().is_dir() {
+            // Keeps empty folders; the others reappear with their files.
+            if can_create_dir {
+                op.create_dir(&target).await?;
+            }
+        } else {
+            move_file(op, entry.path(), &target).await?;
+        }
+    }
+    op.remove_all(from).await?;
+    Ok(())
+}
+
+async fn move_file(op: &Operator, from: &str, to: &str) -> Result<()> {
+    operations::ensure_parent_dir(op, to).await?;
+    let capability = op.info().full_capability();
+    if capability.rename {
+        op.rename(from, to).await?;
+        return Ok(());
+    }
+    if capability.copy {
+        op.copy(from, to).await?;
+    } else {
+        let data = op.read(from).await?;
+        op.write(to, data).await?;
+    }
+    op.delete(from).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    fn memory_operator() -> Operator {
+        Operator::new(Memory::default()).unwrap().finish()
+    }
+
+    #[tokio::test]
+    async fn trashes_and_restores_a_file() {
+        let op = memory_operator();
+        op.write("docs/report.txt", "draft").await.unwrap();
+
+        let item = move_to_trash(&op, "/docs/report.txt").await.unwrap();
+        assert_eq!(item.original_path, "docs/report.txt");
+        assert_eq!(item.size, 5);
+        assert!(!op.exists("docs/report.txt").await.unwrap());
+        assert_eq!(list_trash(&op).await.unwrap(), vec![item.clone()]);
+
+        let restored = restore_from_trash(&op, &item.id).await.unwrap();
+        assert_eq!(restored, "docs/report.txt");
+        assert_eq!(op.read("docs/report.txt").await.unwrap().to_vec(), b"draft");
+        assert!(list_trash(&op).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn restores_folders_next_to_a_replacement() {
+        let op = memory_operator();
+        op.write("photos/a.jpg", "a").await.unwrap();
+        op.write("photos/raw/b.cr2", "b").await.unwrap();
+
+        let item = move_to_trash(&op, "photos/").await.unwrap();
+        assert!(item.is_dir);
+        op.write("photos/new.jpg", "new").await.unwrap();
+
+        let restored = restore_from_trash(&op, &item.id).await.unwrap();
+        assert_eq!(restored, "photos copy/");
+        assert_eq!(
+            op.read("photos copy/raw/b.cr2").await.unwrap().to_vec(),
+            b"b"
+        );
+        assert!(op.exists("photos/new.jpg").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn empty_trash_deletes_everything_and_hides_from_listing() {
+        let op = memory_operator();
+        op.write("a.txt", "a").await.unwrap();
+        op.write("b.txt", "b").await.unwrap();
+        move_to_trash(&op, "a.txt").await.unwrap();
+        move_to_trash(&op, "b.txt").await.unwrap();
+
+        let root = operations::list_entries(&op, "/").await.unwrap();
+        assert!(root.is_empty(), "{root:?}");
+        assert!(move_to_trash(&op, TRASH_DIR).await.is_err());
+
+        assert_eq!(empty_trash(&op).await.unwrap(), 2);
+        assert!(list_trash(&op).await.unwrap().is_empty());
+    }
+}
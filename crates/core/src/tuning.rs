@@ -0,0 +1,221 @@
+//! Per-backend tuning of chunked/multipart transfers.
+//!
+//! Chunk size and write concurrency are derived from the latency and
+//! throughput measured on recent transfers against the same backend, unless
+//! a manual override has been registered for the operator. Measurements are
+//! keyed by scheme, name and root, so every operator built for one backend
+//! shares them; overrides belong to the source's own operator.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+
+use crate::per_operator::PerOperator;
+
+/// Smallest part size accepted by S3-style multipart uploads.
+const MIN_CHUNK_SIZE: usize = 5 * 1024 * 1024;
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+const MAX_CONCURRENCY: usize = 8;
+/// Aim for each chunk to take roughly this long at the measured throughput.
+const TARGET_CHUNK_SECS: f64 = 2.0;
+/// Each extra in-flight part hides about this much round-trip latency.
+const LATENCY_PER_EXTRA_PART_SECS: f64 = 0.05;
+/// Transfers smaller than this say little about throughput.
+const MIN_SAMPLE_BYTES: u64 = 1024 * 1024;
+/// Weight of the newest sample in the moving averages.
+const SMOOTHING: f64 = 0.3;
+
+/// Chunk size (bytes) and number of in-flight parts used for transfers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferTuning {
+    pub chunk_size: usize,
+    pub concurrency: usize,
+}
+
+impl Default for TransferTuning {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            concurrency: 1,
+        }
+    }
+}
+
+impl TransferTuning {
+    /// Build a manual override from source config keys `chunkSizeMb` and
+    /// `writeConcurrency`. Returns `None` when neither key is set.
+    pub fn override_from_config(get: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let chunk_mb = get("chunkSizeMb").and_then(|v| v.trim().parse::<usize>().ok());
+        let concurrency = get("writeConcurrency").and_then(|v| v.trim().parse::<usize>().ok());
+        if chunk_mb.is_none() && concurrency.is_none() {
+            return None;
+        }
+
+        let defaults = Self::default();
+        Some(Self {
+            chunk_size: chunk_mb
+                .map(|mb| mb.saturating_mul(1024 * 1024).max(MIN_CHUNK_SIZE))
+                .unwrap_or(defaults.chunk_size),
+            concurrency: concurrency
+                .unwrap_or(defaults.concurrency)
+                .clamp(1, MAX_CONCURRENCY),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Measurements {
+    throughput_bps: Option<f64>,
+    latency_secs: Option<f64>,
+}
+
+#[derive(Default)]
+struct TunerState {
+    measured: HashMap<String, Measurements>,
+}
+
+static OVERRIDES: PerOperator<TransferTuning> = PerOperator::new();
+
+fn state() -> &'static Mutex<TunerState> {
+    static STATE: OnceLock<Mutex<TunerState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+pub(crate) fn operator_key(op: &Operator) -> String {
+    let info = op.info();
+    format!("{}://{}{}", info.scheme(), info.name(), info.root())
+}
+
+fn smooth(previous: Option<f64>, sample: f64) -> f64 {
+    match previous {
+        Some(prev) => prev + SMOOTHING * (sample - prev),
+        None => sample,
+    }
+}
+
+/// Register (or clear, with `None`) a manual tuning override for `op` and
+/// its clones.
+pub fn set_override(op: &Operator, tuning: Option<TransferTuning>) {
+    OVERRIDES.set(op, tuning);
+}
+
+/// Record the round-trip time of a cheap metadata call against a backend.
+pub fn record_latency(op: &Operator, elapsed: Duration) {
+    let key = operator_key(op);
+    let mut state = state().lock().unwrap_or_else(|e| e.into_inner());
+    let entry = state.measured.entry(key).or_default();
+    entry.latency_secs = Some(smooth(entry.latency_secs, elapsed.as_secs_f64()));
+}
+
+/// Smoothed round-trip time measured against a backend, if any.
+pub fn measured_latency(op: &Operator) -> Option<Duration> {
+    let key = operator_key(op);
+    let state = state().lock().unwrap_or_else(|e| e.into_inner());
+    state
+        .measured
+        .get(&key)
+        .and_then(|measured| measured.latency_secs)
+        .map(Duration::from_secs_f64)
+}
+
+/// Record a completed transfer of `bytes` that took `elapsed`.
+pub fn record_throughput(op: &Operator, bytes: u64, elapsed: Duration) {
+    let secs = elapsed.as_secs_f64();
+    if bytes < MIN_SAMPLE_BYTES || secs <= 0.0 {
+        return;
+    }
+
+    let key = operator_key(op);
+    let mut state = state().lock().unwrap_or_else(|e| e.into_inner());
+    let entry = state.measured.entry(key).or_default();
+    entry.throughput_bps = Some(smooth(entry.throughput_bps, bytes as f64 / secs));
+}
+
+/// Tuning to use for the next transfer against this backend.
+pub fn tuning_for(op: &Operator) -> TransferTuning {
+    if let Some(tuning) = OVERRIDES.with(op, |tuning| *tuning) {
+        return tuning;
+    }
+    let state = state().lock().unwrap_or_else(|e| e.into_inner());
+    state
+        .measured
+        .get(&operator_key(op))
+        .map(derive_tuning)
+        .unwrap_or_default()
+}
+
+fn derive_tuning(measured: &Measurements) -> TransferTuning {
+    let defaults = TransferTuning::default();
+
+    let chunk_size = measured
+        .throughput_bps
+        .map(|bps| ((bps * TARGET_CHUNK_SECS) as usize).clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE))
+        .unwrap_or(defaults.chunk_size);
+
+    let concurrency = measured
+        .latency_secs
+        .map(|latency| 1 + (latency / LATENCY_PER_EXTRA_PART_SECS) as usize)
+        .unwrap_or(defaults.concurrency)
+        .clamp(1, MAX_CONCURRENCY);
+
+    TransferTuning {
+        chunk_size,
+        concurrency,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    fn memory_operator(root: &str) -> Operator {
+        Operator::new(Memory::default().root(root))
+            .unwrap()
+            .finish()
+    }
+
+    #[test]
+    fn unmeasured_backends_use_defaults() {
+        let op = memory_operator("/tuning-defaults");
+        assert_eq!(tuning_for(&op), TransferTuning::default());
+    }
+
+    #[test]
+    fn high_latency_and_throughput_grow_concurrency_and_chunks() {
+        let op = memory_operator("/tuning-measured");
+        record_latency(&op, Duration::from_millis(220));
+        record_throughput(&op, 400 * 1024 * 1024, Duration::from_secs(20));
+
+        let tuning = tuning_for(&op);
+        assert_eq!(tuning.concurrency, 5);
+        assert_eq!(tuning.chunk_size, 40 * 1024 * 1024);
+    }
+
+    #[test]
+    fn manual_override_wins_over_measurements() {
+        let op = memory_operator("/tuning-override");
+        record_latency(&op, Duration::from_millis(500));
+
+        let config = HashMap::from([("chunkSizeMb".to_string(), "16".to_string())]);
+        let manual = TransferTuning::override_from_config(|k| config.get(k).cloned());
+        set_override(&op, manual);
+        assert_eq!(
+            tuning_for(&op),
+            TransferTuning {
+                chunk_size: 16 * 1024 * 1024,
+                concurrency: 1,
+            }
+        );
+        // An operator built separately for the same root has no override.
+        let other = memory_operator("/tuning-override");
+        assert_ne!(tuning_for(&other).chunk_size, 16 * 1024 * 1024);
+
+        set_override(&op, None);
+        assert_eq!(tuning_for(&op).concurrency, MAX_CONCURRENCY);
+    }
+}
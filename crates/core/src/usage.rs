@@ -0,0 +1,329 @@
+//! Storage usage of whole sources, for the storage overview.
+//!
+//! A [`Scan`] walks every object of a source once and records how many files
+//! there are and how many bytes they take, in total and per top-level
+//! folder. Results are kept in `source-stats.json` next to the config, so the
+//! overview shows the last scan right away while a new one runs in the
+//! background; [`status`] reports both.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+
+use crate::cancel::CancelToken;
+use crate::config;
+use crate::models::Result;
+
+/// A running scan publishes its totals every this many files.
+const PROGRESS_EVERY: u64 = 1000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderUsage {
+    pub name: String,
+    pub objects: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStats {
+    pub source_id: String,
+    /// When the scan finished.
+    pub scanned_at: DateTime<Utc>,
+    /// Files; folders are not counted.
+    pub objects: u64,
+    pub total_bytes: u64,
+    /// Files directly at the root, outside any folder.
+    pub root_objects: u64,
+    pub root_bytes: u64,
+    /// Per top-level folder, largest first.
+    pub folders: Vec<FolderUsage>,
+}
+
+/// How far a running scan got.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanProgress {
+    pub started_at: DateTime<Utc>,
+    pub objects: u64,
+    pub bytes: u64,
+}
+
+/// What is known about a source's usage; see [`status`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceStatsStatus {
+    /// Result of the last scan that completed.
+    pub stats: Option<SourceStats>,
+    /// Set while a scan runs.
+    pub scanning: Option<ScanProgress>,
+    /// Why the last scan failed, until the next one starts.
+    pub error: Option<String>,
+}
+
+/// Count the files of the whole source and their bytes, per top-level
+/// folder. `on_progress` gets the running file and byte totals every
+/// [`PROGRESS_EVERY`] files.
+async fn measure(
+    op: &Operator,
+    source_id: &str,
+    cancel: &CancelToken,
+    mut on_progress: impl FnMut(u64, u64),
+) -> Result<SourceStats> {
+    let mut stats = SourceStats {
+        source_id: source_id.to_string(),
+        scanned_at: Utc::now(),
+        objects: 0,
+        total_bytes: 0,
+        root_objects: 0,
+        root_bytes: 0,
+        folders: Vec::new(),
+    };
+    let mut folders: BTreeMap<String, FolderUsage> = BTreeMap::new();
+    let mut lister = op.lister_with("/").recursive(true).await?;
+    while let Some(entry) = lister.try_next().await? {
+        cancel.check()?;
+        if entry.metadata().is_dir() {
+            continue;
+        }
+        // Some backends list without sizes.
+        let size = match entry.metadata().content_length() {
+            0 => op
+                .stat(entry.path())
+                .await
+                .map_or(0, |meta| meta.content_length()),
+            len => len,
+        };
+        stats.objects += 1;
+        stats.total_bytes += size;
+        match entry.path().trim_start_matches('/').split_once('/') {
+            Some((folder, _)) => {
+                let usage = folders
+                    .entry(folder.to_string())
+                    .or_insert_with(|| FolderUsage {
+                        name: folder.to_string(),
+                        ..Default::default()
+                    });
+                usage.objects += 1;
+                usage.bytes += size;
+            }
+            None => {
+                stats.root_objects += 1;
+                stats.root_bytes += size;
+            }
+        }
+        if stats.objects.is_multiple_of(PROGRESS_EVERY) {
+            on_progress(stats.objects, stats.total_bytes);
+        }
+    }
+    stats.folders = folders.into_values().collect();
+    stats
+        .folders
+        .sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    stats.scanned_at = Utc::now();
+    Ok(stats)
+}
+
+/// Stored scan results by source id.
+#[derive(Debug, Default)]
+pub struct StatsStore {
+    path: Option<PathBuf>,
+    stats: HashMap<String, SourceStats>,
+}
+
+impl StatsStore {
+    /// Load the results stored at `path`. A missing or unreadable file yields
+    /// an empty store: the sources just need scanning again.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let stats = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            stats,
+        }
+    }
+
+    /// Load the results from `source-stats.json` in the config directory.
+    pub fn load_default() -> Self {
+        Self::load(config::data_dir().join("source-stats.json"))
+    }
+
+    pub fn get(&self, source_id: &str) -> Option<&SourceStats> {
+        self.stats.get(source_id)
+    }
+
+    /// Replace the source's previous result, then write the store back.
+    pub fn insert(&mut self, stats: SourceStats) -> Result<()> {
+        self.stats.insert(stats.source_id.clone(), stats);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let data = serde_json::to_string_pretty(&self.stats)?;
+        config::write_atomic(path, data.as_bytes())
+    }
+}
+
+fn store() -> MutexGuard<'static, StatsStore> {
+    static STORE: OnceLock<Mutex<StatsStore>> = OnceLock::new();
+    STORE
+        .get_or_init(|| Mutex::new(StatsStore::load_default()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Default)]
+struct Jobs {
+    running: HashMap<String, ScanProgress>,
+    failed: HashMap<String, String>,
+}
+
+fn jobs() -> MutexGuard<'static, Jobs> {
+    static JOBS: OnceLock<Mutex<Jobs>> = OnceLock::new();
+    JOBS.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// A scan registered with [`start`]. The source shows as being scanned
+/// until it is dropped.
+#[derive(Debug)]
+pub struct Scan {
+    source_id: String,
+}
+
+/// Register a scan of `source_id`, or `None` when one is already running.
+pub fn start(source_id: &str) -> Option<Scan> {
+    let mut jobs = jobs();
+    if jobs.running.contains_key(source_id) {
+        return None;
+    }
+    jobs.failed.remove(source_id);
+    jobs.running.insert(
+        source_id.to_string(),
+        ScanProgress {
+            started_at: Utc::now(),
+            objects: 0,
+            bytes: 0,
+        },
+    );
+    Some(Scan {
+        source_id: source_id.to_string(),
+    })
+}
+
+impl Scan {
+    /// Walk the source behind `op` and store the result, replacing the
+    /// previous one. A failure is kept for [`status`] instead.
+    pub async fn run(self, op: &Operator, cancel: &CancelToken) -> Result<SourceStats> {
+        let result = measure(op, &self.source_id, cancel, |objects, bytes| {
+            if let Some(progress) = jobs().running.get_mut(&self.source_id) {
+                progress.objects = objects;
+                progress.bytes = bytes;
+            }
+        })
+        .await
+        .and_then(|stats| store().insert(stats.clone()).map(|()| stats));
+        if let Err(error) = &result {
+            jobs()
+                .failed
+                .insert(self.source_id.clone(), error.to_string());
+        }
+        result
+    }
+}
+
+impl Drop for Scan {
+    fn drop(&mut self) {
+        jobs().running.remove(&self.source_id);
+    }
+}
+
+/// The last stored result for `source_id`, and the progress or failure of
+/// its latest scan.
+pub fn status(source_id: &str) -> SourceStatsStatus {
+    let jobs = jobs();
+    SourceStatsStatus {
+        stats: store().get(source_id).cloned(),
+        scanning: jobs.running.get(source_id).cloned(),
+        error: jobs.failed.get(source_id).cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Memory;
+
+    #[tokio::test]
+    async fn measures_usage_per_top_level_folder() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("photos/2024/a.jpg", vec![0u8; 300]).await.unwrap();
+        op.write("photos/b.jpg", vec![0u8; 200]).await.unwrap();
+        op.write("docs/report.pdf", vec![0u8; 100]).await.unwrap();
+        op.write("readme.txt", "hello").await.unwrap();
+        op.create_dir("empty/").await.unwrap();
+
+        let mut calls = 0;
+        let stats = measure(&op, "mem", &CancelToken::new(), |_, _| calls += 1)
+            .await
+            .unwrap();
+        assert_eq!(stats.objects, 4);
+        assert_eq!(stats.total_bytes, 605);
+        assert_eq!((stats.root_objects, stats.root_bytes), (1, 5));
+        let folders: Vec<(&str, u64, u64)> = stats
+            .folders
+            .iter()
+            .map(|f| (f.name.as_str(), f.objects, f.bytes))
+            .collect();
+        assert_eq!(folders, [("photos", 2, 500), ("docs", 1, 100)]);
+        assert_eq!(calls, 0);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert!(measure(&op, "mem", &cancel, |_, _| {}).await.is_err());
+    }
+
+    #[test]
+    fn only_one_scan_per_source_runs_at_a_time() {
+        let scan = start("usage-test").unwrap();
+        assert!(start("usage-test").is_none());
+        assert!(status("usage-test").scanning.is_some());
+        drop(scan);
+        assert!(status("usage-test").scanning.is_none());
+        assert!(start("usage-test").is_some());
+    }
+
+    #[test]
+    fn store_round_trips_through_disk() {
+        let dir = std::env::temp_dir().join(format!("infimount-usage-{}", std::process::id()));
+        let path = dir.join("source-stats.json");
+
+        let stats = SourceStats {
+            source_id: "src".to_string(),
+            scanned_at: Utc::now(),
+            objects: 2,
+            total_bytes: 10,
+            root_objects: 0,
+            root_bytes: 0,
+            folders: vec![FolderUsage {
+                name: "logs".to_string(),
+                objects: 2,
+                bytes: 10,
+            }],
+        };
+        StatsStore::load(&path).insert(stats.clone()).unwrap();
+        assert_eq!(StatsStore::load(&path).get("src"), Some(&stats));
+        assert_eq!(StatsStore::load(&path).get("other"), None);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
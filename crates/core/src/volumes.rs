@@ -0,0 +1,523 @@
+//! Discovery of mounted drives, volumes and common user folders that can be
+//! added as Local sources.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeKind {
+    Fixed,
+    Removable,
+    Network,
+}
+
+/// A mounted volume the user can pick as the root of a Local source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalRoot {
+    pub path: String,
+    pub label: String,
+    pub kind: VolumeKind,
+    pub total_bytes: Option<u64>,
+    pub available_bytes: Option<u64>,
+}
+
+impl LocalRoot {
+    fn new(path: PathBuf, label: String, kind: VolumeKind) -> Self {
+        let total_bytes = fs2::total_space(&path).ok();
+        let available_bytes = fs2::available_space(&path).ok();
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            label,
+            kind,
+            total_bytes,
+            available_bytes,
+        }
+    }
+}
+
+/// Volumes that appeared or disappeared between two discovery passes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolumeChanges {
+    pub added: Vec<LocalRoot>,
+    pub removed: Vec<LocalRoot>,
+}
+
+impl VolumeChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compare two discovery passes by mount path. Free-space changes on a volume
+/// that stayed mounted are not reported.
+pub fn diff_roots(previous: &[LocalRoot], current: &[LocalRoot]) -> VolumeChanges {
+    let missing_from =
+        |roots: &[LocalRoot], root: &LocalRoot| !roots.iter().any(|other| other.path == root.path);
+    VolumeChanges {
+        added: current
+            .iter()
+            .filter(|root| missing_from(previous, root))
+            .cloned()
+            .collect(),
+        removed: previous
+            .iter()
+            .filter(|root| missing_from(current, root))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// List mounted drives/volumes, including removable media and network mounts.
+/// Pseudo filesystems (proc, tmpfs, snaps, ...) are left out.
+pub fn discover_local_roots() -> Vec<LocalRoot> {
+    platform::discover()
+}
+
+/// A user folder offered as a Local source during onboarding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuggestedSource {
+    /// Stable id: `home`, `documents`, `downloads`, `desktop` or `pictures`.
+    pub id: String,
+    /// Folder name in the current locale, usable as the source name.
+    pub name: String,
+    pub path: String,
+}
+
+/// Home, Documents, Downloads, Desktop and Pictures, in that order. Folders
+/// that don't exist, or that resolve to one already listed (an XDG desktop
+/// that is just `$HOME`), are left out.
+pub fn suggested_sources() -> Vec<SuggestedSource> {
+    suggest_folders([
+        ("home", dirs::home_dir()),
+        ("documents", dirs::document_dir()),
+        ("downloads", dirs::download_dir()),
+        ("desktop", dirs::desktop_dir()),
+        ("pictures", dirs::picture_dir()),
+    ])
+}
+
+fn suggest_folders<const N: usize>(folders: [(&str, Option<PathBuf>); N]) -> Vec<SuggestedSource> {
+    let mut seen = Vec::new();
+    let mut suggestions = Vec::new();
+    for (id, path) in folders {
+        let Some(path) = path.filter(|path| path.is_dir()) else {
+            continue;
+        };
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical);
+        suggestions.push(SuggestedSource {
+            id: id.to_string(),
+            name: Message::new(format!("suggested-source-{id}"))
+                .translate()
+                .unwrap_or_else(|| label_from_path(&path)),
+            path: path.to_string_lossy().into_owned(),
+        });
+    }
+    suggestions
+}
+
+fn label_from_path(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::{label_from_path, LocalRoot, VolumeKind};
+
+    const DISK_FILESYSTEMS: &[&str] = &[
+        "ext2", "ext3", "ext4", "xfs", "btrfs", "zfs", "f2fs", "vfat", "exfat", "ntfs", "ntfs3",
+        "fuseblk", "hfsplus", "apfs", "iso9660", "udf",
+    ];
+    const NETWORK_FILESYSTEMS: &[&str] = &[
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb3",
+        "smbfs",
+        "fuse.sshfs",
+        "fuse.rclone",
+        "9p",
+    ];
+
+    pub(super) fn discover() -> Vec<LocalRoot> {
+        let Ok(mounts) = fs::read_to_string("/proc/self/mounts") else {
+            return Vec::new();
+        };
+        let labels = device_labels();
+        parse_mounts(&mounts)
+            .into_iter()
+            .filter_map(|mount| {
+                let kind = classify(&mount)?;
+                let path = PathBuf::from(&mount.mount_point);
+                let label = labels
+                    .get(&canonical_device(&mount.device))
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        if mount.mount_point == "/" {
+                            "System".to_string()
+                        } else {
+                            label_from_path(&path)
+                        }
+                    });
+                Some(LocalRoot::new(path, label, kind))
+            })
+            .collect()
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub(super) struct Mount {
+        pub device: String,
+        pub mount_point: String,
+        pub fs_type: String,
+    }
+
+    pub(super) fn parse_mounts(contents: &str) -> Vec<Mount> {
+        let mut seen = std::collections::HashSet::new();
+        contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                Some(Mount {
+                    device: unescape(fields.next()?),
+                    mount_point: unescape(fields.next()?),
+                    fs_type: fields.next()?.to_string(),
+                })
+            })
+            // Bind mounts and btrfs subvolumes repeat the same device.
+            .filter(|mount| seen.insert(mount.mount_point.clone()))
+            .collect()
+    }
+
+    pub(super) fn classify(mount: &Mount) -> Option<VolumeKind> {
+        if NETWORK_FILESYSTEMS.contains(&mount.fs_type.as_str()) {
+            return Some(VolumeKind::Network);
+        }
+        if !DISK_FILESYSTEMS.contains(&mount.fs_type.as_str()) {
+            return None;
+        }
+        // Boot partitions and snap/flatpak images are not useful roots.
+        if mount.mount_point.starts_with("/boot")
+            || mount.mount_point.starts_with("/snap")
+            || mount.mount_point.starts_with("/var/lib")
+        {
+            return None;
+        }
+        if mount.mount_point.starts_with("/media/")
+            || mount.mount_point.starts_with("/run/media/")
+            || is_removable_device(&mount.device)
+        {
+            return Some(VolumeKind::Removable);
+        }
+        Some(VolumeKind::Fixed)
+    }
+
+    /// `/proc/self/mounts` escapes spaces and tabs as octal sequences.
+    fn unescape(field: &str) -> String {
+        field
+            .replace("\\040", " ")
+            .replace("\\011", "\t")
+            .replace("\\012", "\n")
+            .replace("\\134", "\\")
+    }
+
+    fn canonical_device(device: &str) -> PathBuf {
+        fs::canonicalize(device).unwrap_or_else(|_| PathBuf::from(device))
+    }
+
+    fn device_labels() -> HashMap<PathBuf, String> {
+        let Ok(entries) = fs::read_dir("/dev/disk/by-label") else {
+            return HashMap::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let device = fs::canonicalize(entry.path()).ok()?;
+                let label = unescape_udev(&entry.file_name().to_string_lossy());
+                Some((device, label))
+            })
+            .collect()
+    }
+
+    /// udev encodes unsafe label characters as `\xNN`.
+    fn unescape_udev(label: &str) -> String {
+        let mut out = String::with_capacity(label.len());
+        let mut rest = label;
+        while let Some(idx) = rest.find("\\x") {
+            out.push_str(&rest[..idx]);
+            let hex = rest.get(idx + 2..idx + 4);
+            match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                Some(byte) => {
+                    out.push(byte as char);
+                    rest = &rest[idx + 4..];
+                }
+                None => {
+                    out.push_str("\\x");
+                    rest = &rest[idx + 2..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Check `/sys/block/<disk>/removable` for the disk backing a partition.
+    fn is_removable_device(device: &str) -> bool {
+        let device = canonical_device(device);
+        let Some(name) = device.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            return false;
+        };
+        let Ok(sys_path) = fs::canonicalize(Path::new("/sys/class/block").join(&name)) else {
+            return false;
+        };
+        // Partitions live below their disk: /sys/devices/.../sdb/sdb1.
+        let disk = if sys_path.join("partition").exists() {
+            sys_path.parent().map(Path::to_path_buf)
+        } else {
+            Some(sys_path)
+        };
+        disk.and_then(|disk| fs::read_to_string(disk.join("removable")).ok())
+            .map(|flag| flag.trim() == "1")
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::{label_from_path, LocalRoot, VolumeKind};
+
+    pub(super) fn discover() -> Vec<LocalRoot> {
+        let mut roots = vec![LocalRoot::new(
+            PathBuf::from("/"),
+            "Macintosh HD".to_string(),
+            VolumeKind::Fixed,
+        )];
+        let Ok(entries) = fs::read_dir("/Volumes") else {
+            return roots;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // The boot volume also appears in /Volumes as a symlink to "/".
+            if fs::read_link(&path).is_ok() {
+                continue;
+            }
+            let kind = if is_network_mount(&path) {
+                VolumeKind::Network
+            } else {
+                VolumeKind::Removable
+            };
+            roots.push(LocalRoot::new(path.clone(), label_from_path(&path), kind));
+        }
+        roots
+    }
+
+    fn is_network_mount(path: &std::path::Path) -> bool {
+        let Ok(output) = std::process::Command::new("/sbin/mount").output() else {
+            return false;
+        };
+        let needle = format!(" on {} (", path.display());
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find(|line| line.contains(&needle))
+            .map(|line| {
+                ["smbfs", "nfs", "afpfs", "webdav"]
+                    .iter()
+                    .any(|fs| line.contains(fs))
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::PathBuf;
+
+    use super::{LocalRoot, VolumeKind};
+
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_FIXED: u32 = 3;
+    const DRIVE_REMOTE: u32 = 4;
+    const DRIVE_CDROM: u32 = 5;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetLogicalDrives() -> u32;
+        fn GetDriveTypeW(root: *const u16) -> u32;
+        fn GetVolumeInformationW(
+            root: *const u16,
+            name: *mut u16,
+            name_len: u32,
+            serial: *mut u32,
+            max_component: *mut u32,
+            flags: *mut u32,
+            fs_name: *mut u16,
+            fs_name_len: u32,
+        ) -> i32;
+    }
+
+    pub(super) fn discover() -> Vec<LocalRoot> {
+        // SAFETY: GetLogicalDrives takes no arguments and returns a bitmask.
+        let mask = unsafe { GetLogicalDrives() };
+        (0..26u8)
+            .filter(|bit| mask & (1 << bit) != 0)
+            .filter_map(|bit| {
+                let root = format!("{}:\\", (b'A' + bit) as char);
+                let wide: Vec<u16> = std::ffi::OsStr::new(&root)
+                    .encode_wide()
+                    .chain(Some(0))
+                    .collect();
+                // SAFETY: `wide` is a NUL-terminated UTF-16 root path.
+                let kind = match unsafe { GetDriveTypeW(wide.as_ptr()) } {
+                    DRIVE_FIXED => VolumeKind::Fixed,
+                    DRIVE_REMOVABLE | DRIVE_CDROM => VolumeKind::Removable,
+                    DRIVE_REMOTE => VolumeKind::Network,
+                    _ => return None,
+                };
+                let label = volume_label(&wide)
+                    .filter(|label| !label.is_empty())
+                    .unwrap_or_else(|| root.trim_end_matches('\\').to_string());
+                Some(LocalRoot::new(PathBuf::from(&root), label, kind))
+            })
+            .collect()
+    }
+
+    fn volume_label(root: &[u16]) -> Option<String> {
+        let mut name = [0u16; 261];
+        // SAFETY: buffers are valid for the lengths passed; optional out
+        // parameters are null.
+        let ok = unsafe {
+            GetVolumeInformationW(
+                root.as_ptr(),
+                name.as_mut_ptr(),
+                name.len() as u32,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        if ok == 0 {
+            return None;
+        }
+        let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+        Some(
+            OsString::from_wide(&name[..len])
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::LocalRoot;
+
+    pub(super) fn discover() -> Vec<LocalRoot> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(path: &str, available: u64) -> LocalRoot {
+        LocalRoot {
+            path: path.to_string(),
+            label: path.to_string(),
+            kind: VolumeKind::Removable,
+            total_bytes: Some(100),
+            available_bytes: Some(available),
+        }
+    }
+
+    #[test]
+    fn diff_reports_inserted_and_ejected_volumes() {
+        let before = vec![root("/media/a", 10), root("/media/b", 10)];
+        let after = vec![root("/media/b", 5), root("/media/c", 10)];
+
+        let changes = diff_roots(&before, &after);
+        assert_eq!(changes.added, vec![root("/media/c", 10)]);
+        assert_eq!(changes.removed, vec![root("/media/a", 10)]);
+        assert!(diff_roots(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn suggestions_skip_missing_and_duplicate_folders() {
+        let home = std::env::temp_dir().join(format!("infimount-home-{}", std::process::id()));
+        std::fs::create_dir_all(home.join("Documents")).unwrap();
+
+        let suggestions = suggest_folders([
+            ("home", Some(home.clone())),
+            ("documents", Some(home.join("Documents"))),
+            ("downloads", Some(home.join("Downloads"))),
+            ("desktop", Some(home.clone())),
+            ("pictures", None),
+        ]);
+        let ids: Vec<_> = suggestions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["home", "documents"]);
+        assert_eq!(suggestions[1].name, "Documents");
+        let _ = std::fs::remove_dir_all(home);
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod linux_tests {
+    use super::platform::{classify, parse_mounts};
+    use super::*;
+
+    #[test]
+    fn mounts_are_classified_and_pseudo_filesystems_skipped() {
+        let mounts = parse_mounts(
+            "proc /proc proc rw 0 0\n\
+             /dev/nvme0n1p2 / ext4 rw 0 0\n\
+             /dev/nvme0n1p1 /boot/efi vfat rw 0 0\n\
+             tmpfs /run tmpfs rw 0 0\n\
+             /dev/sdb1 /media/me/USB\\040STICK exfat rw 0 0\n\
+             nas:/export /mnt/nas nfs4 rw 0 0\n\
+             /dev/nvme0n1p2 / ext4 rw 0 0\n",
+        );
+        assert_eq!(mounts.len(), 6);
+
+        let kinds: Vec<_> = mounts
+            .iter()
+            .map(|m| (m.mount_point.as_str(), classify(m)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("/proc", None),
+                ("/", Some(VolumeKind::Fixed)),
+                ("/boot/efi", None),
+                ("/run", None),
+                ("/media/me/USB STICK", Some(VolumeKind::Removable)),
+                ("/mnt/nas", Some(VolumeKind::Network)),
+            ]
+        );
+    }
+
+    #[test]
+    fn discovery_skips_pseudo_filesystems() {
+        let roots = discover_local_roots();
+        assert!(roots.iter().all(|root| !root.path.starts_with("/proc")));
+    }
+}
@@ -0,0 +1,169 @@
+//! Dedicated worker pool for backend calls made on behalf of the UI.
+//!
+//! Listing, reading and searching run here instead of on the runtime that
+//! serves UI commands, so a stuck backend cannot starve unrelated commands.
+//! Each job has a timeout, and at most [`MAX_IN_FLIGHT`] jobs run at once;
+//! further jobs wait for a slot (backpressure) instead of piling onto the
+//! backends. [`stats`] reports what the pool is doing.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::Semaphore;
+
+use crate::models::{CoreError, Result};
+
+/// Jobs running at the same time; later ones queue.
+pub const MAX_IN_FLIGHT: usize = 64;
+/// Timeout for listings and metadata calls.
+pub const LIST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Timeout for reading file contents.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Timeout for walks over a whole tree, such as searches.
+pub const WALK_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+struct Pool {
+    runtime: Runtime,
+    threads: usize,
+    slots: Semaphore,
+    queued: AtomicUsize,
+    running: AtomicUsize,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    timed_out: AtomicU64,
+}
+
+fn pool() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let threads = std::thread::available_parallelism()
+            .map_or(4, usize::from)
+            .clamp(2, 8);
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(threads)
+            .thread_name("infimount-worker")
+            .enable_all()
+            .build()
+            .expect("failed to start the worker pool");
+        Pool {
+            runtime,
+            threads,
+            slots: Semaphore::new(MAX_IN_FLIGHT),
+            queued: AtomicUsize::new(0),
+            running: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            timed_out: AtomicU64::new(0),
+        }
+    })
+}
+
+/// What the worker pool is doing, for diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuntimeStats {
+    pub worker_threads: usize,
+    pub max_in_flight: usize,
+    /// Jobs waiting for a slot.
+    pub queued: usize,
+    pub running: usize,
+    /// Finished jobs since startup, by outcome.
+    pub completed: u64,
+    pub failed: u64,
+    pub timed_out: u64,
+}
+
+pub fn stats() -> RuntimeStats {
+    let pool = pool();
+    RuntimeStats {
+        worker_threads: pool.threads,
+        max_in_flight: MAX_IN_FLIGHT,
+        queued: pool.queued.load(Ordering::Relaxed),
+        running: pool.running.load(Ordering::Relaxed),
+        completed: pool.completed.load(Ordering::Relaxed),
+        failed: pool.failed.load(Ordering::Relaxed),
+        timed_out: pool.timed_out.load(Ordering::Relaxed),
+    }
+}
+
+/// Counts itself in a gauge for as long as it lives, so callers that go
+/// away while waiting are not left counted.
+struct Gauge<'a>(&'a AtomicUsize);
+
+impl<'a> Gauge<'a> {
+    fn enter(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for Gauge<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Run `job` on the worker pool once a slot is free, giving up with
+/// [`CoreError::Timeout`] after `timeout`. `name` describes the job in the
+/// error.
+pub async fn run<T, F>(name: &str, timeout: Duration, job: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T>> + Send + 'static,
+{
+    let pool = pool();
+    let queued = Gauge::enter(&pool.queued);
+    let _slot = pool
+        .slots
+        .acquire()
+        .await
+        .map_err(|_| CoreError::Timeout(name.to_string()))?;
+    drop(queued);
+
+    let running = Gauge::enter(&pool.running);
+    // Dropping the timed-out job cancels it on the pool as well.
+    let outcome = pool.runtime.spawn(tokio::time::timeout(timeout, job)).await;
+    drop(running);
+
+    let counter = match &outcome {
+        Ok(Ok(Ok(_))) => &pool.completed,
+        Ok(Err(_)) => &pool.timed_out,
+        _ => &pool.failed,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    match outcome {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err(CoreError::Timeout(name.to_string())),
+        Err(join_error) => Err(CoreError::Io(std::io::Error::other(format!(
+            "{name} failed: {join_error}"
+        )))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn jobs_run_on_the_pool_with_a_timeout() {
+        let thread = run("name", LIST_TIMEOUT, async {
+            Ok(std::thread::current().name().map(str::to_string))
+        })
+        .await
+        .unwrap();
+        assert_eq!(thread.as_deref(), Some("infimount-worker"));
+
+        let before = stats().timed_out;
+        let slow = run("slow listing", Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+        assert!(matches!(slow, Err(CoreError::Timeout(name)) if name == "slow listing"));
+        assert!(stats().timed_out > before);
+        assert_eq!(stats().max_in_flight, MAX_IN_FLIGHT);
+    }
+}
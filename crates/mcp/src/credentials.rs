@@ -0,0 +1,391 @@
+//! Named credential sets shared between storages.
+//!
+//! A credential set is a bundle of secret config values (for example the
+//! access key pair of one MinIO server) kept in the OS keyring under
+//! `credentials/<set id>/<key>`. Storages that use a set hold `keyring:`
+//! references to those accounts plus a `credentialId` config key, so
+//! rotating the set updates every storage at once. `credentials.json`, next
+//! to the storage registry, only lists the sets and their key names.
+
+use chrono::Utc;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::errors::{err, err_with_details, map_io_error, McpErrorCode, McpResult};
+use crate::registry::StorageRecord;
+use crate::secrets::{self, REFERENCE_PREFIX};
+
+const CREDENTIALS_LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Keyring account prefix of values owned by a credential set.
+pub const ACCOUNT_PREFIX: &str = "credentials/";
+
+/// Storage config key naming the credential set a storage uses.
+pub const CREDENTIAL_ID_KEY: &str = "credentialId";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialSet {
+    pub id: String,
+    pub name: String,
+    /// Config keys provided by this set (e.g. `accessKeyId`).
+    pub keys: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl CredentialSet {
+    fn account(&self, key: &str) -> String {
+        format!("{ACCOUNT_PREFIX}{}/{key}", self.id)
+    }
+
+    /// Config values that point a storage at this set.
+    pub fn references(&self) -> Map<String, Value> {
+        let mut config: Map<String, Value> = self
+            .keys
+            .iter()
+            .map(|key| {
+                (
+                    key.clone(),
+                    Value::String(format!("{REFERENCE_PREFIX}{}", self.account(key))),
+                )
+            })
+            .collect();
+        config.insert(
+            CREDENTIAL_ID_KEY.to_string(),
+            Value::String(self.id.clone()),
+        );
+        config
+    }
+}
+
+/// Whether a keyring account belongs to a credential set rather than to a
+/// single storage.
+pub fn is_shared_account(account: &str) -> bool {
+    account.starts_with(ACCOUNT_PREFIX)
+}
+
+/// Point `storage` at `set`: its keys are replaced with references to the
+/// shared keyring entries.
+pub fn link(storage: &mut StorageRecord, set: &CredentialSet) {
+    if !storage.config.is_object() {
+        storage.config = Value::Object(Map::new());
+    }
+    if let Value::Object(config) = &mut storage.config {
+        config.extend(set.references());
+    }
+}
+
+/// Id of the credential set `storage` uses, if any.
+pub fn linked_set_id(storage: &StorageRecord) -> Option<&str> {
+    storage.config.get(CREDENTIAL_ID_KEY)?.as_str()
+}
+
+#[derive(Debug, Clone)]
+pub struct CredentialStore {
+    path: PathBuf,
+    lock_path: PathBuf,
+}
+
+impl CredentialStore {
+    pub fn new(path: PathBuf) -> Self {
+        let lock_path = path.with_extension("lock");
+        Self { path, lock_path }
+    }
+
+    /// Store kept next to the storage registry file at `registry_path`.
+    pub fn beside(registry_path: &Path) -> Self {
+        Self::new(registry_path.with_file_name("credentials.json"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn list(&self) -> McpResult<Vec<CredentialSet>> {
+        self.with_file_lock(|| self.load_unlocked())
+    }
+
+    pub fn get(&self, id: &str) -> McpResult<CredentialSet> {
+        self.list()?
+            .into_iter()
+            .find(|set| set.id == id)
+            .ok_or_else(|| not_found(id))
+    }
+
+    /// Create a set (`id` = `None`) or rotate an existing one. `values` are
+    /// written to the keyring; keys the set no longer provides are deleted.
+    pub fn save(
+        &self,
+        id: Option<&str>,
+        name: &str,
+        values: &Map<String, Value>,
+    ) -> McpResult<CredentialSet> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(err(
+                McpErrorCode::ERR_INVALID_STORAGE_NAME,
+                "credential set name must not be empty",
+            ));
+        }
+
+        self.with_file_lock(|| {
+            let mut sets = self.load_unlocked()?;
+            if sets
+                .iter()
+                .any(|set| set.name == name && Some(set.id.as_str()) != id)
+            {
+                return Err(err_with_details(
+                    McpErrorCode::ERR_ALREADY_EXISTS,
+                    format!("credential set '{name}' already exists"),
+                    json!({ "name": name }),
+                ));
+            }
+
+            let now = Utc::now().to_rfc3339();
+            let (idx, previous_keys) = match id {
+                Some(id) => {
+                    let idx = sets
+                        .iter()
+                        .position(|set| set.id == id)
+                        .ok_or_else(|| not_found(id))?;
+                    (idx, sets[idx].keys.clone())
+                }
+                None => {
+                    sets.push(CredentialSet {
+                        id: Uuid::new_v4().to_string(),
+                        name: name.to_string(),
+                        keys: Vec::new(),
+                        created_at: now.clone(),
+                        updated_at: now.clone(),
+                    });
+                    (sets.len() - 1, Vec::new())
+                }
+            };
+
+            let set = &mut sets[idx];
+            set.name = name.to_string();
+            set.updated_at = now;
+            set.keys = values.keys().cloned().collect();
+            for (key, value) in values {
+                let Some(secret) = value.as_str() else {
+                    return Err(err_with_details(
+                        McpErrorCode::ERR_INTERNAL,
+                        format!("credential value '{key}' must be a string"),
+                        json!({ "key": key }),
+                    ));
+                };
+                secrets::store(&set.account(key), secret)?;
+            }
+            for key in previous_keys
+                .iter()
+                .filter(|key| !values.contains_key(*key))
+            {
+                secrets::delete(&set.account(key));
+            }
+
+            let saved = set.clone();
+            self.save_atomic_unlocked(&sets)?;
+            Ok(saved)
+        })
+    }
+
+    /// Delete a set and its keyring entries. Fails while any of `storages`
+    /// still uses it.
+    pub fn remove(&self, id: &str, storages: &[StorageRecord]) -> McpResult<()> {
+        let users: Vec<&str> = storages
+            .iter()
+            .filter(|storage| linked_set_id(storage) == Some(id))
+            .map(|storage| storage.name.as_str())
+            .collect();
+        if !users.is_empty() {
+            return Err(err_with_details(
+                McpErrorCode::ERR_CREDENTIAL_IN_USE,
+                format!("credential set is used by {}", users.join(", ")),
+                json!({ "credential_id": id, "storages": users }),
+            ));
+        }
+
+        self.with_file_lock(|| {
+            let mut sets = self.load_unlocked()?;
+            let idx = sets
+                .iter()
+                .position(|set| set.id == id)
+                .ok_or_else(|| not_found(id))?;
+            let removed = sets.remove(idx);
+            self.save_atomic_unlocked(&sets)?;
+            for key in &removed.keys {
+                secrets::delete(&removed.account(key));
+            }
+            Ok(())
+        })
+    }
+
+    /// Add the sets from another machine that are missing here, keeping
+    /// their ids so imported storages stay linked. Their keyring entries are
+    /// not copied; rotate each returned set to enter its values.
+    pub fn restore(&self, sets: &[CredentialSet]) -> McpResult<Vec<CredentialSet>> {
+        self.with_file_lock(|| {
+            let mut existing = self.load_unlocked()?;
+            let mut restored = Vec::new();
+            for set in sets {
+                if existing.iter().any(|other| other.id == set.id) {
+                    continue;
+                }
+                let mut set = set.clone();
+                if existing.iter().any(|other| other.name == set.name) {
+                    set.name = format!("{} (imported)", set.name);
+                }
+                existing.push(set.clone());
+                restored.push(set);
+            }
+            if !restored.is_empty() {
+                self.save_atomic_unlocked(&existing)?;
+            }
+            Ok(restored)
+        })
+    }
+
+    fn load_unlocked(&self) -> McpResult<Vec<CredentialSet>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let data = fs::read_to_string(&self.path)
+            .map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
+        serde_json::from_str(&data).map_err(|e| {
+            err_with_details(
+                McpErrorCode::ERR_INTERNAL,
+                "failed to parse credential sets",
+                json!({ "serde_error": e.to_string(), "path": self.path }),
+            )
+        })
+    }
+
+    fn save_atomic_unlocked(&self, sets: &[CredentialSet]) -> McpResult<()> {
+        ensure_parent(&self.path)?;
+        let tmp_path = self.path.with_file_name(format!(
+            ".credentials.json.tmp.{}.{}",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+
+        let payload = serde_json::to_vec_pretty(sets).map_err(|e| {
+            err_with_details(
+                McpErrorCode::ERR_INTERNAL,
+                "failed to serialize credential sets",
+                json!({ "serde_error": e.to_string() }),
+            )
+        })?;
+
+        fs::write(&tmp_path, payload).map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
+        fs::rename(&tmp_path, &self.path)
+            .map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
+        Ok(())
+    }
+
+    fn with_file_lock<T>(&self, f: impl FnOnce() -> McpResult<T>) -> McpResult<T> {
+        ensure_parent(&self.lock_path)?;
+
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(&self.lock_path)
+            .map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
+
+        let start = Instant::now();
+        loop {
+            match lock_file.try_lock_exclusive() {
+                Ok(()) => break,
+                Err(_) if start.elapsed() >= CREDENTIALS_LOCK_TIMEOUT => {
+                    return Err(err(
+                        McpErrorCode::ERR_REGISTRY_LOCK_TIMEOUT,
+                        "timed out acquiring credential store lock",
+                    ));
+                }
+                Err(_) => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+
+        let result = f();
+        let _ = lock_file.unlock();
+        result
+    }
+}
+
+fn not_found(id: &str) -> crate::errors::McpError {
+    err_with_details(
+        McpErrorCode::ERR_CREDENTIAL_NOT_FOUND,
+        format!("credential set '{id}' not found"),
+        json!({ "credential_id": id }),
+    )
+}
+
+fn ensure_parent(path: &Path) -> McpResult<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent).map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infimount_core::secrets::memory_keyring;
+
+    #[test]
+    fn rotating_a_set_updates_every_linked_storage() {
+        memory_keyring::install();
+        let dir = tempfile::tempdir().unwrap();
+        let store = CredentialStore::beside(&dir.path().join("storages.json"));
+
+        let values = |secret: &str| {
+            Map::from_iter([
+                ("accessKeyId".to_string(), json!("minio")),
+                ("secretAccessKey".to_string(), json!(secret)),
+            ])
+        };
+        let set = store.save(None, "MinIO", &values("old")).unwrap();
+
+        let mut storages: Vec<StorageRecord> = ["a", "b"]
+            .iter()
+            .map(|bucket| {
+                let mut storage = StorageRecord::new(
+                    format!("bucket-{bucket}"),
+                    "s3".to_string(),
+                    json!({ "bucketName": bucket }),
+                );
+                link(&mut storage, &set);
+                storage
+            })
+            .collect();
+        assert_eq!(linked_set_id(&storages[0]), Some(set.id.as_str()));
+        assert!(!secrets::is_secret_key(CREDENTIAL_ID_KEY));
+
+        store.save(Some(&set.id), "MinIO", &values("new")).unwrap();
+        for storage in &storages {
+            let resolved = secrets::resolve(storage).unwrap();
+            assert_eq!(resolved.config["secretAccessKey"], "new");
+            assert_eq!(resolved.config["bucketName"], storage.config["bucketName"]);
+        }
+
+        assert!(store.remove(&set.id, &storages).is_err());
+        storages.clear();
+        store.remove(&set.id, &storages).unwrap();
+        assert!(store.list().unwrap().is_empty());
+        assert!(!memory_keyring::contains(&format!(
+            "credentials/{}/secretAccessKey",
+            set.id
+        )));
+    }
+}
@@ -0,0 +1,534 @@
+//! Reading and writing remotes encrypted with rclone's `crypt` backend.
+//!
+//! A storage whose config has `cryptPassword` is wrapped in [`CryptLayer`]:
+//! paths are encrypted on the way down, listed names and sizes decrypted
+//! on the way up, and file contents are decrypted while streaming, so the
+//! rest of the app sees plain files. Settings mirror the crypt remote in
+//! `rclone.conf`:
+//!
+//! - `cryptPassword`, `cryptPassword2`: `password` and `password2` (the
+//!   salt). Set `cryptObscured: true` to paste them as they appear in
+//!   `rclone.conf` instead of in plain text.
+//! - `cryptFilenameEncryption`: `standard` (default), `obfuscate` or `off`.
+//! - `cryptFilenameEncoding`: `base32` (default) or `base64`.
+//! - `cryptDirectoryNameEncryption`: defaults to `true`.
+//!
+//! Entries whose names do not decrypt are skipped in listings, as rclone
+//! does. Presigned links are unavailable since they would serve ciphertext.
+
+pub mod cipher;
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use opendal::raw::{
+    oio, Access, Accessor, BytesRange, Layer, LayeredAccess, OpCopy, OpCreateDir, OpDelete, OpList,
+    OpPresign, OpRead, OpRename, OpStat, OpWrite, RpCopy, RpCreateDir, RpDelete, RpList, RpPresign,
+    RpRead, RpRename, RpStat, RpWrite,
+};
+use opendal::{Buffer, ErrorKind, Metadata, Operator};
+use serde_json::Value;
+
+use self::cipher::{
+    block_nonce, decrypted_size, Cipher, CryptError, NameEncoding, NameEncryption, BLOCK_DATA_SIZE,
+    BLOCK_SIZE, HEADER_SIZE, NONCE_SIZE,
+};
+use crate::errors::{err_with_details, McpErrorCode, McpResult};
+use crate::registry::StorageRecord;
+
+/// Sealed blocks are sent to the backend in batches of this size, which
+/// is above the smallest multipart part S3 accepts.
+const WRITE_BATCH_SIZE: usize = 128 * BLOCK_SIZE;
+
+pub struct CryptLayer(Arc<Cipher>);
+
+impl CryptLayer {
+    /// The layer for a storage with `cryptPassword` set, `None` otherwise.
+    pub fn for_storage(storage: &StorageRecord) -> McpResult<Option<Self>> {
+        let text = |key: &str| {
+            storage
+                .config
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+        let Some(password) = text("cryptPassword") else {
+            return Ok(None);
+        };
+        let salt = text("cryptPassword2").unwrap_or_default();
+        let obscured = matches!(storage.config.get("cryptObscured"), Some(Value::Bool(true)))
+            || text("cryptObscured").is_some_and(|v| v.eq_ignore_ascii_case("true"));
+        let (password, salt) = if obscured {
+            let reveal = |value: &str| {
+                if value.is_empty() {
+                    return Ok(String::new());
+                }
+                cipher::reveal(value).map_err(|e| invalid(storage, e.to_string()))
+            };
+            (reveal(password)?, reveal(salt)?)
+        } else {
+            (password.to_string(), salt.to_string())
+        };
+
+        let names = match text("cryptFilenameEncryption").unwrap_or("standard") {
+            "standard" => NameEncryption::Standard,
+            "obfuscate" => NameEncryption::Obfuscate,
+            "off" => NameEncryption::Off,
+            other => {
+                return Err(invalid(
+                    storage,
+                    format!("unknown filename encryption '{other}'"),
+                ))
+            }
+        };
+        let encoding = match text("cryptFilenameEncoding").unwrap_or("base32") {
+            "base32" => NameEncoding::Base32,
+            "base64" => NameEncoding::Base64,
+            other => {
+                return Err(err_with_details(
+                    McpErrorCode::ERR_BACKEND_UNSUPPORTED,
+                    format!("filename encoding '{other}' is not supported"),
+                    serde_json::json!({ "storage": storage.name }),
+                ))
+            }
+        };
+        let encrypt_dirs = match storage.config.get("cryptDirectoryNameEncryption") {
+            Some(Value::Bool(value)) => *value,
+            Some(Value::String(value)) => !value.eq_ignore_ascii_case("false"),
+            _ => true,
+        };
+        Ok(Some(Self(Arc::new(Cipher::new(
+            &password,
+            &salt,
+            names,
+            encoding,
+            encrypt_dirs,
+        )))))
+    }
+}
+
+fn invalid(storage: &StorageRecord, reason: String) -> crate::McpError {
+    err_with_details(
+        McpErrorCode::ERR_INTERNAL,
+        format!("invalid crypt config: {reason}"),
+        serde_json::json!({ "storage": storage.name }),
+    )
+}
+
+fn crypt_error(path: &str, error: CryptError) -> opendal::Error {
+    opendal::Error::new(ErrorKind::Unexpected, error.to_string()).with_context("path", path)
+}
+
+impl Layer<Accessor> for CryptLayer {
+    type LayeredAccess = CryptAccessor;
+
+    fn layer(&self, inner: Accessor) -> Self::LayeredAccess {
+        inner.info().update_full_capability(|mut cap| {
+            cap.presign = false;
+            cap.presign_read = false;
+            cap.presign_stat = false;
+            cap.presign_write = false;
+            cap.write_can_append = false;
+            // Encrypted names do not sort like the plain ones.
+            cap.list_with_start_after = false;
+            cap
+        });
+        CryptAccessor {
+            // Listing goes through the public API, whose entries expose
+            // the metadata sizes are decrypted from.
+            op: Operator::from_inner(inner.clone()),
+            inner,
+            cipher: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CryptAccessor {
+    inner: Accessor,
+    op: Operator,
+    cipher: Arc<Cipher>,
+}
+
+/// File sizes in metadata are ciphertext sizes; directories are left alone.
+fn decrypt_metadata(metadata: Metadata) -> Metadata {
+    if !metadata.is_file() {
+        return metadata;
+    }
+    match decrypted_size(metadata.content_length()) {
+        Ok(size) => metadata.with_content_length(size),
+        Err(_) => metadata,
+    }
+}
+
+impl LayeredAccess for CryptAccessor {
+    type Inner = Accessor;
+    type Reader = CryptReader;
+    type Writer = CryptWriter;
+    type Lister = CryptLister;
+    type Deleter = CryptDeleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> opendal::Result<RpCreateDir> {
+        self.inner
+            .create_dir(&self.cipher.encrypt_path(path), args)
+            .await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+        let encrypted = self.cipher.encrypt_path(path);
+        let range = args.range();
+        let first_block = range.offset() / BLOCK_DATA_SIZE as u64;
+        // Block ends are unknown without a stat, so read to the end and
+        // stop once the requested range is served.
+        let (nonce, offset) = if first_block == 0 {
+            (None, 0)
+        } else {
+            let (_, mut header) = self
+                .inner
+                .read(
+                    &encrypted,
+                    OpRead::new().with_range(BytesRange::new(0, Some(HEADER_SIZE as u64))),
+                )
+                .await?;
+            let header = oio::Read::read_all(&mut header).await?.to_vec();
+            let nonce = Cipher::parse_header(&header).map_err(|e| crypt_error(path, e))?;
+            (
+                Some(nonce),
+                HEADER_SIZE as u64 + first_block * BLOCK_SIZE as u64,
+            )
+        };
+        let (_, reader) = self
+            .inner
+            .read(&encrypted, args.with_range(BytesRange::new(offset, None)))
+            .await?;
+        Ok((
+            RpRead::new(),
+            CryptReader {
+                inner: reader,
+                cipher: self.cipher.clone(),
+                path: path.to_string(),
+                nonce,
+                block: first_block,
+                pending: Vec::new(),
+                eof: false,
+                skip: (range.offset() % BLOCK_DATA_SIZE as u64) as usize,
+                remaining: range.size(),
+            },
+        ))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> opendal::Result<(RpWrite, Self::Writer)> {
+        if args.append() {
+            return Err(opendal::Error::new(
+                ErrorKind::Unsupported,
+                "appending to encrypted files is not supported",
+            ));
+        }
+        let (rp, writer) = self
+            .inner
+            .write(&self.cipher.encrypt_path(path), args)
+            .await?;
+        let (header, nonce) = Cipher::new_header();
+        Ok((
+            rp,
+            CryptWriter {
+                inner: writer,
+                cipher: self.cipher.clone(),
+                nonce,
+                block: 0,
+                plain: Vec::with_capacity(BLOCK_DATA_SIZE),
+                sealed: header.to_vec(),
+                written: 0,
+            },
+        ))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> opendal::Result<RpCopy> {
+        // Contents do not depend on the name, so server-side copies work.
+        self.inner
+            .copy(
+                &self.cipher.encrypt_path(from),
+                &self.cipher.encrypt_path(to),
+                args,
+            )
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> opendal::Result<RpRename> {
+        self.inner
+            .rename(
+                &self.cipher.encrypt_path(from),
+                &self.cipher.encrypt_path(to),
+                args,
+            )
+            .await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> opendal::Result<RpStat> {
+        self.inner
+            .stat(&self.cipher.encrypt_path(path), args)
+            .await
+            .map(|rp| rp.map_metadata(decrypt_metadata))
+    }
+
+    async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+        let (rp, deleter) = self.inner.delete().await?;
+        Ok((
+            rp,
+            CryptDeleter {
+                inner: deleter,
+                cipher: self.cipher.clone(),
+            },
+        ))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+        let encrypted = self.cipher.encrypt_path(path);
+        let mut lister = self
+            .op
+            .lister_with(&encrypted)
+            .recursive(args.recursive())
+            .versions(args.versions())
+            .deleted(args.deleted());
+        if let Some(limit) = args.limit() {
+            lister = lister.limit(limit);
+        }
+        Ok((
+            RpList::default(),
+            CryptLister {
+                inner: lister.await?,
+                cipher: self.cipher.clone(),
+            },
+        ))
+    }
+
+    async fn presign(&self, _: &str, _: OpPresign) -> opendal::Result<RpPresign> {
+        Err(opendal::Error::new(
+            ErrorKind::Unsupported,
+            "presigned links are not available for encrypted storages",
+        ))
+    }
+}
+
+pub struct CryptReader {
+    inner: oio::Reader,
+    cipher: Arc<Cipher>,
+    path: String,
+    /// `None` until the header at the start of the stream is read.
+    nonce: Option<[u8; NONCE_SIZE]>,
+    block: u64,
+    pending: Vec<u8>,
+    eof: bool,
+    /// Plaintext bytes to drop from the first block.
+    skip: usize,
+    remaining: Option<u64>,
+}
+
+impl CryptReader {
+    async fn fill(&mut self, want: usize) -> opendal::Result<()> {
+        while self.pending.len() < want && !self.eof {
+            let buffer = oio::Read::read(&mut self.inner).await?;
+            if buffer.is_empty() {
+                self.eof = true;
+            } else {
+                self.pending.extend_from_slice(&buffer.to_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl oio::Read for CryptReader {
+    async fn read(&mut self) -> opendal::Result<Buffer> {
+        loop {
+            if self.remaining == Some(0) {
+                return Ok(Buffer::new());
+            }
+            let nonce = match self.nonce {
+                Some(nonce) => nonce,
+                None => {
+                    self.fill(HEADER_SIZE).await?;
+                    let nonce = Cipher::parse_header(&self.pending)
+                        .map_err(|e| crypt_error(&self.path, e))?;
+                    self.pending.drain(..HEADER_SIZE);
+                    self.nonce = Some(nonce);
+                    nonce
+                }
+            };
+            self.fill(BLOCK_SIZE).await?;
+            if self.pending.is_empty() {
+                return Ok(Buffer::new());
+            }
+            let take = self.pending.len().min(BLOCK_SIZE);
+            let mut plain = self
+                .cipher
+                .open_block(&block_nonce(&nonce, self.block), &self.pending[..take])
+                .map_err(|e| crypt_error(&self.path, e))?;
+            self.pending.drain(..take);
+            self.block += 1;
+
+            let skip = self.skip.min(plain.len());
+            plain.drain(..skip);
+            self.skip -= skip;
+            if let Some(remaining) = &mut self.remaining {
+                plain.truncate(plain.len().min(*remaining as usize));
+                *remaining -= plain.len() as u64;
+            }
+            if !plain.is_empty() {
+                return Ok(Buffer::from(plain));
+            }
+        }
+    }
+}
+
+pub struct CryptWriter {
+    inner: oio::Writer,
+    cipher: Arc<Cipher>,
+    nonce: [u8; NONCE_SIZE],
+    block: u64,
+    plain: Vec<u8>,
+    /// Header and sealed blocks not yet sent.
+    sealed: Vec<u8>,
+    written: u64,
+}
+
+impl CryptWriter {
+    fn seal(&mut self, len: usize) {
+        let nonce = block_nonce(&self.nonce, self.block);
+        self.cipher
+            .seal_block(&nonce, &self.plain[..len], &mut self.sealed);
+        self.plain.drain(..len);
+        self.block += 1;
+    }
+
+    async fn flush(&mut self) -> opendal::Result<()> {
+        if !self.sealed.is_empty() {
+            let batch = std::mem::take(&mut self.sealed);
+            oio::Write::write(&mut self.inner, Buffer::from(batch)).await?;
+        }
+        Ok(())
+    }
+}
+
+impl oio::Write for CryptWriter {
+    async fn write(&mut self, bs: Buffer) -> opendal::Result<()> {
+        self.written += bs.len() as u64;
+        self.plain.extend_from_slice(&bs.to_bytes());
+        while self.plain.len() >= BLOCK_DATA_SIZE {
+            self.seal(BLOCK_DATA_SIZE);
+        }
+        if self.sealed.len() >= WRITE_BATCH_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> opendal::Result<Metadata> {
+        if !self.plain.is_empty() {
+            self.seal(self.plain.len());
+        }
+        self.flush().await?;
+        let metadata = oio::Write::close(&mut self.inner).await?;
+        Ok(metadata.with_content_length(self.written))
+    }
+
+    async fn abort(&mut self) -> opendal::Result<()> {
+        self.plain.clear();
+        self.sealed.clear();
+        oio::Write::abort(&mut self.inner).await
+    }
+}
+
+pub struct CryptLister {
+    inner: opendal::Lister,
+    cipher: Arc<Cipher>,
+}
+
+impl oio::List for CryptLister {
+    async fn next(&mut self) -> opendal::Result<Option<oio::Entry>> {
+        while let Some(entry) = self.inner.next().await {
+            let (path, metadata) = entry?.into_parts();
+            match self.cipher.decrypt_path(&path) {
+                Ok(path) => {
+                    return Ok(Some(oio::Entry::new(&path, decrypt_metadata(metadata))));
+                }
+                Err(_) => tracing::debug!(%path, "skipping entry that does not decrypt"),
+            }
+        }
+        Ok(None)
+    }
+}
+
+pub struct CryptDeleter {
+    inner: oio::Deleter,
+    cipher: Arc<Cipher>,
+}
+
+impl oio::Delete for CryptDeleter {
+    fn delete(&mut self, path: &str, args: OpDelete) -> opendal::Result<()> {
+        self.inner.delete(&self.cipher.encrypt_path(path), args)
+    }
+
+    async fn flush(&mut self) -> opendal::Result<usize> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opendal::services::Fs;
+
+    #[tokio::test]
+    async fn encrypted_storage_reads_back_plain_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let raw = Operator::new(Fs::default().root(dir.path().to_str().unwrap()))
+            .unwrap()
+            .finish();
+        let cipher = Cipher::from_key(
+            &[7; 80],
+            NameEncryption::Standard,
+            NameEncoding::Base32,
+            true,
+        );
+        let op = raw.clone().layer(CryptLayer(Arc::new(cipher)));
+
+        let data: Vec<u8> = (0..BLOCK_DATA_SIZE * 2 + 5).map(|i| i as u8).collect();
+        op.write("docs/report.bin", data.clone()).await.unwrap();
+
+        assert_eq!(op.read("docs/report.bin").await.unwrap().to_vec(), data);
+        let start = BLOCK_DATA_SIZE as u64 + 10;
+        let tail = op
+            .read_with("docs/report.bin")
+            .range(start..start + 100)
+            .await
+            .unwrap();
+        assert_eq!(tail.to_vec(), data[start as usize..start as usize + 100]);
+        assert_eq!(
+            op.stat("docs/report.bin").await.unwrap().content_length(),
+            data.len() as u64
+        );
+
+        let names: Vec<String> = op
+            .list("docs/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        assert_eq!(names, ["docs/", "docs/report.bin"]);
+
+        let stored = raw.list_with("").recursive(true).await.unwrap();
+        assert!(stored
+            .iter()
+            .all(|e| !e.path().contains("docs") && !e.path().contains("report")));
+
+        op.delete("docs/report.bin").await.unwrap();
+        assert!(!op.exists("docs/report.bin").await.unwrap());
+    }
+}
@@ -0,0 +1,335 @@
+//! Health check of the local Infimount setup (`infimount_mcp doctor`).
+//!
+//! Validates the storage registry and MCP settings files, checks that every
+//! credential reference (keyring entries, shared credential sets, `${VAR}`
+//! placeholders) resolves, optionally probes each enabled storage, and checks
+//! the on-disk caches. Every problem comes with a suggested fix.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+
+use crate::credentials::{self, CredentialStore};
+use crate::opendal_adapter::build_operator;
+use crate::registry::{StorageRecord, StorageRegistry};
+use crate::secrets;
+use crate::settings::McpSettingsStore;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    /// What was checked, e.g. `registry` or `storage 'Photos'`.
+    pub subject: String,
+    pub status: CheckStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            status: CheckStatus::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        status: CheckStatus,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            subject: subject.into(),
+            status,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    pub fn has_errors(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|check| check.status == CheckStatus::Error)
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Ok => "ok",
+                CheckStatus::Warning => "warn",
+                CheckStatus::Error => "FAIL",
+            };
+            writeln!(f, "[{status:>4}] {}: {}", check.subject, check.message)?;
+            if let Some(fix) = &check.fix {
+                writeln!(f, "       fix: {fix}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run all checks. With `probe`, every enabled storage is also contacted.
+pub async fn run(
+    registry: &StorageRegistry,
+    settings: &McpSettingsStore,
+    probe: bool,
+) -> DoctorReport {
+    let mut report = DoctorReport::default();
+    let checks = &mut report.checks;
+
+    match settings.load() {
+        Ok(_) => checks.push(DoctorCheck::ok("settings", "MCP settings are readable")),
+        Err(error) => checks.push(DoctorCheck::problem(
+            CheckStatus::Error,
+            "settings",
+            error.message,
+            "Fix or delete the MCP settings file; defaults are used when it is missing.",
+        )),
+    }
+
+    let credential_store = CredentialStore::beside(registry.path());
+    let credential_sets = match credential_store.list() {
+        Ok(sets) => Some(sets),
+        Err(error) => {
+            checks.push(DoctorCheck::problem(
+                CheckStatus::Error,
+                "credential sets",
+                error.message,
+                format!(
+                    "Fix the JSON in {} or recreate the credential sets.",
+                    credential_store.path().display()
+                ),
+            ));
+            None
+        }
+    };
+
+    checks.push(check_hash_cache(
+        &registry.path().with_file_name("hash-cache.json"),
+    ));
+
+    let storages = match registry.load_all() {
+        Ok(storages) => {
+            checks.push(DoctorCheck::ok(
+                "registry",
+                format!(
+                    "{} storage(s) in {}",
+                    storages.len(),
+                    registry.path().display()
+                ),
+            ));
+            storages
+        }
+        Err(error) => {
+            checks.push(DoctorCheck::problem(
+                CheckStatus::Error,
+                "registry",
+                error.message,
+                format!(
+                    "Fix the JSON in {} or import a previously exported profile.",
+                    registry.path().display()
+                ),
+            ));
+            return report;
+        }
+    };
+
+    for (idx, storage) in storages.iter().enumerate() {
+        if storages[..idx]
+            .iter()
+            .any(|other| other.name == storage.name)
+        {
+            checks.push(DoctorCheck::problem(
+                CheckStatus::Warning,
+                format!("storage '{}'", storage.name),
+                "another storage has the same name",
+                "Rename one of them; MCP clients address storages by name.",
+            ));
+        }
+        checks.push(check_storage(storage, credential_sets.as_deref(), probe).await);
+    }
+    report
+}
+
+async fn check_storage(
+    storage: &StorageRecord,
+    credential_sets: Option<&[credentials::CredentialSet]>,
+    probe: bool,
+) -> DoctorCheck {
+    let subject = format!("storage '{}'", storage.name);
+
+    if let (Some(id), Some(sets)) = (credentials::linked_set_id(storage), credential_sets) {
+        if !sets.iter().any(|set| set.id == id) {
+            return DoctorCheck::problem(
+                CheckStatus::Error,
+                subject,
+                format!("uses credential set '{id}', which no longer exists"),
+                "Edit the storage and pick another credential set or enter its credentials.",
+            );
+        }
+    }
+    let expanded = match secrets::expand_env(storage) {
+        Ok(expanded) => expanded,
+        Err(error) => {
+            return DoctorCheck::problem(
+                CheckStatus::Error,
+                subject,
+                error.message,
+                "Set the variable in the environment the app or MCP server runs in.",
+            )
+        }
+    };
+    if let Err(error) = secrets::resolve(&expanded) {
+        return DoctorCheck::problem(
+            CheckStatus::Error,
+            subject,
+            error.message,
+            "Edit the storage and enter the secret again.",
+        );
+    }
+
+    if !storage.enabled {
+        return DoctorCheck::ok(subject, "credentials resolve; disabled, not probed");
+    }
+    if !probe {
+        return DoctorCheck::ok(subject, "credentials resolve");
+    }
+
+    let op = match build_operator(storage) {
+        Ok(op) => op,
+        Err(error) => {
+            return DoctorCheck::problem(
+                CheckStatus::Error,
+                subject,
+                error.message,
+                "Edit the storage settings; a required field is missing or invalid.",
+            )
+        }
+    };
+    let started = Instant::now();
+    let probed = timeout(PROBE_TIMEOUT, async {
+        if op.info().full_capability().list {
+            op.lister("").await.map(|_| ())
+        } else {
+            op.stat("").await.map(|_| ())
+        }
+    })
+    .await;
+    let elapsed_ms = started.elapsed().as_millis();
+    match probed {
+        Ok(Ok(())) => DoctorCheck::ok(subject, format!("reachable ({elapsed_ms} ms)")),
+        Ok(Err(error)) => DoctorCheck::problem(
+            CheckStatus::Error,
+            subject,
+            error.to_string(),
+            match error.kind() {
+                opendal::ErrorKind::PermissionDenied => {
+                    "Check the credentials and the permissions granted to them."
+                }
+                opendal::ErrorKind::NotFound => {
+                    "Check the bucket, container or root path; it does not exist."
+                }
+                _ => "Check the endpoint and network access, then try again.",
+            },
+        ),
+        Err(_) => DoctorCheck::problem(
+            CheckStatus::Warning,
+            subject,
+            format!("no response within {}s", PROBE_TIMEOUT.as_secs()),
+            "Check the endpoint and network access; the service may be slow or unreachable.",
+        ),
+    }
+}
+
+/// The checksum cache is disposable, so a damaged file is only a warning.
+fn check_hash_cache(path: &Path) -> DoctorCheck {
+    let Ok(data) = fs::read_to_string(path) else {
+        return DoctorCheck::ok("hash cache", "no cache file yet");
+    };
+    match serde_json::from_str::<Value>(&data) {
+        Ok(Value::Object(entries)) => DoctorCheck::ok(
+            "hash cache",
+            format!("{} cached checksum(s)", entries.len()),
+        ),
+        _ => DoctorCheck::problem(
+            CheckStatus::Warning,
+            "hash cache",
+            format!("{} is not a valid cache file", path.display()),
+            "Delete it; checksums are recomputed when needed.",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infimount_core::secrets::memory_keyring;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn doctor_reports_unresolvable_credentials_and_broken_caches() {
+        memory_keyring::install();
+        let dir = tempfile::tempdir().unwrap();
+        let registry = StorageRegistry::new(Some(dir.path().join("storages.json")));
+        let settings = McpSettingsStore::new(Some(dir.path().join("mcp-settings.json")));
+
+        let local = StorageRecord::new(
+            "Local".to_string(),
+            "local".to_string(),
+            json!({ "root": dir.path() }),
+        );
+        let broken = StorageRecord::new(
+            "Broken".to_string(),
+            "s3".to_string(),
+            json!({ "bucketName": "b", "secretAccessKey": "keyring:missing/secret" }),
+        );
+        fs::write(
+            dir.path().join("storages.json"),
+            serde_json::to_string(&[local, broken]).unwrap(),
+        )
+        .unwrap();
+        fs::write(dir.path().join("hash-cache.json"), "not json").unwrap();
+
+        let report = run(&registry, &settings, true).await;
+        let status = |subject: &str| {
+            report
+                .checks
+                .iter()
+                .find(|check| check.subject == subject)
+                .map(|check| check.status)
+        };
+        assert_eq!(status("registry"), Some(CheckStatus::Ok));
+        assert_eq!(status("storage 'Local'"), Some(CheckStatus::Ok));
+        assert_eq!(status("storage 'Broken'"), Some(CheckStatus::Error));
+        assert_eq!(status("hash cache"), Some(CheckStatus::Warning));
+        assert!(report.has_errors());
+        assert!(report.to_string().contains("fix: Edit the storage"));
+    }
+}
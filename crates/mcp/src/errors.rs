@@ -25,6 +25,8 @@ pub enum McpErrorCode {
     ERR_BACKEND_UNSUPPORTED,
     ERR_VERSIONS_NOT_SUPPORTED,
     ERR_VERSIONS_NOT_ENABLED,
+    ERR_CREDENTIAL_NOT_FOUND,
+    ERR_CREDENTIAL_IN_USE,
     ERR_SESSION_NOT_FOUND,
     ERR_SESSION_FORBIDDEN,
     ERR_UNAUTHORIZED,
@@ -113,6 +115,7 @@ pub fn map_opendal_error(err: &opendal::Error, fallback: McpErrorCode) -> McpErr
         opendal::ErrorKind::NotFound => McpErrorCode::ERR_PATH_NOT_FOUND,
         opendal::ErrorKind::PermissionDenied => McpErrorCode::ERR_PERMISSION_DENIED,
         opendal::ErrorKind::AlreadyExists => McpErrorCode::ERR_ALREADY_EXISTS,
+        opendal::ErrorKind::Unsupported => McpErrorCode::ERR_BACKEND_UNSUPPORTED,
         _ => fallback,
     };
 
@@ -1,17 +1,24 @@
+pub mod credentials;
+pub mod crypt;
+pub mod doctor;
 pub mod errors;
 pub mod opendal_adapter;
 pub mod path;
 pub mod prompts;
 pub mod registry;
 pub mod resources;
+pub mod restic;
+pub mod retry;
 pub mod runtime;
 pub mod schemas;
+pub mod secrets;
 pub mod server;
 pub mod session;
 pub mod settings;
 pub mod telemetry;
 pub mod tools_fs;
 pub mod tools_storage;
+pub mod workspace;
 
 pub use errors::{McpError, McpErrorCode, McpResult};
 pub use path::{parse_mcp_path, FsOp, ParsedPath};
@@ -1,3 +1,4 @@
+use infimount_mcp::doctor;
 use infimount_mcp::registry::StorageRegistry;
 use infimount_mcp::runtime::{serve_stdio, start_http_server};
 use infimount_mcp::settings::{
@@ -15,8 +16,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_writer(std::io::stderr)
         .try_init();
 
+    if let Err(error) = infimount_core::config::migrate_legacy_config_dir() {
+        eprintln!("failed to move ~/.infimount to the config directory: {error}");
+    }
+
     let _ = init_telemetry();
 
+    if std::env::args().nth(1).as_deref() == Some("doctor") {
+        let report = doctor::run(
+            &StorageRegistry::new(None),
+            &McpSettingsStore::new(None),
+            !arg_present("--no-probe"),
+        )
+        .await;
+        print!("{report}");
+        std::process::exit(if report.has_errors() { 1 } else { 0 });
+    }
+
     let transport = arg_value("--transport").unwrap_or_else(|| "stdio".to_string());
     let allow_insecure = arg_present("--allow-insecure");
     let auth_token = normalize_auth_token(arg_value("--auth-token"))
@@ -1,6 +1,9 @@
+use crate::crypt::CryptLayer;
 use crate::errors::{err_with_details, McpErrorCode, McpResult};
 use crate::registry::StorageRecord;
-use opendal::services::{Azblob, Fs, Gcs, Webdav, S3};
+use crate::retry::RetryPolicy;
+use crate::secrets;
+use opendal::services::{Azblob, Fs, Ftp, Gcs, Gdrive, Http, Ipfs, Ipmfs, Webdav, Webhdfs, B2, S3};
 use opendal::Operator;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +13,8 @@ pub struct StorageBackendCapabilities {
     pub read_with_version: bool,
     pub delete_with_version: bool,
     pub versioning_disabled: bool,
+    pub delete_to_trash: bool,
+    pub links: bool,
 }
 
 pub fn get_capabilities(op: &Operator) -> StorageBackendCapabilities {
@@ -20,9 +25,21 @@ pub fn get_capabilities(op: &Operator) -> StorageBackendCapabilities {
         read_with_version: full.read_with_version,
         delete_with_version: full.delete_with_version,
         versioning_disabled: false,
+        delete_to_trash: false,
+        links: false,
     }
 }
 
+/// Local storages can opt into routing deletions to the OS trash via
+/// `config.useOsTrash` (either a boolean or the string "true").
+pub fn uses_os_trash(storage: &StorageRecord) -> bool {
+    if !matches!(storage.backend.as_str(), "local" | "fs") {
+        return false;
+    }
+
+    config_flag(storage, "useOsTrash").unwrap_or(false)
+}
+
 pub fn check_versioning_disabled(storage: &StorageRecord) -> Option<bool> {
     match storage.backend.as_str() {
         "s3" => storage
@@ -45,18 +62,33 @@ pub fn check_versioning_disabled(storage: &StorageRecord) -> Option<bool> {
 }
 
 pub fn build_operator(storage: &StorageRecord) -> McpResult<Operator> {
-    match storage.backend.as_str() {
+    let storage = &secrets::resolve(&secrets::expand_env(storage)?)?;
+    let retry = RetryPolicy::for_storage(storage)?;
+    let crypt = CryptLayer::for_storage(storage)?;
+    let op = match storage.backend.as_str() {
         "local" | "fs" => build_fs_operator(storage),
         "s3" => build_s3_operator(storage),
         "webdav" => build_webdav_operator(storage),
         "azure_blob" | "azblob" => build_azblob_operator(storage),
         "gcs" => build_gcs_operator(storage),
+        "ftp" => build_ftp_operator(storage),
+        "gdrive" => build_gdrive_operator(storage),
+        "b2" => build_b2_operator(storage),
+        "hdfs" => build_hdfs_operator(storage),
+        "ipfs" => build_ipfs_operator(storage),
+        "ipmfs" => build_ipmfs_operator(storage),
+        "http" => build_http_operator(storage),
         other => Err(err_with_details(
             McpErrorCode::ERR_BACKEND_UNSUPPORTED,
             format!("unsupported backend '{other}'"),
             serde_json::json!({ "backend": other }),
         )),
-    }
+    }?;
+    let op = retry.apply(op);
+    Ok(match crypt {
+        Some(crypt) => op.layer(crypt),
+        None => op,
+    })
 }
 
 fn build_fs_operator(storage: &StorageRecord) -> McpResult<Operator> {
@@ -81,33 +113,123 @@ fn build_fs_operator(storage: &StorageRecord) -> McpResult<Operator> {
 }
 
 fn build_s3_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let settings = s3_settings(storage);
     let mut builder = S3::default();
-
-    if let Some(bucket) = storage.config.get("bucket").and_then(|v| v.as_str()) {
+    if let Some(bucket) = &settings.bucket {
         builder = builder.bucket(bucket);
     }
-    if let Some(region) = storage.config.get("region").and_then(|v| v.as_str()) {
+    if let Some(region) = &settings.region {
         builder = builder.region(region);
     }
-    if let Some(ak) = storage.config.get("accessKeyId").and_then(|v| v.as_str()) {
+    if let Some(endpoint) = &settings.endpoint {
+        builder = builder.endpoint(endpoint);
+    }
+    if settings.virtual_host_style {
+        builder = builder.enable_virtual_host_style();
+    }
+    if let Some(ak) = &settings.access_key_id {
         builder = builder.access_key_id(ak);
     }
-    if let Some(sk) = storage
-        .config
-        .get("secretAccessKey")
-        .and_then(|v| v.as_str())
-    {
+    if let Some(sk) = &settings.secret_access_key {
         builder = builder.secret_access_key(sk);
     }
-    if let Some(endpoint) = storage.config.get("endpoint").and_then(|v| v.as_str()) {
-        builder = builder.endpoint(endpoint);
-    }
 
     Operator::new(builder)
         .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
         .map(|op| op.finish())
 }
 
+/// Bucket, addressing and credentials of an S3 storage after provider
+/// presets are applied, for callers that talk to S3 without OpenDAL.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct S3Settings {
+    pub bucket: Option<String>,
+    pub region: Option<String>,
+    pub endpoint: Option<String>,
+    pub virtual_host_style: bool,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+}
+
+/// [`S3Settings`] of `storage` with secrets resolved, or `None` when it is
+/// not an S3 storage.
+pub fn resolve_s3_settings(storage: &StorageRecord) -> McpResult<Option<S3Settings>> {
+    if storage.backend != "s3" {
+        return Ok(None);
+    }
+    let storage = secrets::resolve(&secrets::expand_env(storage)?)?;
+    Ok(Some(s3_settings(&storage)))
+}
+
+fn s3_settings(storage: &StorageRecord) -> S3Settings {
+    let string = |key: &str| {
+        storage
+            .config
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let mut settings = S3Settings {
+        bucket: string("bucket").or_else(|| string("bucketName")),
+        region: storage.config.get("region").and_then(config_scalar),
+        endpoint: None,
+        virtual_host_style: false,
+        access_key_id: string("accessKeyId"),
+        secret_access_key: string("secretAccessKey"),
+    };
+    if let Some((default_region, endpoint, virtual_host_style)) = storage
+        .config
+        .get("provider")
+        .and_then(|v| v.as_str())
+        .and_then(s3_provider_preset)
+    {
+        let region = settings
+            .region
+            .get_or_insert_with(|| default_region.to_string());
+        if let Some(template) = endpoint {
+            let account_id = storage
+                .config
+                .get("accountId")
+                .and_then(config_scalar)
+                .unwrap_or_default();
+            settings.endpoint = Some(
+                template
+                    .replace("{region}", region)
+                    .replace("{accountId}", &account_id),
+            );
+        }
+        settings.virtual_host_style = virtual_host_style;
+    }
+    if let Some(endpoint) = string("endpoint") {
+        settings.endpoint = Some(endpoint);
+    }
+    settings
+}
+
+/// Region, endpoint template and virtual-host addressing for S3-compatible
+/// providers selected with `config.provider`.
+fn s3_provider_preset(provider: &str) -> Option<(&'static str, Option<&'static str>, bool)> {
+    match provider {
+        "minio" => Some(("us-east-1", None, false)),
+        "r2" => Some((
+            "auto",
+            Some("https://{accountId}.r2.cloudflarestorage.com"),
+            false,
+        )),
+        "wasabi" => Some((
+            "us-east-1",
+            Some("https://s3.{region}.wasabisys.com"),
+            false,
+        )),
+        "digitalocean" => Some((
+            "nyc3",
+            Some("https://{region}.digitaloceanspaces.com"),
+            true,
+        )),
+        _ => None,
+    }
+}
+
 fn build_webdav_operator(storage: &StorageRecord) -> McpResult<Operator> {
     let mut builder = Webdav::default();
 
@@ -161,34 +283,169 @@ fn build_azblob_operator(storage: &StorageRecord) -> McpResult<Operator> {
 }
 
 fn build_gcs_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let settings = gcs_settings(storage);
     let mut builder = Gcs::default();
-
-    if let Some(bucket) = storage
-        .config
-        .get("bucket")
-        .or_else(|| storage.config.get("bucketName"))
-        .and_then(|v| v.as_str())
-    {
+    if let Some(bucket) = &settings.bucket {
         builder = builder.bucket(bucket);
     }
-    if let Some(endpoint) = storage.config.get("endpoint").and_then(|v| v.as_str()) {
+    if let Some(endpoint) = &settings.endpoint {
         builder = builder.endpoint(endpoint);
     }
-    if let Some(root) = storage.config.get("root").and_then(|v| v.as_str()) {
+    if let Some(root) = &settings.root {
+        builder = builder.root(root);
+    }
+    if let Some(credential) = &settings.credential {
+        builder = builder.credential(credential);
+    }
+
+    Operator::new(builder)
+        .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
+        .map(|op| op.finish())
+}
+
+/// Bucket, root and credentials of a GCS storage, for callers that talk to
+/// the Cloud Storage JSON API without OpenDAL.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcsSettings {
+    pub bucket: Option<String>,
+    pub endpoint: Option<String>,
+    pub root: Option<String>,
+    /// Base64-encoded service account JSON.
+    pub credential: Option<String>,
+}
+
+/// [`GcsSettings`] of `storage` with secrets resolved, or `None` when it is
+/// not a GCS storage.
+pub fn resolve_gcs_settings(storage: &StorageRecord) -> McpResult<Option<GcsSettings>> {
+    if storage.backend != "gcs" {
+        return Ok(None);
+    }
+    let storage = secrets::resolve(&secrets::expand_env(storage)?)?;
+    Ok(Some(gcs_settings(&storage)))
+}
+
+fn gcs_settings(storage: &StorageRecord) -> GcsSettings {
+    let string = |key: &str| {
+        storage
+            .config
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    GcsSettings {
+        bucket: string("bucket").or_else(|| string("bucketName")),
+        endpoint: string("endpoint"),
+        root: string("root"),
+        credential: string("credential").or_else(|| string("serviceAccountJson")),
+    }
+}
+
+fn build_ftp_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let mut builder = Ftp::default();
+
+    if let Some(host) = storage.config.get("host").and_then(|v| v.as_str()) {
+        let host = host.trim().trim_end_matches('/');
+        let mut endpoint = if host.contains("://") {
+            host.to_string()
+        } else if config_flag(storage, "tls") == Some(false) {
+            format!("ftp://{host}")
+        } else {
+            format!("ftps://{host}")
+        };
+        if let Some(port) = storage.config.get("port").and_then(config_scalar) {
+            endpoint = format!("{endpoint}:{port}");
+        }
+        builder = builder.endpoint(&endpoint);
+    }
+    if let Some(username) = storage.config.get("username").and_then(|v| v.as_str()) {
+        builder = builder.user(username);
+    }
+    if let Some(password) = storage.config.get("password").and_then(|v| v.as_str()) {
+        builder = builder.password(password);
+    }
+    if let Some(root) = storage.config.get("rootPath").and_then(|v| v.as_str()) {
         builder = builder.root(root);
     }
+
+    Operator::new(builder)
+        .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
+        .map(|op| op.finish())
+}
+
+fn build_gdrive_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let mut builder = Gdrive::default();
+
+    if let Some(root) = storage.config.get("rootPath").and_then(|v| v.as_str()) {
+        builder = builder.root(root);
+    }
+    if let Some(token) = storage.config.get("refreshToken").and_then(config_scalar) {
+        builder = builder.refresh_token(&token);
+    }
+    if let Some(token) = storage.config.get("accessToken").and_then(config_scalar) {
+        builder = builder.access_token(&token);
+    }
+    if let Some(client_id) = storage.config.get("clientId").and_then(|v| v.as_str()) {
+        builder = builder.client_id(client_id);
+    }
+    if let Some(client_secret) = storage.config.get("clientSecret").and_then(|v| v.as_str()) {
+        builder = builder.client_secret(client_secret);
+    }
+
+    Operator::new(builder)
+        .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
+        .map(|op| op.finish())
+}
+
+fn build_b2_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let mut builder = B2::default();
+
+    if let Some(bucket) = storage.config.get("bucket").and_then(|v| v.as_str()) {
+        builder = builder.bucket(bucket);
+    }
+    if let Some(bucket_id) = storage.config.get("bucketId").and_then(|v| v.as_str()) {
+        builder = builder.bucket_id(bucket_id);
+    }
+    if let Some(key_id) = storage
+        .config
+        .get("applicationKeyId")
+        .and_then(|v| v.as_str())
+    {
+        builder = builder.application_key_id(key_id);
+    }
     if let Some(key) = storage
         .config
-        .get("credential")
+        .get("applicationKey")
         .and_then(|v| v.as_str())
-        .or_else(|| {
-            storage
-                .config
-                .get("serviceAccountJson")
-                .and_then(|v| v.as_str())
-        })
     {
-        builder = builder.credential(key);
+        builder = builder.application_key(key);
+    }
+    if let Some(root) = storage.config.get("rootPath").and_then(|v| v.as_str()) {
+        builder = builder.root(root);
+    }
+
+    Operator::new(builder)
+        .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
+        .map(|op| op.finish())
+}
+
+fn build_hdfs_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let mut builder = Webhdfs::default();
+
+    if let Some(name_node) = storage.config.get("nameNodeUrl").and_then(|v| v.as_str()) {
+        builder = builder.endpoint(name_node);
+    }
+    if let Some(user_name) = storage.config.get("userName").and_then(config_scalar) {
+        builder = builder.user_name(&user_name);
+    }
+    if let Some(token) = storage
+        .config
+        .get("delegationToken")
+        .and_then(config_scalar)
+    {
+        builder = builder.delegation(&token);
+    }
+    if let Some(root) = storage.config.get("rootPath").and_then(|v| v.as_str()) {
+        builder = builder.root(root);
     }
 
     Operator::new(builder)
@@ -196,6 +453,84 @@ fn build_gcs_operator(storage: &StorageRecord) -> McpResult<Operator> {
         .map(|op| op.finish())
 }
 
+fn build_ipfs_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let mut builder = Ipfs::default();
+
+    if let Some(gateway) = storage.config.get("gatewayUrl").and_then(|v| v.as_str()) {
+        builder = builder.endpoint(gateway);
+    }
+    if let Some(path) = storage.config.get("contentPath").and_then(config_scalar) {
+        let path = path.trim_matches('/');
+        let root = if path.starts_with("ipfs/") || path.starts_with("ipns/") {
+            format!("/{path}/")
+        } else {
+            format!("/ipfs/{path}/")
+        };
+        builder = builder.root(&root);
+    }
+
+    Operator::new(builder)
+        .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
+        .map(|op| op.finish())
+}
+
+fn build_ipmfs_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let mut builder = Ipmfs::default();
+
+    if let Some(api_url) = storage.config.get("apiUrl").and_then(|v| v.as_str()) {
+        builder = builder.endpoint(api_url);
+    }
+    if let Some(root) = storage.config.get("rootPath").and_then(|v| v.as_str()) {
+        builder = builder.root(root);
+    }
+
+    Operator::new(builder)
+        .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
+        .map(|op| op.finish())
+}
+
+fn build_http_operator(storage: &StorageRecord) -> McpResult<Operator> {
+    let mut builder = Http::default();
+
+    if let Some(endpoint) = storage.config.get("endpoint").and_then(|v| v.as_str()) {
+        builder = builder.endpoint(endpoint);
+    }
+    if let Some(root) = storage.config.get("rootPath").and_then(|v| v.as_str()) {
+        builder = builder.root(root);
+    }
+    if let Some(username) = storage.config.get("username").and_then(|v| v.as_str()) {
+        builder = builder.username(username);
+    }
+    if let Some(password) = storage.config.get("password").and_then(|v| v.as_str()) {
+        builder = builder.password(password);
+    }
+    if let Some(token) = storage.config.get("token").and_then(|v| v.as_str()) {
+        builder = builder.token(token);
+    }
+
+    Operator::new(builder)
+        .map_err(|e| super::errors::map_opendal_error(&e, McpErrorCode::ERR_INTERNAL))
+        .map(|op| op.finish())
+}
+
+/// Read a boolean config flag stored either as a JSON bool or a "true"/"false" string.
+fn config_flag(storage: &StorageRecord, key: &str) -> Option<bool> {
+    match storage.config.get(key)? {
+        serde_json::Value::Bool(value) => Some(*value),
+        serde_json::Value::String(value) => Some(value.eq_ignore_ascii_case("true")),
+        _ => None,
+    }
+}
+
+/// Read a non-empty string or number config value as a string.
+fn config_scalar(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
 fn expand_home_path(input: &str) -> String {
     if input == "~" {
         return std::env::var("HOME")
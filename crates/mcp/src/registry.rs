@@ -1,15 +1,22 @@
-use crate::errors::{err, err_with_details, map_io_error, McpErrorCode, McpResult};
+use crate::errors::{err, err_with_details, map_io_error, McpError, McpErrorCode, McpResult};
+use crate::secrets::{self, is_secret_key};
 use chrono::Utc;
 use fs2::FileExt;
+use infimount_core::config::{self, ConfigStamp};
+use infimount_core::CoreError;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::BTreeSet;
 use std::fs::{self, OpenOptions};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 const REGISTRY_LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long a removed storage (and its keyring secrets) can be restored.
+pub const REMOVED_RETENTION_DAYS: i64 = 30;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageRecord {
@@ -22,6 +29,15 @@ pub struct StorageRecord {
     pub read_only: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// Session-only storage (e.g. an inserted USB drive) that the desktop app
+    /// keeps in memory and never writes to the registry file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ephemeral: bool,
+    /// Operations the backend supported when the storage was added or last
+    /// edited (`"list"`, `"rename"`, ...). Unsupported ones fail up front;
+    /// `None` for storages saved before capabilities were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<BTreeSet<String>>,
 }
 
 impl StorageRecord {
@@ -37,21 +53,56 @@ impl StorageRecord {
             read_only: false,
             created_at: now.clone(),
             updated_at: now,
+            ephemeral: false,
+            capabilities: None,
         }
     }
 }
 
+/// A storage removed from the registry, kept in `removed-storages.json` for
+/// [`REMOVED_RETENTION_DAYS`] so it can be restored with its credentials.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedStorage {
+    #[serde(flatten)]
+    pub storage: StorageRecord,
+    pub removed_at: String,
+}
+
+impl RemovedStorage {
+    fn expired(&self) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.removed_at).map_or(true, |at| {
+            Utc::now() - at.with_timezone(&Utc) > chrono::Duration::days(REMOVED_RETENTION_DAYS)
+        })
+    }
+}
+
+/// Storages file shared by the desktop app and MCP servers. Every write
+/// holds an advisory lock on `storages.lock` and replaces the file through a
+/// temp file and rename; reads are served from the last version this process
+/// read or wrote until another process changes the file.
 #[derive(Debug, Clone)]
 pub struct StorageRegistry {
     path: PathBuf,
     lock_path: PathBuf,
+    /// File contents as last read or written, shared by clones.
+    snapshot: Arc<Mutex<Option<Snapshot>>>,
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    stamp: ConfigStamp,
+    storages: Vec<StorageRecord>,
 }
 
 impl StorageRegistry {
     pub fn new(path: Option<PathBuf>) -> Self {
         let path = path.unwrap_or_else(default_registry_path);
         let lock_path = path.with_extension("lock");
-        Self { path, lock_path }
+        Self {
+            path,
+            lock_path,
+            snapshot: Arc::default(),
+        }
     }
 
     pub fn path(&self) -> &Path {
@@ -59,9 +110,44 @@ impl StorageRegistry {
     }
 
     pub fn load_all(&self) -> McpResult<Vec<StorageRecord>> {
+        if let Some(storages) = self.unchanged() {
+            return Ok(storages);
+        }
         self.with_file_lock(REGISTRY_LOCK_TIMEOUT, || self.load_all_unlocked())
     }
 
+    /// Reread the file if another process changed it since this registry
+    /// last read or wrote it. Returns whether it did.
+    pub fn reload_if_changed(&self) -> McpResult<bool> {
+        if self.unchanged().is_some() || (self.snapshot().is_none() && !self.path.exists()) {
+            return Ok(false);
+        }
+        self.with_file_lock(REGISTRY_LOCK_TIMEOUT, || self.load_all_unlocked())?;
+        Ok(true)
+    }
+
+    fn snapshot(&self) -> MutexGuard<'_, Option<Snapshot>> {
+        self.snapshot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The storages of the snapshot, if the file still matches it.
+    fn unchanged(&self) -> Option<Vec<StorageRecord>> {
+        let stamp = config::stamp(&self.path)?;
+        self.snapshot()
+            .as_ref()
+            .filter(|snapshot| snapshot.stamp == stamp)
+            .map(|snapshot| snapshot.storages.clone())
+    }
+
+    fn remember(&self, storages: &[StorageRecord]) {
+        *self.snapshot() = config::stamp(&self.path).map(|stamp| Snapshot {
+            stamp,
+            storages: storages.to_vec(),
+        });
+    }
+
     pub fn save_all_atomic(&self, storages: &[StorageRecord]) -> McpResult<()> {
         self.with_file_lock(REGISTRY_LOCK_TIMEOUT, || {
             self.save_all_atomic_unlocked(storages)
@@ -116,10 +202,81 @@ impl StorageRegistry {
         Ok(storage)
     }
 
+    /// Recently removed storages that can still be restored, newest first.
+    pub fn list_removed(&self) -> McpResult<Vec<RemovedStorage>> {
+        let mut removed =
+            self.with_file_lock(REGISTRY_LOCK_TIMEOUT, || Ok(self.load_removed_unlocked()))?;
+        removed.retain(|item| !item.expired());
+        removed.sort_by(|a, b| b.removed_at.cmp(&a.removed_at));
+        Ok(removed)
+    }
+
+    /// Put a removed storage back into the registry.
+    pub fn restore_removed(&self, id: &str) -> McpResult<StorageRecord> {
+        self.with_file_lock(REGISTRY_LOCK_TIMEOUT, || {
+            let removed = self
+                .load_removed_unlocked()
+                .into_iter()
+                .find(|item| item.storage.id == id && !item.expired())
+                .ok_or_else(|| removed_not_found(id))?;
+            let mut storages = self.load_all_unlocked()?;
+            ensure_unique_name(&storages, &removed.storage.name, None)?;
+            let mut storage = removed.storage;
+            storage.updated_at = Utc::now().to_rfc3339();
+            storages.push(storage.clone());
+            self.save_all_atomic_unlocked(&storages)?;
+            Ok(storage)
+        })
+    }
+
+    /// Drop a removed storage and its keyring secrets before the grace
+    /// period is over.
+    pub fn forget_removed(&self, id: &str) -> McpResult<()> {
+        self.with_file_lock(REGISTRY_LOCK_TIMEOUT, || {
+            let (forgotten, kept): (Vec<_>, Vec<_>) = self
+                .load_removed_unlocked()
+                .into_iter()
+                .partition(|item| item.storage.id == id);
+            if forgotten.is_empty() {
+                return Err(removed_not_found(id));
+            }
+            self.save_removed_unlocked(&kept)?;
+            let mut referenced = self.load_all_unlocked()?;
+            referenced.extend(kept.into_iter().map(|item| item.storage));
+            let forgotten: Vec<_> = forgotten.into_iter().map(|item| item.storage).collect();
+            secrets::forget_unreferenced(&forgotten, &referenced);
+            Ok(())
+        })
+    }
+
+    fn removed_path(&self) -> PathBuf {
+        self.path.with_file_name("removed-storages.json")
+    }
+
+    fn load_removed_unlocked(&self) -> Vec<RemovedStorage> {
+        fs::read_to_string(self.removed_path())
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_removed_unlocked(&self, removed: &[RemovedStorage]) -> McpResult<()> {
+        let payload = serde_json::to_vec_pretty(removed).map_err(|e| {
+            err_with_details(
+                McpErrorCode::ERR_INTERNAL,
+                "failed to serialize removed storages",
+                json!({ "serde_error": e.to_string() }),
+            )
+        })?;
+        write_atomic(&self.removed_path(), &payload)
+    }
+
     fn load_all_unlocked(&self) -> McpResult<Vec<StorageRecord>> {
-        if !self.path.exists() {
+        // Stamped before reading: a write in between only causes a reread.
+        let Some(stamp) = config::stamp(&self.path) else {
+            *self.snapshot() = None;
             return Ok(Vec::new());
-        }
+        };
 
         let data = fs::read_to_string(&self.path)
             .map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
@@ -130,28 +287,22 @@ impl StorageRegistry {
                 json!({ "serde_error": e.to_string(), "path": self.path }),
             )
         })?;
+        *self.snapshot() = Some(Snapshot {
+            stamp,
+            storages: storages.clone(),
+        });
         Ok(storages)
     }
 
     fn save_all_atomic_unlocked(&self, storages: &[StorageRecord]) -> McpResult<()> {
-        ensure_parent(&self.path)?;
-
-        let parent = self.path.parent().ok_or_else(|| {
-            err_with_details(
-                McpErrorCode::ERR_INTERNAL,
-                "registry path has no parent directory",
-                json!({ "path": self.path }),
-            )
-        })?;
-
-        let tmp_name = format!(
-            ".storages.json.tmp.{}.{}",
-            std::process::id(),
-            Utc::now().timestamp_nanos_opt().unwrap_or_default()
-        );
-        let tmp_path = parent.join(tmp_name);
+        let mut persistent: Vec<StorageRecord> =
+            storages.iter().filter(|s| !s.ephemeral).cloned().collect();
+        for storage in &mut persistent {
+            secrets::externalize(storage)?;
+        }
+        let previous = self.load_all_unlocked().unwrap_or_default();
 
-        let payload = serde_json::to_vec_pretty(storages).map_err(|e| {
+        let payload = serde_json::to_vec_pretty(&persistent).map_err(|e| {
             err_with_details(
                 McpErrorCode::ERR_INTERNAL,
                 "failed to serialize storage registry",
@@ -159,9 +310,32 @@ impl StorageRegistry {
             )
         })?;
 
-        fs::write(&tmp_path, payload).map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
-        fs::rename(&tmp_path, &self.path)
-            .map_err(|e| map_io_error(&e, McpErrorCode::ERR_INTERNAL))?;
+        write_atomic(&self.path, &payload)?;
+        self.remember(&persistent);
+
+        // Storages that disappear are kept for a while instead of being
+        // forgotten, so a wrong removal can be undone with its secrets.
+        let removed_at = Utc::now().to_rfc3339();
+        let mut removed = self.load_removed_unlocked();
+        for storage in &previous {
+            if !persistent.iter().any(|s| s.id == storage.id) {
+                removed.retain(|item| item.storage.id != storage.id);
+                removed.push(RemovedStorage {
+                    storage: storage.clone(),
+                    removed_at: removed_at.clone(),
+                });
+            }
+        }
+        removed.retain(|item| !persistent.iter().any(|s| s.id == item.storage.id));
+        let (expired, removed): (Vec<_>, Vec<_>) =
+            removed.into_iter().partition(RemovedStorage::expired);
+        self.save_removed_unlocked(&removed)?;
+
+        let mut referenced = persistent;
+        referenced.extend(removed.into_iter().map(|item| item.storage));
+        let mut dropped = previous;
+        dropped.extend(expired.into_iter().map(|item| item.storage));
+        secrets::forget_unreferenced(&dropped, &referenced);
         Ok(())
     }
 
@@ -200,6 +374,22 @@ impl StorageRegistry {
     }
 }
 
+/// Replace `path` with `payload` through a synced temp file and a rename.
+fn write_atomic(path: &Path, payload: &[u8]) -> McpResult<()> {
+    config::write_atomic(path, payload).map_err(|e| match e {
+        CoreError::Io(e) => map_io_error(&e, McpErrorCode::ERR_INTERNAL),
+        e => err(McpErrorCode::ERR_INTERNAL, e.to_string()),
+    })
+}
+
+fn removed_not_found(id: &str) -> McpError {
+    err_with_details(
+        McpErrorCode::ERR_STORAGE_NOT_FOUND,
+        format!("no recently removed storage '{id}'"),
+        json!({ "storage_id": id }),
+    )
+}
+
 pub fn validate_storage_name(raw: &str) -> McpResult<String> {
     let name = raw.trim();
     if name.is_empty() {
@@ -255,6 +445,9 @@ pub fn ensure_unique_name(
     Ok(())
 }
 
+/// Placeholder that replaces secret values in masked records.
+pub const MASK: &str = "********";
+
 pub fn mask_storage_record(storage: &StorageRecord) -> StorageRecord {
     let mut masked = storage.clone();
     masked.config = mask_secrets_in_value(&masked.config);
@@ -267,7 +460,7 @@ pub fn mask_secrets_in_value(value: &Value) -> Value {
             let mut out = serde_json::Map::new();
             for (key, val) in map {
                 if is_secret_key(key) {
-                    out.insert(key.clone(), Value::String("********".to_string()));
+                    out.insert(key.clone(), Value::String(MASK.to_string()));
                 } else {
                     out.insert(key.clone(), mask_secrets_in_value(val));
                 }
@@ -279,37 +472,10 @@ pub fn mask_secrets_in_value(value: &Value) -> Value {
     }
 }
 
-pub fn is_secret_key(key: &str) -> bool {
-    let lowered = key.to_ascii_lowercase();
-    [
-        "secret",
-        "password",
-        "token",
-        "access_key",
-        "secret_key",
-        "client_secret",
-        "session_token",
-    ]
-    .iter()
-    .any(|needle| lowered.contains(needle))
-}
-
-pub fn default_config_dir() -> PathBuf {
-    #[cfg(target_os = "windows")]
-    {
-        let base = std::env::var("APPDATA").unwrap_or_else(|_| ".".to_string());
-        return PathBuf::from(base).join("infimount");
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        PathBuf::from(home).join(".infimount")
-    }
-}
-
+/// `storages.json` in the per-user config directory of
+/// [`infimount_core::config::config_dir`].
 pub fn default_registry_path() -> PathBuf {
-    default_config_dir().join("storages.json")
+    config::config_dir().join("storages.json")
 }
 
 fn ensure_parent(path: &Path) -> McpResult<()> {
@@ -324,6 +490,7 @@ fn ensure_parent(path: &Path) -> McpResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use infimount_core::oauth;
 
     #[test]
     fn storage_name_rules() {
@@ -349,4 +516,154 @@ mod tests {
         assert_eq!(masked["nested"]["client_secret"], "********");
         assert_eq!(masked["nested"]["safe"], "ok");
     }
+
+    #[test]
+    fn secrets_are_not_written_to_the_registry_file() {
+        infimount_core::secrets::memory_keyring::install();
+        let dir = tempfile::tempdir().unwrap();
+        let registry = StorageRegistry::new(Some(dir.path().join("storages.json")));
+
+        let storage = StorageRecord::new(
+            "Backups".into(),
+            "s3".into(),
+            json!({ "bucketName": "backups", "secretAccessKey": "plaintext-secret" }),
+        );
+        registry.save_all_atomic(&[storage]).unwrap();
+
+        let on_disk = fs::read_to_string(registry.path()).unwrap();
+        assert!(!on_disk.contains("plaintext-secret"));
+        let loaded = registry.load_all().unwrap();
+        let resolved = crate::secrets::resolve(&loaded[0]).unwrap();
+        assert_eq!(resolved.config["secretAccessKey"], "plaintext-secret");
+
+        // Saving again keeps the reference instead of re-storing it.
+        registry.save_all_atomic(&loaded).unwrap();
+        assert_eq!(registry.load_all().unwrap()[0].config, loaded[0].config);
+
+        // Removed storages keep their secrets until they are forgotten.
+        registry.save_all_atomic(&[]).unwrap();
+        assert!(crate::secrets::resolve(&loaded[0]).is_ok());
+        registry.forget_removed(&loaded[0].id).unwrap();
+        assert!(crate::secrets::resolve(&loaded[0]).is_err());
+        assert!(registry.list_removed().unwrap().is_empty());
+    }
+
+    #[test]
+    fn token_expiry_survives_a_reload() {
+        infimount_core::secrets::memory_keyring::install();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storages.json");
+        let expires_at = Utc::now() + chrono::Duration::minutes(1);
+        let storage = StorageRecord::new(
+            "Drive".into(),
+            "gdrive".into(),
+            json!({
+                "accessToken": "access",
+                "refreshToken": "refresh",
+                (oauth::EXPIRES_AT_KEY): expires_at.to_rfc3339(),
+            }),
+        );
+        StorageRegistry::new(Some(path.clone()))
+            .save_all_atomic(&[storage])
+            .unwrap();
+
+        let loaded = StorageRegistry::new(Some(path)).load_all().unwrap();
+        let config = &loaded[0].config;
+        assert!(secrets::is_reference(
+            config["refreshToken"].as_str().unwrap()
+        ));
+        let recorded = config[oauth::EXPIRES_AT_KEY].as_str().unwrap();
+        assert!(oauth::needs_refresh(oauth::parse_expiry(recorded)));
+    }
+
+    #[test]
+    fn removed_storages_can_be_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = StorageRegistry::new(Some(dir.path().join("storages.json")));
+        let work = StorageRecord::new("work".into(), "s3".into(), json!({ "bucket": "w" }));
+        let home = StorageRecord::new("home".into(), "s3".into(), json!({ "bucket": "h" }));
+        registry
+            .save_all_atomic(&[work.clone(), home.clone()])
+            .unwrap();
+
+        registry
+            .with_locked_mutation(|storages| {
+                storages.retain(|s| s.id != work.id);
+                Ok(())
+            })
+            .unwrap();
+        let removed = registry.list_removed().unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].storage.id, work.id);
+
+        let restored = registry.restore_removed(&work.id).unwrap();
+        assert_eq!(restored.config, work.config);
+        assert_eq!(registry.load_all().unwrap().len(), 2);
+        assert!(registry.list_removed().unwrap().is_empty());
+        assert!(registry.restore_removed(&work.id).is_err());
+
+        // Past the grace period a removed storage is gone for good.
+        let mut stale = RemovedStorage {
+            storage: home,
+            removed_at: (Utc::now() - chrono::Duration::days(REMOVED_RETENTION_DAYS + 1))
+                .to_rfc3339(),
+        };
+        assert!(stale.expired());
+        stale.removed_at = Utc::now().to_rfc3339();
+        assert!(!stale.expired());
+    }
+
+    #[test]
+    fn changes_from_other_processes_are_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storages.json");
+        let app = StorageRegistry::new(Some(path.clone()));
+        let server = StorageRegistry::new(Some(path));
+        assert!(!app.reload_if_changed().unwrap());
+
+        let work = StorageRecord::new("work".into(), "s3".into(), json!({ "bucket": "w" }));
+        app.save_all_atomic(std::slice::from_ref(&work)).unwrap();
+        assert!(!app.reload_if_changed().unwrap());
+        assert_eq!(server.load_all().unwrap().len(), 1);
+
+        server
+            .with_locked_mutation(|storages| {
+                storages.push(StorageRecord::new(
+                    "home".into(),
+                    "s3".into(),
+                    json!({ "bucket": "h" }),
+                ));
+                Ok(())
+            })
+            .unwrap();
+        assert!(app.reload_if_changed().unwrap());
+        assert!(!app.reload_if_changed().unwrap());
+        let names: Vec<_> = app
+            .load_all()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["work", "home"]);
+    }
+
+    #[test]
+    fn ephemeral_storages_are_not_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = StorageRegistry::new(Some(dir.path().join("storages.json")));
+
+        let kept = StorageRecord::new("docs".into(), "local".into(), json!({ "root": "/tmp" }));
+        let mut usb = StorageRecord::new(
+            "USB".into(),
+            "local".into(),
+            json!({ "root": "/media/usb" }),
+        );
+        usb.ephemeral = true;
+        registry.save_all_atomic(&[kept.clone(), usb]).unwrap();
+
+        let loaded = registry.load_all().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, kept.id);
+        assert!(!loaded[0].ephemeral);
+    }
 }
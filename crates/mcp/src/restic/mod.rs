@@ -0,0 +1,484 @@
+//! Browsing restic backup repositories.
+//!
+//! A restic source shows a repository kept on another source read-only:
+//! the root holds one folder per snapshot, named after the snapshot time
+//! and short id (`2024-05-01 12-30-00 1a2b3c4d/`), and each of those holds
+//! the files as they were backed up. Repository versions 1 and 2 are
+//! supported, including zstd-compressed data. Borg repositories are not.
+//!
+//! Opening a repository derives the key and loads every snapshot and index
+//! file; the result is kept per source for [`REOPEN_AFTER`], so snapshots
+//! added in the meantime show up later. Trees are fetched as folders are
+//! opened and file contents are streamed blob by blob.
+
+mod repo;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use opendal::raw::{
+    oio, Access, AccessorInfo, OpList, OpRead, OpStat, RpList, RpRead, RpStat, Timestamp,
+};
+use opendal::{
+    Buffer, Capability, EntryMode, Error, ErrorKind, Metadata, Operator, OperatorBuilder,
+};
+use tokio::sync::OnceCell;
+
+pub use self::repo::ResticError;
+use self::repo::{Id, Node, Repository};
+
+/// Backend id of restic sources in storage records.
+pub const RESTIC_BACKEND: &str = "restic";
+
+type Opened = Arc<OnceCell<Arc<Repository>>>;
+
+/// How long an opened repository is reused before it is read again.
+pub const REOPEN_AFTER: Duration = Duration::from_secs(10 * 60);
+
+struct Cached {
+    location: String,
+    password: String,
+    opened_at: Instant,
+    repository: Opened,
+}
+
+/// Repositories opened this session by source id, so the operators built
+/// for each request share one unlock and index load.
+static REPOSITORIES: LazyLock<Mutex<HashMap<String, Cached>>> = LazyLock::new(Default::default);
+
+/// A read-only operator over the restic repository at `path` in `store`.
+/// The repository is opened on first use; a wrong password fails every
+/// request with `PermissionDenied`.
+pub fn operator(store: Operator, path: &str, password: &str) -> Operator {
+    build(store, path, password, Opened::default())
+}
+
+/// [`operator`] for the restic source `source_id`, reusing the repository
+/// opened for it until [`REOPEN_AFTER`] passes or its path or password
+/// change.
+pub fn source_operator(source_id: &str, store: Operator, path: &str, password: &str) -> Operator {
+    let store_info = store.info();
+    let location = format!(
+        "{}:{}:{}{}",
+        store_info.scheme(),
+        store_info.name(),
+        store_info.root(),
+        path.trim_matches('/')
+    );
+    let mut repositories = REPOSITORIES.lock().unwrap_or_else(|e| e.into_inner());
+    let repository = match repositories.get(source_id) {
+        Some(cached)
+            if cached.location == location
+                && cached.password == password
+                && cached.opened_at.elapsed() < REOPEN_AFTER =>
+        {
+            cached.repository.clone()
+        }
+        _ => {
+            let repository = Opened::default();
+            repositories.insert(
+                source_id.to_string(),
+                Cached {
+                    location,
+                    password: password.to_string(),
+                    opened_at: Instant::now(),
+                    repository: repository.clone(),
+                },
+            );
+            repository
+        }
+    };
+    drop(repositories);
+    build(store, path, password, repository)
+}
+
+/// Drop the repository kept for `source_id`, e.g. once the source is
+/// removed.
+pub fn forget(source_id: &str) {
+    REPOSITORIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(source_id);
+}
+
+fn build(store: Operator, path: &str, password: &str, repository: Opened) -> Operator {
+    let info = AccessorInfo::default();
+    info.set_scheme(RESTIC_BACKEND);
+    info.set_name(RESTIC_BACKEND);
+    info.set_root("/");
+    info.set_native_capability(Capability {
+        read: true,
+        stat: true,
+        list: true,
+        shared: false,
+        ..Default::default()
+    });
+    OperatorBuilder::new(ResticBackend {
+        info: Arc::new(info),
+        store,
+        path: path.to_string(),
+        password: password.to_string(),
+        repository,
+    })
+    .finish()
+}
+
+fn into_error(error: ResticError) -> Error {
+    let kind = match error {
+        ResticError::Storage(error) => return error,
+        ResticError::NotARepository => ErrorKind::NotFound,
+        ResticError::WrongPassword => ErrorKind::PermissionDenied,
+        ResticError::UnsupportedVersion(_) => ErrorKind::Unsupported,
+        _ => ErrorKind::Unexpected,
+    };
+    Error::new(kind, error.to_string())
+}
+
+fn not_found() -> Error {
+    Error::new(ErrorKind::NotFound, "restic repository has no such entry")
+}
+
+fn timestamp(time: chrono::DateTime<chrono::FixedOffset>) -> Option<Timestamp> {
+    Timestamp::new(time.timestamp(), time.timestamp_subsec_nanos() as i32).ok()
+}
+
+fn dir_metadata(modified: Option<Timestamp>) -> Metadata {
+    let metadata = Metadata::new(EntryMode::DIR);
+    match modified {
+        Some(modified) => metadata.with_last_modified(modified),
+        None => metadata,
+    }
+}
+
+fn node_metadata(node: &Node) -> Metadata {
+    let modified = node.modified().and_then(timestamp);
+    if node.is_dir() {
+        return dir_metadata(modified);
+    }
+    let metadata = Metadata::new(EntryMode::FILE).with_content_length(node.size);
+    match modified {
+        Some(modified) => metadata.with_last_modified(modified),
+        None => metadata,
+    }
+}
+
+enum Resolved {
+    Root,
+    Dir {
+        tree: Id,
+        modified: Option<Timestamp>,
+    },
+    File(Node),
+}
+
+struct ResticBackend {
+    info: Arc<AccessorInfo>,
+    store: Operator,
+    path: String,
+    password: String,
+    repository: Opened,
+}
+
+impl std::fmt::Debug for ResticBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResticBackend")
+            .field("store", &self.store.info().name())
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResticBackend {
+    async fn repository(&self) -> opendal::Result<Arc<Repository>> {
+        self.repository
+            .get_or_try_init(|| async {
+                Repository::open(self.store.clone(), &self.path, &self.password)
+                    .await
+                    .map(Arc::new)
+            })
+            .await
+            .cloned()
+            .map_err(into_error)
+    }
+
+    async fn resolve(&self, repo: &Repository, path: &str) -> opendal::Result<Resolved> {
+        let trimmed = path.trim_matches('/');
+        if trimmed.is_empty() {
+            return Ok(Resolved::Root);
+        }
+        let mut names = trimmed.split('/');
+        let snapshot = repo
+            .snapshot(names.next().unwrap_or_default())
+            .ok_or_else(not_found)?;
+        let mut resolved = Resolved::Dir {
+            tree: snapshot.tree,
+            modified: timestamp(snapshot.time),
+        };
+        for name in names {
+            let Resolved::Dir { tree, .. } = resolved else {
+                return Err(not_found());
+            };
+            let tree = repo.tree(&tree).await.map_err(into_error)?;
+            let node = tree
+                .nodes
+                .iter()
+                .find(|node| node.name == name && (node.is_dir() || node.is_file()))
+                .ok_or_else(not_found)?;
+            resolved = if node.is_dir() {
+                Resolved::Dir {
+                    tree: node.subtree().map_err(into_error)?,
+                    modified: node.modified().and_then(timestamp),
+                }
+            } else {
+                Resolved::File(node.clone())
+            };
+        }
+        if path.ends_with('/') && matches!(resolved, Resolved::File(_)) {
+            return Err(not_found());
+        }
+        Ok(resolved)
+    }
+}
+
+impl Access for ResticBackend {
+    type Reader = ResticReader;
+    type Writer = ();
+    type Lister = ResticLister;
+    type Deleter = ();
+
+    fn info(&self) -> Arc<AccessorInfo> {
+        self.info.clone()
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> opendal::Result<RpStat> {
+        let repo = self.repository().await?;
+        let metadata = match self.resolve(&repo, path).await? {
+            Resolved::Root => Metadata::new(EntryMode::DIR),
+            Resolved::Dir { modified, .. } => dir_metadata(modified),
+            Resolved::File(node) => node_metadata(&node),
+        };
+        Ok(RpStat::new(metadata))
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+        let repo = self.repository().await?;
+        let Resolved::File(node) = self.resolve(&repo, path).await? else {
+            return Err(Error::new(ErrorKind::IsADirectory, "cannot read a folder"));
+        };
+        let range = args.range();
+        let start = range.offset();
+        let end = range
+            .size()
+            .map_or(node.size, |size| start.saturating_add(size))
+            .min(node.size);
+
+        // The part of each blob that falls inside the range.
+        let mut parts = VecDeque::new();
+        let mut at = 0;
+        for id in node.content().map_err(into_error)? {
+            let size = repo.blob_size(&id).map_err(into_error)?;
+            let (from, to) = (start.max(at), end.min(at + size));
+            if from < to {
+                parts.push_back((id, (from - at) as usize, (to - from) as usize));
+            }
+            at += size;
+        }
+        Ok((RpRead::new(), ResticReader { repo, parts }))
+    }
+
+    async fn list(&self, path: &str, _: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+        let repo = self.repository().await?;
+        let entries: Vec<oio::Entry> = match self.resolve(&repo, path).await? {
+            Resolved::Root => repo
+                .snapshots
+                .iter()
+                .map(|snapshot| {
+                    oio::Entry::new(
+                        &format!("{}/", snapshot.name),
+                        dir_metadata(timestamp(snapshot.time)),
+                    )
+                })
+                .collect(),
+            Resolved::Dir { tree, .. } => {
+                let prefix = format!("{}/", path.trim_matches('/'));
+                let tree = repo.tree(&tree).await.map_err(into_error)?;
+                tree.nodes
+                    .iter()
+                    .filter(|node| node.is_dir() || node.is_file())
+                    .map(|node| {
+                        let slash = if node.is_dir() { "/" } else { "" };
+                        oio::Entry::new(
+                            &format!("{prefix}{}{slash}", node.name),
+                            node_metadata(node),
+                        )
+                    })
+                    .collect()
+            }
+            Resolved::File(_) => {
+                return Err(Error::new(ErrorKind::NotADirectory, "not a folder"));
+            }
+        };
+        Ok((
+            RpList::default(),
+            ResticLister {
+                entries: entries.into_iter(),
+            },
+        ))
+    }
+}
+
+/// Streams a file one blob at a time.
+pub struct ResticReader {
+    repo: Arc<Repository>,
+    /// Blob, offset into it and length still to be returned.
+    parts: VecDeque<(Id, usize, usize)>,
+}
+
+impl oio::Read for ResticReader {
+    async fn read(&mut self) -> opendal::Result<Buffer> {
+        let Some((id, skip, take)) = self.parts.pop_front() else {
+            return Ok(Buffer::new());
+        };
+        let mut data = self.repo.blob(&id).await.map_err(into_error)?;
+        if skip + take > data.len() {
+            return Err(into_error(ResticError::Truncated));
+        }
+        data.truncate(skip + take);
+        data.drain(..skip);
+        Ok(Buffer::from(data))
+    }
+}
+
+pub struct ResticLister {
+    entries: std::vec::IntoIter<oio::Entry>,
+}
+
+impl oio::List for ResticLister {
+    async fn next(&mut self) -> opendal::Result<Option<oio::Entry>> {
+        Ok(self.entries.next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::repo::{id_hex, Key};
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use opendal::services::Fs;
+    use serde_json::json;
+
+    /// `data` as a zstd frame with one uncompressed block.
+    fn stored_frame(data: &[u8]) -> Vec<u8> {
+        let header = (data.len() as u32) << 3 | 1;
+        let mut frame = vec![0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x00];
+        frame.extend_from_slice(&header.to_le_bytes()[..3]);
+        frame.extend_from_slice(data);
+        frame
+    }
+
+    #[tokio::test]
+    async fn browses_snapshots_of_a_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Operator::new(Fs::default().root(dir.path().to_str().unwrap()))
+            .unwrap()
+            .finish();
+        let b64 = |data: &[u8]| STANDARD.encode(data);
+
+        let mut derived = [0u8; 64];
+        let params = scrypt::Params::new(10, 1, 1, 64).unwrap();
+        scrypt::scrypt(b"secret", &[9; 16], &params, &mut derived).unwrap();
+        let user = Key::new(&derived[..32], &derived[32..48], &derived[48..]).unwrap();
+        let master = Key::new(&[1; 32], &[2; 16], &[3; 16]).unwrap();
+        let master_json = json!({
+            "mac": { "k": b64(&[2; 16]), "r": b64(&[3; 16]) },
+            "encrypt": b64(&[1; 32]),
+        });
+        let key_file = json!({
+            "kdf": "scrypt", "N": 1024, "r": 1, "p": 1, "salt": b64(&[9; 16]),
+            "data": b64(&user.seal([4; 16], master_json.to_string().as_bytes())),
+        });
+        store
+            .write(
+                &format!("repo/keys/{}", id_hex(&[7; 32])),
+                key_file.to_string(),
+            )
+            .await
+            .unwrap();
+        let config = master.seal([5; 16], br#"{"version":2,"id":"x"}"#);
+        store.write("repo/config", config).await.unwrap();
+
+        // One pack: two data blobs and the trees of `docs/` and the root.
+        let id = |n: u8| id_hex(&[n; 32]);
+        let docs = json!({ "nodes": [{
+            "name": "notes.txt", "type": "file", "size": 18,
+            "mtime": "2024-05-01T10:00:00+02:00", "content": [id(1), id(2)],
+        }]});
+        let root = json!({ "nodes": [{ "name": "docs", "type": "dir", "subtree": id(3) }] });
+        let blobs = [
+            (1, b"hello ".to_vec()),
+            (2, b"restic world".to_vec()),
+            (3, docs.to_string().into_bytes()),
+            (4, root.to_string().into_bytes()),
+        ];
+        let (mut pack, mut entries) = (Vec::new(), Vec::new());
+        for (n, data) in blobs {
+            let sealed = master.seal([n; 16], &data);
+            entries.push(json!({
+                "id": id(n), "type": "data", "offset": pack.len(), "length": sealed.len(),
+            }));
+            pack.extend(sealed);
+        }
+        store
+            .write(&format!("repo/data/ab/{}", id(0xab)), pack)
+            .await
+            .unwrap();
+        // Version 2 repositories compress their metadata files.
+        let index = json!({ "packs": [{ "id": id(0xab), "blobs": entries }] });
+        let index = [&[2][..], &stored_frame(index.to_string().as_bytes())].concat();
+        store
+            .write(
+                &format!("repo/index/{}", id(8)),
+                master.seal([6; 16], &index),
+            )
+            .await
+            .unwrap();
+        let snapshot = json!({ "time": "2024-05-01T12:30:00.5+02:00", "tree": id(4) });
+        store
+            .write(
+                &format!("repo/snapshots/{}", id(0x5e)),
+                master.seal([7; 16], snapshot.to_string().as_bytes()),
+            )
+            .await
+            .unwrap();
+
+        let op = operator(store.clone(), "repo", "secret");
+        let snapshots = op.list("").await.unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].path(), "2024-05-01 12-30-00 5e5e5e5e/");
+
+        let path = "2024-05-01 12-30-00 5e5e5e5e/docs/notes.txt";
+        let listed = op.list("2024-05-01 12-30-00 5e5e5e5e/docs/").await.unwrap();
+        let paths: Vec<&str> = listed.iter().map(|e| e.path()).collect();
+        assert!(paths.contains(&path), "{paths:?}");
+        assert_eq!(op.stat(path).await.unwrap().content_length(), 18);
+        assert_eq!(op.read(path).await.unwrap().to_vec(), b"hello restic world");
+        let middle = op.read_with(path).range(3..10).await.unwrap();
+        assert_eq!(middle.to_vec(), b"lo rest");
+        assert!(op.write("new.txt", "x").await.is_err());
+
+        let locked = operator(store.clone(), "repo", "wrong");
+        let error = locked.stat(path).await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+
+        // A source keeps its repository until the password changes.
+        let source = source_operator("restic-1", store.clone(), "repo", "secret");
+        assert_eq!(source.stat(path).await.unwrap().content_length(), 18);
+        let changed = source_operator("restic-1", store, "repo", "wrong");
+        let error = changed.stat(path).await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+        forget("restic-1");
+        assert!(!REPOSITORIES.lock().unwrap().contains_key("restic-1"));
+    }
+}
@@ -0,0 +1,497 @@
+//! Reading restic's repository format: key files, the encrypted `config`,
+//! snapshots, the index and the tree and data blobs stored in pack files.
+//!
+//! Everything but key files is encrypted with AES-256-CTR and authenticated
+//! with Poly1305-AES: `IV || ciphertext || MAC`, where the MAC covers the
+//! ciphertext only.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockEncrypt, KeyInit, KeyIvInit, StreamCipher};
+use aes::{Aes128, Aes256};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, FixedOffset};
+use ctr::Ctr128BE;
+use futures::{StreamExt, TryStreamExt};
+use opendal::{ErrorKind, Operator};
+use poly1305::Poly1305;
+use ruzstd::decoding::StreamingDecoder;
+use serde::Deserialize;
+
+/// Files fetched at once while loading snapshots and the index.
+const LOAD_CONCURRENCY: usize = 8;
+/// Bytes an encrypted blob adds to its plaintext: IV and MAC.
+const OVERHEAD: u64 = 32;
+
+/// Ids of files and blobs: the SHA-256 of their (encrypted) contents.
+pub(super) type Id = [u8; 32];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResticError {
+    #[error("no restic repository at this path")]
+    NotARepository,
+    #[error("wrong repository password")]
+    WrongPassword,
+    #[error("unsupported repository version {0}")]
+    UnsupportedVersion(u32),
+    #[error("repository data failed authentication")]
+    BadMac,
+    #[error("repository data is truncated")]
+    Truncated,
+    #[error("invalid repository data: {0}")]
+    Invalid(String),
+    #[error("blob {0} is missing from the index")]
+    MissingBlob(String),
+    #[error("corrupt zstd data: {0}")]
+    Zstd(String),
+    #[error(transparent)]
+    Storage(#[from] opendal::Error),
+}
+
+impl From<serde_json::Error> for ResticError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Invalid(error.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, ResticError>;
+
+pub(super) fn parse_id(hex: &str) -> Option<Id> {
+    let mut id = [0u8; 32];
+    if hex.len() != 64 {
+        return None;
+    }
+    for (byte, pair) in id.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(id)
+}
+
+pub(super) fn id_hex(id: &Id) -> String {
+    id.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| ResticError::Invalid(e.to_string()))
+}
+
+/// An encryption key with its Poly1305-AES MAC key.
+pub(super) struct Key {
+    encrypt: [u8; 32],
+    mac_k: Aes128,
+    mac_r: [u8; 16],
+}
+
+impl Key {
+    pub(super) fn new(encrypt: &[u8], mac_k: &[u8], mac_r: &[u8]) -> Result<Self> {
+        let invalid = || ResticError::Invalid("key has the wrong length".into());
+        Ok(Self {
+            encrypt: encrypt.try_into().map_err(|_| invalid())?,
+            mac_k: Aes128::new_from_slice(mac_k).map_err(|_| invalid())?,
+            mac_r: mac_r.try_into().map_err(|_| invalid())?,
+        })
+    }
+
+    /// AES-256 in CTR mode with a big-endian 128-bit counter.
+    fn ctr(&self, iv: &[u8; 16]) -> Ctr128BE<Aes256> {
+        Ctr128BE::new(&self.encrypt.into(), iv.into())
+    }
+
+    fn mac(&self, iv: &[u8; 16], ciphertext: &[u8]) -> [u8; 16] {
+        let mut poly_key = [0u8; 32];
+        poly_key[..16].copy_from_slice(&self.mac_r);
+        poly_key[16..].copy_from_slice(iv);
+        self.mac_k
+            .encrypt_block(GenericArray::from_mut_slice(&mut poly_key[16..]));
+        Poly1305::new(&poly_key.into())
+            .compute_unpadded(ciphertext)
+            .into()
+    }
+
+    pub(super) fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if (data.len() as u64) < OVERHEAD {
+            return Err(ResticError::Truncated);
+        }
+        let (iv, rest) = data.split_at(16);
+        let (ciphertext, tag) = rest.split_at(rest.len() - 16);
+        let iv: &[u8; 16] = iv.try_into().expect("16-byte IV");
+        // Constant time: the comparison must not leak how much matched.
+        let expected = self.mac(iv, ciphertext);
+        if expected
+            .iter()
+            .zip(tag)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            != 0
+        {
+            return Err(ResticError::BadMac);
+        }
+        let mut plaintext = ciphertext.to_vec();
+        self.ctr(iv).apply_keystream(&mut plaintext);
+        Ok(plaintext)
+    }
+
+    #[cfg(test)]
+    pub(super) fn seal(&self, iv: [u8; 16], plaintext: &[u8]) -> Vec<u8> {
+        let mut ciphertext = plaintext.to_vec();
+        self.ctr(&iv).apply_keystream(&mut ciphertext);
+        let tag = self.mac(&iv, &ciphertext);
+        [&iv[..], &ciphertext, &tag].concat()
+    }
+}
+
+/// A file in `keys/`: the master key, encrypted with a key derived from
+/// the password.
+#[derive(Deserialize)]
+struct KeyFile {
+    kdf: String,
+    #[serde(rename = "N")]
+    n: u64,
+    r: u32,
+    p: u32,
+    salt: String,
+    data: String,
+}
+
+impl KeyFile {
+    fn derive(&self, password: &str) -> Result<Key> {
+        if self.kdf != "scrypt" || !self.n.is_power_of_two() {
+            return Err(ResticError::Invalid(format!(
+                "unsupported key derivation {} (N={})",
+                self.kdf, self.n
+            )));
+        }
+        let params = scrypt::Params::new(self.n.trailing_zeros() as u8, self.r, self.p, 64)
+            .map_err(|e| ResticError::Invalid(e.to_string()))?;
+        let mut derived = [0u8; 64];
+        scrypt::scrypt(
+            password.as_bytes(),
+            &decode_base64(&self.salt)?,
+            &params,
+            &mut derived,
+        )
+        .map_err(|e| ResticError::Invalid(e.to_string()))?;
+        Key::new(&derived[..32], &derived[32..48], &derived[48..])
+    }
+}
+
+#[derive(Deserialize)]
+struct MasterKey {
+    mac: MacKey,
+    encrypt: String,
+}
+
+#[derive(Deserialize)]
+struct MacKey {
+    k: String,
+    r: String,
+}
+
+#[derive(Deserialize)]
+struct Config {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct SnapshotFile {
+    time: String,
+    tree: String,
+}
+
+#[derive(Deserialize)]
+struct IndexFile {
+    packs: Vec<IndexPack>,
+}
+
+#[derive(Deserialize)]
+struct IndexPack {
+    id: String,
+    blobs: Vec<IndexBlob>,
+}
+
+#[derive(Deserialize)]
+struct IndexBlob {
+    id: String,
+    offset: u64,
+    length: u64,
+    #[serde(default)]
+    uncompressed_length: u64,
+}
+
+pub(super) struct Snapshot {
+    /// Folder name: snapshot time and short id.
+    pub name: String,
+    pub time: DateTime<FixedOffset>,
+    pub tree: Id,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Tree {
+    #[serde(default)]
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct Node {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub mtime: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub content: Option<Vec<String>>,
+    #[serde(default)]
+    pub subtree: Option<String>,
+}
+
+impl Node {
+    pub fn is_dir(&self) -> bool {
+        self.kind == "dir"
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.kind == "file"
+    }
+
+    pub fn modified(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(self.mtime.as_deref()?).ok()
+    }
+
+    pub fn subtree(&self) -> Result<Id> {
+        self.subtree
+            .as_deref()
+            .and_then(parse_id)
+            .ok_or_else(|| ResticError::Invalid(format!("folder {} has no tree", self.name)))
+    }
+
+    pub fn content(&self) -> Result<Vec<Id>> {
+        self.content
+            .iter()
+            .flatten()
+            .map(|hex| {
+                parse_id(hex).ok_or_else(|| ResticError::Invalid(format!("bad blob id {hex}")))
+            })
+            .collect()
+    }
+}
+
+/// Where a blob is stored: pack number, offset and encrypted length, and
+/// its size once decompressed when it is compressed.
+struct BlobLocation {
+    pack: u32,
+    offset: u64,
+    length: u32,
+    uncompressed_length: u32,
+}
+
+pub(super) struct Repository {
+    store: Operator,
+    /// Repository path inside `store`, empty or ending in `/`.
+    root: String,
+    key: Key,
+    pub snapshots: Vec<Snapshot>,
+    packs: Vec<Id>,
+    blobs: HashMap<Id, BlobLocation>,
+    trees: Mutex<HashMap<Id, Arc<Tree>>>,
+}
+
+impl Repository {
+    /// Unlock the repository at `path` in `store` and load its snapshots
+    /// and index.
+    pub async fn open(store: Operator, path: &str, password: &str) -> Result<Self> {
+        let path = path.trim_matches('/');
+        let root = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{path}/")
+        };
+        let config = match store.read(&format!("{root}config")).await {
+            Ok(config) => config.to_vec(),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Err(ResticError::NotARepository),
+            Err(e) => return Err(e.into()),
+        };
+        let key = unlock(&store, &root, password).await?;
+        let config: Config = serde_json::from_slice(&decode_file(&key, &config)?)?;
+        if !(1..=2).contains(&config.version) {
+            return Err(ResticError::UnsupportedVersion(config.version));
+        }
+
+        let mut repo = Self {
+            store,
+            root,
+            key,
+            snapshots: Vec::new(),
+            packs: Vec::new(),
+            blobs: HashMap::new(),
+            trees: Mutex::new(HashMap::new()),
+        };
+        for (name, data) in repo.read_dir("snapshots/").await? {
+            let snapshot: SnapshotFile = serde_json::from_slice(&data)?;
+            let invalid = || ResticError::Invalid(format!("snapshot {name}"));
+            let time = DateTime::parse_from_rfc3339(&snapshot.time).map_err(|_| invalid())?;
+            repo.snapshots.push(Snapshot {
+                name: format!(
+                    "{} {}",
+                    time.format("%Y-%m-%d %H-%M-%S"),
+                    name.get(..8).unwrap_or(&name)
+                ),
+                time,
+                tree: parse_id(&snapshot.tree).ok_or_else(invalid)?,
+            });
+        }
+        repo.snapshots.sort_by_key(|snapshot| snapshot.time);
+
+        for (_, data) in repo.read_dir("index/").await? {
+            let index: IndexFile = serde_json::from_slice(&data)?;
+            for pack in index.packs {
+                let invalid = || ResticError::Invalid(format!("index entry in pack {}", pack.id));
+                repo.packs.push(parse_id(&pack.id).ok_or_else(invalid)?);
+                let number = repo.packs.len() as u32 - 1;
+                for blob in pack.blobs {
+                    let location = BlobLocation {
+                        pack: number,
+                        offset: blob.offset,
+                        length: u32::try_from(blob.length).map_err(|_| invalid())?,
+                        uncompressed_length: u32::try_from(blob.uncompressed_length)
+                            .map_err(|_| invalid())?,
+                    };
+                    repo.blobs
+                        .insert(parse_id(&blob.id).ok_or_else(invalid)?, location);
+                }
+            }
+        }
+        Ok(repo)
+    }
+
+    /// Names and decoded contents of the files in a repository folder.
+    async fn read_dir(&self, dir: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let entries = match self.store.list(&format!("{}{dir}", self.root)).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        futures::stream::iter(entries.into_iter().filter(|e| e.metadata().is_file()))
+            .map(|entry| async move {
+                let data = self.store.read(entry.path()).await?.to_vec();
+                Ok((entry.name().to_string(), decode_file(&self.key, &data)?))
+            })
+            .buffer_unordered(LOAD_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    pub fn snapshot(&self, name: &str) -> Option<&Snapshot> {
+        self.snapshots.iter().find(|snapshot| snapshot.name == name)
+    }
+
+    /// Plaintext size of a blob.
+    pub fn blob_size(&self, id: &Id) -> Result<u64> {
+        let location = self.location(id)?;
+        Ok(match location.uncompressed_length {
+            0 => (location.length as u64).saturating_sub(OVERHEAD),
+            size => size as u64,
+        })
+    }
+
+    fn location(&self, id: &Id) -> Result<&BlobLocation> {
+        self.blobs
+            .get(id)
+            .ok_or_else(|| ResticError::MissingBlob(id_hex(id)))
+    }
+
+    pub async fn blob(&self, id: &Id) -> Result<Vec<u8>> {
+        let location = self.location(id)?;
+        let pack = id_hex(&self.packs[location.pack as usize]);
+        let path = format!("{}data/{}/{pack}", self.root, &pack[..2]);
+        let end = location.offset + location.length as u64;
+        let data = self
+            .store
+            .read_with(&path)
+            .range(location.offset..end)
+            .await?
+            .to_vec();
+        let plaintext = self.key.decrypt(&data)?;
+        if location.uncompressed_length == 0 {
+            return Ok(plaintext);
+        }
+        let data = decompress(&plaintext)?;
+        if data.len() != location.uncompressed_length as usize {
+            return Err(ResticError::Invalid(format!(
+                "blob {} has the wrong size",
+                id_hex(id)
+            )));
+        }
+        Ok(data)
+    }
+
+    pub async fn tree(&self, id: &Id) -> Result<Arc<Tree>> {
+        if let Some(tree) = self.cached_trees().get(id) {
+            return Ok(tree.clone());
+        }
+        let tree: Arc<Tree> = Arc::new(serde_json::from_slice(&self.blob(id).await?)?);
+        self.cached_trees().insert(*id, tree.clone());
+        Ok(tree)
+    }
+
+    fn cached_trees(&self) -> std::sync::MutexGuard<'_, HashMap<Id, Arc<Tree>>> {
+        self.trees.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Find the key file `password` opens and decrypt the master key.
+async fn unlock(store: &Operator, root: &str, password: &str) -> Result<Key> {
+    for entry in store.list(&format!("{root}keys/")).await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+        let file: KeyFile = serde_json::from_slice(&store.read(entry.path()).await?.to_vec())?;
+        let data = decode_base64(&file.data)?;
+        let password = password.to_string();
+        // scrypt is deliberately slow; keep it off the async workers.
+        let user_key = tokio::task::spawn_blocking(move || file.derive(&password))
+            .await
+            .map_err(|e| ResticError::Invalid(e.to_string()))??;
+        let master = match user_key.decrypt(&data) {
+            Ok(master) => master,
+            Err(ResticError::BadMac) => continue,
+            Err(e) => return Err(e),
+        };
+        let master: MasterKey = serde_json::from_slice(&master)?;
+        return Key::new(
+            &decode_base64(&master.encrypt)?,
+            &decode_base64(&master.mac.k)?,
+            &decode_base64(&master.mac.r)?,
+        );
+    }
+    Err(ResticError::WrongPassword)
+}
+
+/// Decompress zstd data, which may hold several frames back to back.
+fn decompress(mut data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    while !data.is_empty() {
+        StreamingDecoder::new(&mut data)
+            .map_err(|error| ResticError::Zstd(error.to_string()))?
+            .read_to_end(&mut out)
+            .map_err(|error| ResticError::Zstd(error.to_string()))?;
+    }
+    Ok(out)
+}
+
+/// Decrypt a repository file. Version 2 repositories prefix compressed
+/// JSON with a `2` byte; plain JSON starts with `{` or `[`.
+fn decode_file(key: &Key, data: &[u8]) -> Result<Vec<u8>> {
+    let plaintext = key.decrypt(data)?;
+    match plaintext.first() {
+        Some(b'{' | b'[') => Ok(plaintext),
+        Some(2) => decompress(&plaintext[1..]),
+        _ => Err(ResticError::Invalid("unknown file encoding".into())),
+    }
+}
@@ -0,0 +1,352 @@
+//! Retrying transient backend failures.
+//!
+//! Every operator is wrapped in OpenDAL's [`RetryLayer`], so a dropped
+//! connection or a throttled request during a large transfer is retried
+//! with exponential backoff instead of failing the whole job. A storage
+//! tunes this through an optional `retry` object in its config:
+//!
+//! ```json
+//! "retry": {
+//!   "maxAttempts": 4,
+//!   "minDelayMs": 250,
+//!   "maxDelayMs": 10000,
+//!   "factor": 2,
+//!   "jitter": true,
+//!   "retryOn": ["temporary", "rate_limited"]
+//! }
+//! ```
+//!
+//! Every field is optional. `maxAttempts` counts the first try, so `1` (or
+//! `"retry": false`) disables retries. `retryOn` picks which failures are
+//! retried: `temporary` covers what the backend itself reports as
+//! transient (timeouts, connection resets, 5xx responses); the other
+//! values name error kinds that are retried regardless.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use opendal::layers::RetryLayer;
+use opendal::raw::{
+    oio, Access, Layer, LayeredAccess, OpCopy, OpCreateDir, OpDelete, OpList, OpPresign, OpRead,
+    OpRename, OpStat, OpWrite, RpCopy, RpCreateDir, RpDelete, RpList, RpPresign, RpRead, RpRename,
+    RpStat, RpWrite,
+};
+use opendal::{Buffer, ErrorKind, Metadata, Operator};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{err_with_details, McpErrorCode, McpResult};
+use crate::registry::StorageRecord;
+
+/// Failures a [`RetryPolicy`] can retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryOn {
+    /// Whatever the backend reports as transient.
+    Temporary,
+    RateLimited,
+    Unexpected,
+    /// For stores that are only eventually consistent after a write.
+    NotFound,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    pub max_attempts: usize,
+    pub min_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub factor: f32,
+    pub jitter: bool,
+    pub retry_on: Vec<RetryOn>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            min_delay_ms: 250,
+            max_delay_ms: 10_000,
+            factor: 2.0,
+            jitter: true,
+            retry_on: vec![RetryOn::Temporary, RetryOn::RateLimited],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The storage's `config.retry`, or the default policy without one.
+    pub fn for_storage(storage: &StorageRecord) -> McpResult<Self> {
+        let policy = match storage.config.get("retry") {
+            None | Some(Value::Null) | Some(Value::Bool(true)) => Self::default(),
+            Some(Value::Bool(false)) => Self {
+                max_attempts: 1,
+                ..Self::default()
+            },
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|e| invalid(storage, e.to_string()))?,
+        };
+        if policy.max_attempts == 0 {
+            return Err(invalid(storage, "maxAttempts must be at least 1".into()));
+        }
+        if policy.factor.is_nan() || policy.factor < 1.0 {
+            return Err(invalid(storage, "factor must be at least 1".into()));
+        }
+        if policy.min_delay_ms > policy.max_delay_ms {
+            return Err(invalid(
+                storage,
+                "minDelayMs must not exceed maxDelayMs".into(),
+            ));
+        }
+        Ok(policy)
+    }
+
+    fn retries(&self, error: &opendal::Error) -> bool {
+        (error.is_temporary() && self.retry_on.contains(&RetryOn::Temporary))
+            || match error.kind() {
+                ErrorKind::RateLimited => self.retry_on.contains(&RetryOn::RateLimited),
+                ErrorKind::Unexpected => self.retry_on.contains(&RetryOn::Unexpected),
+                ErrorKind::NotFound => self.retry_on.contains(&RetryOn::NotFound),
+                _ => false,
+            }
+    }
+
+    /// Wrap `op` so failures this policy covers are retried.
+    pub fn apply(&self, op: Operator) -> Operator {
+        if self.max_attempts <= 1 {
+            return op;
+        }
+        let mut retry = RetryLayer::new()
+            .with_max_times(self.max_attempts - 1)
+            .with_min_delay(Duration::from_millis(self.min_delay_ms))
+            .with_max_delay(Duration::from_millis(self.max_delay_ms))
+            .with_factor(self.factor);
+        if self.jitter {
+            retry = retry.with_jitter();
+        }
+        // The retry layer only looks at `is_temporary`, so mark errors
+        // before they reach it.
+        op.layer(ClassifyLayer(Arc::new(self.clone())))
+            .layer(
+                retry.with_notify(|error: &opendal::Error, delay: Duration| {
+                    tracing::warn!(%error, ?delay, "retrying storage operation");
+                }),
+            )
+    }
+}
+
+fn invalid(storage: &StorageRecord, reason: String) -> crate::McpError {
+    err_with_details(
+        McpErrorCode::ERR_INTERNAL,
+        format!("invalid retry config: {reason}"),
+        serde_json::json!({ "storage": storage.name }),
+    )
+}
+
+/// Marks errors the policy retries as temporary and all others as
+/// permanent.
+struct ClassifyLayer(Arc<RetryPolicy>);
+
+impl<A: Access> Layer<A> for ClassifyLayer {
+    type LayeredAccess = ClassifyAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        ClassifyAccessor {
+            inner,
+            policy: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClassifyAccessor<A> {
+    inner: A,
+    policy: Arc<RetryPolicy>,
+}
+
+#[derive(Debug)]
+struct Classified<T> {
+    inner: T,
+    policy: Arc<RetryPolicy>,
+}
+
+fn classify(policy: &RetryPolicy, error: opendal::Error) -> opendal::Error {
+    if policy.retries(&error) {
+        error.set_temporary()
+    } else if error.is_temporary() {
+        error.set_permanent()
+    } else {
+        error
+    }
+}
+
+impl<A: Access> ClassifyAccessor<A> {
+    fn wrap<T>(&self, inner: T) -> Classified<T> {
+        Classified {
+            inner,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<A: Access> LayeredAccess for ClassifyAccessor<A> {
+    type Inner = A;
+    type Reader = Classified<A::Reader>;
+    type Writer = Classified<A::Writer>;
+    type Lister = Classified<A::Lister>;
+    type Deleter = Classified<A::Deleter>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> opendal::Result<RpCreateDir> {
+        let policy = &self.policy;
+        self.inner
+            .create_dir(path, args)
+            .await
+            .map_err(|e| classify(policy, e))
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+        let policy = &self.policy;
+        let (rp, reader) = self
+            .inner
+            .read(path, args)
+            .await
+            .map_err(|e| classify(policy, e))?;
+        Ok((rp, self.wrap(reader)))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> opendal::Result<(RpWrite, Self::Writer)> {
+        let policy = &self.policy;
+        let (rp, writer) = self
+            .inner
+            .write(path, args)
+            .await
+            .map_err(|e| classify(policy, e))?;
+        Ok((rp, self.wrap(writer)))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> opendal::Result<RpCopy> {
+        let policy = &self.policy;
+        self.inner
+            .copy(from, to, args)
+            .await
+            .map_err(|e| classify(policy, e))
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> opendal::Result<RpRename> {
+        let policy = &self.policy;
+        self.inner
+            .rename(from, to, args)
+            .await
+            .map_err(|e| classify(policy, e))
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> opendal::Result<RpStat> {
+        let policy = &self.policy;
+        self.inner
+            .stat(path, args)
+            .await
+            .map_err(|e| classify(policy, e))
+    }
+
+    async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+        let policy = &self.policy;
+        let (rp, deleter) = self.inner.delete().await.map_err(|e| classify(policy, e))?;
+        Ok((rp, self.wrap(deleter)))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+        let policy = &self.policy;
+        let (rp, lister) = self
+            .inner
+            .list(path, args)
+            .await
+            .map_err(|e| classify(policy, e))?;
+        Ok((rp, self.wrap(lister)))
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> opendal::Result<RpPresign> {
+        let policy = &self.policy;
+        self.inner
+            .presign(path, args)
+            .await
+            .map_err(|e| classify(policy, e))
+    }
+}
+
+impl<R: oio::Read> oio::Read for Classified<R> {
+    async fn read(&mut self) -> opendal::Result<Buffer> {
+        let policy = &self.policy;
+        self.inner.read().await.map_err(|e| classify(policy, e))
+    }
+}
+
+impl<W: oio::Write> oio::Write for Classified<W> {
+    async fn write(&mut self, bs: Buffer) -> opendal::Result<()> {
+        let policy = &self.policy;
+        self.inner.write(bs).await.map_err(|e| classify(policy, e))
+    }
+
+    async fn close(&mut self) -> opendal::Result<Metadata> {
+        let policy = &self.policy;
+        self.inner.close().await.map_err(|e| classify(policy, e))
+    }
+
+    async fn abort(&mut self) -> opendal::Result<()> {
+        let policy = &self.policy;
+        self.inner.abort().await.map_err(|e| classify(policy, e))
+    }
+}
+
+impl<L: oio::List> oio::List for Classified<L> {
+    async fn next(&mut self) -> opendal::Result<Option<opendal::raw::oio::Entry>> {
+        let policy = &self.policy;
+        self.inner.next().await.map_err(|e| classify(policy, e))
+    }
+}
+
+impl<D: oio::Delete> oio::Delete for Classified<D> {
+    fn delete(&mut self, path: &str, args: OpDelete) -> opendal::Result<()> {
+        self.inner
+            .delete(path, args)
+            .map_err(|e| classify(&self.policy, e))
+    }
+
+    async fn flush(&mut self) -> opendal::Result<usize> {
+        let policy = &self.policy;
+        self.inner.flush().await.map_err(|e| classify(policy, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn retry_policy_reads_storage_config() {
+        let mut storage = StorageRecord::new("bucket".into(), "s3".into(), json!({}));
+        assert_eq!(
+            RetryPolicy::for_storage(&storage).unwrap(),
+            RetryPolicy::default()
+        );
+
+        storage.config = json!({ "retry": { "maxAttempts": 6, "retryOn": ["not_found"] } });
+        let policy = RetryPolicy::for_storage(&storage).unwrap();
+        assert_eq!(policy.max_attempts, 6);
+        assert_eq!(policy.min_delay_ms, 250);
+        let missing = opendal::Error::new(ErrorKind::NotFound, "not yet visible");
+        assert!(policy.retries(&missing));
+        let reset = opendal::Error::new(ErrorKind::Unexpected, "reset").set_temporary();
+        assert!(!policy.retries(&reset));
+
+        storage.config = json!({ "retry": false });
+        assert_eq!(RetryPolicy::for_storage(&storage).unwrap().max_attempts, 1);
+
+        storage.config = json!({ "retry": { "maxAttempts": 0 } });
+        assert!(RetryPolicy::for_storage(&storage).is_err());
+    }
+}
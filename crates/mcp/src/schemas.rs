@@ -206,7 +206,8 @@ pub fn schema_import_config() -> serde_json::Value {
       "properties": {
         "json": { "type": "string" },
         "mode": { "type": "string", "default": "merge" },
-        "on_conflict": { "type": "string", "default": "error" }
+        "on_conflict": { "type": "string", "default": "error" },
+        "dry_run": { "type": "boolean", "default": false }
       },
       "required": ["json"],
       "additionalProperties": false
@@ -0,0 +1,118 @@
+//! Storage credentials kept in the OS keyring (macOS Keychain, Windows
+//! Credential Manager, Secret Service on Linux).
+//!
+//! Secret config values (see [`is_secret_key`]) are moved into the keyring
+//! whenever the registry is saved, by the functions of
+//! [`infimount_core::secrets`] applied to the string values of
+//! `StorageRecord::config`, and `storages.json` only keeps a
+//! `keyring:<account>` reference. References are resolved back to the real
+//! value when an operator is built or secrets are explicitly exported.
+//!
+//! Values may also name environment variables as `${VAR}`; those stay in
+//! `storages.json` as written and are expanded by [`expand_env`] when an
+//! operator is built, so headless deployments need no stored secrets at all.
+
+use serde_json::{json, Value};
+
+pub use infimount_core::secrets::{delete, is_reference, is_secret_key, REFERENCE_PREFIX};
+
+use infimount_core::secrets::{self as core, SecretError};
+
+use crate::credentials;
+use crate::errors::{err_with_details, McpError, McpErrorCode, McpResult};
+use crate::registry::StorageRecord;
+
+fn keyring_error(e: keyring::Error) -> McpError {
+    err_with_details(
+        McpErrorCode::ERR_INTERNAL,
+        "OS keyring operation failed",
+        json!({ "keyring_error": e.to_string() }),
+    )
+}
+
+fn secret_error(storage: &StorageRecord, e: SecretError) -> McpError {
+    match e {
+        SecretError::Keyring(e) => keyring_error(e),
+        SecretError::Missing { key } => err_with_details(
+            McpErrorCode::ERR_INTERNAL,
+            format!(
+                "secret '{key}' of storage '{}' is missing from the OS keyring",
+                storage.name
+            ),
+            json!({ "storage_id": storage.id, "key": key }),
+        ),
+        SecretError::UnsetVariable { key, name } => err_with_details(
+            McpErrorCode::ERR_INTERNAL,
+            format!(
+                "environment variable '{name}' used by '{key}' of storage '{}' is not set",
+                storage.name
+            ),
+            json!({ "storage_id": storage.id, "key": key, "variable": name }),
+        ),
+    }
+}
+
+/// The string values of a storage config, for the core secrets functions.
+fn string_values(config: &mut Value) -> impl Iterator<Item = (&String, &mut String)> {
+    config
+        .as_object_mut()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| match value {
+            Value::String(value) => Some((key, value)),
+            _ => None,
+        })
+}
+
+/// Write `secret` to the keyring under `account`.
+pub fn store(account: &str, secret: &str) -> McpResult<()> {
+    core::store(account, secret).map_err(keyring_error)
+}
+
+/// Move plaintext secret values of `storage.config` into the keyring and
+/// replace them with references. Values that already are references are
+/// left alone.
+pub fn externalize(storage: &mut StorageRecord) -> McpResult<()> {
+    core::externalize_values(&storage.id, string_values(&mut storage.config))
+        .map_err(|e| secret_error(storage, e))
+}
+
+/// Copy of `storage` with every keyring reference replaced by its secret.
+pub fn resolve(storage: &StorageRecord) -> McpResult<StorageRecord> {
+    let mut resolved = storage.clone();
+    core::resolve_values(string_values(&mut resolved.config))
+        .map_err(|e| secret_error(storage, e))?;
+    Ok(resolved)
+}
+
+/// Copy of `storage` with `${VAR}` placeholders in its config values
+/// replaced from the process environment. Fails if a variable is unset.
+pub fn expand_env(storage: &StorageRecord) -> McpResult<StorageRecord> {
+    let mut expanded = storage.clone();
+    core::expand_env_values(string_values(&mut expanded.config))
+        .map_err(|e| secret_error(storage, e))?;
+    Ok(expanded)
+}
+
+fn references(storage: &StorageRecord) -> impl Iterator<Item = &str> {
+    core::references(
+        storage
+            .config
+            .as_object()
+            .into_iter()
+            .flat_map(|config| config.values())
+            .filter_map(Value::as_str),
+    )
+}
+
+/// Delete keyring entries referenced by `previous` but no longer by
+/// `current` (removed storages, replaced or cleared secrets). Entries of
+/// shared credential sets are left to [`crate::credentials`].
+pub fn forget_unreferenced(previous: &[StorageRecord], current: &[StorageRecord]) {
+    let kept: Vec<&str> = current.iter().flat_map(references).collect();
+    for account in previous.iter().flat_map(references) {
+        if !kept.contains(&account) && !credentials::is_shared_account(account) {
+            delete(account);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::errors::{err, err_with_details, map_io_error, McpErrorCode, McpResult};
-use crate::registry::default_config_dir;
 use crate::server::default_enabled_tool_names;
 use fs2::FileExt;
+use infimount_core::config::config_dir;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fs::{self, OpenOptions};
@@ -161,7 +161,7 @@ impl McpSettingsStore {
 }
 
 pub fn default_settings_path() -> PathBuf {
-    default_config_dir().join("mcp_settings.json")
+    config_dir().join("mcp_settings.json")
 }
 
 fn ensure_parent(path: &Path) -> McpResult<()> {
@@ -7,7 +7,20 @@ use crate::registry::{mask_storage_record, validate_storage_name, StorageRecord}
 pub(super) fn ensure_backend_supported(backend: &str) -> McpResult<()> {
     if matches!(
         backend,
-        "local" | "fs" | "s3" | "webdav" | "azure_blob" | "azblob" | "gcs"
+        "local"
+            | "fs"
+            | "s3"
+            | "webdav"
+            | "azure_blob"
+            | "azblob"
+            | "gcs"
+            | "ftp"
+            | "gdrive"
+            | "b2"
+            | "hdfs"
+            | "ipfs"
+            | "ipmfs"
+            | "http"
     ) {
         return Ok(());
     }
@@ -75,6 +88,8 @@ impl ImportedStorage {
             read_only: self.read_only,
             created_at: self.created_at.unwrap_or_else(|| now.clone()),
             updated_at: self.updated_at.unwrap_or(now),
+            ephemeral: false,
+            capabilities: None,
         })
     }
 }
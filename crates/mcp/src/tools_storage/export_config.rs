@@ -1,9 +1,14 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
 use crate::errors::{err_with_details, McpErrorCode, McpResult};
 use crate::registry::{mask_storage_record, StorageRecord};
+use crate::secrets;
 use crate::tools_fs::FsToolsContext;
 
+/// Format version of exported profiles.
+const PROFILE_VERSION: u64 = 1;
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ExportConfigInput {
@@ -23,11 +28,20 @@ pub async fn export_config(
     let storages = ctx.registry.load_all()?;
     let exportable: Vec<StorageRecord> = if input.include_secrets {
         storages
+            .iter()
+            .map(secrets::resolve)
+            .collect::<McpResult<_>>()?
     } else {
         storages.iter().map(mask_storage_record).collect()
     };
 
-    let json = serde_json::to_string_pretty(&exportable).map_err(|e| {
+    // A portable profile: `import_config` accepts it on any machine.
+    let profile = serde_json::json!({
+        "version": PROFILE_VERSION,
+        "exported_at": Utc::now().to_rfc3339(),
+        "storages": exportable,
+    });
+    let json = serde_json::to_string_pretty(&profile).map_err(|e| {
         err_with_details(
             McpErrorCode::ERR_INTERNAL,
             "failed to serialize storage registry",
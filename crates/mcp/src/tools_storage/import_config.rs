@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::errors::{err, err_with_details, McpErrorCode, McpResult};
-use crate::registry::{ensure_unique_name, StorageRecord};
+use crate::registry::{ensure_unique_name, StorageRecord, MASK};
+use crate::secrets::is_secret_key;
 use crate::tools_fs::FsToolsContext;
 
 use super::common::{masked, next_renamed_name, ImportedStorage};
@@ -24,12 +25,30 @@ pub struct ImportConfigInput {
     pub mode: String,
     #[serde(default = "default_on_conflict")]
     pub on_conflict: String,
+    /// Report what the import would do without changing the registry.
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ImportConfigOutput {
     pub imported: usize,
     pub storages: Vec<StorageRecord>,
+    pub dry_run: bool,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+/// An imported storage that clashes with one already in the registry (or
+/// earlier in the same profile), and how it is resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportConflict {
+    pub name: String,
+    /// `"name"` or `"id"`.
+    pub kind: String,
+    /// `"error"`, `"overwrite"`, `"rename"` or `"new_id"`.
+    pub resolution: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_to: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -130,57 +149,111 @@ pub async fn import_config(
         .collect::<McpResult<Vec<_>>>()?;
     let imported_count = imported.len();
 
-    let current = if input.mode == "replace" {
-        Vec::new()
-    } else {
-        ctx.registry.load_all()?
-    };
+    if input.dry_run {
+        let mut storages = if input.mode == "replace" {
+            Vec::new()
+        } else {
+            ctx.registry.load_all()?
+        };
+        let conflicts = merge_imported(&mut storages, imported, &input.on_conflict, true)?;
+        return Ok(ImportConfigOutput {
+            imported: imported_count,
+            storages: storages.iter().map(masked).collect(),
+            dry_run: true,
+            conflicts,
+        });
+    }
 
-    let merged = ctx.registry.with_locked_mutation(|storages| {
+    let (merged, conflicts) = ctx.registry.with_locked_mutation(|storages| {
         if input.mode == "replace" {
             storages.clear();
-        } else {
-            *storages = current.clone();
         }
-
-        for mut incoming in imported.clone() {
-            if let Some(idx) = storages
-                .iter()
-                .position(|storage| storage.name == incoming.name)
-            {
-                match input.on_conflict.as_str() {
-                    "error" => {
-                        return Err(err_with_details(
-                            McpErrorCode::ERR_STORAGE_NAME_CONFLICT,
-                            format!("Storage name '{}' already exists", incoming.name),
-                            serde_json::json!({ "name": incoming.name }),
-                        ));
-                    }
-                    "overwrite" => {
-                        let existing = &storages[idx];
-                        incoming.id = existing.id.clone();
-                        incoming.created_at = existing.created_at.clone();
-                        incoming.updated_at = Utc::now().to_rfc3339();
-                        storages[idx] = incoming;
-                    }
-                    "rename" => {
-                        incoming.name = next_renamed_name(storages, &incoming.name);
-                        ensure_unique_name(storages, &incoming.name, None)?;
-                        storages.push(incoming);
-                    }
-                    _ => unreachable!(),
-                }
-            } else {
-                ensure_unique_name(storages, &incoming.name, None)?;
-                storages.push(incoming);
-            }
-        }
-
-        Ok(storages.clone())
+        let conflicts = merge_imported(storages, imported.clone(), &input.on_conflict, false)?;
+        Ok((storages.clone(), conflicts))
     })?;
 
     Ok(ImportConfigOutput {
         imported: imported_count,
         storages: merged.iter().map(masked).collect(),
+        dry_run: false,
+        conflicts,
     })
 }
+
+/// Merge `imported` into `storages`, resolving name clashes per
+/// `on_conflict` and giving storages whose ID is already taken a fresh one.
+/// With `dry_run`, clashes under the `"error"` policy are reported instead of
+/// failing.
+fn merge_imported(
+    storages: &mut Vec<StorageRecord>,
+    imported: Vec<StorageRecord>,
+    on_conflict: &str,
+    dry_run: bool,
+) -> McpResult<Vec<ImportConflict>> {
+    let mut conflicts = Vec::new();
+    for mut incoming in imported {
+        drop_masked_secrets(&mut incoming);
+
+        if let Some(idx) = storages
+            .iter()
+            .position(|storage| storage.name == incoming.name)
+        {
+            let mut conflict = ImportConflict {
+                name: incoming.name.clone(),
+                kind: "name".to_string(),
+                resolution: on_conflict.to_string(),
+                renamed_to: None,
+            };
+            match on_conflict {
+                "error" if dry_run => {
+                    conflicts.push(conflict);
+                    continue;
+                }
+                "error" => {
+                    return Err(err_with_details(
+                        McpErrorCode::ERR_STORAGE_NAME_CONFLICT,
+                        format!("Storage name '{}' already exists", incoming.name),
+                        serde_json::json!({ "name": incoming.name }),
+                    ));
+                }
+                "overwrite" => {
+                    let existing = &storages[idx];
+                    incoming.id = existing.id.clone();
+                    incoming.created_at = existing.created_at.clone();
+                    incoming.updated_at = Utc::now().to_rfc3339();
+                    storages[idx] = incoming;
+                    conflicts.push(conflict);
+                    continue;
+                }
+                "rename" => {
+                    incoming.name = next_renamed_name(storages, &incoming.name);
+                    conflict.renamed_to = Some(incoming.name.clone());
+                    conflicts.push(conflict);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        // Profiles carry IDs from another machine; never let one collide.
+        if storages.iter().any(|storage| storage.id == incoming.id) {
+            conflicts.push(ImportConflict {
+                name: incoming.name.clone(),
+                kind: "id".to_string(),
+                resolution: "new_id".to_string(),
+                renamed_to: None,
+            });
+            incoming.id = uuid::Uuid::new_v4().to_string();
+        }
+        ensure_unique_name(storages, &incoming.name, None)?;
+        storages.push(incoming);
+    }
+    Ok(conflicts)
+}
+
+/// Remove the `********` placeholders of a profile exported without
+/// secrets, so they are not saved as the secret values themselves.
+fn drop_masked_secrets(storage: &mut StorageRecord) {
+    if let Value::Object(config) = &mut storage.config {
+        config.retain(|key, value| !(is_secret_key(key) && value.as_str() == Some(MASK)));
+    }
+}
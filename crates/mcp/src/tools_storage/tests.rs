@@ -4,6 +4,7 @@ use crate::tools_fs::FsToolsContext;
 use tempfile::TempDir;
 
 fn registry_in(dir: &TempDir) -> crate::registry::StorageRegistry {
+    infimount_core::secrets::memory_keyring::install();
     crate::registry::StorageRegistry::new(Some(dir.path().join("storages.json")))
 }
 
@@ -154,6 +155,7 @@ async fn import_config_rename_conflict_appends_suffix() {
             .to_string(),
             mode: "merge".to_string(),
             on_conflict: "rename".to_string(),
+            dry_run: false,
         },
     )
     .await
@@ -168,6 +170,63 @@ async fn import_config_rename_conflict_appends_suffix() {
     assert!(names.contains(&"Local (2)".to_string()));
 }
 
+#[tokio::test]
+async fn import_config_dry_run_reports_conflicts_without_saving() {
+    let dir = TempDir::new().unwrap();
+    let registry = registry_in(&dir);
+    let existing = crate::registry::StorageRecord::new(
+        "Local".to_string(),
+        "local".to_string(),
+        serde_json::json!({"root": "/tmp/one"}),
+    );
+    let existing_id = existing.id.clone();
+    registry.save_all_atomic(&[existing]).unwrap();
+    let ctx = FsToolsContext {
+        registry: registry.clone(),
+        sessions: sessions_in(),
+        allow_insecure: true,
+        auth_token: None,
+    };
+
+    let profile = serde_json::json!({
+        "version": 1,
+        "storages": [
+            { "name": "Local", "backend": "local", "config": { "root": "/tmp/two" } },
+            {
+                "id": existing_id,
+                "name": "Bucket",
+                "backend": "s3",
+                "config": { "bucketName": "b", "secretAccessKey": "********" }
+            }
+        ]
+    })
+    .to_string();
+    let input = |dry_run| ImportConfigInput {
+        json: profile.clone(),
+        mode: "merge".to_string(),
+        on_conflict: "error".to_string(),
+        dry_run,
+    };
+
+    let out = import_config(&ctx, input(true)).await.unwrap();
+    let kinds = out
+        .conflicts
+        .iter()
+        .map(|conflict| (conflict.name.as_str(), conflict.kind.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [("Local", "name"), ("Bucket", "id")]);
+    assert_eq!(registry.load_all().unwrap().len(), 1);
+    assert!(import_config(&ctx, input(false)).await.is_err());
+
+    let mut input = input(false);
+    input.on_conflict = "rename".to_string();
+    import_config(&ctx, input).await.unwrap();
+    let saved = registry.load_all().unwrap();
+    let bucket = saved.iter().find(|s| s.name == "Bucket").unwrap();
+    assert_ne!(bucket.id, existing_id);
+    assert!(bucket.config.get("secretAccessKey").is_none());
+}
+
 #[tokio::test]
 async fn validate_storage_local_root_succeeds() {
     let dir = TempDir::new().unwrap();
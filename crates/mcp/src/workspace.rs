@@ -0,0 +1,270 @@
+//! Whole-workspace export and import, for moving to a new machine.
+//!
+//! A workspace file holds the storages (secrets masked), the credential sets
+//! they use (names and keys only) and the MCP settings (without the auth
+//! token). Front ends can carry their own settings in
+//! [`Workspace::preferences`]. Secrets never leave the keyring: after an
+//! import, each restored credential set and each storage with masked
+//! secrets needs its values entered again.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::credentials::{self, CredentialSet, CredentialStore};
+use crate::errors::{err, err_with_details, McpErrorCode, McpResult};
+use crate::registry::{mask_storage_record, StorageRecord};
+use crate::settings::{McpSettings, McpSettingsStore};
+use crate::tools_fs::FsToolsContext;
+use crate::tools_storage::{import_config, ImportConfigInput, ImportConfigOutput};
+
+/// Format version of workspace files.
+const WORKSPACE_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub version: u64,
+    pub exported_at: String,
+    pub storages: Vec<StorageRecord>,
+    #[serde(default)]
+    pub credential_sets: Vec<CredentialSet>,
+    #[serde(default)]
+    pub mcp_settings: Option<McpSettings>,
+    /// Settings of the app that wrote the file, opaque to this crate.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub preferences: Value,
+}
+
+impl Workspace {
+    pub fn to_json(&self) -> McpResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            err_with_details(
+                McpErrorCode::ERR_INTERNAL,
+                "failed to serialize workspace",
+                json!({ "serde_error": e.to_string() }),
+            )
+        })
+    }
+
+    pub fn from_json(data: &str) -> McpResult<Self> {
+        let workspace: Self = serde_json::from_str(data).map_err(|e| {
+            err_with_details(
+                McpErrorCode::ERR_INTERNAL,
+                "failed to parse workspace file",
+                json!({ "serde_error": e.to_string() }),
+            )
+        })?;
+        if workspace.version > WORKSPACE_VERSION {
+            return Err(err(
+                McpErrorCode::ERR_INTERNAL,
+                format!(
+                    "workspace file version {} is newer than this app supports",
+                    workspace.version
+                ),
+            ));
+        }
+        Ok(workspace)
+    }
+}
+
+/// Collect the workspace of this machine, without secrets.
+pub fn export(
+    registry: &crate::registry::StorageRegistry,
+    credential_store: &CredentialStore,
+    settings_store: &McpSettingsStore,
+) -> McpResult<Workspace> {
+    let credential_sets = credential_store.list()?;
+    let storages = registry
+        .load_all()?
+        .iter()
+        .map(|storage| {
+            let mut masked = mask_storage_record(storage);
+            // References to a credential set are not secrets; keep the link.
+            if let Some(set) = credentials::linked_set_id(storage)
+                .and_then(|id| credential_sets.iter().find(|set| set.id == id))
+            {
+                credentials::link(&mut masked, set);
+            }
+            masked
+        })
+        .collect();
+    let mut mcp_settings = settings_store.load()?;
+    mcp_settings.auth_token = None;
+
+    Ok(Workspace {
+        version: WORKSPACE_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        storages,
+        credential_sets,
+        mcp_settings: Some(mcp_settings),
+        preferences: Value::Null,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceImportInput {
+    pub workspace: Workspace,
+    /// `"merge"` adds to this machine's storages and keeps its settings;
+    /// `"replace"` replaces the storages and takes the settings from the
+    /// file.
+    pub mode: String,
+    pub on_conflict: String,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceImportOutput {
+    pub storages: ImportConfigOutput,
+    /// Credential sets added from the file; their values must be entered
+    /// again.
+    pub restored_credential_sets: Vec<CredentialSet>,
+    /// Whether the MCP settings (and the file's preferences) were taken over.
+    pub settings_applied: bool,
+}
+
+pub async fn import(
+    ctx: &FsToolsContext,
+    credential_store: &CredentialStore,
+    settings_store: &McpSettingsStore,
+    input: WorkspaceImportInput,
+) -> McpResult<WorkspaceImportOutput> {
+    let workspace = input.workspace;
+    let storages_json = serde_json::to_string(&workspace.storages).map_err(|e| {
+        err_with_details(
+            McpErrorCode::ERR_INTERNAL,
+            "failed to serialize workspace storages",
+            json!({ "serde_error": e.to_string() }),
+        )
+    })?;
+    let storages = import_config(
+        ctx,
+        ImportConfigInput {
+            json: storages_json,
+            mode: input.mode.clone(),
+            on_conflict: input.on_conflict,
+            dry_run: input.dry_run,
+        },
+    )
+    .await?;
+
+    let restored_credential_sets = if input.dry_run {
+        let existing = credential_store.list()?;
+        workspace
+            .credential_sets
+            .into_iter()
+            .filter(|set| existing.iter().all(|other| other.id != set.id))
+            .collect()
+    } else {
+        credential_store.restore(&workspace.credential_sets)?
+    };
+
+    let settings_applied = input.mode == "replace" && workspace.mcp_settings.is_some();
+    if settings_applied && !input.dry_run {
+        if let Some(mut settings) = workspace.mcp_settings {
+            settings.auth_token = settings_store.load()?.auth_token;
+            settings_store.save_atomic(&settings)?;
+        }
+    }
+
+    Ok(WorkspaceImportOutput {
+        storages,
+        restored_credential_sets,
+        settings_applied,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::StorageRegistry;
+    use crate::secrets;
+    use crate::session::SessionManager;
+    use infimount_core::secrets::memory_keyring;
+    use serde_json::Map;
+
+    #[tokio::test]
+    async fn workspace_moves_to_another_machine_without_secrets() {
+        memory_keyring::install();
+        let old = tempfile::tempdir().unwrap();
+        let registry = StorageRegistry::new(Some(old.path().join("storages.json")));
+        let credential_store = CredentialStore::beside(registry.path());
+        let settings_store = McpSettingsStore::new(Some(old.path().join("mcp-settings.json")));
+
+        let set = credential_store
+            .save(
+                None,
+                "MinIO",
+                &Map::from_iter([("secretAccessKey".to_string(), json!("hunter2"))]),
+            )
+            .unwrap();
+        let mut linked = StorageRecord::new(
+            "Media".to_string(),
+            "s3".to_string(),
+            json!({ "bucketName": "media" }),
+        );
+        credentials::link(&mut linked, &set);
+        let own_secret = StorageRecord::new(
+            "Docs".to_string(),
+            "webdav".to_string(),
+            json!({ "serverUrl": "https://nas.local", "password": "swordfish" }),
+        );
+        registry.save_all_atomic(&[linked, own_secret]).unwrap();
+        settings_store
+            .save_atomic(&McpSettings {
+                enabled: true,
+                auth_token: Some("local-token".to_string()),
+                ..McpSettings::default()
+            })
+            .unwrap();
+
+        let json = export(&registry, &credential_store, &settings_store)
+            .unwrap()
+            .to_json()
+            .unwrap();
+        assert!(!json.contains("hunter2"));
+        assert!(!json.contains("swordfish"));
+        assert!(!json.contains("local-token"));
+
+        let new = tempfile::tempdir().unwrap();
+        let ctx = FsToolsContext {
+            registry: StorageRegistry::new(Some(new.path().join("storages.json"))),
+            sessions: SessionManager::new(),
+            allow_insecure: true,
+            auth_token: None,
+        };
+        let new_credentials = CredentialStore::beside(ctx.registry.path());
+        let new_settings = McpSettingsStore::new(Some(new.path().join("mcp-settings.json")));
+        let input = |dry_run| WorkspaceImportInput {
+            workspace: Workspace::from_json(&json).unwrap(),
+            mode: "replace".to_string(),
+            on_conflict: "error".to_string(),
+            dry_run,
+        };
+
+        let preview = import(&ctx, &new_credentials, &new_settings, input(true))
+            .await
+            .unwrap();
+        assert_eq!(preview.restored_credential_sets.len(), 1);
+        assert!(ctx.registry.load_all().unwrap().is_empty());
+        assert!(new_credentials.list().unwrap().is_empty());
+
+        let out = import(&ctx, &new_credentials, &new_settings, input(false))
+            .await
+            .unwrap();
+        assert_eq!(out.storages.imported, 2);
+        assert!(out.settings_applied);
+        assert!(new_settings.load().unwrap().enabled);
+        assert_eq!(new_settings.load().unwrap().auth_token, None);
+
+        // The linked storage still resolves through the (same) keyring.
+        let storages = ctx.registry.load_all().unwrap();
+        let media = storages.iter().find(|s| s.name == "Media").unwrap();
+        assert_eq!(credentials::linked_set_id(media), Some(set.id.as_str()));
+        assert_eq!(
+            secrets::resolve(media).unwrap().config["secretAccessKey"],
+            "hunter2"
+        );
+        let docs = storages.iter().find(|s| s.name == "Docs").unwrap();
+        assert!(docs.config.get("password").is_none());
+    }
+}